@@ -1,3 +1,6 @@
+mod report;
+mod usage;
+
 use anyhow::Result;
 use automodel::*;
 use clap::{Arg, ArgMatches, Command};
@@ -10,6 +13,30 @@ async fn main() -> Result<()> {
         Some(("generate", sub_matches)) => {
             generate_command(sub_matches).await?;
         }
+        Some(("vendor-runtime", sub_matches)) => {
+            generate_command(sub_matches).await?;
+        }
+        Some(("config", sub_matches)) => {
+            config_command(sub_matches).await?;
+        }
+        Some(("fmt", sub_matches)) => {
+            fmt_command(sub_matches)?;
+        }
+        Some(("baseline", sub_matches)) => {
+            baseline_command(sub_matches).await?;
+        }
+        Some(("usage", sub_matches)) => {
+            usage_command(sub_matches).await?;
+        }
+        Some(("report", sub_matches)) => {
+            report_command(sub_matches).await?;
+        }
+        Some(("audit", sub_matches)) => {
+            audit_command(sub_matches)?;
+        }
+        Some(("clean", sub_matches)) => {
+            clean_command(sub_matches).await?;
+        }
         _ => {
             build_cli().print_help()?;
             std::process::exit(1);
@@ -29,6 +56,73 @@ fn build_cli() -> Command {
         .subcommand(
             Command::new("generate")
                 .about("Generate Rust code from SQL query files")
+                .args(generate_args())
+                .arg(
+                    Arg::new("vendored-runtime")
+                        .long("vendored-runtime")
+                        .value_name("FILE")
+                        .help("Keep the helpers shared by all modules in this file of the crate, as written by vendor-runtime"),
+                )
+                .arg(
+                    Arg::new("vendored-runtime-module")
+                        .long("vendored-runtime-module")
+                        .value_name("PATH")
+                        .help("Module path of the --vendored-runtime file, derived from the file under src/ by default")
+                        .requires("vendored-runtime"),
+                ),
+        )
+        .subcommand(
+            Command::new("vendor-runtime")
+                .about("Generate Rust code with the helpers shared by all modules written into a file of the crate")
+                .args(generate_args())
+                .arg(
+                    Arg::new("out")
+                        .long("out")
+                        .value_name("FILE")
+                        .help("File of the crate the shared helpers are written to (e.g., 'src/automodel_rt.rs')")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("module-path")
+                        .long("module-path")
+                        .value_name("PATH")
+                        .help("Module path of the --out file, derived from the file under src/ by default (e.g., 'crate::automodel_rt')"),
+                ),
+        )
+        .subcommand(
+            Command::new("config")
+                .about("Print the effective configuration of every query in YAML")
+                .arg(
+                    Arg::new("queries-dir")
+                        .short('q')
+                        .long("queries-dir")
+                        .value_name("DIR")
+                        .help("Directory containing SQL query files (e.g., 'queries')")
+                        .default_value("queries"),
+                )
+                .args(defaults_args()),
+        )
+        .subcommand(
+            Command::new("fmt")
+                .about("Format the SQL of the query files, keeping their metadata blocks")
+                .arg(
+                    Arg::new("queries-dir")
+                        .short('q')
+                        .long("queries-dir")
+                        .value_name("DIR")
+                        .help("Directory containing SQL query files (e.g., 'queries')")
+                        .default_value("queries"),
+                )
+                .arg(
+                    Arg::new("check")
+                        .long("check")
+                        .help("List the files which are not formatted and fail instead of rewriting them")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("baseline")
+                .about("Record the current plan estimates as the baselines of the queries declaring one")
                 .arg(
                     Arg::new("database-url")
                         .short('d')
@@ -45,49 +139,411 @@ fn build_cli() -> Command {
                         .help("Directory containing SQL query files (e.g., 'queries')")
                         .default_value("queries"),
                 )
+                .arg(
+                    Arg::new("all")
+                        .long("all")
+                        .help("Add a baseline to every read-only query, not only those declaring one")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .args(defaults_args()),
+        )
+        .subcommand(
+            Command::new("usage")
+                .about("Report where the generated functions of every query are used in the workspace")
+                .arg(
+                    Arg::new("queries-dir")
+                        .short('q')
+                        .long("queries-dir")
+                        .value_name("DIR")
+                        .help("Directory containing SQL query files (e.g., 'queries')")
+                        .default_value("queries"),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("DIR")
+                        .help("Directory of the generated Rust code")
+                        .default_value("generated"),
+                )
+                .arg(
+                    Arg::new("manifest-path")
+                        .long("manifest-path")
+                        .value_name("PATH")
+                        .help("Cargo.toml of the workspace to scan, as for cargo metadata"),
+                )
+                .arg(
+                    Arg::new("save-report")
+                        .long("save-report")
+                        .value_name("FILE")
+                        .help("Also write the number of call sites of every query to FILE as JSON, for the report command"),
+                )
+                .args(defaults_args()),
+        )
+        .subcommand(
+            Command::new("audit")
+                .about("Check that the generated code only builds SQL from literals and validated values")
                 .arg(
                     Arg::new("output")
                         .short('o')
                         .long("output")
                         .value_name("DIR")
-                        .help("Output directory for generated Rust code")
+                        .help("Directory of the generated Rust code")
                         .default_value("generated"),
+                ),
+        )
+        .subcommand(
+            Command::new("clean")
+                .about("Remove generated artifacts which the current queries and options no longer produce")
+                .arg(
+                    Arg::new("queries-dir")
+                        .short('q')
+                        .long("queries-dir")
+                        .value_name("DIR")
+                        .help("Directory containing SQL query files (e.g., 'queries')")
+                        .default_value("queries"),
                 )
                 .arg(
-                    Arg::new("telemetry-level")
-                        .long("telemetry-level")
-                        .value_name("LEVEL")
-                        .help("Global telemetry level: none, info, debug, trace")
-                        .value_parser(["none", "info", "debug", "trace"])
-                        .default_value("none"),
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("DIR")
+                        .help("Directory of the generated Rust code")
+                        .default_value("generated"),
                 )
                 .arg(
-                    Arg::new("telemetry-include-sql")
-                        .long("telemetry-include-sql")
-                        .help("Include SQL queries in telemetry spans")
+                    Arg::new("all")
+                        .long("all")
+                        .help("Remove the whole output directory")
                         .action(clap::ArgAction::SetTrue),
                 )
                 .arg(
-                    Arg::new("ensure-indexes")
-                        .long("ensure-indexes")
-                        .help("Enable query performance analysis and sequential scan detection")
+                    Arg::new("dry-run")
+                        .long("dry-run")
+                        .help("List what would be removed without removing it")
                         .action(clap::ArgAction::SetTrue),
                 )
+                .args(defaults_args()),
+        )
+        .subcommand(
+            Command::new("report")
+                .about("Warn about queries unused in the usage reports of the latest releases")
                 .arg(
-                    Arg::new("default-module")
-                        .long("default-module")
-                        .value_name("MODULE")
-                        .help("Default module name for queries without explicit module"),
-                ),
+                    Arg::new("queries-dir")
+                        .short('q')
+                        .long("queries-dir")
+                        .value_name("DIR")
+                        .help("Directory containing SQL query files (e.g., 'queries')")
+                        .default_value("queries"),
+                )
+                .arg(
+                    Arg::new("usage-report")
+                        .long("usage-report")
+                        .value_name("FILE")
+                        .help("Usage report of a release written with usage --save-report, oldest first (repeatable)")
+                        .required(true)
+                        .action(clap::ArgAction::Append),
+                )
+                .arg(
+                    Arg::new("unused-for")
+                        .long("unused-for")
+                        .value_name("RELEASES")
+                        .help("Number of latest releases a query must be unused in to be reported")
+                        .default_value("3")
+                        .value_parser(clap::value_parser!(usize)),
+                )
+                .arg(
+                    Arg::new("deny")
+                        .long("deny")
+                        .help("Fail when a query is reported")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .args(defaults_args()),
         )
 }
 
-async fn generate_command(matches: &ArgMatches) -> Result<()> {
-    let database_url = matches.get_one::<String>("database-url").unwrap();
-    let queries_dir = matches.get_one::<String>("queries-dir").unwrap();
-    let output_dir = matches.get_one::<String>("output").unwrap();
+/// Arguments of the commands generating code
+fn generate_args() -> Vec<Arg> {
+    let mut args = vec![
+        Arg::new("database-url")
+            .short('d')
+            .long("database-url")
+            .value_name("URL")
+            .help("PostgreSQL database connection URL")
+            .required(true),
+        Arg::new("queries-dir")
+            .short('q')
+            .long("queries-dir")
+            .value_name("DIR")
+            .help("Directory containing SQL query files (e.g., 'queries')")
+            .default_value("queries"),
+        Arg::new("output")
+            .short('o')
+            .long("output")
+            .value_name("DIR")
+            .help("Output directory for generated Rust code")
+            .default_value("generated"),
+        Arg::new("default-module")
+            .long("default-module")
+            .value_name("MODULE")
+            .help("Default module name for queries without explicit module"),
+        Arg::new("quiet")
+            .long("quiet")
+            .help("Print nothing but errors, warnings are still written to automodel.warn")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("profile-build")
+            .long("profile-build")
+            .help("Print the time spent in each phase and the slowest queries to analyze")
+            .action(clap::ArgAction::SetTrue),
+    ];
+    args.extend(defaults_args());
+    args
+}
 
-    // Build defaults configuration from command-line arguments
+/// Arguments overriding the defaults applied to every query
+fn defaults_args() -> Vec<Arg> {
+    vec![
+        Arg::new("telemetry-level")
+            .long("telemetry-level")
+            .value_name("LEVEL")
+            .help("Global telemetry level: none, info, debug, trace")
+            .value_parser(["none", "info", "debug", "trace"])
+            .default_value("none"),
+        Arg::new("telemetry-include-sql")
+            .long("telemetry-include-sql")
+            .help("Include SQL queries in telemetry spans")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("telemetry-max-param-length")
+            .long("telemetry-max-param-length")
+            .value_name("CHARS")
+            .help("Characters of a parameter kept when it is recorded in spans and slow query events")
+            .value_parser(clap::value_parser!(usize)),
+        Arg::new("telemetry-max-param-elements")
+            .long("telemetry-max-param-elements")
+            .value_name("COUNT")
+            .help("Elements of a list parameter kept when it is recorded in spans and slow query events")
+            .value_parser(clap::value_parser!(usize)),
+        Arg::new("ensure-indexes")
+            .long("ensure-indexes")
+            .help("Enable query performance analysis and sequential scan detection")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("query-timeout")
+            .long("query-timeout")
+            .value_name("SECONDS")
+            .help("Maximum time to analyze a single query before falling back to cached analysis")
+            .value_parser(clap::value_parser!(u64)),
+        Arg::new("analysis-budget")
+            .long("analysis-budget")
+            .value_name("SECONDS")
+            .help("Maximum total analysis time before remaining queries fall back to cached analysis")
+            .value_parser(clap::value_parser!(u64)),
+        Arg::new("analysis-concurrency")
+            .long("analysis-concurrency")
+            .value_name("COUNT")
+            .help("Maximum number of queries analyzed at the same time (default: 40)")
+            .value_parser(clap::value_parser!(usize)),
+        Arg::new("analysis-connections")
+            .long("analysis-connections")
+            .value_name("COUNT")
+            .help("Number of database connections used for analysis (default: 1)")
+            .value_parser(clap::value_parser!(usize)),
+        Arg::new("transaction-pooling")
+            .long("transaction-pooling")
+            .help("Analyze every query in its own transaction, for databases behind PgBouncer in transaction pooling mode")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("min-pg-version")
+            .long("min-pg-version")
+            .value_name("VERSION")
+            .help("Minimum PostgreSQL major version required by all queries")
+            .value_parser(clap::value_parser!(u32)),
+        Arg::new("production-pg-version")
+            .long("production-pg-version")
+            .value_name("VERSION")
+            .help("PostgreSQL major version of the production server")
+            .value_parser(clap::value_parser!(u32)),
+        Arg::new("schema-version-query")
+            .long("schema-version-query")
+            .value_name("SQL")
+            .help("Query reading the schema version recorded as SCHEMA_VERSION in the generated code"),
+        Arg::new("expect-schema-version")
+            .long("expect-schema-version")
+            .value_name("VERSION")
+            .help("Fail unless the analysis database has this schema version (requires --schema-version-query)"),
+        Arg::new("dialect")
+            .long("dialect")
+            .value_name("DIALECT")
+            .help("Database the queries are analyzed against and generated for: postgres or cockroach")
+            .value_parser(clap::value_parser!(Dialect)),
+        Arg::new("sqlx-version")
+            .long("sqlx-version")
+            .value_name("VERSION")
+            .help("Major version of sqlx the generated code compiles against: 0.7, 0.8 or feature (0.7 with the sqlx-0-7 feature)")
+            .value_parser(clap::value_parser!(SqlxVersion)),
+        Arg::new("fail-on")
+            .long("fail-on")
+            .value_name("RULE")
+            .help("Fail generation on performance analysis results: seq_scan, missing_index, subquery, baseline, cost>N (repeatable or comma-separated)")
+            .value_parser(clap::value_parser!(FailOn))
+            .value_delimiter(',')
+            .action(clap::ArgAction::Append),
+        Arg::new("baseline-tolerance")
+            .long("baseline-tolerance")
+            .value_name("RATIO")
+            .help("Allowed increase of plan estimates over query baselines, e.g. 0.5 for 50% (default: 0.5)")
+            .value_parser(clap::value_parser!(f64)),
+        Arg::new("suggest-timeouts")
+            .long("suggest-timeouts")
+            .help("Report a statement timeout suggested from the plan estimates of every read-only query")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("timeout-suggestions-file")
+            .long("timeout-suggestions-file")
+            .help("Write the suggested timeouts to automodel.timeouts.json in the output directory instead of reporting them")
+            .action(clap::ArgAction::SetTrue)
+            .requires("suggest-timeouts"),
+        Arg::new("ms-per-cost")
+            .long("ms-per-cost")
+            .value_name("MS")
+            .help("Milliseconds a unit of planner cost takes, used with --suggest-timeouts (default: 0.01)")
+            .value_parser(clap::value_parser!(f64))
+            .requires("suggest-timeouts"),
+        Arg::new("fail-fast")
+            .long("fail-fast")
+            .help("Stop analyzing at the first query which fails instead of reporting all failing queries")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("analysis-order")
+            .long("analysis-order")
+            .value_name("ORDER")
+            .help("Order in which queries are analyzed: files or recently_modified")
+            .value_parser(clap::value_parser!(AnalysisOrder)),
+        Arg::new("shared-analysis-cache")
+            .long("shared-analysis-cache")
+            .help("Cache the type information of prepared statements in the cargo target directory, shared by the crates of the workspace")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("only")
+            .long("only")
+            .value_name("MODULE[/QUERY]")
+            .help("Only analyze these modules or queries, the others reuse their cached analysis (repeatable or comma-separated)")
+            .value_delimiter(',')
+            .action(clap::ArgAction::Append),
+        Arg::new("plan-sampling")
+            .long("plan-sampling")
+            .help("Generate runtime EXPLAIN ANALYZE sampling hooks for read-only queries")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("cardinality-drift")
+            .long("cardinality-drift")
+            .help("Report calls of read-only queries returning an order of magnitude more or fewer rows than estimated")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("limit-possible-one")
+            .long("limit-possible-one")
+            .help("Add LIMIT 1 to read-only possible_one queries without a LIMIT")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("auto-derives")
+            .long("auto-derives")
+            .help("Derive comparison and hash traits on generated structs when all fields support them")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("define")
+            .long("define")
+            .value_name("NAME=VALUE")
+            .help("Value substituted for @@NAME@@ in the SQL, overriding -- @define lines (repeatable)")
+            .value_parser(parse_define)
+            .action(clap::ArgAction::Append),
+        Arg::new("feature")
+            .long("feature")
+            .value_name("NAME")
+            .help("Cargo feature enabled for the build, selecting the #[cfg(feature = \"NAME\")] blocks of the SQL (repeatable or comma-separated)")
+            .value_delimiter(',')
+            .action(clap::ArgAction::Append),
+        Arg::new("blocking")
+            .long("blocking")
+            .help("Generate *_blocking wrappers behind the `blocking` feature for callers without tokio")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("cancellable")
+            .long("cancellable")
+            .help("Generate *_cancellable variants behind the `cancellation` feature, cancelling the query on the server when a CancellationToken is cancelled")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("slow-query-ms")
+            .long("slow-query-ms")
+            .value_name("MS")
+            .help("Emit a tracing::warn! event when a generated function runs longer than MS milliseconds")
+            .value_parser(clap::value_parser!(u64)),
+        Arg::new("format-sql")
+            .long("format-sql")
+            .help("Format the SQL of every query before generating code from it")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("canonical-shard")
+            .long("canonical-shard")
+            .value_name("SHARD")
+            .help("Shard substituted for ${shard} when analyzing queries with shard_by"),
+        Arg::new("emit-ts")
+            .long("emit-ts")
+            .value_name("DIR")
+            .help("Write TypeScript interfaces of the generated result structs to this directory"),
+        Arg::new("emit-proto")
+            .long("emit-proto")
+            .value_name("DIR")
+            .help("Write proto3 messages of the result structs of queries with proto: true to this directory"),
+        Arg::new("cache-invalidation-migrations")
+            .long("cache-invalidation-migrations")
+            .value_name("DIR")
+            .help("Write a migration to this directory whenever the triggers notifying changes of the tables of queries with cache_invalidation change"),
+        Arg::new("module-crates")
+            .long("module-crates")
+            .value_name("NAME")
+            .help("Write every module into its own crate, re-exported by an umbrella crate with this name"),
+        Arg::new("crate-dependencies")
+            .long("crate-dependencies")
+            .value_name("FILE")
+            .help("File with the [dependencies] lines of the generated crates, used with --module-crates")
+            .requires("module-crates"),
+        Arg::new("max-queries-per-file")
+            .long("max-queries-per-file")
+            .value_name("COUNT")
+            .help("Split modules with more queries into {module}/queries_N.rs files of at most COUNT queries")
+            .value_parser(clap::value_parser!(usize)),
+        Arg::new("max-variant-combinations")
+            .long("max-variant-combinations")
+            .value_name("COUNT")
+            .help("Also analyze up to COUNT variants of each query combining several conditional blocks")
+            .value_parser(clap::value_parser!(usize)),
+        Arg::new("history-queries")
+            .long("history-queries")
+            .value_name("MODULE")
+            .help("Generate get_<table>_history functions into MODULE for every table with an audit history table"),
+        Arg::new("history-suffix")
+            .long("history-suffix")
+            .value_name("SUFFIX")
+            .help("Suffix of the history table names, used with --history-queries [default: _history]")
+            .requires("history-queries"),
+        Arg::new("history-time-column")
+            .long("history-time-column")
+            .value_name("COLUMN")
+            .help("Column of the history tables holding the time of each change, used with --history-queries [default: changed_at]")
+            .requires("history-queries"),
+        Arg::new("mandatory-predicate")
+            .long("mandatory-predicate")
+            .value_name("TABLES:PREDICATE")
+            .help("Predicate every query touching the comma-separated TABLES must apply, e.g. \"orders,invoices:tenant_id = #{tenant_id}\" (repeatable)")
+            .value_parser(parse_mandatory_predicate)
+            .action(clap::ArgAction::Append),
+        Arg::new("inject-mandatory-predicates")
+            .long("inject-mandatory-predicates")
+            .help("Add missing mandatory predicates to the WHERE clause of the queries instead of failing")
+            .action(clap::ArgAction::SetTrue)
+            .requires("mandatory-predicate"),
+        Arg::new("extra-queries-dir")
+            .long("extra-queries-dir")
+            .value_name("DIR")
+            .help("Directory of more SQL query files, loaded after --queries-dir (repeatable)")
+            .action(clap::ArgAction::Append),
+        Arg::new("force")
+            .long("force")
+            .help("Overwrite generated files which were edited by hand since they were generated")
+            .action(clap::ArgAction::SetTrue),
+    ]
+}
+
+/// Build the defaults configuration from the arguments of `defaults_args()`
+fn defaults_from_matches(matches: &ArgMatches) -> Result<DefaultsConfig> {
     let telemetry_level = match matches
         .get_one::<String>("telemetry-level")
         .unwrap()
@@ -100,24 +556,418 @@ async fn generate_command(matches: &ArgMatches) -> Result<()> {
         _ => TelemetryLevel::None,
     };
 
-    let telemetry_include_sql = matches.get_flag("telemetry-include-sql");
-    let ensure_indexes = matches.get_flag("ensure-indexes");
+    let module_crates = match matches.get_one::<String>("module-crates") {
+        Some(name) => {
+            let dependencies = match matches.get_one::<String>("crate-dependencies") {
+                Some(file) => std::fs::read_to_string(file).map_err(|e| {
+                    anyhow::anyhow!("Failed to read crate dependencies from {}: {}", file, e)
+                })?,
+                None => String::new(),
+            };
+            Some(ModuleCratesConfig {
+                name: name.clone(),
+                dependencies,
+            })
+        }
+        None => None,
+    };
+
+    let history_queries = matches.get_one::<String>("history-queries").map(|module| {
+        let defaults = HistoryQueriesConfig::default();
+        HistoryQueriesConfig {
+            module: module.clone(),
+            suffix: matches
+                .get_one::<String>("history-suffix")
+                .cloned()
+                .unwrap_or(defaults.suffix),
+            time_column: matches
+                .get_one::<String>("history-time-column")
+                .cloned()
+                .unwrap_or(defaults.time_column),
+        }
+    });
 
-    let defaults = DefaultsConfig {
+    Ok(DefaultsConfig {
         telemetry: DefaultsTelemetryConfig {
             level: telemetry_level,
-            include_sql: telemetry_include_sql,
+            include_sql: matches.get_flag("telemetry-include-sql"),
+            max_param_length: matches
+                .get_one::<usize>("telemetry-max-param-length")
+                .copied(),
+            max_param_elements: matches
+                .get_one::<usize>("telemetry-max-param-elements")
+                .copied(),
         },
-        ensure_indexes: ensure_indexes,
-    };
+        ensure_indexes: matches.get_flag("ensure-indexes"),
+        query_analysis_timeout: matches
+            .get_one::<u64>("query-timeout")
+            .map(|secs| std::time::Duration::from_secs(*secs)),
+        analysis_budget: matches
+            .get_one::<u64>("analysis-budget")
+            .map(|secs| std::time::Duration::from_secs(*secs)),
+        analysis_concurrency: matches.get_one::<usize>("analysis-concurrency").copied(),
+        analysis_connections: matches.get_one::<usize>("analysis-connections").copied(),
+        transaction_pooling: matches.get_flag("transaction-pooling"),
+        min_pg_version: matches.get_one::<u32>("min-pg-version").copied(),
+        production_pg_version: matches.get_one::<u32>("production-pg-version").copied(),
+        schema_version_query: matches.get_one::<String>("schema-version-query").cloned(),
+        expected_schema_version: matches.get_one::<String>("expect-schema-version").cloned(),
+        fail_on: matches
+            .get_many::<FailOn>("fail-on")
+            .map(|rules| rules.copied().collect())
+            .unwrap_or_default(),
+        baseline_tolerance: matches.get_one::<f64>("baseline-tolerance").copied(),
+        suggest_timeouts: matches.get_flag("suggest-timeouts").then(|| {
+            let defaults = TimeoutSuggestionsConfig::default();
+            TimeoutSuggestionsConfig {
+                ms_per_cost: matches
+                    .get_one::<f64>("ms-per-cost")
+                    .copied()
+                    .unwrap_or(defaults.ms_per_cost),
+                write_file: matches.get_flag("timeout-suggestions-file"),
+                ..defaults
+            }
+        }),
+        plan_sampling: matches.get_flag("plan-sampling"),
+        cardinality_drift: matches.get_flag("cardinality-drift"),
+        limit_possible_one: matches.get_flag("limit-possible-one"),
+        auto_derives: matches.get_flag("auto-derives"),
+        blocking: matches.get_flag("blocking"),
+        cancellable: matches.get_flag("cancellable"),
+        slow_query_ms: matches.get_one::<u64>("slow-query-ms").copied(),
+        canonical_shard: matches.get_one::<String>("canonical-shard").cloned(),
+        emit_ts: matches.get_one::<String>("emit-ts").cloned(),
+        emit_proto: matches.get_one::<String>("emit-proto").cloned(),
+        cache_invalidation_migrations: matches
+            .get_one::<String>("cache-invalidation-migrations")
+            .cloned(),
+        module_crates,
+        max_queries_per_file: matches.get_one::<usize>("max-queries-per-file").copied(),
+        max_variant_combinations: matches
+            .get_one::<usize>("max-variant-combinations")
+            .copied()
+            .unwrap_or_default(),
+        overwrite_edited_files: matches.get_flag("force"),
+        defines: matches
+            .get_many::<(String, String)>("define")
+            .map(|defines| defines.cloned().collect())
+            .unwrap_or_default(),
+        features: matches
+            .get_many::<String>("feature")
+            .map(|features| features.cloned().collect())
+            .unwrap_or_default(),
+        format_sql: matches.get_flag("format-sql"),
+        dialect: matches
+            .get_one::<Dialect>("dialect")
+            .copied()
+            .unwrap_or_default(),
+        fail_fast: matches.get_flag("fail-fast"),
+        analysis_order: matches
+            .get_one::<AnalysisOrder>("analysis-order")
+            .copied()
+            .unwrap_or_default(),
+        only: matches
+            .get_many::<String>("only")
+            .map(|entries| entries.cloned().collect())
+            .unwrap_or_default(),
+        shared_analysis_cache: matches.get_flag("shared-analysis-cache"),
+        sqlx_version: matches
+            .get_one::<SqlxVersion>("sqlx-version")
+            .copied()
+            .unwrap_or_default(),
+        history_queries,
+        vendored_runtime: vendored_runtime_from_matches(matches)?,
+        mandatory_predicates: matches
+            .get_many::<MandatoryPredicate>("mandatory-predicate")
+            .map(|rules| {
+                rules
+                    .map(|rule| MandatoryPredicate {
+                        inject: matches.get_flag("inject-mandatory-predicates"),
+                        ..rule.clone()
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        extra_query_dirs: matches
+            .get_many::<String>("extra-queries-dir")
+            .map(|dirs| dirs.cloned().collect())
+            .unwrap_or_default(),
+    })
+}
+
+async fn usage_command(matches: &ArgMatches) -> Result<()> {
+    let queries_dir = matches.get_one::<String>("queries-dir").unwrap();
+    let output_dir = matches.get_one::<String>("output").unwrap();
+    let manifest_path = matches.get_one::<String>("manifest-path");
+    let defaults = defaults_from_matches(matches)?;
 
-    println!("Loading queries from: {}", queries_dir);
-    println!("Output directory: {}", output_dir);
-    println!("Default telemetry level: {:?}", telemetry_level);
-    println!("Default ensure indexes: {}", ensure_indexes);
+    let automodel = AutoModel::new(queries_dir, defaults)
+        .await
+        .map_err(|e| anyhow::anyhow!("Loading queries failed: {}", e))?;
+    let queries = automodel.query_names();
+    let source_files = automodel.query_source_files();
+    let functions = usage::find_usage(
+        &queries,
+        std::path::Path::new(output_dir),
+        manifest_path.map(String::as_str),
+    )?;
+
+    let mut unused = Vec::new();
+    let mut unused_queries = Vec::new();
+    let mut report_entries = Vec::new();
+    for ((module, query), (_, _, file)) in queries.iter().zip(&source_files) {
+        let query_functions: Vec<_> = functions
+            .iter()
+            .filter(|function| function.module == *module && function.query == *query)
+            .collect();
+        let call_sites: usize = query_functions.iter().map(|f| f.call_sites.len()).sum();
+        println!("{}::{} ({} call sites)", module, query, call_sites);
+        for function in &query_functions {
+            if function.call_sites.is_empty() {
+                println!("  {}: unused", function.name);
+                unused.push(format!("{}::{}", module, function.name));
+            } else {
+                println!("  {}: {}", function.name, function.call_sites.join(", "));
+            }
+        }
+        if call_sites == 0 {
+            unused_queries.push(format!("{}::{}", module, query));
+        }
+        report_entries.push((
+            format!("{}::{}", module, query),
+            call_sites,
+            file.as_ref().map(|file| file.display().to_string()),
+        ));
+    }
+
+    println!();
+    println!("{} unused generated functions", unused.len());
+    for name in &unused {
+        println!("  {}", name);
+    }
+    println!("{} queries without call sites", unused_queries.len());
+    for name in &unused_queries {
+        println!("  {}", name);
+    }
+
+    if let Some(report_path) = matches.get_one::<String>("save-report") {
+        report::save_usage_report(std::path::Path::new(report_path), &report_entries)?;
+        println!("Usage report written to {}", report_path);
+    }
+
+    Ok(())
+}
+
+async fn clean_command(matches: &ArgMatches) -> Result<()> {
+    let queries_dir = matches.get_one::<String>("queries-dir").unwrap();
+    let output_dir = matches.get_one::<String>("output").unwrap();
+    let all = matches.get_flag("all");
+    let dry_run = matches.get_flag("dry-run");
+    let defaults = defaults_from_matches(matches)?;
+
+    let automodel = AutoModel::new(queries_dir, defaults)
+        .await
+        .map_err(|e| anyhow::anyhow!("Loading queries failed: {}", e))?;
+    let removed = automodel.clean(output_dir, all, dry_run)?;
+    for path in &removed {
+        if dry_run {
+            println!("would remove {}", path.display());
+        } else {
+            println!("removed {}", path.display());
+        }
+    }
+    if removed.is_empty() {
+        println!("Nothing to clean in {}", output_dir);
+    }
+    Ok(())
+}
+
+fn audit_command(matches: &ArgMatches) -> Result<()> {
+    let output_dir = std::path::Path::new(matches.get_one::<String>("output").unwrap());
+
+    // Modules are audited with their part files, or as the sources of their crate
+    let mut modules: std::collections::BTreeMap<String, String> = std::collections::BTreeMap::new();
+    let mut files = Vec::new();
+    collect_generated_files(output_dir, &mut files)?;
+    for file in files {
+        let Some(module) = file
+            .strip_prefix(output_dir)
+            .ok()
+            .and_then(|relative| relative.components().next())
+            .and_then(|component| std::path::Path::new(component.as_os_str()).file_stem())
+            .and_then(|stem| stem.to_str())
+        else {
+            continue;
+        };
+        let code = std::fs::read_to_string(&file)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", file.display(), e))?;
+        let sources = modules.entry(module.to_string()).or_default();
+        sources.push_str(&code);
+        sources.push('\n');
+    }
+
+    let mut findings = 0;
+    for (module, code) in &modules {
+        let audit = audit_generated_sql(code);
+        if !audit.dynamic_sql_functions.is_empty() {
+            println!(
+                "{}: {} functions assemble SQL at runtime",
+                module,
+                audit.dynamic_sql_functions.len()
+            );
+            for function in &audit.dynamic_sql_functions {
+                println!("  {}", function);
+            }
+        }
+        for finding in &audit.findings {
+            println!("error: {}::{}", module, finding);
+        }
+        findings += audit.findings.len();
+    }
+    println!(
+        "{} modules audited, {} unvalidated values reaching SQL",
+        modules.len(),
+        findings
+    );
+
+    if findings > 0 {
+        anyhow::bail!(
+            "{} unvalidated values reach the SQL of generated functions",
+            findings
+        );
+    }
+    Ok(())
+}
+
+/// Collect the `.rs` files of the generated code
+fn collect_generated_files(
+    dir: &std::path::Path,
+    files: &mut Vec<std::path::PathBuf>,
+) -> Result<()> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| anyhow::anyhow!("Failed to read directory {}: {}", dir.display(), e))?;
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_generated_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(())
+}
+
+async fn report_command(matches: &ArgMatches) -> Result<()> {
+    let queries_dir = matches.get_one::<String>("queries-dir").unwrap();
+    let unused_for = *matches.get_one::<usize>("unused-for").unwrap();
+    let defaults = defaults_from_matches(matches)?;
+
+    let reports = matches
+        .get_many::<String>("usage-report")
+        .unwrap()
+        .map(|path| report::load_usage_report(std::path::Path::new(path)))
+        .collect::<Result<Vec<_>>>()?;
+    let automodel = AutoModel::new(queries_dir, defaults)
+        .await
+        .map_err(|e| anyhow::anyhow!("Loading queries failed: {}", e))?;
+    let queries: Vec<(String, Option<String>)> = automodel
+        .query_source_files()
+        .into_iter()
+        .map(|(module, query, file)| {
+            (
+                format!("{}::{}", module, query),
+                file.map(|file| file.display().to_string()),
+            )
+        })
+        .collect();
+
+    let stale = report::stale_queries(&queries, &reports, unused_for);
+    for query in &stale {
+        match &query.file {
+            Some(file) => println!(
+                "warning: query {} is unused in the last {} releases, consider removing {}",
+                query.name, query.unused_releases, file
+            ),
+            None => println!(
+                "warning: query {} is unused in the last {} releases",
+                query.name, query.unused_releases
+            ),
+        }
+    }
+    if let Some(latest) = reports.last() {
+        for name in report::removed_queries(&queries, latest) {
+            println!("removed since the latest report: {}", name);
+        }
+    }
+    println!(
+        "{} queries unused in the last {} releases",
+        stale.len(),
+        unused_for
+    );
+
+    if matches.get_flag("deny") && !stale.is_empty() {
+        anyhow::bail!("{} queries are unused, remove them", stale.len());
+    }
+    Ok(())
+}
+
+/// Parse a `--define NAME=VALUE` argument
+fn parse_define(define: &str) -> Result<(String, String), String> {
+    match define.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("expected NAME=VALUE, got '{}'", define)),
+    }
+}
+
+fn parse_mandatory_predicate(rule: &str) -> Result<MandatoryPredicate, String> {
+    match rule.split_once(':') {
+        Some((tables, predicate)) if !predicate.trim().is_empty() => Ok(MandatoryPredicate {
+            tables: tables
+                .split(',')
+                .map(|table| table.trim().to_string())
+                .filter(|table| !table.is_empty())
+                .collect(),
+            predicate: predicate.trim().to_string(),
+            inject: false,
+        }),
+        _ => Err(format!("expected TABLES:PREDICATE, got '{}'", rule)),
+    }
+}
+
+async fn generate_command(matches: &ArgMatches) -> Result<()> {
+    let database_url = matches.get_one::<String>("database-url").unwrap();
+    let queries_dir = matches.get_one::<String>("queries-dir").unwrap();
+    let output_dir = matches.get_one::<String>("output").unwrap();
+
+    let quiet = matches.get_flag("quiet");
+
+    // Build defaults configuration from command-line arguments
+    let defaults = defaults_from_matches(matches)?;
+
+    if !quiet {
+        println!("Loading queries from: {}", queries_dir);
+        println!("Output directory: {}", output_dir);
+        println!("Default telemetry level: {:?}", defaults.telemetry.level);
+        println!("Default ensure indexes: {}", defaults.ensure_indexes);
+        if let Some(vendored_runtime) = &defaults.vendored_runtime {
+            println!(
+                "Vendored runtime: {} ({})",
+                vendored_runtime.file, vendored_runtime.module_path
+            );
+        }
+    }
 
     // Use the same method as build.rs
-    AutoModel::generate(
+    let context = if quiet {
+        GenerationContext::quiet()
+    } else {
+        GenerationContext::default()
+    };
+    AutoModel::generate_with_context(
+        context.clone(),
         || Ok(database_url.to_string()),
         queries_dir,
         output_dir,
@@ -126,7 +976,119 @@ async fn generate_command(matches: &ArgMatches) -> Result<()> {
     .await
     .map_err(|e| anyhow::anyhow!("Code generation failed: {}", e))?;
 
-    println!("✓ Code generation complete!");
+    if !quiet {
+        println!("✓ Code generation complete!");
+    }
+    if matches.get_flag("profile-build") {
+        print_build_profile(&context.build_summary());
+    }
+
+    Ok(())
+}
+
+/// Number of queries listed by `--profile-build`
+const PROFILED_QUERIES: usize = 10;
+
+/// Print the phases of the generation and the slowest queries to analyze
+fn print_build_profile(summary: &BuildSummary) {
+    if summary.queries.is_empty() {
+        println!("Generated code was up to date, no queries were analyzed");
+        return;
+    }
+    println!("Build profile ({}ms):", summary.total().as_millis());
+    println!("  scan:     {:>8}ms", summary.scan.as_millis());
+    println!("  analysis: {:>8}ms", summary.analysis.as_millis());
+    println!("  codegen:  {:>8}ms", summary.codegen.as_millis());
+    println!("  write:    {:>8}ms", summary.write.as_millis());
+    println!("Slowest queries to analyze:");
+    for query in summary.slowest_queries(PROFILED_QUERIES) {
+        println!(
+            "  {:>8}ms  {}::{}{}",
+            query.duration.as_millis(),
+            query.module,
+            query.name,
+            if query.cached { " (cached)" } else { "" }
+        );
+    }
+}
+
+/// Vendored runtime of `vendor-runtime --out` or `generate --vendored-runtime`
+fn vendored_runtime_from_matches(matches: &ArgMatches) -> Result<Option<VendoredRuntimeConfig>> {
+    let (file, module_path) = match matches.try_get_one::<String>("out") {
+        Ok(file) => (file, matches.get_one::<String>("module-path")),
+        Err(_) => (
+            matches
+                .try_get_one::<String>("vendored-runtime")
+                .ok()
+                .flatten(),
+            matches
+                .try_get_one::<String>("vendored-runtime-module")
+                .ok()
+                .flatten(),
+        ),
+    };
+    let Some(file) = file else {
+        return Ok(None);
+    };
+    let vendored_runtime = match module_path {
+        Some(module_path) => VendoredRuntimeConfig {
+            file: file.clone(),
+            module_path: module_path.clone(),
+        },
+        None => VendoredRuntimeConfig::for_file(file)?,
+    };
+    Ok(Some(vendored_runtime))
+}
+
+async fn config_command(matches: &ArgMatches) -> Result<()> {
+    let queries_dir = matches.get_one::<String>("queries-dir").unwrap();
+    let defaults = defaults_from_matches(matches)?;
+
+    let automodel = AutoModel::new(queries_dir, defaults)
+        .await
+        .map_err(|e| anyhow::anyhow!("Loading queries failed: {}", e))?;
+    print!("{}", automodel.effective_config()?);
+
+    Ok(())
+}
+
+async fn baseline_command(matches: &ArgMatches) -> Result<()> {
+    let database_url = matches.get_one::<String>("database-url").unwrap();
+    let queries_dir = matches.get_one::<String>("queries-dir").unwrap();
+    let defaults = defaults_from_matches(matches)?;
+
+    let automodel = AutoModel::new(queries_dir, defaults)
+        .await
+        .map_err(|e| anyhow::anyhow!("Loading queries failed: {}", e))?
+        .with_context(GenerationContext::quiet());
+    let updated = automodel
+        .update_baselines(database_url, matches.get_flag("all"))
+        .await
+        .map_err(|e| anyhow::anyhow!("Updating baselines failed: {}", e))?;
+    for path in &updated {
+        println!("Updated: {}", path.display());
+    }
+    println!("{} baselines updated", updated.len());
+
+    Ok(())
+}
+
+fn fmt_command(matches: &ArgMatches) -> Result<()> {
+    let queries_dir = matches.get_one::<String>("queries-dir").unwrap();
+    let check = matches.get_flag("check");
+
+    let unformatted = AutoModel::format_queries(queries_dir, check)
+        .map_err(|e| anyhow::anyhow!("Formatting queries failed: {}", e))?;
+    for path in &unformatted {
+        if check {
+            println!("Not formatted: {}", path.display());
+        } else {
+            println!("Formatted: {}", path.display());
+        }
+    }
+    if check && !unformatted.is_empty() {
+        std::process::exit(1);
+    }
 
     Ok(())
 }
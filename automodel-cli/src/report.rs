@@ -0,0 +1,96 @@
+/// Queries left unused across several releases, found by comparing the loaded queries with the
+/// usage reports saved by `automodel usage --save-report` at each release
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Uses of every query in one release, keyed by "module::query"
+/// Saved as JSON, e.g. {"users::insert_user": {"uses": 3, "file": "queries/users/01_insert_user.sql"}}
+/// Runtime coverage tools may write the same format with the number of calls as `uses`
+pub type UsageReport = BTreeMap<String, u64>;
+
+/// Query unused in the latest releases
+pub struct StaleQuery {
+    /// "module::query"
+    pub name: String,
+    /// SQL file defining the query, None for queries generated from introspection
+    pub file: Option<String>,
+    /// Number of the latest reports in which the query exists without uses
+    pub unused_releases: usize,
+}
+
+/// Write the usage report of a release
+/// queries: ("module::query", uses, SQL file) of every query
+pub fn save_usage_report(path: &Path, queries: &[(String, usize, Option<String>)]) -> Result<()> {
+    let report: serde_json::Map<String, serde_json::Value> = queries
+        .iter()
+        .map(|(name, uses, file)| {
+            let mut entry = serde_json::json!({ "uses": uses });
+            if let Some(file) = file {
+                entry["file"] = serde_json::Value::from(file.as_str());
+            }
+            (name.clone(), entry)
+        })
+        .collect();
+    std::fs::write(path, serde_json::to_string_pretty(&report)? + "\n")
+        .with_context(|| format!("Failed to write usage report: {}", path.display()))
+}
+
+/// Read a usage report saved by `save_usage_report` or a coverage tool
+pub fn load_usage_report(path: &Path) -> Result<UsageReport> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read usage report: {}", path.display()))?;
+    let report: serde_json::Map<String, serde_json::Value> = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse usage report: {}", path.display()))?;
+    report
+        .into_iter()
+        .map(|(name, entry)| {
+            let uses = entry["uses"].as_u64().with_context(|| {
+                format!(
+                    "Usage report {} has no `uses` count for query '{}'",
+                    path.display(),
+                    name
+                )
+            })?;
+            Ok((name, uses))
+        })
+        .collect()
+}
+
+/// Loaded queries without uses in each of the latest `releases` reports
+/// A query missing from one of these reports did not exist in that release and is not stale yet
+/// reports: usage reports ordered from the oldest to the latest release
+/// queries: ("module::query", SQL file) of every loaded query
+pub fn stale_queries(
+    queries: &[(String, Option<String>)],
+    reports: &[UsageReport],
+    releases: usize,
+) -> Vec<StaleQuery> {
+    queries
+        .iter()
+        .filter_map(|(name, file)| {
+            let unused_releases = reports
+                .iter()
+                .rev()
+                .take_while(|report| report.get(name) == Some(&0))
+                .count();
+            (releases > 0 && unused_releases >= releases).then(|| StaleQuery {
+                name: name.clone(),
+                file: file.clone(),
+                unused_releases,
+            })
+        })
+        .collect()
+}
+
+/// Queries of the latest report which are no longer loaded
+pub fn removed_queries<'a>(
+    queries: &[(String, Option<String>)],
+    latest: &'a UsageReport,
+) -> Vec<&'a str> {
+    latest
+        .keys()
+        .filter(|name| !queries.iter().any(|(query, _)| query == *name))
+        .map(String::as_str)
+        .collect()
+}
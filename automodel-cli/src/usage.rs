@@ -0,0 +1,282 @@
+/// Report of where the generated functions are used in the workspace, to find stale queries and
+/// the code affected by changing one. The generated code and the sources of every workspace
+/// package listed by `cargo metadata` are parsed with syn
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use syn::spanned::Spanned;
+use syn::visit::Visit;
+
+/// Public function of the generated code with the places using it
+pub struct GeneratedFunction {
+    /// Module of the query, i.e. its directory under the queries directory
+    pub module: String,
+    /// Query the function was generated for
+    pub query: String,
+    /// Function name, e.g. "insert_user" or "insert_user_id_only"
+    pub name: String,
+    /// Calls and other references as `path:line`, relative to the workspace root
+    pub call_sites: Vec<String>,
+}
+
+/// Find the public functions generated for the queries and the places using them in the
+/// workspace of the manifest
+/// queries: module and name of every query, as returned by `AutoModel::query_names`
+pub fn find_usage(
+    queries: &[(String, String)],
+    output_dir: &Path,
+    manifest_path: Option<&str>,
+) -> Result<Vec<GeneratedFunction>> {
+    let mut functions = generated_functions(queries, output_dir)?;
+    let (workspace_root, package_dirs) = workspace_packages(manifest_path)?;
+
+    // The generated code is not a use of itself
+    let output_dir = output_dir
+        .canonicalize()
+        .with_context(|| format!("Failed to read output directory: {}", output_dir.display()))?;
+    let mut source_files = Vec::new();
+    for package_dir in &package_dirs {
+        collect_rust_files(package_dir, Some(&output_dir), &mut source_files)?;
+    }
+    source_files.sort();
+    source_files.dedup();
+
+    let mut by_name: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, function) in functions.iter().enumerate() {
+        by_name.entry(function.name.clone()).or_default().push(i);
+    }
+    for source_file in source_files {
+        let content = std::fs::read_to_string(&source_file)
+            .with_context(|| format!("Failed to read source file: {}", source_file.display()))?;
+        // Files which do not parse, e.g. templates or fixtures, cannot call anything
+        let Ok(file) = syn::parse_file(&content) else {
+            continue;
+        };
+        let mut visitor = UsageVisitor {
+            functions: &functions,
+            by_name: &by_name,
+            found: Vec::new(),
+        };
+        visitor.visit_file(&file);
+
+        let display_path = source_file
+            .strip_prefix(&workspace_root)
+            .unwrap_or(&source_file)
+            .display()
+            .to_string();
+        for (i, line) in visitor.found {
+            let call_site = format!("{}:{}", display_path, line);
+            if !functions[i].call_sites.contains(&call_site) {
+                functions[i].call_sites.push(call_site);
+            }
+        }
+    }
+
+    Ok(functions)
+}
+
+/// Public functions of the generated modules, each attributed to the query with the longest
+/// name it starts with, e.g. `insert_user_id_only` to `insert_user`
+/// Functions of the shared `mod.rs` and helpers matching no query are left out
+fn generated_functions(
+    queries: &[(String, String)],
+    output_dir: &Path,
+) -> Result<Vec<GeneratedFunction>> {
+    let mut files = Vec::new();
+    collect_rust_files(output_dir, None, &mut files)?;
+    files.sort();
+
+    let mut functions = Vec::new();
+    for file_path in files {
+        // Modules are `{module}.rs`, `{module}/queries_N.rs` or `{module}/src/lib.rs` crates
+        let Some(module) = file_path
+            .strip_prefix(output_dir)
+            .ok()
+            .and_then(|relative| relative.components().next())
+            .and_then(|component| Path::new(component.as_os_str()).file_stem())
+            .and_then(|stem| stem.to_str())
+        else {
+            continue;
+        };
+        let content = std::fs::read_to_string(&file_path)
+            .with_context(|| format!("Failed to read generated file: {}", file_path.display()))?;
+        let file = syn::parse_file(&content)
+            .with_context(|| format!("Failed to parse generated file: {}", file_path.display()))?;
+
+        for item in &file.items {
+            let syn::Item::Fn(item_fn) = item else {
+                continue;
+            };
+            if !matches!(item_fn.vis, syn::Visibility::Public(_)) {
+                continue;
+            }
+            let name = item_fn.sig.ident.to_string();
+            let query = queries
+                .iter()
+                .filter(|(query_module, query)| {
+                    query_module == module
+                        && (name == *query || name.starts_with(&format!("{}_", query)))
+                })
+                .map(|(_, query)| query)
+                .max_by_key(|query| query.len());
+            // Functions gated behind a feature have a stub with the same name
+            let is_duplicate = functions
+                .iter()
+                .any(|f: &GeneratedFunction| f.module == module && f.name == name);
+            if let (Some(query), false) = (query, is_duplicate) {
+                functions.push(GeneratedFunction {
+                    module: module.to_string(),
+                    query: query.clone(),
+                    name,
+                    call_sites: Vec::new(),
+                });
+            }
+        }
+    }
+
+    Ok(functions)
+}
+
+/// Workspace root and the directories of the workspace packages, from `cargo metadata`
+fn workspace_packages(manifest_path: Option<&str>) -> Result<(PathBuf, Vec<PathBuf>)> {
+    let mut command =
+        std::process::Command::new(std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string()));
+    command.args(["metadata", "--format-version", "1", "--no-deps"]);
+    if let Some(manifest_path) = manifest_path {
+        command.args(["--manifest-path", manifest_path]);
+    }
+    let output = command.output().context("Failed to run cargo metadata")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "cargo metadata failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    let metadata: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .context("Failed to parse the output of cargo metadata")?;
+    let workspace_root = metadata["workspace_root"]
+        .as_str()
+        .map(PathBuf::from)
+        .context("cargo metadata did not report the workspace root")?;
+    let package_dirs = metadata["packages"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|package| package["manifest_path"].as_str())
+        .filter_map(|manifest_path| Path::new(manifest_path).parent().map(Path::to_path_buf))
+        .collect();
+    Ok((workspace_root, package_dirs))
+}
+
+/// Collect the `.rs` files of a directory tree, skipping build output, hidden directories and
+/// the excluded directory
+fn collect_rust_files(dir: &Path, exclude: Option<&Path>, files: &mut Vec<PathBuf>) -> Result<()> {
+    let entries = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory: {}", dir.display()))?;
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            let file_name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or("");
+            let excluded = exclude
+                .is_some_and(|exclude| path.canonicalize().is_ok_and(|path| path == exclude));
+            if file_name != "target" && !file_name.starts_with('.') && !excluded {
+                collect_rust_files(&path, exclude, files)?;
+            }
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Finds the paths naming generated functions and the `#[automodel::query]` attributes binding
+/// trait methods to them, as (function index, line)
+struct UsageVisitor<'a> {
+    functions: &'a [GeneratedFunction],
+    /// Key: function name, value: indices of the functions with that name
+    by_name: &'a HashMap<String, Vec<usize>>,
+    found: Vec<(usize, usize)>,
+}
+
+impl<'ast> Visit<'ast> for UsageVisitor<'_> {
+    fn visit_path(&mut self, path: &'ast syn::Path) {
+        let segments: Vec<String> = path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect();
+        if let Some((name, parents)) = segments.split_last() {
+            for &i in self.by_name.get(name).into_iter().flatten() {
+                // A qualified path has to go through the module of the function
+                let parent = parents.last();
+                if parent.is_none_or(|parent| {
+                    *parent == self.functions[i].module || parent.starts_with("queries_")
+                }) {
+                    let line = path.segments.last().map_or(0, |s| s.span().start().line);
+                    self.found.push((i, line));
+                }
+            }
+        }
+        syn::visit::visit_path(self, path);
+    }
+
+    fn visit_attribute(&mut self, attr: &'ast syn::Attribute) {
+        let is_query_attribute = attr.path().segments.last().is_some_and(|segment| {
+            segment.ident == "query"
+                && attr.path().segments.len() == 2
+                && attr.path().segments[0].ident == "automodel"
+        });
+        if is_query_attribute {
+            let mut file = None;
+            let _ = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("file") {
+                    file = Some(meta.value()?.parse::<syn::LitStr>()?.value());
+                } else if meta.input.peek(syn::Token![=]) {
+                    meta.value()?.parse::<syn::Expr>()?;
+                }
+                Ok(())
+            });
+            if let Some((module, query)) = file.as_deref().and_then(query_location) {
+                let line = attr.span().start().line;
+                for &i in self.by_name.get(&query).into_iter().flatten() {
+                    if self.functions[i].module == module {
+                        self.found.push((i, line));
+                    }
+                }
+            }
+        }
+        syn::visit::visit_attribute(self, attr);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        // Arguments of macros such as println! or tokio::join! are usually expressions
+        let args = mac.parse_body_with(
+            syn::punctuated::Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated,
+        );
+        if let Ok(args) = args {
+            for arg in &args {
+                self.visit_expr(arg);
+            }
+        }
+        syn::visit::visit_macro(self, mac);
+    }
+}
+
+/// Module and query name of a SQL file, e.g. ("users", "find_user_by_email")
+/// for "queries/users/07_find_user_by_email.sql"
+fn query_location(sql_path: &str) -> Option<(String, String)> {
+    let sql_path = Path::new(sql_path);
+    let module = sql_path.parent()?.file_name()?.to_str()?.to_string();
+    let file_stem = sql_path.file_stem()?.to_str()?;
+
+    // Strip numeric prefix if present (e.g., "01_query_name" -> "query_name")
+    let query_name = match file_stem.split_once('_') {
+        Some((prefix, name)) if prefix.chars().all(|c| c.is_ascii_digit()) => name.to_string(),
+        _ => file_stem.to_string(),
+    };
+    Some((module, query_name))
+}
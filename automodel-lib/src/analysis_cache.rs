@@ -0,0 +1,154 @@
+/// Prepared statement introspection results shared by the crates of a workspace on disk, so that
+/// queries analyzed by several build scripts against the same database are only prepared once
+use crate::types_extractor::QueryTypeInfo;
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// Directory of the cache inside the cargo target directory
+const ANALYSIS_CACHE_DIR_NAME: &str = "automodel-analysis-cache";
+
+/// Fingerprint of the catalog entries the introspection depends on: columns of the relations,
+/// enum labels, domains and function return types outside the system schemas
+const SCHEMA_FINGERPRINT_SQL: &str = "SELECT md5(coalesce(string_agg(entry, ',' ORDER BY entry), '')) FROM (
+    SELECT c.oid::text || ':' || a.attnum::text || ':' || a.attname || ':' || a.atttypid::text || ':' || a.attnotnull::text AS entry
+    FROM pg_catalog.pg_attribute a
+    JOIN pg_catalog.pg_class c ON c.oid = a.attrelid
+    JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace
+    WHERE a.attnum > 0 AND NOT a.attisdropped
+      AND n.nspname NOT IN ('pg_catalog', 'information_schema', 'pg_toast', 'crdb_internal', 'pg_extension')
+    UNION ALL
+    SELECT e.enumtypid::text || ':' || e.enumsortorder::text || ':' || e.enumlabel
+    FROM pg_catalog.pg_enum e
+    UNION ALL
+    SELECT t.oid::text || ':' || t.typname || ':' || t.typbasetype::text || ':' || t.typnotnull::text
+    FROM pg_catalog.pg_type t
+    JOIN pg_catalog.pg_namespace n ON n.oid = t.typnamespace
+    WHERE t.typtype = 'd'
+      AND n.nspname NOT IN ('pg_catalog', 'information_schema', 'crdb_internal', 'pg_extension')
+    UNION ALL
+    SELECT p.oid::text || ':' || p.proname || ':' || p.prorettype::text || ':' || p.proretset::text
+    FROM pg_catalog.pg_proc p
+    JOIN pg_catalog.pg_namespace n ON n.oid = p.pronamespace
+    WHERE n.nspname NOT IN ('pg_catalog', 'information_schema', 'crdb_internal', 'pg_extension')
+) entries";
+
+/// Cache of the type information of prepared statements, one file per statement
+/// Entries are keyed by the SQL text, its type mappings and search_path, the server version and
+/// a fingerprint of the schema, so that any schema change misses the cache
+#[derive(Debug, Clone)]
+pub(crate) struct SharedAnalysisCache {
+    dir: PathBuf,
+    /// Hash of the automodel version, server version and schema fingerprint
+    scope: String,
+}
+
+impl SharedAnalysisCache {
+    /// Directory of the cache: `$CARGO_TARGET_DIR/automodel-analysis-cache` when set, otherwise
+    /// the `target` directory of the workspace containing the current directory
+    pub(crate) fn default_dir() -> PathBuf {
+        if let Some(target_dir) = std::env::var_os("CARGO_TARGET_DIR") {
+            return PathBuf::from(target_dir).join(ANALYSIS_CACHE_DIR_NAME);
+        }
+        // Build scripts run in the directory of their package, the workspace root is the
+        // closest ancestor with a Cargo.lock
+        let current_dir = std::env::current_dir().unwrap_or_default();
+        let workspace_root = current_dir
+            .ancestors()
+            .find(|dir| dir.join("Cargo.lock").is_file())
+            .unwrap_or(&current_dir);
+        workspace_root.join("target").join(ANALYSIS_CACHE_DIR_NAME)
+    }
+
+    /// Open the cache in the given directory for the analysis database
+    /// Reads the server version and the schema fingerprint of the database
+    pub(crate) async fn open(dir: &Path, client: &tokio_postgres::Client) -> Result<Self> {
+        let row = client
+            .query_one(
+                &format!(
+                    "SELECT current_setting('server_version'), ({})",
+                    SCHEMA_FINGERPRINT_SQL
+                ),
+                &[],
+            )
+            .await
+            .context("Failed to read the schema fingerprint for the shared analysis cache")?;
+        let server_version: String = row.get(0);
+        let schema_fingerprint: String = row.get(1);
+
+        let mut hasher = Sha256::new();
+        hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+        hasher.update(b"\0");
+        hasher.update(server_version.as_bytes());
+        hasher.update(b"\0");
+        hasher.update(schema_fingerprint.as_bytes());
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            scope: format!("{:x}", hasher.finalize()),
+        })
+    }
+
+    /// Cached type information of a statement
+    /// Returns None if the statement is not cached or the entry cannot be read
+    pub(crate) fn get(
+        &self,
+        sql: &str,
+        types: Option<&std::collections::HashMap<String, String>>,
+        search_path: &[String],
+    ) -> Option<QueryTypeInfo> {
+        let content = std::fs::read_to_string(self.entry_path(sql, types, search_path)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Store the type information of a statement
+    /// The entry is written to a temporary file and renamed, as build scripts of other crates
+    /// may read or write it at the same time
+    pub(crate) fn put(
+        &self,
+        sql: &str,
+        types: Option<&std::collections::HashMap<String, String>>,
+        search_path: &[String],
+        type_info: &QueryTypeInfo,
+    ) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.entry_path(sql, types, search_path);
+        let temp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+        std::fs::write(&temp_path, serde_json::to_string(type_info)?)?;
+        std::fs::rename(&temp_path, &path)?;
+        Ok(())
+    }
+
+    /// File of the entry of a statement
+    fn entry_path(
+        &self,
+        sql: &str,
+        types: Option<&std::collections::HashMap<String, String>>,
+        search_path: &[String],
+    ) -> PathBuf {
+        let mut hasher = Sha256::new();
+        hasher.update(self.scope.as_bytes());
+        hasher.update(b"\0");
+        hasher.update(sql.as_bytes());
+
+        // Sort type mappings for deterministic hashing
+        if let Some(types) = types {
+            let mut mappings: Vec<_> = types.iter().collect();
+            mappings.sort();
+            for (field, rust_type) in mappings {
+                hasher.update(b"\0");
+                hasher.update(field.as_bytes());
+                hasher.update(b"\0");
+                hasher.update(rust_type.as_bytes());
+            }
+        }
+
+        // Unqualified names resolve through the search_path of the query
+        hasher.update(b"\0search_path");
+        for schema in search_path {
+            hasher.update(b"\0");
+            hasher.update(schema.as_bytes());
+        }
+
+        self.dir.join(format!("{:x}.json", hasher.finalize()))
+    }
+}
@@ -0,0 +1,135 @@
+/// Facts about the server the queries were analyzed against, recorded in the generated mod.rs and
+/// the build summary to tell apart generated code from different databases
+use crate::query_definition::Dialect;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Prefix of the mod.rs comment holding the server version and the analysis time
+const ANALYZED_COMMENT: &str = "// Analyzed against server version ";
+
+/// Prefix of the mod.rs comment holding the planner settings of the analysis
+const SETTINGS_COMMENT: &str = "// Analysis settings: ";
+
+/// Server version, planner settings and time of the analysis of the generated code
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnalysisEnvironment {
+    /// `server_version` of the analysis database, e.g. "16.4"
+    pub server_version: String,
+    /// `enable_seqscan` of the analysis sessions, "off" when analysis disables sequential scans
+    /// None when the server has no such setting, e.g. CockroachDB
+    pub enable_seqscan: Option<String>,
+    /// `search_path` of the analysis sessions, queries with their own `search_path` use theirs
+    pub search_path: String,
+    /// Schema version read with `schema_version_query`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// Time of the analysis in UTC, e.g. "2024-05-01T12:30:00Z"
+    pub analyzed_at: String,
+}
+
+impl AnalysisEnvironment {
+    /// Read the settings of an analysis session
+    pub(crate) async fn read(
+        client: &tokio_postgres::Client,
+        dialect: Dialect,
+        schema_version: Option<String>,
+    ) -> anyhow::Result<Self> {
+        let row = client
+            .query_one(
+                "SELECT current_setting('server_version'), current_setting('search_path')",
+                &[],
+            )
+            .await?;
+        // CockroachDB has no enable_seqscan setting
+        let enable_seqscan = if dialect.disables_seqscan() {
+            let row = client
+                .query_one("SELECT current_setting('enable_seqscan')", &[])
+                .await?;
+            Some(row.get(0))
+        } else {
+            None
+        };
+        Ok(Self {
+            server_version: row.get(0),
+            enable_seqscan,
+            search_path: row.get(1),
+            schema_version,
+            analyzed_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        })
+    }
+
+    /// Comment lines of the generated mod.rs, e.g.
+    /// `// Analyzed against server version 16.4 at 2024-05-01T12:30:00Z`
+    pub(crate) fn comment(&self) -> String {
+        let mut settings = Vec::new();
+        if let Some(enable_seqscan) = &self.enable_seqscan {
+            settings.push(format!("enable_seqscan = {}", enable_seqscan));
+        }
+        settings.push(format!("search_path = {}", self.search_path));
+        if let Some(schema_version) = &self.schema_version {
+            settings.push(format!("schema_version = {}", schema_version));
+        }
+        format!(
+            "{}{} at {}\n{}{}\n",
+            ANALYZED_COMMENT,
+            self.server_version,
+            self.analyzed_at,
+            SETTINGS_COMMENT,
+            settings.join(", ")
+        )
+    }
+
+    /// Read the environment back from the comments of generated code, e.g. the mod.rs of the
+    /// output directory, None for code generated without them
+    pub fn parse(code: &str) -> Option<Self> {
+        // The comments follow the header lines
+        let header: Vec<&str> = code
+            .lines()
+            .take_while(|line| line.starts_with("//"))
+            .collect();
+        let (server_version, analyzed_at) = header
+            .iter()
+            .find_map(|line| line.strip_prefix(ANALYZED_COMMENT))?
+            .rsplit_once(" at ")?;
+        let mut environment = Self {
+            server_version: server_version.to_string(),
+            analyzed_at: analyzed_at.to_string(),
+            ..Self::default()
+        };
+        let settings = header
+            .iter()
+            .find_map(|line| line.strip_prefix(SETTINGS_COMMENT))?;
+        // search_path lists schemas separated by ", ", so settings are split at known names
+        let mut rest = settings;
+        while let Some((name, value)) = rest.split_once(" = ") {
+            let end = [
+                ", enable_seqscan = ",
+                ", search_path = ",
+                ", schema_version = ",
+            ]
+            .iter()
+            .filter_map(|next| value.find(next))
+            .min()
+            .unwrap_or(value.len());
+            let setting = value[..end].to_string();
+            match name {
+                "enable_seqscan" => environment.enable_seqscan = Some(setting),
+                "search_path" => environment.search_path = setting,
+                "schema_version" => environment.schema_version = Some(setting),
+                _ => {}
+            }
+            rest = value[end..].trim_start_matches(", ");
+        }
+        Some(environment)
+    }
+
+    /// Read the environment of the code generated into an output directory, from its mod.rs or
+    /// the lib.rs of its umbrella crate with `module_crates`
+    pub fn load(output_dir: &Path) -> Option<Self> {
+        ["mod.rs", "src/lib.rs"].iter().find_map(|file| {
+            std::fs::read_to_string(output_dir.join(file))
+                .ok()
+                .and_then(|code| Self::parse(&code))
+        })
+    }
+}
@@ -0,0 +1,184 @@
+/// Snapshot of Phase 1 analysis results persisted next to the generated code
+/// Used as a fallback when analysis of a query exceeds its time limit
+use crate::query_definition::{NumericAs, QueryDefinition};
+use crate::query_definition_rt::{ExplainParams, PerformanceAnalysis, QueryDefinitionRuntime};
+use crate::types_extractor::{ConstraintInfo, QueryTypeInfo};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// File name of the analysis snapshot inside the output directory
+pub const SNAPSHOT_FILE_NAME: &str = "automodel.snapshot.json";
+
+/// Cached analysis of a single query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotEntry {
+    /// Module the query belongs to
+    pub module: String,
+    /// Query name
+    pub name: String,
+    /// Fingerprint of the query inputs that affect analysis (SQL, type mappings and analysis params)
+    /// A cached entry is only reused when the fingerprint still matches
+    pub fingerprint: String,
+    /// Cached type information
+    pub type_info: QueryTypeInfo,
+    /// Cached mutation detection result
+    pub is_mutation: bool,
+    /// Cached constraint information
+    pub constraints: Vec<ConstraintInfo>,
+    /// Cached performance analysis results
+    pub performance_analysis: Option<PerformanceAnalysis>,
+    /// Cached EXPLAIN query parameters for each variant
+    pub explain_params: Vec<Option<ExplainParams>>,
+}
+
+/// All cached query analyses from the last generation run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnalysisSnapshot {
+    /// Version of automodel that wrote the snapshot
+    /// Snapshots written by other versions are ignored
+    pub version: String,
+    /// Cached analysis for each query
+    pub entries: Vec<SnapshotEntry>,
+}
+
+impl AnalysisSnapshot {
+    /// Load the snapshot from the output directory
+    /// Returns an empty snapshot if the file is missing, unreadable or from another version
+    pub fn load(output_dir: &Path) -> Self {
+        let path = output_dir.join(SNAPSHOT_FILE_NAME);
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        match serde_json::from_str::<AnalysisSnapshot>(&content) {
+            Ok(snapshot) if snapshot.version == env!("CARGO_PKG_VERSION") => snapshot,
+            _ => Self::default(),
+        }
+    }
+
+    /// Write the snapshot to the output directory
+    pub fn save(&self, output_dir: &Path) -> Result<()> {
+        let path = output_dir.join(SNAPSHOT_FILE_NAME);
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Build a snapshot from freshly analyzed queries
+    pub fn from_analyzed(analyzed_queries: &[QueryDefinitionRuntime]) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            entries: analyzed_queries
+                .iter()
+                .map(|analyzed| SnapshotEntry {
+                    module: analyzed.definition.module.clone(),
+                    name: analyzed.definition.name.clone(),
+                    fingerprint: fingerprint(&analyzed.definition),
+                    type_info: analyzed.type_info.clone(),
+                    is_mutation: analyzed.is_mutation,
+                    constraints: analyzed.constraints.clone(),
+                    performance_analysis: analyzed.performance_analysis.clone(),
+                    explain_params: analyzed.explain_params.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Rebuild the analysis of a query from the snapshot
+    /// Returns None if the query is not cached or its SQL/type mappings/analysis params changed since
+    pub fn restore(&self, query: &QueryDefinition) -> Option<QueryDefinitionRuntime> {
+        let query_fingerprint = fingerprint(query);
+        self.entries
+            .iter()
+            .find(|entry| {
+                entry.module == query.module
+                    && entry.name == query.name
+                    && entry.fingerprint == query_fingerprint
+            })
+            .map(|entry| {
+                QueryDefinitionRuntime::new(
+                    query.clone(),
+                    entry.type_info.clone(),
+                    entry.is_mutation,
+                    entry.constraints.clone(),
+                    entry.performance_analysis.clone(),
+                    entry.explain_params.clone(),
+                )
+            })
+    }
+}
+
+/// Compute a stable fingerprint of the query inputs that affect analysis
+fn fingerprint(query: &QueryDefinition) -> String {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    hasher.update(query.sql.as_bytes());
+
+    // Sort type mappings for deterministic hashing
+    if let Some(types) = &query.types {
+        let mut mappings: Vec<_> = types.iter().collect();
+        mappings.sort();
+        for (field, rust_type) in mappings {
+            hasher.update(b"\0");
+            hasher.update(field.as_bytes());
+            hasher.update(b"\0");
+            hasher.update(rust_type.as_bytes());
+        }
+    }
+
+    // Analysis parameters change the query plan
+    let mut analysis_params: Vec<_> = query.analysis_params.iter().collect();
+    analysis_params.sort_by(|a, b| a.0.cmp(b.0));
+    for (param, value) in analysis_params {
+        hasher.update(b"\0");
+        hasher.update(param.as_bytes());
+        hasher.update(b"\0");
+        hasher.update(serde_yaml::to_string(value).unwrap_or_default().as_bytes());
+    }
+
+    // NUMERIC output types are resolved during analysis
+    match &query.numeric_as {
+        Some(NumericAs::Query(numeric_type)) => {
+            hasher.update(format!("\0numeric_as\0{:?}", numeric_type).as_bytes());
+        }
+        Some(NumericAs::Columns(columns)) => {
+            let mut columns: Vec<_> = columns.iter().collect();
+            columns.sort_by(|a, b| a.0.cmp(b.0));
+            for (column, numeric_type) in columns {
+                hasher.update(format!("\0numeric_as\0{}\0{:?}", column, numeric_type).as_bytes());
+            }
+        }
+        None => {}
+    }
+
+    // Currencies change parameter and column types
+    for (column, currency) in &query.currency_columns {
+        hasher.update(format!("\0currency_columns\0{}\0{}", column, currency).as_bytes());
+    }
+
+    // Flags change parameter and column types
+    for (column, flag) in &query.char_flags {
+        hasher.update(format!("\0char_flags\0{}\0{:?}", column, flag).as_bytes());
+    }
+
+    // CHECK constraint values change parameter and column types
+    for (column, style) in &query.check_values {
+        hasher.update(format!("\0check_values\0{}\0{:?}", column, style).as_bytes());
+    }
+
+    // The counting query of the DELETE is built during analysis
+    if query.cascade_preview {
+        hasher.update(b"\0cascade_preview");
+    }
+
+    // Sharded queries are analyzed against the canonical shard table
+    if let (Some(_), Some(shard)) = (&query.shard_by, &query.canonical_shard) {
+        hasher.update(format!("\0canonical_shard\0{}", shard).as_bytes());
+    }
+
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
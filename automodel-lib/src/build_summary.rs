@@ -0,0 +1,88 @@
+/// Timing breakdown of a code generation, to diagnose slow build scripts on large projects
+use crate::analysis_environment::AnalysisEnvironment;
+use anyhow::Result;
+use serde::{Serialize, Serializer};
+use std::path::Path;
+use std::time::Duration;
+
+/// File name of the build summary inside the `OUT_DIR` of build scripts
+pub const SUMMARY_FILE_NAME: &str = "automodel.summary.json";
+
+/// Time spent in each phase of the last code generation of a `GenerationContext`
+/// Durations are written as milliseconds to the summary file
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BuildSummary {
+    /// Hashing, loading and parsing the SQL files, and introspecting the history queries
+    #[serde(rename = "scan_ms", serialize_with = "serialize_ms")]
+    pub scan: Duration,
+    /// Analyzing all queries against the database, including connecting
+    #[serde(rename = "analysis_ms", serialize_with = "serialize_ms")]
+    pub analysis: Duration,
+    /// Generating and formatting the code of the modules and the shared items
+    #[serde(rename = "codegen_ms", serialize_with = "serialize_ms")]
+    pub codegen: Duration,
+    /// Writing the generated files, the analysis snapshot and the warnings
+    #[serde(rename = "write_ms", serialize_with = "serialize_ms")]
+    pub write: Duration,
+    /// Analysis time of every query, in the order of the SQL files
+    pub queries: Vec<QueryTiming>,
+    /// Server version, planner settings and time of the analysis, None when generation did not
+    /// connect to the database
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub environment: Option<AnalysisEnvironment>,
+}
+
+/// Analysis time of a single query
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryTiming {
+    /// Module the query belongs to
+    pub module: String,
+    /// Query name
+    pub name: String,
+    /// Time from the start of its analysis to its completion, including waiting for its
+    /// connection while other queries use it
+    #[serde(rename = "duration_ms", serialize_with = "serialize_ms")]
+    pub duration: Duration,
+    /// Whether the analysis was restored from the snapshot instead of run against the database
+    pub cached: bool,
+}
+
+impl BuildSummary {
+    /// Total time of all phases
+    pub fn total(&self) -> Duration {
+        self.scan + self.analysis + self.codegen + self.write
+    }
+
+    /// The `count` queries which took the longest to analyze, slowest first
+    pub fn slowest_queries(&self, count: usize) -> Vec<&QueryTiming> {
+        let mut queries: Vec<&QueryTiming> = self.queries.iter().collect();
+        queries.sort_by_key(|query| std::cmp::Reverse(query.duration));
+        queries.truncate(count);
+        queries
+    }
+
+    /// One line summary of the phases, e.g. "scan 12ms, analysis 3140ms, codegen 410ms, write 8ms"
+    pub(crate) fn phases_line(&self) -> String {
+        format!(
+            "scan {}ms, analysis {}ms, codegen {}ms, write {}ms",
+            self.scan.as_millis(),
+            self.analysis.as_millis(),
+            self.codegen.as_millis(),
+            self.write.as_millis()
+        )
+    }
+
+    /// Write the summary to the given directory
+    pub(crate) fn save(&self, dir: &Path) -> Result<()> {
+        std::fs::write(
+            dir.join(SUMMARY_FILE_NAME),
+            serde_json::to_string_pretty(self)?,
+        )?;
+        Ok(())
+    }
+}
+
+/// Serialize a duration as fractional milliseconds
+fn serialize_ms<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64() * 1000.0)
+}
@@ -0,0 +1,231 @@
+/// Triggers notifying the application when the tables of cached queries change, written as
+/// migrations from the same analysis as the `cache_invalidation` module listening to them
+use crate::query_definition_rt::QueryDefinitionRuntime;
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Channel notified by the triggers, with the changed table as payload
+pub const CACHE_INVALIDATION_CHANNEL: &str = "automodel_cache_invalidation";
+
+/// First line of the generated migration
+const MIGRATION_HEADER: &str =
+    "-- This file was automatically generated by AutoModel. Do not edit manually.";
+
+/// File name of the generated migrations after their version, e.g. "014_cache_invalidation.sql"
+const MIGRATION_FILE_SUFFIX: &str = "cache_invalidation.sql";
+
+/// Prefix of the line listing the tables with triggers once the migration is applied, read back
+/// to find the changes the next migration makes
+const TABLES_PREFIX: &str = "-- Tables: ";
+
+/// Name of the triggers and the trigger function
+const TRIGGER_NAME: &str = "automodel_cache_invalidation";
+
+/// Tables read by the queries with `cache_invalidation`, with the cached queries reading them
+/// as "module::query"
+/// Fails for mutations, whose results are not cached
+pub(crate) fn cached_tables(
+    analyzed_queries: &[QueryDefinitionRuntime],
+) -> Result<BTreeMap<String, Vec<String>>> {
+    let mut tables: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for analyzed in analyzed_queries {
+        let definition = &analyzed.definition;
+        if !definition.cache_invalidation {
+            continue;
+        }
+        if analyzed.is_mutation {
+            anyhow::bail!(
+                "Query '{}' sets cache_invalidation, which only applies to read-only queries",
+                definition.name
+            );
+        }
+        for table in &analyzed.type_info.tables {
+            tables
+                .entry(table.clone())
+                .or_default()
+                .push(format!("{}::{}", definition.module, definition.name));
+        }
+    }
+    Ok(tables)
+}
+
+/// Write a new migration to the directory when the tables of cached queries changed since the
+/// latest migration written there, creating the triggers of the added tables and dropping those
+/// of the removed ones; applied migrations are never rewritten
+/// tables: result of `cached_tables`
+pub(crate) fn write_migrations(dir: &Path, tables: &BTreeMap<String, Vec<String>>) -> Result<()> {
+    let mut latest_version = None;
+    let mut latest_migration = None;
+    if dir.exists() {
+        for entry in std::fs::read_dir(dir)? {
+            let file_name = entry?.file_name().to_string_lossy().to_string();
+            let Some((version, description)) = file_name.split_once('_') else {
+                continue;
+            };
+            let Ok(number) = version.parse::<i64>() else {
+                continue;
+            };
+            if latest_version
+                .as_ref()
+                .is_none_or(|(latest, _)| number > *latest)
+            {
+                latest_version = Some((number, version.len()));
+            }
+            if description == MIGRATION_FILE_SUFFIX
+                && latest_migration
+                    .as_ref()
+                    .is_none_or(|(latest, _)| number > *latest)
+            {
+                latest_migration = Some((number, file_name));
+            }
+        }
+    }
+
+    let previous_tables = match &latest_migration {
+        Some((_, file_name)) => migration_tables(&std::fs::read_to_string(dir.join(file_name))?),
+        None => Vec::new(),
+    };
+    let current_tables: Vec<&String> = tables.keys().collect();
+    if previous_tables.iter().eq(current_tables.iter().copied()) {
+        return Ok(());
+    }
+
+    // The version follows the latest migration of the directory, keeping the width of its number
+    let (version, width) = match latest_version {
+        Some((number, width)) => (number + 1, width),
+        None => (1, 3),
+    };
+    let path = dir.join(format!(
+        "{:0width$}_{}",
+        version,
+        MIGRATION_FILE_SUFFIX,
+        width = width
+    ));
+    let content = migration_sql(tables, &previous_tables, latest_migration.is_none());
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+/// Tables listed by the `TABLES_PREFIX` line of a migration, in the order of `cached_tables`
+fn migration_tables(migration: &str) -> Vec<String> {
+    migration
+        .lines()
+        .find_map(|line| line.strip_prefix(TABLES_PREFIX))
+        .map(|tables| {
+            tables
+                .split(", ")
+                .filter(|table| !table.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Statements creating the triggers of the tables missing from `previous_tables` and dropping
+/// the triggers of the tables no longer cached, with the trigger function in the first migration
+fn migration_sql(
+    tables: &BTreeMap<String, Vec<String>>,
+    previous_tables: &[String],
+    is_first: bool,
+) -> String {
+    let table_names: Vec<&str> = tables.keys().map(String::as_str).collect();
+    let mut sql = format!(
+        "{}\n-- Triggers notifying {} when the tables of queries with cache_invalidation change\n{}{}\n",
+        MIGRATION_HEADER,
+        CACHE_INVALIDATION_CHANNEL,
+        TABLES_PREFIX,
+        table_names.join(", ")
+    );
+    if is_first {
+        sql.push_str(&format!(
+            "\nCREATE OR REPLACE FUNCTION public.{}() RETURNS trigger\nLANGUAGE plpgsql AS $$\nBEGIN\n    PERFORM pg_notify('{}', TG_ARGV[0]);\n    RETURN NULL;\nEND;\n$$;\n",
+            TRIGGER_NAME, CACHE_INVALIDATION_CHANNEL
+        ));
+    }
+    for table in previous_tables {
+        if !tables.contains_key(table) {
+            sql.push_str(&format!(
+                "\n-- No longer read by queries with cache_invalidation\nDROP TRIGGER IF EXISTS {} ON {};\n",
+                TRIGGER_NAME,
+                qualified_table(table)
+            ));
+        }
+    }
+    for (table, queries) in tables {
+        if previous_tables.contains(table) {
+            continue;
+        }
+        let qualified = qualified_table(table);
+        sql.push_str(&format!(
+            "\n-- {}\nDROP TRIGGER IF EXISTS {} ON {};\nCREATE TRIGGER {}\n    AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON {}\n    FOR EACH STATEMENT EXECUTE FUNCTION public.{}('{}');\n",
+            queries.join(", "),
+            TRIGGER_NAME,
+            qualified,
+            TRIGGER_NAME,
+            qualified,
+            TRIGGER_NAME,
+            table.replace('\'', "''")
+        ));
+    }
+    sql
+}
+
+/// Table qualified for SQL, e.g. `public."users"`
+fn qualified_table(table: &str) -> String {
+    match table.split_once('.') {
+        Some((schema, name)) => format!("{}.{}", quote_ident(schema), quote_ident(name)),
+        None => format!("public.{}", quote_ident(table)),
+    }
+}
+
+/// Identifier quoted for SQL, e.g. `"users"`
+fn quote_ident(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tables(names: &[&str]) -> BTreeMap<String, Vec<String>> {
+        names
+            .iter()
+            .map(|name| (name.to_string(), vec![format!("{}::get", name)]))
+            .collect()
+    }
+
+    #[test]
+    fn changes_of_the_tables_are_written_as_new_migrations() {
+        let dir = std::env::temp_dir().join(format!(
+            "automodel_cache_invalidation_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("007_add_users.sql"), "CREATE TABLE users ();").unwrap();
+
+        write_migrations(&dir, &tables(&["posts", "users"])).unwrap();
+        write_migrations(&dir, &tables(&["posts", "users"])).unwrap();
+        let first = std::fs::read_to_string(dir.join("008_cache_invalidation.sql")).unwrap();
+        assert!(first.contains("CREATE OR REPLACE FUNCTION"));
+        assert!(first.contains("ON public.\"posts\""));
+        assert!(!dir.join("009_cache_invalidation.sql").exists());
+
+        write_migrations(&dir, &tables(&["comments", "users"])).unwrap();
+        let second = std::fs::read_to_string(dir.join("009_cache_invalidation.sql")).unwrap();
+        assert!(!second.contains("CREATE OR REPLACE FUNCTION"));
+        assert!(second.contains(
+            "DROP TRIGGER IF EXISTS automodel_cache_invalidation ON public.\"posts\";\n"
+        ));
+        assert!(second.contains("CREATE TRIGGER automodel_cache_invalidation\n    AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON public.\"comments\""));
+        assert!(!second.contains("ON public.\"users\""));
+        assert_eq!(
+            std::fs::read_to_string(dir.join("008_cache_invalidation.sql")).unwrap(),
+            first
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
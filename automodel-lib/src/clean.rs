@@ -0,0 +1,170 @@
+/// Artifacts of earlier generation runs which no current module or option produces anymore
+/// Generation only removes the Rust files of deleted modules, the other artifacts are found here
+use crate::analysis_snapshot::SNAPSHOT_FILE_NAME;
+use crate::codegen::{GENERATED_PART_HEADER, PROTO_HEADER, TYPESCRIPT_HEADER};
+use crate::module_crates::ModuleCratesConfig;
+use crate::query_registry::QUERY_REGISTRY_FILE_NAME;
+use crate::timeout_suggestions::TIMEOUT_SUGGESTIONS_FILE_NAME;
+use crate::DefaultsConfig;
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File name of the warnings of the last generation run inside the output directory
+pub const WARN_FILE_NAME: &str = "automodel.warn";
+
+/// Files and directories of the output directory which the current modules and options do not
+/// produce:
+/// - generated files of modules which no longer exist, and their part directories or crates
+/// - TypeScript and proto files of modules which no longer exist
+/// - the analysis snapshot when analysis is neither time-boxed nor limited to some queries
+/// - the timeout suggestions when they are not written to a file
+/// - the warnings, reports and query registry of an output directory without generated code
+pub(crate) fn stale_artifacts(
+    output_path: &Path,
+    modules: &[String],
+    defaults: &DefaultsConfig,
+) -> Result<Vec<PathBuf>> {
+    let mut stale = Vec::new();
+    let has_generated_code = has_generated_code(output_path, defaults);
+    let uses_snapshot = defaults.query_analysis_timeout.is_some()
+        || defaults.analysis_budget.is_some()
+        || !defaults.only.is_empty();
+    let writes_timeouts = defaults
+        .suggest_timeouts
+        .as_ref()
+        .is_some_and(|suggest_timeouts| suggest_timeouts.write_file);
+    for (file_name, is_produced) in [
+        (WARN_FILE_NAME, has_generated_code),
+        (QUERY_REGISTRY_FILE_NAME, has_generated_code),
+        (SNAPSHOT_FILE_NAME, has_generated_code && uses_snapshot),
+        (
+            TIMEOUT_SUGGESTIONS_FILE_NAME,
+            has_generated_code && writes_timeouts,
+        ),
+    ] {
+        let path = output_path.join(file_name);
+        if !is_produced && path.is_file() {
+            stale.push(path);
+        }
+    }
+
+    match &defaults.module_crates {
+        Some(_) => stale.extend(ModuleCratesConfig::stale_module_crates(
+            output_path,
+            modules,
+        )?),
+        None => stale.extend(stale_module_files(output_path, modules)?),
+    }
+
+    for (dir, extension, header) in [
+        (&defaults.emit_ts, "ts", TYPESCRIPT_HEADER),
+        (&defaults.emit_proto, "proto", PROTO_HEADER),
+    ] {
+        if let Some(dir) = dir {
+            stale.extend(
+                generated_files(Path::new(dir), extension, header)?
+                    .into_iter()
+                    .filter(|path| !is_module_file(path, modules)),
+            );
+        }
+    }
+    Ok(stale)
+}
+
+/// The output directory itself, which must hold generated code or nothing
+pub(crate) fn output_dir_artifacts(
+    output_path: &Path,
+    defaults: &DefaultsConfig,
+) -> Result<Vec<PathBuf>> {
+    if !output_path.exists() {
+        return Ok(Vec::new());
+    }
+    let is_empty = fs::read_dir(output_path)?.next().is_none();
+    if !is_empty && !has_generated_code(output_path, defaults) {
+        anyhow::bail!(
+            "Output directory {} does not hold generated code, refusing to remove it",
+            output_path.display()
+        );
+    }
+    Ok(vec![output_path.to_path_buf()])
+}
+
+/// Remove the files and directories found by `stale_artifacts` or `output_dir_artifacts`
+pub(crate) fn remove_artifacts(paths: &[PathBuf]) -> Result<()> {
+    for path in paths {
+        if path.is_dir() {
+            fs::remove_dir_all(path)?;
+        } else {
+            fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether the output directory holds the mod.rs or umbrella crate written by generation
+fn has_generated_code(output_path: &Path, defaults: &DefaultsConfig) -> bool {
+    let mod_file = match &defaults.module_crates {
+        Some(_) => ModuleCratesConfig::lib_file(output_path),
+        None => output_path.join("mod.rs"),
+    };
+    fs::read_to_string(mod_file).is_ok_and(|content| content.starts_with("// AUTOMODEL_HASH: "))
+}
+
+/// Generated `{module}.rs` files and `{module}/` part directories of modules which no longer
+/// exist, directories with files which were not generated only have their generated files listed
+fn stale_module_files(output_path: &Path, modules: &[String]) -> Result<Vec<PathBuf>> {
+    let mut stale = Vec::new();
+    let Ok(entries) = fs::read_dir(output_path) else {
+        return Ok(stale);
+    };
+    for entry in entries {
+        let path = entry?.path();
+        if is_module_file(&path, modules) || path.file_name().is_some_and(|name| name == "mod.rs") {
+            continue;
+        }
+        if path.is_dir() {
+            let parts = generated_files(&path, "rs", GENERATED_PART_HEADER)?;
+            let entries = fs::read_dir(&path)?.count();
+            if !parts.is_empty() && parts.len() == entries {
+                stale.push(path);
+            } else {
+                stale.extend(parts);
+            }
+        } else if is_generated(&path, "rs", GENERATED_PART_HEADER) {
+            stale.push(path);
+        }
+    }
+    stale.sort();
+    Ok(stale)
+}
+
+/// Files of a directory with the extension starting with the header
+fn generated_files(dir: &Path, extension: &str, header: &str) -> Result<Vec<PathBuf>> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(Vec::new());
+    };
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if is_generated(&path, extension, header) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Whether the path is a file with the extension starting with the header
+fn is_generated(path: &Path, extension: &str, header: &str) -> bool {
+    path.is_file()
+        && path.extension().is_some_and(|ext| ext == extension)
+        && fs::read_to_string(path).is_ok_and(|content| content.starts_with(header))
+}
+
+/// Whether the file or directory is named after one of the modules
+fn is_module_file(path: &Path, modules: &[String]) -> bool {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .is_some_and(|stem| modules.iter().any(|module| module == stem))
+}
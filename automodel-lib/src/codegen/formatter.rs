@@ -0,0 +1,63 @@
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// rustfmt binary, the `RUSTFMT` environment variable if set, otherwise `rustfmt` from PATH
+fn rustfmt() -> String {
+    std::env::var("RUSTFMT").unwrap_or_else(|_| "rustfmt".to_string())
+}
+
+/// Whether rustfmt can be run, checked once per generation so that a missing rustfmt is reported
+/// once instead of failing every file
+pub fn rustfmt_available() -> bool {
+    Command::new(rustfmt())
+        .arg("--version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Format generated code with rustfmt so regenerations produce stable, minimal diffs
+/// Fails if rustfmt is not available or rejects the code, see `rustfmt_available`
+pub fn format_generated_code(code: &str) -> Result<String> {
+    let rustfmt = rustfmt();
+    let mut child = Command::new(&rustfmt)
+        .args(["--edition", "2021", "--emit", "stdout"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| {
+            format!(
+                "Failed to run '{}', rustfmt is required to format the generated code \
+                 (install it with `rustup component add rustfmt` or set RUSTFMT)",
+                rustfmt
+            )
+        })?;
+
+    // Write from a separate thread so a full stdout pipe cannot block rustfmt
+    let mut stdin = child
+        .stdin
+        .take()
+        .context("Failed to open the standard input of rustfmt")?;
+    let input = code.to_string();
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+    let output = child
+        .wait_with_output()
+        .with_context(|| format!("Failed to wait for '{}'", rustfmt))?;
+    writer
+        .join()
+        .map_err(|_| anyhow::anyhow!("Failed to write the generated code to rustfmt"))?
+        .context("Failed to write the generated code to rustfmt")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "'{}' failed to format the generated code ({}): {}",
+            rustfmt,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    String::from_utf8(output.stdout).context("rustfmt returned code that is not valid UTF-8")
+}
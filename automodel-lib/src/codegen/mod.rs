@@ -1,4 +1,10 @@
+mod formatter;
 mod module_generator;
+mod proto_generator;
 mod types_generator;
+mod typescript_generator;
 
+pub use formatter::*;
 pub use module_generator::*;
+pub use proto_generator::*;
+pub use typescript_generator::*;
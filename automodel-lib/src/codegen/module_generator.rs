@@ -1,29 +1,51 @@
+use crate::analysis_environment::AnalysisEnvironment;
+use crate::cache_invalidation::CACHE_INVALIDATION_CHANNEL;
 use crate::codegen::types_generator::{
-    generate_conditional_diff_params, generate_conditional_diff_struct, generate_enum_definition,
-    generate_input_params_with_names, generate_multiunzip_input_struct, generate_multiunzip_param,
-    generate_result_struct_with_name, generate_return_type, generate_structured_params_signature,
-    generate_structured_params_struct,
+    generate_conditional_diff_params, generate_conditional_diff_struct, generate_dynamic_map_impl,
+    generate_enum_definition, generate_filter_enum, generate_input_params_with_names,
+    generate_multiunzip_input_struct, generate_multiunzip_param, generate_result_struct_with_name,
+    generate_return_type, generate_structured_params_signature, generate_structured_params_struct,
+    DynamicFieldKind,
+};
+use crate::diagnostics::{Diagnostic, DiagnosticCode};
+use crate::query_definition::{
+    search_path_list, CacheInvalidateOn, Dialect, ExpectedResult, LargeListStrategy, ModuleConfig,
+    QueryDefinition, QueryScope, ReturnShape, SqlxVersion, TelemetryLevel,
 };
-use crate::query_definition::{ExpectedResult, QueryDefinition, TelemetryLevel};
 use crate::query_definition_rt::QueryDefinitionRuntime;
 use crate::types_extractor::{
-    extract_enum_types, parse_parameter_names_from_sql, OutputColumn, QueryTypeInfo,
+    aggregate_output_columns, convert_named_params_to_positional, filter_query_parts,
+    large_list_params, large_list_table_name, parse_parameter_names_from_sql,
+    rewrite_large_list_params, single_values_row, sorted_enum_types, BulkUpdateQuery,
+    CascadePreview, CharFlagColumn, FilterQueryParts, IdOnlyQuery, OutputColumn, QueryTypeInfo,
+    RetryLookup, RustType, MAX_SHARD_LEN, SHARD_PLACEHOLDER,
 };
-use crate::utils::{to_pascal_case, to_snake_case};
+use crate::utils::{parse_message_template, to_pascal_case, to_rust_ident, to_snake_case};
 use anyhow::Result;
 
-pub fn generate_root_module(modules: &Vec<String>, source_hash: u64) -> String {
+pub fn generate_root_module(
+    modules: &Vec<String>,
+    module_scopes: &std::collections::HashMap<String, QueryScope>,
+    source_hash: u64,
+    environment: &AnalysisEnvironment,
+    shared_items: &str,
+) -> String {
     let mut mod_content = String::new();
 
     // Add hash comment at the top for consistency with build-time generation
     mod_content.push_str(&format!("// AUTOMODEL_HASH: {}\n", source_hash));
-    mod_content.push_str(
-        "// This file was automatically generated by AutoModel. Do not edit manually.\n\n",
-    );
+    mod_content
+        .push_str("// This file was automatically generated by AutoModel. Do not edit manually.\n");
+    mod_content.push_str(&environment.comment());
+    mod_content.push('\n');
 
     let mut mod_declarations = Vec::new();
     for module in modules {
-        mod_declarations.push(format!("pub mod {};", module));
+        // Modules of test or bench queries are only compiled into those builds
+        match module_scopes.get(module).and_then(QueryScope::cfg) {
+            Some(cfg) => mod_declarations.push(format!("#[cfg({})]\npub mod {};", cfg, module)),
+            None => mod_declarations.push(format!("pub mod {};", module)),
+        }
     }
 
     // Add module declarations first
@@ -35,1385 +57,7370 @@ pub fn generate_root_module(modules: &Vec<String>, source_hash: u64) -> String {
         mod_content.push('\n');
     }
 
-    // Add generic Error type
-    mod_content.push_str(&generate_generic_error_type());
-
+    mod_content.push_str(shared_items);
     mod_content
 }
 
-/// Generate the generic Error<C> type for mod.rs
-pub fn generate_generic_error_type() -> String {
-    r#"#[derive(Debug, Clone)]
-pub struct ErrorConstraintInfo {
-    /// Name of the violated constraint
-    pub constraint_name: String,
-    pub table_name: String,
-    #[allow(unused)]
-    pub kind: ErrorConstraintKind,
+/// Shared items needed by at least one generated function, besides the error types
+#[derive(Debug, Clone, Default)]
+pub struct SharedItems {
+    /// Runtime plan sampling, for `plan_sampling`
+    pub plan_sampling: bool,
+    /// Temp table helpers, for `large_list_strategy: temp_table`
+    pub large_lists: bool,
+    /// Shard key validation, for `shard_by`
+    pub sharding: bool,
+    /// Batching task, for `batch`
+    pub batching: bool,
+    /// tsvector and tsquery wrappers
+    pub full_text_search: bool,
+    /// Runtime of the blocking wrappers, for `blocking`
+    pub blocking: bool,
+    /// Server-side cancellation of the cancellable variants, for `cancellable`
+    pub cancellation: bool,
+    /// Session type, for `requires_session`
+    pub sessions: bool,
+    /// CascadeImpact type, for `cascade_preview`
+    pub cascade_previews: bool,
+    /// Cardinality drift reporting, for `cardinality_drift`
+    pub cardinality_drift: bool,
+    /// Listener of the cache invalidation triggers, for `cache_invalidation`
+    pub cache_invalidation: bool,
+    /// Channels clearing the caches of the `*_cached` functions, for `cache_invalidate_on`
+    pub query_cache_channels: Vec<String>,
+    /// Feature flag provider, for `feature_flag`
+    pub feature_flags: bool,
+    /// Checked Decimal conversions, for `numeric_as: i64`
+    pub numeric_conversions: bool,
+    /// Currency amount type, for `currency_columns`
+    pub money: bool,
+    /// Flag code conversions, for `char_flags`
+    pub char_flags: bool,
+    /// Array element conversions, for `name[]` keys of `types`
+    pub array_elements: bool,
+    /// Parameter formatting, for telemetry `max_param_length` and `max_param_elements`
+    pub param_limits: bool,
+    /// Database whose error codes `classify_error` knows
+    pub dialect: Dialect,
+    /// Major version of sqlx the shared items compile against
+    pub sqlx_version: SqlxVersion,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum ErrorConstraintKind {
-    UniqueViolation,
-    ForeignKeyViolation,
-    NotNullViolation,
-    CheckViolation,
-    Other,
-}
+/// Generate the items shared by all modules, which they refer to as `super::...`
+pub fn generate_shared_items(items: SharedItems) -> String {
+    let SharedItems {
+        plan_sampling,
+        large_lists,
+        sharding,
+        batching,
+        full_text_search,
+        blocking,
+        cancellation,
+        sessions,
+        cascade_previews,
+        cardinality_drift,
+        cache_invalidation,
+        query_cache_channels,
+        feature_flags,
+        numeric_conversions,
+        money,
+        char_flags,
+        array_elements,
+        param_limits,
+        dialect,
+        sqlx_version,
+    } = items;
+    let mut mod_content = String::new();
 
-impl From<sqlx::error::ErrorKind> for ErrorConstraintKind {
-    fn from(kind: sqlx::error::ErrorKind) -> Self {
-        match kind {
-            sqlx::error::ErrorKind::UniqueViolation => Self::UniqueViolation,
-            sqlx::error::ErrorKind::ForeignKeyViolation => Self::ForeignKeyViolation,
-            sqlx::error::ErrorKind::NotNullViolation => Self::NotNullViolation,
-            sqlx::error::ErrorKind::CheckViolation => Self::CheckViolation,
-            _ => Self::Other,
-        }
+    // Add generic Error type
+    mod_content.push_str(&generate_generic_error_type(dialect, sqlx_version));
+
+    // Add the report of the `warmup` functions of the modules
+    mod_content.push('\n');
+    mod_content.push_str(&generate_warmup_module());
+
+    // Add the sqlx version shims unless the code only targets the current sqlx
+    if sqlx_version.needs_compat() {
+        mod_content.push('\n');
+        mod_content.push_str(&generate_sqlx_compat_module(sqlx_version));
     }
-}
 
-/// Generic error type
-#[derive(Debug)]
-pub enum Error<C: TryFrom<ErrorConstraintInfo>> {
-    /// Catches the cases when a mutation query violates a constraint
-    /// Type C would be an enum specific to each query.
-    /// It would enumerate variants in pascal case for each constraint that can be violated.
-    /// The list of constaints is inferred automatically by the automodel based on the table schema
-    /// involved in the query.
-    /// The Option<C> is None when the constraint name is not recognized (unknown constraint).
-    ConstraintViolation(Option<C>, ErrorConstraintInfo),
+    // Add the Session type if any function uses requires_session
+    if sessions {
+        mod_content.push('\n');
+        mod_content.push_str(&generate_session_type());
+    }
 
-    /// Row not found error
-    RowNotFound,
-    
-    /// System under stress, timeout
-    PoolTimeout,
+    // Add the CascadeImpact type if any DELETE uses cascade_preview
+    if cascade_previews {
+        mod_content.push('\n');
+        mod_content.push_str(&generate_cascade_impact_type());
+    }
 
-    InternalError(String, sqlx::Error),
-}
+    // Add the feature flag provider if any function uses feature_flag
+    if feature_flags {
+        mod_content.push('\n');
+        mod_content.push_str(&generate_feature_flags_module());
+    }
 
-impl<C: TryFrom<ErrorConstraintInfo>> From<sqlx::Error> for Error<C> {
-    fn from(error: sqlx::Error) -> Self {
-        match &error {
-            sqlx::Error::RowNotFound => Self::RowNotFound,
-            sqlx::Error::ColumnNotFound(col) => {
-                Self::InternalError(format!("Column not found: {}", col), error)
-            }
-            sqlx::Error::Database(db_err) => {
-                // Extract constraint name and table from error
-                let constraint_name = db_err.constraint().unwrap_or("").to_string();
-                let table_name = db_err.table().unwrap_or("").to_string();
-                let kind = db_err.kind();
+    // Add runtime plan sampling support if any function uses it
+    if plan_sampling {
+        mod_content.push('\n');
+        mod_content.push_str(&generate_plan_sampling_module());
+    }
 
-                let violation = ErrorConstraintInfo {
-                    constraint_name,
-                    table_name,
-                    kind: kind.into(),
-                };
-                Self::ConstraintViolation(violation.clone().try_into().ok(), violation)
-            }
-            sqlx::Error::Configuration(_) => {
-                Self::InternalError("Configuration error".to_string(), error)
-            }
-            sqlx::Error::InvalidArgument(_) => {
-                Self::InternalError("Invalid argument".to_string(), error)
-            }
-            sqlx::Error::Io(_) => Self::InternalError("IO error".to_string(), error),
-            sqlx::Error::Tls(_) => Self::InternalError("TLS error".to_string(), error),
-            sqlx::Error::Protocol(_) => Self::InternalError("Protocol error".to_string(), error),
-            sqlx::Error::TypeNotFound { type_name } => {
-                Self::InternalError(format!("Type not found: {}", type_name), error)
-            }
-            sqlx::Error::ColumnIndexOutOfBounds { index, len } => Self::InternalError(
-                format!("Column index out of bounds: index {}, len {}", index, len),
-                error,
-            ),
-            sqlx::Error::ColumnDecode { index, source } => Self::InternalError(
-                format!("Column decode error at index {}: {}", index, source),
-                error,
-            ),
-            sqlx::Error::Encode(_) => Self::InternalError("Encode error".to_string(), error),
-            sqlx::Error::Decode(_) => Self::InternalError("Decode error".to_string(), error),
-            sqlx::Error::AnyDriverError(_) => {
-                Self::InternalError("Driver error".to_string(), error)
-            }
-            sqlx::Error::PoolTimedOut => Self::PoolTimeout,
-            sqlx::Error::PoolClosed => Self::InternalError("Pool closed".to_string(), error),
-            sqlx::Error::WorkerCrashed => Self::InternalError("Worker crashed".to_string(), error),
-            sqlx::Error::Migrate(_) => Self::InternalError("Migration error".to_string(), error),
-            sqlx::Error::InvalidSavePointStatement => Self::InternalError(
-                "Invalid save point statement".to_string(),
-                error,
-            ),
-            sqlx::Error::BeginFailed => Self::InternalError("Begin failed".to_string(), error),
-            _ => Self::InternalError("Unknown sqlx error".to_string(), error),
-        }
+    // Add cardinality drift reporting if any function uses it
+    if cardinality_drift {
+        mod_content.push('\n');
+        mod_content.push_str(&generate_cardinality_drift_module());
+    }
+
+    // Add the listener of the cache invalidation triggers and the query caches if any query is
+    // cached
+    if cache_invalidation || !query_cache_channels.is_empty() {
+        mod_content.push('\n');
+        mod_content.push_str(&generate_cache_invalidation_module(&query_cache_channels));
+    }
+
+    // Add temp table helpers if any function uses large_list_strategy: temp_table
+    if large_lists {
+        mod_content.push('\n');
+        mod_content.push_str(&generate_large_list_module());
+    }
+
+    // Add shard key validation if any function uses shard_by
+    if sharding {
+        mod_content.push('\n');
+        mod_content.push_str(&generate_sharding_module(sqlx_version));
+    }
+
+    // Add the batching task if any function uses batch
+    if batching {
+        mod_content.push('\n');
+        mod_content.push_str(&generate_batching_module());
+    }
+
+    // Add the tsvector and tsquery wrappers if any function uses them
+    if full_text_search {
+        mod_content.push('\n');
+        mod_content.push_str(&generate_full_text_search_module(sqlx_version));
+    }
+
+    // Add the runtime of the blocking wrappers if any function uses blocking
+    if blocking {
+        mod_content.push('\n');
+        mod_content.push_str(&generate_blocking_module());
+    }
+
+    // Add the server-side cancellation if any function is cancellable
+    if cancellation {
+        mod_content.push('\n');
+        mod_content.push_str(&generate_cancellation_module(dialect));
+    }
+
+    // Add the checked Decimal conversions if any column uses numeric_as: i64
+    if numeric_conversions {
+        mod_content.push('\n');
+        mod_content.push_str(&generate_numeric_module());
+    }
+
+    // Add the currency amount type if any column or parameter uses currency_columns
+    if money {
+        mod_content.push('\n');
+        mod_content.push_str(&generate_money_module(sqlx_version));
+    }
+
+    // Add the flag code conversions if any column or parameter uses char_flags
+    if char_flags {
+        mod_content.push('\n');
+        mod_content.push_str(&generate_char_flags_module());
+    }
+
+    // Add the element conversions if any column or parameter maps its array elements
+    if array_elements {
+        mod_content.push('\n');
+        mod_content.push_str(&generate_array_elements_module());
+    }
+
+    // Add the parameter formatting if any function records parameters cut to telemetry limits
+    if param_limits {
+        mod_content.push('\n');
+        mod_content.push_str(&generate_telemetry_module());
     }
+
+    mod_content
 }
 
-impl<C> std::fmt::Display for Error<C>
-where
-    C: std::fmt::Debug + TryFrom<ErrorConstraintInfo>,
-{
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Error::ConstraintViolation(constraint, info) => {
-                if let Some(c) = constraint {
-                    write!(f, "Constraint violation: {:#?}", c)
-                } else {
-                    write!(f, "Unknown constraint violation: {} on table {}", info.constraint_name, info.table_name)
+/// Generate the constant recording the schema version the code was generated against
+pub fn generate_schema_version_const(schema_version: &str) -> String {
+    format!(
+        "/// Schema version of the database the code was generated against\npub const SCHEMA_VERSION: &str = {:?};\n",
+        schema_version
+    )
+}
+
+/// Generate the telemetry module for mod.rs
+/// Formats the parameters recorded in spans and slow query events with telemetry
+/// `max_param_length` or `max_param_elements`
+fn generate_telemetry_module() -> String {
+    r#"/// Formatting of the parameters recorded in spans and slow query events, cut to the telemetry
+/// `max_param_length` and `max_param_elements` of the query
+pub mod telemetry {
+    use std::fmt::Write;
+
+    /// Writer keeping the first `remaining` characters of the formatted value
+    struct Truncated {
+        text: String,
+        remaining: usize,
+        truncated: bool,
+    }
+
+    impl Write for Truncated {
+        fn write_str(&mut self, s: &str) -> std::fmt::Result {
+            for ch in s.chars() {
+                if self.remaining == 0 {
+                    self.truncated = true;
+                    // Stops formatting the rest of the value
+                    return Err(std::fmt::Error);
                 }
+                self.text.push(ch);
+                self.remaining -= 1;
             }
-            Error::RowNotFound => write!(f, "Row not found"),
-            Error::PoolTimeout => write!(f, "Pool timeout"),
-            Error::InternalError(msg, err) => {
-                write!(f, "Internal error: {}, caused by: {}", msg, err)
-            }
+            Ok(())
         }
     }
-}
 
-impl<C> std::error::Error for Error<C>
-where
-    C: std::fmt::Debug + TryFrom<ErrorConstraintInfo>,
-{
-    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        match self {
-            Error::InternalError(_, err) => Some(err),
-            _ => None,
+    /// Debug representation of a parameter, cut after `max_length` characters followed by "..."
+    pub fn param<T: std::fmt::Debug + ?Sized>(value: &T, max_length: Option<usize>) -> String {
+        let mut writer = Truncated {
+            text: String::new(),
+            remaining: max_length.unwrap_or(usize::MAX),
+            truncated: false,
+        };
+        let _ = write!(writer, "{:?}", value);
+        if writer.truncated {
+            writer.text.push_str("...");
+        }
+        writer.text
+    }
+
+    /// Debug representation of the first `max_elements` elements of a list parameter, followed
+    /// by the number of omitted elements, cut after `max_length` characters
+    pub fn list_param<T: std::fmt::Debug>(
+        values: &[T],
+        max_elements: Option<usize>,
+        max_length: Option<usize>,
+    ) -> String {
+        match max_elements {
+            Some(max_elements) if values.len() > max_elements => param(
+                &format_args!(
+                    "{:?} and {} more",
+                    &values[..max_elements],
+                    values.len() - max_elements
+                ),
+                max_length,
+            ),
+            _ => param(values, max_length),
         }
     }
 }
+"#
+    .to_string()
+}
 
-/// Generic error type for read-only queries
-#[derive(Debug)]
-pub enum ErrorReadOnly {
-    /// Row not found error
-    RowNotFound,
+/// Generate the plan_sampling module for mod.rs
+/// Holds the global sampling knob shared by all generated functions with plan sampling
+pub fn generate_plan_sampling_module() -> String {
+    r#"/// Runtime sampling of query plans with EXPLAIN ANALYZE
+/// Disabled until `enable()` is called
+pub mod plan_sampling {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, RwLock};
+
+    /// Query plan captured for a sampled call
+    #[derive(Debug, Clone)]
+    pub struct PlanSample {
+        /// Generated function which was sampled, e.g. "users::get_user_by_id"
+        pub query: &'static str,
+        /// Output of EXPLAIN (ANALYZE, FORMAT JSON) for the call's SQL and parameters
+        pub plan: serde_json::Value,
+    }
 
-    /// System under stress, timeout
-    PoolTimeout,
+    /// Callback receiving sampled query plans
+    pub type PlanObserver = Arc<dyn Fn(PlanSample) + Send + Sync>;
 
-    InternalError(String, sqlx::Error),
-}
+    struct Sampler {
+        one_in: u64,
+        shadow: sqlx::PgPool,
+        observer: PlanObserver,
+    }
 
-impl From<sqlx::Error> for ErrorReadOnly {
-    fn from(error: sqlx::Error) -> Self {
-        Error::<ErrorConstraintInfo>::from(error).into()
+    static SAMPLER: RwLock<Option<Sampler>> = RwLock::new(None);
+    static CALLS: AtomicU64 = AtomicU64::new(0);
+
+    /// Sample 1 in `one_in` calls of generated read-only functions
+    /// Sampled calls run EXPLAIN ANALYZE on the `shadow` pool before the query itself
+    /// and pass the plan to `observer`; sampling failures are ignored
+    pub fn enable(shadow: sqlx::PgPool, one_in: u64, observer: impl Fn(PlanSample) + Send + Sync + 'static) {
+        if let Ok(mut sampler) = SAMPLER.write() {
+            *sampler = Some(Sampler { one_in: one_in.max(1), shadow, observer: Arc::new(observer) });
+        }
     }
-}
 
-impl Into<Error<ErrorConstraintInfo>> for ErrorReadOnly {
-    fn into(self) -> Error<ErrorConstraintInfo> {
-        match self {
-            ErrorReadOnly::RowNotFound => Error::RowNotFound,
-            ErrorReadOnly::PoolTimeout => Error::PoolTimeout,
-            ErrorReadOnly::InternalError(msg, err) => Error::InternalError(msg, err),
+    /// Stop sampling query plans
+    pub fn disable() {
+        if let Ok(mut sampler) = SAMPLER.write() {
+            *sampler = None;
         }
     }
-}
 
-impl From<Error<ErrorConstraintInfo>> for ErrorReadOnly {
-    fn from(error: Error<ErrorConstraintInfo>) -> Self {
-        match error {
-            Error::RowNotFound => Self::RowNotFound,
-            Error::PoolTimeout => Self::PoolTimeout,
-            Error::InternalError(msg, err) => Self::InternalError(msg, err),
-            Error::ConstraintViolation(c, info) => Self::InternalError(
-                "Constraint violation in read-only query".to_string(),
-                sqlx::Error::Protocol(format!(
-                    "Constraint violation in read-only query: constraint={}, table={}, parsed={:?}",
-                    info.constraint_name, info.table_name, c
-                )),
-            ),
+    /// Decide whether the current call is sampled
+    /// Returns the shadow pool and observer for sampled calls
+    pub(crate) fn sample() -> Option<(sqlx::PgPool, PlanObserver)> {
+        let sampler = SAMPLER.read().ok()?;
+        let sampler = sampler.as_ref()?;
+        if !CALLS.fetch_add(1, Ordering::Relaxed).is_multiple_of(sampler.one_in) {
+            return None;
         }
+        Some((sampler.shadow.clone(), sampler.observer.clone()))
     }
 }
+"#
+    .to_string()
+}
 
-impl std::fmt::Display for ErrorReadOnly {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ErrorReadOnly::RowNotFound => write!(f, "Row not found"),
-            ErrorReadOnly::PoolTimeout => write!(f, "Pool timeout"),
-            ErrorReadOnly::InternalError(msg, err) => {
-                write!(f, "Internal error: {}, caused by: {}", msg, err)
-            }
+/// Generate the feature_flags module for mod.rs
+/// Holds the provider deciding whether the functions of `feature_flag` queries run
+fn generate_feature_flags_module() -> String {
+    r#"/// Runtime state of the feature flags checked by generated functions
+/// Every flag is off until a provider switching it on is set with `set_provider()`
+pub mod feature_flags {
+    use std::sync::{Arc, RwLock};
+
+    /// Source of the state of feature flags, e.g. a configuration file or a flag service client
+    pub trait FeatureFlagProvider: Send + Sync {
+        /// Whether the flag is switched on
+        fn is_enabled(&self, flag: &str) -> bool;
+    }
+
+    impl<F: Fn(&str) -> bool + Send + Sync> FeatureFlagProvider for F {
+        fn is_enabled(&self, flag: &str) -> bool {
+            self(flag)
         }
     }
-}
 
-impl std::error::Error for ErrorReadOnly {
-    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        match self {
-            Self::InternalError(_, err) => Some(err),
-            _ => None,
+    static PROVIDER: RwLock<Option<Arc<dyn FeatureFlagProvider>>> = RwLock::new(None);
+
+    /// Decide the feature flags of generated functions with `provider`
+    pub fn set_provider(provider: impl FeatureFlagProvider + 'static) {
+        if let Ok(mut current) = PROVIDER.write() {
+            *current = Some(Arc::new(provider));
+        }
+    }
+
+    /// Remove the provider, switching every flag off
+    pub fn clear_provider() {
+        if let Ok(mut current) = PROVIDER.write() {
+            *current = None;
         }
     }
-}
 
+    /// Whether the flag is switched on by the current provider
+    pub fn is_enabled(flag: &str) -> bool {
+        let provider = PROVIDER.read().ok().and_then(|current| current.clone());
+        provider.is_some_and(|provider| provider.is_enabled(flag))
+    }
+}
 "#
     .to_string()
 }
 
-/// Generate per-query constraint enum with TryFrom<ErrorConstraintInfo> implementation
-pub fn generate_query_constraint_enum(
-    enum_name: &str,
-    constraints: &[crate::types_extractor::ConstraintInfo],
-    custom_derives: &[String],
-) -> String {
-    use std::collections::HashSet;
+/// Generate the cardinality_drift module for mod.rs
+/// Holds the observer receiving the calls of `cardinality_drift` functions whose result size
+/// diverged from the planner estimate
+fn generate_cardinality_drift_module() -> String {
+    r#"/// Reporting of calls returning an order of magnitude more or fewer rows than the planner
+/// estimated when the code was generated, a sign that statistics or indexes need attention
+/// Disabled until `enable()` is called
+pub mod cardinality_drift {
+    use std::sync::{Arc, RwLock};
+
+    /// Call whose result size diverged from the planner estimate
+    #[derive(Debug, Clone)]
+    pub struct CardinalityDrift {
+        /// Generated function which was called, e.g. "users::get_all_users"
+        pub query: &'static str,
+        /// Rows estimated by the planner at generation time, rounded to their order of magnitude
+        pub estimated_rows: f64,
+        /// Rows returned by the call
+        pub actual_rows: usize,
+    }
 
-    let mut code = String::new();
+    /// Callback receiving cardinality drift reports
+    pub type DriftObserver = Arc<dyn Fn(CardinalityDrift) + Send + Sync>;
 
-    // Deduplicate constraints by name
-    let mut seen_constraints: HashSet<String> = HashSet::new();
-    let mut unique_constraints = Vec::new();
-    for constraint in constraints {
-        if !seen_constraints.contains(&constraint.name) {
-            seen_constraints.insert(constraint.name.clone());
-            unique_constraints.push(constraint);
+    static OBSERVER: RwLock<Option<DriftObserver>> = RwLock::new(None);
+
+    /// Pass calls returning at least 10 times more or fewer rows than estimated to `observer`
+    pub fn enable(observer: impl Fn(CardinalityDrift) + Send + Sync + 'static) {
+        if let Ok(mut current) = OBSERVER.write() {
+            *current = Some(Arc::new(observer));
         }
     }
 
-    code.push_str(&format!(
-        "/// Constraint violations specific to this query\n"
-    ));
+    /// Stop reporting cardinality drift
+    pub fn disable() {
+        if let Ok(mut current) = OBSERVER.write() {
+            *current = None;
+        }
+    }
 
-    // Build derive attribute with Debug as default plus custom derives
-    let mut all_derives = vec!["Debug".to_string()];
-    all_derives.extend(custom_derives.iter().cloned());
+    /// Report the call if its result size diverged from the estimate by an order of magnitude
+    /// Empty results count as one row, so that small estimates are not reported for them
+    pub(crate) fn record(query: &'static str, estimated_rows: f64, actual_rows: usize) {
+        let Some(observer) = OBSERVER.read().ok().and_then(|current| current.clone()) else {
+            return;
+        };
+        let estimated = estimated_rows.max(1.0);
+        let actual = (actual_rows as f64).max(1.0);
+        if actual >= estimated * 10.0 || estimated >= actual * 10.0 {
+            observer(CardinalityDrift { query, estimated_rows, actual_rows });
+        }
+    }
+}
+"#
+    .to_string()
+}
 
-    // Remove duplicates while preserving order
-    let mut seen = HashSet::new();
-    all_derives.retain(|d| seen.insert(d.clone()));
+/// Generate the cache_invalidation module for mod.rs
+/// Receives the notifications of the triggers written to `cache_invalidation_migrations` and of
+/// the channels of `cache_invalidate_on`, and holds the caches of the `*_cached` functions
+fn generate_cache_invalidation_module(query_cache_channels: &[String]) -> String {
+    let query_channels: Vec<String> = query_cache_channels
+        .iter()
+        .map(|channel| format!("{:?}", channel))
+        .collect();
+    format!(
+        r#"/// Invalidation of the application caches of queries with `cache_invalidation`, notified by the
+/// triggers of the generated cache invalidation migrations, and of the caches of the `*_cached`
+/// functions of queries with `cache_invalidate_on`
+pub mod cache_invalidation {{
+    /// Channel notified by the triggers, with the changed table as payload
+    pub const CHANNEL: &str = "{}";
+
+    /// Channels of the queries with `cache_invalidate_on`, listened to as well
+    pub const QUERY_CHANNELS: &[&str] = &[{}];
+
+    /// Change invalidating cached query results
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum Invalidation {{
+        /// Rows of the table changed, named as in the `TABLES` of the modules, e.g. "users"
+        Table(String),
+        /// One of the `QUERY_CHANNELS` was notified, with the payload naming the changed key or
+        /// empty
+        Channel {{ channel: String, payload: String }},
+        /// The connection of the listener was lost, so changes may have been missed
+        All,
+    }}
+
+    impl Invalidation {{
+        /// Whether cached results of a query reading the tables, e.g. an entry of the
+        /// `CACHED_QUERIES` of a module, are stale
+        pub fn affects(&self, tables: &[&str]) -> bool {{
+            match self {{
+                Self::Table(table) => tables.contains(&table.as_str()),
+                Self::Channel {{ .. }} => false,
+                Self::All => true,
+            }}
+        }}
+    }}
+
+    /// Listener receiving the changes of the tables of cached queries and the notifications of
+    /// the `QUERY_CHANNELS` on a connection of its own
+    pub struct Listener {{
+        listener: sqlx::postgres::PgListener,
+    }}
+
+    impl Listener {{
+        /// Listen to `CHANNEL` and the `QUERY_CHANNELS` on a connection taken from the pool
+        pub async fn connect(pool: &sqlx::PgPool) -> Result<Self, sqlx::Error> {{
+            let mut listener = sqlx::postgres::PgListener::connect_with(pool).await?;
+            listener
+                .listen_all(std::iter::once(CHANNEL).chain(QUERY_CHANNELS.iter().copied()))
+                .await?;
+            Ok(Self {{ listener }})
+        }}
+
+        /// Wait for the next change, `Invalidation::All` when the connection was lost, which the
+        /// listener re-establishes on the next call
+        pub async fn recv(&mut self) -> Result<Invalidation, sqlx::Error> {{
+            Ok(match self.listener.try_recv().await? {{
+                Some(notification) if notification.channel() == CHANNEL => {{
+                    Invalidation::Table(notification.payload().to_string())
+                }}
+                Some(notification) => Invalidation::Channel {{
+                    channel: notification.channel().to_string(),
+                    payload: notification.payload().to_string(),
+                }},
+                None => Invalidation::All,
+            }})
+        }}
+    }}
+
+    /// Results of a query with `cache_invalidate_on` cached by its `*_cached` function, keyed by
+    /// the arguments
+    /// The task of `spawn` clears the entries named by the notifications on the channel of the
+    /// query, every entry when one of the tables of the query is notified by the triggers of the
+    /// cache invalidation migrations, and every entry when its connection was lost
+    /// The cache is unbounded, it holds the result of every distinct arguments until they are
+    /// cleared
+    pub struct QueryCache<K, V> {{
+        channel: &'static str,
+        /// Tables read by the query, named as in `Invalidation::Table`
+        tables: &'static [&'static str],
+        registered: std::sync::Once,
+        state: std::sync::Mutex<CacheState<K, V>>,
+    }}
+
+    struct CacheState<K, V> {{
+        /// Incremented by every invalidation, so that results read before it are not cached
+        generation: u64,
+        /// Cached results by arguments, with the text of the key argument notifications name
+        entries: std::collections::BTreeMap<K, (Option<String>, V)>,
+    }}
+
+    impl<K: Ord + Send + 'static, V: Clone + Send + 'static> QueryCache<K, V> {{
+        /// Empty cache of a query, cleared by the notifications on the channel and the changes of
+        /// the tables
+        pub const fn new(channel: &'static str, tables: &'static [&'static str]) -> Self {{
+            Self {{
+                channel,
+                tables,
+                registered: std::sync::Once::new(),
+                state: std::sync::Mutex::new(CacheState {{
+                    generation: 0,
+                    entries: std::collections::BTreeMap::new(),
+                }}),
+            }}
+        }}
+
+        /// Cached result of the arguments
+        pub fn get(&self, key: &K) -> Option<V> {{
+            lock(&self.state).entries.get(key).map(|(_, value)| value.clone())
+        }}
+
+        /// Generation to pass to `insert`, read before running the query
+        /// The cache is registered with `invalidate` first, so that no invalidation happening
+        /// while the query runs is missed
+        pub fn generation(&'static self) -> u64 {{
+            self.registered.call_once(|| lock(&CACHES).push(self));
+            lock(&self.state).generation
+        }}
+
+        /// Cache the result of the arguments, unless an invalidation happened since the generation
+        /// was read; `invalidation_key` is the text of the argument notifications name
+        pub fn insert(&self, key: K, invalidation_key: Option<String>, generation: u64, value: V) {{
+            let mut state = lock(&self.state);
+            if state.generation == generation {{
+                state.entries.insert(key, (invalidation_key, value));
+            }}
+        }}
+
+        /// Remove every cached result
+        pub fn clear(&self) {{
+            let mut state = lock(&self.state);
+            state.generation += 1;
+            state.entries.clear();
+        }}
+    }}
+
+    /// Cache cleared by `invalidate`
+    trait Invalidate {{
+        fn invalidate(&self, invalidation: &Invalidation);
+    }}
+
+    impl<K: Ord + Send + 'static, V: Clone + Send + 'static> Invalidate for QueryCache<K, V> {{
+        fn invalidate(&self, invalidation: &Invalidation) {{
+            match invalidation {{
+                Invalidation::Channel {{ channel, payload }} if channel == self.channel => {{
+                    let mut state = lock(&self.state);
+                    state.generation += 1;
+                    if payload.is_empty() {{
+                        state.entries.clear();
+                    }} else {{
+                        // Entries of caches without key are cleared by any notification
+                        state
+                            .entries
+                            .retain(|_, (key, _)| key.as_ref().is_some_and(|key| key != payload));
+                    }}
+                }}
+                invalidation if invalidation.affects(self.tables) => self.clear(),
+                _ => {{}}
+            }}
+        }}
+    }}
+
+    /// Caches of the `*_cached` functions, registered when they first read their generation
+    static CACHES: std::sync::Mutex<Vec<&'static (dyn Invalidate + Sync)>> =
+        std::sync::Mutex::new(Vec::new());
+
+    fn lock<T>(mutex: &std::sync::Mutex<T>) -> std::sync::MutexGuard<'_, T> {{
+        mutex.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }}
+
+    /// Clear the cached results of the `*_cached` functions affected by the change
+    pub fn invalidate(invalidation: &Invalidation) {{
+        let caches = lock(&CACHES).clone();
+        for cache in caches {{
+            cache.invalidate(invalidation);
+        }}
+    }}
+
+    /// Spawn the task shared by the caches of the `*_cached` functions, which listens on a
+    /// connection taken from the pool and clears the cached results the notifications name
+    /// Without the task, cached results are never cleared
+    pub async fn spawn(pool: &sqlx::PgPool) -> Result<tokio::task::JoinHandle<()>, sqlx::Error> {{
+        let mut listener = Listener::connect(pool).await?;
+        Ok(tokio::spawn(async move {{
+            loop {{
+                match listener.recv().await {{
+                    Ok(invalidation) => invalidate(&invalidation),
+                    // Notifications may be missed until the connection is re-established
+                    Err(_) => {{
+                        invalidate(&Invalidation::All);
+                        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                    }}
+                }}
+            }}
+        }}))
+    }}
+}}
+"#,
+        CACHE_INVALIDATION_CHANNEL,
+        query_channels.join(", ")
+    )
+}
 
-    code.push_str(&format!("#[derive({})]\n", all_derives.join(", ")));
-    code.push_str(&format!("pub enum {} {{\n", enum_name));
+/// Generate the large_list module for mod.rs
+/// Encodes list parameters for COPY into the temp tables of `large_list_strategy: temp_table` queries
+fn generate_large_list_module() -> String {
+    r#"/// Helpers for functions generated with `large_list_strategy: temp_table`
+pub(crate) mod large_list {
+    /// Encode values as the rows of a single-column COPY in text format
+    pub(crate) fn copy_data<T: std::fmt::Display>(values: &[T]) -> Vec<u8> {
+        let mut data = String::new();
+        for value in values {
+            for ch in value.to_string().chars() {
+                match ch {
+                    '\\' => data.push_str("\\\\"),
+                    '\n' => data.push_str("\\n"),
+                    '\r' => data.push_str("\\r"),
+                    '\t' => data.push_str("\\t"),
+                    ch => data.push(ch),
+                }
+            }
+            data.push('\n');
+        }
+        data.into_bytes()
+    }
+}
+"#
+    .to_string()
+}
 
-    // Generate a variant for each unique constraint
-    for constraint in &unique_constraints {
-        let variant_name = to_pascal_case(&constraint.name);
-        code.push_str(&format!(
-            "    /// Constraint: {} on table {}\n",
-            constraint.name, constraint.table_name
-        ));
-        code.push_str(&format!("    {},\n", variant_name));
+/// Generate the sharding module for mod.rs
+/// Validates shard keys before they are formatted into the SQL of `shard_by` queries
+fn generate_sharding_module(sqlx_version: SqlxVersion) -> String {
+    format!(
+        r#"/// Helpers for functions generated with `shard_by`
+pub(crate) mod sharding {{
+    /// Maximum shard key length, keeping suffixed table names within PostgreSQL's identifier limit
+    const MAX_SHARD_KEY_LEN: usize = {max_len};
+
+    /// Substitute the shard key for `{placeholder}` in the SQL of a sharded query
+    /// Keys may only contain ASCII letters, digits and underscores, so they cannot alter the SQL
+    pub(crate) fn shard_sql(sql: &str, shard_key: &str) -> Result<String, sqlx::Error> {{
+        let is_valid = !shard_key.is_empty()
+            && shard_key.len() <= MAX_SHARD_KEY_LEN
+            && shard_key.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_');
+        if !is_valid {{
+            return Err({invalid_argument}(format!("invalid shard key '{{}}'", shard_key)));
+        }}
+        Ok(sql.replace("{placeholder}", shard_key))
+    }}
+}}
+"#,
+        max_len = MAX_SHARD_LEN,
+        placeholder = SHARD_PLACEHOLDER,
+        invalid_argument = invalid_argument_error(sqlx_version),
+    )
+}
+
+/// Constructor of the error for an argument rejected before running the query, which is called
+/// with the message
+fn invalid_argument_error(sqlx_version: SqlxVersion) -> &'static str {
+    if sqlx_version.needs_compat() {
+        "super::sqlx_compat::invalid_argument"
+    } else {
+        "sqlx::Error::InvalidArgument"
+    }
+}
+
+/// Constructor of the SQL of a query with `prepare: false`, run with the simple query protocol
+fn raw_sql_constructor(sqlx_version: SqlxVersion) -> &'static str {
+    if sqlx_version.needs_compat() {
+        "super::sqlx_compat::raw_sql"
+    } else {
+        "sqlx::raw_sql"
     }
+}
 
-    code.push_str("}\n\n");
+/// Attributes selecting each sqlx version the generated code compiles against, as
+/// (whether it is sqlx 0.7, cfg attribute line or "" when only one version is generated)
+fn sqlx_version_variants(sqlx_version: SqlxVersion) -> Vec<(bool, &'static str)> {
+    match sqlx_version {
+        SqlxVersion::V0_7 => vec![(true, "")],
+        SqlxVersion::V0_8 => vec![(false, "")],
+        SqlxVersion::Feature => vec![
+            (false, "#[cfg(not(feature = \"sqlx-0-7\"))]\n"),
+            (true, "#[cfg(feature = \"sqlx-0-7\")]\n"),
+        ],
+    }
+}
 
-    // Generate TryFrom<ErrorConstraintInfo> implementation
-    code.push_str(&format!(
-        "impl TryFrom<super::ErrorConstraintInfo> for {} {{\n",
-        enum_name
-    ));
-    code.push_str("    type Error = ();\n\n");
-    code.push_str(
-        "    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {\n",
+/// Generate the sqlx_compat module for mod.rs
+/// Covers the API differences between sqlx 0.7 and 0.8 the generated code depends on
+fn generate_sqlx_compat_module(sqlx_version: SqlxVersion) -> String {
+    let mut code = String::from(
+        "/// Differences between the sqlx versions the generated code compiles against\npub mod sqlx_compat {\n    type BoxDynError = Box<dyn std::error::Error + Send + Sync + 'static>;\n",
     );
-    code.push_str("        match info.constraint_name.as_str() {\n");
+    for (is_0_7, cfg) in sqlx_version_variants(sqlx_version) {
+        let items = if is_0_7 {
+            r#"
+    /// Return type of `sqlx::Encode::encode_by_ref`, which cannot fail in sqlx 0.7
+    {cfg}pub type EncodeResult = sqlx::encode::IsNull;
+
+    /// Return value of `sqlx::Encode::encode_by_ref` for the result of encoding a value
+    /// sqlx 0.7 cannot report encoding errors, so values which cannot be encoded are logged and
+    /// sent as NULL; the encoders write nothing to the buffer when they fail
+    {cfg}pub fn encoded(result: Result<sqlx::encode::IsNull, BoxDynError>) -> EncodeResult {
+        result.unwrap_or_else(|e| {
+            tracing::error!(error = %e, "failed to encode value, sent as NULL");
+            sqlx::encode::IsNull::Yes
+        })
+    }
 
-    for constraint in &unique_constraints {
-        let variant_name = to_pascal_case(&constraint.name);
-        code.push_str(&format!(
-            "            \"{}\" => Ok(Self::{}),\n",
-            constraint.name, variant_name
-        ));
+    /// Error for an argument rejected before running the query
+    /// sqlx 0.7 has no `sqlx::Error::InvalidArgument`
+    {cfg}pub fn invalid_argument(message: String) -> sqlx::Error {
+        sqlx::Error::Protocol(message)
     }
 
-    code.push_str("            _ => Err(()),\n");
-    code.push_str("        }\n");
-    code.push_str("    }\n");
-    code.push_str("}\n");
+    /// SQL run with the simple query protocol, for queries with `prepare: false`
+    /// sqlx 0.7 has no `sqlx::raw_sql`, but runs SQL passed without arguments the same way
+    {cfg}pub struct RawSql(&'static str);
 
-    code
-}
+    /// SQL run with the simple query protocol, without preparing a statement
+    {cfg}pub fn raw_sql(sql: &'static str) -> RawSql {
+        RawSql(sql)
+    }
 
-/// Remove statistics from EXPLAIN plan lines to make output stable
-/// Removes patterns like: (cost=X..Y rows=Z width=W)
-fn remove_plan_statistics(line: &str) -> String {
-    use regex::Regex;
-    use std::sync::OnceLock;
+    {cfg}impl RawSql {
+        pub async fn execute<'e, E>(self, executor: E) -> Result<sqlx::postgres::PgQueryResult, sqlx::Error>
+        where
+            E: sqlx::Executor<'e, Database = sqlx::Postgres>,
+        {
+            sqlx::Executor::execute(executor, self.0).await
+        }
 
-    static RE: OnceLock<Regex> = OnceLock::new();
-    let re = RE.get_or_init(|| {
-        // Match patterns like (cost=10000000000.00..10000000003.74 rows=59 width=64)
-        // The pattern handles:
-        // - Optional cost=X..Y
-        // - Optional rows=Z
-        // - Optional width=W
-        // All enclosed in parentheses with spaces
-        Regex::new(r"\s*\((?:cost=[\d.]+\.\.[\d.]+\s*)?(?:rows=\d+\s*)?(?:width=\d+\s*)?\)")
-            .unwrap()
-    });
+        pub async fn fetch_all<'e, E>(self, executor: E) -> Result<Vec<sqlx::postgres::PgRow>, sqlx::Error>
+        where
+            E: sqlx::Executor<'e, Database = sqlx::Postgres>,
+        {
+            sqlx::Executor::fetch_all(executor, self.0).await
+        }
 
-    re.replace_all(line, "").trim_end().to_string()
-}
+        pub async fn fetch_one<'e, E>(self, executor: E) -> Result<sqlx::postgres::PgRow, sqlx::Error>
+        where
+            E: sqlx::Executor<'e, Database = sqlx::Postgres>,
+        {
+            sqlx::Executor::fetch_one(executor, self.0).await
+        }
 
-/// Generate tracing::instrument attribute for a function
-fn generate_tracing_attribute(query: &QueryDefinition, param_names: &[String]) -> String {
-    use std::collections::HashSet;
+        pub async fn fetch_optional<'e, E>(self, executor: E) -> Result<Option<sqlx::postgres::PgRow>, sqlx::Error>
+        where
+            E: sqlx::Executor<'e, Database = sqlx::Postgres>,
+        {
+            sqlx::Executor::fetch_optional(executor, self.0).await
+        }
+    }
+"#
+        } else {
+            r#"
+    /// Return type of `sqlx::Encode::encode_by_ref`
+    {cfg}pub type EncodeResult = Result<sqlx::encode::IsNull, BoxDynError>;
 
-    let telemetry_level = query.telemetry.level;
-    if telemetry_level == TelemetryLevel::None {
-        return String::new();
+    /// Return value of `sqlx::Encode::encode_by_ref` for the result of encoding a value
+    {cfg}pub fn encoded(result: Result<sqlx::encode::IsNull, BoxDynError>) -> EncodeResult {
+        result
     }
 
-    let mut attributes = Vec::new();
+    /// Error for an argument rejected before running the query
+    {cfg}pub fn invalid_argument(message: String) -> sqlx::Error {
+        sqlx::Error::InvalidArgument(message)
+    }
 
-    // No need for explicit span name - tracing::instrument will use the function name automatically
+    /// SQL run with the simple query protocol, without preparing a statement
+    {cfg}pub fn raw_sql(sql: &'static str) -> sqlx::RawSql<'static> {
+        sqlx::raw_sql(sql)
+    }
+"#
+        };
+        let cfg = if cfg.is_empty() {
+            String::new()
+        } else {
+            format!("{}    ", cfg)
+        };
+        code.push_str(&items.replace("{cfg}", &cfg));
+    }
+    code.push_str("}\n");
+    code
+}
 
-    // Add instrumentation level
-    let level = match telemetry_level {
-        TelemetryLevel::Info => "info",
-        TelemetryLevel::Debug => "debug",
-        TelemetryLevel::Trace => "trace",
-        TelemetryLevel::None => unreachable!(),
-    };
-    attributes.push(format!("level = \"{}\"", level));
+/// Generate the batching module for mod.rs
+/// Runs the background tasks behind the `{query}_batcher` functions of `batch` queries
+fn generate_batching_module() -> String {
+    r#"/// Group commit batching for functions generated with `batch`
+pub mod batching {
+    use std::future::Future;
+    use std::time::Duration;
+    use tokio::sync::{mpsc, oneshot};
+
+    /// Handle to a background task which coalesces concurrent calls into batches
+    /// Clones share the task, which stops when the last handle is dropped
+    pub struct Batcher<T, R> {
+        sender: mpsc::UnboundedSender<(T, oneshot::Sender<R>)>,
+    }
 
-    // Determine parameter skipping strategy
-    let mut skip_params = HashSet::new();
-    skip_params.insert("executor".to_string());
+    impl<T, R> Clone for Batcher<T, R> {
+        fn clone(&self) -> Self {
+            Self { sender: self.sender.clone() }
+        }
+    }
 
-    // Parameter inclusion logic (independent of telemetry level)
-    if let Some(include_params) = &query.telemetry.include_params {
-        if include_params.is_empty() {
-            // Empty include_params list means skip all parameters
-            skip_params.extend(param_names.iter().cloned());
-        } else {
-            let included: Vec<String> = include_params
-                .iter()
-                .filter(|param| param_names.contains(param))
-                .cloned()
-                .collect();
+    impl<T: Send + 'static, R: Send + 'static> Batcher<T, R> {
+        /// Spawn the batching task on the current tokio runtime
+        /// A batch is flushed when it holds `max_size` items or `max_delay` after its first item,
+        /// calls arriving while a batch is flushed are collected into the next one
+        pub fn spawn<F, Fut>(max_size: usize, max_delay: Duration, flush: F) -> Self
+        where
+            F: Fn(Vec<T>) -> Fut + Send + 'static,
+            Fut: Future<Output = Vec<R>> + Send + 'static,
+        {
+            let (sender, mut receiver) = mpsc::unbounded_channel::<(T, oneshot::Sender<R>)>();
+            tokio::spawn(async move {
+                while let Some(first) = receiver.recv().await {
+                    let deadline = tokio::time::Instant::now() + max_delay;
+                    let mut batch = vec![first];
+                    while batch.len() < max_size {
+                        match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                            Ok(Some(item)) => batch.push(item),
+                            _ => break,
+                        }
+                    }
 
-            if !included.is_empty() {
-                // Skip parameters not in the include list
-                for param in param_names {
-                    if !included.contains(param) {
-                        skip_params.insert(param.clone());
+                    let (items, senders): (Vec<T>, Vec<oneshot::Sender<R>>) = batch.into_iter().unzip();
+                    let results = flush(items).await;
+                    for (sender, result) in senders.into_iter().zip(results) {
+                        // The caller may have stopped waiting for its result
+                        let _ = sender.send(result);
                     }
                 }
-            } else {
-                // No valid included parameters, skip all
-                skip_params.extend(param_names.iter().cloned());
+            });
+            Self { sender }
+        }
+
+        /// Queue an item and wait for the result of its batch
+        /// Returns None if the batching task is no longer running
+        pub async fn submit(&self, item: T) -> Option<R> {
+            let (sender, receiver) = oneshot::channel();
+            self.sender.send((item, sender)).ok()?;
+            receiver.await.ok()
+        }
+    }
+
+    /// Shift the positional parameters ($1, $2, ...) of a VALUES row by `offset`
+    pub(crate) fn renumber_params(row: &str, offset: usize) -> String {
+        let mut result = String::with_capacity(row.len() + 8);
+        let mut chars = row.chars().peekable();
+        while let Some(ch) = chars.next() {
+            result.push(ch);
+            if ch != '$' {
+                continue;
+            }
+            let mut digits = String::new();
+            while let Some(digit) = chars.peek().filter(|c| c.is_ascii_digit()) {
+                digits.push(*digit);
+                chars.next();
+            }
+            match digits.parse::<usize>() {
+                Ok(index) => result.push_str(&(index + offset).to_string()),
+                Err(_) => result.push_str(&digits),
             }
         }
-    } else {
-        // No include_params specified, skip all parameters
-        skip_params.extend(param_names.iter().cloned());
+        result
     }
+}
+"#
+    .to_string()
+}
 
-    // Check if we should use skip_all (all params including query params are skipped)
-    let total_params = param_names.len() + 1; // +1 for executor
-    let should_use_skip_all = param_names.len() > 0 && skip_params.len() == total_params;
+/// Generate the Session type for mod.rs
+/// Functions generated with `requires_session` take it instead of any executor, so that the
+/// compiler ensures they run on the connection holding the session state
+fn generate_session_type() -> String {
+    r#"/// Pooled connection pinned for multi-step flows relying on session state, such as temp tables
+/// and session settings; functions generated with `requires_session` only accept a Session
+pub struct Session {
+    connection: sqlx::pool::PoolConnection<sqlx::Postgres>,
+}
 
-    // Generate skip attribute
-    if should_use_skip_all {
-        attributes.push("skip_all".to_string());
-    } else if skip_params.len() > 1 {
-        // More than just executor
-        let mut skip_vec: Vec<_> = skip_params.into_iter().collect();
-        skip_vec.sort(); // Sort for consistent output
-        attributes.push(format!("skip({})", skip_vec.join(", ")));
-    } else {
-        attributes.push("skip_all".to_string());
+impl Session {
+    /// Pin a connection of the pool until the session is dropped
+    pub async fn acquire(pool: &sqlx::PgPool) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            connection: pool.acquire().await?,
+        })
     }
 
-    // Determine whether to include SQL based on configuration (default false)
-    let should_include_sql = query.telemetry.include_sql;
-    if should_include_sql {
-        let escaped_sql = query
-            .sql
-            .replace('\\', "\\\\")
-            .replace('"', "\\\"")
-            .replace('\n', "\\n");
-        attributes.push(format!("fields(sql = \"{}\")", escaped_sql));
+    /// Connection of the session, e.g. to create temp tables or set session settings
+    pub fn connection(&mut self) -> &mut sqlx::PgConnection {
+        &mut self.connection
     }
 
-    format!("#[tracing::instrument({})]\n", attributes.join(", "))
+    /// Release the session, returning its connection
+    pub fn into_connection(self) -> sqlx::pool::PoolConnection<sqlx::Postgres> {
+        self.connection
+    }
+}
+"#
+    .to_string()
 }
 
-/// Generate an indented raw string literal with proper formatting
-fn generate_indented_raw_string_literal(sql: &str) -> String {
-    // Find a delimiter that doesn't appear in the SQL
-    let mut delimiter_count = 0;
-    let delimiter = loop {
-        let delimiter = "#".repeat(delimiter_count);
-        let pattern = format!("\"{}\"", delimiter);
-        if !sql.contains(&pattern) {
-            break delimiter;
-        }
-        delimiter_count += 1;
-    };
+/// Generate the CascadeImpact type returned by the `{query}_preview` functions for mod.rs
+fn generate_cascade_impact_type() -> String {
+    r#"/// Rows a DELETE would remove, and the rows of other tables referencing them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CascadeImpact {
+    /// Rows matched by the DELETE
+    pub deleted: i64,
+    /// Referencing rows per foreign key, including those referencing rows deleted by cascade
+    pub referencing: Vec<ReferencingRows>,
+}
 
-    // Add proper indentation to each line of SQL
-    let indented_sql = sql
-        .lines()
-        .enumerate()
-        .map(|(i, line)| {
-            if i == 0 {
-                line.to_string() // First line doesn't need extra indentation
-            } else {
-                format!("        {}", line) // Subsequent lines get 8 spaces of indentation
-            }
+impl CascadeImpact {
+    /// Whether referencing rows make the DELETE fail (ON DELETE NO ACTION or RESTRICT)
+    pub fn is_blocked(&self) -> bool {
+        self.referencing.iter().any(|referencing| {
+            referencing.rows > 0
+                && matches!(
+                    referencing.on_delete,
+                    OnDelete::NoAction | OnDelete::Restrict
+                )
         })
-        .collect::<Vec<_>>()
-        .join("\n");
+    }
 
-    format!(
-        "        r{delimiter}\"{indented_sql}\"{delimiter}",
-        delimiter = delimiter,
-        indented_sql = indented_sql
-    )
+    /// Rows the DELETE removes in total, including those deleted by cascade
+    pub fn total_deleted(&self) -> i64 {
+        self.deleted
+            + self
+                .referencing
+                .iter()
+                .filter(|referencing| referencing.on_delete == OnDelete::Cascade)
+                .map(|referencing| referencing.rows)
+                .sum::<i64>()
+    }
 }
 
-/// Generate Rust function code for a SQL query without enum definitions
-/// (assumes enums are already defined elsewhere in the module)
-pub fn generate_function_code_without_enums(
-    query: &QueryDefinition,
-    type_info: &QueryTypeInfo,
-    emitted_struct_names: &mut std::collections::HashSet<String>,
-    constraints: &[crate::types_extractor::ConstraintInfo],
-    performance_analysis: &Option<crate::query_definition_rt::PerformanceAnalysis>,
-) -> Result<String> {
-    let mut code = String::new();
+/// Rows of a table referencing deleted rows through a foreign key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReferencingRows {
+    /// Schema-qualified referencing table
+    pub table: &'static str,
+    /// Foreign key constraint name
+    pub constraint: &'static str,
+    /// What the foreign key does with the referencing rows
+    pub on_delete: OnDelete,
+    /// Number of referencing rows
+    pub rows: i64,
+}
 
-    // Generate per-query constraint enum if there are constraints
-    let constraint_enum_name = if !constraints.is_empty() {
-        // Determine the enum name from error_type config or use default
-        let enum_name = if let Some(ref custom_name) = query.error_type {
-            custom_name.to_string()
-        } else {
-            format!("{}Constraints", to_pascal_case(&query.name))
-        };
+/// ON DELETE action of a foreign key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnDelete {
+    NoAction,
+    Restrict,
+    Cascade,
+    SetNull,
+    SetDefault,
+}
+"#
+    .to_string()
+}
 
-        // Only generate the enum if it hasn't been emitted yet
-        if !emitted_struct_names.contains(&enum_name) {
-            code.push_str(&generate_query_constraint_enum(
-                &enum_name,
-                constraints,
-                &query.error_type_derives,
-            ));
-            code.push('\n');
-            emitted_struct_names.insert(enum_name.clone());
-        }
+/// Generate the WarmupStatus type returned by the `warmup` functions of the modules for mod.rs
+fn generate_warmup_module() -> String {
+    r#"/// Result of preparing a query with the `warmup` function of its module
+#[derive(Debug)]
+pub struct WarmupStatus {
+    /// Name of the query
+    pub query: &'static str,
+    /// Error of the first SQL variant of the query which failed to prepare, or a protocol error
+    /// when the result columns differ from those the code was generated for
+    pub result: Result<(), sqlx::Error>,
+}
 
-        Some(enum_name)
-    } else {
-        None
-    };
+impl WarmupStatus {
+    /// Whether the query matches the schema of the database
+    pub fn is_ok(&self) -> bool {
+        self.result.is_ok()
+    }
+}
 
-    // Extract clean parameter names directly from the SQL for function signature
-    let original_param_names = parse_parameter_names_from_sql(&query.sql);
-    let clean_param_names: Vec<String> = original_param_names
-        .iter()
-        .map(|name| {
-            if name.ends_with('?') {
-                name.trim_end_matches('?').to_string()
-            } else {
-                name.clone()
+/// Prepare the SQL variants of a query, checking the result columns of the first one
+pub async fn warmup_query(
+    executor: &mut sqlx::PgConnection,
+    variants: &[&'static str],
+    columns: &[&str],
+) -> Result<(), sqlx::Error> {
+    use sqlx::{Column, Executor, Statement};
+    for (i, sql) in variants.iter().enumerate() {
+        let statement = (&mut *executor).prepare(sql).await?;
+        if i == 0 {
+            let actual: Vec<&str> = statement.columns().iter().map(|c| c.name()).collect();
+            if actual != columns {
+                return Err(sqlx::Error::Protocol(format!(
+                    "result columns {:?} differ from the generated {:?}",
+                    actual, columns
+                )));
             }
-        })
-        .collect();
+        }
+    }
+    Ok(())
+}
+"#
+    .to_string()
+}
 
-    let use_multiunzip = query.multiunzip;
+/// Generate the blocking module for mod.rs
+/// Holds the runtime shared by the `{query}_blocking` wrappers of `blocking` queries
+/// Generate the numeric module for mod.rs
+/// Holds the checked conversion of the columns decoded as Decimal and returned as i64
+fn generate_numeric_module() -> String {
+    r#"/// Checked conversions of NUMERIC columns, for `numeric_as: i64`
+pub mod numeric {
+    use rust_decimal::prelude::ToPrimitive;
+
+    /// Convert a decoded NUMERIC value to i64, failing with a column decode error holding
+    /// `NumericOverflow` when it is not an integer or out of range
+    pub fn to_i64(column: &'static str, value: rust_decimal::Decimal) -> Result<i64, sqlx::Error> {
+        value
+            .fract()
+            .is_zero()
+            .then(|| value.to_i64())
+            .flatten()
+            .ok_or_else(|| sqlx::Error::ColumnDecode {
+                index: column.to_string(),
+                source: Box::new(super::NumericOverflow {
+                    column,
+                    value: value.to_string(),
+                }),
+            })
+    }
+}
+"#
+    .to_string()
+}
 
-    // Determine if conditions_type is enabled and get the struct name override
-    let (use_conditional_diff, diff_struct_name_override) = match &query.conditions_type {
-        crate::query_definition::ConditionsType::Enabled(true) => (true, None),
-        crate::query_definition::ConditionsType::Named(name) => (true, Some(name.as_str())),
-        _ => (false, None),
-    };
+/// Generate the char_flags module for mod.rs
+/// Holds the conversions of the CHAR(1) and "char" columns and parameters listed in `char_flags`
+fn generate_char_flags_module() -> String {
+    r#"/// Conversions of flag codes, for the CHAR(1) and "char" columns and parameters of `char_flags`
+pub mod char_flags {
+    /// Code stored in a flag column, the text of CHAR(1) columns or the byte of "char" columns
+    pub trait Code: std::fmt::Debug {
+        fn matches(&self, code: &str) -> bool;
+    }
 
-    // Determine if parameters_type is enabled and get the struct name
-    let (use_structured_params, struct_name_override) = match &query.parameters_type {
-        crate::query_definition::ParametersType::Enabled(true) if !use_conditional_diff => {
-            (true, None)
+    impl Code for String {
+        fn matches(&self, code: &str) -> bool {
+            // CHAR(n) values are padded with spaces
+            self.trim_end() == code
         }
-        crate::query_definition::ParametersType::Named(name) if !use_conditional_diff => {
-            (true, Some(name.as_str()))
+    }
+
+    impl Code for i8 {
+        fn matches(&self, code: &str) -> bool {
+            code.as_bytes() == [*self as u8]
         }
-        _ => (false, None),
+    }
+
+    /// Value of the code of a column, failing with `ColumnDecode` for unknown codes
+    pub fn decode<C: Code, T: Copy>(
+        column: &str,
+        code: C,
+        values: &[(&str, T)],
+    ) -> Result<T, sqlx::Error> {
+        values
+            .iter()
+            .find(|(value_code, _)| code.matches(value_code))
+            .map(|(_, value)| *value)
+            .ok_or_else(|| sqlx::Error::ColumnDecode {
+                index: column.to_string(),
+                source: format!("unknown flag code {:?}", code).into(),
+            })
+    }
+
+    /// Code of a value bound to a CHAR(1) or text parameter
+    pub fn encode<T: PartialEq>(value: T, values: &[(&'static str, T)]) -> &'static str {
+        values
+            .iter()
+            .find(|(_, code_value)| *code_value == value)
+            .map_or("", |(code, _)| code)
+    }
+
+    /// Code of a value bound to a "char" parameter
+    pub fn encode_byte<T: PartialEq>(value: T, values: &[(&'static str, T)]) -> i8 {
+        encode(value, values).bytes().next().unwrap_or(0) as i8
+    }
+}
+"#
+    .to_string()
+}
+
+/// Generate the array_elements module for mod.rs
+/// Holds the conversions of the text array columns and parameters mapped with `name[]` keys
+fn generate_array_elements_module() -> String {
+    r#"/// Conversions of array elements, for the text array columns and parameters of `name[]` keys
+/// of `types`
+pub mod array_elements {
+    /// Elements of a text array column, failing with `ColumnDecode` for unparsable elements
+    pub fn decode<T>(column: &str, values: Vec<String>) -> Result<Vec<T>, sqlx::Error>
+    where
+        T: std::str::FromStr,
+        T::Err: std::fmt::Display,
+    {
+        values
+            .into_iter()
+            .map(|value| {
+                value.parse().map_err(|e: T::Err| sqlx::Error::ColumnDecode {
+                    index: column.to_string(),
+                    source: format!("invalid element {:?}: {}", value, e).into(),
+                })
+            })
+            .collect()
+    }
+
+    /// Text of the elements bound to a text array parameter
+    pub fn encode<T: std::fmt::Display>(values: &[T]) -> Vec<String> {
+        values.iter().map(ToString::to_string).collect()
+    }
+}
+"#
+    .to_string()
+}
+
+/// Generate the money module for mod.rs
+/// Holds the amount type of the NUMERIC columns and parameters listed in `currency_columns`
+fn generate_money_module(sqlx_version: SqlxVersion) -> String {
+    // sqlx 0.7 has no fallible Encode, Decimal's encoding has the result type of the version
+    let encode_result = if sqlx_version.needs_compat() {
+        "super::sqlx_compat::EncodeResult"
+    } else {
+        "Result<sqlx::encode::IsNull, Box<dyn std::error::Error + Send + Sync + 'static>>"
     };
+    r#"/// Amounts of money, for the NUMERIC columns and parameters of `currency_columns`
+pub mod money {
+    /// An amount of money in a currency, stored as NUMERIC
+    /// Columns are decoded with the currency of their `currency_columns` entry, and parameters in
+    /// any other currency are rejected before the query runs
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize)]
+    pub struct Money {
+        pub amount: rust_decimal::Decimal,
+        /// ISO 4217 currency code, e.g. "EUR"
+        pub currency: &'static str,
+    }
 
-    // Generate input struct for multiunzip if needed
-    if use_multiunzip {
-        let struct_name = format!("{}Record", to_pascal_case(&query.name));
-        if !emitted_struct_names.contains(&struct_name) {
-            if let Some(input_struct) = generate_multiunzip_input_struct(
-                &query.name,
-                &clean_param_names,
-                &type_info.input_types,
-                &query.parameters_type_derives,
-            ) {
-                code.push_str(&input_struct);
-                code.push('\n');
-                emitted_struct_names.insert(struct_name);
-            }
+    impl Money {
+        pub fn new(amount: rust_decimal::Decimal, currency: &'static str) -> Self {
+            Self { amount, currency }
         }
     }
 
-    // Generate diff struct for conditions_type if needed
-    if use_conditional_diff && type_info.parsed_sql.is_some() {
-        let struct_name = if let Some(override_name) = diff_struct_name_override {
-            override_name.to_string()
-        } else {
-            format!("{}Params", to_pascal_case(&query.name))
-        };
+    impl std::fmt::Display for Money {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "{} {}", self.amount, self.currency)
+        }
+    }
 
-        if !emitted_struct_names.contains(&struct_name) {
-            if let Some(diff_struct) = generate_conditional_diff_struct(
-                &struct_name,
-                &original_param_names,
-                &type_info.input_types,
-                &query.conditions_type_derives,
-            ) {
-                code.push_str(&diff_struct);
-                code.push('\n');
-                emitted_struct_names.insert(struct_name);
-            }
+    impl sqlx::Type<sqlx::Postgres> for Money {
+        fn type_info() -> sqlx::postgres::PgTypeInfo {
+            <rust_decimal::Decimal as sqlx::Type<sqlx::Postgres>>::type_info()
         }
     }
 
-    // Generate struct for parameters_type if needed (but not for conditions_type)
-    if use_structured_params {
-        let struct_name = if let Some(override_name) = struct_name_override {
-            override_name.to_string()
-        } else {
-            format!("{}Params", to_pascal_case(&query.name))
-        };
+    impl sqlx::postgres::PgHasArrayType for Money {
+        fn array_type_info() -> sqlx::postgres::PgTypeInfo {
+            <rust_decimal::Decimal as sqlx::postgres::PgHasArrayType>::array_type_info()
+        }
+    }
 
-        if !emitted_struct_names.contains(&struct_name) {
-            if let Some(params_struct) = generate_structured_params_struct(
-                struct_name_override.unwrap_or(&query.name),
-                &clean_param_names,
-                &type_info.input_types,
-                &query.parameters_type_derives,
-            ) {
-                code.push_str(&params_struct);
-                code.push('\n');
-                emitted_struct_names.insert(struct_name);
-            }
+    impl<'q> sqlx::Encode<'q, sqlx::Postgres> for Money {
+        fn encode_by_ref(&self, buf: &mut sqlx::postgres::PgArgumentBuffer) -> {encode_result} {
+            sqlx::Encode::<sqlx::Postgres>::encode_by_ref(&self.amount, buf)
         }
     }
+}
+"#
+    .replace("{encode_result}", encode_result)
+}
 
-    // Generate result struct if needed (but no enums)
-    // Generate if: multiple columns OR single column with explicit return_type configured
-    let should_generate_struct = type_info.output_types.len() > 1 || query.return_type.is_some();
+fn generate_blocking_module() -> String {
+    r#"/// Runtime of the `*_blocking` wrappers generated with `blocking`, for callers without tokio
+#[cfg(feature = "blocking")]
+pub mod blocking {
+    use std::future::Future;
+    use std::sync::OnceLock;
 
-    if should_generate_struct {
-        let result_struct_name = if let Some(ref custom_name) = query.return_type {
-            custom_name.to_string()
-        } else {
-            format!("{}Item", to_pascal_case(&query.name))
-        };
+    static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+
+    /// Runtime the blocking wrappers run on, started on first use
+    /// Connections used with the wrappers must be opened on it too, e.g. with
+    /// `block_on(sqlx::PgPool::connect(url))`, as they are driven by the runtime they were opened on
+    pub fn runtime() -> &'static tokio::runtime::Runtime {
+        RUNTIME.get_or_init(|| {
+            tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build()
+                .expect("failed to start the runtime of the blocking wrappers")
+        })
+    }
 
-        if !emitted_struct_names.contains(&result_struct_name) {
-            if let Some(struct_def) = generate_result_struct_with_name(
-                &result_struct_name,
-                &type_info.output_types,
-                &query.return_type_derives,
-            ) {
-                code.push_str(&struct_def);
-                code.push('\n');
-                emitted_struct_names.insert(result_struct_name);
-            }
-        }
+    /// Run a future to completion on the shared runtime
+    /// Panics when called from async code running on a tokio runtime
+    pub fn block_on<F: Future>(future: F) -> F::Output {
+        runtime().block_on(future)
     }
+}
+"#
+    .to_string()
+}
 
-    // Generate function documentation
-    if let Some(description) = &query.description {
-        code.push_str(&format!("/// {}\n", description));
+/// Generate the cancellation module for mod.rs
+/// Holds the helpers of the `{query}_cancellable` variants of `cancellable` queries
+/// CockroachDB has no `pg_cancel_backend`, its queries are stopped by closing the connection
+fn generate_cancellation_module(dialect: Dialect) -> String {
+    let (backend_pid, cancel) = match dialect {
+        Dialect::Postgres => (
+            r#"sqlx::query_scalar("SELECT pg_backend_pid()")
+            .fetch_one(&mut **connection)
+            .await
+            .map(Some)"#,
+            r#"if let Some(backend_pid) = backend_pid {
+                let _ = sqlx::query("SELECT pg_cancel_backend($1)")
+                    .bind(backend_pid)
+                    .execute(&pool)
+                    .await;
+            }"#,
+        ),
+        Dialect::Cockroach => (
+            r#"let _ = connection;
+        Ok(None)"#,
+            r#"let _ = (pool, backend_pid);"#,
+        ),
+    };
+    format!(
+        r#"/// Server-side cancellation of the `*_cancellable` variants generated with `cancellable`
+#[cfg(feature = "cancellation")]
+pub mod cancellation {{
+    /// Server process running the queries of a connection, cancelled with `pg_cancel_backend`
+    pub async fn backend_pid(
+        connection: &mut sqlx::pool::PoolConnection<sqlx::Postgres>,
+    ) -> Result<Option<i32>, sqlx::Error> {{
+        {backend_pid}
+    }}
+
+    /// Cancel the query running on a connection and close the connection, which is left in
+    /// the middle of the query, instead of returning it to the pool
+    /// Runs in the background so that the cancelled function returns right away
+    pub fn cancel(
+        pool: &sqlx::PgPool,
+        mut connection: sqlx::pool::PoolConnection<sqlx::Postgres>,
+        backend_pid: Option<i32>,
+    ) {{
+        connection.close_on_drop();
+        let pool = pool.clone();
+        tokio::spawn(async move {{
+            {cancel}
+            drop(connection);
+        }});
+    }}
+}}
+"#,
+        backend_pid = backend_pid,
+        cancel = cancel,
+    )
+}
+
+/// Generate the full_text_search module for mod.rs
+/// Wraps tsvector and tsquery values, which sqlx does not support, and converts between
+/// their text representation and PostgreSQL's binary wire format
+fn generate_full_text_search_module(sqlx_version: SqlxVersion) -> String {
+    let mut code = r#"/// PostgreSQL full-text search types for functions with tsvector or tsquery columns and parameters
+pub mod full_text_search {
+    type BoxDynError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+    const OPERAND: u8 = 1;
+    const OPERATOR: u8 = 2;
+    const OP_NOT: u8 = 1;
+    const OP_AND: u8 = 2;
+    const OP_OR: u8 = 3;
+    const OP_PHRASE: u8 = 4;
+    /// Highest lexeme position PostgreSQL stores, larger positions are clamped to it
+    const MAX_POSITION: u32 = 0x3fff;
+
+    /// A tsvector in PostgreSQL's text representation, e.g. "'fat':2 'rat':3A"
+    #[derive(Debug, Clone, PartialEq, Eq, Hash, Default, serde::Serialize, serde::Deserialize)]
+    #[serde(transparent)]
+    pub struct TsVector(pub String);
+
+    /// A tsquery in PostgreSQL's text representation, e.g. "'fat' & ( 'rat' | 'cat' )"
+    #[derive(Debug, Clone, PartialEq, Eq, Hash, Default, serde::Serialize, serde::Deserialize)]
+    #[serde(transparent)]
+    pub struct TsQuery(pub String);
+
+    impl std::fmt::Display for TsVector {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str(&self.0)
+        }
     }
 
-    // Add query plan as documentation comments if available
-    if let Some(perf_analysis) = performance_analysis {
-        if let Some(plan) = &perf_analysis.query_plan {
-            code.push_str("///\n");
-            code.push_str("/// Query Plan:\n");
-            for line in plan.lines() {
-                // Remove cost estimates, row counts, and width from the plan to avoid instability
-                // These values change between runs and make the generated code unstable
-                let cleaned_line = remove_plan_statistics(line);
-                code.push_str(&format!("/// {}\n", cleaned_line));
-            }
+    impl std::fmt::Display for TsQuery {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str(&self.0)
         }
     }
 
-    let tracing_attribute = generate_tracing_attribute(query, &clean_param_names);
-    code.push_str(&tracing_attribute);
+    /// Parse free-form search input with `websearch_to_tsquery` and the given text search
+    /// configuration (e.g. "english"), for passing to queries with tsquery parameters
+    /// Quoted phrases, `or` and `-word` are supported and invalid input never fails
+    pub async fn websearch_to_tsquery<'e, E>(executor: E, config: &str, input: &str) -> Result<TsQuery, sqlx::Error>
+    where
+        E: sqlx::Executor<'e, Database = sqlx::Postgres>,
+    {
+        sqlx::query_scalar("SELECT websearch_to_tsquery($1::regconfig, $2)")
+            .bind(config)
+            .bind(input)
+            .fetch_one(executor)
+            .await
+    }
 
-    let input_params = if use_multiunzip {
-        // For multiunzip, generate a single Vec<StructName> parameter
-        generate_multiunzip_param(&query.name, "items")
-    } else if use_conditional_diff && type_info.parsed_sql.is_some() {
-        // For conditions_type, generate old and new struct parameters
-        generate_conditional_diff_params(
-            &query.name,
-            &original_param_names,
-            &type_info.input_types,
-            diff_struct_name_override.as_deref(),
-        )
-    } else if use_structured_params {
-        // For parameters_type, generate a single struct parameter
-        generate_structured_params_signature(&query.name, struct_name_override.as_deref())
-    } else {
-        generate_input_params_with_names(&type_info.input_types, &clean_param_names)
-    };
+    impl sqlx::Type<sqlx::Postgres> for TsVector {
+        fn type_info() -> sqlx::postgres::PgTypeInfo {
+            sqlx::postgres::PgTypeInfo::with_name("tsvector")
+        }
+    }
 
-    let base_return_type = if query.return_type.is_some() || type_info.output_types.len() > 1 {
-        // Use struct name if: explicit return_type configured OR multi-column result
-        if let Some(ref custom_name) = query.return_type {
-            custom_name.to_string()
-        } else {
-            format!("{}Item", to_pascal_case(&query.name))
+    impl sqlx::postgres::PgHasArrayType for TsVector {
+        fn array_type_info() -> sqlx::postgres::PgTypeInfo {
+            sqlx::postgres::PgTypeInfo::array_of("tsvector")
         }
-    } else {
-        // Single column result without explicit return_type - use direct type
-        generate_return_type(type_info.output_types.first())
-    };
+    }
 
-    // Generate function signature
-    let params_str = if input_params.is_empty() {
-        "executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>".to_string()
-    } else {
-        format!(
-            "executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, {}",
-            input_params
-        )
-    };
+    impl<'r> sqlx::Decode<'r, sqlx::Postgres> for TsVector {
+        fn decode(value: sqlx::postgres::PgValueRef<'r>) -> Result<Self, BoxDynError> {
+            if value.format() == sqlx::postgres::PgValueFormat::Text {
+                return Ok(Self(value.as_str()?.to_string()));
+            }
+            let mut bytes = value.as_bytes()?;
+            let mut lexemes = Vec::new();
+            for _ in 0..read_u32(&mut bytes)? {
+                let mut lexeme = quote_lexeme(read_cstr(&mut bytes)?);
+                for i in 0..read_u16(&mut bytes)? {
+                    let position = read_u16(&mut bytes)?;
+                    lexeme.push(if i == 0 { ':' } else { ',' });
+                    lexeme.push_str(&(u32::from(position) & MAX_POSITION).to_string());
+                    match position >> 14 {
+                        3 => lexeme.push('A'),
+                        2 => lexeme.push('B'),
+                        1 => lexeme.push('C'),
+                        _ => {}
+                    }
+                }
+                lexemes.push(lexeme);
+            }
+            Ok(Self(lexemes.join(" ")))
+        }
+    }
 
-    let return_type = if type_info.output_types.is_empty() {
-        let error_type = if let Some(ref enum_name) = constraint_enum_name {
-            format!("super::Error<{}>", enum_name)
-        } else {
-            "super::ErrorReadOnly".to_string()
-        };
-        format!("Result<(), {}>", error_type)
-    } else {
-        match query.expect {
-            ExpectedResult::ExactlyOne => {
-                let error_type = if let Some(ref enum_name) = constraint_enum_name {
-                    format!("super::Error<{}>", enum_name)
-                } else {
-                    "super::ErrorReadOnly".to_string()
-                };
-                format!("Result<{}, {}>", base_return_type, error_type)
+    /// Write a tsvector in PostgreSQL's binary format
+    /// The value is only written once it is parsed, so nothing is written when it is invalid
+    fn encode_tsvector(text: &str, buf: &mut sqlx::postgres::PgArgumentBuffer) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        // PostgreSQL expects sorted unique lexemes, each with ascending unique positions
+        let mut lexemes: std::collections::BTreeMap<String, std::collections::BTreeMap<u32, u16>> =
+            std::collections::BTreeMap::new();
+        let mut chars = text.chars().peekable();
+        loop {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            if chars.peek().is_none() {
+                break;
             }
-            ExpectedResult::PossibleOne => {
-                let error_type = if let Some(ref enum_name) = constraint_enum_name {
-                    format!("super::Error<{}>", enum_name)
-                } else {
-                    "super::ErrorReadOnly".to_string()
-                };
-                format!("Result<Option<{}>, {}>", base_return_type, error_type)
+            let lexeme = parse_lexeme(&mut chars, |c| c.is_whitespace() || c == ':')?;
+            let positions = lexemes.entry(lexeme).or_default();
+            if chars.next_if_eq(&':').is_none() {
+                continue;
             }
-            ExpectedResult::AtLeastOne | ExpectedResult::Multiple => {
-                let error_type = if let Some(ref enum_name) = constraint_enum_name {
-                    format!("super::Error<{}>", enum_name)
-                } else {
-                    "super::ErrorReadOnly".to_string()
+            loop {
+                let mut digits = String::new();
+                while let Some(digit) = chars.next_if(|c| c.is_ascii_digit()) {
+                    digits.push(digit);
+                }
+                let position = digits
+                    .parse::<u32>()
+                    .map_err(|_| format!("invalid lexeme position in tsvector \"{}\"", text))?
+                    .clamp(1, MAX_POSITION);
+                let weight: u16 = match chars.next_if(|c| "ABCDabcd".contains(*c)) {
+                    Some('A' | 'a') => 3,
+                    Some('B' | 'b') => 2,
+                    Some('C' | 'c') => 1,
+                    _ => 0,
                 };
-                format!("Result<Vec<{}>, {}>", base_return_type, error_type)
+                positions.insert(position, weight << 14 | position as u16);
+                if chars.next_if_eq(&',').is_none() {
+                    break;
+                }
             }
         }
-    };
-
-    code.push_str(&format!(
-        "pub async fn {}({}) -> {} {{\n",
-        query.name, params_str, return_type
-    ));
 
-    // Generate function body
-    let function_body = generate_function_body(query, type_info, &base_return_type)?;
-    code.push_str(&function_body);
-
-    code.push_str("}\n");
-
-    Ok(code)
-}
-
-/// Generate struct creation code for multi-column results
+        buf.extend_from_slice(&(lexemes.len() as u32).to_be_bytes());
+        for (lexeme, positions) in lexemes {
+            buf.extend_from_slice(lexeme.as_bytes());
+            buf.push(0);
+            buf.extend_from_slice(&(positions.len() as u16).to_be_bytes());
+            for position in positions.values() {
+                buf.extend_from_slice(&position.to_be_bytes());
+            }
+        }
+        Ok(sqlx::encode::IsNull::No)
+    }
 
-/// Generate the function body using SQLx
-fn generate_function_body(
-    query: &QueryDefinition,
-    type_info: &QueryTypeInfo,
-    return_type: &str,
-) -> Result<String> {
-    let mut body = String::new();
+    impl sqlx::Type<sqlx::Postgres> for TsQuery {
+        fn type_info() -> sqlx::postgres::PgTypeInfo {
+            sqlx::postgres::PgTypeInfo::with_name("tsquery")
+        }
+    }
 
-    // Check if this query has conditional blocks
-    if let Some(parsed_sql) = &type_info.parsed_sql {
-        // Generate dynamic SQL building for conditional queries
-        generate_conditional_function_body(&mut body, query, type_info, parsed_sql, return_type)?;
-    } else {
-        // Generate standard static SQL
-        generate_static_function_body(&mut body, query, type_info, return_type)?;
+    impl sqlx::postgres::PgHasArrayType for TsQuery {
+        fn array_type_info() -> sqlx::postgres::PgTypeInfo {
+            sqlx::postgres::PgTypeInfo::array_of("tsquery")
+        }
     }
 
-    Ok(body)
-}
+    impl<'r> sqlx::Decode<'r, sqlx::Postgres> for TsQuery {
+        fn decode(value: sqlx::postgres::PgValueRef<'r>) -> Result<Self, BoxDynError> {
+            if value.format() == sqlx::postgres::PgValueFormat::Text {
+                return Ok(Self(value.as_str()?.to_string()));
+            }
+            let mut bytes = value.as_bytes()?;
+            let mut items = Vec::new();
+            for _ in 0..read_u32(&mut bytes)? {
+                items.push(read_query_item(&mut bytes)?);
+            }
 
-/// Generate function body for static (non-conditional) SQL queries
-fn generate_static_function_body(
-    body: &mut String,
-    query: &QueryDefinition,
-    type_info: &QueryTypeInfo,
-    return_type: &str,
-) -> Result<()> {
-    let use_multiunzip = query.multiunzip;
-    let use_conditional_diff = query.conditions_type.is_enabled();
-    let use_structured_params = query.parameters_type.is_enabled() && !use_conditional_diff;
-    // Add itertools import if using multiunzip
-    if use_multiunzip {
-        body.push_str("    use itertools::Itertools;\n");
+            // Reversed, the items are in postfix order, so the text is built on a stack of the
+            // operands with the binding strength of their top operator, without recursion
+            let mut operands: Vec<(String, u8)> = Vec::new();
+            for item in items.into_iter().rev() {
+                let operand = match item {
+                    QueryItem::Operand { lexeme, weight, prefix } => (operand_text(&lexeme, weight, prefix), 5),
+                    QueryItem::Operator { operator: OP_NOT, .. } => {
+                        let (operand, strength) = operands.pop().ok_or("missing tsquery operand")?;
+                        (format!("!{}", parenthesize(operand, strength < 4)), 4)
+                    }
+                    QueryItem::Operator { operator, distance } => {
+                        let (right, right_strength) = operands.pop().ok_or("missing tsquery operand")?;
+                        let (left, left_strength) = operands.pop().ok_or("missing tsquery operand")?;
+                        let (symbol, strength) = match operator {
+                            OP_AND => ("&".to_string(), 2),
+                            OP_OR => ("|".to_string(), 1),
+                            _ if distance == 1 => ("<->".to_string(), 3),
+                            _ => (format!("<{}>", distance), 3),
+                        };
+                        let text = format!(
+                            "{} {} {}",
+                            parenthesize(left, left_strength < strength),
+                            symbol,
+                            // `&` and `|` are associative, phrase operators are not
+                            parenthesize(right, right_strength < strength || (operator == OP_PHRASE && right_strength == strength))
+                        );
+                        (text, strength)
+                    }
+                };
+                operands.push(operand);
+            }
+            match (operands.pop(), operands.is_empty()) {
+                (Some((text, _)), true) => Ok(Self(text)),
+                (None, _) => Ok(Self(String::new())),
+                (Some(_), false) => Err("tsquery operand without operator".into()),
+            }
+        }
     }
 
-    // Get pre-computed converted SQL and param names from first variant (base query)
-    let (converted_sql, param_names, _variant_label) = &query.sql_variants[0];
+    /// Write a tsquery in PostgreSQL's binary format
+    /// The value is only written once it is parsed, so nothing is written when it is invalid
+    fn encode_tsquery(text: &str, buf: &mut sqlx::postgres::PgArgumentBuffer) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        // PostgreSQL accepts empty tsqueries as text but rejects them in the binary format
+        if text.trim().is_empty() {
+            return Err("tsquery without lexemes".into());
+        }
+        let items = parse_query(text)?;
+        buf.extend_from_slice(&(items.len() as u32).to_be_bytes());
+        for item in items.iter().rev() {
+            item.write(buf);
+        }
+        Ok(sqlx::encode::IsNull::No)
+    }
 
-    // Build the SQLx query with parameter bindings
-    let raw_string = generate_indented_raw_string_literal(converted_sql);
-    body.push_str(&format!(
-        "    let query = sqlx::query(\n{}\n    );\n",
-        raw_string
-    ));
+    type Chars<'a> = std::iter::Peekable<std::str::Chars<'a>>;
 
-    // Add parameter bindings using method chaining
-    if !type_info.input_types.is_empty() {
-        if use_multiunzip {
-            // Generate multiunzip pattern with struct field extraction
+    /// Item of a tsquery, which the wire format lists in prefix order with the right operand first
+    enum QueryItem {
+        Operand { lexeme: String, weight: u8, prefix: bool },
+        Operator { operator: u8, distance: u16 },
+    }
 
-            // Get clean parameter names for field extraction
-            let original_param_names = parse_parameter_names_from_sql(&query.sql);
-            let clean_param_names: Vec<String> = original_param_names
-                .iter()
-                .map(|name| {
-                    if name.ends_with('?') {
-                        name.trim_end_matches('?').to_string()
-                    } else {
-                        name.clone()
+    impl QueryItem {
+        fn write(&self, buf: &mut Vec<u8>) {
+            match self {
+                QueryItem::Operand { lexeme, weight, prefix } => {
+                    buf.extend_from_slice(&[OPERAND, *weight, u8::from(*prefix)]);
+                    buf.extend_from_slice(lexeme.as_bytes());
+                    buf.push(0);
+                }
+                QueryItem::Operator { operator, distance } => {
+                    buf.extend_from_slice(&[OPERATOR, *operator]);
+                    if *operator == OP_PHRASE {
+                        buf.extend_from_slice(&distance.to_be_bytes());
                     }
-                })
-                .collect();
-
-            // Generate the tuple pattern based on number of types
-            let num_types = type_info.input_types.len();
-            let tuple_vars: Vec<String> = clean_param_names
-                .iter()
-                .map(|name| {
-                    // Convert to snake_case for consistency
-                    to_snake_case(name)
-                })
-                .collect();
-            let tuple_pattern = tuple_vars.join(", ");
+                }
+            }
+        }
+    }
 
-            body.push_str(&format!(
-                "    let ({}): ({}) =\n",
-                tuple_pattern,
-                vec!["Vec<_>"; num_types].join(", ")
-            ));
-            body.push_str("        items\n");
-            body.push_str("            .into_iter()\n");
+    /// Parse a tsquery into its items in postfix order, with the shunting-yard algorithm so that
+    /// deeply nested values cannot overflow the stack
+    /// Operators from loosest to tightest binding: `|`, `&`, `<->` / `<N>`, `!`
+    fn parse_query(text: &str) -> Result<Vec<QueryItem>, BoxDynError> {
+        let mut chars = text.chars().peekable();
+        let mut items = Vec::new();
+        // Pending operators with their distance and binding strength, None for open parentheses
+        let mut operators: Vec<Option<(u8, u16, u8)>> = Vec::new();
+        let mut expects_operand = true;
+        while let Some(c) = skip_whitespace(&mut chars) {
+            if expects_operand {
+                match c {
+                    '!' => {
+                        chars.next();
+                        operators.push(Some((OP_NOT, 0, 4)));
+                    }
+                    '(' => {
+                        chars.next();
+                        operators.push(None);
+                    }
+                    _ => {
+                        items.push(parse_operand(&mut chars)?);
+                        expects_operand = false;
+                    }
+                }
+                continue;
+            }
+            chars.next();
+            let (operator, distance, strength) = match c {
+                ')' => {
+                    loop {
+                        match operators.pop() {
+                            Some(Some((operator, distance, _))) => items.push(QueryItem::Operator { operator, distance }),
+                            Some(None) => break,
+                            None => return Err("unbalanced parenthesis in tsquery".into()),
+                        }
+                    }
+                    continue;
+                }
+                '|' => (OP_OR, 0, 1),
+                '&' => (OP_AND, 0, 2),
+                '<' => (OP_PHRASE, parse_distance(&mut chars)?, 3),
+                _ => return Err(format!("syntax error in tsquery \"{}\"", text).into()),
+            };
+            // Binary operators are left-associative, so the pending operators binding at least as
+            // tightly apply first
+            while let Some(Some((pending, pending_distance, pending_strength))) = operators.last().copied() {
+                if pending_strength < strength {
+                    break;
+                }
+                operators.pop();
+                items.push(QueryItem::Operator { operator: pending, distance: pending_distance });
+            }
+            operators.push(Some((operator, distance, strength)));
+            expects_operand = true;
+        }
+        if expects_operand {
+            return Err("unexpected end of full-text search value".into());
+        }
+        while let Some(pending) = operators.pop() {
+            let Some((operator, distance, _)) = pending else {
+                return Err("unbalanced parenthesis in tsquery".into());
+            };
+            items.push(QueryItem::Operator { operator, distance });
+        }
+        Ok(items)
+    }
 
-            // Generate the map closure to extract struct fields
-            body.push_str(&format!(
-                "            .map(|item| ({}))\n",
-                tuple_vars
-                    .iter()
-                    .map(|field| format!("item.{}", field))
-                    .collect::<Vec<_>>()
-                    .join(", ")
-            ));
+    /// Parse the distance of a phrase operator after its `<`, `-` for `<->`
+    fn parse_distance(chars: &mut Chars) -> Result<u16, BoxDynError> {
+        let distance = if chars.next_if_eq(&'-').is_some() {
+            1
+        } else {
+            let mut digits = String::new();
+            while let Some(digit) = chars.next_if(|c| c.is_ascii_digit()) {
+                digits.push(digit);
+            }
+            digits.parse::<u16>().map_err(|_| "invalid distance in tsquery phrase operator")?
+        };
+        if chars.next_if_eq(&'>').is_none() {
+            return Err("unterminated tsquery phrase operator".into());
+        }
+        Ok(distance)
+    }
 
-            body.push_str("            .multiunzip();\n");
+    /// Parse a lexeme with its optional `:*` prefix flag and weights
+    fn parse_operand(chars: &mut Chars) -> Result<QueryItem, BoxDynError> {
+        let lexeme = parse_lexeme(chars, |c| c.is_whitespace() || ":&|!()<".contains(c))?;
+        let (mut weight, mut prefix) = (0, false);
+        if chars.next_if_eq(&':').is_some() {
+            while let Some(flag) = chars.next_if(|c| "*ABCDabcd".contains(*c)) {
+                match flag.to_ascii_uppercase() {
+                    '*' => prefix = true,
+                    'A' => weight |= 8,
+                    'B' => weight |= 4,
+                    'C' => weight |= 2,
+                    _ => weight |= 1,
+                }
+            }
+        }
+        Ok(QueryItem::Operand { lexeme, weight, prefix })
+    }
 
-            // Bind each array
-            for (i, var) in tuple_vars.iter().enumerate() {
-                let rust_type_info = &type_info.input_types[i];
+    /// Read a tsquery item from the wire format
+    fn read_query_item(bytes: &mut &[u8]) -> Result<QueryItem, BoxDynError> {
+        if read_u8(bytes)? == OPERAND {
+            let weight = read_u8(bytes)?;
+            let prefix = read_u8(bytes)? != 0;
+            let lexeme = read_cstr(bytes)?.to_string();
+            return Ok(QueryItem::Operand { lexeme, weight, prefix });
+        }
+        let operator = read_u8(bytes)?;
+        let distance = match operator {
+            OP_NOT | OP_AND | OP_OR => 0,
+            OP_PHRASE => read_u16(bytes)?,
+            _ => return Err(format!("unknown tsquery operator {}", operator).into()),
+        };
+        Ok(QueryItem::Operator { operator, distance })
+    }
 
-                if rust_type_info.needs_json_wrapper {
-                    // For custom types in arrays, we need to serialize each element
-                    body.push_str(&format!(
-                        "    let {}_json: Result<Vec<serde_json::Value>, _> = {}.into_iter().map(|v| serde_json::to_value(&v)).collect();\n",
-                        var, var
-                    ));
-                    body.push_str(&format!(
-                        "    let query = query.bind({}_json.map_err(|e| sqlx::Error::Encode(Box::new(e)))?);\n",
-                        var
-                    ));
-                } else {
-                    body.push_str(&format!("    let query = query.bind({});\n", var));
+    /// Text of a tsquery operand the way PostgreSQL prints it, e.g. "'rat':*AB"
+    fn operand_text(lexeme: &str, weight: u8, prefix: bool) -> String {
+        let mut text = quote_lexeme(lexeme);
+        if prefix || weight != 0 {
+            text.push(':');
+            if prefix {
+                text.push('*');
+            }
+            for (bit, letter) in [(8, 'A'), (4, 'B'), (2, 'C'), (1, 'D')] {
+                if weight & bit != 0 {
+                    text.push(letter);
                 }
             }
-        } else if use_structured_params {
-            // For parameters_type, bind from params struct
-            for (i, name) in param_names.iter().enumerate() {
-                let clean_name = if name.ends_with('?') {
-                    name.trim_end_matches('?').to_string()
-                } else {
-                    name.clone()
-                };
+        }
+        text
+    }
 
-                let rust_type_info = &type_info.input_types[i];
-                let param_type = &rust_type_info.rust_type;
+    fn parenthesize(text: String, needed: bool) -> String {
+        if needed {
+            format!("( {} )", text)
+        } else {
+            text
+        }
+    }
 
-                // Check if this is a custom type that needs JSON serialization
-                if rust_type_info.needs_json_wrapper {
-                    // For custom types, serialize to JSON before binding
-                    body.push_str(&format!(
-                        "    let query = query.bind(serde_json::to_value(&params.{}).map_err(|e| sqlx::Error::Encode(Box::new(e)))?);\n", 
-                        clean_name
-                    ));
-                } else if param_type == "String" {
-                    // Use reference for String parameters to avoid move issues
-                    body.push_str(&format!(
-                        "    let query = query.bind(&params.{});\n",
-                        clean_name
-                    ));
-                } else if rust_type_info.is_nullable {
-                    // For Option types, we can bind directly
-                    body.push_str(&format!(
-                        "    let query = query.bind(params.{});\n",
-                        clean_name
-                    ));
-                } else {
-                    // For Copy types, we can bind directly
-                    body.push_str(&format!(
-                        "    let query = query.bind(params.{});\n",
-                        clean_name
-                    ));
+    /// Parse a quoted ('it''s') or unquoted lexeme, with backslash escapes
+    fn parse_lexeme(chars: &mut Chars, is_delimiter: impl Fn(char) -> bool) -> Result<String, BoxDynError> {
+        let mut lexeme = String::new();
+        if chars.next_if_eq(&'\'').is_some() {
+            loop {
+                match chars.next() {
+                    Some('\'') if chars.next_if_eq(&'\'').is_some() => lexeme.push('\''),
+                    Some('\'') => break,
+                    Some('\\') => lexeme.push(chars.next().ok_or("unterminated escape in lexeme")?),
+                    Some(c) => lexeme.push(c),
+                    None => return Err("unterminated quoted lexeme".into()),
                 }
             }
-        } else if param_names.is_empty() {
-            // Fallback to generic param names
-            for i in 1..=type_info.input_types.len() {
-                body.push_str(&format!("    let query = query.bind(param_{});\n", i));
-            }
         } else {
-            // Use meaningful parameter names from SQL
-            for (i, name) in param_names.iter().enumerate() {
-                let clean_name = if name.ends_with('?') {
-                    name.trim_end_matches('?').to_string()
-                } else {
-                    name.clone()
-                };
-
-                let rust_type_info = &type_info.input_types[i];
-                let param_type = &rust_type_info.rust_type;
-
-                // Check if this is a custom type that needs JSON serialization
-                if rust_type_info.needs_json_wrapper {
-                    // For custom types, serialize to JSON before binding
-                    body.push_str(&format!(
-                        "    let query = query.bind(serde_json::to_value(&{}).map_err(|e| sqlx::Error::Encode(Box::new(e)))?);\n", 
-                        clean_name
-                    ));
-                } else if param_type == "String" {
-                    // Use reference for String parameters to avoid move issues
-                    body.push_str(&format!("    let query = query.bind(&{});\n", clean_name));
+            while let Some(c) = chars.next_if(|c| !is_delimiter(*c)) {
+                if c == '\\' {
+                    lexeme.push(chars.next().ok_or("unterminated escape in lexeme")?);
                 } else {
-                    body.push_str(&format!("    let query = query.bind({});\n", clean_name));
+                    lexeme.push(c);
                 }
             }
         }
+        if lexeme.is_empty() {
+            return Err("empty lexeme in full-text search value".into());
+        }
+        Ok(lexeme)
     }
 
-    generate_query_execution(body, query, type_info, return_type);
-    Ok(())
-}
-
-/// Generate function body for conditional (dynamic) SQL queries
-fn generate_conditional_function_body(
-    body: &mut String,
-    query: &QueryDefinition,
-    type_info: &QueryTypeInfo,
-    parsed_sql: &crate::types_extractor::ParsedSql,
-    return_type: &str,
-) -> Result<()> {
-    use crate::types_extractor::parse_parameter_names_from_sql;
-
-    let use_conditional_diff = query.conditions_type.is_enabled();
-    let use_structured_params = query.parameters_type.is_enabled() && !use_conditional_diff;
-    // Parse all parameters from the original SQL to get their order and types
-    let all_params = parse_parameter_names_from_sql(&query.sql);
-
-    // Track parameter count for proper numbering across all parts
-    let mut current_param_num = 1usize;
+    /// Quote a lexeme the way PostgreSQL prints it
+    fn quote_lexeme(lexeme: &str) -> String {
+        format!("'{}'", lexeme.replace('\\', "\\\\").replace('\'', "''"))
+    }
 
-    // Start with the base SQL template that has placeholders for conditional blocks
-    let mut sql_template = parsed_sql.base_sql.clone();
+    fn skip_whitespace(chars: &mut Chars) -> Option<char> {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        chars.peek().copied()
+    }
 
-    // Replace non-conditional parameters in the template with numbered placeholders
-    for param_name in parse_parameter_names_from_sql(&parsed_sql.base_sql) {
-        if !param_name.ends_with('?') {
-            // Only replace non-conditional parameters
-            sql_template = sql_template.replace(
-                &format!("#{{{}}}", param_name),
-                &format!("${}", current_param_num),
-            );
-            current_param_num += 1;
+    fn read_bytes<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8], BoxDynError> {
+        if bytes.len() < len {
+            return Err("unexpected end of full-text search value".into());
         }
+        let (head, tail) = bytes.split_at(len);
+        *bytes = tail;
+        Ok(head)
     }
 
-    // Create conditional block info (without fixed parameter numbers)
-    let mut conditional_replacements = Vec::new();
-    for (i, block) in parsed_sql.conditional_blocks.iter().enumerate() {
-        if !block.parameters.is_empty() {
-            let first_param = &block.parameters[0];
-            let block_sql = block.sql_content.clone(); // Keep original parameter syntax for now
-            conditional_replacements.push((i, block_sql, first_param.clone()));
-        }
+    fn read_u8(bytes: &mut &[u8]) -> Result<u8, BoxDynError> {
+        Ok(read_bytes(bytes, 1)?[0])
     }
 
-    // Generate the SQL building code with complete parameter renumbering
-    body.push_str("    let mut final_sql = r\"");
-    body.push_str(&sql_template);
-    body.push_str("\".to_string();\n");
-    body.push_str("    let mut included_params = Vec::new();\n\n");
+    fn read_u16(bytes: &mut &[u8]) -> Result<u16, BoxDynError> {
+        Ok(u16::from_be_bytes(read_bytes(bytes, 2)?.try_into()?))
+    }
 
-    // Replace each conditional block based on parameter presence or diff
-    for (_, block_sql, param_name) in &conditional_replacements {
-        let clean_param = if param_name.ends_with('?') {
-            param_name.trim_end_matches('?')
+    fn read_u32(bytes: &mut &[u8]) -> Result<u32, BoxDynError> {
+        Ok(u32::from_be_bytes(read_bytes(bytes, 4)?.try_into()?))
+    }
+
+    fn read_cstr<'a>(bytes: &mut &'a [u8]) -> Result<&'a str, BoxDynError> {
+        let len = bytes.iter().position(|b| *b == 0).ok_or("unterminated lexeme")?;
+        let lexeme = read_bytes(bytes, len + 1)?;
+        Ok(std::str::from_utf8(&lexeme[..len])?)
+    }
+"#
+    .to_string();
+
+    // sqlx 0.7 has no fallible Encode, the compat module adapts the result
+    for (type_name, encode_fn) in [
+        ("TsVector", "encode_tsvector"),
+        ("TsQuery", "encode_tsquery"),
+    ] {
+        let (encode_result, encoded) = if sqlx_version.needs_compat() {
+            (
+                "super::sqlx_compat::EncodeResult",
+                format!("super::sqlx_compat::encoded({}(&self.0, buf))", encode_fn),
+            )
         } else {
-            param_name
+            (
+                "Result<sqlx::encode::IsNull, BoxDynError>",
+                format!("{}(&self.0, buf)", encode_fn),
+            )
         };
+        code.push_str(&format!(
+            r#"
+    impl<'q> sqlx::Encode<'q, sqlx::Postgres> for {type_name} {{
+        fn encode_by_ref(&self, buf: &mut sqlx::postgres::PgArgumentBuffer) -> {encode_result} {{
+            {encoded}
+        }}
+    }}
+"#
+        ));
+    }
+    code.push_str("}\n");
+    code
+}
 
-        let conditional_block = format!("#[{}]", block_sql);
+/// Arms of the generated `From<sqlx::Error>` matching variants which sqlx 0.7 does not have
+const SQLX_0_8_ERROR_ARMS: [&str; 4] = [
+    "            sqlx::Error::InvalidArgument(_) => {\n                Self::InternalError(\"Invalid argument\".to_string(), error)\n            }\n",
+    "            sqlx::Error::Encode(_) => Self::InternalError(\"Encode error\".to_string(), error),\n",
+    "            sqlx::Error::InvalidSavePointStatement => Self::InternalError(\n                \"Invalid save point statement\".to_string(),\n                error,\n            ),\n",
+    "            sqlx::Error::BeginFailed => Self::InternalError(\"Begin failed\".to_string(), error),\n",
+];
 
-        if use_conditional_diff {
-            // For conditions_type, check if old and new values differ
-            body.push_str(&format!(
+/// Generate the generic Error<C> type for mod.rs
+pub fn generate_generic_error_type(dialect: Dialect, sqlx_version: SqlxVersion) -> String {
+    let mut code = r#"#[derive(Debug, Clone)]
+pub struct ErrorConstraintInfo {
+    /// Name of the violated constraint
+    pub constraint_name: String,
+    pub table_name: String,
+    #[allow(unused)]
+    pub kind: ErrorConstraintKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorConstraintKind {
+    UniqueViolation,
+    ForeignKeyViolation,
+    NotNullViolation,
+    CheckViolation,
+    Other,
+}
+
+impl From<sqlx::error::ErrorKind> for ErrorConstraintKind {
+    fn from(kind: sqlx::error::ErrorKind) -> Self {
+        match kind {
+            sqlx::error::ErrorKind::UniqueViolation => Self::UniqueViolation,
+            sqlx::error::ErrorKind::ForeignKeyViolation => Self::ForeignKeyViolation,
+            sqlx::error::ErrorKind::NotNullViolation => Self::NotNullViolation,
+            sqlx::error::ErrorKind::CheckViolation => Self::CheckViolation,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// A `numeric_as: i64` column whose value is not an integer or is out of the range of i64
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumericOverflow {
+    /// Name of the column
+    pub column: &'static str,
+    /// The decoded value
+    pub value: String,
+}
+
+impl std::fmt::Display for NumericOverflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Value {} of column {} does not fit in i64", self.value, self.column)
+    }
+}
+
+impl std::error::Error for NumericOverflow {}
+
+/// Generic error type
+#[derive(Debug)]
+pub enum Error<C: TryFrom<ErrorConstraintInfo>> {
+    /// Catches the cases when a mutation query violates a constraint
+    /// Type C would be an enum specific to each query.
+    /// It would enumerate variants in pascal case for each constraint that can be violated.
+    /// The list of constaints is inferred automatically by the automodel based on the table schema
+    /// involved in the query.
+    /// The Option<C> is None when the constraint name is not recognized (unknown constraint).
+    ConstraintViolation(Option<C>, ErrorConstraintInfo),
+
+    /// Row not found error
+    RowNotFound,
+
+    /// Row not found, with the `not_found_message` of the query
+    NotFound(String),
+
+    /// The `feature_flag` of the query is switched off
+    FeatureDisabled(&'static str),
+
+    /// The cancellation token of a `_cancellable` function was cancelled before the query finished
+    Cancelled,
+    
+    /// A `numeric_as: i64` column does not fit in i64
+    Overflow(NumericOverflow),
+
+    /// System under stress, timeout
+    PoolTimeout,
+
+    InternalError(String, sqlx::Error),
+}
+
+impl<C: TryFrom<ErrorConstraintInfo>> From<sqlx::Error> for Error<C> {
+    fn from(error: sqlx::Error) -> Self {
+        if let sqlx::Error::ColumnDecode { source, .. } = &error {
+            if let Some(overflow) = source.downcast_ref::<NumericOverflow>() {
+                return Self::Overflow(overflow.clone());
+            }
+        }
+        match &error {
+            sqlx::Error::RowNotFound => Self::RowNotFound,
+            sqlx::Error::ColumnNotFound(col) => {
+                Self::InternalError(format!("Column not found: {}", col), error)
+            }
+            sqlx::Error::Database(db_err)
+                if classify_error(&error) == DbErrorKind::ConstraintViolation =>
+            {
+                // Extract constraint name and table from error
+                let constraint_name = db_err.constraint().unwrap_or("").to_string();
+                let table_name = db_err.table().unwrap_or("").to_string();
+                let kind = db_err.kind();
+
+                let violation = ErrorConstraintInfo {
+                    constraint_name,
+                    table_name,
+                    kind: kind.into(),
+                };
+                Self::ConstraintViolation(violation.clone().try_into().ok(), violation)
+            }
+            sqlx::Error::Database(_) => Self::InternalError("Database error".to_string(), error),
+            sqlx::Error::Configuration(_) => {
+                Self::InternalError("Configuration error".to_string(), error)
+            }
+            sqlx::Error::InvalidArgument(_) => {
+                Self::InternalError("Invalid argument".to_string(), error)
+            }
+            sqlx::Error::Io(_) => Self::InternalError("IO error".to_string(), error),
+            sqlx::Error::Tls(_) => Self::InternalError("TLS error".to_string(), error),
+            sqlx::Error::Protocol(_) => Self::InternalError("Protocol error".to_string(), error),
+            sqlx::Error::TypeNotFound { type_name } => {
+                Self::InternalError(format!("Type not found: {}", type_name), error)
+            }
+            sqlx::Error::ColumnIndexOutOfBounds { index, len } => Self::InternalError(
+                format!("Column index out of bounds: index {}, len {}", index, len),
+                error,
+            ),
+            sqlx::Error::ColumnDecode { index, source } => Self::InternalError(
+                format!("Column decode error at index {}: {}", index, source),
+                error,
+            ),
+            sqlx::Error::Encode(_) => Self::InternalError("Encode error".to_string(), error),
+            sqlx::Error::Decode(_) => Self::InternalError("Decode error".to_string(), error),
+            sqlx::Error::AnyDriverError(_) => {
+                Self::InternalError("Driver error".to_string(), error)
+            }
+            sqlx::Error::PoolTimedOut => Self::PoolTimeout,
+            sqlx::Error::PoolClosed => Self::InternalError("Pool closed".to_string(), error),
+            sqlx::Error::WorkerCrashed => Self::InternalError("Worker crashed".to_string(), error),
+            sqlx::Error::Migrate(_) => Self::InternalError("Migration error".to_string(), error),
+            sqlx::Error::InvalidSavePointStatement => Self::InternalError(
+                "Invalid save point statement".to_string(),
+                error,
+            ),
+            sqlx::Error::BeginFailed => Self::InternalError("Begin failed".to_string(), error),
+            _ => Self::InternalError("Unknown sqlx error".to_string(), error),
+        }
+    }
+}
+
+impl<C> std::fmt::Display for Error<C>
+where
+    C: std::fmt::Debug + TryFrom<ErrorConstraintInfo>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::ConstraintViolation(constraint, info) => {
+                if let Some(c) = constraint {
+                    write!(f, "Constraint violation: {:#?}", c)
+                } else {
+                    write!(f, "Unknown constraint violation: {} on table {}", info.constraint_name, info.table_name)
+                }
+            }
+            Error::RowNotFound => write!(f, "Row not found"),
+            Error::NotFound(message) => write!(f, "Row not found: {}", message),
+            Error::FeatureDisabled(flag) => write!(f, "Feature disabled: {}", flag),
+            Error::Cancelled => write!(f, "Cancelled"),
+            Error::Overflow(overflow) => write!(f, "Overflow: {}", overflow),
+            Error::PoolTimeout => write!(f, "Pool timeout"),
+            Error::InternalError(msg, err) => {
+                write!(f, "Internal error: {}, caused by: {}", msg, err)
+            }
+        }
+    }
+}
+
+impl<C> std::error::Error for Error<C>
+where
+    C: std::fmt::Debug + TryFrom<ErrorConstraintInfo>,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::InternalError(_, err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Generic error type for read-only queries
+#[derive(Debug)]
+pub enum ErrorReadOnly {
+    /// Row not found error
+    RowNotFound,
+
+    /// Row not found, with the `not_found_message` of the query
+    NotFound(String),
+
+    /// The `feature_flag` of the query is switched off
+    FeatureDisabled(&'static str),
+
+    /// The cancellation token of a `_cancellable` function was cancelled before the query finished
+    Cancelled,
+
+    /// A `numeric_as: i64` column does not fit in i64
+    Overflow(NumericOverflow),
+
+    /// System under stress, timeout
+    PoolTimeout,
+
+    InternalError(String, sqlx::Error),
+}
+
+impl From<sqlx::Error> for ErrorReadOnly {
+    fn from(error: sqlx::Error) -> Self {
+        Error::<ErrorConstraintInfo>::from(error).into()
+    }
+}
+
+impl<C: TryFrom<ErrorConstraintInfo>> From<ErrorReadOnly> for Error<C> {
+    fn from(error: ErrorReadOnly) -> Self {
+        match error {
+            ErrorReadOnly::RowNotFound => Error::RowNotFound,
+            ErrorReadOnly::NotFound(message) => Error::NotFound(message),
+            ErrorReadOnly::FeatureDisabled(flag) => Error::FeatureDisabled(flag),
+            ErrorReadOnly::Cancelled => Error::Cancelled,
+            ErrorReadOnly::Overflow(overflow) => Error::Overflow(overflow),
+            ErrorReadOnly::PoolTimeout => Error::PoolTimeout,
+            ErrorReadOnly::InternalError(msg, err) => Error::InternalError(msg, err),
+        }
+    }
+}
+
+impl From<Error<ErrorConstraintInfo>> for ErrorReadOnly {
+    fn from(error: Error<ErrorConstraintInfo>) -> Self {
+        match error {
+            Error::RowNotFound => Self::RowNotFound,
+            Error::NotFound(message) => Self::NotFound(message),
+            Error::FeatureDisabled(flag) => Self::FeatureDisabled(flag),
+            Error::Cancelled => Self::Cancelled,
+            Error::Overflow(overflow) => Self::Overflow(overflow),
+            Error::PoolTimeout => Self::PoolTimeout,
+            Error::InternalError(msg, err) => Self::InternalError(msg, err),
+            Error::ConstraintViolation(c, info) => Self::InternalError(
+                "Constraint violation in read-only query".to_string(),
+                sqlx::Error::Protocol(format!(
+                    "Constraint violation in read-only query: constraint={}, table={}, parsed={:?}",
+                    info.constraint_name, info.table_name, c
+                )),
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorReadOnly {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErrorReadOnly::RowNotFound => write!(f, "Row not found"),
+            ErrorReadOnly::NotFound(message) => write!(f, "Row not found: {}", message),
+            ErrorReadOnly::FeatureDisabled(flag) => write!(f, "Feature disabled: {}", flag),
+            ErrorReadOnly::Cancelled => write!(f, "Cancelled"),
+            ErrorReadOnly::Overflow(overflow) => write!(f, "Overflow: {}", overflow),
+            ErrorReadOnly::PoolTimeout => write!(f, "Pool timeout"),
+            ErrorReadOnly::InternalError(msg, err) => {
+                write!(f, "Internal error: {}, caused by: {}", msg, err)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ErrorReadOnly {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InternalError(_, err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+"#
+    .to_string();
+    code.push_str(&generate_error_classification(dialect));
+    code.push_str(
+        r#"
+impl<C: TryFrom<ErrorConstraintInfo>> Error<C> {
+    /// Broad category of the error, see `classify_error`
+    pub fn kind(&self) -> DbErrorKind {
+        match self {
+            Error::ConstraintViolation(_, _) => DbErrorKind::ConstraintViolation,
+            Error::RowNotFound
+            | Error::NotFound(_)
+            | Error::FeatureDisabled(_)
+            | Error::Cancelled
+            | Error::Overflow(_) => DbErrorKind::Other,
+            Error::PoolTimeout => DbErrorKind::Timeout,
+            Error::InternalError(_, err) => classify_error(err),
+        }
+    }
+
+    /// Whether no row was found, with or without a `not_found_message`
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, Error::RowNotFound | Error::NotFound(_))
+    }
+
+    /// Replace RowNotFound with NotFound carrying the `not_found_message` of the query
+    pub fn with_not_found_message(self, message: String) -> Self {
+        match self {
+            Error::RowNotFound => Error::NotFound(message),
+            error => error,
+        }
+    }
+}
+
+impl ErrorReadOnly {
+    /// Broad category of the error, see `classify_error`
+    pub fn kind(&self) -> DbErrorKind {
+        match self {
+            ErrorReadOnly::RowNotFound
+            | ErrorReadOnly::NotFound(_)
+            | ErrorReadOnly::FeatureDisabled(_)
+            | ErrorReadOnly::Cancelled
+            | ErrorReadOnly::Overflow(_) => DbErrorKind::Other,
+            ErrorReadOnly::PoolTimeout => DbErrorKind::Timeout,
+            ErrorReadOnly::InternalError(_, err) => classify_error(err),
+        }
+    }
+
+    /// Whether no row was found, with or without a `not_found_message`
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, ErrorReadOnly::RowNotFound | ErrorReadOnly::NotFound(_))
+    }
+
+    /// Replace RowNotFound with NotFound carrying the `not_found_message` of the query
+    pub fn with_not_found_message(self, message: String) -> Self {
+        match self {
+            ErrorReadOnly::RowNotFound => ErrorReadOnly::NotFound(message),
+            error => error,
+        }
+    }
+}
+
+"#,
+    );
+
+    // Variants added in sqlx 0.8 fall through to the catch-all arm with sqlx 0.7
+    if sqlx_version.needs_compat() {
+        for arm in SQLX_0_8_ERROR_ARMS {
+            let gated_arm = match sqlx_version {
+                SqlxVersion::Feature => {
+                    format!("            #[cfg(not(feature = \"sqlx-0-7\"))]\n{}", arm)
+                }
+                _ => String::new(),
+            };
+            code = code.replace(arm, &gated_arm);
+        }
+    }
+    code
+}
+
+/// Generate DbErrorKind and `classify_error`, including the SQLSTATEs specific to the dialect
+fn generate_error_classification(dialect: Dialect) -> String {
+    let (database, connection_loss, dialect_arms) = match dialect {
+        Dialect::Postgres => ("PostgreSQL", "", ""),
+        Dialect::Cockroach => (
+            "CockroachDB",
+            ",\n    /// or the outcome of a statement is unknown as its gateway node failed (40003)",
+            "            Some(\"40003\") => DbErrorKind::ConnectionLoss,\n",
+        ),
+    };
+    format!(
+        r#"/// Broad category of a database error, for retry and compensation logic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DbErrorKind {{
+    /// Integrity constraint violated (SQLSTATE class 23)
+    ConstraintViolation,
+    /// Transaction aborted by a serialization failure or deadlock, safe to retry (40001, 40P01)
+    SerializationFailure,
+    /// Connection failed, was closed or the server is shutting down (class 08, 57P01-57P03){connection_loss}
+    ConnectionLoss,
+    /// Statement, lock or pool acquisition timed out (57014, 55P03, 25P03)
+    Timeout,
+    /// Any other error
+    Other,
+}}
+
+/// Classify an error by its {database} SQLSTATE or connection failure
+pub fn classify_error(error: &sqlx::Error) -> DbErrorKind {{
+    match error {{
+        sqlx::Error::Database(db_err) => match db_err.code().as_deref() {{
+            Some("40001") | Some("40P01") => DbErrorKind::SerializationFailure,
+            Some("57014") | Some("55P03") | Some("25P03") => DbErrorKind::Timeout,
+            Some("57P01") | Some("57P02") | Some("57P03") => DbErrorKind::ConnectionLoss,
+{dialect_arms}            Some(code) if code.starts_with("23") => DbErrorKind::ConstraintViolation,
+            Some(code) if code.starts_with("08") => DbErrorKind::ConnectionLoss,
+            _ => DbErrorKind::Other,
+        }},
+        sqlx::Error::PoolTimedOut => DbErrorKind::Timeout,
+        sqlx::Error::Io(_)
+        | sqlx::Error::Tls(_)
+        | sqlx::Error::PoolClosed
+        | sqlx::Error::WorkerCrashed => DbErrorKind::ConnectionLoss,
+        _ => DbErrorKind::Other,
+    }}
+}}
+"#
+    )
+}
+
+/// Generate per-query constraint enum with TryFrom<ErrorConstraintInfo> implementation
+pub fn generate_query_constraint_enum(
+    enum_name: &str,
+    constraints: &[crate::types_extractor::ConstraintInfo],
+    custom_derives: &[String],
+) -> String {
+    use std::collections::HashSet;
+
+    let mut code = String::new();
+
+    // Deduplicate constraints by name
+    let mut seen_constraints: HashSet<String> = HashSet::new();
+    let mut unique_constraints = Vec::new();
+    for constraint in constraints {
+        if !seen_constraints.contains(&constraint.name) {
+            seen_constraints.insert(constraint.name.clone());
+            unique_constraints.push(constraint);
+        }
+    }
+
+    code.push_str(&format!(
+        "/// Constraint violations specific to this query\n"
+    ));
+
+    // Build derive attribute with Debug as default plus custom derives
+    let mut all_derives = vec!["Debug".to_string()];
+    all_derives.extend(custom_derives.iter().cloned());
+
+    // Remove duplicates while preserving order
+    let mut seen = HashSet::new();
+    all_derives.retain(|d| seen.insert(d.clone()));
+
+    code.push_str(&format!("#[derive({})]\n", all_derives.join(", ")));
+    code.push_str(&format!("pub enum {} {{\n", enum_name));
+
+    // Generate a variant for each unique constraint
+    for constraint in &unique_constraints {
+        let variant_name = to_pascal_case(&constraint.name);
+        code.push_str(&format!(
+            "    /// Constraint: {} on table {}\n",
+            constraint.name, constraint.table_name
+        ));
+        code.push_str(&format!("    {},\n", variant_name));
+    }
+
+    code.push_str("}\n\n");
+
+    // Generate TryFrom<ErrorConstraintInfo> implementation
+    code.push_str(&format!(
+        "impl TryFrom<super::ErrorConstraintInfo> for {} {{\n",
+        enum_name
+    ));
+    code.push_str("    type Error = ();\n\n");
+    code.push_str(
+        "    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {\n",
+    );
+    code.push_str("        match info.constraint_name.as_str() {\n");
+
+    for constraint in &unique_constraints {
+        let variant_name = to_pascal_case(&constraint.name);
+        code.push_str(&format!(
+            "            \"{}\" => Ok(Self::{}),\n",
+            constraint.name, variant_name
+        ));
+    }
+
+    code.push_str("            _ => Err(()),\n");
+    code.push_str("        }\n");
+    code.push_str("    }\n");
+    code.push_str("}\n");
+
+    code
+}
+
+/// Remove statistics from EXPLAIN plan lines to make output stable
+/// Removes patterns like: (cost=X..Y rows=Z width=W)
+fn remove_plan_statistics(line: &str) -> String {
+    use regex::Regex;
+    use std::sync::OnceLock;
+
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let re = RE.get_or_init(|| {
+        // Match patterns like (cost=10000000000.00..10000000003.74 rows=59 width=64)
+        // The pattern handles:
+        // - Optional cost=X..Y
+        // - Optional rows=Z
+        // - Optional width=W
+        // All enclosed in parentheses with spaces
+        Regex::new(r"\s*\((?:cost=[\d.]+\.\.[\d.]+\s*)?(?:rows=\d+\s*)?(?:width=\d+\s*)?\)")
+            .unwrap()
+    });
+
+    re.replace_all(line, "").trim_end().to_string()
+}
+
+/// Generate tracing::instrument attribute for a function
+/// With telemetry `max_param_length` or `max_param_elements`, the included parameters are
+/// recorded as fields formatted by `recorded_values`, given as (name, value expression);
+/// parameters without a value expression are not recorded
+fn generate_tracing_attribute(
+    query: &QueryDefinition,
+    param_names: &[String],
+    recorded_values: &[(String, String)],
+) -> String {
+    use std::collections::HashSet;
+
+    let telemetry_level = query.telemetry.level;
+    if telemetry_level == TelemetryLevel::None {
+        return String::new();
+    }
+
+    let mut attributes = Vec::new();
+
+    // No need for explicit span name - tracing::instrument will use the function name automatically
+
+    // Add instrumentation level
+    let level = match telemetry_level {
+        TelemetryLevel::Info => "info",
+        TelemetryLevel::Debug => "debug",
+        TelemetryLevel::Trace => "trace",
+        TelemetryLevel::None => unreachable!(),
+    };
+    attributes.push(format!("level = \"{}\"", level));
+
+    // Determine parameter skipping strategy
+    let mut skip_params = HashSet::new();
+    skip_params.insert(if query.requires_session {
+        "session".to_string()
+    } else {
+        "executor".to_string()
+    });
+
+    // Parameter inclusion logic (independent of telemetry level)
+    if let Some(include_params) = &query.telemetry.include_params {
+        if include_params.is_empty() {
+            // Empty include_params list means skip all parameters
+            skip_params.extend(param_names.iter().cloned());
+        } else {
+            let included: Vec<String> = include_params
+                .iter()
+                .filter(|param| param_names.contains(param))
+                .cloned()
+                .collect();
+
+            if !included.is_empty() {
+                // Skip parameters not in the include list
+                for param in param_names {
+                    if !included.contains(param) {
+                        skip_params.insert(param.clone());
+                    }
+                }
+            } else {
+                // No valid included parameters, skip all
+                skip_params.extend(param_names.iter().cloned());
+            }
+        }
+    } else {
+        // No include_params specified, skip all parameters
+        skip_params.extend(param_names.iter().cloned());
+    }
+
+    // Sensitive parameters are never recorded, even when listed in include_params
+    skip_params.extend(
+        param_names
+            .iter()
+            .filter(|param| query.sensitivity.contains_key(param.as_str()))
+            .cloned(),
+    );
+
+    // Check if we should use skip_all (all params including query params are skipped)
+    let total_params = param_names.len() + 1; // +1 for executor
+    let should_use_skip_all = param_names.len() > 0 && skip_params.len() == total_params;
+
+    // Parameters cut to the telemetry limits are recorded as fields instead of arguments
+    // Field names are parsed as any identifier, so keywords need no raw prefix
+    let mut fields = Vec::new();
+    if query.telemetry.limits_params() {
+        let mut recorded = HashSet::new();
+        for param in param_names {
+            if skip_params.contains(param) || !recorded.insert(param) {
+                continue;
+            }
+            if let Some((_, value)) = recorded_values.iter().find(|(name, _)| name == param) {
+                fields.push(format!("{} = %{}", param, value));
+            }
+        }
+        skip_params.extend(param_names.iter().cloned());
+    }
+    let has_field_expressions = !fields.is_empty();
+
+    // Generate skip attribute
+    if should_use_skip_all || query.telemetry.limits_params() {
+        attributes.push("skip_all".to_string());
+    } else if skip_params.len() > 1 {
+        // More than just executor
+        let mut skip_vec: Vec<_> = skip_params
+            .into_iter()
+            .map(|param| to_rust_ident(&param))
+            .collect();
+        skip_vec.sort(); // Sort for consistent output
+        attributes.push(format!("skip({})", skip_vec.join(", ")));
+    } else {
+        attributes.push("skip_all".to_string());
+    }
+
+    // Determine whether to include SQL based on configuration (default false)
+    let should_include_sql = query.telemetry.include_sql;
+    if should_include_sql {
+        let escaped_sql = query
+            .sql
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n");
+        fields.push(format!("sql = \"{}\"", escaped_sql));
+    }
+
+    // rustfmt leaves attributes with field expressions as they are, so they are laid out here
+    if has_field_expressions {
+        let mut attribute = "#[tracing::instrument(\n".to_string();
+        for attr in &attributes {
+            attribute.push_str(&format!("    {},\n", attr));
+        }
+        attribute.push_str("    fields(\n");
+        for field in &fields {
+            attribute.push_str(&format!("        {},\n", field));
+        }
+        attribute.push_str("    )\n)]\n");
+        return attribute;
+    }
+    if !fields.is_empty() {
+        attributes.push(format!("fields({})", fields.join(", ")));
+    }
+    format!("#[tracing::instrument({})]\n", attributes.join(", "))
+}
+
+/// Generate an indented raw string literal with proper formatting
+fn generate_indented_raw_string_literal(sql: &str) -> String {
+    // Find a delimiter that doesn't appear in the SQL
+    let mut delimiter_count = 0;
+    let delimiter = loop {
+        let delimiter = "#".repeat(delimiter_count);
+        let pattern = format!("\"{}\"", delimiter);
+        if !sql.contains(&pattern) {
+            break delimiter;
+        }
+        delimiter_count += 1;
+    };
+
+    // Add proper indentation to each line of SQL
+    let indented_sql = sql
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            if i == 0 {
+                line.to_string() // First line doesn't need extra indentation
+            } else {
+                format!("        {}", line) // Subsequent lines get 8 spaces of indentation
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!(
+        "        r{delimiter}\"{indented_sql}\"{delimiter}",
+        delimiter = delimiter,
+        indented_sql = indented_sql
+    )
+}
+
+/// Generate Rust function code for a SQL query without enum definitions
+/// (assumes enums are already defined elsewhere in the module)
+pub fn generate_function_code_without_enums(
+    query: &QueryDefinition,
+    type_info: &QueryTypeInfo,
+    emitted_struct_names: &mut std::collections::HashSet<String>,
+    constraints: &[crate::types_extractor::ConstraintInfo],
+    performance_analysis: &Option<crate::query_definition_rt::PerformanceAnalysis>,
+    is_mutation: bool,
+) -> Result<String> {
+    let mut code = String::new();
+
+    // Generate per-query constraint enum if there are constraints
+    let constraint_enum_name = constraint_enum_name(query, constraints);
+    if let Some(enum_name) = &constraint_enum_name {
+        // Only generate the enum if it hasn't been emitted yet
+        if !emitted_struct_names.contains(enum_name) {
+            code.push_str(&generate_query_constraint_enum(
+                enum_name,
+                constraints,
+                &query.error_type_derives,
+            ));
+            code.push('\n');
+            emitted_struct_names.insert(enum_name.clone());
+        }
+    }
+
+    // Extract clean parameter names directly from the SQL for function signature
+    let original_param_names = parse_parameter_names_from_sql(&query.sql);
+    let clean_param_names: Vec<String> = original_param_names
+        .iter()
+        .map(|name| {
+            if name.ends_with('?') {
+                name.trim_end_matches('?').to_string()
+            } else {
+                name.clone()
+            }
+        })
+        .collect();
+
+    let use_multiunzip = query.multiunzip;
+
+    // Determine if conditions_type is enabled and get the struct name override
+    let (use_conditional_diff, diff_struct_name_override) = match &query.conditions_type {
+        crate::query_definition::ConditionsType::Enabled(true) => (true, None),
+        crate::query_definition::ConditionsType::Named(name) => (true, Some(name.as_str())),
+        _ => (false, None),
+    };
+
+    // Determine if parameters_type is enabled and get the struct name
+    let (use_structured_params, struct_name_override) = match &query.parameters_type {
+        crate::query_definition::ParametersType::Enabled(true) if !use_conditional_diff => {
+            (true, None)
+        }
+        crate::query_definition::ParametersType::Named(name) if !use_conditional_diff => {
+            (true, Some(name.as_str()))
+        }
+        _ => (false, None),
+    };
+
+    // Generate input struct for multiunzip if needed
+    if use_multiunzip {
+        let struct_name = format!("{}Record", to_pascal_case(&query.name));
+        if !emitted_struct_names.contains(&struct_name) {
+            if let Some(input_struct) = generate_multiunzip_input_struct(
+                &query.name,
+                &clean_param_names,
+                &type_info.input_types,
+                &query.parameters_type_derives,
+                query.auto_derives,
+                &query.sensitivity,
+                query.redact_debug,
+            ) {
+                code.push_str(&input_struct);
+                code.push('\n');
+                emitted_struct_names.insert(struct_name);
+            }
+        }
+    }
+
+    // Generate diff struct for conditions_type if needed
+    if use_conditional_diff && type_info.parsed_sql.is_some() {
+        let struct_name = if let Some(override_name) = diff_struct_name_override {
+            override_name.to_string()
+        } else {
+            format!("{}Params", to_pascal_case(&query.name))
+        };
+
+        if !emitted_struct_names.contains(&struct_name) {
+            if let Some(diff_struct) = generate_conditional_diff_struct(
+                &struct_name,
+                &original_param_names,
+                &type_info.input_types,
+                &query.conditions_type_derives,
+                query.auto_derives,
+                &query.sensitivity,
+                query.redact_debug,
+            ) {
+                code.push_str(&diff_struct);
+                code.push('\n');
+                if query.dynamic {
+                    let fields = dynamic_fields_for_params(
+                        &original_param_names,
+                        &type_info.input_types,
+                        true,
+                    );
+                    code.push_str(&generate_dynamic_map_impl(&struct_name, &fields));
+                    code.push('\n');
+                }
+                emitted_struct_names.insert(struct_name);
+            }
+        }
+    }
+
+    // Generate struct for parameters_type if needed (but not for conditions_type)
+    if use_structured_params {
+        let struct_name = if let Some(override_name) = struct_name_override {
+            override_name.to_string()
+        } else {
+            format!("{}Params", to_pascal_case(&query.name))
+        };
+
+        if !emitted_struct_names.contains(&struct_name) {
+            if let Some(params_struct) = generate_structured_params_struct(
+                struct_name_override.unwrap_or(&query.name),
+                &clean_param_names,
+                &type_info.input_types,
+                &query.parameters_type_derives,
+                query.auto_derives,
+                &query.sensitivity,
+                query.redact_debug,
+            ) {
+                code.push_str(&params_struct);
+                code.push('\n');
+                if query.dynamic {
+                    let fields = dynamic_fields_for_params(
+                        &clean_param_names,
+                        &type_info.input_types,
+                        false,
+                    );
+                    code.push_str(&generate_dynamic_map_impl(&struct_name, &fields));
+                    code.push('\n');
+                }
+                emitted_struct_names.insert(struct_name);
+            }
+        }
+    }
+
+    // Generate result struct if needed (but no enums)
+    // Generate if: multiple columns OR single column with explicit return_type configured
+    // Tuple-shaped results don't need a struct
+    let should_generate_struct = (type_info.output_types.len() > 1 || query.return_type.is_some())
+        && !returns_tuple(query, type_info);
+
+    // Row mappers take and return the result struct
+    if (query.map_row.is_some() || query.try_map_row.is_some()) && !should_generate_struct {
+        anyhow::bail!(
+            "Query '{}' uses map_row or try_map_row, which requires a result struct (multiple output columns or return_type, without return_shape: tuple)",
+            query.name
+        );
+    }
+
+    if should_generate_struct {
+        let result_struct_name = if let Some(ref custom_name) = query.return_type {
+            custom_name.to_string()
+        } else {
+            format!("{}Item", to_pascal_case(&query.name))
+        };
+
+        if !emitted_struct_names.contains(&result_struct_name) {
+            if let Some(struct_def) = generate_result_struct_with_name(
+                &result_struct_name,
+                &type_info.output_types,
+                &query.return_type_derives,
+                query.auto_derives,
+                &query.sensitivity,
+                query.redact_debug,
+                &query.flatten,
+            ) {
+                code.push_str(&struct_def);
+                code.push('\n');
+                if query.dynamic {
+                    let fields: Vec<(String, bool, DynamicFieldKind)> = type_info
+                        .output_types
+                        .iter()
+                        .map(|col| {
+                            (
+                                to_snake_case(&col.name),
+                                col.rust_type.is_nullable,
+                                dynamic_field_kind(&col.rust_type),
+                            )
+                        })
+                        .collect();
+                    code.push_str(&generate_dynamic_map_impl(&result_struct_name, &fields));
+                    code.push('\n');
+                }
+                emitted_struct_names.insert(result_struct_name);
+            }
+        }
+    }
+
+    // Generate the filter enum for filter_type, whose conditions replace the WHERE clause
+    let filter = if query.filter_type.is_enabled() {
+        let enum_name = match query.filter_type.get_enum_name() {
+            Some(name) => name.to_string(),
+            None => format!("{}Filter", to_pascal_case(&query.name)),
+        };
+        if !emitted_struct_names.insert(enum_name.clone()) {
+            anyhow::bail!(
+                "Query '{}' filter_type '{}' is already generated for another query",
+                query.name,
+                enum_name
+            );
+        }
+        let parts = filter_query_parts(&query.sql)
+            .map_err(|e| anyhow::anyhow!("Query '{}' uses filter_type, which {}", query.name, e))?;
+        let conditions: Vec<_> = parts
+            .conditions
+            .iter()
+            .filter_map(|condition| {
+                let index = clean_param_names
+                    .iter()
+                    .position(|p| *p == condition.param)?;
+                Some((condition, type_info.input_types.get(index)?))
+            })
+            .collect();
+        code.push_str(&generate_filter_enum(
+            &enum_name,
+            &query.name,
+            &conditions,
+            query.auto_derives,
+            &query.sensitivity,
+            query.redact_debug,
+        ));
+        code.push('\n');
+        Some((enum_name, parts))
+    } else {
+        None
+    };
+    // Parameters of the function, the filter parameters are replaced by the filters
+    let signature_param_names: Vec<String> = match &filter {
+        Some((_, parts)) => std::iter::once("filters".to_string())
+            .chain(
+                clean_param_names
+                    .iter()
+                    .filter(|name| !is_filter_param(parts, name))
+                    .cloned(),
+            )
+            .collect(),
+        None => clean_param_names.clone(),
+    };
+
+    // Generate function documentation
+    if let Some(description) = &query.description {
+        code.push_str(&format!("/// {}\n", description));
+    }
+
+    // Document the column order of tuple-shaped results
+    if returns_tuple(query, type_info) {
+        let columns: Vec<String> = type_info
+            .output_types
+            .iter()
+            .map(|col| format!("`{}`", col.name))
+            .collect();
+        if query.description.is_some() {
+            code.push_str("///\n");
+        }
+        code.push_str(&format!("/// Returns tuples of ({})\n", columns.join(", ")));
+    }
+
+    // The statement was rewritten from the DELETE of the SQL file
+    if let Some(archive_tables) = &query.archive_to {
+        if query.description.is_some() || returns_tuple(query, type_info) {
+            code.push_str("///\n");
+        }
+        code.push_str(&format!(
+            "/// Moves the deleted rows of `{}` to `{}` and returns the archived rows\n",
+            archive_tables.source, archive_tables.archive
+        ));
+    }
+
+    // Names are resolved with the search_path the query was analyzed with
+    if !query.search_path.is_empty() {
+        if query.description.is_some()
+            || returns_tuple(query, type_info)
+            || query.archive_to.is_some()
+        {
+            code.push_str("///\n");
+        }
+        if query.search_path_at_runtime {
+            code.push_str(&format!(
+                "/// Runs in a transaction with `SET LOCAL search_path TO {}`\n",
+                query.search_path.join(", ")
+            ));
+        } else {
+            code.push_str(&format!(
+                "/// Requires the search_path of the connection to be `{}`\n",
+                query.search_path.join(", ")
+            ));
+        }
+    }
+
+    // Tables from the analysis, to find the functions affected by an incident on a table
+    if !type_info.tables.is_empty() {
+        if query.description.is_some()
+            || returns_tuple(query, type_info)
+            || query.archive_to.is_some()
+            || !query.search_path.is_empty()
+        {
+            code.push_str("///\n");
+        }
+        let tables: Vec<String> = type_info
+            .tables
+            .iter()
+            .map(|table| format!("`{}`", table))
+            .collect();
+        code.push_str(&format!("/// Tables: {}\n", tables.join(", ")));
+    }
+
+    // Add query plan as documentation comments if available
+    if let Some(perf_analysis) = performance_analysis {
+        if let Some(plan) = &perf_analysis.query_plan {
+            code.push_str("///\n");
+            code.push_str("/// Query Plan:\n");
+            for line in plan.lines() {
+                // Remove cost estimates, row counts, and width from the plan to avoid instability
+                // These values change between runs and make the generated code unstable
+                let cleaned_line = remove_plan_statistics(line);
+                code.push_str(&format!("/// {}\n", cleaned_line));
+            }
+        }
+    }
+
+    // The shard key of sharded queries is an argument too and can be recorded in the span
+    let traced_param_names: Vec<String> = query
+        .shard_by
+        .iter()
+        .cloned()
+        .chain(signature_param_names.iter().cloned())
+        .collect();
+    // Lists are recorded with their first max_param_elements elements
+    let list_params: Vec<String> = clean_param_names
+        .iter()
+        .zip(&type_info.input_types)
+        .filter(|(_, rust_type)| is_list_param(rust_type))
+        .map(|(name, _)| name.clone())
+        .chain(filter.iter().map(|_| "filters".to_string()))
+        .collect();
+    // Parameters cut to the telemetry limits are read from the arguments or the params struct
+    let recorded_values: Vec<(String, String)> = if use_multiunzip || use_conditional_diff {
+        Vec::new()
+    } else {
+        traced_param_names
+            .iter()
+            .map(|name| {
+                let value = if use_structured_params && query.shard_by.as_ref() != Some(name) {
+                    format!("params.{}", to_rust_ident(name))
+                } else {
+                    to_rust_ident(name)
+                };
+                let value = limited_param_value(query, &value, list_params.contains(name));
+                (name.clone(), value)
+            })
+            .collect()
+    };
+    let tracing_attribute =
+        generate_tracing_attribute(query, &traced_param_names, &recorded_values);
+    // Functions gated on a cargo feature are replaced by a stub when it is not enabled
+    let feature_cfg = query
+        .feature_flag
+        .as_deref()
+        .filter(|_| query.feature_flag_cfg);
+    if let Some(flag) = feature_cfg {
+        code.push_str(&format!("#[cfg(feature = \"{}\")]\n", flag));
+    }
+    code.push_str(&tracing_attribute);
+
+    let input_params = if let Some((enum_name, parts)) = &filter {
+        // For filter_type, the filters followed by the parameters outside the WHERE clause
+        let (param_types, param_names): (Vec<RustType>, Vec<String>) = type_info
+            .input_types
+            .iter()
+            .zip(&clean_param_names)
+            .filter(|(_, name)| !is_filter_param(parts, name))
+            .map(|(rust_type, name)| (rust_type.clone(), name.clone()))
+            .unzip();
+        let other_params = generate_input_params_with_names(&param_types, &param_names);
+        if other_params.is_empty() {
+            format!("filters: Vec<{}>", enum_name)
+        } else {
+            format!("filters: Vec<{}>, {}", enum_name, other_params)
+        }
+    } else if use_multiunzip {
+        // For multiunzip, generate a single Vec<StructName> parameter
+        generate_multiunzip_param(&query.name, "items")
+    } else if use_conditional_diff && type_info.parsed_sql.is_some() {
+        // For conditions_type, generate old and new struct parameters
+        generate_conditional_diff_params(
+            &query.name,
+            &original_param_names,
+            &type_info.input_types,
+            diff_struct_name_override.as_deref(),
+        )
+    } else if use_structured_params {
+        // For parameters_type, generate a single struct parameter
+        generate_structured_params_signature(&query.name, struct_name_override.as_deref())
+    } else {
+        generate_input_params_with_names(&type_info.input_types, &clean_param_names)
+    };
+
+    let base_return_type = base_return_type(query, type_info);
+
+    // Generate function signature
+    // Temp tables must be created, filled and joined on the same connection
+    let use_temp_tables = query.large_list_strategy == LargeListStrategy::TempTable;
+    let executor_param = if query.requires_session {
+        "session: &mut super::Session"
+    } else if use_temp_tables {
+        "executor: &mut sqlx::PgConnection"
+    } else if query.search_path_at_runtime {
+        // The search_path is set in a transaction begun on the executor
+        "executor: impl sqlx::Acquire<'_, Database = sqlx::Postgres>"
+    } else {
+        "executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>"
+    };
+    let executor_param_len = executor_param.len();
+    // Sharded queries take the shard key right after the executor
+    let executor_param = match &query.shard_by {
+        Some(shard_by) => format!("{}, {}: &str", executor_param, to_rust_ident(shard_by)),
+        None => executor_param.to_string(),
+    };
+    let params_str = if input_params.is_empty() {
+        executor_param
+    } else {
+        format!("{}, {}", executor_param, input_params)
+    };
+
+    let return_type = format!(
+        "Result<{}, {}>",
+        function_value_type(query, type_info),
+        function_error_type(constraint_enum_name.as_deref())
+    );
+
+    code.push_str(&format!(
+        "pub async fn {}({}) -> {} {{\n",
+        query.name, params_str, return_type
+    ));
+
+    // Fail while the feature flag of the query is off, before anything is bound
+    if let Some(flag) = &query.feature_flag {
+        code.push_str(&format!(
+            "    if !super::feature_flags::is_enabled(\"{}\") {{\n",
+            flag
+        ));
+        code.push_str(&format!(
+            "        return Err({});\n",
+            feature_disabled_error(flag, constraint_enum_name.is_some())
+        ));
+        code.push_str("    }\n");
+    }
+
+    // Check `check_values: const` parameters and currencies before anything is bound
+    code.push_str(&generate_value_checks(
+        type_info,
+        &clean_param_names,
+        use_structured_params,
+        query.sqlx_version,
+    ));
+    code.push_str(&generate_currency_checks(
+        type_info,
+        &clean_param_names,
+        use_structured_params,
+        query.sqlx_version,
+    ));
+    code.push_str(&generate_char_flag_encoding(type_info, &clean_param_names));
+    code.push_str(&generate_array_element_encoding(
+        type_info,
+        &clean_param_names,
+    ));
+
+    // Queries requiring a session run on its pinned connection
+    if query.requires_session {
+        code.push_str("    let executor = session.connection();\n");
+    }
+
+    // Generate function body
+    // Read-only queries can be re-run with EXPLAIN ANALYZE without side effects
+    // Temp table queries cannot, since the temp tables only exist on the caller's connection
+    // Sharded and filtered queries are not sampled, their SQL is only known at runtime
+    // Neither are queries setting their search_path, which the shadow pool does not use
+    let sample_plan = query.plan_sampling
+        && !is_mutation
+        && !query.search_path_at_runtime
+        && !query.multiunzip
+        && !use_temp_tables
+        && query.shard_by.is_none()
+        && filter.is_none();
+    let function_body = if use_temp_tables {
+        generate_temp_table_function_body(query, type_info, &base_return_type, &return_type)?
+    } else if let Some((enum_name, parts)) = &filter {
+        generate_filter_function_body(query, type_info, enum_name, parts, &base_return_type)
+    } else {
+        generate_function_body(query, type_info, &base_return_type, sample_plan)?
+    };
+    let function_body = match &query.not_found_message {
+        Some(message) => generate_not_found_message_body(
+            query,
+            type_info,
+            message,
+            &function_body,
+            &return_type,
+            use_structured_params,
+        )?,
+        None => function_body,
+    };
+    let function_body = match type_info.estimated_rows {
+        Some(estimated_rows) => {
+            generate_cardinality_drift_body(query, estimated_rows, &function_body, &return_type)
+        }
+        None => function_body,
+    };
+    let function_body = if query.search_path_at_runtime {
+        generate_search_path_body(query, &function_body, &return_type)
+    } else {
+        function_body
+    };
+    match query.slow_query_ms {
+        Some(threshold_ms) => code.push_str(&generate_slow_query_body(
+            query,
+            threshold_ms,
+            &function_body,
+            &return_type,
+            if filter.is_some() {
+                &signature_param_names
+            } else {
+                &original_param_names
+            },
+            use_conditional_diff,
+            &|name| {
+                let value = if use_structured_params {
+                    format!("params.{}", to_rust_ident(name))
+                } else {
+                    to_rust_ident(name)
+                };
+                if query.telemetry.limits_params() {
+                    limited_param_value(query, &value, list_params.iter().any(|p| p == name))
+                } else {
+                    value
+                }
+            },
+        )),
+        None => code.push_str(&function_body),
+    }
+
+    code.push_str("}\n");
+
+    if let Some(flag) = feature_cfg {
+        code.push('\n');
+        code.push_str(&generate_feature_stub(
+            &query.name,
+            flag,
+            &params_str,
+            &return_type,
+            constraint_enum_name.is_some(),
+        ));
+    }
+
+    // Generate the wrapper for sync callers
+    if query.blocking {
+        code.push('\n');
+        code.push_str(&generate_blocking_wrapper(
+            &query.name,
+            &params_str,
+            &return_type,
+        ));
+    }
+
+    // Generate the variant for callers with request-scoped cancellation
+    if query.cancellable {
+        code.push('\n');
+        code.push_str(&generate_cancellable_variant(
+            &query.name,
+            &params_str[executor_param_len..],
+            &return_type,
+            constraint_enum_name.is_some(),
+        ));
+    }
+
+    // Generate the cached variant with its cache
+    if let Some(invalidate_on) = &query.cache_invalidate_on {
+        if is_mutation {
+            anyhow::bail!(
+                "Query '{}' sets cache_invalidate_on, which only applies to read-only queries",
+                query.name
+            );
+        }
+        code.push('\n');
+        code.push_str(&generate_cached_variant(
+            query,
+            type_info,
+            invalidate_on,
+            &params_str,
+            &return_type,
+        )?);
+    }
+
+    // Generate the SQL builder used by conditional queries
+    if let Some(parsed_sql) = &type_info.parsed_sql {
+        code.push('\n');
+        if let Some(flag) = feature_cfg {
+            code.push_str(&format!("#[cfg(feature = \"{}\")]\n", flag));
+        }
+        code.push_str(&generate_conditional_sql_builder(query, parsed_sql));
+    }
+
+    // Generate chunked iteration helpers
+    if query.iterate.is_some() {
+        let error_type = if let Some(ref enum_name) = constraint_enum_name {
+            format!("super::Error<{}>", enum_name)
+        } else {
+            "super::ErrorReadOnly".to_string()
+        };
+        code.push('\n');
+        code.push_str(&generate_iterate_helpers(
+            query,
+            type_info,
+            &base_return_type,
+            &input_params,
+            &error_type,
+        )?);
+    }
+
+    // Generate the group commit batcher
+    if query.batch.is_some() {
+        code.push('\n');
+        code.push_str(&generate_batch_helpers(
+            query,
+            type_info,
+            &input_params,
+            &base_return_type,
+            &return_type,
+        )?);
+    }
+
+    Ok(code)
+}
+
+/// Wrap a function body so that a missing row fails with the `not_found_message` of the query
+/// The message is formatted before the body runs, as the arguments are moved into the query
+fn generate_not_found_message_body(
+    query: &QueryDefinition,
+    type_info: &QueryTypeInfo,
+    message: &str,
+    function_body: &str,
+    return_type: &str,
+    use_structured_params: bool,
+) -> Result<String> {
+    if type_info.output_types.is_empty() {
+        anyhow::bail!(
+            "Query '{}' sets not_found_message, which requires a query returning rows",
+            query.name
+        );
+    }
+    let (format_string, placeholders) = parse_message_template(message).map_err(|e| {
+        anyhow::anyhow!(
+            "Query '{}' has an invalid not_found_message: {}",
+            query.name,
+            e
+        )
+    })?;
+    let args: String = placeholders
+        .iter()
+        .map(|placeholder| {
+            let ident = to_rust_ident(placeholder);
+            if use_structured_params && query.shard_by.as_deref() != Some(placeholder.as_str()) {
+                format!(", params.{}", ident)
+            } else {
+                format!(", {}", ident)
+            }
+        })
+        .collect();
+
+    let mut code = String::new();
+    code.push_str(&format!(
+        "    let not_found_message = format!({:?}{});\n",
+        format_string, args
+    ));
+    code.push_str(&format!(
+        "    let result: {} = async move {{\n",
+        return_type
+    ));
+    code.push_str(function_body);
+    code.push_str("    }\n    .await;\n");
+    code.push_str("    result.map_err(|e| e.with_not_found_message(not_found_message))\n");
+    Ok(code)
+}
+
+/// Wrap a function body so that calls returning an order of magnitude more or fewer rows than
+/// the planner estimate are reported to the `cardinality_drift` observer
+fn generate_cardinality_drift_body(
+    query: &QueryDefinition,
+    estimated_rows: f64,
+    function_body: &str,
+    return_type: &str,
+) -> String {
+    let mut code = String::new();
+    code.push_str(&format!(
+        "    let result: {} = async move {{\n",
+        return_type
+    ));
+    code.push_str(function_body);
+    code.push_str("    }\n    .await;\n");
+    code.push_str("    if let Ok(rows) = &result {\n");
+    code.push_str(&format!(
+        "        super::cardinality_drift::record(\"{}::{}\", {:?}, rows.len());\n",
+        query.module, query.name, estimated_rows
+    ));
+    code.push_str("    }\n");
+    code.push_str("    result\n");
+    code
+}
+
+/// Wrap a function body so that the statement runs in a transaction begun on the executor, after
+/// `SET LOCAL search_path` sets the search_path the query was analyzed with
+/// The transaction is committed when the body succeeds and rolled back otherwise
+/// The previous search_path is restored before the commit, as the transaction is a savepoint
+/// when the executor is a transaction, whose release keeps the `SET LOCAL` in the outer one
+fn generate_search_path_body(
+    query: &QueryDefinition,
+    function_body: &str,
+    return_type: &str,
+) -> String {
+    let mut code = String::new();
+    code.push_str("    let mut tx = executor.begin().await?;\n");
+    code.push_str(
+        "    let search_path: String = sqlx::query_scalar(\"SELECT current_setting('search_path')\")\n        .fetch_one(&mut *tx)\n        .await?;\n",
+    );
+    code.push_str(&format!(
+        "    sqlx::query({:?})\n        .execute(&mut *tx)\n        .await?;\n",
+        format!(
+            "SET LOCAL search_path TO {}",
+            search_path_list(&query.search_path)
+        )
+    ));
+    code.push_str("    let executor = &mut *tx;\n");
+    code.push_str(&format!(
+        "    let result: {} = async move {{\n",
+        return_type
+    ));
+    code.push_str(function_body);
+    code.push_str("    }\n    .await;\n");
+    code.push_str("    if result.is_ok() {\n");
+    code.push_str(
+        "        sqlx::query(\"SELECT set_config('search_path', $1, true)\")\n            .bind(search_path)\n            .execute(&mut *tx)\n            .await?;\n",
+    );
+    code.push_str("        tx.commit().await?;\n");
+    code.push_str("    }\n");
+    code.push_str("    result\n");
+    code
+}
+
+/// Wrap a function body so that a call running longer than `slow_query_ms` emits a
+/// `tracing::warn!` event
+/// Parameters listed in the telemetry `include_params` are formatted before the body runs, as the
+/// arguments are moved into the query; sensitive parameters are never included
+/// `param_value` gives the expression formatted for a parameter, cut to the telemetry limits when
+/// the query has them
+fn generate_slow_query_body(
+    query: &QueryDefinition,
+    threshold_ms: u64,
+    function_body: &str,
+    return_type: &str,
+    original_param_names: &[String],
+    use_conditional_diff: bool,
+    param_value: &dyn Fn(&str) -> String,
+) -> String {
+    // Conditional parameters of conditions_type queries are only available in the old/new structs
+    let mut included: Vec<String> = Vec::new();
+    if let (Some(include_params), false) = (&query.telemetry.include_params, query.multiunzip) {
+        for param in original_param_names {
+            if use_conditional_diff && param.ends_with('?') {
+                continue;
+            }
+            let name = param.trim_end_matches('?');
+            if include_params.iter().any(|p| p == name)
+                && !query.sensitivity.contains_key(name)
+                && !included.iter().any(|p| p == name)
+            {
+                included.push(name.to_string());
+            }
+        }
+    }
+
+    let mut code = String::new();
+    let params_field = if included.is_empty() {
+        String::new()
+    } else {
+        // Values cut to the telemetry limits are formatted by the telemetry module
+        let format_string: Vec<String> = included
+            .iter()
+            .map(|name| {
+                if query.telemetry.limits_params() {
+                    format!("{}={{}}", name)
+                } else {
+                    format!("{}={{:?}}", name)
+                }
+            })
+            .collect();
+        let args: Vec<String> = included.iter().map(|name| param_value(name)).collect();
+        code.push_str(&format!(
+            "    let slow_query_params = format!({:?}, {});\n",
+            format_string.join(", "),
+            args.join(", ")
+        ));
+        "            params = %slow_query_params,\n".to_string()
+    };
+    code.push_str("    let started = std::time::Instant::now();\n");
+    code.push_str(&format!(
+        "    let result: {} = async move {{\n",
+        return_type
+    ));
+    code.push_str(function_body);
+    code.push_str("    }\n    .await;\n");
+    code.push_str("    let elapsed = started.elapsed();\n");
+    code.push_str(&format!(
+        "    if elapsed >= std::time::Duration::from_millis({}) {{\n",
+        threshold_ms
+    ));
+    code.push_str("        tracing::warn!(\n");
+    code.push_str(&format!(
+        "            query = \"{}::{}\",\n",
+        query.module, query.name
+    ));
+    code.push_str("            elapsed_ms = elapsed.as_millis() as u64,\n");
+    code.push_str(&format!(
+        "            threshold_ms = {}u64,\n",
+        threshold_ms
+    ));
+    code.push_str(&params_field);
+    code.push_str("            \"slow query\"\n");
+    code.push_str("        );\n");
+    code.push_str("    }\n");
+    code.push_str("    result\n");
+    code
+}
+
+/// Whether a parameter is a list, whose recorded value is cut to the telemetry
+/// `max_param_elements`
+fn is_list_param(rust_type: &RustType) -> bool {
+    rust_type.rust_type.starts_with("Vec<") && !rust_type.is_nullable && !rust_type.is_optional
+}
+
+/// Expression formatting a parameter recorded in spans and slow query events, cut to the
+/// telemetry `max_param_length` and `max_param_elements` of the query
+fn limited_param_value(query: &QueryDefinition, value: &str, is_list: bool) -> String {
+    let telemetry = &query.telemetry;
+    if is_list {
+        format!(
+            "super::telemetry::list_param(&{}, {:?}, {:?})",
+            value, telemetry.max_param_elements, telemetry.max_param_length
+        )
+    } else {
+        format!(
+            "super::telemetry::param(&{}, {:?})",
+            value, telemetry.max_param_length
+        )
+    }
+}
+
+/// Type of a single result row: a tuple, the result struct or the type of the only column
+fn base_return_type(query: &QueryDefinition, type_info: &QueryTypeInfo) -> String {
+    if returns_tuple(query, type_info) {
+        // Tuple of column types in SELECT order
+        let column_types: Vec<String> = type_info
+            .output_types
+            .iter()
+            .map(|col| generate_return_type(Some(col)))
+            .collect();
+        format!("({})", column_types.join(", "))
+    } else if query.return_type.is_some() || type_info.output_types.len() > 1 {
+        // Use struct name if: explicit return_type configured OR multi-column result
+        if let Some(ref custom_name) = query.return_type {
+            custom_name.to_string()
+        } else {
+            format!("{}Item", to_pascal_case(&query.name))
+        }
+    } else {
+        // Single column result without explicit return_type - use direct type
+        generate_return_type(type_info.output_types.first())
+    }
+}
+
+/// Generate the checks of parameters against the allowed values of their `check_values: const` column
+/// Each check returns `sqlx::Error::InvalidArgument` naming the rejected value
+fn generate_value_checks(
+    type_info: &QueryTypeInfo,
+    param_names: &[String],
+    use_structured_params: bool,
+    sqlx_version: SqlxVersion,
+) -> String {
+    let mut code = String::new();
+    let mut checked_params = std::collections::HashSet::new();
+    for value_check in &type_info.value_checks {
+        for (i, param_name) in param_names.iter().enumerate() {
+            if param_name != &value_check.column || !checked_params.insert(param_name) {
+                continue;
+            }
+            let Some(rust_type) = type_info.input_types.get(i) else {
+                continue;
+            };
+            let param = if use_structured_params {
+                format!("params.{}", to_rust_ident(param_name))
+            } else {
+                to_rust_ident(param_name)
+            };
+            // Options and lists are checked for each value they hold
+            let is_option = rust_type.is_nullable || rust_type.is_optional;
+            let values = match (is_option, rust_type.rust_type.starts_with("Vec<")) {
+                (true, true) => format!("{}.iter().flatten()", param),
+                (true, false) | (false, true) => format!("{}.iter()", param),
+                (false, false) => format!("std::iter::once(&{})", param),
+            };
+            code.push_str(&format!(
+                "    if let Some(value) = {}.find(|value| !{}.contains(&value.as_str())) {{\n",
+                values, value_check.const_name
+            ));
+            code.push_str(&format!(
+                "        return Err({}(format!(\n            \"{} '{{}}' is not one of {{}}\",\n            value,\n            {}.join(\", \")\n        )).into());\n",
+                invalid_argument_error(sqlx_version),
+                param_name,
+                value_check.const_name
+            ));
+            code.push_str("    }\n");
+        }
+    }
+    code
+}
+
+/// Generate the checks of `currency_columns` parameters against the currency of their entry
+/// Each check returns `sqlx::Error::InvalidArgument` naming the rejected currency
+fn generate_currency_checks(
+    type_info: &QueryTypeInfo,
+    param_names: &[String],
+    use_structured_params: bool,
+    sqlx_version: SqlxVersion,
+) -> String {
+    let mut code = String::new();
+    let mut checked_params = std::collections::HashSet::new();
+    for (i, param_name) in param_names.iter().enumerate() {
+        let Some(rust_type) = type_info.input_types.get(i) else {
+            continue;
+        };
+        let Some(currency) = &rust_type.currency else {
+            continue;
+        };
+        if !checked_params.insert(param_name) {
+            continue;
+        }
+        let param = if use_structured_params {
+            format!("params.{}", to_rust_ident(param_name))
+        } else {
+            to_rust_ident(param_name)
+        };
+        // Options and lists are checked for each amount they hold
+        let is_option = rust_type.is_nullable || rust_type.is_optional;
+        let amounts = match (is_option, rust_type.rust_type.starts_with("Vec<")) {
+            (true, true) => format!("{}.iter().flatten()", param),
+            (true, false) | (false, true) => format!("{}.iter()", param),
+            (false, false) => format!("std::iter::once(&{})", param),
+        };
+        code.push_str(&format!(
+            "    if let Some(amount) = {}.find(|amount| amount.currency != \"{}\") {{\n",
+            amounts, currency
+        ));
+        code.push_str(&format!(
+            "        return Err({}(format!(\n            \"{} is in {{}}, not {}\",\n            amount.currency\n        )).into());\n",
+            invalid_argument_error(sqlx_version),
+            param_name,
+            currency
+        ));
+        code.push_str("    }\n");
+    }
+    code
+}
+
+/// Generate the conversion of `char_flags` parameters to the codes bound in their place
+/// Each parameter is shadowed by its code, so that all bindings of the body bind the code
+fn generate_char_flag_encoding(type_info: &QueryTypeInfo, param_names: &[String]) -> String {
+    let mut code = String::new();
+    let mut converted_params = std::collections::HashSet::new();
+    for (i, param_name) in param_names.iter().enumerate() {
+        let Some(rust_type) = type_info.input_types.get(i) else {
+            continue;
+        };
+        let Some(char_flag) = &rust_type.char_flag else {
+            continue;
+        };
+        if !converted_params.insert(param_name) {
+            continue;
+        }
+        let param = to_rust_ident(param_name);
+        let encode = if char_flag.code_type == "i8" {
+            "super::char_flags::encode_byte"
+        } else {
+            "super::char_flags::encode"
+        };
+        let values = char_flag_values(char_flag);
+        if rust_type.is_nullable || rust_type.is_optional {
+            code.push_str(&format!(
+                "    let {0} = {0}.map(|value| {1}(value, {2}));\n",
+                param, encode, values
+            ));
+        } else {
+            code.push_str(&format!(
+                "    let {0} = {1}({0}, {2});\n",
+                param, encode, values
+            ));
+        }
+    }
+    code
+}
+
+/// Generate the conversion of parameters with mapped array elements to the text arrays bound in
+/// their place
+fn generate_array_element_encoding(type_info: &QueryTypeInfo, param_names: &[String]) -> String {
+    let mut code = String::new();
+    let mut converted_params = std::collections::HashSet::new();
+    for (i, param_name) in param_names.iter().enumerate() {
+        let Some(rust_type) = type_info.input_types.get(i) else {
+            continue;
+        };
+        if rust_type.array_element.is_none() || !converted_params.insert(param_name) {
+            continue;
+        }
+        let param = to_rust_ident(param_name);
+        if rust_type.is_nullable || rust_type.is_optional {
+            code.push_str(&format!(
+                "    let {0} = {0}.as_deref().map(super::array_elements::encode);\n",
+                param
+            ));
+        } else {
+            code.push_str(&format!(
+                "    let {0} = super::array_elements::encode(&{0});\n",
+                param
+            ));
+        }
+    }
+    code
+}
+
+/// Codes of a `char_flags` flag with their values, e.g. `&[("Y", true), ("N", false)]`
+fn char_flag_values(char_flag: &CharFlagColumn) -> String {
+    let values: Vec<String> = char_flag
+        .flag
+        .codes
+        .iter()
+        .map(|(code, value)| format!("({:?}, {})", code, value))
+        .collect();
+    format!("&[{}]", values.join(", "))
+}
+
+/// Check if a query returns tuples instead of a struct
+/// Only multi-column results are affected, single columns are always returned as raw values
+/// Name of the constraint enum of a query, None when it can violate no constraint
+fn constraint_enum_name(
+    query: &QueryDefinition,
+    constraints: &[crate::types_extractor::ConstraintInfo],
+) -> Option<String> {
+    if constraints.is_empty() {
+        return None;
+    }
+    // Determine the enum name from error_type config or use default
+    Some(
+        query
+            .error_type
+            .clone()
+            .unwrap_or_else(|| format!("{}Constraints", to_pascal_case(&query.name))),
+    )
+}
+
+/// Type of the value returned by the function of a query, e.g. `Vec<GetUsersItem>`
+fn function_value_type(query: &QueryDefinition, type_info: &QueryTypeInfo) -> String {
+    if type_info.output_types.is_empty() {
+        return "()".to_string();
+    }
+    let base_return_type = base_return_type(query, type_info);
+    match query.expect {
+        ExpectedResult::ExactlyOne | ExpectedResult::ExactlyOneOrInsert => base_return_type,
+        ExpectedResult::PossibleOne => format!("Option<{}>", base_return_type),
+        ExpectedResult::AtLeastOne | ExpectedResult::Multiple => {
+            format!("Vec<{}>", base_return_type)
+        }
+    }
+}
+
+/// Type of the error returned by the function of a query
+fn function_error_type(constraint_enum_name: Option<&str>) -> String {
+    match constraint_enum_name {
+        Some(enum_name) => format!("super::Error<{}>", enum_name),
+        None => "super::ErrorReadOnly".to_string(),
+    }
+}
+
+pub(crate) fn returns_tuple(query: &QueryDefinition, type_info: &QueryTypeInfo) -> bool {
+    query.return_shape == ReturnShape::Tuple && type_info.output_types.len() > 1
+}
+
+/// Classify a type for dynamic map conversion
+/// Enums generated by AutoModel (as opposed to custom mapped types) need Display/FromStr
+fn dynamic_field_kind(rust_type: &RustType) -> DynamicFieldKind {
+    if rust_type.enum_variants.is_none() || rust_type.needs_json_wrapper {
+        DynamicFieldKind::Plain
+    } else if rust_type.is_enum_array {
+        DynamicFieldKind::EnumArray
+    } else {
+        DynamicFieldKind::Enum
+    }
+}
+
+/// Collect (field_name, is_option, kind) for a params struct, mirroring the struct generators
+/// When conditional_only is true, only conditional parameters (those ending with '?') are included
+fn dynamic_fields_for_params(
+    param_names: &[String],
+    input_types: &[RustType],
+    conditional_only: bool,
+) -> Vec<(String, bool, DynamicFieldKind)> {
+    let mut fields: Vec<(String, bool, DynamicFieldKind)> = Vec::new();
+    for (i, rust_type) in input_types.iter().enumerate() {
+        let Some(param_name) = param_names.get(i) else {
+            continue;
+        };
+        if conditional_only && !param_name.ends_with('?') {
+            continue;
+        }
+        let clean_param_name = param_name.trim_end_matches('?').to_string();
+        if !fields.iter().any(|(name, _, _)| name == &clean_param_name) {
+            fields.push((
+                clean_param_name,
+                rust_type.is_nullable,
+                dynamic_field_kind(rust_type),
+            ));
+        }
+    }
+    fields
+}
+
+/// Value bound to the EXPLAIN ANALYZE query of a sampled call
+/// Parameters are borrowed so the query itself can still consume them afterwards
+fn plan_sampling_bind_value(rust_type: &RustType, value: &str) -> String {
+    if rust_type.needs_json_wrapper {
+        format!(
+            "serde_json::to_value({}).unwrap_or(serde_json::Value::Null)",
+            value
+        )
+    } else {
+        value.to_string()
+    }
+}
+
+/// Generate the runtime plan sampling block of a function
+/// setup: lines preparing the EXPLAIN ANALYZE SQL, explain_query: expression creating the query,
+/// binds: lines binding its parameters
+fn generate_plan_sampling_block(
+    query: &QueryDefinition,
+    setup: &str,
+    explain_query: &str,
+    binds: &[String],
+) -> String {
+    let mut code = String::new();
+    code.push_str("    if let Some((shadow, observer)) = super::plan_sampling::sample() {\n");
+    code.push_str(setup);
+    if binds.is_empty() {
+        code.push_str(&format!("        let explain = {};\n", explain_query));
+    } else {
+        code.push_str(&format!("        let mut explain = {};\n", explain_query));
+        for bind in binds {
+            code.push_str(bind);
+        }
+    }
+    code.push_str("        if let Ok(row) = explain.fetch_one(&shadow).await {\n");
+    code.push_str(
+        "            if let Ok(plan) = sqlx::Row::try_get::<serde_json::Value, _>(&row, 0) {\n",
+    );
+    code.push_str(&format!(
+        "                observer(super::plan_sampling::PlanSample {{ query: \"{}::{}\", plan }});\n",
+        query.module, query.name
+    ));
+    code.push_str("            }\n");
+    code.push_str("        }\n");
+    code.push_str("    }\n");
+    code
+}
+
+/// PostgreSQL type of the temp table column holding the elements of a list parameter
+/// Returns None for element types without a text representation accepted by COPY
+fn large_list_element_pg_type(rust_type: &RustType) -> Option<String> {
+    if rust_type.is_enum_array {
+        return rust_type.pg_type_name.clone();
+    }
+    let element_type = rust_type
+        .rust_type
+        .strip_prefix("Vec<")?
+        .strip_suffix('>')?;
+    let pg_type = match element_type {
+        "i16" => "int2",
+        "i32" => "int4",
+        "i64" => "int8",
+        "f32" => "float4",
+        "f64" => "float8",
+        "bool" => "bool",
+        "String" => "text",
+        "uuid::Uuid" => "uuid",
+        "rust_decimal::Decimal" => "numeric",
+        "chrono::NaiveDate" => "date",
+        _ => return None,
+    };
+    Some(pg_type.to_string())
+}
+
+/// Generate the function body for `large_list_strategy: temp_table`
+/// List parameters are COPYed into temp tables which replace the `= ANY(...)` comparisons,
+/// all within one transaction on the caller's connection
+fn generate_temp_table_function_body(
+    query: &QueryDefinition,
+    type_info: &QueryTypeInfo,
+    base_return_type: &str,
+    return_type: &str,
+) -> Result<String> {
+    let use_structured_params = query.parameters_type.is_enabled();
+    let list_params = large_list_params(&query.sql);
+    let (_, original_param_names, _) = &query.sql_variants[0];
+
+    // Rewrite the query to join the temp tables; the remaining parameters keep their types
+    let rewritten_sql = rewrite_large_list_params(&query.sql, &list_params);
+    let (converted_sql, param_names) = convert_named_params_to_positional(&rewritten_sql);
+    let input_types: Vec<RustType> = original_param_names
+        .iter()
+        .zip(&type_info.input_types)
+        .filter(|(name, _)| !list_params.contains(name))
+        .map(|(_, rust_type)| rust_type.clone())
+        .collect();
+    let mut rewritten_query = query.clone();
+    rewritten_query.sql_variants = vec![(converted_sql, param_names, "base".to_string())];
+    let rewritten_type_info = QueryTypeInfo {
+        input_types,
+        output_types: type_info.output_types.clone(),
+        parsed_sql: None,
+        value_checks: type_info.value_checks.clone(),
+        cascade_preview: None,
+        estimated_rows: None,
+        id_only: None,
+        retry_lookups: Vec::new(),
+        bulk_update: None,
+        tables: type_info.tables.clone(),
+        plan_estimates: None,
+    };
+
+    let mut body = String::new();
+    body.push_str("    let mut tx = sqlx::Connection::begin(executor).await?;\n");
+    for param in &list_params {
+        let rust_type = original_param_names
+            .iter()
+            .position(|name| name == param)
+            .and_then(|i| type_info.input_types.get(i))
+            .ok_or_else(|| {
+                anyhow::anyhow!("Query '{}' has no parameter '{}'", query.name, param)
+            })?;
+        let pg_type = large_list_element_pg_type(rust_type).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Query '{}' uses large_list_strategy: temp_table, which does not support parameter '{}' of type {}",
+                query.name,
+                param,
+                rust_type.rust_type
+            )
+        })?;
+        let table = large_list_table_name(param);
+        let value = if use_structured_params {
+            format!("&params.{}", to_rust_ident(param))
+        } else {
+            format!("&{}", to_rust_ident(param))
+        };
+        body.push_str(&format!(
+            "    sqlx::query(r\"CREATE TEMP TABLE {} (value {})\").execute(&mut *tx).await?;\n",
+            table, pg_type
+        ));
+        body.push_str(&format!(
+            "    let mut copy = tx.copy_in_raw(r\"COPY {} (value) FROM STDIN\").await?;\n",
+            table
+        ));
+        body.push_str(&format!(
+            "    copy.send(super::large_list::copy_data({})).await?;\n",
+            value
+        ));
+        body.push_str("    copy.finish().await?;\n");
+        body.push_str(&format!(
+            "    sqlx::query(r\"ANALYZE {}\").execute(&mut *tx).await?;\n",
+            table
+        ));
+    }
+    body.push('\n');
+
+    // Run the rewritten query inside the transaction, then drop the temp tables
+    let query_body = generate_function_body(
+        &rewritten_query,
+        &rewritten_type_info,
+        base_return_type,
+        false,
+    )?;
+    body.push_str(&format!("    let result: {} = async {{\n", return_type));
+    body.push_str("        let executor = &mut *tx;\n");
+    for line in query_body.lines() {
+        if line.is_empty() {
+            body.push('\n');
+        } else {
+            body.push_str(&format!("    {}\n", line));
+        }
+    }
+    body.push_str("    }\n");
+    body.push_str("    .await;\n");
+    body.push_str("    let result = result?;\n\n");
+    for param in &list_params {
+        body.push_str(&format!(
+            "    sqlx::query(r\"DROP TABLE {}\").execute(&mut *tx).await?;\n",
+            large_list_table_name(param)
+        ));
+    }
+    body.push_str("    tx.commit().await?;\n");
+    body.push_str("    Ok(result)\n");
+    Ok(body)
+}
+
+/// Generate struct creation code for multi-column results
+
+/// Generate the function body using SQLx
+fn generate_function_body(
+    query: &QueryDefinition,
+    type_info: &QueryTypeInfo,
+    return_type: &str,
+    sample_plan: bool,
+) -> Result<String> {
+    let mut body = String::new();
+
+    // Check if this query has conditional blocks
+    if let Some(parsed_sql) = &type_info.parsed_sql {
+        // Generate dynamic SQL building for conditional queries
+        generate_conditional_function_body(
+            &mut body,
+            query,
+            type_info,
+            parsed_sql,
+            return_type,
+            sample_plan,
+        )?;
+    } else {
+        // Generate standard static SQL
+        generate_static_function_body(&mut body, query, type_info, return_type, sample_plan)?;
+    }
+
+    Ok(body)
+}
+
+/// Generate function body for static (non-conditional) SQL queries
+fn generate_static_function_body(
+    body: &mut String,
+    query: &QueryDefinition,
+    type_info: &QueryTypeInfo,
+    return_type: &str,
+    sample_plan: bool,
+) -> Result<()> {
+    let use_multiunzip = query.multiunzip;
+    let use_conditional_diff = query.conditions_type.is_enabled();
+    let use_structured_params = query.parameters_type.is_enabled() && !use_conditional_diff;
+    // Add itertools import if using multiunzip
+    if use_multiunzip {
+        body.push_str("    use itertools::Itertools;\n");
+    }
+
+    // Get pre-computed converted SQL and param names from first variant (base query)
+    let (converted_sql, param_names, _variant_label) = &query.sql_variants[0];
+
+    if sample_plan {
+        let binds: Vec<String> = param_names
+            .iter()
+            .enumerate()
+            .filter_map(|(i, name)| {
+                let ident = to_rust_ident(name.trim_end_matches('?'));
+                let value = if use_structured_params {
+                    format!("&params.{}", ident)
+                } else {
+                    format!("&{}", ident)
+                };
+                let rust_type = type_info.input_types.get(i)?;
+                Some(format!(
+                    "        explain = explain.bind({});\n",
+                    plan_sampling_bind_value(rust_type, &value)
+                ))
+            })
+            .collect();
+        let explain_query = format!(
+            "sqlx::query(\n{}\n        )",
+            generate_indented_raw_string_literal(&format!(
+                "EXPLAIN (ANALYZE, FORMAT JSON)\n{}",
+                converted_sql
+            ))
+        );
+        body.push_str(&generate_plan_sampling_block(
+            query,
+            "",
+            &explain_query,
+            &binds,
+        ));
+        body.push('\n');
+    }
+
+    // Build the SQLx query with parameter bindings
+    let raw_string = generate_indented_raw_string_literal(converted_sql);
+    if let Some(shard_by) = &query.shard_by {
+        // Substitute the validated shard key for the table shard placeholder
+        body.push_str(&format!(
+            "    let sql = super::sharding::shard_sql(\n{},\n        {},\n    )?;\n",
+            raw_string,
+            to_rust_ident(shard_by)
+        ));
+        body.push_str("    let query = sqlx::query(&sql);\n");
+    } else if !query.prepare {
+        // Run with the simple query protocol, which cannot bind parameters
+        body.push_str(&format!(
+            "    let query = {}(\n{}\n    );\n",
+            raw_sql_constructor(query.sqlx_version),
+            raw_string
+        ));
+    } else {
+        body.push_str(&format!(
+            "    let query = sqlx::query(\n{}\n    );\n",
+            raw_string
+        ));
+    }
+
+    // Add parameter bindings using method chaining
+    if !type_info.input_types.is_empty() {
+        if use_multiunzip {
+            // Generate multiunzip pattern with struct field extraction
+
+            // Get clean parameter names for field extraction
+            let original_param_names = parse_parameter_names_from_sql(&query.sql);
+            let clean_param_names: Vec<String> = original_param_names
+                .iter()
+                .map(|name| {
+                    if name.ends_with('?') {
+                        name.trim_end_matches('?').to_string()
+                    } else {
+                        name.clone()
+                    }
+                })
+                .collect();
+
+            // Generate the tuple pattern based on number of types
+            let num_types = type_info.input_types.len();
+            let tuple_vars: Vec<String> = clean_param_names
+                .iter()
+                .map(|name| {
+                    // Convert to snake_case for consistency
+                    to_rust_ident(&to_snake_case(name))
+                })
+                .collect();
+            let tuple_pattern = tuple_vars.join(", ");
+
+            body.push_str(&format!(
+                "    let ({}): ({}) =\n",
+                tuple_pattern,
+                vec!["Vec<_>"; num_types].join(", ")
+            ));
+            body.push_str("        items\n");
+            body.push_str("            .into_iter()\n");
+
+            // Generate the map closure to extract struct fields
+            body.push_str(&format!(
+                "            .map(|item| ({}))\n",
+                tuple_vars
+                    .iter()
+                    .map(|field| format!("item.{}", field))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+
+            body.push_str("            .multiunzip();\n");
+
+            // Bind each array
+            for (i, var) in tuple_vars.iter().enumerate() {
+                let rust_type_info = &type_info.input_types[i];
+
+                if rust_type_info.needs_json_wrapper {
+                    // For custom types in arrays, we need to serialize each element
+                    body.push_str(&format!(
+                        "    let {}_json: Result<Vec<serde_json::Value>, _> = {}.into_iter().map(|v| serde_json::to_value(&v)).collect();\n",
+                        var, var
+                    ));
+                    body.push_str(&format!(
+                        "    let query = query.bind({}_json.map_err(|e| sqlx::Error::Encode(Box::new(e)))?);\n",
+                        var
+                    ));
+                } else {
+                    body.push_str(&format!("    let query = query.bind({});\n", var));
+                }
+            }
+        } else if use_structured_params {
+            // For parameters_type, bind from params struct
+            for (i, name) in param_names.iter().enumerate() {
+                let clean_name = to_rust_ident(name.trim_end_matches('?'));
+
+                let rust_type_info = &type_info.input_types[i];
+                let param_type = &rust_type_info.rust_type;
+
+                // Check if this is a custom type that needs JSON serialization
+                if rust_type_info.needs_json_wrapper {
+                    // For custom types, serialize to JSON before binding
+                    body.push_str(&format!(
+                        "    let query = query.bind(serde_json::to_value(&params.{}).map_err(|e| sqlx::Error::Encode(Box::new(e)))?);\n", 
+                        clean_name
+                    ));
+                } else if param_type == "String" {
+                    // Use reference for String parameters to avoid move issues
+                    body.push_str(&format!(
+                        "    let query = query.bind(&params.{});\n",
+                        clean_name
+                    ));
+                } else if rust_type_info.is_nullable {
+                    // For Option types, we can bind directly
+                    body.push_str(&format!(
+                        "    let query = query.bind(params.{});\n",
+                        clean_name
+                    ));
+                } else {
+                    // For Copy types, we can bind directly
+                    body.push_str(&format!(
+                        "    let query = query.bind(params.{});\n",
+                        clean_name
+                    ));
+                }
+            }
+        } else if param_names.is_empty() {
+            // Fallback to generic param names
+            for i in 1..=type_info.input_types.len() {
+                body.push_str(&format!("    let query = query.bind(param_{});\n", i));
+            }
+        } else {
+            // Use meaningful parameter names from SQL
+            for (i, name) in param_names.iter().enumerate() {
+                let clean_name = to_rust_ident(name.trim_end_matches('?'));
+
+                let rust_type_info = &type_info.input_types[i];
+                let param_type = &rust_type_info.rust_type;
+
+                // Check if this is a custom type that needs JSON serialization
+                if rust_type_info.needs_json_wrapper {
+                    // For custom types, serialize to JSON before binding
+                    body.push_str(&format!(
+                        "    let query = query.bind(serde_json::to_value(&{}).map_err(|e| sqlx::Error::Encode(Box::new(e)))?);\n", 
+                        clean_name
+                    ));
+                } else if param_type == "String" {
+                    // Use reference for String parameters to avoid move issues
+                    body.push_str(&format!("    let query = query.bind(&{});\n", clean_name));
+                } else {
+                    body.push_str(&format!("    let query = query.bind({});\n", clean_name));
+                }
+            }
+        }
+    }
+
+    generate_query_execution(body, query, type_info, return_type);
+    Ok(())
+}
+
+/// Generate function body for conditional (dynamic) SQL queries
+fn generate_conditional_function_body(
+    body: &mut String,
+    query: &QueryDefinition,
+    type_info: &QueryTypeInfo,
+    parsed_sql: &crate::types_extractor::ParsedSql,
+    return_type: &str,
+    sample_plan: bool,
+) -> Result<()> {
+    use crate::types_extractor::parse_parameter_names_from_sql;
+
+    let use_conditional_diff = query.conditions_type.is_enabled();
+    let use_structured_params = query.parameters_type.is_enabled() && !use_conditional_diff;
+    // Parse all parameters from the original SQL to get their order and types
+    let all_params = parse_parameter_names_from_sql(&query.sql);
+
+    // Decide which conditional blocks are included based on parameter presence or diff
+    body.push_str("    let mut included_params: Vec<&str> = Vec::new();\n");
+    for block in &parsed_sql.conditional_blocks {
+        let Some(param_name) = block.parameters.first() else {
+            continue;
+        };
+        let clean_param = param_name.trim_end_matches('?');
+        let param_ident = to_rust_ident(clean_param);
+
+        if use_conditional_diff {
+            // For conditions_type, check if old and new values differ
+            body.push_str(&format!(
                 "    if old.{} != new.{} {{\n",
-                clean_param, clean_param
+                param_ident, param_ident
+            ));
+        } else if use_structured_params {
+            // For parameters_type, check if parameter is Some (for Option types)
+            body.push_str(&format!("    if params.{}.is_some() {{\n", param_ident));
+        } else {
+            // For regular conditional, check if parameter is Some
+            body.push_str(&format!("    if {}.is_some() {{\n", param_ident));
+        }
+        body.push_str(&format!(
+            "        included_params.push(\"{}\");\n",
+            clean_param
+        ));
+        body.push_str("    }\n");
+    }
+    body.push_str(&format!(
+        "    let final_sql = {}(&included_params);\n",
+        conditional_sql_builder_name(&query.name)
+    ));
+
+    if sample_plan {
+        let mut binds = Vec::new();
+        for (param_name, block_param) in ordered_conditional_params(parsed_sql) {
+            let clean_param = param_name.trim_end_matches('?');
+            let Some(param_index) = all_params
+                .iter()
+                .position(|p| p.trim_end_matches('?') == clean_param)
+            else {
+                continue;
+            };
+            let Some(rust_type_info) = type_info.input_types.get(param_index) else {
+                continue;
+            };
+            let param_ident = to_rust_ident(clean_param);
+
+            let value = match (
+                block_param.is_some(),
+                use_conditional_diff,
+                use_structured_params,
+            ) {
+                (false, _, true) => format!("&params.{}", param_ident),
+                (false, _, false) => format!("&{}", param_ident),
+                (true, true, _) => format!("&new.{}", param_ident),
+                (true, false, true) => format!("params.{}.as_ref().unwrap()", param_ident),
+                (true, false, false) => format!("{}.as_ref().unwrap()", param_ident),
+            };
+            let bind = format!(
+                "explain = explain.bind({});",
+                plan_sampling_bind_value(rust_type_info, &value)
+            );
+            if block_param.is_some() {
+                binds.push(format!(
+                    "        if included_params.contains(&r\"{}\") {{\n            {}\n        }}\n",
+                    clean_param, bind
+                ));
+            } else {
+                binds.push(format!("        {}\n", bind));
+            }
+        }
+        body.push('\n');
+        body.push_str(&generate_plan_sampling_block(
+            query,
+            "        let explain_sql = format!(\"EXPLAIN (ANALYZE, FORMAT JSON) {}\", final_sql);\n",
+            "sqlx::query(&explain_sql)",
+            &binds,
+        ));
+    }
+    body.push_str("\n    let mut query = sqlx::query(&final_sql);\n\n");
+
+    // Bind parameters in the order they appear in the final SQL, so that they match
+    // the positional numbering assigned by the SQL builder
+    for (param_name, block_param) in ordered_conditional_params(parsed_sql) {
+        let clean_param = param_name.trim_end_matches('?');
+        let Some(param_index) = all_params
+            .iter()
+            .position(|p| p.trim_end_matches('?') == clean_param)
+        else {
+            continue;
+        };
+        let Some(rust_type_info) = type_info.input_types.get(param_index) else {
+            continue;
+        };
+        let param_ident = to_rust_ident(clean_param);
+
+        if block_param.is_none() {
+            // Base (non-conditional) parameter is always bound
+            if rust_type_info.needs_json_wrapper {
+                if use_structured_params {
+                    body.push_str(&format!("    let {}_json = serde_json::to_value(&params.{}).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;\n", param_ident, param_ident));
+                } else {
+                    body.push_str(&format!("    let {}_json = serde_json::to_value(&{}).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;\n", param_ident, param_ident));
+                }
+                body.push_str(&format!("    query = query.bind({}_json);\n", param_ident));
+            } else if use_structured_params {
+                body.push_str(&format!(
+                    "    query = query.bind(&params.{});\n",
+                    param_ident
+                ));
+            } else {
+                body.push_str(&format!("    query = query.bind(&{});\n", param_ident));
+            }
+            continue;
+        }
+
+        // Conditional parameter is bound only if its block is included
+        body.push_str(&format!(
+            "    if included_params.contains(&r\"{}\") {{\n",
+            clean_param
+        ));
+        if rust_type_info.needs_json_wrapper {
+            if use_conditional_diff {
+                // For conditions_type, use new.field directly
+                body.push_str(&format!("        let {}_json = serde_json::to_value(&new.{}).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;\n", param_ident, param_ident));
+            } else if use_structured_params {
+                // For parameters_type, unwrap from params struct
+                body.push_str(&format!("        let {}_json = serde_json::to_value(&params.{}.as_ref().unwrap()).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;\n", param_ident, param_ident));
+            } else {
+                // For regular conditional, unwrap the Option
+                body.push_str(&format!("        let {}_json = serde_json::to_value(&{}.as_ref().unwrap()).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;\n", param_ident, param_ident));
+            }
+            body.push_str(&format!(
+                "        query = query.bind({}_json);\n",
+                param_ident
+            ));
+        } else if use_conditional_diff {
+            // For conditions_type, bind new.field directly
+            body.push_str(&format!(
+                "        query = query.bind(&new.{});\n",
+                param_ident
+            ));
+        } else if use_structured_params {
+            // For parameters_type, unwrap from params struct
+            body.push_str(&format!(
+                "        query = query.bind(params.{}.as_ref().unwrap());\n",
+                param_ident
+            ));
+        } else {
+            // For regular conditional, unwrap the Option
+            body.push_str(&format!(
+                "        query = query.bind({}.as_ref().unwrap());\n",
+                param_ident
+            ));
+        }
+        body.push_str("    }\n");
+    }
+    body.push('\n');
+
+    generate_query_execution(body, query, type_info, return_type);
+    Ok(())
+}
+/// Whether a parameter is compared with a column by a condition of a filter query
+fn is_filter_param(parts: &FilterQueryParts, param_name: &str) -> bool {
+    parts
+        .conditions
+        .iter()
+        .any(|condition| condition.param == param_name)
+}
+
+/// Generate function body for queries with `filter_type`
+/// The WHERE clause is assembled from the given filters, numbering their parameters after the
+/// parameters before the WHERE clause and before the parameters after it
+fn generate_filter_function_body(
+    query: &QueryDefinition,
+    type_info: &QueryTypeInfo,
+    enum_name: &str,
+    parts: &FilterQueryParts,
+    return_type: &str,
+) -> String {
+    let mut body = String::new();
+    let all_params = parse_parameter_names_from_sql(&query.sql);
+
+    // Parameters before the WHERE clause keep their positions
+    let (before_sql, before_params) = convert_named_params_to_positional(&parts.before);
+    body.push_str(&format!(
+        "    let mut final_sql = String::from(\n{},\n    );\n",
+        generate_indented_raw_string_literal(&before_sql)
+    ));
+    body.push_str("    for (index, filter) in filters.iter().enumerate() {\n");
+    body.push_str(
+        "        final_sql.push_str(if index == 0 { \"\\nWHERE \" } else { \"\\n  AND \" });\n",
+    );
+    let offset = if before_params.is_empty() {
+        String::new()
+    } else {
+        format!("{} + ", before_params.len())
+    };
+    body.push_str(&format!(
+        "        final_sql.push_str(&filter.condition({}index + 1));\n",
+        offset
+    ));
+    body.push_str("    }\n");
+
+    // Parameters after the WHERE clause are numbered after the filters
+    let mut after_params: Vec<String> = Vec::new();
+    if !parts.after.is_empty() {
+        let mut format_string = String::new();
+        let mut positions = Vec::new();
+        let mut rest = parts.after.as_str();
+        while let Some(start) = rest.find("#{") {
+            let Some(len) = rest[start..].find('}') else {
+                break;
+            };
+            let param_name = &rest[start + 2..start + len];
+            format_string.push_str(&rest[..start].replace('{', "{{").replace('}', "}}"));
+            let position = match before_params.iter().position(|p| p == param_name) {
+                Some(index) => format!("{}", index + 1),
+                None => {
+                    let index = after_params
+                        .iter()
+                        .position(|p| p == param_name)
+                        .unwrap_or_else(|| {
+                            after_params.push(param_name.to_string());
+                            after_params.len() - 1
+                        });
+                    format!("{}filters.len() + {}", offset, index + 1)
+                }
+            };
+            format_string.push_str(&format!("${{{}}}", positions.len()));
+            positions.push(position);
+            rest = &rest[start + len + 1..];
+        }
+        format_string.push_str(&rest.replace('{', "{{").replace('}', "}}"));
+        if positions.is_empty() {
+            body.push_str(&format!("    final_sql.push_str({:?});\n", parts.after));
+        } else {
+            body.push_str(&format!(
+                "    final_sql.push_str(&format!(\n        {:?},\n        {}\n    ));\n",
+                format_string,
+                positions.join(",\n        ")
+            ));
+        }
+    }
+
+    // Bind the parameters in the order of their positions
+    body.push_str("\n    let mut query = sqlx::query(&final_sql);\n");
+    let bind_param = |body: &mut String, param_name: &str| {
+        let Some(rust_type) = all_params
+            .iter()
+            .position(|p| p == param_name)
+            .and_then(|index| type_info.input_types.get(index))
+        else {
+            return;
+        };
+        let ident = to_rust_ident(param_name);
+        if rust_type.needs_json_wrapper {
+            body.push_str(&format!("    query = query.bind(serde_json::to_value(&{}).map_err(|e| sqlx::Error::Encode(Box::new(e)))?);\n", ident));
+        } else if rust_type.rust_type == "String" {
+            body.push_str(&format!("    query = query.bind(&{});\n", ident));
+        } else {
+            body.push_str(&format!("    query = query.bind({});\n", ident));
+        }
+    };
+    for param_name in &before_params {
+        bind_param(&mut body, param_name);
+    }
+    body.push_str("    for filter in filters {\n");
+    body.push_str("        query = match filter {\n");
+    for condition in &parts.conditions {
+        let needs_json_wrapper = all_params
+            .iter()
+            .position(|p| *p == condition.param)
+            .and_then(|index| type_info.input_types.get(index))
+            .is_some_and(|rust_type| rust_type.needs_json_wrapper);
+        let value = if needs_json_wrapper {
+            "serde_json::to_value(&value).map_err(|e| sqlx::Error::Encode(Box::new(e)))?"
+        } else {
+            "value"
+        };
+        body.push_str(&format!(
+            "            {}::{}(value) => query.bind({}),\n",
+            enum_name, condition.variant, value
+        ));
+    }
+    body.push_str("        };\n");
+    body.push_str("    }\n");
+    for param_name in &after_params {
+        bind_param(&mut body, param_name);
+    }
+    body.push('\n');
+
+    generate_query_execution(&mut body, query, type_info, return_type);
+    body
+}
+
+/// Name of the generated function that assembles the SQL of a conditional query
+fn conditional_sql_builder_name(query_name: &str) -> String {
+    format!("{}_sql", query_name)
+}
+
+/// Collect the parameters of a conditional query in the order they first appear in the SQL
+/// Returns (param_name, block_param) where block_param is the clean name of the parameter
+/// controlling the enclosing conditional block, or None for base parameters
+/// Repeated parameters are listed once, as they share a single positional placeholder
+fn ordered_conditional_params(
+    parsed_sql: &crate::types_extractor::ParsedSql,
+) -> Vec<(String, Option<String>)> {
+    let mut ordered: Vec<(String, Option<String>)> = Vec::new();
+    let is_listed = |ordered: &[(String, Option<String>)], param_name: &str| {
+        ordered
+            .iter()
+            .any(|(name, _)| name.trim_end_matches('?') == param_name.trim_end_matches('?'))
+    };
+    let mut blocks = parsed_sql.conditional_blocks.iter();
+    let mut rest = parsed_sql.base_sql.as_str();
+
+    loop {
+        let next_block = rest.find("#[");
+        let next_param = rest
+            .find("#{")
+            .filter(|param_pos| !matches!(next_block, Some(block_pos) if block_pos < *param_pos));
+
+        if let Some(param_pos) = next_param {
+            let after = &rest[param_pos + 2..];
+            let Some(end) = after.find('}') else {
+                break;
+            };
+            let param_name = &after[..end];
+            if !param_name.ends_with('?') && !is_listed(&ordered, param_name) {
+                ordered.push((param_name.to_string(), None));
+            }
+            rest = &after[end + 1..];
+        } else if let Some(block_pos) = next_block {
+            let Some(block) = blocks.next() else {
+                break;
+            };
+            // Only the first parameter of a block controls (and is bound for) the block
+            if let Some(first_param) = block
+                .parameters
+                .first()
+                .filter(|first_param| !is_listed(&ordered, first_param))
+            {
+                ordered.push((
+                    first_param.clone(),
+                    Some(first_param.trim_end_matches('?').to_string()),
+                ));
+            }
+            let block_len = format!("#[{}]", block.sql_content).len();
+            rest = &rest[(block_pos + block_len).min(rest.len())..];
+        } else {
+            break;
+        }
+    }
+
+    ordered
+}
+
+/// Generate the function that assembles the SQL of a conditional query
+/// Included blocks are expanded and all parameters are numbered in order of first appearance,
+/// so every variant matches the SQL analyzed during generation
+fn generate_conditional_sql_builder(
+    query: &QueryDefinition,
+    parsed_sql: &crate::types_extractor::ParsedSql,
+) -> String {
+    let mut code = String::new();
+
+    code.push_str(&format!(
+        "/// Assemble the SQL of `{}` for the given included conditional parameters\n",
+        query.name
+    ));
+    code.push_str(&format!(
+        "fn {}(included_params: &[&str]) -> String {{\n",
+        conditional_sql_builder_name(&query.name)
+    ));
+    code.push_str("    let mut final_sql = r\"");
+    code.push_str(&parsed_sql.base_sql);
+    code.push_str("\".to_string();\n\n");
+
+    // Expand or remove each conditional block
+    for block in &parsed_sql.conditional_blocks {
+        let Some(param_name) = block.parameters.first() else {
+            continue;
+        };
+        let conditional_block = format!("#[{}]", block.sql_content);
+        code.push_str(&format!(
+            "    if included_params.contains(&\"{}\") {{\n",
+            param_name.trim_end_matches('?')
+        ));
+        code.push_str(&format!(
+            "        final_sql = final_sql.replacen(r\"{}\", r\"{}\", 1);\n",
+            conditional_block, block.sql_content
+        ));
+        code.push_str("    } else {\n");
+        code.push_str(&format!(
+            "        final_sql = final_sql.replacen(r\"{}\", \"\", 1);\n",
+            conditional_block
+        ));
+        code.push_str("    }\n");
+    }
+
+    // Number the remaining parameters in order of first appearance, every occurrence of a
+    // parameter gets the same number
+    code.push_str("\n    #[allow(unused_assignments)]\n");
+    code.push_str("    let mut param_counter = 1;\n");
+    for (param_name, block_param) in ordered_conditional_params(parsed_sql) {
+        let indent = if let Some(block_param) = &block_param {
+            code.push_str(&format!(
+                "    if included_params.contains(&\"{}\") {{\n",
+                block_param
+            ));
+            "        "
+        } else {
+            "    "
+        };
+        code.push_str(&format!(
+            "{}final_sql = final_sql.replace(r\"#{{{}}}\", &format!(\"${{}}\", param_counter));\n",
+            indent, param_name
+        ));
+        code.push_str(&format!("{}param_counter += 1;\n", indent));
+        if block_param.is_some() {
+            code.push_str("    }\n");
+        }
+    }
+    code.push_str("    let _ = param_counter; // Suppress unused assignment warning\n");
+    code.push_str("    final_sql\n");
+    code.push_str("}\n");
+
+    code
+}
+
+/// Generate a test module asserting that every runtime-assembled SQL variant of the
+/// conditional queries matches the variant analyzed during generation
+fn generate_conditional_variant_tests(module_queries: &[&QueryDefinitionRuntime]) -> String {
+    let mut tests = String::new();
+
+    for analyzed in module_queries {
+        let Some(parsed_sql) = &analyzed.type_info.parsed_sql else {
+            continue;
+        };
+        let query = &analyzed.definition;
+
+        tests.push_str("    #[test]\n");
+        tests.push_str(&format!("    fn {}_sql_variants() {{\n", query.name));
+        for (variant_sql, _param_names, label) in &query.sql_variants {
+            // Variants are "base" (no blocks), "variant N" (only the N-th block included) or
+            // "variants N+M" (the listed blocks included)
+            let block_numbers = label
+                .strip_prefix("variant ")
+                .or_else(|| label.strip_prefix("variants "))
+                .unwrap_or_default();
+            let included_params: Option<Vec<String>> = block_numbers
+                .split('+')
+                .filter(|number| !number.is_empty())
+                .map(|number| {
+                    let block = number
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|n| parsed_sql.conditional_blocks.get(n.checked_sub(1)?))?;
+                    let param_name = block.parameters.first()?;
+                    Some(format!("\"{}\"", param_name.trim_end_matches('?')))
+                })
+                .collect();
+            let Some(included_params) = included_params else {
+                continue;
+            };
+            let included_param = included_params.join(", ");
+            // The SQL of get-or-create queries is assembled by their lookup function
+            let builder_name = if query.expect == ExpectedResult::ExactlyOneOrInsert {
+                conditional_sql_builder_name(&get_or_create_lookup_name(&query.name))
+            } else {
+                conditional_sql_builder_name(&query.name)
+            };
+            tests.push_str(&format!(
+                "        assert_eq!(\n            normalize_sql(&{}(&[{}])),\n            normalize_sql(r\"{}\"),\n            \"{} ({})\"\n        );\n",
+                builder_name,
+                included_param,
+                variant_sql,
+                query.name,
+                label
+            ));
+        }
+        tests.push_str("    }\n\n");
+    }
+
+    if tests.is_empty() {
+        return tests;
+    }
+
+    let mut code = String::new();
+    code.push_str("#[cfg(test)]\n");
+    code.push_str("mod conditional_variant_tests {\n");
+    code.push_str("    use super::*;\n\n");
+    code.push_str("    fn normalize_sql(sql: &str) -> String {\n");
+    code.push_str("        sql.split_whitespace().collect::<Vec<_>>().join(\" \")\n");
+    code.push_str("    }\n\n");
+    code.push_str(tests.trim_end());
+    code.push_str("\n}\n");
+    code
+}
+
+/// Generate the `warmup` function preparing every query of the module on a connection
+/// Queries whose SQL is assembled at runtime from shards or temp tables, unprepared queries,
+/// queries compiled out with `feature_flag_cfg` and queries setting their search_path at runtime,
+/// which the search_path of the connection may not resolve, are not included
+fn generate_warmup_function(module_queries: &[&QueryDefinitionRuntime]) -> String {
+    let entries: Vec<String> = module_queries
+        .iter()
+        .filter(|analyzed| {
+            let query = &analyzed.definition;
+            query.prepare
+                && query.shard_by.is_none()
+                && !query.feature_flag_cfg
+                && !query.search_path_at_runtime
+                && query.large_list_strategy != LargeListStrategy::TempTable
+        })
+        .map(|analyzed| {
+            let query = &analyzed.definition;
+            // The literals match those of the generated functions, so that the statements
+            // prepared here are the ones the functions find in the statement cache
+            let variants: Vec<String> = query
+                .sql_variants
+                .iter()
+                .map(|(sql, _, _)| generate_indented_raw_string_literal(sql).trim().to_string())
+                .collect();
+            let columns: Vec<String> = analyzed
+                .type_info
+                .output_types
+                .iter()
+                .map(|col| format!("{:?}", col.name))
+                .collect();
+            format!(
+                "    ({:?}, &[{}], &[{}]),\n",
+                query.name,
+                variants.join(", "),
+                columns.join(", ")
+            )
+        })
+        .collect();
+
+    format!(
+        "/// Prepare every query of this module on the connection, e.g. in `after_connect` of the pool\n\
+         /// The statements stay cached on the connection; failed queries no longer match the schema\n\
+         pub async fn warmup(executor: &mut sqlx::PgConnection) -> Vec<super::WarmupStatus> {{\n    \
+         const QUERIES: &[(&str, &[&str], &[&str])] = &[\n{}    ];\n    \
+         let mut report = Vec::with_capacity(QUERIES.len());\n    \
+         for &(query, variants, columns) in QUERIES {{\n        \
+         let result = super::warmup_query(&mut *executor, variants, columns).await;\n        \
+         report.push(super::WarmupStatus {{ query, result }});\n    \
+         }}\n    \
+         report\n\
+         }}\n\n",
+        entries.concat()
+    )
+}
+
+/// Generate a test module running every `isolation_sensitive` query in two interleaved
+/// SERIALIZABLE transactions, with its analysis_params values inlined into the SQL
+/// The tests connect to DATABASE_URL and are ignored unless run with `--ignored`
+fn generate_isolation_tests(module_queries: &[&QueryDefinitionRuntime]) -> String {
+    let mut tests = String::new();
+
+    for analyzed in module_queries {
+        let query = &analyzed.definition;
+        if !query.isolation_sensitive {
+            continue;
+        }
+
+        // Replace parameters from the highest number down, so that $1 does not match $10
+        let (converted_sql, param_names, _label) = &query.sql_variants[0];
+        let mut sql = converted_sql.clone();
+        for (index, param_name) in param_names.iter().enumerate().rev() {
+            let Some(value) = query.analysis_params.get(param_name.trim_end_matches('?')) else {
+                continue;
+            };
+            let literal = crate::analysis_param_literal(value).replace('\'', "''");
+            sql = sql.replace(&format!("${}", index + 1), &format!("'{}'", literal));
+        }
+
+        tests.push_str("    #[tokio::test]\n");
+        tests.push_str("    #[ignore = \"runs interleaved transactions against DATABASE_URL\"]\n");
+        tests.push_str(&format!(
+            "    async fn {}_serialization_retries() {{\n",
+            query.name
+        ));
+        tests.push_str(&format!(
+            "        let retries = interleaved_retries(r\"{}\").await;\n",
+            sql
+        ));
+        tests.push_str(&format!(
+            "        assert!(retries > 0, \"{} caused no serialization failure\");\n",
+            query.name
+        ));
+        tests.push_str("    }\n\n");
+    }
+
+    if tests.is_empty() {
+        return tests;
+    }
+
+    let mut code = String::new();
+    code.push_str("\n#[cfg(test)]\n");
+    code.push_str("mod isolation_tests {\n");
+    code.push_str("    use super::*;\n\n");
+    // sqlx 0.7 has no `raw_sql`, executing a string without arguments uses the simple query
+    // protocol in both versions
+    let sqlx_version = module_queries
+        .first()
+        .map(|analyzed| analyzed.definition.sqlx_version)
+        .unwrap_or_default();
+    if sqlx_version.needs_compat() {
+        code.push_str(
+            &ISOLATION_TEST_HELPERS
+                .replace(
+                    "sqlx::raw_sql(\"SET TRANSACTION ISOLATION LEVEL SERIALIZABLE\")\n            .execute(&mut *tx)\n            .await?;",
+                    "sqlx::Executor::execute(&mut *tx, \"SET TRANSACTION ISOLATION LEVEL SERIALIZABLE\")\n            .await?;",
+                )
+                .replace(
+                    "sqlx::raw_sql(sql).execute(&mut *tx).await?;",
+                    "sqlx::Executor::execute(&mut *tx, sql).await?;",
+                ),
+        );
+    } else {
+        code.push_str(ISOLATION_TEST_HELPERS);
+    }
+    code.push_str(tests.trim_end());
+    code.push_str("\n}\n");
+    code
+}
+
+/// Helpers of the generated isolation tests
+/// Both transactions execute the statement before either commits, the first one holds its
+/// transaction open briefly so that the second one conflicts with it
+const ISOLATION_TEST_HELPERS: &str = r#"    /// Attempts of each transaction before the test fails
+    const MAX_ATTEMPTS: usize = 5;
+
+    /// Run the statement in two concurrent SERIALIZABLE transactions, retrying serialization
+    /// failures, and return the total number of retries
+    async fn interleaved_retries(sql: &str) -> usize {
+        let database_url =
+            std::env::var("DATABASE_URL").expect("DATABASE_URL must be set to run isolation tests");
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .max_connections(2)
+            .connect(&database_url)
+            .await
+            .expect("failed to connect to DATABASE_URL");
+        let (first, second) = tokio::join!(
+            run_with_retries(&pool, sql, std::time::Duration::from_millis(200)),
+            run_with_retries(&pool, sql, std::time::Duration::ZERO)
+        );
+        first + second
+    }
+
+    /// Run the statement in a SERIALIZABLE transaction until it commits, returning the retries
+    async fn run_with_retries(pool: &sqlx::PgPool, sql: &str, hold: std::time::Duration) -> usize {
+        for attempt in 0..MAX_ATTEMPTS {
+            let hold = if attempt == 0 {
+                hold
+            } else {
+                std::time::Duration::ZERO
+            };
+            match run_once(pool, sql, hold).await {
+                Ok(()) => return attempt,
+                Err(e) if classify_error(&e) == DbErrorKind::SerializationFailure => continue,
+                Err(e) => panic!("transaction failed: {}", e),
+            }
+        }
+        panic!("transaction did not commit after {} attempts", MAX_ATTEMPTS)
+    }
+
+    async fn run_once(
+        pool: &sqlx::PgPool,
+        sql: &str,
+        hold: std::time::Duration,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        sqlx::raw_sql("SET TRANSACTION ISOLATION LEVEL SERIALIZABLE")
+            .execute(&mut *tx)
+            .await?;
+        sqlx::raw_sql(sql).execute(&mut *tx).await?;
+        tokio::time::sleep(hold).await;
+        tx.commit().await
+    }
+
+"#;
+
+/// Most rows written by one statement of a batcher whose INSERT has a RETURNING clause
+const MAX_RETURNING_BATCH_SIZE: usize = 100;
+
+/// Generate the group commit batcher of a single-row INSERT with `batch`
+/// `{query}_batcher` spawns the batching task, `{query}_batched` queues a call on it and
+/// a private `{query}_batch` runs each batch as one multi-row INSERT
+fn generate_batch_helpers(
+    query: &QueryDefinition,
+    type_info: &QueryTypeInfo,
+    input_params: &str,
+    item_type: &str,
+    return_type: &str,
+) -> Result<String> {
+    let Some(batch) = &query.batch else {
+        return Ok(String::new());
+    };
+
+    // Split the INSERT around its VALUES row, which is repeated for every call in the batch
+    let (converted_sql, param_names, _variant_label) = &query.sql_variants[0];
+    let values_row = single_values_row(converted_sql).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Query '{}' uses batch, which requires an INSERT of a single `VALUES (...)` row",
+            query.name
+        )
+    })?;
+    if param_names.is_empty() {
+        anyhow::bail!(
+            "Query '{}' uses batch, which requires query parameters",
+            query.name
+        );
+    }
+    let sql_prefix = &converted_sql[..values_row.start];
+    let sql_row = &converted_sql[values_row.clone()];
+    let sql_suffix = &converted_sql[values_row.end..];
+
+    // PostgreSQL accepts at most 65535 bind parameters per statement, and rows with RETURNING are
+    // inserted by a CTE each, whose planning time grows faster than their count
+    let returns_rows = !type_info.output_types.is_empty();
+    let mut max_size = (batch.max_size as usize).min(65535 / param_names.len());
+    if returns_rows {
+        max_size = max_size.min(MAX_RETURNING_BATCH_SIZE);
+    }
+
+    // Queued calls: the params struct, or a tuple of the arguments in signature order
+    let use_structured_params = query.parameters_type.is_enabled();
+    let mut arg_names: Vec<String> = Vec::new();
+    let mut arg_types: Vec<String> = Vec::new();
+    for (name, rust_type) in param_names.iter().zip(&type_info.input_types) {
+        let clean_name = to_rust_ident(name.trim_end_matches('?'));
+        if !arg_names.contains(&clean_name) {
+            arg_names.push(clean_name);
+            arg_types.push(if rust_type.is_nullable || rust_type.is_optional {
+                format!("Option<{}>", rust_type.rust_type)
+            } else {
+                rust_type.rust_type.clone()
+            });
+        }
+    }
+    let (row_type, row_value, field_of) = if use_structured_params {
+        let struct_name = query
+            .parameters_type
+            .get_struct_name()
+            .map(|name| name.to_string())
+            .unwrap_or_else(|| format!("{}Params", to_pascal_case(&query.name)));
+        (struct_name, "params".to_string(), None)
+    } else if arg_names.len() == 1 {
+        (
+            format!("({},)", arg_types[0]),
+            format!("({},)", arg_names[0]),
+            Some(&arg_names),
+        )
+    } else {
+        (
+            format!("({})", arg_types.join(", ")),
+            format!("({})", arg_names.join(", ")),
+            Some(&arg_names),
+        )
+    };
+    let field = |name: &str| -> String {
+        match field_of {
+            Some(names) => format!(
+                "row.{}",
+                names.iter().position(|n| n == name).unwrap_or_default()
+            ),
+            None => format!("row.{}", name),
+        }
+    };
+    let single_call_args = match field_of {
+        Some(names) => (0..names.len())
+            .map(|i| format!("row.{}", i))
+            .collect::<Vec<_>>()
+            .join(", "),
+        None => "&row".to_string(),
+    };
+
+    let batcher_type = format!("{}Batcher", to_pascal_case(&query.name));
+    let batched_params = if use_structured_params {
+        format!("batcher: &{}, params: {}", batcher_type, row_type)
+    } else {
+        format!("batcher: &{}, {}", batcher_type, input_params)
+    };
+    let mut code = String::new();
+    code.push_str(&format!(
+        "/// Background task coalescing concurrent `{}` calls into multi-row INSERT statements\n",
+        query.name
+    ));
+    code.push_str(&format!(
+        "pub type {} = super::batching::Batcher<{}, {}>;\n\n",
+        batcher_type, row_type, return_type
+    ));
+
+    code.push_str(&format!(
+        "/// Spawn the batcher for `{}` on the current tokio runtime\n",
+        query.name
+    ));
+    code.push_str(&format!(
+        "/// A batch is written when it holds {} rows or {} ms after its first call\n",
+        max_size, batch.max_delay_ms
+    ));
+    code.push_str(&format!(
+        "pub fn {}_batcher(pool: sqlx::PgPool) -> {} {{\n",
+        query.name, batcher_type
+    ));
+    code.push_str(&format!(
+        "    super::batching::Batcher::spawn({}, std::time::Duration::from_millis({}), move |rows| {{\n",
+        max_size, batch.max_delay_ms
+    ));
+    code.push_str("        let pool = pool.clone();\n");
+    code.push_str(&format!(
+        "        async move {{ {}_batch(&pool, rows).await }}\n",
+        query.name
+    ));
+    code.push_str("    })\n");
+    code.push_str("}\n\n");
+
+    code.push_str(&format!(
+        "/// Same as `{}`, but written together with concurrent calls queued on the batcher\n",
+        query.name
+    ));
+    code.push_str(&format!(
+        "pub async fn {}_batched({}) -> {} {{\n",
+        query.name, batched_params, return_type
+    ));
+    code.push_str(&format!("    batcher.submit({}).await\n", row_value));
+    code.push_str("        .unwrap_or_else(|| Err(sqlx::Error::WorkerCrashed.into()))\n");
+    code.push_str("}\n\n");
+
+    code.push_str("/// Write a batch with one multi-row INSERT\n");
+    code.push_str("/// Falls back to one INSERT per row if the batch fails, so every caller gets its own result\n");
+    code.push_str(&format!(
+        "async fn {}_batch(pool: &sqlx::PgPool, rows: Vec<{}>) -> Vec<{}> {{\n",
+        query.name, row_type, return_type
+    ));
+    code.push_str(&format!(
+        "    if let Ok(results) = {}_batch_statement(pool, &rows).await {{\n",
+        query.name
+    ));
+    code.push_str("        return results;\n");
+    code.push_str("    }\n");
+    code.push_str("    let mut results = Vec::with_capacity(rows.len());\n");
+    code.push_str("    for row in rows {\n");
+    code.push_str(&format!(
+        "        results.push({}(pool, {}).await);\n",
+        query.name, single_call_args
+    ));
+    code.push_str("    }\n");
+    code.push_str("    results\n");
+    code.push_str("}\n\n");
+
+    if returns_rows {
+        // Each row is inserted by its own CTE returning the row's ordinal, as the RETURNING rows
+        // of a multi-row INSERT come in no guaranteed order
+        let statement = converted_sql.trim_end().trim_end_matches(';');
+        code.push_str("/// Run the INSERT of every row of a batch in one statement\n");
+        code.push_str("/// Each row is inserted by a CTE returning its ordinal, so results are matched to the rows\n");
+        code.push_str(
+            "/// by ordinal; fails unless the statement succeeds with a result for every row\n",
+        );
+        code.push_str(&format!(
+            "async fn {}_batch_statement(pool: &sqlx::PgPool, rows: &[{}]) -> Result<Vec<{}>, sqlx::Error> {{\n",
+            query.name, row_type, return_type
+        ));
+        code.push_str("    let mut sql = String::from(\"WITH \");\n");
+        code.push_str("    for i in 0..rows.len() {\n");
+        code.push_str("        if i > 0 {\n");
+        code.push_str("            sql.push_str(\",\\n\");\n");
+        code.push_str("        }\n");
+        code.push_str("        sql.push_str(&format!(\"automodel_row_{} AS (\\n\", i));\n");
+        code.push_str(&format!(
+            "        sql.push_str(&super::batching::renumber_params(\n    {},\n            i * {},\n        ));\n",
+            generate_indented_raw_string_literal(statement),
+            param_names.len()
+        ));
+        code.push_str("        sql.push_str(&format!(\"\\n, {} AS automodel_ordinal)\", i));\n");
+        code.push_str("    }\n");
+        code.push_str("    for i in 0..rows.len() {\n");
+        code.push_str("        if i > 0 {\n");
+        code.push_str("            sql.push_str(\"\\nUNION ALL\");\n");
+        code.push_str("        }\n");
+        code.push_str(
+            "        sql.push_str(&format!(\"\\nSELECT * FROM automodel_row_{}\", i));\n",
+        );
+        code.push_str("    }\n");
+        code.push_str("    sql.push_str(\"\\nORDER BY automodel_ordinal\");\n");
+    } else {
+        code.push_str("/// Run the multi-row INSERT of a batch\n");
+        code.push_str("/// Fails unless the statement succeeds with a result for every row\n");
+        code.push_str(&format!(
+            "async fn {}_batch_statement(pool: &sqlx::PgPool, rows: &[{}]) -> Result<Vec<{}>, sqlx::Error> {{\n",
+            query.name, row_type, return_type
+        ));
+        code.push_str(&format!(
+            "    let mut sql = String::from(\n{}\n    );\n",
+            generate_indented_raw_string_literal(sql_prefix)
+        ));
+        code.push_str("    for i in 0..rows.len() {\n");
+        code.push_str("        if i > 0 {\n");
+        code.push_str("            sql.push_str(\", \");\n");
+        code.push_str("        }\n");
+        code.push_str(&format!(
+            "        sql.push_str(&super::batching::renumber_params(\n    {},\n            i * {},\n        ));\n",
+            generate_indented_raw_string_literal(sql_row),
+            param_names.len()
+        ));
+        code.push_str("    }\n");
+        if !sql_suffix.is_empty() {
+            code.push_str(&format!(
+                "    sql.push_str(\n{}\n    );\n",
+                generate_indented_raw_string_literal(sql_suffix)
+            ));
+        }
+    }
+    code.push_str("    let mut query = sqlx::query(&sql);\n");
+    code.push_str("    for row in rows {\n");
+    for (name, rust_type) in param_names.iter().zip(&type_info.input_types) {
+        let value = field(&to_rust_ident(name.trim_end_matches('?')));
+        if rust_type.needs_json_wrapper {
+            code.push_str(&format!(
+                "        query = query.bind(serde_json::to_value(&{}).map_err(|e| sqlx::Error::Encode(Box::new(e)))?);\n",
+                value
+            ));
+        } else if is_copy_type(rust_type) {
+            code.push_str(&format!("        query = query.bind({});\n", value));
+        } else {
+            code.push_str(&format!("        query = query.bind(&{});\n", value));
+        }
+    }
+    code.push_str("    }\n");
+
+    if !returns_rows {
+        code.push_str("    query.execute(pool).await?;\n");
+        code.push_str("    Ok(rows.iter().map(|_| Ok(())).collect())\n");
+    } else {
+        let row_creation = if type_info.output_types.len() == 1 && query.return_type.is_none() {
+            generate_sqlx_value_extraction(&type_info.output_types[0], 0)
+        } else {
+            generate_sqlx_row_creation(query, type_info, item_type)
+        };
+        code.push_str("    let fetched = query.fetch_all(pool).await?;\n");
+        code.push_str("    if fetched.len() != rows.len() {\n");
+        code.push_str("        return Err(sqlx::Error::RowNotFound);\n");
+        code.push_str("    }\n");
+        code.push_str("    let mut results = Vec::with_capacity(rows.len());\n");
+        code.push_str("    for (i, row) in fetched.iter().enumerate() {\n");
+        code.push_str("        let ordinal: i32 = row.try_get(\"automodel_ordinal\")?;\n");
+        code.push_str("        if usize::try_from(ordinal).ok() != Some(i) {\n");
+        code.push_str("            return Err(sqlx::Error::RowNotFound);\n");
+        code.push_str("        }\n");
+        code.push_str(&format!(
+            "        let result: Result<{}, sqlx::Error> = (|| Ok({}))();\n",
+            item_type, row_creation
+        ));
+        code.push_str("        results.push(result.map_err(Into::into));\n");
+        code.push_str("    }\n");
+        code.push_str("    Ok(results)\n");
+    }
+    code.push_str("}\n");
+
+    Ok(code)
+}
+
+/// Check if a type is known to implement Copy (primitives and common Copy types)
+fn is_copy_type(rust_type: &RustType) -> bool {
+    !rust_type.needs_json_wrapper
+        && matches!(
+            rust_type.rust_type.as_str(),
+            "bool"
+                | "i8"
+                | "i16"
+                | "i32"
+                | "i64"
+                | "u32"
+                | "u64"
+                | "f32"
+                | "f64"
+                | "uuid::Uuid"
+                | "rust_decimal::Decimal"
+                | "chrono::NaiveDate"
+                | "chrono::NaiveTime"
+                | "chrono::NaiveDateTime"
+                | "chrono::DateTime<chrono::Utc>"
+        )
+}
+
+/// Generate `for_each_*`/`try_for_each_*` helpers which execute the query repeatedly,
+/// fetching chunks of rows ordered by the iterate key and resuming after the last key seen
+fn generate_iterate_helpers(
+    query: &QueryDefinition,
+    type_info: &QueryTypeInfo,
+    item_type: &str,
+    input_params: &str,
+    error_type: &str,
+) -> Result<String> {
+    let Some(iterate) = &query.iterate else {
+        return Ok(String::new());
+    };
+
+    // Validate the query can be paged through by key
+    if query.multiunzip || query.conditions_type.is_enabled() || type_info.parsed_sql.is_some() {
+        anyhow::bail!(
+            "Query '{}' uses iterate, which is not supported with multiunzip, conditions_type or conditional blocks",
+            query.name
+        );
+    }
+    let sql_start = query.sql.trim_start().to_uppercase();
+    if !sql_start.starts_with("SELECT") && !sql_start.starts_with("WITH") {
+        anyhow::bail!(
+            "Query '{}' uses iterate, which is only supported for SELECT queries",
+            query.name
+        );
+    }
+    let Some(key_index) = type_info
+        .output_types
+        .iter()
+        .position(|col| col.name == iterate.key)
+    else {
+        anyhow::bail!(
+            "Query '{}' iterate key '{}' is not an output column",
+            query.name,
+            iterate.key
+        );
+    };
+    let key_column = &type_info.output_types[key_index];
+    if key_column.rust_type.is_nullable {
+        anyhow::bail!(
+            "Query '{}' iterate key '{}' must be non-nullable",
+            query.name,
+            iterate.key
+        );
+    }
+    let key_type = generate_return_type(Some(key_column));
+
+    // Expression reading the key from a fetched item
+    let key_clone = if is_copy_type(&key_column.rust_type) {
+        ""
+    } else {
+        ".clone()"
+    };
+    let key_access = if type_info.output_types.len() == 1 && query.return_type.is_none() {
+        format!("item{}", key_clone)
+    } else if returns_tuple(query, type_info) {
+        format!("item.{}{}", key_index, key_clone)
+    } else {
+        format!(
+            "item.{}{}",
+            to_rust_ident(&to_snake_case(&key_column.name)),
+            key_clone
+        )
+    };
+
+    // Row conversion code
+    let row_creation = if type_info.output_types.len() == 1 && query.return_type.is_none() {
+        generate_sqlx_value_extraction(&type_info.output_types[0], 0)
+    } else {
+        generate_sqlx_row_creation(query, type_info, item_type)
+    };
+
+    // Wrap the query so the key range and chunk size can be applied to any SELECT
+    let (converted_sql, param_names, _variant_label) = &query.sql_variants[0];
+    let first_chunk_sql = format!(
+        "SELECT * FROM (\n{}\n) AS iterate_chunk\nORDER BY iterate_chunk.{}\nLIMIT {}",
+        converted_sql, iterate.key, iterate.chunk_size
+    );
+    let next_chunk_sql = format!(
+        "SELECT * FROM (\n{}\n) AS iterate_chunk\nWHERE iterate_chunk.{} > ${}\nORDER BY iterate_chunk.{}\nLIMIT {}",
+        converted_sql,
+        iterate.key,
+        param_names.len() + 1,
+        iterate.key,
+        iterate.chunk_size
+    );
+
+    let use_structured_params = query.parameters_type.is_enabled();
+    let param_prefix = if use_structured_params { "params." } else { "" };
+
+    // Arguments forwarded from for_each_* to try_for_each_*
+    let mut forwarded_args: Vec<String> = Vec::new();
+    if use_structured_params {
+        forwarded_args.push("params".to_string());
+    } else {
+        for name in param_names {
+            let clean_name = to_rust_ident(name.trim_end_matches('?'));
+            if !forwarded_args.contains(&clean_name) {
+                forwarded_args.push(clean_name);
+            }
+        }
+    }
+
+    let params_str = if input_params.is_empty() {
+        "executor: E, mut f: F".to_string()
+    } else {
+        format!("executor: E, {}, mut f: F", input_params)
+    };
+    let map_err = format!("map_err(|e| FErr::from({}::from(e)))", error_type);
+
+    let mut code = String::new();
+
+    // try_for_each_*: the callback may fail and stop the iteration
+    code.push_str(&format!(
+        "/// Process the results of `{}` in chunks of up to {} rows ordered by `{}`\n",
+        query.name, iterate.chunk_size, iterate.key
+    ));
+    code.push_str("/// Stops at the first error returned by the query or by `f`\n");
+    code.push_str(&format!(
+        "pub async fn try_for_each_{}<'c, E, F, Fut, FErr>({}) -> Result<(), FErr>\n",
+        query.name, params_str
+    ));
+    code.push_str("where\n");
+    code.push_str("    E: sqlx::Executor<'c, Database = sqlx::Postgres> + Copy,\n");
+    code.push_str(&format!("    F: FnMut(Vec<{}>) -> Fut,\n", item_type));
+    code.push_str("    Fut: std::future::Future<Output = Result<(), FErr>>,\n");
+    code.push_str(&format!("    FErr: From<{}>,\n", error_type));
+    code.push_str("{\n");
+
+    // Serialize custom typed parameters once
+    let mut serialized: Vec<String> = Vec::new();
+    for (i, name) in param_names.iter().enumerate() {
+        let clean_name = to_rust_ident(name.trim_end_matches('?'));
+        if type_info.input_types[i].needs_json_wrapper && !serialized.contains(&clean_name) {
+            code.push_str(&format!(
+                "    let {}_json = serde_json::to_value(&{}{}).map_err(|e| sqlx::Error::Encode(Box::new(e))).{}?;\n",
+                clean_name, param_prefix, clean_name, map_err
+            ));
+            serialized.push(clean_name);
+        }
+    }
+
+    code.push_str(&format!(
+        "    let mut last_key: Option<{}> = None;\n",
+        key_type
+    ));
+    code.push_str("    loop {\n");
+    code.push_str("        let mut query = if last_key.is_none() {\n");
+    code.push_str(&format!(
+        "            sqlx::query(\n{}\n            )\n",
+        generate_indented_raw_string_literal(&first_chunk_sql)
+    ));
+    code.push_str("        } else {\n");
+    code.push_str(&format!(
+        "            sqlx::query(\n{}\n            )\n",
+        generate_indented_raw_string_literal(&next_chunk_sql)
+    ));
+    code.push_str("        };\n");
+    for (i, name) in param_names.iter().enumerate() {
+        let clean_name = to_rust_ident(name.trim_end_matches('?'));
+        if type_info.input_types[i].needs_json_wrapper {
+            code.push_str(&format!(
+                "        query = query.bind(&{}_json);\n",
+                clean_name
+            ));
+        } else if is_copy_type(&type_info.input_types[i]) {
+            // Copy types are bound by value, everything else by reference to reuse it across chunks
+            code.push_str(&format!(
+                "        query = query.bind({}{});\n",
+                param_prefix, clean_name
+            ));
+        } else {
+            code.push_str(&format!(
+                "        query = query.bind(&{}{});\n",
+                param_prefix, clean_name
+            ));
+        }
+    }
+    if is_copy_type(&key_column.rust_type) {
+        code.push_str("        if let Some(key) = last_key {\n");
+    } else {
+        code.push_str("        if let Some(key) = &last_key {\n");
+    }
+    code.push_str("            query = query.bind(key);\n");
+    code.push_str("        }\n\n");
+    code.push_str(&format!(
+        "        let rows = query.fetch_all(executor).await.{}?;\n",
+        map_err
+    ));
+    code.push_str(&format!(
+        "        let chunk: Result<Vec<{}>, sqlx::Error> = rows.iter().map(|row| {{\n",
+        item_type
+    ));
+    code.push_str(&format!("        Ok({})\n", row_creation));
+    code.push_str("        }).collect();\n");
+    code.push_str(&format!("        let chunk = chunk.{}?;\n\n", map_err));
+    code.push_str(&format!(
+        "        let is_last_chunk = chunk.len() < {};\n",
+        iterate.chunk_size
+    ));
+    code.push_str(&format!(
+        "        last_key = chunk.last().map(|item| {});\n",
+        key_access
+    ));
+    code.push_str("        if chunk.is_empty() {\n");
+    code.push_str("            return Ok(());\n");
+    code.push_str("        }\n");
+    code.push_str("        f(chunk).await?;\n");
+    code.push_str("        if is_last_chunk {\n");
+    code.push_str("            return Ok(());\n");
+    code.push_str("        }\n");
+    code.push_str("    }\n");
+    code.push_str("}\n\n");
+
+    // for_each_*: infallible callback, delegates to try_for_each_*
+    code.push_str(&format!(
+        "/// Process the results of `{}` in chunks of up to {} rows ordered by `{}`\n",
+        query.name, iterate.chunk_size, iterate.key
+    ));
+    code.push_str(&format!(
+        "pub async fn for_each_{}<'c, E, F, Fut>({}) -> Result<(), {}>\n",
+        query.name, params_str, error_type
+    ));
+    code.push_str("where\n");
+    code.push_str("    E: sqlx::Executor<'c, Database = sqlx::Postgres> + Copy,\n");
+    code.push_str(&format!("    F: FnMut(Vec<{}>) -> Fut,\n", item_type));
+    code.push_str("    Fut: std::future::Future<Output = ()>,\n");
+    code.push_str("{\n");
+    let mut call_args = vec!["executor".to_string()];
+    call_args.extend(forwarded_args);
+    code.push_str(&format!(
+        "    try_for_each_{}({}, |chunk| {{\n",
+        query.name,
+        call_args.join(", ")
+    ));
+    code.push_str("        let fut = f(chunk);\n");
+    code.push_str("        async move {\n");
+    code.push_str("            fut.await;\n");
+    code.push_str(&format!("            Ok::<(), {}>(())\n", error_type));
+    code.push_str("        }\n");
+    code.push_str("    })\n");
+    code.push_str("    .await\n");
+    code.push_str("}\n");
+
+    Ok(code)
+}
+
+/// Generate the query execution part (common for both static and conditional queries)
+fn generate_query_execution(
+    body: &mut String,
+    query: &QueryDefinition,
+    type_info: &QueryTypeInfo,
+    return_type: &str,
+) {
+    // Always use .map_err(Into::into) since both Error<C> and ErrorReadOnly have From<sqlx::Error>
+    let map_err_suffix = ".map_err(Into::into)";
+
+    if type_info.output_types.is_empty() {
+        // For queries that don't return data (INSERT, UPDATE, DELETE)
+        body.push_str("    query.execute(executor).await?;\n");
+        body.push_str("    Ok(())\n");
+    } else if type_info.output_types.len() == 1 && query.return_type.is_none() {
+        // For queries that return a single column as a raw type (no explicit return_type)
+        match query.expect {
+            ExpectedResult::ExactlyOne | ExpectedResult::ExactlyOneOrInsert => {
+                body.push_str("    let row = query.fetch_one(executor).await?;\n");
+                let value_extraction =
+                    generate_sqlx_value_extraction(&type_info.output_types[0], 0);
+                body.push_str(&format!("    Ok({})\n", value_extraction));
+            }
+            ExpectedResult::PossibleOne => {
+                body.push_str("    let row = query.fetch_optional(executor).await?;\n");
+                body.push_str("    match row {\n");
+                body.push_str("        Some(row) => {\n");
+                let value_extraction =
+                    generate_sqlx_value_extraction(&type_info.output_types[0], 0);
+                if type_info.output_types[0].rust_type.is_nullable {
+                    body.push_str(&format!("            Ok({})\n", value_extraction));
+                } else {
+                    body.push_str(&format!("            Ok(Some({}))\n", value_extraction));
+                }
+                body.push_str("        },\n");
+                body.push_str("        None => Ok(None),\n");
+                body.push_str("    }\n");
+            }
+            ExpectedResult::AtLeastOne => {
+                body.push_str("    let rows = query.fetch_all(executor).await?;\n");
+                body.push_str("    if rows.is_empty() {\n");
+                body.push_str("        return Err(sqlx::Error::RowNotFound.into());\n");
+                body.push_str("    }\n");
+                body.push_str(
+                    "    let result: Result<Vec<_>, sqlx::Error> = rows.iter().map(|row| {\n",
+                );
+                let value_extraction =
+                    generate_sqlx_value_extraction(&type_info.output_types[0], 0);
+                body.push_str(&format!("        Ok({})\n", value_extraction));
+                body.push_str("    }).collect();\n");
+                body.push_str(&format!("    result{}\n", map_err_suffix));
+            }
+            ExpectedResult::Multiple => {
+                body.push_str("    let rows = query.fetch_all(executor).await?;\n");
+                body.push_str(
+                    "    let result: Result<Vec<_>, sqlx::Error> = rows.iter().map(|row| {\n",
+                );
+                let value_extraction =
+                    generate_sqlx_value_extraction(&type_info.output_types[0], 0);
+                body.push_str(&format!("        Ok({})\n", value_extraction));
+                body.push_str("    }).collect();\n");
+                body.push_str(&format!("    result{}\n", map_err_suffix));
+            }
+        }
+    } else {
+        // For queries that return multiple columns
+        match query.expect {
+            ExpectedResult::ExactlyOne | ExpectedResult::ExactlyOneOrInsert => {
+                body.push_str("    let row = query.fetch_one(executor).await?;\n");
+                body.push_str("    let result: Result<_, sqlx::Error> = (|| {\n");
+                let struct_creation = generate_sqlx_row_creation(query, type_info, return_type);
+                body.push_str(&format!("        Ok({})\n", struct_creation));
+                body.push_str("    })();\n");
+                body.push_str(&format!("    result{}\n", map_err_suffix));
+            }
+            ExpectedResult::PossibleOne => {
+                body.push_str("    let row = query.fetch_optional(executor).await?;\n");
+                body.push_str("    match row {\n");
+                body.push_str("        Some(row) => {\n");
+                body.push_str("            let result: Result<_, sqlx::Error> = (|| {\n");
+                let struct_creation = generate_sqlx_row_creation(query, type_info, return_type);
+                body.push_str(&format!("                Ok({})\n", struct_creation));
+                body.push_str("            })();\n");
+                body.push_str(&format!("            result.map(Some){}\n", map_err_suffix));
+                body.push_str("        },\n");
+                body.push_str("        None => Ok(None),\n");
+                body.push_str("    }\n");
+            }
+            ExpectedResult::AtLeastOne => {
+                body.push_str("    let rows = query.fetch_all(executor).await?;\n");
+                body.push_str("    if rows.is_empty() {\n");
+                body.push_str("        return Err(sqlx::Error::RowNotFound.into());\n");
+                body.push_str("    }\n");
+                body.push_str(
+                    "    let result: Result<Vec<_>, sqlx::Error> = rows.iter().map(|row| {\n",
+                );
+                let struct_creation = generate_sqlx_row_creation(query, type_info, return_type);
+                body.push_str(&format!("        Ok({})\n", struct_creation));
+                body.push_str("    }).collect();\n");
+                body.push_str(&format!("    result{}\n", map_err_suffix));
+            }
+            ExpectedResult::Multiple => {
+                body.push_str("    let rows = query.fetch_all(executor).await?;\n");
+                body.push_str(
+                    "    let result: Result<Vec<_>, sqlx::Error> = rows.iter().map(|row| {\n",
+                );
+                let struct_creation = generate_sqlx_row_creation(query, type_info, return_type);
+                body.push_str(&format!("        Ok({})\n", struct_creation));
+                body.push_str("    }).collect();\n");
+                body.push_str(&format!("    result{}\n", map_err_suffix));
+            }
+        }
+    }
+}
+
+/// Generate SQLx value extraction for a single column
+fn generate_sqlx_value_extraction(output_col: &OutputColumn, _index: usize) -> String {
+    let column_name = &output_col.name;
+
+    if output_col.rust_type.needs_json_wrapper {
+        // For custom types, we need to extract as serde_json::Value and then deserialize
+        let inner_type = &output_col.rust_type.rust_type;
+        if output_col.rust_type.is_nullable {
+            format!(
+                "row.try_get::<Option<serde_json::Value>, _>(\"{}\")?
+            .map(|v| serde_json::from_value::<{}>(v)
+            .map_err(|e| sqlx::Error::Decode(Box::new(e))))
+            .transpose()?",
+                column_name, inner_type
+            )
+        } else {
+            format!(
+                "serde_json::from_value::<{}>(
+            row.try_get::<serde_json::Value, _>(\"{}\")?)
+            .map_err(|e| sqlx::Error::Decode(Box::new(e)))?",
+                inner_type, column_name
+            )
+        }
+    } else if output_col.rust_type.is_numeric_as_f64 {
+        // NUMERIC decodes only into Decimal, convert to f64 after extraction
+        if output_col.rust_type.is_nullable {
+            format!(
+                "row.try_get::<Option<rust_decimal::Decimal>, _>(\"{}\")?
+            .map(|v| rust_decimal::prelude::ToPrimitive::to_f64(&v).unwrap_or(f64::NAN))",
+                column_name
+            )
+        } else {
+            format!(
+                "rust_decimal::prelude::ToPrimitive::to_f64(
+            &row.try_get::<rust_decimal::Decimal, _>(\"{}\")?)
+            .unwrap_or(f64::NAN)",
+                column_name
+            )
+        }
+    } else if output_col.rust_type.array_element.is_some() {
+        // Mapped elements decode as text and are parsed one by one
+        if output_col.rust_type.is_nullable {
+            format!(
+                "row.try_get::<Option<Vec<String>>, _>(\"{}\")?
+            .map(|values| super::array_elements::decode(\"{}\", values))
+            .transpose()?",
+                column_name, column_name
+            )
+        } else {
+            format!(
+                "super::array_elements::decode(
+            \"{}\",
+            row.try_get::<Vec<String>, _>(\"{}\")?)?",
+                column_name, column_name
+            )
+        }
+    } else if let Some(char_flag) = &output_col.rust_type.char_flag {
+        // Flags decode as their codes, mapped to the values of `char_flags`
+        let values = char_flag_values(char_flag);
+        if output_col.rust_type.is_nullable {
+            format!(
+                "row.try_get::<Option<{}>, _>(\"{}\")?
+            .map(|code| super::char_flags::decode(\"{}\", code, {}))
+            .transpose()?",
+                char_flag.code_type, column_name, column_name, values
+            )
+        } else {
+            format!(
+                "super::char_flags::decode(
+            \"{}\",
+            row.try_get::<{}, _>(\"{}\")?,
+            {})?",
+                column_name, char_flag.code_type, column_name, values
+            )
+        }
+    } else if let Some(currency) = &output_col.rust_type.currency {
+        // NUMERIC decodes into Decimal, the currency comes from `currency_columns`
+        let to_money = format!(
+            "|amount| super::money::Money::new(amount, \"{}\")",
+            currency
+        );
+        match (
+            output_col.rust_type.is_nullable,
+            output_col.rust_type.rust_type.starts_with("Vec<"),
+        ) {
+            (false, false) => format!(
+                "super::money::Money::new(row.try_get::<rust_decimal::Decimal, _>(\"{}\")?, \"{}\")",
+                column_name, currency
+            ),
+            (true, false) => format!(
+                "row.try_get::<Option<rust_decimal::Decimal>, _>(\"{}\")?
+            .map({})",
+                column_name, to_money
+            ),
+            (false, true) => format!(
+                "row.try_get::<Vec<rust_decimal::Decimal>, _>(\"{}\")?
+            .into_iter()
+            .map({})
+            .collect()",
+                column_name, to_money
+            ),
+            (true, true) => format!(
+                "row.try_get::<Option<Vec<rust_decimal::Decimal>>, _>(\"{}\")?
+            .map(|amounts| amounts.into_iter().map({}).collect())",
+                column_name, to_money
+            ),
+        }
+    } else if output_col.rust_type.is_numeric_as_i64 {
+        // NUMERIC decodes only into Decimal, convert to i64 checking the range after extraction
+        if output_col.rust_type.is_nullable {
+            format!(
+                "row.try_get::<Option<rust_decimal::Decimal>, _>(\"{0}\")?
+            .map(|v| super::numeric::to_i64(\"{0}\", v))
+            .transpose()?",
+                column_name
+            )
+        } else {
+            format!(
+                "super::numeric::to_i64(
+            \"{0}\",
+            row.try_get::<rust_decimal::Decimal, _>(\"{0}\")?)?",
+                column_name
+            )
+        }
+    } else {
+        // For standard types, extract directly
+        if output_col.rust_type.is_nullable {
+            format!(
+                "row.try_get::<Option<{}>, _>(\"{}\")?",
+                output_col.rust_type.rust_type, column_name
+            )
+        } else {
+            format!(
+                "row.try_get::<{}, _>(\"{}\")?",
+                output_col.rust_type.rust_type, column_name
+            )
+        }
+    }
+}
+
+/// Generate SQLx row conversion code for multi-column results (struct or tuple)
+/// Rows of queries with `map_row`/`try_map_row` are passed through the configured function,
+/// the code runs in a closure returning `Result<_, sqlx::Error>`
+fn generate_sqlx_row_creation(
+    query: &QueryDefinition,
+    type_info: &QueryTypeInfo,
+    return_type: &str,
+) -> String {
+    if returns_tuple(query, type_info) {
+        return generate_sqlx_tuple_creation(&type_info.output_types);
+    }
+    let creation = generate_sqlx_struct_creation(return_type, &type_info.output_types);
+    if let Some(map_row) = &query.map_row {
+        format!("{}({})", map_row, creation)
+    } else if let Some(try_map_row) = &query.try_map_row {
+        format!(
+            "{}({}).map_err(|e| sqlx::Error::Decode(e.into()))?",
+            try_map_row, creation
+        )
+    } else {
+        creation
+    }
+}
+
+/// Generate SQLx tuple creation code for multi-column results
+fn generate_sqlx_tuple_creation(output_types: &[OutputColumn]) -> String {
+    let mut creation = "(\n".to_string();
+
+    for (i, col) in output_types.iter().enumerate() {
+        let value_extraction = generate_sqlx_value_extraction(col, i);
+        creation.push_str(&format!("        {},\n", value_extraction));
+    }
+
+    creation.push_str("    )");
+    creation
+}
+
+/// Generate SQLx struct creation code for multi-column results
+fn generate_sqlx_struct_creation(struct_name: &str, output_types: &[OutputColumn]) -> String {
+    let mut creation = format!("{} {{\n", struct_name);
+
+    for (i, col) in output_types.iter().enumerate() {
+        let field_name = to_rust_ident(&to_snake_case(&col.name));
+        let value_extraction = generate_sqlx_value_extraction(col, i);
+        creation.push_str(&format!("        {}: {},\n", field_name, value_extraction));
+    }
+
+    creation.push_str("    }");
+    creation
+}
+
+/// Validates that a referenced struct exists and has matching parameter fields
+/// Returns Ok if validation passes, Err with a descriptive message if not
+pub fn validate_struct_reference(
+    struct_name: &str,
+    query_params: &[String],
+    query_param_types: &[crate::types_extractor::RustType],
+    available_structs: &std::collections::HashMap<String, Vec<(String, String)>>,
+    is_conditional_diff: bool,
+) -> Result<()> {
+    // Check if the struct exists
+    let struct_fields = available_structs.get(struct_name).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Referenced struct '{}' does not exist. Make sure it's defined by a previous query (either via parameters_type: true for params structs, or as a return type for multi-column queries)",
+            struct_name
+        )
+    })?;
+
+    // Build a map of query parameters for comparison
+    let mut query_field_map: std::collections::HashMap<String, String> =
+        std::collections::HashMap::new();
+    for (i, param_name) in query_params.iter().enumerate() {
+        let clean_param = param_name.trim_end_matches('?');
+        if let Some(param_type) = query_param_types.get(i) {
+            let type_str = if param_type.is_nullable || param_type.is_optional {
+                format!("Option<{}>", param_type.rust_type)
+            } else {
+                param_type.rust_type.clone()
+            };
+            query_field_map.insert(clean_param.to_string(), type_str);
+        }
+    }
+
+    // Check if all query parameters exist in the struct with matching types
+    for (param_name, param_type) in &query_field_map {
+        let struct_field = struct_fields
+            .iter()
+            .find(|(name, _)| name == param_name)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Query parameter '{}' not found in struct '{}'. Available fields: {}",
+                    param_name,
+                    struct_name,
+                    struct_fields
+                        .iter()
+                        .map(|(n, _)| n.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })?;
+
+        // For conditional diff, allow flexible type matching
+        // The struct can have String while query has Option<String>, since we're comparing old vs new
+        let types_match = if is_conditional_diff {
+            // Check if types are compatible: exact match OR struct is non-nullable and query is nullable
+            &struct_field.1 == param_type
+                || (param_type.starts_with("Option<")
+                    && param_type.ends_with(">")
+                    && &struct_field.1 == &param_type[7..param_type.len() - 1])
+        } else {
+            // For non-conditional, require exact match
+            &struct_field.1 == param_type
+        };
+
+        if !types_match {
+            anyhow::bail!(
+                "Type mismatch for parameter '{}' in struct '{}': expected '{}', but query requires '{}'",
+                param_name,
+                struct_name,
+                struct_field.1,
+                param_type
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Warnings reported for a query: performance issues, unknown types and renamed identifiers
+/// Warnings with a code listed in the query's `allow` are left out
+pub fn query_warnings(analyzed: &QueryDefinitionRuntime) -> Vec<Diagnostic> {
+    let mut warnings = Vec::new();
+    if let Some(perf) = &analyzed.performance_analysis {
+        warnings.extend(perf.warnings.clone());
+    }
+    warnings.extend(unknown_type_warnings(analyzed));
+    warnings.extend(renamed_identifier_warnings(analyzed));
+    warnings.extend(aggregate_overflow_warnings(analyzed));
+    warnings.extend(plan_regression_warnings(analyzed));
+    warnings.extend(missing_limit_warnings(analyzed));
+    let allowed = &analyzed.definition.allow_diagnostics;
+    warnings.retain(|warning| !allowed.contains(&warning.code));
+    warnings
+}
+
+/// Warnings for parameters and columns of PostgreSQL types without a Rust mapping, which are
+/// generated as String
+fn unknown_type_warnings(analyzed: &QueryDefinitionRuntime) -> Vec<Diagnostic> {
+    let param_names = parse_parameter_names_from_sql(&analyzed.definition.sql);
+    let names = param_names
+        .iter()
+        .map(|name| ("parameter", name.trim_end_matches('?')))
+        .zip(&analyzed.type_info.input_types)
+        .chain(
+            analyzed
+                .type_info
+                .output_types
+                .iter()
+                .map(|col| (("column", col.name.as_str()), &col.rust_type)),
+        );
+
+    let mut warnings = Vec::new();
+    for ((kind, name), rust_type) in names {
+        let Some(pg_type) = rust_type
+            .rust_type
+            .strip_prefix("/* Unknown type: ")
+            .and_then(|rest| rest.split(" */").next())
+        else {
+            continue;
+        };
+        warnings.push(Diagnostic::new(
+            DiagnosticCode::UnknownType,
+            format!(
+                "Query '{}' {} '{}' has PostgreSQL type '{}' without a Rust mapping and is generated as String, map it with `types`",
+                analyzed.definition.name, kind, name, pg_type
+            ),
+        ));
+    }
+    warnings
+}
+
+/// Warnings for SUM() and COUNT() output columns whose type can overflow or lose precision:
+/// NUMERIC sums decoded as Decimal without `numeric_as`, aggregates cast to integer or smallint
+/// and sums converted to f64
+fn aggregate_overflow_warnings(analyzed: &QueryDefinitionRuntime) -> Vec<Diagnostic> {
+    let query_name = &analyzed.definition.name;
+    let mut warnings = Vec::new();
+    for (name, function) in aggregate_output_columns(&analyzed.definition.sql) {
+        let Some(col) = analyzed
+            .type_info
+            .output_types
+            .iter()
+            .find(|col| col.name == name)
+        else {
+            continue;
+        };
+        let rust_type = &col.rust_type;
+        let message = if rust_type.is_numeric_as_f64 {
+            format!(
+                "Query '{}' column '{}' is a {}() converted to f64, which loses precision above 2^53, use `numeric_as: {{ {}: i64 }}` for a checked conversion",
+                query_name, name, function, name
+            )
+        } else if rust_type.rust_type == "rust_decimal::Decimal"
+            && analyzed.definition.numeric_as.is_none()
+        {
+            format!(
+                "Query '{}' column '{}' is a {}() returned as NUMERIC and generated as rust_decimal::Decimal, use `numeric_as: {{ {}: i64 }}` for a checked conversion to i64",
+                query_name, name, function, name
+            )
+        } else if rust_type.rust_type == "i32" || rust_type.rust_type == "i16" {
+            format!(
+                "Query '{}' column '{}' is a {}() cast to {}, which fails on large tables once the value is out of range, return it as bigint",
+                query_name, name, function, rust_type.rust_type
+            )
+        } else {
+            continue;
+        };
+        warnings.push(Diagnostic::new(DiagnosticCode::AggregateOverflow, message));
+    }
+    warnings
+}
+
+/// Warnings for plan estimates exceeding the query's `baseline` by more than its tolerance, and
+/// for baselines of queries whose plan could not be estimated
+pub(crate) fn plan_regression_warnings(analyzed: &QueryDefinitionRuntime) -> Vec<Diagnostic> {
+    let Some(baseline) = &analyzed.definition.baseline else {
+        return Vec::new();
+    };
+    let query_name = &analyzed.definition.name;
+    let messages = match &analyzed.type_info.plan_estimates {
+        Some(estimates) => baseline
+            .regressions(estimates)
+            .into_iter()
+            .map(|regression| {
+                format!(
+                    "Query '{}' {}, update it with `automodel baseline` if expected",
+                    query_name, regression
+                )
+            })
+            .collect(),
+        None if analyzed.is_mutation => vec![format!(
+            "Query '{}' has a baseline, which only applies to read-only queries",
+            query_name
+        )],
+        None => vec![format!(
+            "Query '{}' has a baseline, but its plan could not be estimated",
+            query_name
+        )],
+    };
+    messages
+        .into_iter()
+        .map(|message| Diagnostic::new(DiagnosticCode::PlanRegression, message))
+        .collect()
+}
+
+/// Warnings for read-only `expect: possible_one` queries without a LIMIT whose plan estimates
+/// several rows, which read every matching row to return the first one
+fn missing_limit_warnings(analyzed: &QueryDefinitionRuntime) -> Vec<Diagnostic> {
+    let definition = &analyzed.definition;
+    if analyzed.is_mutation
+        || definition.expect != ExpectedResult::PossibleOne
+        || crate::types_extractor::has_top_level_limit(&definition.sql)
+    {
+        return Vec::new();
+    }
+    let Some(rows) = analyzed
+        .type_info
+        .plan_estimates
+        .as_ref()
+        .and_then(|estimates| estimates.rows)
+        .filter(|rows| *rows > 1.0)
+    else {
+        return Vec::new();
+    };
+    vec![Diagnostic::new(
+        DiagnosticCode::MissingLimit,
+        format!(
+            "Query '{}' expects possible_one, but its plan estimates {} rows and it has no LIMIT, add LIMIT 1 or set `limit_possible_one: true`",
+            definition.name, rows
+        ),
+    )]
+}
+
+/// Notices for parameter and column names which are renamed in generated code
+/// Other Rust keywords are kept as raw identifiers (`r#type`) and need no notice
+fn renamed_identifier_warnings(analyzed: &QueryDefinitionRuntime) -> Vec<Diagnostic> {
+    let param_names = parse_parameter_names_from_sql(&analyzed.definition.sql);
+    let names = param_names
+        .iter()
+        .map(|name| ("parameter", name.trim_end_matches('?').to_string()))
+        .chain(
+            analyzed
+                .type_info
+                .output_types
+                .iter()
+                .map(|col| ("column", to_snake_case(&col.name))),
+        );
+
+    let mut warnings = Vec::new();
+    for (kind, name) in names {
+        let ident = to_rust_ident(&name);
+        let warning = Diagnostic::new(
+            DiagnosticCode::RenamedIdentifier,
+            format!(
+                "Query '{}' {} '{}' is a Rust keyword and is named '{}' in generated code",
+                analyzed.definition.name, kind, name, ident
+            ),
+        );
+        if ident != name && !ident.starts_with("r#") && !warnings.contains(&warning) {
+            warnings.push(warning);
+        }
+    }
+    warnings
+}
+
+/// Name of the private function looking up the existing row of a get-or-create query
+fn get_or_create_lookup_name(query_name: &str) -> String {
+    format!("{}_lookup", query_name)
+}
+
+/// Function arguments of a query as (clean name, Rust type, is Copy), in signature order
+fn signature_params(
+    query: &QueryDefinition,
+    type_info: &QueryTypeInfo,
+) -> Vec<(String, String, bool)> {
+    parse_parameter_names_from_sql(&query.sql)
+        .iter()
+        .zip(&type_info.input_types)
+        .map(|(name, rust_type)| {
+            let param_type = if rust_type.is_nullable || rust_type.is_optional {
+                format!("Option<{}>", rust_type.rust_type)
+            } else {
+                rust_type.rust_type.clone()
+            };
+            (
+                name.trim_end_matches('?').to_string(),
+                param_type,
+                is_copy_type(rust_type),
+            )
+        })
+        .collect()
+}
+
+/// Generate the functions of a query with `expect: exactly_one_or_insert`
+/// The SELECT becomes a private `{query}_lookup` function, and `{query}` looks the row up,
+/// inserts it with the companion `insert` query when missing and looks it up again, all in one
+/// transaction. The insert runs in a savepoint, so that a unique violation caused by a
+/// concurrent insert of the same row can be rolled back and the other row returned
+fn generate_get_or_create_functions(
+    analyzed: &QueryDefinitionRuntime,
+    module_queries: &[&QueryDefinitionRuntime],
+    emitted_struct_names: &mut std::collections::HashSet<String>,
+) -> Result<String> {
+    let query = &analyzed.definition;
+    let type_info = &analyzed.type_info;
+    let insert_name = query.insert.as_deref().unwrap_or_default();
+    let insert = module_queries
+        .iter()
+        .find(|other| other.definition.name == insert_name)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Query '{}' insert query '{}' does not exist in module '{}'",
+                query.name,
+                insert_name,
+                query.module
+            )
+        })?;
+    let insert_query = &insert.definition;
+
+    if insert_query.expect == ExpectedResult::ExactlyOneOrInsert
+        || insert_query.multiunzip
+        || insert_query.conditions_type.is_enabled()
+        || insert_query.parameters_type.is_enabled()
+        || insert_query.shard_by.is_some()
+        || insert_query.requires_session
+    {
+        anyhow::bail!(
+            "Query '{}' insert query '{}' must take plain parameters, without multiunzip, conditions_type, parameters_type, shard_by, requires_session or expect: exactly_one_or_insert",
+            query.name,
+            insert_name
+        );
+    }
+    // Concurrently inserted rows are only detected through unique violations
+    if !insert.constraints.iter().any(|constraint| {
+        matches!(
+            constraint.constraint_type.as_str(),
+            "unique" | "primary_key"
+        )
+    }) {
+        anyhow::bail!(
+            "Query '{}' insert query '{}' can violate no unique constraint, so rows inserted concurrently could not be detected",
+            query.name,
+            insert_name
+        );
+    }
+    if type_info.output_types.is_empty() {
+        anyhow::bail!(
+            "Query '{}' uses expect: exactly_one_or_insert, which requires a query returning the row",
+            query.name
+        );
+    }
+    if type_info.output_types.len() == 1
+        && query.return_type.is_none()
+        && type_info.output_types[0].rust_type.is_nullable
+    {
+        anyhow::bail!(
+            "Query '{}' uses expect: exactly_one_or_insert, which requires a non-nullable column when returning a single column",
+            query.name
+        );
+    }
+
+    // Arguments are the lookup parameters followed by those only used by the insert
+    let lookup_params = signature_params(query, type_info);
+    let insert_params = signature_params(insert_query, &insert.type_info);
+    let mut params = lookup_params.clone();
+    for (name, param_type, is_copy) in &insert_params {
+        match params.iter().find(|(other, _, _)| other == name) {
+            Some((_, other_type, _)) if other_type != param_type => anyhow::bail!(
+                "Query '{}' parameter '{}' is {}, but {} in insert query '{}'",
+                query.name,
+                name,
+                other_type,
+                param_type,
+                insert_name
+            ),
+            Some(_) => {}
+            None => params.push((name.clone(), param_type.clone(), *is_copy)),
+        }
+    }
+
+    // The lookup is the query itself, returning None for missing rows
+    let lookup_name = get_or_create_lookup_name(&query.name);
+    let mut lookup = query.clone();
+    lookup.name = lookup_name.clone();
+    lookup.expect = ExpectedResult::PossibleOne;
+    lookup.description = Some(format!("Look up the existing row of `{}`", query.name));
+    if lookup.return_type.is_none()
+        && type_info.output_types.len() > 1
+        && !returns_tuple(query, type_info)
+    {
+        lookup.return_type = Some(format!("{}Item", to_pascal_case(&query.name)));
+    }
+    let mut code = generate_function_code_without_enums(
+        &lookup,
+        type_info,
+        emitted_struct_names,
+        &[],
+        &analyzed.performance_analysis,
+        false,
+    )?
+    .replacen(
+        &format!("pub async fn {}(", lookup_name),
+        &format!("async fn {}(", lookup_name),
+        1,
+    );
+    code.push('\n');
+
+    // Values used by a later call are cloned, the last call takes them
+    let call_args = |call_params: &[(String, String, bool)],
+                     is_used_later: &dyn Fn(&str) -> bool| {
+        call_params
+            .iter()
+            .map(|(name, _, is_copy)| {
+                if !is_copy && is_used_later(name) {
+                    format!("{}.clone()", to_rust_ident(name))
+                } else {
+                    to_rust_ident(name)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let first_lookup_args = call_args(&lookup_params, &|_| true);
+    let insert_args = call_args(&insert_params, &|name| {
+        lookup_params.iter().any(|(other, _, _)| other == name)
+    });
+    let second_lookup_args = call_args(&lookup_params, &|_| false);
+    let with_executor = |args: String| {
+        if args.is_empty() {
+            "&mut *tx".to_string()
+        } else {
+            format!("&mut *tx, {}", args)
+        }
+    };
+
+    let error_type = format!(
+        "super::Error<{}>",
+        insert_query
+            .error_type
+            .clone()
+            .unwrap_or_else(|| format!("{}Constraints", to_pascal_case(insert_name)))
+    );
+    let params_str = std::iter::once("executor: &mut sqlx::PgConnection".to_string())
+        .chain(
+            params
+                .iter()
+                .map(|(name, param_type, _)| format!("{}: {}", to_rust_ident(name), param_type)),
+        )
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    if let Some(description) = &query.description {
+        code.push_str(&format!("/// {}\n///\n", description));
+    }
+    code.push_str(&format!(
+        "/// Returns the existing row, or inserts it with `{}` and returns the inserted row\n",
+        insert_name
+    ));
+    code.push_str("/// Runs in a transaction, a row inserted concurrently by another transaction is returned\n");
+    code.push_str(&format!(
+        "pub async fn {}({}) -> Result<{}, {}> {{\n",
+        query.name,
+        params_str,
+        base_return_type(query, type_info),
+        error_type
+    ));
+    code.push_str("    let mut tx = sqlx::Connection::begin(executor).await?;\n");
+    code.push_str(&format!(
+        "    if let Some(row) = {}({}).await? {{\n",
+        lookup_name,
+        with_executor(first_lookup_args)
+    ));
+    code.push_str("        tx.commit().await?;\n");
+    code.push_str("        return Ok(row);\n");
+    code.push_str("    }\n\n");
+    code.push_str(
+        "    // A unique violation means another transaction inserted the row in the meantime,\n",
+    );
+    code.push_str(
+        "    // rolling back to the savepoint keeps the transaction usable for looking it up\n",
+    );
+    code.push_str("    let mut savepoint = sqlx::Connection::begin(&mut *tx).await?;\n");
+    code.push_str(&format!(
+        "    match {}(&mut *savepoint{}).await {{\n",
+        insert_name,
+        if insert_args.is_empty() {
+            String::new()
+        } else {
+            format!(", {}", insert_args)
+        }
+    ));
+    code.push_str("        Ok(_) => savepoint.commit().await?,\n");
+    code.push_str("        Err(super::Error::ConstraintViolation(_, info))\n");
+    code.push_str("            if info.kind == super::ErrorConstraintKind::UniqueViolation =>\n");
+    code.push_str("        {\n");
+    code.push_str("            savepoint.rollback().await?\n");
+    code.push_str("        }\n");
+    code.push_str("        Err(e) => return Err(e),\n");
+    code.push_str("    }\n\n");
+    code.push_str(&format!(
+        "    let row = {}({})\n        .await?\n        .ok_or(super::Error::RowNotFound)?;\n",
+        lookup_name,
+        with_executor(second_lookup_args)
+    ));
+    code.push_str("    tx.commit().await?;\n");
+    code.push_str("    Ok(row)\n");
+    code.push_str("}\n");
+
+    if query.blocking {
+        code.push('\n');
+        code.push_str(&generate_blocking_wrapper(
+            &query.name,
+            &params_str,
+            &format!(
+                "Result<{}, {}>",
+                base_return_type(query, type_info),
+                error_type
+            ),
+        ));
+    }
+
+    Ok(code)
+}
+
+/// Generate the `{query}_preview` function of a DELETE with `cascade_preview`
+/// It runs the counting query built during analysis with the arguments of the DELETE
+fn generate_cascade_preview_function(
+    query: &QueryDefinition,
+    type_info: &QueryTypeInfo,
+    preview: &CascadePreview,
+) -> String {
+    let clean_param_names: Vec<String> = parse_parameter_names_from_sql(&query.sql)
+        .iter()
+        .map(|name| name.trim_end_matches('?').to_string())
+        .collect();
+    let mut preview_query = query.clone();
+    preview_query.name = format!("{}_preview", query.name);
+    preview_query.sql = preview.sql.clone();
+    preview_query.requires_session = false;
+
+    let mut code = String::new();
+    code.push_str(&format!(
+        "/// Count the rows `{}` would delete and the rows referencing them, without deleting anything\n",
+        query.name
+    ));
+    code.push_str(
+        "/// Referencing rows are counted per foreign key, following ON DELETE CASCADE foreign keys\n",
+    );
+    let recorded_values: Vec<(String, String)> = clean_param_names
+        .iter()
+        .zip(&type_info.input_types)
+        .map(|(name, rust_type)| {
+            let value = limited_param_value(
+                &preview_query,
+                &to_rust_ident(name),
+                is_list_param(rust_type),
+            );
+            (name.clone(), value)
+        })
+        .collect();
+    code.push_str(&generate_tracing_attribute(
+        &preview_query,
+        &clean_param_names,
+        &recorded_values,
+    ));
+
+    let input_params = generate_input_params_with_names(&type_info.input_types, &clean_param_names);
+    let executor_param = "executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>";
+    let params_str = if input_params.is_empty() {
+        executor_param.to_string()
+    } else {
+        format!("{}, {}", executor_param, input_params)
+    };
+    let return_type = "Result<super::CascadeImpact, super::ErrorReadOnly>";
+    code.push_str(&format!(
+        "pub async fn {}({}) -> {} {{\n",
+        preview_query.name, params_str, return_type
+    ));
+
+    let (converted_sql, param_names) = convert_named_params_to_positional(&preview.sql);
+    code.push_str(&format!(
+        "    let query = sqlx::query(\n{}\n    );\n",
+        generate_indented_raw_string_literal(&converted_sql)
+    ));
+    for name in &param_names {
+        let clean_name = name.trim_end_matches('?');
+        let Some(rust_type) = clean_param_names
+            .iter()
+            .position(|param| param == clean_name)
+            .and_then(|i| type_info.input_types.get(i))
+        else {
+            continue;
+        };
+        let ident = to_rust_ident(clean_name);
+        if rust_type.needs_json_wrapper {
+            code.push_str(&format!(
+                "    let query = query.bind(serde_json::to_value(&{}).map_err(|e| sqlx::Error::Encode(Box::new(e)))?);\n",
+                ident
             ));
-        } else if use_structured_params {
-            // For parameters_type, check if parameter is Some (for Option types)
-            body.push_str(&format!("    if params.{}.is_some() {{\n", clean_param));
+        } else if rust_type.rust_type == "String" {
+            code.push_str(&format!("    let query = query.bind(&{});\n", ident));
         } else {
-            // For regular conditional, check if parameter is Some
-            body.push_str(&format!("    if {}.is_some() {{\n", clean_param));
+            code.push_str(&format!("    let query = query.bind({});\n", ident));
+        }
+    }
+    code.push_str("    let row = query.fetch_one(executor).await?;\n");
+    code.push_str("    Ok(super::CascadeImpact {\n");
+    code.push_str("        deleted: sqlx::Row::try_get(&row, 0)?,\n");
+    code.push_str("        referencing: vec![\n");
+    for (i, foreign_key) in preview.foreign_keys.iter().enumerate() {
+        code.push_str("            super::ReferencingRows {\n");
+        code.push_str(&format!(
+            "                table: {:?},\n",
+            foreign_key.table
+        ));
+        code.push_str(&format!(
+            "                constraint: {:?},\n",
+            foreign_key.constraint
+        ));
+        code.push_str(&format!(
+            "                on_delete: super::OnDelete::{},\n",
+            foreign_key.on_delete
+        ));
+        code.push_str(&format!(
+            "                rows: sqlx::Row::try_get(&row, {})?,\n",
+            i + 1
+        ));
+        code.push_str("            },\n");
+    }
+    code.push_str("        ],\n");
+    code.push_str("    })\n");
+    code.push_str("}\n");
+
+    if query.blocking {
+        code.push('\n');
+        code.push_str(&generate_blocking_wrapper(
+            &preview_query.name,
+            &params_str,
+            return_type,
+        ));
+    }
+
+    code
+}
+
+/// Generate the `{query}_id_only` function of an INSERT with `id_only`, taking the same arguments
+/// and returning only the primary key of the inserted row
+fn generate_id_only_function(
+    analyzed: &QueryDefinitionRuntime,
+    id_only: &IdOnlyQuery,
+    emitted_struct_names: &mut std::collections::HashSet<String>,
+) -> Result<String> {
+    let query = &analyzed.definition;
+    let mut id_only_query = query.clone();
+    id_only_query.name = format!("{}_id_only", query.name);
+    id_only_query.description = Some(format!(
+        "Same as [`{}`], returning only the primary key of the inserted row",
+        query.name
+    ));
+    let (converted_sql, param_names) = convert_named_params_to_positional(&id_only.sql);
+    id_only_query.sql = id_only.sql.clone();
+    id_only_query.sql_variants = vec![(converted_sql, param_names, "base".to_string())];
+    // Composite keys are returned as tuples, the constraint enum of the query is reused
+    id_only_query.return_type = None;
+    id_only_query.return_shape = ReturnShape::Tuple;
+    id_only_query.map_row = None;
+    id_only_query.try_map_row = None;
+    if id_only_query.error_type.is_none() && !analyzed.constraints.is_empty() {
+        id_only_query.error_type = Some(format!("{}Constraints", to_pascal_case(&query.name)));
+    }
+    let id_only_type_info = QueryTypeInfo {
+        input_types: analyzed.type_info.input_types.clone(),
+        output_types: id_only.output_types.clone(),
+        parsed_sql: None,
+        value_checks: analyzed.type_info.value_checks.clone(),
+        cascade_preview: None,
+        estimated_rows: None,
+        id_only: None,
+        retry_lookups: Vec::new(),
+        bulk_update: None,
+        tables: analyzed.type_info.tables.clone(),
+        plan_estimates: None,
+    };
+
+    generate_function_code_without_enums(
+        &id_only_query,
+        &id_only_type_info,
+        emitted_struct_names,
+        &analyzed.constraints,
+        &None,
+        analyzed.is_mutation,
+    )
+}
+
+/// Generate the `{query}_retrying` function of an INSERT with `retrying`
+/// The insert runs in a savepoint, and a unique violation of a constraint with a lookup rolls it
+/// back and returns the conflicting row with a private `{query}_conflict_{constraint}` function
+fn generate_retrying_functions(
+    analyzed: &QueryDefinitionRuntime,
+    retry_lookups: &[RetryLookup],
+    emitted_struct_names: &mut std::collections::HashSet<String>,
+) -> Result<String> {
+    let query = &analyzed.definition;
+    let type_info = &analyzed.type_info;
+    if type_info.output_types.is_empty() {
+        anyhow::bail!(
+            "Query '{}' uses retrying, which requires a query returning the row",
+            query.name
+        );
+    }
+    let (_, insert_param_names, _) = &query.sql_variants[0];
+    let params = signature_params(query, type_info);
+
+    // Lookups take the parameters inserted into the columns of their constraint, and return the
+    // same type as the insert
+    let mut code = String::new();
+    let mut lookups = Vec::new();
+    for lookup in retry_lookups {
+        let lookup_name = format!(
+            "{}_conflict_{}",
+            query.name,
+            to_snake_case(&lookup.constraint)
+        );
+        let mut lookup_query = query.clone();
+        lookup_query.name = lookup_name.clone();
+        lookup_query.description = Some(format!(
+            "Look up the row conflicting with `{}` on `{}`",
+            query.name, lookup.constraint
+        ));
+        let (converted_sql, param_names) = convert_named_params_to_positional(&lookup.sql);
+        lookup_query.sql = lookup.sql.clone();
+        lookup_query.sql_variants = vec![(converted_sql, param_names.clone(), "base".to_string())];
+        lookup_query.expect = ExpectedResult::PossibleOne;
+        lookup_query.retrying = None;
+        lookup_query.id_only = false;
+        lookup_query.error_type = None;
+        lookup_query.not_found_message = None;
+        if lookup_query.return_type.is_none()
+            && type_info.output_types.len() > 1
+            && !returns_tuple(query, type_info)
+        {
+            lookup_query.return_type = Some(format!("{}Item", to_pascal_case(&query.name)));
+        }
+        let input_types = param_names
+            .iter()
+            .map(|name| {
+                insert_param_names
+                    .iter()
+                    .position(|other| other == name)
+                    .and_then(|i| type_info.input_types.get(i))
+                    .cloned()
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("Query '{}' has no parameter '{}'", query.name, name)
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let lookup_type_info = QueryTypeInfo {
+            input_types,
+            output_types: type_info.output_types.clone(),
+            parsed_sql: None,
+            value_checks: type_info.value_checks.clone(),
+            cascade_preview: None,
+            estimated_rows: None,
+            id_only: None,
+            retry_lookups: Vec::new(),
+            bulk_update: None,
+            tables: type_info.tables.clone(),
+            plan_estimates: None,
+        };
+        code.push_str(
+            &generate_function_code_without_enums(
+                &lookup_query,
+                &lookup_type_info,
+                emitted_struct_names,
+                &[],
+                &None,
+                false,
+            )?
+            .replacen(
+                &format!("pub async fn {}(", lookup_name),
+                &format!("async fn {}(", lookup_name),
+                1,
+            ),
+        );
+        code.push('\n');
+        lookups.push((
+            lookup,
+            lookup_name,
+            parse_parameter_names_from_sql(&lookup.sql),
+        ));
+    }
+
+    // Values used by a lookup are cloned for the insert
+    let insert_args = params
+        .iter()
+        .map(|(name, _, is_copy)| {
+            let is_looked_up = lookups
+                .iter()
+                .any(|(_, _, lookup_params)| lookup_params.contains(name));
+            if !is_copy && is_looked_up {
+                format!("{}.clone()", to_rust_ident(name))
+            } else {
+                to_rust_ident(name)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let error_type = format!(
+        "super::Error<{}>",
+        query
+            .error_type
+            .clone()
+            .unwrap_or_else(|| format!("{}Constraints", to_pascal_case(&query.name)))
+    );
+    let return_type = base_return_type(query, type_info);
+    let params_str = std::iter::once("executor: &mut sqlx::PgConnection".to_string())
+        .chain(
+            params
+                .iter()
+                .map(|(name, param_type, _)| format!("{}: {}", to_rust_ident(name), param_type)),
+        )
+        .collect::<Vec<_>>()
+        .join(", ");
+    let retrying_name = format!("{}_retrying", query.name);
+
+    code.push_str(&format!(
+        "/// Same as [`{}`], returning the row inserted concurrently by another transaction\n",
+        query.name
+    ));
+    code.push_str(&format!(
+        "/// The insert runs in a savepoint, a unique violation of {} rolls it back and\n",
+        lookups
+            .iter()
+            .map(|(lookup, _, _)| format!("`{}`", lookup.constraint))
+            .collect::<Vec<_>>()
+            .join(", ")
+    ));
+    code.push_str(
+        "/// returns the conflicting row instead, other errors are returned as they are\n",
+    );
+    code.push_str(&format!(
+        "pub async fn {}({}) -> Result<{}, {}> {{\n",
+        retrying_name, params_str, return_type, error_type
+    ));
+    code.push_str("    let mut savepoint = sqlx::Connection::begin(&mut *executor).await?;\n");
+    code.push_str(&format!(
+        "    let (constraint, info) = match {}(&mut *savepoint{}).await {{\n",
+        query.name,
+        if insert_args.is_empty() {
+            String::new()
+        } else {
+            format!(", {}", insert_args)
         }
+    ));
+    code.push_str("        Ok(row) => {\n");
+    code.push_str("            savepoint.commit().await?;\n");
+    code.push_str("            return Ok(row);\n");
+    code.push_str("        }\n");
+    code.push_str("        Err(super::Error::ConstraintViolation(constraint, info))\n");
+    code.push_str("            if info.kind == super::ErrorConstraintKind::UniqueViolation =>\n");
+    code.push_str("        {\n");
+    code.push_str("            savepoint.rollback().await?;\n");
+    code.push_str("            (constraint, info)\n");
+    code.push_str("        }\n");
+    code.push_str("        Err(e) => return Err(e),\n");
+    code.push_str("    };\n\n");
+    code.push_str("    let row = match info.constraint_name.as_str() {\n");
+    for (lookup, lookup_name, lookup_params) in &lookups {
+        let args = std::iter::once("&mut *executor".to_string())
+            .chain(lookup_params.iter().map(|name| to_rust_ident(name)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        code.push_str(&format!(
+            "        \"{}\" => {}({}).await?,\n",
+            lookup.constraint, lookup_name, args
+        ));
+    }
+    code.push_str("        _ => None,\n");
+    code.push_str("    };\n");
+    code.push_str("    row.ok_or(super::Error::ConstraintViolation(constraint, info))\n");
+    code.push_str("}\n");
+
+    if query.blocking {
+        code.push('\n');
+        code.push_str(&generate_blocking_wrapper(
+            &retrying_name,
+            &params_str,
+            &format!("Result<{}, {}>", return_type, error_type),
+        ));
+    }
+
+    Ok(code)
+}
+
+/// Generate the `{query}_bulk` function of an UPDATE with `bulk_update`, taking a Vec of records
+/// with a field per parameter and binding an array per field, as for `multiunzip`
+/// Rows returned by RETURNING use the result struct of the query
+fn generate_bulk_update_function(
+    analyzed: &QueryDefinitionRuntime,
+    bulk_update: &BulkUpdateQuery,
+    emitted_struct_names: &mut std::collections::HashSet<String>,
+) -> Result<String> {
+    let query = &analyzed.definition;
+    let mut bulk_query = query.clone();
+    bulk_query.name = format!("{}_bulk", query.name);
+    bulk_query.description = Some(format!(
+        "Same as [`{}`] for a list of records, updating the row of every record with one statement",
+        query.name
+    ));
+    let (converted_sql, param_names) = convert_named_params_to_positional(&bulk_update.sql);
+    bulk_query.sql = bulk_update.sql.clone();
+    bulk_query.sql_variants = vec![(converted_sql, param_names, "base".to_string())];
+    bulk_query.expect = ExpectedResult::Multiple;
+    bulk_query.multiunzip = true;
+    bulk_query.bulk_update = false;
+    bulk_query.not_found_message = None;
+    if bulk_query.return_type.is_none()
+        && analyzed.type_info.output_types.len() > 1
+        && !returns_tuple(query, &analyzed.type_info)
+    {
+        bulk_query.return_type = Some(format!("{}Item", to_pascal_case(&query.name)));
+    }
+    if bulk_query.error_type.is_none() && !analyzed.constraints.is_empty() {
+        bulk_query.error_type = Some(format!("{}Constraints", to_pascal_case(&query.name)));
+    }
+    // Values of `check_values: const` columns are left to the CHECK constraints of the table
+    let bulk_type_info = QueryTypeInfo {
+        input_types: analyzed.type_info.input_types.clone(),
+        output_types: analyzed.type_info.output_types.clone(),
+        parsed_sql: None,
+        value_checks: Vec::new(),
+        cascade_preview: None,
+        estimated_rows: None,
+        id_only: None,
+        retry_lookups: Vec::new(),
+        bulk_update: None,
+        tables: analyzed.type_info.tables.clone(),
+        plan_estimates: None,
+    };
+
+    generate_function_code_without_enums(
+        &bulk_query,
+        &bulk_type_info,
+        emitted_struct_names,
+        &analyzed.constraints,
+        &None,
+        analyzed.is_mutation,
+    )
+}
 
-        body.push_str(&format!(
-            "        final_sql = final_sql.replace(r\"{}\", r\"{}\");\n",
-            conditional_block,
-            &conditional_block[2..conditional_block.len() - 1]
-        ));
-        body.push_str(&format!(
-            "        included_params.push(\"{}\");\n",
-            clean_param
-        ));
-        body.push_str("    } else {\n");
-        body.push_str(&format!(
-            "        final_sql = final_sql.replace(r\"{}\", \"\");\n",
-            conditional_block
-        ));
-        body.push_str("    }\n\n");
+/// Argument of a step of a unit of work
+enum UnitOfWorkArg {
+    /// Field of the `{Unit}Params` struct
+    Param(String),
+    /// Expression reading the result of an earlier step
+    Result(String),
+}
+
+/// Generate the `perform_{unit}` function of the queries of a module with `unit_of_work: {unit}`
+/// It runs the steps in module order in one transaction, taking their arguments from the
+/// `{Unit}Params` struct. An argument named like a column of the result of an earlier
+/// `exactly_one` step takes its value from that result instead
+fn generate_unit_of_work_function(
+    unit: &str,
+    steps: &[&QueryDefinitionRuntime],
+    module_queries: &[&QueryDefinitionRuntime],
+    emitted_struct_names: &mut std::collections::HashSet<String>,
+) -> Result<String> {
+    let function_name = format!("perform_{}", unit);
+    let unit_pascal = to_pascal_case(unit);
+    let params_struct = format!("{}Params", unit_pascal);
+    let output_struct = format!("{}Output", unit_pascal);
+    let error_enum = format!("{}Error", unit_pascal);
+    if module_queries
+        .iter()
+        .any(|other| other.definition.name == function_name)
+    {
+        anyhow::bail!(
+            "Unit of work '{}' generates function '{}', which is already the name of a query",
+            unit,
+            function_name
+        );
+    }
+    for type_name in [&params_struct, &output_struct, &error_enum] {
+        if emitted_struct_names.contains(type_name) {
+            anyhow::bail!(
+                "Unit of work '{}' generates type '{}', which is already generated for a query",
+                unit,
+                type_name
+            );
+        }
+    }
+    if steps
+        .iter()
+        .any(|step| to_pascal_case(&step.definition.name) == "Transaction")
+    {
+        anyhow::bail!(
+            "Unit of work '{}' cannot have a step named 'transaction', which is the error variant of the transaction itself",
+            unit
+        );
     }
 
-    body.push_str("    #[allow(unused_assignments)]\n");
-    body.push_str("    let mut param_counter = 1;\n");
+    // Results of earlier steps as (column name, expression, Rust type, is Copy)
+    let mut results: Vec<(String, String, String, bool)> = Vec::new();
+    // Fields of the params struct as (name, Rust type, argument type, is Copy), in order of first use
+    let mut fields: Vec<(String, RustType, String, bool)> = Vec::new();
+    let mut step_args: Vec<Vec<UnitOfWorkArg>> = Vec::new();
+    let mut sensitivity = crate::codegen::types_generator::FieldSensitivity::new();
+    for step in steps {
+        let query = &step.definition;
+        let type_info = &step.type_info;
+        let mut args = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+        for ((name, param_type, is_copy), rust_type) in signature_params(query, type_info)
+            .into_iter()
+            .zip(&type_info.input_types)
+        {
+            // Repeated parameters are a single argument of the function
+            if !seen.insert(name.clone()) {
+                continue;
+            }
+            let is_field = fields.iter().any(|(field, _, _, _)| *field == name);
+            if let Some((_, expr, result_type, result_is_copy)) = results
+                .iter()
+                .rev()
+                .find(|(column, _, _, _)| *column == name)
+            {
+                if is_field {
+                    anyhow::bail!(
+                        "Unit of work '{}' parameter '{}' of step '{}' is both an argument of an earlier step and a result of an earlier step",
+                        unit,
+                        name,
+                        query.name
+                    );
+                }
+                if *result_type != param_type {
+                    anyhow::bail!(
+                        "Unit of work '{}' parameter '{}' of step '{}' is {}, but {} in the result of an earlier step",
+                        unit,
+                        name,
+                        query.name,
+                        param_type,
+                        result_type
+                    );
+                }
+                // Results are moved into the output at the end
+                args.push(UnitOfWorkArg::Result(if *result_is_copy {
+                    expr.clone()
+                } else {
+                    format!("{}.clone()", expr)
+                }));
+                continue;
+            }
+            if let Some((_, _, field_type, _)) =
+                fields.iter().find(|(field, _, _, _)| *field == name)
+            {
+                if *field_type != param_type {
+                    anyhow::bail!(
+                        "Unit of work '{}' parameter '{}' is {} in step '{}', but {} in an earlier step",
+                        unit,
+                        name,
+                        param_type,
+                        query.name,
+                        field_type
+                    );
+                }
+            } else {
+                // Optional parameters of conditional queries are Option arguments as well
+                let mut field_type = rust_type.clone();
+                field_type.is_nullable |= field_type.is_optional;
+                fields.push((name.clone(), field_type, param_type, is_copy));
+                if let Some(class) = query.sensitivity.get(&name) {
+                    sensitivity.insert(name.clone(), *class);
+                }
+            }
+            args.push(UnitOfWorkArg::Param(name));
+        }
+        step_args.push(args);
 
-    // Renumber base (non-conditional) parameters first
-    for param_name in parse_parameter_names_from_sql(&parsed_sql.base_sql) {
-        if !param_name.ends_with('?') {
-            body.push_str(&format!(
-                "    final_sql = final_sql.replace(r\"#{{{}}}\", &format!(\"${{}}\", param_counter));\n",
-                param_name
+        // Results of exactly_one steps can be arguments of later steps
+        if query.expect != ExpectedResult::ExactlyOne || returns_tuple(query, type_info) {
+            continue;
+        }
+        let step_var = to_rust_ident(&query.name);
+        if query.return_type.is_some() || type_info.output_types.len() > 1 {
+            for col in &type_info.output_types {
+                results.push((
+                    to_snake_case(&col.name),
+                    format!("{}.{}", step_var, to_rust_ident(&to_snake_case(&col.name))),
+                    generate_return_type(Some(col)),
+                    is_copy_type(&col.rust_type),
+                ));
+            }
+        } else if let Some(col) = type_info.output_types.first() {
+            results.push((
+                to_snake_case(&col.name),
+                step_var,
+                generate_return_type(Some(col)),
+                is_copy_type(&col.rust_type),
             ));
-            body.push_str("    param_counter += 1;\n");
         }
     }
 
-    // Renumber conditional parameters that are included
-    for (_, _, param_name) in &conditional_replacements {
-        let clean_param = if param_name.ends_with('?') {
-            param_name.trim_end_matches('?')
-        } else {
-            param_name
-        };
+    let step_list = steps
+        .iter()
+        .map(|step| format!("`{}`", step.definition.name))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut code = String::new();
 
-        body.push_str(&format!(
-            "    if included_params.contains(&r\"{}\") {{\n",
-            clean_param
+    // Params struct, with the sensitive fields of any step
+    let field_names: Vec<String> = fields.iter().map(|(name, _, _, _)| name.clone()).collect();
+    let field_types: Vec<RustType> = fields
+        .iter()
+        .map(|(_, rust_type, _, _)| rust_type.clone())
+        .collect();
+    let params_struct_code = generate_structured_params_struct(
+        unit,
+        &field_names,
+        &field_types,
+        &[],
+        steps.iter().all(|step| step.definition.auto_derives),
+        &sensitivity,
+        steps.iter().any(|step| step.definition.redact_debug),
+    );
+    if let Some(struct_code) = &params_struct_code {
+        code.push_str(&format!(
+            "/// Arguments of the steps of [`{}`] which are not results of earlier steps\n",
+            function_name
         ));
-        body.push_str(&format!(
-            "        final_sql = final_sql.replace(r\"#{{{}}}\", &format!(\"${{}}\", param_counter));\n",
-            param_name
+        code.push_str(struct_code);
+        code.push('\n');
+        emitted_struct_names.insert(params_struct.clone());
+    }
+
+    // Output struct, with the result of every step returning a value
+    let outputs: Vec<(String, String)> = steps
+        .iter()
+        .map(|step| {
+            (
+                to_rust_ident(&step.definition.name),
+                function_value_type(&step.definition, &step.type_info),
+            )
+        })
+        .filter(|(_, value_type)| value_type != "()")
+        .collect();
+    if !outputs.is_empty() {
+        code.push_str(&format!(
+            "/// Results of the steps of [`{}`] returning a value\n",
+            function_name
         ));
-        body.push_str("        param_counter += 1;\n");
-        body.push_str("    }\n");
+        code.push_str("#[derive(Debug, Clone)]\n");
+        code.push_str(&format!("pub struct {} {{\n", output_struct));
+        for (field, value_type) in &outputs {
+            code.push_str(&format!("    pub {}: {},\n", field, value_type));
+        }
+        code.push_str("}\n\n");
+        emitted_struct_names.insert(output_struct.clone());
     }
 
-    // Ensure param_counter is marked as used to avoid unused assignment warnings
-    body.push_str("    let _ = param_counter; // Suppress unused assignment warning\n");
-    body.push_str("\n    let mut query = sqlx::query(&final_sql);\n\n");
+    // Error enum, with a variant holding the error of each step
+    let variants: Vec<(String, String, String)> = steps
+        .iter()
+        .map(|step| {
+            let query = &step.definition;
+            (
+                to_pascal_case(&query.name),
+                query.name.clone(),
+                function_error_type(constraint_enum_name(query, &step.constraints).as_deref()),
+            )
+        })
+        .collect();
+    code.push_str(&format!(
+        "/// Error of [`{}`], by failing step; the transaction is rolled back\n",
+        function_name
+    ));
+    code.push_str("#[derive(Debug)]\n");
+    code.push_str(&format!("pub enum {} {{\n", error_enum));
+    code.push_str("    /// Beginning or committing the transaction failed\n");
+    code.push_str("    Transaction(super::ErrorReadOnly),\n");
+    for (variant, step_name, error_type) in &variants {
+        code.push_str(&format!("    /// Step `{}` failed\n", step_name));
+        code.push_str(&format!("    {}({}),\n", variant, error_type));
+    }
+    code.push_str("}\n\n");
+    code.push_str(&format!("impl std::fmt::Display for {} {{\n", error_enum));
+    code.push_str("    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {\n");
+    code.push_str("        match self {\n");
+    code.push_str(&format!(
+        "            Self::Transaction(e) => write!(f, \"{}: transaction failed: {{}}\", e),\n",
+        function_name
+    ));
+    for (variant, step_name, _) in &variants {
+        code.push_str(&format!(
+            "            Self::{}(e) => write!(f, \"{}: step {} failed: {{}}\", e),\n",
+            variant, function_name, step_name
+        ));
+    }
+    code.push_str("        }\n    }\n}\n\n");
+    code.push_str(&format!("impl std::error::Error for {} {{\n", error_enum));
+    code.push_str(
+        "    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {\n        match self {\n",
+    );
+    code.push_str("            Self::Transaction(e) => Some(e),\n");
+    for (variant, _, _) in &variants {
+        code.push_str(&format!("            Self::{}(e) => Some(e),\n", variant));
+    }
+    code.push_str("        }\n    }\n}\n\n");
 
-    // Bind parameters in the order they will appear in the final SQL
-    // First, bind base (non-conditional) parameters
-    for param_name in parse_parameter_names_from_sql(&parsed_sql.base_sql) {
-        if !param_name.ends_with('?') {
-            // Only bind non-conditional parameters
-            let clean_param = param_name.clone();
+    // Fields used by several steps are cloned, the last use takes them
+    let mut remaining_uses: std::collections::HashMap<&str, usize> =
+        std::collections::HashMap::new();
+    for args in &step_args {
+        for arg in args {
+            if let UnitOfWorkArg::Param(name) = arg {
+                *remaining_uses.entry(name.as_str()).or_default() += 1;
+            }
+        }
+    }
 
-            if let Some(param_index) = all_params.iter().position(|p| {
-                let clean_p = if p.ends_with('?') {
-                    p.trim_end_matches('?')
-                } else {
-                    p
-                };
-                clean_p == clean_param
-            }) {
-                if let Some(rust_type_info) = type_info.input_types.get(param_index) {
-                    if rust_type_info.needs_json_wrapper {
-                        if use_structured_params {
-                            body.push_str(&format!("    let {}_json = serde_json::to_value(&params.{}).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;\n", clean_param, clean_param));
-                        } else {
-                            body.push_str(&format!("    let {}_json = serde_json::to_value(&{}).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;\n", clean_param, clean_param));
-                        }
-                        body.push_str(&format!("    query = query.bind({}_json);\n", clean_param));
+    code.push_str(&format!(
+        "/// Unit of work `{}`: runs {} in order in one transaction\n",
+        unit, step_list
+    ));
+    code.push_str(
+        "/// Called with a transaction, the unit runs in a savepoint of it, which is rolled back when a step fails\n",
+    );
+    let params_arg = if params_struct_code.is_some() {
+        format!(", params: {}", params_struct)
+    } else {
+        String::new()
+    };
+    let return_value = if outputs.is_empty() {
+        "()".to_string()
+    } else {
+        output_struct.clone()
+    };
+    code.push_str(&format!(
+        "pub async fn {}(executor: impl sqlx::Acquire<'_, Database = sqlx::Postgres>{}) -> Result<{}, {}> {{\n",
+        function_name, params_arg, return_value, error_enum
+    ));
+    code.push_str(&format!(
+        "    let mut tx = executor\n        .begin()\n        .await\n        .map_err(|e| {}::Transaction(e.into()))?;\n",
+        error_enum
+    ));
+    for ((step, args), (variant, _, _)) in steps.iter().zip(&step_args).zip(&variants) {
+        let mut call_args = vec!["&mut *tx".to_string()];
+        for arg in args {
+            call_args.push(match arg {
+                UnitOfWorkArg::Result(expr) => expr.clone(),
+                UnitOfWorkArg::Param(name) => {
+                    let uses = remaining_uses.get_mut(name.as_str()).unwrap();
+                    *uses -= 1;
+                    let is_copy = fields
+                        .iter()
+                        .any(|(field, _, _, is_copy)| field == name && *is_copy);
+                    if *uses > 0 && !is_copy {
+                        format!("params.{}.clone()", to_rust_ident(name))
                     } else {
-                        if use_structured_params {
-                            body.push_str(&format!(
-                                "    query = query.bind(&params.{});\n",
-                                clean_param
-                            ));
-                        } else {
-                            body.push_str(&format!("    query = query.bind(&{});\n", clean_param));
-                        }
+                        format!("params.{}", to_rust_ident(name))
                     }
                 }
-            }
+            });
         }
-    }
-
-    // Then, bind conditional parameters only if they are included
-    for (_, _, param_name) in &conditional_replacements {
-        let clean_param = if param_name.ends_with('?') {
-            param_name.trim_end_matches('?')
+        let binding = if function_value_type(&step.definition, &step.type_info) == "()" {
+            String::new()
         } else {
-            param_name
+            format!("let {} = ", to_rust_ident(&step.definition.name))
         };
-
-        body.push_str(&format!(
-            "    if included_params.contains(&r\"{}\") {{\n",
-            clean_param
+        code.push_str(&format!(
+            "    {}{}({})\n        .await\n        .map_err({}::{})?;\n",
+            binding,
+            step.definition.name,
+            call_args.join(", "),
+            error_enum,
+            variant
+        ));
+    }
+    code.push_str(&format!(
+        "    tx.commit()\n        .await\n        .map_err(|e| {}::Transaction(e.into()))?;\n",
+        error_enum
+    ));
+    if outputs.is_empty() {
+        code.push_str("    Ok(())\n");
+    } else {
+        code.push_str(&format!(
+            "    Ok({} {{\n{}    }})\n",
+            output_struct,
+            outputs
+                .iter()
+                .map(|(field, _)| format!("        {},\n", field))
+                .collect::<String>()
         ));
+    }
+    code.push_str("}\n");
 
-        if let Some(param_index) = all_params.iter().position(|p| {
-            let clean_p = if p.ends_with('?') {
-                p.trim_end_matches('?')
-            } else {
-                p
-            };
-            clean_p == clean_param
-        }) {
-            if let Some(rust_type_info) = type_info.input_types.get(param_index) {
-                if rust_type_info.needs_json_wrapper {
-                    if use_conditional_diff {
-                        // For conditions_type, use new.field directly
-                        body.push_str(&format!("        let {}_json = serde_json::to_value(&new.{}).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;\n", clean_param, clean_param));
-                    } else if use_structured_params {
-                        // For parameters_type, unwrap from params struct
-                        body.push_str(&format!("        let {}_json = serde_json::to_value(&params.{}.as_ref().unwrap()).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;\n", clean_param, clean_param));
-                    } else {
-                        // For regular conditional, unwrap the Option
-                        body.push_str(&format!("        let {}_json = serde_json::to_value(&{}.as_ref().unwrap()).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;\n", clean_param, clean_param));
-                    }
-                    body.push_str(&format!(
-                        "        query = query.bind({}_json);\n",
-                        clean_param
-                    ));
-                } else {
-                    if use_conditional_diff {
-                        // For conditions_type, bind new.field directly
-                        body.push_str(&format!(
-                            "        query = query.bind(&new.{});\n",
-                            clean_param
-                        ));
-                    } else if use_structured_params {
-                        // For parameters_type, unwrap from params struct
-                        body.push_str(&format!(
-                            "        query = query.bind(params.{}.as_ref().unwrap());\n",
-                            clean_param
-                        ));
-                    } else {
-                        // For regular conditional, unwrap the Option
-                        body.push_str(&format!(
-                            "        query = query.bind({}.as_ref().unwrap());\n",
-                            clean_param
-                        ));
-                    }
-                }
-            }
-        }
+    Ok(code)
+}
 
-        body.push_str("    }\n\n");
-    }
+/// Generate the `{function}_blocking` wrapper of a query function, taking the same arguments
+/// and running the function on the runtime of the `blocking` module
+fn generate_blocking_wrapper(function_name: &str, params_str: &str, return_type: &str) -> String {
+    let args = signature_arg_names(params_str).join(", ");
+    // Anonymous lifetimes in `impl Trait` arguments are only allowed in async functions
+    let (generics, params_str) = if params_str.contains("'_") {
+        ("<'e>", params_str.replace("'_", "'e"))
+    } else {
+        ("", params_str.to_string())
+    };
+    format!(
+        "/// Blocking version of [`{name}`], for callers without a tokio runtime\n\
+         /// Panics when called from async code running on a tokio runtime\n\
+         #[cfg(feature = \"blocking\")]\n\
+         pub fn {name}_blocking{generics}({params}) -> {ret} {{\n    \
+         super::blocking::block_on({name}({args}))\n\
+         }}\n",
+        name = function_name,
+        generics = generics,
+        params = params_str,
+        ret = return_type,
+        args = args,
+    )
+}
 
-    generate_query_execution(body, query, type_info, return_type);
-    Ok(())
+/// Generate the `{function}_cancellable` variant of a query function, taking a pool and a
+/// cancellation token instead of the executor and running the function on a connection of the
+/// pool, which is cancelled on the server when the token is cancelled first
+/// `params_str` holds the parameters following the executor, starting with ", "
+fn generate_cancellable_variant(
+    function_name: &str,
+    params_str: &str,
+    return_type: &str,
+    has_constraints: bool,
+) -> String {
+    let args: Vec<String> = std::iter::once("&mut *connection".to_string())
+        .chain(signature_arg_names(params_str))
+        .collect();
+    let cancelled = if has_constraints {
+        "super::ErrorReadOnly::Cancelled.into()"
+    } else {
+        "super::ErrorReadOnly::Cancelled"
+    };
+    format!(
+        "/// Cancellable version of [`{name}`], running it on a connection of the pool\n\
+         /// When the token is cancelled first, the query is cancelled on the server, the connection is\n\
+         /// closed and `Cancelled` is returned\n\
+         #[cfg(feature = \"cancellation\")]\n\
+         pub async fn {name}_cancellable(pool: &sqlx::PgPool, token: &tokio_util::sync::CancellationToken{params}) -> {ret} {{\n    \
+         let mut connection = tokio::select! {{\n        \
+         biased;\n        \
+         _ = token.cancelled() => return Err({cancelled}),\n        \
+         connection = pool.acquire() => connection?,\n    \
+         }};\n    \
+         let backend_pid = super::cancellation::backend_pid(&mut connection).await?;\n    \
+         let result = tokio::select! {{\n        \
+         biased;\n        \
+         _ = token.cancelled() => None,\n        \
+         result = {name}({args}) => Some(result),\n    \
+         }};\n    \
+         match result {{\n        \
+         Some(result) => result,\n        \
+         None => {{\n            \
+         super::cancellation::cancel(pool, connection, backend_pid);\n            \
+         Err({cancelled})\n        \
+         }}\n    \
+         }}\n\
+         }}\n",
+        name = function_name,
+        params = params_str,
+        ret = return_type,
+        args = args.join(", "),
+        cancelled = cancelled,
+    )
 }
-/// Generate the query execution part (common for both static and conditional queries)
-fn generate_query_execution(
-    body: &mut String,
+
+/// Parameter types without the total order required by the keys of the query caches
+const UNORDERED_PARAM_TYPES: [&str; 5] = ["f32", "f64", "serde_json::", "PgRange", "PgInterval"];
+
+/// Generate the `{function}_cached` variant of a query with `cache_invalidate_on`, returning the
+/// result cached for the same arguments, and the cache it shares with the invalidation task
+/// `params_str` holds the parameters of the query function, starting with the executor
+fn generate_cached_variant(
     query: &QueryDefinition,
     type_info: &QueryTypeInfo,
+    invalidate_on: &CacheInvalidateOn,
+    params_str: &str,
     return_type: &str,
-) {
-    // Always use .map_err(Into::into) since both Error<C> and ErrorReadOnly have From<sqlx::Error>
-    let map_err_suffix = ".map_err(Into::into)";
-
-    if type_info.output_types.is_empty() {
-        // For queries that don't return data (INSERT, UPDATE, DELETE)
-        body.push_str("    query.execute(executor).await?;\n");
-        body.push_str("    Ok(())\n");
-    } else if type_info.output_types.len() == 1 && query.return_type.is_none() {
-        // For queries that return a single column as a raw type (no explicit return_type)
-        match query.expect {
-            ExpectedResult::ExactlyOne => {
-                body.push_str("    let row = query.fetch_one(executor).await?;\n");
-                let value_extraction =
-                    generate_sqlx_value_extraction(&type_info.output_types[0], 0);
-                body.push_str(&format!("    Ok({})\n", value_extraction));
-            }
-            ExpectedResult::PossibleOne => {
-                body.push_str("    let row = query.fetch_optional(executor).await?;\n");
-                body.push_str("    match row {\n");
-                body.push_str("        Some(row) => {\n");
-                let value_extraction =
-                    generate_sqlx_value_extraction(&type_info.output_types[0], 0);
-                if type_info.output_types[0].rust_type.is_nullable {
-                    body.push_str(&format!("            Ok({})\n", value_extraction));
-                } else {
-                    body.push_str(&format!("            Ok(Some({}))\n", value_extraction));
-                }
-                body.push_str("        },\n");
-                body.push_str("        None => Ok(None),\n");
-                body.push_str("    }\n");
-            }
-            ExpectedResult::AtLeastOne => {
-                body.push_str("    let rows = query.fetch_all(executor).await?;\n");
-                body.push_str("    if rows.is_empty() {\n");
-                body.push_str("        return Err(sqlx::Error::RowNotFound.into());\n");
-                body.push_str("    }\n");
-                body.push_str(
-                    "    let result: Result<Vec<_>, sqlx::Error> = rows.iter().map(|row| {\n",
-                );
-                let value_extraction =
-                    generate_sqlx_value_extraction(&type_info.output_types[0], 0);
-                body.push_str(&format!("        Ok({})\n", value_extraction));
-                body.push_str("    }).collect();\n");
-                body.push_str(&format!("    result{}\n", map_err_suffix));
-            }
-            ExpectedResult::Multiple => {
-                body.push_str("    let rows = query.fetch_all(executor).await?;\n");
-                body.push_str(
-                    "    let result: Result<Vec<_>, sqlx::Error> = rows.iter().map(|row| {\n",
-                );
-                let value_extraction =
-                    generate_sqlx_value_extraction(&type_info.output_types[0], 0);
-                body.push_str(&format!("        Ok({})\n", value_extraction));
-                body.push_str("    }).collect();\n");
-                body.push_str(&format!("    result{}\n", map_err_suffix));
-            }
-        }
-    } else {
-        // For queries that return multiple columns
-        match query.expect {
-            ExpectedResult::ExactlyOne => {
-                body.push_str("    let row = query.fetch_one(executor).await?;\n");
-                body.push_str("    let result: Result<_, sqlx::Error> = (|| {\n");
-                let struct_creation =
-                    generate_sqlx_struct_creation(return_type, &type_info.output_types);
-                body.push_str(&format!("        Ok({})\n", struct_creation));
-                body.push_str("    })();\n");
-                body.push_str(&format!("    result{}\n", map_err_suffix));
-            }
-            ExpectedResult::PossibleOne => {
-                body.push_str("    let row = query.fetch_optional(executor).await?;\n");
-                body.push_str("    match row {\n");
-                body.push_str("        Some(row) => {\n");
-                body.push_str("            let result: Result<_, sqlx::Error> = (|| {\n");
-                let struct_creation =
-                    generate_sqlx_struct_creation(return_type, &type_info.output_types);
-                body.push_str(&format!("                Ok({})\n", struct_creation));
-                body.push_str("            })();\n");
-                body.push_str(&format!("            result.map(Some){}\n", map_err_suffix));
-                body.push_str("        },\n");
-                body.push_str("        None => Ok(None),\n");
-                body.push_str("    }\n");
-            }
-            ExpectedResult::AtLeastOne => {
-                body.push_str("    let rows = query.fetch_all(executor).await?;\n");
-                body.push_str("    if rows.is_empty() {\n");
-                body.push_str("        return Err(sqlx::Error::RowNotFound.into());\n");
-                body.push_str("    }\n");
-                body.push_str(
-                    "    let result: Result<Vec<_>, sqlx::Error> = rows.iter().map(|row| {\n",
-                );
-                let struct_creation =
-                    generate_sqlx_struct_creation(return_type, &type_info.output_types);
-                body.push_str(&format!("        Ok({})\n", struct_creation));
-                body.push_str("    }).collect();\n");
-                body.push_str(&format!("    result{}\n", map_err_suffix));
-            }
-            ExpectedResult::Multiple => {
-                body.push_str("    let rows = query.fetch_all(executor).await?;\n");
-                body.push_str(
-                    "    let result: Result<Vec<_>, sqlx::Error> = rows.iter().map(|row| {\n",
+) -> Result<String> {
+    let params = signature_params(query, type_info);
+    if let Some((name, rust_type, _)) = params.iter().find(|(_, rust_type, _)| {
+        UNORDERED_PARAM_TYPES
+            .iter()
+            .any(|unordered| rust_type.contains(unordered))
+    }) {
+        anyhow::bail!(
+            "Query '{}' sets cache_invalidate_on, but parameter '{}' of type {} cannot be part of a cache key",
+            query.name,
+            name,
+            rust_type
+        );
+    }
+    let invalidation_key = match invalidate_on.key() {
+        Some(key) => {
+            let (index, (_, rust_type, _)) = params
+                .iter()
+                .enumerate()
+                .find(|(_, (name, _, _))| name == key)
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Query '{}' cache_invalidate_on key '{}' is not a parameter of the query",
+                        query.name,
+                        key
+                    )
+                })?;
+            if rust_type.starts_with("Option<") || rust_type.starts_with("Vec<") {
+                anyhow::bail!(
+                    "Query '{}' cache_invalidate_on key '{}' must be a single value, not {}",
+                    query.name,
+                    key,
+                    rust_type
                 );
-                let struct_creation =
-                    generate_sqlx_struct_creation(return_type, &type_info.output_types);
-                body.push_str(&format!("        Ok({})\n", struct_creation));
-                body.push_str("    }).collect();\n");
-                body.push_str(&format!("    result{}\n", map_err_suffix));
             }
+            Some(format!("key.{}.to_string()", index))
         }
-    }
-}
-
-/// Generate SQLx value extraction for a single column
-fn generate_sqlx_value_extraction(output_col: &OutputColumn, _index: usize) -> String {
-    let column_name = &output_col.name;
+        None => None,
+    };
 
-    if output_col.rust_type.needs_json_wrapper {
-        // For custom types, we need to extract as serde_json::Value and then deserialize
-        let inner_type = &output_col.rust_type.rust_type;
-        if output_col.rust_type.is_nullable {
-            format!(
-                "row.try_get::<Option<serde_json::Value>, _>(\"{}\")?
-            .map(|v| serde_json::from_value::<{}>(v)
-            .map_err(|e| sqlx::Error::Decode(Box::new(e))))
-            .transpose()?",
-                column_name, inner_type
-            )
+    // The arguments are moved into the query function, so the key holds copies of them
+    let key_types: Vec<String> = params
+        .iter()
+        .map(|(_, rust_type, _)| rust_type.clone())
+        .collect();
+    let key_values: Vec<String> = params
+        .iter()
+        .map(|(name, _, is_copy)| {
+            if *is_copy {
+                to_rust_ident(name)
+            } else {
+                format!("{}.clone()", to_rust_ident(name))
+            }
+        })
+        .collect();
+    let tuple = |items: Vec<String>| {
+        if items.len() == 1 {
+            format!("({},)", items[0])
         } else {
-            format!(
-                "serde_json::from_value::<{}>(
-            row.try_get::<serde_json::Value, _>(\"{}\")?)
-            .map_err(|e| sqlx::Error::Decode(Box::new(e)))?",
-                inner_type, column_name
-            )
+            format!("({})", items.join(", "))
         }
-    } else {
-        // For standard types, extract directly
-        if output_col.rust_type.is_nullable {
-            format!(
-                "row.try_get::<Option<{}>, _>(\"{}\")?",
-                output_col.rust_type.rust_type, column_name
-            )
-        } else {
-            format!(
-                "row.try_get::<{}, _>(\"{}\")?",
-                output_col.rust_type.rust_type, column_name
-            )
+    };
+    let key_type = tuple(key_types);
+    let key_value = tuple(key_values);
+
+    let cache = format!("{}_CACHE", to_snake_case(&query.name).to_uppercase());
+    let cleared_by = match invalidate_on.key() {
+        Some(key) => format!(
+            "a notification on `{}` with the `{}` of the call as payload",
+            invalidate_on.channel(),
+            key
+        ),
+        None => format!("a notification on `{}`", invalidate_on.channel()),
+    };
+    let tables: Vec<String> = type_info
+        .tables
+        .iter()
+        .map(|table| format!("{:?}", table))
+        .collect();
+    let args = signature_arg_names(params_str).join(", ");
+
+    let mut code = String::new();
+    code.push_str(&format!(
+        "/// Results of [`{name}`] cached by [`{name}_cached`]\n",
+        name = query.name
+    ));
+    code.push_str(&format!(
+        "static {}: super::cache_invalidation::QueryCache<{}, {}> =\n    super::cache_invalidation::QueryCache::new({:?}, &[{}]);\n\n",
+        cache,
+        key_type,
+        function_value_type(query, type_info),
+        invalidate_on.channel(),
+        tables.join(", ")
+    ));
+    code.push_str(&format!(
+        "/// Cached version of [`{}`], returning the result of an earlier call with the same\n",
+        query.name
+    ));
+    code.push_str(&format!("/// arguments until {} clears it\n", cleared_by));
+    code.push_str(
+        "/// Cached results are only cleared while the task of `cache_invalidation::spawn` runs\n",
+    );
+    code.push_str(
+        "/// Changes of the tables it reads notified by the cache invalidation triggers clear every result\n",
+    );
+    code.push_str("/// The cache is unbounded, it keeps the result of every distinct arguments\n");
+    code.push_str(&format!(
+        "pub async fn {}_cached({}) -> {} {{\n",
+        query.name, params_str, return_type
+    ));
+    code.push_str(&format!("    let key = {};\n", key_value));
+    code.push_str(&format!(
+        "    if let Some(value) = {}.get(&key) {{\n        return Ok(value);\n    }}\n",
+        cache
+    ));
+    code.push_str(&format!("    let generation = {}.generation();\n", cache));
+    code.push_str(&format!(
+        "    let value = {}({}).await?;\n",
+        query.name, args
+    ));
+    match invalidation_key {
+        Some(invalidation_key) => {
+            code.push_str(&format!(
+                "    let invalidation_key = Some({});\n",
+                invalidation_key
+            ));
+            code.push_str(&format!(
+                "    {}.insert(key, invalidation_key, generation, value.clone());\n",
+                cache
+            ));
         }
+        None => code.push_str(&format!(
+            "    {}.insert(key, None, generation, value.clone());\n",
+            cache
+        )),
     }
+    code.push_str("    Ok(value)\n");
+    code.push_str("}\n");
+    Ok(code)
 }
 
-/// Generate SQLx struct creation code for multi-column results
-fn generate_sqlx_struct_creation(struct_name: &str, output_types: &[OutputColumn]) -> String {
-    let mut creation = format!("{} {{\n", struct_name);
-
-    for (i, col) in output_types.iter().enumerate() {
-        let field_name = to_snake_case(&col.name);
-        let value_extraction = generate_sqlx_value_extraction(col, i);
-        creation.push_str(&format!("        {}: {},\n", field_name, value_extraction));
+/// `FeatureDisabled` error of a function behind a feature flag, converted to `Error<C>` for
+/// queries which can violate constraints
+fn feature_disabled_error(flag: &str, has_constraints: bool) -> String {
+    let error = format!("super::ErrorReadOnly::FeatureDisabled(\"{}\")", flag);
+    if has_constraints {
+        format!("{}.into()", error)
+    } else {
+        error
     }
+}
 
-    creation.push_str("    }");
-    creation
+/// Generate the stub replacing a function gated on a cargo feature when the feature is not
+/// enabled, so that callers compile either way
+fn generate_feature_stub(
+    function_name: &str,
+    flag: &str,
+    params_str: &str,
+    return_type: &str,
+    has_constraints: bool,
+) -> String {
+    let args = signature_arg_names(params_str);
+    let args = if args.len() == 1 {
+        args.join(", ")
+    } else {
+        format!("({})", args.join(", "))
+    };
+    format!(
+        "/// Stub of `{name}` failing with `FeatureDisabled`, as the `{flag}` feature is not enabled\n\
+         #[cfg(not(feature = \"{flag}\"))]\n\
+         pub async fn {name}({params}) -> {ret} {{\n    \
+         let _ = {args};\n    \
+         Err({error})\n\
+         }}\n",
+        name = function_name,
+        flag = flag,
+        params = params_str,
+        ret = return_type,
+        args = args,
+        error = feature_disabled_error(flag, has_constraints),
+    )
 }
 
-/// Validates that a referenced struct exists and has matching parameter fields
-/// Returns Ok if validation passes, Err with a descriptive message if not
-pub fn validate_struct_reference(
-    struct_name: &str,
-    query_params: &[String],
-    query_param_types: &[crate::types_extractor::RustType],
-    available_structs: &std::collections::HashMap<String, Vec<(String, String)>>,
-    is_conditional_diff: bool,
-) -> Result<()> {
-    // Check if the struct exists
-    let struct_fields = available_structs.get(struct_name).ok_or_else(|| {
-        anyhow::anyhow!(
-            "Referenced struct '{}' does not exist. Make sure it's defined by a previous query (either via parameters_type: true for params structs, or as a return type for multi-column queries)",
-            struct_name
-        )
-    })?;
+/// Argument names of a function signature such as "executor: &mut sqlx::PgConnection, id: i32"
+fn signature_arg_names(params_str: &str) -> Vec<String> {
+    split_signature_args(params_str)
+        .into_iter()
+        .filter_map(arg_name)
+        .collect()
+}
 
-    // Build a map of query parameters for comparison
-    let mut query_field_map: std::collections::HashMap<String, String> =
-        std::collections::HashMap::new();
-    for (i, param_name) in query_params.iter().enumerate() {
-        let clean_param = param_name.trim_end_matches('?');
-        if let Some(param_type) = query_param_types.get(i) {
-            let type_str = if param_type.is_nullable || param_type.is_optional {
-                format!("Option<{}>", param_type.rust_type)
-            } else {
-                param_type.rust_type.clone()
+/// Executor parameters of query functions a `Dao` method can pass its connection to
+const DAO_EXECUTOR_PARAMS: [&str; 3] = [
+    "executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>",
+    "executor: &mut sqlx::PgConnection",
+    "executor: impl sqlx::Acquire<'_, Database = sqlx::Postgres>",
+];
+
+/// Generate the `Dao<'c>` struct of a module, with a method per query function taking an
+/// executor, which calls the function on the borrowed connection
+/// Functions with generic parameters, such as the `for_each` functions, and functions taking a
+/// pool or a session are not included; `#[cfg]` attributes of the functions are kept
+fn generate_dao(functions_code: &[&str]) -> String {
+    let mut methods = String::new();
+    for code in functions_code {
+        let lines: Vec<&str> = code.lines().collect();
+        for (i, line) in lines.iter().enumerate() {
+            let Some((name, params, return_type)) = parse_function_signature(line) else {
+                continue;
             };
-            query_field_map.insert(clean_param.to_string(), type_str);
+            if matches!(name, "new" | "connection") {
+                continue;
+            }
+            let Some(other_params) = DAO_EXECUTOR_PARAMS
+                .iter()
+                .find_map(|executor| params.strip_prefix(executor))
+                .filter(|other| other.is_empty() || other.starts_with(", "))
+            else {
+                continue;
+            };
+
+            let cfg_attributes: Vec<&str> = lines[..i]
+                .iter()
+                .rev()
+                .take_while(|line| line.starts_with("#[") || line.starts_with("///"))
+                .filter(|line| line.starts_with("#[cfg"))
+                .copied()
+                .collect();
+            let args: Vec<String> = std::iter::once("&mut *self.conn".to_string())
+                .chain(signature_arg_names(other_params))
+                .collect();
+            methods.push_str(&format!("\n    /// Calls [`{}`] on the connection\n", name));
+            for attribute in cfg_attributes.iter().rev() {
+                methods.push_str(&format!("    {}\n", attribute));
+            }
+            methods.push_str(&format!(
+                "    pub async fn {name}(&mut self{params}) -> {ret} {{\n        {name}({args}).await\n    }}\n",
+                name = name,
+                params = other_params,
+                ret = return_type,
+                args = args.join(", "),
+            ));
         }
     }
 
-    // Check if all query parameters exist in the struct with matching types
-    for (param_name, param_type) in &query_field_map {
-        let struct_field = struct_fields
-            .iter()
-            .find(|(name, _)| name == param_name)
-            .ok_or_else(|| {
-                anyhow::anyhow!(
-                    "Query parameter '{}' not found in struct '{}'. Available fields: {}",
-                    param_name,
-                    struct_name,
-                    struct_fields
-                        .iter()
-                        .map(|(n, _)| n.as_str())
-                        .collect::<Vec<_>>()
-                        .join(", ")
-                )
-            })?;
+    format!(
+        "\n/// Queries of this module on one connection or transaction, e.g.\n\
+         /// `let mut dao = Dao::new(&mut transaction);` to run several queries in `transaction`\n\
+         pub struct Dao<'c> {{\n    \
+         conn: &'c mut sqlx::PgConnection,\n\
+         }}\n\n\
+         impl<'c> Dao<'c> {{\n    \
+         /// Borrow a connection, a pooled connection or a transaction\n    \
+         pub fn new(conn: &'c mut sqlx::PgConnection) -> Self {{\n        \
+         Self {{ conn }}\n    \
+         }}\n\n    \
+         /// The borrowed connection, e.g. to run queries of other modules between the calls\n    \
+         pub fn connection(&mut self) -> &mut sqlx::PgConnection {{\n        \
+         self.conn\n    \
+         }}\n\
+         {}}}\n",
+        methods
+    )
+}
 
-        // For conditional diff, allow flexible type matching
-        // The struct can have String while query has Option<String>, since we're comparing old vs new
-        let types_match = if is_conditional_diff {
-            // Check if types are compatible: exact match OR struct is non-nullable and query is nullable
-            &struct_field.1 == param_type
-                || (param_type.starts_with("Option<")
-                    && param_type.ends_with(">")
-                    && &struct_field.1 == &param_type[7..param_type.len() - 1])
-        } else {
-            // For non-conditional, require exact match
-            &struct_field.1 == param_type
-        };
+/// Name, parameter list and return type of a generated `pub async fn` line, e.g.
+/// `pub async fn get_user(executor: impl ..., id: i32) -> Result<User, super::ErrorReadOnly> {`
+/// Generic functions and signatures spanning several lines are not recognized
+fn parse_function_signature(line: &str) -> Option<(&str, &str, &str)> {
+    let (name, rest) = line.strip_prefix("pub async fn ")?.split_once('(')?;
+    if !name
+        .chars()
+        .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+    {
+        return None;
+    }
+    let rest = rest.strip_suffix(" {")?;
+    // The parameter list ends at the parenthesis closing the opening one
+    let mut depth = 1usize;
+    let params_end = rest.char_indices().find_map(|(j, ch)| {
+        match ch {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        (depth == 0).then_some(j)
+    })?;
+    let return_type = rest[params_end + 1..].strip_prefix(" -> ")?;
+    Some((name, &rest[..params_end], return_type))
+}
 
-        if !types_match {
-            anyhow::bail!(
-                "Type mismatch for parameter '{}' in struct '{}': expected '{}', but query requires '{}'",
-                param_name,
-                struct_name,
-                struct_field.1,
-                param_type
-            );
+/// Arguments of the generated function of every query after its executor, as (name, type)
+/// Queries whose function is generic or not generated, e.g. units of work, are left out
+fn query_signatures(
+    module_queries: &[&QueryDefinitionRuntime],
+    functions_code: &[&str],
+) -> Vec<(String, Vec<(String, String)>)> {
+    module_queries
+        .iter()
+        .filter_map(|analyzed| {
+            let name = analyzed.definition.name.as_str();
+            let params = functions_code
+                .iter()
+                .flat_map(|code| code.lines())
+                .filter_map(parse_function_signature)
+                .find(|(function, _, _)| *function == name)
+                .map(|(_, params, _)| params)?;
+            let args = split_signature_args(params)
+                .into_iter()
+                .skip(1)
+                .filter_map(|arg| {
+                    let (name, rust_type) = arg.split_once(':')?;
+                    Some((name.trim().to_string(), rust_type.trim().to_string()))
+                })
+                .collect();
+            Some((name.to_string(), args))
+        })
+        .collect()
+}
+
+/// Arguments of a function signature such as "executor: &mut sqlx::PgConnection, id: i32"
+/// Commas nested in generic arguments, tuples or slices do not separate arguments
+fn split_signature_args(params_str: &str) -> Vec<&str> {
+    let mut args = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, ch) in params_str.char_indices() {
+        match ch {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                args.push(&params_str[start..i]);
+                start = i + 1;
+            }
+            _ => {}
         }
     }
+    args.push(&params_str[start..]);
+    args.retain(|arg| !arg.trim().is_empty());
+    args
+}
 
-    Ok(())
+/// Name of a single "name: Type" argument
+fn arg_name(param: &str) -> Option<String> {
+    param
+        .split_once(':')
+        .map(|(name, _)| name.trim().to_string())
+        .filter(|name| !name.is_empty())
 }
 
 /// Generate code for a single module
+/// Modules with more queries than `max_queries_per_file` are split, their functions are written
+/// to `{module}/queries_N.rs` files re-exported from `{module}.rs`
 pub fn generate_code_for_module(
     analyzed_queries: &[QueryDefinitionRuntime],
     module: &str,
-) -> Result<(String, Vec<String>)> {
+    module_config: &ModuleConfig,
+    max_queries_per_file: Option<usize>,
+) -> Result<GeneratedModule> {
     let mut generated_code = String::new();
     let mut warnings = Vec::new();
 
@@ -1429,36 +7436,65 @@ pub fn generate_code_for_module(
         .collect();
 
     if module_queries.is_empty() {
-        return Ok((generated_code, warnings));
+        return Ok(GeneratedModule {
+            code: generated_code,
+            parts: Vec::new(),
+            warnings,
+            signatures: Vec::new(),
+        });
     }
 
     // Collect warnings from performance analysis
     for analyzed in &module_queries {
-        if let Some(perf) = &analyzed.performance_analysis {
-            warnings.extend(perf.warnings.clone());
-        }
+        warnings.extend(query_warnings(analyzed));
     }
 
+    // Same for every query, from DefaultsConfig::sqlx_version
+    let sqlx_version = module_queries[0].definition.sqlx_version;
+
     // Check if any query has output types (needs Row trait for try_get method)
     let needs_row_import = module_queries
         .iter()
         .any(|q| !q.type_info.output_types.is_empty());
 
+    // Module-level attributes must precede all items
+    for attribute in &module_config.attributes {
+        let attribute = attribute.trim();
+        let attribute = attribute
+            .strip_prefix("#![")
+            .and_then(|a| a.strip_suffix(']'))
+            .unwrap_or(attribute);
+        generated_code.push_str(&format!("#![{}]\n", attribute));
+    }
+    if !module_config.attributes.is_empty() {
+        generated_code.push('\n');
+    }
+
     if needs_row_import {
-        generated_code.push_str("use sqlx::Row;\n\n");
+        generated_code.push_str("use sqlx::Row;\n");
     }
+    // Error classification shared by all functions of the module
+    generated_code.push_str("#[allow(unused_imports)]\n");
+    generated_code.push_str("pub use super::{classify_error, DbErrorKind};\n");
+
+    // Extra imports from the module configuration
+    for import in &module_config.imports {
+        let import = import.trim();
+        let import = import.strip_prefix("use ").unwrap_or(import);
+        generated_code.push_str(&format!("use {};\n", import.trim_end_matches(';')));
+    }
+    generated_code.push('\n');
 
     // Extract and generate all unique enum types for this module
-    let mut all_enum_types = std::collections::HashMap::new();
-    for analyzed in &module_queries {
-        let enum_types = extract_enum_types(
-            &analyzed.type_info.input_types,
-            &analyzed.type_info.output_types,
-        );
-        for (enum_name, enum_variants, pg_type_name) in enum_types {
-            all_enum_types.insert(enum_name, (enum_variants, pg_type_name));
-        }
-    }
+    let all_enum_types = sorted_enum_types(module_queries.iter().flat_map(|analyzed| {
+        let type_info = &analyzed.type_info;
+        type_info.input_types.iter().chain(
+            type_info
+                .output_types
+                .iter()
+                .map(|column| &column.rust_type),
+        )
+    }));
 
     // Generate enum definitions once at the top of the module
     for (enum_name, (enum_variants, pg_type_name)) in all_enum_types {
@@ -1466,10 +7502,86 @@ pub fn generate_code_for_module(
             &enum_variants,
             &enum_name,
             &pg_type_name,
+            sqlx_version,
         ));
         generated_code.push('\n');
     }
 
+    // Generate the allowed values of `check_values: const` columns, once per module
+    let mut all_value_checks = std::collections::BTreeMap::new();
+    for analyzed in &module_queries {
+        for value_check in &analyzed.type_info.value_checks {
+            all_value_checks.insert(value_check.const_name.clone(), value_check);
+        }
+    }
+    for (const_name, value_check) in all_value_checks {
+        let values: Vec<String> = value_check
+            .values
+            .iter()
+            .map(|value| format!("{:?}", value))
+            .collect();
+        generated_code.push_str(&format!(
+            "/// Values allowed by the CHECK constraint of {}\npub const {}: &[&str] = &[{}];\n\n",
+            value_check.location,
+            const_name,
+            values.join(", ")
+        ));
+    }
+
+    // List the tables of all queries, e.g. for operational tooling mapping tables to modules
+    let module_tables: std::collections::BTreeSet<&String> = module_queries
+        .iter()
+        .flat_map(|analyzed| &analyzed.type_info.tables)
+        .collect();
+    let module_tables: Vec<String> = module_tables
+        .into_iter()
+        .map(|table| format!("{:?}", table))
+        .collect();
+    generated_code.push_str(&format!(
+        "/// Tables read or modified by the queries of this module\npub const TABLES: &[&str] = &[{}];\n\n",
+        module_tables.join(", ")
+    ));
+
+    // List the cached queries with the tables whose notifications invalidate them
+    let cached_queries: Vec<String> = module_queries
+        .iter()
+        .filter(|analyzed| analyzed.definition.cache_invalidation)
+        .map(|analyzed| {
+            let tables: Vec<String> = analyzed
+                .type_info
+                .tables
+                .iter()
+                .map(|table| format!("{:?}", table))
+                .collect();
+            format!("({:?}, &[{}])", analyzed.definition.name, tables.join(", "))
+        })
+        .collect();
+    if !cached_queries.is_empty() {
+        generated_code.push_str(&format!(
+            "/// Queries with `cache_invalidation` and the tables whose changes invalidate their cached results\npub const CACHED_QUERIES: &[(&str, &[&str])] = &[{}];\n\n",
+            cached_queries.join(", ")
+        ));
+    }
+
+    // List the sensitive parameters and columns, e.g. for redaction in logs and exports
+    let sensitive_fields: Vec<String> = module_queries
+        .iter()
+        .flat_map(|analyzed| {
+            let query = &analyzed.definition;
+            query.sensitivity.iter().map(move |(field, class)| {
+                format!("({:?}, {:?}, {:?})", query.name, field, class.as_str())
+            })
+        })
+        .collect();
+    if !sensitive_fields.is_empty() {
+        generated_code.push_str(&format!(
+            "/// Sensitive fields of the queries of this module, as (query, field, sensitivity)\npub const SENSITIVE_FIELDS: &[(&str, &str, &str)] = &[\n    {},\n];\n\n",
+            sensitive_fields.join(",\n    ")
+        ));
+    }
+
+    generated_code.push_str(&generate_warmup_function(&module_queries));
+
     // Track generated structs for validation
     let mut generated_structs: std::collections::HashMap<String, Vec<(String, String)>> =
         std::collections::HashMap::new();
@@ -1595,7 +7707,8 @@ pub fn generate_code_for_module(
         }
 
         // Track return type struct (if multiple columns OR explicit return_type configured)
-        let should_track_struct = type_info.output_types.len() > 1 || query.return_type.is_some();
+        let should_track_struct = (type_info.output_types.len() > 1 || query.return_type.is_some())
+            && !returns_tuple(query, type_info);
 
         if should_track_struct {
             let struct_name = if let Some(ref custom_name) = query.return_type {
@@ -1697,19 +7810,167 @@ pub fn generate_code_for_module(
         }
     }
 
-    // Generate functions
+    // Generate functions, in part files of at most max_queries_per_file queries for large modules
+    let chunk_size = match max_queries_per_file {
+        Some(max) if module_queries.len() > max => max,
+        _ => module_queries.len(),
+    };
     let mut emitted_struct_names = std::collections::HashSet::new();
+    let mut parts = Vec::new();
+    for chunk in module_queries.chunks(chunk_size) {
+        let mut functions_code = String::new();
+        for analyzed in chunk {
+            if analyzed.definition.expect == ExpectedResult::ExactlyOneOrInsert {
+                functions_code.push_str(&generate_get_or_create_functions(
+                    analyzed,
+                    &module_queries,
+                    &mut emitted_struct_names,
+                )?);
+                functions_code.push('\n');
+                continue;
+            }
+            let function_code = generate_function_code_without_enums(
+                &analyzed.definition,
+                &analyzed.type_info,
+                &mut emitted_struct_names,
+                &analyzed.constraints,
+                &analyzed.performance_analysis,
+                analyzed.is_mutation,
+            )?;
+            functions_code.push_str(&function_code);
+            functions_code.push('\n');
+            if let Some(preview) = &analyzed.type_info.cascade_preview {
+                functions_code.push_str(&generate_cascade_preview_function(
+                    &analyzed.definition,
+                    &analyzed.type_info,
+                    preview,
+                ));
+                functions_code.push('\n');
+            }
+            if let Some(id_only) = &analyzed.type_info.id_only {
+                functions_code.push_str(&generate_id_only_function(
+                    analyzed,
+                    id_only,
+                    &mut emitted_struct_names,
+                )?);
+                functions_code.push('\n');
+            }
+            if !analyzed.type_info.retry_lookups.is_empty() {
+                functions_code.push_str(&generate_retrying_functions(
+                    analyzed,
+                    &analyzed.type_info.retry_lookups,
+                    &mut emitted_struct_names,
+                )?);
+                functions_code.push('\n');
+            }
+            if let Some(bulk_update) = &analyzed.type_info.bulk_update {
+                functions_code.push_str(&generate_bulk_update_function(
+                    analyzed,
+                    bulk_update,
+                    &mut emitted_struct_names,
+                )?);
+                functions_code.push('\n');
+            }
+        }
+
+        // Generate tests for the SQL variants of conditional queries
+        let mut tests_code = generate_conditional_variant_tests(chunk);
+        tests_code.push_str(&generate_isolation_tests(chunk));
+        parts.push((functions_code, tests_code));
+    }
+
+    // Units of work call the functions of their steps, which can be in any part
+    let mut units: Vec<&str> = Vec::new();
     for analyzed in &module_queries {
-        let function_code = generate_function_code_without_enums(
-            &analyzed.definition,
-            &analyzed.type_info,
+        if let Some(unit) = &analyzed.definition.unit_of_work {
+            if !units.contains(&unit.as_str()) {
+                units.push(unit);
+            }
+        }
+    }
+    let mut units_code = String::new();
+    for unit in units {
+        let steps: Vec<&QueryDefinitionRuntime> = module_queries
+            .iter()
+            .copied()
+            .filter(|analyzed| analyzed.definition.unit_of_work.as_deref() == Some(unit))
+            .collect();
+        units_code.push('\n');
+        units_code.push_str(&generate_unit_of_work_function(
+            unit,
+            &steps,
+            &module_queries,
             &mut emitted_struct_names,
-            &analyzed.constraints,
-            &analyzed.performance_analysis,
-        )?;
-        generated_code.push_str(&function_code);
-        generated_code.push('\n');
+        )?);
+    }
+
+    // The Dao lives in the module file, where the functions of all parts are in scope
+    if module_config.dao {
+        let functions_code: Vec<&str> = parts
+            .iter()
+            .map(|(functions_code, _)| functions_code.as_str())
+            .chain(std::iter::once(units_code.as_str()))
+            .collect();
+        units_code.push_str(&generate_dao(&functions_code));
+    }
+
+    let functions_code: Vec<&str> = parts
+        .iter()
+        .map(|(functions_code, _)| functions_code.as_str())
+        .collect();
+    let signatures = query_signatures(&module_queries, &functions_code);
+
+    if parts.len() == 1 {
+        let (functions_code, tests_code) = parts.remove(0);
+        generated_code.push_str(&functions_code);
+        generated_code.push_str(&units_code);
+        generated_code.push_str(&tests_code);
+        return Ok(GeneratedModule {
+            code: generated_code,
+            parts: Vec::new(),
+            warnings,
+            signatures,
+        });
     }
 
-    Ok((generated_code, warnings))
+    // Part files see the module's imports, enums and the other parts through `use super::*`,
+    // and `super::` paths to the shared items resolve through the module's glob import
+    generated_code.push_str("#[allow(unused_imports)]\nuse super::*;\n\n");
+    let parts = parts
+        .into_iter()
+        .enumerate()
+        .map(|(i, (functions_code, tests_code))| {
+            let part_name = format!("queries_{}", i + 1);
+            generated_code.push_str(&format!("mod {};\npub use {}::*;\n", part_name, part_name));
+            let part_code = format!(
+                "{}\n\n#[allow(unused_imports)]\nuse super::*;\n\n{}{}",
+                GENERATED_PART_HEADER, functions_code, tests_code
+            );
+            (format!("{}.rs", part_name), part_code)
+        })
+        .collect();
+    generated_code.push_str(&units_code);
+
+    Ok(GeneratedModule {
+        code: generated_code,
+        parts,
+        warnings,
+        signatures,
+    })
+}
+
+/// First line of the part files of split modules, used to recognize them when cleaning up
+pub const GENERATED_PART_HEADER: &str =
+    "// This file was automatically generated by AutoModel. Do not edit manually.";
+
+/// Generated code of a module
+pub struct GeneratedModule {
+    /// Code of `{module}.rs`
+    pub code: String,
+    /// Files of the `{module}/` directory as (file name, code), empty unless the module is split
+    pub parts: Vec<(String, String)>,
+    /// Warnings reported for the queries of the module
+    pub warnings: Vec<Diagnostic>,
+    /// Arguments of the query functions after their executor, as (query, [(name, type)])
+    pub signatures: Vec<(String, Vec<(String, String)>)>,
 }
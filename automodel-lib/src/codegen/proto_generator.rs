@@ -0,0 +1,162 @@
+use super::module_generator::returns_tuple;
+use crate::query_definition_rt::QueryDefinitionRuntime;
+use crate::types_extractor::{enum_name_of, sorted_enum_types, OutputColumn};
+use crate::utils::{to_pascal_case, to_snake_case};
+
+/// First line of generated proto files, used to recognize them when cleaning up
+pub const PROTO_HEADER: &str =
+    "// This file was automatically generated by AutoModel. Do not edit manually.";
+
+/// Generate proto3 messages mirroring the result structs of the queries of a module with
+/// `proto: true`, in the package named after the module
+/// Field numbers follow the column order of the query
+/// Returns None if no tagged query of the module generates a result struct
+pub fn generate_proto_for_module(
+    analyzed_queries: &[QueryDefinitionRuntime],
+    module: &str,
+) -> Option<String> {
+    let mut messages = String::new();
+    let mut emitted_struct_names = std::collections::HashSet::new();
+    let mut enum_columns = Vec::new();
+
+    for analyzed in analyzed_queries
+        .iter()
+        .filter(|q| q.module() == module && q.definition.proto)
+    {
+        let query = &analyzed.definition;
+        let type_info = &analyzed.type_info;
+
+        // Same conditions as for generating the Rust result struct
+        let has_struct = (type_info.output_types.len() > 1 || query.return_type.is_some())
+            && !returns_tuple(query, type_info);
+        if !has_struct || type_info.output_types.is_empty() {
+            continue;
+        }
+        let struct_name = match &query.return_type {
+            Some(custom_name) => custom_name.clone(),
+            None => format!("{}Item", to_pascal_case(&query.name)),
+        };
+        if !emitted_struct_names.insert(struct_name.clone()) {
+            continue;
+        }
+
+        if let Some(description) = &query.description {
+            messages.push_str(&format!("// Row of `{}`: {}\n", query.name, description));
+        } else {
+            messages.push_str(&format!("// Row of `{}`\n", query.name));
+        }
+        messages.push_str(&format!("message {} {{\n", struct_name));
+        let mut tag = 0;
+        for col in &type_info.output_types {
+            tag += 1;
+            // Fields of flattened custom types are only known to their serde implementation
+            if query.flatten.contains(&col.name) {
+                messages.push_str(&format!(
+                    "  // Field {} of `{}` is flattened and has no proto representation\n",
+                    tag, col.name
+                ));
+                messages.push_str(&format!("  reserved {};\n", tag));
+                continue;
+            }
+            messages.push_str(&format!(
+                "  {} = {};\n",
+                proto_field(col, &to_snake_case(&col.name)),
+                tag
+            ));
+            if !col.rust_type.needs_json_wrapper {
+                enum_columns.push(&col.rust_type);
+            }
+        }
+        messages.push_str("}\n\n");
+    }
+
+    if emitted_struct_names.is_empty() {
+        return None;
+    }
+
+    let mut code = String::new();
+    code.push_str(PROTO_HEADER);
+    code.push_str("\n\nsyntax = \"proto3\";\n\n");
+    code.push_str(&format!("package {};\n\n", module));
+    for (enum_name, (variants, _)) in sorted_enum_types(enum_columns) {
+        // Values of proto3 enums share the scope of the package, so they are prefixed with the
+        // enum name, and the zero value stands for an unknown value
+        let prefix = to_snake_case(&enum_name).to_uppercase();
+        code.push_str(&format!("enum {} {{\n", enum_name));
+        code.push_str(&format!("  {}_UNSPECIFIED = 0;\n", prefix));
+        for (i, variant) in variants.iter().enumerate() {
+            code.push_str(&format!(
+                "  {}_{} = {};\n",
+                prefix,
+                proto_enum_value(variant),
+                i + 1
+            ));
+        }
+        code.push_str("}\n\n");
+    }
+    code.push_str(&messages);
+    Some(code.trim_end().to_string() + "\n")
+}
+
+/// Proto field declaration of a result struct field, without its number
+/// Nullable columns are `optional`, arrays are `repeated` (an empty list stands for NULL)
+fn proto_field(col: &OutputColumn, field_name: &str) -> String {
+    let rust_type = &col.rust_type;
+    let (is_repeated, base_type) = if rust_type.needs_json_wrapper {
+        // Custom types and JSON values are carried as their JSON text
+        (false, "string".to_string())
+    } else if rust_type.enum_variants.is_some() {
+        (rust_type.is_enum_array, enum_name_of(rust_type))
+    } else {
+        match rust_type
+            .rust_type
+            .strip_prefix("Vec<")
+            .and_then(|rest| rest.strip_suffix('>'))
+        {
+            // Byte arrays are a scalar in proto
+            Some("u8") => (false, "bytes".to_string()),
+            // Nested lists cannot be repeated twice and are carried as their JSON text
+            Some(element_type) if element_type.starts_with("Vec<") => (false, "string".to_string()),
+            Some(element_type) => (true, proto_type(element_type)),
+            None => (false, proto_type(&rust_type.rust_type)),
+        }
+    };
+
+    if is_repeated {
+        format!("repeated {} {}", base_type, field_name)
+    } else if rust_type.is_nullable {
+        format!("optional {} {}", base_type, field_name)
+    } else {
+        format!("{} {}", base_type, field_name)
+    }
+}
+
+/// Proto scalar type of a Rust type
+/// Types without a proto scalar are carried as strings, as in their serde representation
+fn proto_type(rust_type: &str) -> String {
+    match rust_type {
+        "i8" | "i16" | "i32" => "int32",
+        "i64" => "int64",
+        "u32" => "uint32",
+        "u64" => "uint64",
+        "f32" => "float",
+        "f64" => "double",
+        "bool" => "bool",
+        _ => "string",
+    }
+    .to_string()
+}
+
+/// Proto enum value name of a database enum label, e.g. "in-progress" becomes "IN_PROGRESS"
+fn proto_enum_value(label: &str) -> String {
+    to_snake_case(label)
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() {
+                ch.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
@@ -1,6 +1,7 @@
 use crate::{
-    types_extractor::{OutputColumn, RustType},
-    utils::{to_pascal_case, to_snake_case},
+    query_definition::{Sensitivity, SqlxVersion},
+    types_extractor::{FilterCondition, OutputColumn, RustType},
+    utils::{to_pascal_case, to_rust_ident, to_snake_case},
 };
 
 /// Build derive attribute string from a list of custom derives and default derives
@@ -20,6 +21,156 @@ fn build_derive_attribute(default_derives: &[&str], custom_derives: &[String]) -
     format!("#[derive({})]", all_derives.join(", "))
 }
 
+/// Sensitivity classification of struct fields, keyed by parameter or column name
+pub type FieldSensitivity = std::collections::BTreeMap<String, Sensitivity>;
+
+/// Field of a generated struct, documented with its classification when it is sensitive
+fn struct_field(
+    name: &str,
+    ident: &str,
+    field_type: &str,
+    sensitivity: &FieldSensitivity,
+) -> String {
+    let mut field = String::new();
+    if let Some(class) = sensitivity.get(name) {
+        field.push_str(&format!("    /// Sensitive: {}\n", class.as_str()));
+    }
+    field.push_str(&format!("    pub {}: {},\n", ident, field_type));
+    field
+}
+
+/// Derives every generated struct starts with, without Debug when it is implemented by hand to
+/// redact the sensitive fields
+fn base_derives(derives: &[&'static str], redact_debug: bool) -> Vec<&'static str> {
+    derives
+        .iter()
+        .copied()
+        .filter(|derive| !(redact_debug && *derive == "Debug"))
+        .collect()
+}
+
+/// Debug impl printing `[redacted]` instead of the values of sensitive fields
+/// Fields are given as (identifier, is sensitive)
+fn generate_redacted_debug_impl(struct_name: &str, fields: &[(String, bool)]) -> String {
+    let mut code = format!(
+        "impl std::fmt::Debug for {} {{\n    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {{\n        f.debug_struct(\"{}\")\n",
+        struct_name, struct_name
+    );
+    for (ident, is_sensitive) in fields {
+        let name = ident.trim_start_matches("r#");
+        if *is_sensitive {
+            code.push_str(&format!(
+                "            .field(\"{}\", &\"[redacted]\")\n",
+                name
+            ));
+        } else {
+            code.push_str(&format!(
+                "            .field(\"{}\", &self.{})\n",
+                name, ident
+            ));
+        }
+    }
+    code.push_str("            .finish()\n    }\n}\n");
+    code
+}
+
+/// Comparison traits derived for generated structs with `auto_derives`, in derive order
+const COMPARISON_DERIVES: [&str; 5] = ["PartialEq", "Eq", "Hash", "PartialOrd", "Ord"];
+
+/// Default derives of a generated struct, with the comparison traits every field type implements
+/// added when `auto_derives` is enabled
+fn struct_default_derives<'a>(
+    default_derives: &[&'static str],
+    auto_derives: bool,
+    field_types: impl IntoIterator<Item = &'a RustType>,
+) -> Vec<&'static str> {
+    let mut derives = default_derives.to_vec();
+    if !auto_derives {
+        return derives;
+    }
+
+    let mut supported = [true; COMPARISON_DERIVES.len()];
+    for rust_type in field_types {
+        for (is_supported, implemented) in supported.iter_mut().zip(comparison_traits(rust_type)) {
+            *is_supported &= implemented;
+        }
+    }
+    derives.extend(
+        COMPARISON_DERIVES
+            .iter()
+            .zip(supported)
+            .filter(|(_, is_supported)| *is_supported)
+            .map(|(derive, _)| *derive),
+    );
+    derives
+}
+
+/// Which of COMPARISON_DERIVES a field type implements
+/// Option and Vec implement the same comparison traits as their element type
+fn comparison_traits(rust_type: &RustType) -> [bool; COMPARISON_DERIVES.len()] {
+    const ALL: [bool; 5] = [true, true, true, true, true];
+    const EQ: [bool; 5] = [true, true, false, false, false];
+    const EQ_HASH: [bool; 5] = [true, true, true, false, false];
+    const PARTIAL: [bool; 5] = [true, false, false, true, false];
+    const PARTIAL_EQ: [bool; 5] = [true, false, false, false, false];
+    const NONE: [bool; 5] = [false, false, false, false, false];
+
+    // Custom types from `types:` mappings are not known to implement anything
+    if rust_type.needs_json_wrapper {
+        return NONE;
+    }
+    if rust_type.enum_variants.is_some() {
+        return ALL;
+    }
+
+    let mut element_type = rust_type.rust_type.as_str();
+    while let Some(inner) = element_type
+        .strip_prefix("Vec<")
+        .and_then(|rest| rest.strip_suffix('>'))
+    {
+        element_type = inner;
+    }
+
+    match element_type {
+        "bool"
+        | "i8"
+        | "i16"
+        | "i32"
+        | "i64"
+        | "u8"
+        | "u32"
+        | "u64"
+        | "(u32, u32)"
+        | "String"
+        | "rust_decimal::Decimal"
+        | "uuid::Uuid"
+        | "chrono::NaiveDate"
+        | "chrono::NaiveTime"
+        | "chrono::NaiveDateTime"
+        | "chrono::DateTime<chrono::Utc>"
+        | "std::net::IpAddr"
+        | "mac_address::MacAddress"
+        | "bit_vec::BitVec" => ALL,
+        // Floats have neither a total order nor a hash
+        "f32" | "f64" => PARTIAL,
+        "sqlx::postgres::types::PgInterval"
+        | "super::money::Money"
+        | "super::full_text_search::TsVector"
+        | "super::full_text_search::TsQuery" => EQ_HASH,
+        "serde_json::Value" | "sqlx::postgres::types::PgMoney" => EQ,
+        range if range.starts_with("sqlx::postgres::types::PgRange<") => EQ,
+        "sqlx::postgres::types::PgTimeTz"
+        | "sqlx::postgres::types::PgPoint"
+        | "sqlx::postgres::types::PgLine"
+        | "sqlx::postgres::types::PgLseg"
+        | "sqlx::postgres::types::PgBox"
+        | "sqlx::postgres::types::PgPath"
+        | "sqlx::postgres::types::PgPolygon"
+        | "sqlx::postgres::types::PgCircle" => PARTIAL_EQ,
+        _ => NONE,
+    }
+}
+
 /// Generate function parameter list with custom parameter names
 pub fn generate_input_params_with_names(
     input_types: &[RustType],
@@ -62,7 +213,7 @@ pub fn generate_input_params_with_names(
         .iter()
         .map(|param_name| {
             let param_type = unique_params.get(param_name).unwrap();
-            format!("{}: {}", param_name, param_type)
+            format!("{}: {}", to_rust_ident(param_name), param_type)
         })
         .collect::<Vec<_>>()
         .join(", ")
@@ -73,7 +224,7 @@ pub fn generate_input_params_with_names(
 /// For example: items: Vec<InsertUsersBatchRecord>
 pub fn generate_multiunzip_param(query_name: &str, param_name: &str) -> String {
     let struct_name = format!("{}Record", to_pascal_case(query_name));
-    format!("{}: Vec<{}>", param_name, struct_name)
+    format!("{}: Vec<{}>", to_rust_ident(param_name), struct_name)
 }
 
 /// Generate return type for single column results or empty results
@@ -95,9 +246,10 @@ pub fn generate_enum_definition(
     enum_variants: &[String],
     enum_name: &str,
     pg_type_name: &str,
+    sqlx_version: SqlxVersion,
 ) -> String {
     let mut enum_def = format!(
-        "#[derive(Debug, Clone, PartialEq, Eq)]\npub enum {} {{\n",
+        "#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]\npub enum {} {{\n",
         enum_name
     );
 
@@ -163,12 +315,26 @@ pub fn generate_enum_definition(
 "#
     ));
 
+    // sqlx 0.7 has no fallible Encode, the compat module of the shared items has its return type
+    let encode_result = if sqlx_version.needs_compat() {
+        "super::sqlx_compat::EncodeResult"
+    } else {
+        "Result<sqlx::encode::IsNull, Box<dyn std::error::Error + Send + Sync + 'static>>"
+    };
+
+    // Postgres reports column types without the schema, so accept the unqualified name as well
+    let unqualified_type_name = pg_type_name.rsplit('.').next().unwrap_or(pg_type_name);
+
     // Add SQLx Type implementation for enum
     enum_def.push_str(&format!(
         r#"impl sqlx::Type<sqlx::Postgres> for {} {{
     fn type_info() -> sqlx::postgres::PgTypeInfo {{
         sqlx::postgres::PgTypeInfo::with_name("{}")
     }}
+
+    fn compatible(ty: &sqlx::postgres::PgTypeInfo) -> bool {{
+        *ty == Self::type_info() || *ty == sqlx::postgres::PgTypeInfo::with_name("{}")
+    }}
 }}
 
 impl<'r> sqlx::Decode<'r, sqlx::Postgres> for {} {{
@@ -179,13 +345,31 @@ impl<'r> sqlx::Decode<'r, sqlx::Postgres> for {} {{
 }}
 
 impl<'q> sqlx::Encode<'q, sqlx::Postgres> for {} {{
-    fn encode_by_ref(&self, buf: &mut sqlx::postgres::PgArgumentBuffer) -> Result<sqlx::encode::IsNull, Box<dyn std::error::Error + Send + Sync + 'static>> {{
+    fn encode_by_ref(&self, buf: &mut sqlx::postgres::PgArgumentBuffer) -> {} {{
         <&str as sqlx::Encode<sqlx::Postgres>>::encode(&self.to_string(), buf)
     }}
 }}
 
+impl sqlx::postgres::PgHasArrayType for {} {{
+    fn array_type_info() -> sqlx::postgres::PgTypeInfo {{
+        sqlx::postgres::PgTypeInfo::array_of("{}")
+    }}
+
+    fn array_compatible(ty: &sqlx::postgres::PgTypeInfo) -> bool {{
+        *ty == Self::array_type_info() || *ty == sqlx::postgres::PgTypeInfo::array_of("{}")
+    }}
+}}
+
 "#,
-        enum_name, pg_type_name, enum_name, enum_name
+        enum_name,
+        pg_type_name,
+        unqualified_type_name,
+        enum_name,
+        enum_name,
+        encode_result,
+        enum_name,
+        pg_type_name,
+        unqualified_type_name
     ));
 
     enum_def
@@ -196,28 +380,50 @@ pub fn generate_result_struct_with_name(
     struct_name: &str,
     output_types: &[OutputColumn],
     custom_derives: &[String],
+    auto_derives: bool,
+    sensitivity: &FieldSensitivity,
+    redact_debug: bool,
+    flatten: &[String],
 ) -> Option<String> {
     if output_types.is_empty() {
         return None;
     }
 
-    let derive_attr = build_derive_attribute(&["Debug", "Clone"], custom_derives);
+    let redact_debug = redact_debug
+        && output_types
+            .iter()
+            .any(|col| sensitivity.contains_key(&col.name));
+    let default_derives = struct_default_derives(
+        &base_derives(&["Debug", "Clone"], redact_debug),
+        auto_derives,
+        output_types.iter().map(|col| &col.rust_type),
+    );
+    let derive_attr = build_derive_attribute(&default_derives, custom_derives);
     let mut struct_def = format!("{}\npub struct {} {{\n", derive_attr, struct_name);
 
+    let mut debug_fields = Vec::new();
     for col in output_types {
         let field_type = if col.rust_type.is_nullable {
             format!("Option<{}>", col.rust_type.rust_type)
         } else {
             col.rust_type.rust_type.clone()
         };
-        struct_def.push_str(&format!(
-            "    pub {}: {},\n",
-            to_snake_case(&col.name),
-            field_type
-        ));
+        let ident = to_rust_ident(&to_snake_case(&col.name));
+        let mut field = struct_field(&col.name, &ident, &field_type, sensitivity);
+        if flatten.contains(&col.name) {
+            // The attribute goes between the doc comment and the field
+            let field_start = field.find("    pub ").unwrap_or(0);
+            field.insert_str(field_start, "    #[serde(flatten)]\n");
+        }
+        struct_def.push_str(&field);
+        debug_fields.push((ident, sensitivity.contains_key(&col.name)));
     }
 
     struct_def.push_str("}\n");
+    if redact_debug {
+        struct_def.push('\n');
+        struct_def.push_str(&generate_redacted_debug_impl(struct_name, &debug_fields));
+    }
     Some(struct_def)
 }
 
@@ -228,14 +434,27 @@ pub fn generate_multiunzip_input_struct(
     param_names: &[String],
     input_types: &[RustType],
     custom_derives: &[String],
+    auto_derives: bool,
+    sensitivity: &FieldSensitivity,
+    redact_debug: bool,
 ) -> Option<String> {
     if input_types.is_empty() {
         return None;
     }
 
     let struct_name = format!("{}Record", to_pascal_case(query_name));
-    let derive_attr = build_derive_attribute(&["Debug", "Clone"], custom_derives);
+    let redact_debug = redact_debug
+        && param_names
+            .iter()
+            .any(|name| sensitivity.contains_key(name.trim_end_matches('?')));
+    let default_derives = struct_default_derives(
+        &base_derives(&["Debug", "Clone"], redact_debug),
+        auto_derives,
+        input_types,
+    );
+    let derive_attr = build_derive_attribute(&default_derives, custom_derives);
     let mut struct_def = format!("{}\npub struct {} {{\n", derive_attr, struct_name);
+    let mut debug_fields = Vec::new();
 
     for (i, param_name) in param_names.iter().enumerate() {
         if let Some(rust_type) = input_types.get(i) {
@@ -253,15 +472,17 @@ pub fn generate_multiunzip_input_struct(
                 base_type.to_string()
             };
 
-            struct_def.push_str(&format!(
-                "    pub {}: {},\n",
-                to_snake_case(param_name),
-                field_type
-            ));
+            let ident = to_rust_ident(&to_snake_case(param_name));
+            struct_def.push_str(&struct_field(param_name, &ident, &field_type, sensitivity));
+            debug_fields.push((ident, sensitivity.contains_key(param_name)));
         }
     }
 
     struct_def.push_str("}\n");
+    if redact_debug {
+        struct_def.push('\n');
+        struct_def.push_str(&generate_redacted_debug_impl(&struct_name, &debug_fields));
+    }
     Some(struct_def)
 }
 
@@ -273,6 +494,9 @@ pub fn generate_conditional_diff_struct(
     param_names: &[String],
     input_types: &[RustType],
     custom_derives: &[String],
+    auto_derives: bool,
+    sensitivity: &FieldSensitivity,
+    redact_debug: bool,
 ) -> Option<String> {
     if input_types.is_empty() {
         return None;
@@ -280,7 +504,22 @@ pub fn generate_conditional_diff_struct(
 
     let mut code = String::new();
 
-    let derive_attr = build_derive_attribute(&["Debug", "Clone", "PartialEq"], custom_derives);
+    // Only the conditional parameters become fields
+    let field_types = input_types
+        .iter()
+        .zip(param_names)
+        .filter(|(_, name)| name.ends_with('?'))
+        .map(|(rust_type, _)| rust_type);
+    let redact_debug = redact_debug
+        && param_names.iter().any(|name| {
+            name.ends_with('?') && sensitivity.contains_key(name.trim_end_matches('?'))
+        });
+    let default_derives = struct_default_derives(
+        &base_derives(&["Debug", "Clone", "PartialEq"], redact_debug),
+        auto_derives,
+        field_types,
+    );
+    let derive_attr = build_derive_attribute(&default_derives, custom_derives);
     code.push_str(&derive_attr);
     code.push('\n');
     code.push_str(&format!("pub struct {} {{\n", struct_name));
@@ -320,12 +559,19 @@ pub fn generate_conditional_diff_struct(
     }
 
     // Generate struct fields
+    let mut debug_fields = Vec::new();
     for param_name in &param_order {
         let param_type = unique_params.get(param_name).unwrap();
-        code.push_str(&format!("    pub {}: {},\n", param_name, param_type));
+        let ident = to_rust_ident(param_name);
+        code.push_str(&struct_field(param_name, &ident, param_type, sensitivity));
+        debug_fields.push((ident, sensitivity.contains_key(param_name)));
     }
 
     code.push_str("}\n");
+    if redact_debug {
+        code.push('\n');
+        code.push_str(&generate_redacted_debug_impl(struct_name, &debug_fields));
+    }
 
     Some(code)
 }
@@ -356,7 +602,11 @@ pub fn generate_conditional_diff_params(
                 } else {
                     rust_type.rust_type.clone()
                 };
-                non_conditional_params.push(format!("{}: {}", param_name, final_type));
+                non_conditional_params.push(format!(
+                    "{}: {}",
+                    to_rust_ident(param_name),
+                    final_type
+                ));
             }
         }
     }
@@ -379,6 +629,9 @@ pub fn generate_structured_params_struct(
     param_names: &[String],
     input_types: &[RustType],
     custom_derives: &[String],
+    auto_derives: bool,
+    sensitivity: &FieldSensitivity,
+    redact_debug: bool,
 ) -> Option<String> {
     if input_types.is_empty() {
         return None;
@@ -387,7 +640,16 @@ pub fn generate_structured_params_struct(
     let struct_name = format!("{}Params", to_pascal_case(query_name));
     let mut code = String::new();
 
-    let derive_attr = build_derive_attribute(&["Debug", "Clone"], custom_derives);
+    let redact_debug = redact_debug
+        && param_names
+            .iter()
+            .any(|name| sensitivity.contains_key(name.trim_end_matches('?')));
+    let default_derives = struct_default_derives(
+        &base_derives(&["Debug", "Clone"], redact_debug),
+        auto_derives,
+        input_types,
+    );
+    let derive_attr = build_derive_attribute(&default_derives, custom_derives);
     code.push_str(&derive_attr);
     code.push('\n');
     code.push_str(&format!("pub struct {} {{\n", struct_name));
@@ -418,12 +680,19 @@ pub fn generate_structured_params_struct(
     }
 
     // Generate struct fields
+    let mut debug_fields = Vec::new();
     for param_name in &param_order {
         let param_type = unique_params.get(param_name).unwrap();
-        code.push_str(&format!("    pub {}: {},\n", param_name, param_type));
+        let ident = to_rust_ident(param_name);
+        code.push_str(&struct_field(param_name, &ident, param_type, sensitivity));
+        debug_fields.push((ident, sensitivity.contains_key(param_name)));
     }
 
     code.push_str("}\n");
+    if redact_debug {
+        code.push('\n');
+        code.push_str(&generate_redacted_debug_impl(&struct_name, &debug_fields));
+    }
 
     Some(code)
 }
@@ -441,3 +710,190 @@ pub fn generate_structured_params_signature(
     };
     format!("params: &{}", struct_name)
 }
+
+/// Generate the filter enum of a query with `filter_type`, with a variant for each condition of
+/// its WHERE clause holding the value compared with the column, and the SQL of each condition
+/// Conditions are given with the type of their parameter
+pub fn generate_filter_enum(
+    enum_name: &str,
+    query_name: &str,
+    conditions: &[(&FilterCondition, &RustType)],
+    auto_derives: bool,
+    sensitivity: &FieldSensitivity,
+    redact_debug: bool,
+) -> String {
+    let mut code = String::new();
+
+    let redact_debug = redact_debug
+        && conditions
+            .iter()
+            .any(|(condition, _)| sensitivity.contains_key(&condition.param));
+    let default_derives = struct_default_derives(
+        &base_derives(&["Debug", "Clone"], redact_debug),
+        auto_derives,
+        conditions.iter().map(|(_, rust_type)| *rust_type),
+    );
+    code.push_str(&format!(
+        "/// Filter of `{}`, the given filters are combined with AND\n",
+        query_name
+    ));
+    code.push_str(&build_derive_attribute(&default_derives, &[]));
+    code.push('\n');
+    code.push_str(&format!("pub enum {} {{\n", enum_name));
+    for (condition, rust_type) in conditions {
+        code.push_str(&format!(
+            "    /// `{}#{{{}}}{}`\n",
+            condition.sql_before_param, condition.param, condition.sql_after_param
+        ));
+        code.push_str(&format!(
+            "    {}({}),\n",
+            condition.variant, rust_type.rust_type
+        ));
+    }
+    code.push_str("}\n\n");
+
+    if redact_debug {
+        code.push_str(&format!(
+            "impl std::fmt::Debug for {} {{\n    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {{\n        match self {{\n",
+            enum_name
+        ));
+        for (condition, _) in conditions {
+            let (binding, value) = if sensitivity.contains_key(&condition.param) {
+                ("_", "&\"[redacted]\"")
+            } else {
+                ("value", "value")
+            };
+            code.push_str(&format!(
+                "            Self::{}({}) => f.debug_tuple(\"{}\").field({}).finish(),\n",
+                condition.variant, binding, condition.variant, value
+            ));
+        }
+        code.push_str("        }\n    }\n}\n\n");
+    }
+
+    code.push_str(&format!("impl {} {{\n", enum_name));
+    code.push_str(
+        "    /// SQL of the condition, comparing the column with the positional parameter `index`\n",
+    );
+    code.push_str("    fn condition(&self, index: usize) -> String {\n");
+    code.push_str("        match self {\n");
+    for (condition, _) in conditions {
+        let format_string = format!(
+            "{}${{}}{}",
+            condition
+                .sql_before_param
+                .replace('{', "{{")
+                .replace('}', "}}"),
+            condition
+                .sql_after_param
+                .replace('{', "{{")
+                .replace('}', "}}")
+        );
+        code.push_str(&format!(
+            "            Self::{}(_) => format!({:?}, index),\n",
+            condition.variant, format_string
+        ));
+    }
+    code.push_str("        }\n    }\n}\n");
+
+    code
+}
+
+/// How a field is converted to and from JSON in `to_map()`/`from_map()`
+/// Generated enums don't implement serde traits, so they go through Display/FromStr instead
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DynamicFieldKind {
+    /// Any serde-compatible type
+    Plain,
+    /// A generated enum
+    Enum,
+    /// A `Vec` of a generated enum
+    EnumArray,
+}
+
+/// Generate `to_map()`/`from_map()` conversions for a generated struct
+/// The impl is gated behind the `dynamic` feature so the typed path stays untouched
+/// Each field is described as (field_name, is_option, kind)
+pub fn generate_dynamic_map_impl(
+    struct_name: &str,
+    fields: &[(String, bool, DynamicFieldKind)],
+) -> String {
+    let mut code = String::new();
+    let parse_error =
+        "map_err(|e: String| serde_json::Error::io(std::io::Error::new(std::io::ErrorKind::InvalidData, e)))";
+
+    code.push_str("#[cfg(feature = \"dynamic\")]\n");
+    code.push_str(&format!("impl {} {{\n", struct_name));
+
+    // to_map: serialize every field into a JSON value keyed by field name
+    code.push_str("    /// Convert into a map of field names to JSON values\n");
+    code.push_str("    pub fn to_map(&self) -> Result<std::collections::HashMap<String, serde_json::Value>, serde_json::Error> {\n");
+    code.push_str("        let mut map = std::collections::HashMap::new();\n");
+    for (field_name, is_option, kind) in fields {
+        let field = to_rust_ident(field_name);
+        let value = match (kind, is_option) {
+            (DynamicFieldKind::Enum, true) => format!(
+                "serde_json::to_value(self.{}.as_ref().map(|v| v.to_string()))?",
+                field
+            ),
+            (DynamicFieldKind::Enum, false) => {
+                format!("serde_json::to_value(self.{}.to_string())?", field)
+            }
+            (DynamicFieldKind::EnumArray, true) => format!(
+                "serde_json::to_value(self.{}.as_ref().map(|v| v.iter().map(|e| e.to_string()).collect::<Vec<_>>()))?",
+                field
+            ),
+            (DynamicFieldKind::EnumArray, false) => format!(
+                "serde_json::to_value(self.{}.iter().map(|e| e.to_string()).collect::<Vec<_>>())?",
+                field
+            ),
+            (DynamicFieldKind::Plain, _) => format!("serde_json::to_value(&self.{})?", field),
+        };
+        code.push_str(&format!(
+            "        map.insert(\"{}\".to_string(), {});\n",
+            field_name, value
+        ));
+    }
+    code.push_str("        Ok(map)\n");
+    code.push_str("    }\n\n");
+
+    // from_map: missing keys are treated as JSON null
+    code.push_str("    /// Build from a map of field names to JSON values (missing fields are treated as null)\n");
+    code.push_str("    pub fn from_map(mut map: std::collections::HashMap<String, serde_json::Value>) -> Result<Self, serde_json::Error> {\n");
+    code.push_str("        Ok(Self {\n");
+    for (field_name, is_option, kind) in fields {
+        let raw_value = format!(
+            "map.remove(\"{}\").unwrap_or(serde_json::Value::Null)",
+            field_name
+        );
+        let value = match (kind, is_option) {
+            (DynamicFieldKind::Enum, true) => format!(
+                "serde_json::from_value::<Option<String>>({})?.map(|s| s.parse()).transpose().{}?",
+                raw_value, parse_error
+            ),
+            (DynamicFieldKind::Enum, false) => format!(
+                "serde_json::from_value::<String>({})?.parse().{}?",
+                raw_value, parse_error
+            ),
+            (DynamicFieldKind::EnumArray, true) => format!(
+                "serde_json::from_value::<Option<Vec<String>>>({})?.map(|v| v.iter().map(|s| s.parse()).collect::<Result<Vec<_>, String>>()).transpose().{}?",
+                raw_value, parse_error
+            ),
+            (DynamicFieldKind::EnumArray, false) => format!(
+                "serde_json::from_value::<Vec<String>>({})?.iter().map(|s| s.parse()).collect::<Result<Vec<_>, String>>().{}?",
+                raw_value, parse_error
+            ),
+            (DynamicFieldKind::Plain, _) => format!("serde_json::from_value({})?", raw_value),
+        };
+        code.push_str(&format!(
+            "            {}: {},\n",
+            to_rust_ident(field_name),
+            value
+        ));
+    }
+    code.push_str("        })\n");
+    code.push_str("    }\n");
+    code.push_str("}\n");
+
+    code
+}
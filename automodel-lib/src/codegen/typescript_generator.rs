@@ -0,0 +1,144 @@
+use super::module_generator::returns_tuple;
+use crate::query_definition_rt::QueryDefinitionRuntime;
+use crate::types_extractor::{enum_name_of, sorted_enum_types, OutputColumn};
+use crate::utils::{to_pascal_case, to_snake_case};
+
+/// First line of generated TypeScript files, used to recognize them when cleaning up
+pub const TYPESCRIPT_HEADER: &str =
+    "// This file was automatically generated by AutoModel. Do not edit manually.";
+
+/// Generate TypeScript interfaces mirroring the result structs of a module
+/// Field names are the serialized names of the Rust fields, nullable columns are `T | null`
+/// Returns None if no query of the module generates a result struct
+pub fn generate_typescript_for_module(
+    analyzed_queries: &[QueryDefinitionRuntime],
+    module: &str,
+) -> Option<String> {
+    let mut interfaces = String::new();
+    let mut emitted_struct_names = std::collections::HashSet::new();
+    let mut enum_columns = Vec::new();
+
+    for analyzed in analyzed_queries.iter().filter(|q| q.module() == module) {
+        let query = &analyzed.definition;
+        let type_info = &analyzed.type_info;
+
+        // Same conditions as for generating the Rust result struct
+        let has_struct = (type_info.output_types.len() > 1 || query.return_type.is_some())
+            && !returns_tuple(query, type_info);
+        if !has_struct || type_info.output_types.is_empty() {
+            continue;
+        }
+        let struct_name = match &query.return_type {
+            Some(custom_name) => custom_name.clone(),
+            None => format!("{}Item", to_pascal_case(&query.name)),
+        };
+        if !emitted_struct_names.insert(struct_name.clone()) {
+            continue;
+        }
+
+        if let Some(description) = &query.description {
+            interfaces.push_str(&format!(
+                "/** Row of `{}`: {} */\n",
+                query.name, description
+            ));
+        } else {
+            interfaces.push_str(&format!("/** Row of `{}` */\n", query.name));
+        }
+        interfaces.push_str(&format!("export interface {} {{\n", struct_name));
+        for col in &type_info.output_types {
+            if query.flatten.contains(&col.name) {
+                continue;
+            }
+            interfaces.push_str(&format!(
+                "  {}: {};\n",
+                to_snake_case(&col.name),
+                typescript_field_type(col)
+            ));
+            if !col.rust_type.needs_json_wrapper {
+                enum_columns.push(&col.rust_type);
+            }
+        }
+        // Fields of flattened custom types are inlined with names only their serde
+        // implementation knows
+        if !query.flatten.is_empty() {
+            interfaces.push_str("  [key: string]: unknown;\n");
+        }
+        interfaces.push_str("}\n\n");
+    }
+
+    if emitted_struct_names.is_empty() {
+        return None;
+    }
+
+    let mut code = String::new();
+    code.push_str(TYPESCRIPT_HEADER);
+    code.push_str("\n\n");
+    for (enum_name, (variants, _)) in sorted_enum_types(enum_columns) {
+        let literals: Vec<String> = variants
+            .iter()
+            .map(|variant| format!("{:?}", variant))
+            .collect();
+        code.push_str(&format!(
+            "export type {} = {};\n\n",
+            enum_name,
+            literals.join(" | ")
+        ));
+    }
+    code.push_str(&interfaces);
+    Some(code.trim_end().to_string() + "\n")
+}
+
+/// TypeScript type of a result struct field, `T | null` for nullable columns
+fn typescript_field_type(col: &OutputColumn) -> String {
+    let base_type = if col.rust_type.needs_json_wrapper {
+        // Custom types are serialized with their own serde implementation
+        "unknown".to_string()
+    } else if col.rust_type.enum_variants.is_some() {
+        let enum_name = enum_name_of(&col.rust_type);
+        if col.rust_type.is_enum_array {
+            format!("{}[]", enum_name)
+        } else {
+            enum_name
+        }
+    } else {
+        typescript_type(&col.rust_type.rust_type)
+    };
+
+    if col.rust_type.is_nullable {
+        format!("{} | null", base_type)
+    } else {
+        base_type
+    }
+}
+
+/// TypeScript type of a value serialized with serde_json
+/// Types without a well-known JSON representation are `unknown`
+fn typescript_type(rust_type: &str) -> String {
+    if let Some(element_type) = rust_type
+        .strip_prefix("Vec<")
+        .and_then(|rest| rest.strip_suffix('>'))
+    {
+        return format!("{}[]", typescript_type(element_type));
+    }
+
+    match rust_type {
+        "i8" | "i16" | "i32" | "i64" | "u32" | "u64" | "f32" | "f64" | "u8" => "number",
+        "bool" => "boolean",
+        // Decimal is serialized as a string to keep its precision
+        "String"
+        | "rust_decimal::Decimal"
+        | "uuid::Uuid"
+        | "chrono::NaiveDate"
+        | "chrono::NaiveTime"
+        | "chrono::NaiveDateTime"
+        | "chrono::DateTime<chrono::Utc>"
+        | "std::net::IpAddr"
+        | "mac_address::MacAddress"
+        | "super::full_text_search::TsVector"
+        | "super::full_text_search::TsQuery" => "string",
+        "(u32, u32)" => "[number, number]",
+        "super::money::Money" => "{ amount: string; currency: string }",
+        _ => "unknown",
+    }
+    .to_string()
+}
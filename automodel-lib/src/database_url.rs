@@ -0,0 +1,90 @@
+/// Database URL resolution for build scripts
+/// Reads the URL from an environment variable, optionally loading `.env` files first,
+/// and resolves secret manager URIs (e.g. `aws-sm://automodel/db`) with registered resolvers
+use std::collections::HashMap;
+
+/// Callback resolving a secret manager URI to the actual database URL
+pub type SecretResolver = Box<dyn Fn(&str) -> Result<String, String>>;
+
+/// URL schemes passed through as database URLs without secret resolution
+const DATABASE_URL_SCHEMES: &[&str] = &["postgres", "postgresql"];
+
+/// Resolves the database URL used for code generation
+///
+/// ```rust,no_run
+/// // build.rs
+/// let database_url = automodel::DatabaseUrl::from_env("AUTOMODEL_DATABASE_URL")
+///     .with_dotenv()
+///     .with_secret_resolver("aws-sm", |uri| {
+///         // e.g. shell out to `aws secretsmanager get-secret-value`
+///         Err(format!("cannot resolve {}", uri))
+///     });
+/// // AutoModel::generate(|| database_url.resolve(), "queries", "src/generated", defaults)
+/// ```
+pub struct DatabaseUrl {
+    /// Environment variable holding the database URL or a secret manager URI
+    env_var: String,
+    /// Whether to load `.env` files before reading the environment variable
+    dotenv: bool,
+    /// Key: URI scheme (e.g. "aws-sm", "vault")
+    secret_resolvers: HashMap<String, SecretResolver>,
+}
+
+impl DatabaseUrl {
+    /// Read the database URL from the given environment variable
+    pub fn from_env(env_var: &str) -> Self {
+        Self {
+            env_var: env_var.to_string(),
+            dotenv: false,
+            secret_resolvers: HashMap::new(),
+        }
+    }
+
+    /// Load `.env` from the current directory or its parents before reading the variable
+    /// Variables already set in the environment take precedence over the file
+    pub fn with_dotenv(mut self) -> Self {
+        self.dotenv = true;
+        self
+    }
+
+    /// Resolve values like `{scheme}://path` with the given callback
+    /// The callback receives the full URI and returns the database URL
+    pub fn with_secret_resolver<F>(mut self, scheme: &str, resolver: F) -> Self
+    where
+        F: Fn(&str) -> Result<String, String> + 'static,
+    {
+        self.secret_resolvers
+            .insert(scheme.to_string(), Box::new(resolver));
+        self
+    }
+
+    /// Resolve the database URL
+    /// Fails if the variable is not set or names a secret manager without a registered resolver
+    pub fn resolve(&self) -> Result<String, String> {
+        if self.dotenv {
+            // A missing .env file is not an error, the variable may be set directly
+            let _ = dotenvy::dotenv();
+        }
+
+        let value = std::env::var(&self.env_var).map_err(|_| {
+            format!(
+                "{} environment variable must be set for code generation",
+                self.env_var
+            )
+        })?;
+
+        let Some((scheme, _)) = value.split_once("://") else {
+            return Ok(value);
+        };
+        if DATABASE_URL_SCHEMES.contains(&scheme) {
+            return Ok(value);
+        }
+        match self.secret_resolvers.get(scheme) {
+            Some(resolver) => resolver(&value),
+            None => Err(format!(
+                "{} uses the '{}://' scheme, but no secret resolver is registered for it",
+                self.env_var, scheme
+            )),
+        }
+    }
+}
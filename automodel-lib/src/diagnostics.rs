@@ -0,0 +1,111 @@
+/// Warnings reported during analysis and code generation, each with a code identifying its kind
+/// Codes are shown in cargo warnings and `automodel.warn`, and queries suppress the warnings of
+/// a code with `allow: [AM003]`
+use serde::{Deserialize, Serialize};
+
+/// Kind of warning, written as its code (e.g. "AM001")
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum DiagnosticCode {
+    /// Query plan performs a sequential scan
+    #[serde(rename = "AM001")]
+    SeqScan,
+    /// Parameter or column of a PostgreSQL type without a Rust mapping, generated as String
+    #[serde(rename = "AM002")]
+    UnknownType,
+    /// Query plan filters the rows of a table, which an index might serve
+    #[serde(rename = "AM003")]
+    UnindexedFilter,
+    /// EXPLAIN failed, so the query plan could not be analyzed
+    #[serde(rename = "AM004")]
+    ExplainFailed,
+    /// Subquery which could be rewritten as a join
+    #[serde(rename = "AM005")]
+    SubqueryRewrite,
+    /// LIKE or ILIKE pattern which no available index can serve
+    #[serde(rename = "AM006")]
+    LikePattern,
+    /// Parameter or column named after a Rust keyword, renamed in generated code
+    #[serde(rename = "AM007")]
+    RenamedIdentifier,
+    /// Analysis exceeded its time limit and the cached analysis was used
+    #[serde(rename = "AM008")]
+    StaleAnalysis,
+    /// Aggregate output column whose type can overflow or surprise, e.g. SUM() returned as NUMERIC
+    #[serde(rename = "AM009")]
+    AggregateOverflow,
+    /// Plan estimates exceed the query's `baseline` by more than its tolerance
+    #[serde(rename = "AM010")]
+    PlanRegression,
+    /// Statement timeout suggested from the plan estimates of a read-only query
+    #[serde(rename = "AM011")]
+    TimeoutSuggestion,
+    /// `expect: possible_one` query without a LIMIT whose plan estimates several rows
+    #[serde(rename = "AM012")]
+    MissingLimit,
+}
+
+impl DiagnosticCode {
+    /// Code as written in warnings and `allow` lists, e.g. "AM001"
+    pub fn code(&self) -> &'static str {
+        match self {
+            DiagnosticCode::SeqScan => "AM001",
+            DiagnosticCode::UnknownType => "AM002",
+            DiagnosticCode::UnindexedFilter => "AM003",
+            DiagnosticCode::ExplainFailed => "AM004",
+            DiagnosticCode::SubqueryRewrite => "AM005",
+            DiagnosticCode::LikePattern => "AM006",
+            DiagnosticCode::RenamedIdentifier => "AM007",
+            DiagnosticCode::StaleAnalysis => "AM008",
+            DiagnosticCode::AggregateOverflow => "AM009",
+            DiagnosticCode::PlanRegression => "AM010",
+            DiagnosticCode::TimeoutSuggestion => "AM011",
+            DiagnosticCode::MissingLimit => "AM012",
+        }
+    }
+
+    /// Short name of the kind of warning, e.g. "seq-scan"
+    pub fn name(&self) -> &'static str {
+        match self {
+            DiagnosticCode::SeqScan => "seq-scan",
+            DiagnosticCode::UnknownType => "unknown-type",
+            DiagnosticCode::UnindexedFilter => "unindexed-filter",
+            DiagnosticCode::ExplainFailed => "explain-failed",
+            DiagnosticCode::SubqueryRewrite => "subquery-rewrite",
+            DiagnosticCode::LikePattern => "like-pattern",
+            DiagnosticCode::RenamedIdentifier => "renamed-identifier",
+            DiagnosticCode::StaleAnalysis => "stale-analysis",
+            DiagnosticCode::AggregateOverflow => "aggregate-overflow",
+            DiagnosticCode::PlanRegression => "plan-regression",
+            DiagnosticCode::TimeoutSuggestion => "timeout-suggestion",
+            DiagnosticCode::MissingLimit => "missing-limit",
+        }
+    }
+}
+
+impl std::fmt::Display for DiagnosticCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// Warning reported for a query
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Diagnostic {
+    /// Kind of warning
+    pub code: DiagnosticCode,
+    /// Description of the issue, naming the query
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub(crate) fn new(code: DiagnosticCode, message: String) -> Self {
+        Self { code, message }
+    }
+}
+
+/// Written as `[AM001 seq-scan] message`, the format of cargo warnings and `automodel.warn`
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{} {}] {}", self.code, self.code.name(), self.message)
+    }
+}
@@ -0,0 +1,99 @@
+/// Protection of generated files edited by hand, so that regeneration does not silently discard
+/// manual patches. Every generated file ends with a footer holding the hash of its content, a
+/// file whose content no longer matches its footer was changed after it was generated
+use anyhow::Result;
+use std::fs;
+use std::path::Path;
+
+/// Prefix of the footer line holding the content hash of a generated file
+const CONTENT_HASH_PREFIX: &str = "// AUTOMODEL_CONTENT_HASH: ";
+
+/// Writes generated files and keeps track of those skipped because they were edited by hand
+pub(crate) struct EditGuard {
+    /// Whether edited files are overwritten anyway
+    force: bool,
+    /// Edited files which were not overwritten, as `path:line` of their first difference
+    edited: Vec<String>,
+}
+
+impl EditGuard {
+    pub(crate) fn new(force: bool) -> Self {
+        Self {
+            force,
+            edited: Vec::new(),
+        }
+    }
+
+    /// Write a generated file followed by its content hash footer
+    /// Files edited by hand are left alone unless forced, unchanged files are not rewritten
+    pub(crate) fn write(&mut self, path: &Path, content: &str) -> std::io::Result<()> {
+        let content = with_content_hash(content);
+        let existing = fs::read_to_string(path).ok();
+        if existing.as_deref() == Some(content.as_str()) {
+            return Ok(());
+        }
+        if let (Some(existing), false) = (&existing, self.force) {
+            if let Some(line) = edited_line(existing, &content) {
+                self.edited.push(format!("{}:{}", path.display(), line));
+                return Ok(());
+            }
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, content)
+    }
+
+    /// Fail listing the edited files which were not overwritten
+    pub(crate) fn check(&self) -> Result<()> {
+        if self.edited.is_empty() {
+            return Ok(());
+        }
+        anyhow::bail!(
+            "Generated files were edited by hand and were not overwritten, move the changes into the queries or their configuration and regenerate, or overwrite them with --force (DefaultsConfig::overwrite_edited_files):\n  {}",
+            self.edited.join("\n  ")
+        )
+    }
+}
+
+/// Content followed by the footer holding its hash
+fn with_content_hash(content: &str) -> String {
+    let mut content = content.to_string();
+    if !content.ends_with('\n') {
+        content.push('\n');
+    }
+    let hash = content_hash(&content);
+    content.push_str(CONTENT_HASH_PREFIX);
+    content.push_str(&hash);
+    content.push('\n');
+    content
+}
+
+/// First 8 bytes of the SHA-256 of the content, in hex
+fn content_hash(content: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    Sha256::digest(content.as_bytes())[..8]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Line of the first difference between an edited file and its regenerated content (1-based)
+/// None if the file still matches its footer, or has none as it predates content hashes
+fn edited_line(existing: &str, regenerated: &str) -> Option<usize> {
+    let footer_start = existing.rfind(CONTENT_HASH_PREFIX)?;
+    let (content, footer) = existing.split_at(footer_start);
+    let recorded_hash = footer[CONTENT_HASH_PREFIX.len()..].trim();
+    if content_hash(content) == recorded_hash {
+        return None;
+    }
+
+    // Lines added or removed at the end differ right after the shorter content
+    let line = content
+        .lines()
+        .zip(regenerated.lines())
+        .position(|(existing, regenerated)| existing != regenerated)
+        .unwrap_or_else(|| content.lines().count().min(regenerated.lines().count()));
+    Some(line + 1)
+}
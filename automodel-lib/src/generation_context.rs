@@ -0,0 +1,172 @@
+/// Output and caches of a code generation, injected so that the library can be embedded in other
+/// tools and tests: each context has its own caches and reports through its own logger
+use crate::build_summary::BuildSummary;
+use crate::types_extractor::EnumTypeCache;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Receives the output of a code generation
+pub trait GenerationLogger: Send + Sync {
+    /// A file or directory whose changes must rerun the build script
+    fn rerun_if_changed(&self, path: &Path);
+    /// Progress of the generation, e.g. the query being analyzed
+    fn info(&self, message: &str);
+    /// Warnings, e.g. the diagnostics of the queries
+    fn warning(&self, message: &str);
+    /// The error failing the generation
+    fn error(&self, message: &str);
+}
+
+/// Prints the output as cargo build script instructions, e.g. `cargo:warning=...`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CargoLogger;
+
+impl GenerationLogger for CargoLogger {
+    fn rerun_if_changed(&self, path: &Path) {
+        println!("cargo:rerun-if-changed={}", path.display());
+    }
+
+    fn info(&self, message: &str) {
+        println!("cargo:info={}", message);
+    }
+
+    fn warning(&self, message: &str) {
+        println!("cargo:warning={}", message);
+    }
+
+    fn error(&self, message: &str) {
+        println!("cargo:error={}", message);
+    }
+}
+
+/// Discards the output, for programmatic use
+/// Warnings are still written to `automodel.warn` and errors are still returned
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QuietLogger;
+
+impl GenerationLogger for QuietLogger {
+    fn rerun_if_changed(&self, _path: &Path) {}
+
+    fn info(&self, _message: &str) {}
+
+    fn warning(&self, _message: &str) {}
+
+    fn error(&self, _message: &str) {}
+}
+
+/// Post-processes the generated code of every module before it is written, e.g. to add
+/// organization-specific headers or impls, or to strip items
+pub trait CodegenPlugin: Send + Sync {
+    /// Transform the formatted code of a file of the module, its `{module}.rs` file and the
+    /// `{module}/queries_N.rs` parts of split modules
+    /// The returned code is written as is, without formatting
+    fn transform(&self, module: &str, code: String) -> String;
+
+    /// Version of the transformation, part of the source hash so that changing it regenerates
+    /// the code
+    /// Defaults to an empty string (changes of the plugin only apply to the next regeneration)
+    fn version(&self) -> String {
+        String::new()
+    }
+}
+
+/// Logger, caches and plugins of a code generation
+/// Clones share the logger and the caches, separate contexts share nothing
+///
+/// ```rust,no_run
+/// # async fn run(defaults: automodel::DefaultsConfig) -> Result<(), Box<dyn std::error::Error>> {
+/// use automodel::{AutoModel, GenerationContext};
+///
+/// AutoModel::generate_with_context(
+///     GenerationContext::quiet(),
+///     || Ok("postgres://localhost/app".to_string()),
+///     "queries",
+///     "src/generated",
+///     defaults,
+/// )
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct GenerationContext {
+    logger: Arc<dyn GenerationLogger>,
+    /// Enum types of the analysis database by type oid
+    enum_types: Arc<EnumTypeCache>,
+    /// Plugins transforming the generated code, in registration order
+    plugins: Vec<Arc<dyn CodegenPlugin>>,
+    /// Timings of the last code generation
+    summary: Arc<Mutex<BuildSummary>>,
+}
+
+impl Default for GenerationContext {
+    /// Context printing cargo build script instructions
+    fn default() -> Self {
+        Self::new(CargoLogger)
+    }
+}
+
+impl GenerationContext {
+    /// Context reporting through the given logger, with empty caches
+    pub fn new<L: GenerationLogger + 'static>(logger: L) -> Self {
+        Self {
+            logger: Arc::new(logger),
+            enum_types: Arc::new(EnumTypeCache::default()),
+            plugins: Vec::new(),
+            summary: Arc::new(Mutex::new(BuildSummary::default())),
+        }
+    }
+
+    /// Register a plugin transforming the generated code of every module
+    /// Plugins run in registration order, each on the output of the previous one
+    pub fn with_plugin<P: CodegenPlugin + 'static>(mut self, plugin: P) -> Self {
+        self.plugins.push(Arc::new(plugin));
+        self
+    }
+
+    /// Context discarding all output, see `QuietLogger`
+    pub fn quiet() -> Self {
+        Self::new(QuietLogger)
+    }
+
+    /// Logger receiving the output of the generation
+    pub fn logger(&self) -> &dyn GenerationLogger {
+        self.logger.as_ref()
+    }
+
+    /// Run the registered plugins on the code of a file of the module
+    pub(crate) fn transform(&self, module: &str, code: String) -> String {
+        self.plugins
+            .iter()
+            .fold(code, |code, plugin| plugin.transform(module, code))
+    }
+
+    /// Versions of the registered plugins, in registration order
+    pub(crate) fn plugin_versions(&self) -> impl Iterator<Item = String> + '_ {
+        self.plugins.iter().map(|plugin| plugin.version())
+    }
+
+    /// Timing breakdown of the last code generation with this context or its clones
+    /// Empty when the generated code was up to date
+    pub fn build_summary(&self) -> BuildSummary {
+        self.summary
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Update the timings of the current code generation
+    pub(crate) fn update_summary(&self, update: impl FnOnce(&mut BuildSummary)) {
+        update(
+            &mut self
+                .summary
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        );
+    }
+
+    /// Cache of the enum types of the analysis database
+    pub(crate) fn enum_types(&self) -> &EnumTypeCache {
+        &self.enum_types
+    }
+}
@@ -0,0 +1,130 @@
+/// Query functions generated from introspection for tables with an audit history table
+/// Every table `t` with a `t{suffix}` table in the same schema gets `get_t_history(id, time_range)`
+use crate::query_definition::QueryDefinition;
+use crate::sqlfile_parser::{is_valid_rust_identifier, parse_sql_source, validate_module_name};
+use anyhow::Result;
+use std::path::Path;
+
+/// Tables with a single column primary key paired with their history table, which must have
+/// the primary key column and the time column: schema, table, primary key column and the type
+/// of the time column
+const HISTORY_TABLES_QUERY: &str = "
+SELECT n.nspname::text, t.relname::text, a.attname::text, format_type(tc.atttypid, NULL)
+FROM pg_catalog.pg_class t
+JOIN pg_catalog.pg_namespace n ON n.oid = t.relnamespace
+JOIN pg_catalog.pg_class h ON h.relnamespace = t.relnamespace AND h.relname = t.relname || $1::text
+JOIN pg_catalog.pg_index i ON i.indrelid = t.oid AND i.indisprimary AND i.indnatts = 1
+JOIN pg_catalog.pg_attribute a ON a.attrelid = t.oid AND a.attnum = i.indkey[0]
+JOIN pg_catalog.pg_attribute ha
+  ON ha.attrelid = h.oid AND ha.attname = a.attname AND NOT ha.attisdropped
+JOIN pg_catalog.pg_attribute tc
+  ON tc.attrelid = h.oid AND tc.attname = $2::text AND NOT tc.attisdropped
+WHERE t.relkind IN ('r', 'p') AND h.relkind IN ('r', 'p')
+  AND n.nspname NOT IN ('pg_catalog', 'information_schema', 'crdb_internal', 'pg_extension')
+  AND n.nspname NOT LIKE 'pg\\_%'
+ORDER BY n.nspname, t.relname";
+
+/// Generate a `get_<table>_history(id, time_range)` function for every table with an audit
+/// history table, found by introspecting the database when generating
+///
+/// The history table is named after the table with `suffix`, e.g. `users_history`, and holds the
+/// table's single column primary key and `time_column`; the generated function returns its rows
+/// for one primary key whose time is within a range, oldest first
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryQueriesConfig {
+    /// Module the history functions are generated into, a module of the queries directory or
+    /// a new one
+    /// Defaults to "history"
+    pub module: String,
+    /// Suffix of the history table names
+    /// Defaults to "_history"
+    pub suffix: String,
+    /// Column of the history tables holding the time of each change, a timestamp or date column
+    /// Defaults to "changed_at"
+    pub time_column: String,
+}
+
+impl Default for HistoryQueriesConfig {
+    fn default() -> Self {
+        Self {
+            module: "history".to_string(),
+            suffix: "_history".to_string(),
+            time_column: "changed_at".to_string(),
+        }
+    }
+}
+
+impl HistoryQueriesConfig {
+    /// Fail if the module name or the table and column names cannot be used
+    pub(crate) fn validate(&self) -> Result<()> {
+        validate_module_name(&self.module)
+            .map_err(|e| anyhow::anyhow!("history_queries module is not valid: {}", e))?;
+        if self.suffix.is_empty() || self.time_column.is_empty() {
+            anyhow::bail!("history_queries suffix and time_column must not be empty");
+        }
+        Ok(())
+    }
+
+    /// Introspect the tables with a history table and build their history queries
+    /// Tables whose name or primary key column is not a valid Rust identifier are skipped
+    pub(crate) async fn queries(
+        &self,
+        client: &tokio_postgres::Client,
+        defaults: &crate::DefaultsConfig,
+    ) -> Result<Vec<QueryDefinition>> {
+        self.validate()?;
+
+        let rows = client
+            .query(HISTORY_TABLES_QUERY, &[&self.suffix, &self.time_column])
+            .await?;
+
+        let mut queries = Vec::new();
+        for row in rows {
+            let schema: String = row.get(0);
+            let table: String = row.get(1);
+            let key_column: String = row.get(2);
+            let time_type: String = row.get(3);
+            if !is_valid_rust_identifier(&table) || !is_valid_rust_identifier(&key_column) {
+                continue;
+            }
+
+            let history_table = format!("{}{}", table, self.suffix);
+            let range_type = match time_type.as_str() {
+                "timestamp with time zone" => "tstzrange",
+                "timestamp without time zone" => "tsrange",
+                "date" => "daterange",
+                _ => anyhow::bail!(
+                    "history table {}.{} column '{}' has type {}, expected a timestamp or date",
+                    schema,
+                    history_table,
+                    self.time_column,
+                    time_type
+                ),
+            };
+
+            let name = format!("get_{}_history", table);
+            let source = format!(
+                "-- @automodel\n\
+                 --    description: Changes of a {schema}.{table} row recorded in {schema}.{history_table} within a time range, oldest first\n\
+                 --    expect: multiple\n\
+                 -- @end\n\
+                 \n\
+                 SELECT * FROM {schema}.{history_table}\n\
+                 WHERE {key_column} = #{{{key_column}}} AND {time_column} <@ #{{time_range}}::{range_type}\n\
+                 ORDER BY {time_column}\n",
+                time_column = self.time_column,
+            );
+            queries.push(parse_sql_source(
+                Path::new(&format!("{}.{}", schema, history_table)),
+                &source,
+                &self.module,
+                &name,
+                defaults.clone(),
+                None,
+                None,
+            )?);
+        }
+
+        Ok(queries)
+    }
+}
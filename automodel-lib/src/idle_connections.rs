@@ -0,0 +1,110 @@
+/// Connections handed to one analysis at a time. Behind a transaction pooler a query is analyzed
+/// in a transaction of its own, so two analyses sharing a connection would end each other's
+/// transaction: every analysis checks out an idle connection and returns it when it completes
+use std::sync::Mutex;
+use tokio::sync::Semaphore;
+
+/// Connections not in use by an analysis
+pub(crate) struct IdleConnections<'a, T> {
+    idle: Mutex<Vec<&'a T>>,
+    /// One permit per idle connection, waited for when all of them are checked out
+    available: Semaphore,
+}
+
+impl<'a, T> IdleConnections<'a, T> {
+    pub(crate) fn new(connections: &'a [T]) -> Self {
+        Self {
+            idle: Mutex::new(connections.iter().collect()),
+            available: Semaphore::new(connections.len()),
+        }
+    }
+
+    /// Wait for an idle connection, which is returned when the checkout is dropped
+    /// (including when the analysis using it is cancelled)
+    pub(crate) async fn checkout(&self) -> CheckedOut<'_, 'a, T> {
+        self.available
+            .acquire()
+            .await
+            .expect("the semaphore of the idle connections is never closed")
+            .forget();
+        let connection = self
+            .idle
+            .lock()
+            .unwrap()
+            .pop()
+            .expect("a permit is only available with an idle connection");
+        CheckedOut {
+            pool: self,
+            connection,
+        }
+    }
+}
+
+/// A connection used by a single analysis until it is dropped
+pub(crate) struct CheckedOut<'p, 'a, T> {
+    pool: &'p IdleConnections<'a, T>,
+    connection: &'a T,
+}
+
+impl<T> std::ops::Deref for CheckedOut<'_, '_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.connection
+    }
+}
+
+impl<T> Drop for CheckedOut<'_, '_, T> {
+    fn drop(&mut self) {
+        self.pool.idle.lock().unwrap().push(self.connection);
+        self.pool.available.add_permits(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::stream::{self, StreamExt};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[tokio::test]
+    async fn more_analyses_than_connections_never_share_one() {
+        let connections: Vec<AtomicBool> = (0..3).map(|_| AtomicBool::new(false)).collect();
+        let pool = IdleConnections::new(&connections);
+        let pool = &pool;
+
+        // Uneven durations make analyses complete out of the order they started in
+        let completed = stream::iter(0..50u64)
+            .map(|i| async move {
+                let connection = pool.checkout().await;
+                assert!(
+                    !connection.swap(true, Ordering::SeqCst),
+                    "analysis {} got a connection in use",
+                    i
+                );
+                tokio::time::sleep(std::time::Duration::from_millis((i * 7) % 5)).await;
+                connection.store(false, Ordering::SeqCst);
+            })
+            .buffer_unordered(connections.len())
+            .count()
+            .await;
+
+        assert_eq!(completed, 50);
+        assert_eq!(pool.idle.lock().unwrap().len(), connections.len());
+    }
+
+    #[tokio::test]
+    async fn cancelled_analyses_return_their_connection() {
+        let connections = vec![()];
+        let pool = IdleConnections::new(&connections);
+
+        let analysis = async {
+            let _connection = pool.checkout().await;
+            std::future::pending::<()>().await
+        };
+        let timed_out = tokio::time::timeout(std::time::Duration::from_millis(10), analysis).await;
+
+        assert!(timed_out.is_err());
+        let _connection = pool.checkout().await;
+    }
+}
@@ -1,21 +1,77 @@
+mod analysis_cache;
+mod analysis_environment;
+mod analysis_snapshot;
+mod build_summary;
+mod cache_invalidation;
+mod clean;
 mod codegen;
+mod database_url;
+mod diagnostics;
+mod edit_guard;
+mod generation_context;
+mod history_queries;
+mod idle_connections;
+mod mandatory_predicates;
+mod module_crates;
+mod query_analysis;
 mod query_definition;
 mod query_definition_rt;
+mod query_registry;
+mod schema_assumptions;
+mod sql_audit;
+mod sql_format;
 mod sqlfile_parser;
+mod timeout_suggestions;
 mod types_extractor;
 mod utils;
+mod vendored_runtime;
 
+use analysis_cache::SharedAnalysisCache;
+use analysis_snapshot::*;
+use edit_guard::EditGuard;
+use idle_connections::IdleConnections;
 use query_definition::*;
 use query_definition_rt::*;
 use sqlfile_parser::*;
 use types_extractor::*;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use std::path::Path;
+use std::time::Duration;
 
-pub use query_definition::TelemetryLevel;
+pub use analysis_environment::AnalysisEnvironment;
+pub use automodel_macros::query;
+pub use build_summary::{BuildSummary, QueryTiming};
+pub use cache_invalidation::CACHE_INVALIDATION_CHANNEL;
+pub use clean::WARN_FILE_NAME;
+pub use database_url::{DatabaseUrl, SecretResolver};
+pub use diagnostics::{Diagnostic, DiagnosticCode};
+pub use generation_context::{
+    CargoLogger, CodegenPlugin, GenerationContext, GenerationLogger, QuietLogger,
+};
+pub use history_queries::HistoryQueriesConfig;
+pub use mandatory_predicates::MandatoryPredicate;
+pub use module_crates::ModuleCratesConfig;
+pub use query_analysis::{AnalyzedColumn, AnalyzedConstraint, AnalyzedParameter, QueryAnalysis};
+pub use query_definition::{
+    AnalysisOrder, Dialect, FailOn, PerformanceCheck, PlanEstimates, SqlxVersion, TelemetryLevel,
+};
+pub use query_registry::QUERY_REGISTRY_FILE_NAME;
+pub use sql_audit::{audit_generated_sql, SqlAudit, SqlAuditFinding};
+pub use timeout_suggestions::{
+    TimeoutSuggestion, TimeoutSuggestionsConfig, TIMEOUT_SUGGESTIONS_FILE_NAME,
+};
+pub use vendored_runtime::VendoredRuntimeConfig;
 
-use crate::codegen::generate_root_module;
+use crate::codegen::{
+    format_generated_code, generate_proto_for_module, generate_root_module,
+    generate_schema_version_const, generate_shared_items, generate_typescript_for_module,
+    query_warnings, rustfmt_available, SharedItems, GENERATED_PART_HEADER, PROTO_HEADER,
+    TYPESCRIPT_HEADER,
+};
+
+/// Number of queries analyzed at the same time when `analysis_concurrency` is not set
+const DEFAULT_ANALYSIS_CONCURRENCY: usize = 40;
 
 /// Default configuration for telemetry and analysis
 #[derive(Debug, Clone, Default, PartialEq)]
@@ -25,6 +81,219 @@ pub struct DefaultsConfig {
     /// Whether to analyze query performance and warn about sequential scans
     /// Defaults to false
     pub ensure_indexes: bool,
+    /// Maximum time to spend analyzing a single query
+    /// When exceeded, the cached analysis from the previous run is used instead
+    /// Defaults to None (no limit)
+    pub query_analysis_timeout: Option<Duration>,
+    /// Maximum total time to spend analyzing all queries
+    /// Queries not analyzed within the budget fall back to the cached analysis from the previous run
+    /// Defaults to None (no limit)
+    pub analysis_budget: Option<Duration>,
+    /// Maximum number of queries analyzed at the same time
+    /// Lower it for small development databases
+    /// Defaults to None (40)
+    pub analysis_concurrency: Option<usize>,
+    /// Number of database connections used for analysis, queries are spread over them in turn
+    /// Capped at the analysis concurrency
+    /// Defaults to None (1)
+    pub analysis_connections: Option<usize>,
+    /// Whether the analysis database is reached through a connection pooler in transaction
+    /// pooling mode, such as PgBouncer, which does not keep session settings between transactions
+    /// Every query is then analyzed in its own transaction with the settings applied by `SET LOCAL`,
+    /// and each connection analyzes one query at a time
+    /// Defaults to false (settings are applied once per analysis connection)
+    pub transaction_pooling: bool,
+    /// Minimum PostgreSQL major version required by all queries (e.g. 15 for MERGE)
+    /// Can be overridden per query with `min_pg_version`
+    /// Defaults to None (no requirement)
+    pub min_pg_version: Option<u32>,
+    /// PostgreSQL major version of the production server, if older than the analysis server
+    /// Queries requiring a newer version fail generation even if the analysis server supports them
+    /// Defaults to None (only the analysis server version is checked)
+    pub production_pg_version: Option<u32>,
+    /// Query reading the schema version of the analysis database, e.g.
+    /// `SELECT max(version) FROM public._sqlx_migrations`, recorded as `SCHEMA_VERSION` in the
+    /// generated mod.rs so that it shows which schema the code was generated against
+    /// Defaults to None (no schema version is recorded)
+    pub schema_version_query: Option<String>,
+    /// Schema version the analysis database must have, generation fails for any other version
+    /// so that code is not accidentally generated against the wrong environment
+    /// Requires `schema_version_query`
+    /// Defaults to None (any schema version is accepted)
+    pub expected_schema_version: Option<String>,
+    /// Performance analysis results which fail code generation, e.g. for gating merges in CI
+    /// Only applies to queries with `ensure_indexes` enabled, except `baseline` which applies to
+    /// queries with a `baseline`; queries can opt out with `allow`
+    /// Defaults to empty (performance issues are reported as warnings only)
+    pub fail_on: Vec<FailOn>,
+    /// Allowed relative increase of the plan estimates over the `baseline` of a query, e.g. 0.5
+    /// for 50%
+    /// Can be overridden per query with the `tolerance` of its baseline
+    /// Defaults to None (0.5)
+    pub baseline_tolerance: Option<f64>,
+    /// Statement timeouts suggested for every read-only query from its plan estimates and the
+    /// cost recorded in its `baseline`, reported as `timeout-suggestion` warnings or written to
+    /// `automodel.timeouts.json`; queries can opt out with `allow: [AM011]`
+    /// Defaults to None (no suggestions)
+    pub suggest_timeouts: Option<TimeoutSuggestionsConfig>,
+    /// Whether generated read-only functions can sample their query plans at runtime
+    /// Sampling is switched on at runtime with `generated::plan_sampling::enable()`
+    /// Can be overridden per query with `plan_sampling`
+    /// Defaults to false
+    pub plan_sampling: bool,
+    /// Whether generated read-only functions returning rows report calls returning an order of
+    /// magnitude more or fewer rows than the planner estimated at generation time
+    /// Reports are passed to the observer set with `generated::cardinality_drift::enable()`
+    /// Can be overridden per query with `cardinality_drift`
+    /// Defaults to false
+    pub cardinality_drift: bool,
+    /// Whether `LIMIT 1` is added to read-only `expect: possible_one` queries without a LIMIT,
+    /// instead of reporting a `missing-limit` warning when their plan estimates several rows
+    /// Can be overridden per query with `limit_possible_one`
+    /// Defaults to false
+    pub limit_possible_one: bool,
+    /// Whether to generate `{query}_blocking` wrappers running the query functions on a lazily
+    /// started tokio runtime, for sync callers such as CLI tools and scripts
+    /// The wrappers are gated behind the `blocking` feature of the consuming crate
+    /// Can be overridden per query with `blocking`
+    /// Defaults to false
+    pub blocking: bool,
+    /// Whether to generate `{query}_cancellable` variants taking a pool and a tokio-util
+    /// `CancellationToken`, for request-scoped timeouts; a cancelled token cancels the running
+    /// query on the server and the variant returns `Cancelled`
+    /// The variants are gated behind the `cancellation` feature of the consuming crate
+    /// Can be overridden per query with `cancellable`
+    /// Defaults to false
+    pub cancellable: bool,
+    /// Execution time in milliseconds above which generated functions emit a `tracing::warn!`
+    /// slow query event, with the parameters listed in the query's telemetry `include_params`
+    /// Can be overridden per query with `slow_query_ms` (0 disables it for the query)
+    /// Defaults to None (no slow query events)
+    pub slow_query_ms: Option<u64>,
+    /// Whether generated structs derive PartialEq, Eq, Hash, PartialOrd and Ord when every field
+    /// type implements them, e.g. to deduplicate rows in sets and maps
+    /// Can be overridden per query with `auto_derives`
+    /// Defaults to false
+    pub auto_derives: bool,
+    /// Shard substituted for `${shard}` when analyzing queries with `shard_by`
+    /// The canonical shard table must exist in the analysis database
+    /// Can be overridden per query with `canonical_shard`
+    /// Defaults to None (every sharded query must set `canonical_shard`)
+    pub canonical_shard: Option<String>,
+    /// Directory where TypeScript interfaces mirroring the generated result structs are written,
+    /// one `{module}.ts` file per module with result structs (e.g. "frontend/src/generated")
+    /// Defaults to None (no TypeScript is generated)
+    pub emit_ts: Option<String>,
+    /// Directory where proto3 messages mirroring the result structs of queries with
+    /// `proto: true` are written, one `{module}.proto` file per module with tagged queries
+    /// (e.g. "proto")
+    /// Defaults to None (no proto files are generated)
+    pub emit_proto: Option<String>,
+    /// Migrations directory where the migrations creating triggers which notify
+    /// `CACHE_INVALIDATION_CHANNEL` when the tables of queries with `cache_invalidation` change
+    /// are written, a new `{version}_cache_invalidation.sql` whenever the tables change
+    /// (e.g. "migrations")
+    /// Defaults to None (no migration is written)
+    pub cache_invalidation_migrations: Option<String>,
+    /// Maximum number of variants combining several conditional blocks of a query which are
+    /// analyzed in addition to the base and single-block variants, with fewer blocks first
+    /// Can be overridden per query with `max_variant_combinations`
+    /// Defaults to 0 (combinations of blocks are not analyzed)
+    pub max_variant_combinations: usize,
+    /// Write every module into its own crate under the output directory, so that cargo compiles
+    /// them in parallel and only rebuilds modules whose queries changed
+    /// Defaults to None (modules are written as `{module}.rs` files next to a `mod.rs`)
+    pub module_crates: Option<ModuleCratesConfig>,
+    /// Write the error types and helpers shared by all modules into a file of the crate, which
+    /// the generated `mod.rs` re-exports, instead of into the `mod.rs` itself
+    /// Cannot be combined with `module_crates`, whose modules depend on the generated runtime crate
+    /// Defaults to None (the helpers are part of the generated `mod.rs`)
+    pub vendored_runtime: Option<VendoredRuntimeConfig>,
+    /// Maximum number of queries whose functions are written to a single file
+    /// Larger modules are split into `{module}/queries_N.rs` files re-exported from `{module}.rs`,
+    /// as very long generated files slow down rust-analyzer
+    /// Defaults to None (each module is a single file)
+    pub max_queries_per_file: Option<usize>,
+    /// Whether to overwrite generated files which were edited by hand since they were generated
+    /// Edits are detected with the content hash at the end of every generated file
+    /// Defaults to false (generation fails listing the edited files and leaves them untouched)
+    pub overwrite_edited_files: bool,
+    /// Values substituted for `@@NAME@@` in the SQL of every query, e.g. per environment
+    /// Take precedence over the `-- @define NAME value` lines of the SQL files
+    /// Defaults to empty (only the values defined in the SQL files)
+    pub defines: std::collections::BTreeMap<String, String>,
+    /// Cargo features enabled for the build, selecting the `#[cfg(feature = "...")] ... #[end]`
+    /// blocks of the SQL; in build.rs pass `automodel::cargo_features()`
+    /// The blocks of the other branches are checked against the database too
+    /// Only features referenced by some block are part of the source hash
+    /// Defaults to empty (no feature is enabled)
+    pub features: Vec<String>,
+    /// Whether the SQL of every query is formatted before it is analyzed, so that the SQL in doc
+    /// comments, telemetry fields and generated query strings is laid out consistently
+    /// Requires the `sql-format` feature; `AutoModel::format_queries` formats the SQL files too
+    /// Defaults to false (the SQL is used as written)
+    pub format_sql: bool,
+    /// Database the queries are analyzed against and generated for
+    /// With `Dialect::Cockroach` plans are read from CockroachDB's EXPLAIN output, hidden columns
+    /// are left out of constraints and the generated `classify_error` knows its error codes
+    /// Defaults to `Dialect::Postgres`
+    pub dialect: Dialect,
+    /// Whether analysis stops at the first query which fails, instead of reporting the errors of
+    /// all failing queries together
+    /// Defaults to false
+    pub fail_fast: bool,
+    /// Order in which queries are analyzed, the generated code keeps the order of the SQL files
+    /// Defaults to `AnalysisOrder::Files`
+    pub analysis_order: AnalysisOrder,
+    /// Queries analyzed against the database when generating, as "module" or "module/query", to
+    /// quickly re-run the queries being worked on
+    /// Other queries reuse the cached analysis of the previous run, and are only analyzed when
+    /// none is cached or their SQL changed since
+    /// Defaults to empty (every query is analyzed)
+    pub only: Vec<String>,
+    /// Whether the type information of prepared statements is cached on disk and shared with the
+    /// other crates of the workspace, in `$CARGO_TARGET_DIR/automodel-analysis-cache` (or the
+    /// `target` directory of the workspace), so that queries analyzed by several build scripts
+    /// are prepared once
+    /// Entries are keyed by the SQL text, the server version and a fingerprint of the schema
+    /// Defaults to false
+    pub shared_analysis_cache: bool,
+    /// Major version of sqlx the generated code compiles against, for workspaces using both
+    /// With `SqlxVersion::Feature` the items differing between the versions are generated for
+    /// both, and sqlx 0.7 is selected with the `sqlx-0-7` feature of the consuming crate
+    /// Defaults to `SqlxVersion::V0_8`
+    pub sqlx_version: SqlxVersion,
+    /// Generate a `get_<table>_history` function for every table with an audit history table
+    /// The tables are found by introspecting the database when generating
+    /// Defaults to None (no history functions)
+    pub history_queries: Option<HistoryQueriesConfig>,
+    /// Predicates every query reading, updating or deleting rows of some tables must apply, e.g.
+    /// `tenant_id = #{tenant_id}` for the tables of a multi-tenant schema
+    /// Generation fails for queries touching the tables without the predicate, unless the rule
+    /// injects it
+    /// Defaults to empty (no mandatory predicates)
+    pub mandatory_predicates: Vec<MandatoryPredicate>,
+    /// Query directories loaded after the queries directory, e.g. service-specific queries next
+    /// to a directory of queries shared by the services of a monorepo
+    /// A module may span several directories, its queries are ordered by directory and then by
+    /// file; a query name defined in two directories, or a `module.yaml` in two directories for
+    /// the same module, fails generation
+    /// Defaults to empty (only the queries directory)
+    pub extra_query_dirs: Vec<String>,
+}
+
+/// Cargo features enabled for the crate whose build script is running, for
+/// `DefaultsConfig::features`
+/// Read from the `CARGO_FEATURE_*` variables, so names are lowercase with `-` replaced by `_`
+pub fn cargo_features() -> Vec<String> {
+    let mut features: Vec<String> = std::env::vars()
+        .filter_map(|(name, _)| {
+            name.strip_prefix("CARGO_FEATURE_")
+                .map(|feature| feature.to_ascii_lowercase())
+        })
+        .collect();
+    features.sort();
+    features
 }
 
 /// Default configuration for telemetry and analysis
@@ -35,21 +304,235 @@ pub struct DefaultsTelemetryConfig {
     /// Whether to include SQL queries as fields in spans by default
     /// Defaults to false
     pub include_sql: bool,
+    /// Characters of the Debug representation of a parameter kept when it is recorded in spans
+    /// and slow query events, the rest is replaced with "..."
+    /// Defaults to None (unlimited)
+    pub max_param_length: Option<usize>,
+    /// Elements of a list parameter kept when it is recorded in spans and slow query events
+    /// Defaults to None (unlimited)
+    pub max_param_elements: Option<usize>,
 }
 
 /// Main entry point for the automodel library
+#[derive(Clone)]
 pub struct AutoModel {
     queries: Vec<QueryDefinition>,
+    module_configs: std::collections::HashMap<String, ModuleConfig>,
+    defaults: DefaultsConfig,
+    context: GenerationContext,
+    /// Whether plan estimates are collected for every read-only query, not only those with a
+    /// `baseline`, for `analyze` and `update_baselines`
+    record_plan_estimates: bool,
 }
 
 impl AutoModel {
     /// Create a new AutoModel instance by loading queries from SQL files in a directory
     /// with explicit defaults configuration (no YAML file required)
     pub async fn new<P: AsRef<Path>>(queries_dir: P, defaults: DefaultsConfig) -> Result<Self> {
-        // Scan SQL files from the queries directory
-        let queries = scan_sql_files(queries_dir.as_ref(), defaults).await?;
+        if let Some(name) = defaults.defines.keys().find(|name| !is_define_name(name)) {
+            anyhow::bail!(
+                "defines name '{}' is not valid, use letters, digits and underscores",
+                name
+            );
+        }
+        for rule in &defaults.mandatory_predicates {
+            rule.validate()?;
+        }
+        if let Some(suggest_timeouts) = &defaults.suggest_timeouts {
+            suggest_timeouts.validate()?;
+        }
+        if let Some(dir) = defaults
+            .extra_query_dirs
+            .iter()
+            .find(|dir| !Path::new(dir).is_dir())
+        {
+            anyhow::bail!("extra_query_dirs directory '{}' does not exist", dir);
+        }
+
+        // Scan SQL files from every queries directory, reporting the errors of all scans together
+        let query_dirs: Vec<&Path> = std::iter::once(queries_dir.as_ref())
+            .chain(defaults.extra_query_dirs.iter().map(Path::new))
+            .collect();
+        let mut queries: Vec<QueryDefinition> = Vec::new();
+        let mut module_configs = std::collections::HashMap::new();
+        // Key: module name, value: directory of its module.yaml
+        let mut module_config_dirs: std::collections::HashMap<String, &Path> =
+            std::collections::HashMap::new();
+        let mut errors = Vec::new();
+        let source_file = |query: &QueryDefinition| {
+            query
+                .source_file
+                .as_ref()
+                .map_or_else(|| query.module.clone(), |path| path.display().to_string())
+        };
+        for query_dir in &query_dirs {
+            let (dir_queries, dir_module_configs) = match (
+                scan_sql_files(query_dir, defaults.clone()).await,
+                scan_module_configs(query_dir).await,
+            ) {
+                (Ok(queries), Ok(module_configs)) => (queries, module_configs),
+                (Err(queries_error), Err(configs_error)) => {
+                    errors.push(format!("{:#}\n{:#}", queries_error, configs_error));
+                    continue;
+                }
+                (Err(e), _) | (_, Err(e)) => {
+                    errors.push(format!("{:#}", e));
+                    continue;
+                }
+            };
+
+            for query in dir_queries {
+                if let Some(existing) = queries
+                    .iter()
+                    .find(|existing| existing.module == query.module && existing.name == query.name)
+                {
+                    errors.push(format!(
+                        "Query {}::{} is defined in both {} and {}",
+                        query.module,
+                        query.name,
+                        source_file(existing),
+                        source_file(&query)
+                    ));
+                    continue;
+                }
+                queries.push(query);
+            }
+            for (module, config) in dir_module_configs {
+                if let Some(existing_dir) = module_config_dirs.get(&module) {
+                    errors.push(format!(
+                        "Module '{}' is configured by {} in both {} and {}",
+                        module,
+                        MODULE_CONFIG_FILE_NAME,
+                        existing_dir.display(),
+                        query_dir.display()
+                    ));
+                    continue;
+                }
+                module_config_dirs.insert(module.clone(), query_dir);
+                module_configs.insert(module, config);
+            }
+        }
+        if !errors.is_empty() {
+            anyhow::bail!("{}", errors.join("\n"));
+        }
+
+        if let Some(entry) = defaults
+            .only
+            .iter()
+            .find(|entry| !queries.iter().any(|query| only_entry_selects(entry, query)))
+        {
+            anyhow::bail!("only entry '{}' matches no module or query", entry);
+        }
+
+        Ok(Self {
+            queries,
+            module_configs,
+            defaults,
+            context: GenerationContext::default(),
+            record_plan_estimates: false,
+        })
+    }
+
+    /// Report through the logger and use the caches of the given context instead of a new one
+    /// printing cargo build script instructions
+    pub fn with_context(mut self, context: GenerationContext) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// Format the SQL of every query file in a queries directory, as `format_sql` formats it for
+    /// code generation, keeping the metadata blocks as written
+    /// Returns the files which were not formatted, and rewrites them unless `check` is set
+    /// Requires the `sql-format` feature
+    pub fn format_queries<P: AsRef<Path>>(
+        queries_dir: P,
+        check: bool,
+    ) -> Result<Vec<std::path::PathBuf>> {
+        sql_format::format_query_files(queries_dir.as_ref(), check)
+    }
+
+    /// Module and name of every loaded query, e.g. ("users", "find_user_by_email"), in the order
+    /// of their SQL files
+    pub fn query_names(&self) -> Vec<(String, String)> {
+        self.queries
+            .iter()
+            .map(|query| (query.module.clone(), query.name.clone()))
+            .collect()
+    }
+
+    /// SQL file of every loaded query as (module, name, file), in the order of their SQL files
+    /// The file is None for queries generated from `history_tables` introspection
+    pub fn query_source_files(&self) -> Vec<(String, String, Option<std::path::PathBuf>)> {
+        self.queries
+            .iter()
+            .map(|query| {
+                (
+                    query.module.clone(),
+                    query.name.clone(),
+                    query.source_file.clone(),
+                )
+            })
+            .collect()
+    }
+
+    /// Remove the artifacts of earlier generation runs which the loaded modules and the defaults
+    /// no longer produce, e.g. the files of deleted modules or a snapshot left after disabling
+    /// `query_analysis_timeout`, or the whole output directory with `all`
+    /// Returns the removed files and directories, which are only listed with `dry_run`
+    pub fn clean(
+        &self,
+        output_dir: &str,
+        all: bool,
+        dry_run: bool,
+    ) -> Result<Vec<std::path::PathBuf>> {
+        let output_path = Path::new(output_dir);
+        let artifacts = if all {
+            clean::output_dir_artifacts(output_path, &self.defaults)?
+        } else {
+            // History queries are only loaded when generating, their module is kept
+            let mut modules = self.get_modules();
+            if let Some(history_queries) = &self.defaults.history_queries {
+                modules.push(history_queries.module.clone());
+            }
+            clean::stale_artifacts(output_path, &modules, &self.defaults)?
+        };
+        if !dry_run {
+            clean::remove_artifacts(&artifacts)?;
+        }
+        Ok(artifacts)
+    }
+
+    /// Effective configuration of every query as YAML, grouped by module
+    /// Shows the result of merging the defaults, `module.yaml` and each file's metadata block and
+    /// sidecar metadata file, without connecting to the database
+    pub fn effective_config(&self) -> Result<String> {
+        use std::collections::BTreeMap;
+
+        #[derive(serde::Serialize)]
+        struct EffectiveModuleConfig<'a> {
+            /// Settings from `module.yaml`
+            module: &'a ModuleConfig,
+            /// Key: query name
+            queries: BTreeMap<&'a str, &'a QueryDefinition>,
+        }
+
+        let default_module_config = ModuleConfig::default();
+        let mut modules: BTreeMap<&str, EffectiveModuleConfig> = BTreeMap::new();
+        for query in &self.queries {
+            modules
+                .entry(&query.module)
+                .or_insert_with(|| EffectiveModuleConfig {
+                    module: self
+                        .module_configs
+                        .get(&query.module)
+                        .unwrap_or(&default_module_config),
+                    queries: BTreeMap::new(),
+                })
+                .queries
+                .insert(&query.name, query);
+        }
 
-        Ok(Self { queries })
+        Ok(serde_yaml::to_string(&modules)?)
     }
 
     /// Build script helper for automatically generating code at build time.
@@ -91,52 +574,272 @@ impl AutoModel {
         output_dir: &str,
         defaults: crate::DefaultsConfig,
     ) -> Result<(), Box<dyn std::error::Error>>
+    where
+        F: FnOnce() -> Result<String, String>,
+    {
+        Self::generate_with_context(
+            GenerationContext::default(),
+            database_url_cb,
+            queries_dir,
+            output_dir,
+            defaults,
+        )
+        .await
+    }
+
+    /// Same as `generate`, reporting through the logger of `context` instead of printing cargo
+    /// build script instructions, e.g. `GenerationContext::quiet()` when embedded in other tools
+    /// or tests
+    pub async fn generate_with_context<F>(
+        context: GenerationContext,
+        database_url_cb: F,
+        queries_dir: &str,
+        output_dir: &str,
+        defaults: crate::DefaultsConfig,
+    ) -> Result<(), Box<dyn std::error::Error>>
     where
         F: FnOnce() -> Result<String, String>,
     {
         use sha2::{Digest, Sha256};
         use std::fs;
 
-        println!("cargo:rerun-if-changed={}", output_dir);
+        let started = std::time::Instant::now();
+        context.update_summary(|summary| *summary = BuildSummary::default());
+        let logger = context.logger();
+        logger.rerun_if_changed(Path::new(output_dir));
 
         let output_path = Path::new(output_dir);
-        let mod_file = output_path.join("mod.rs");
-        println!("cargo:rerun-if-changed={}", mod_file.display());
+        // The source hash is stored in the umbrella crate's lib.rs in module crates mode
+        let mod_file = match &defaults.module_crates {
+            Some(_) => ModuleCratesConfig::lib_file(output_path),
+            None => output_path.join("mod.rs"),
+        };
+        logger.rerun_if_changed(&mod_file);
 
         let mut hasher = Sha256::new();
         hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
 
-        let queries_dir = Path::new(queries_dir);
-        if queries_dir.exists() && queries_dir.is_dir() {
-            println!("cargo:rerun-if-changed={}", queries_dir.display());
+        let query_dirs: Vec<&Path> = std::iter::once(Path::new(queries_dir))
+            .chain(defaults.extra_query_dirs.iter().map(Path::new))
+            .collect();
+        // Features of the #[cfg(...)] blocks of the SQL, the only features changing the code
+        let mut cfg_features = std::collections::BTreeSet::new();
+        for (index, queries_dir) in query_dirs.iter().enumerate() {
+            // Files moving between directories change the order of the queries
+            if index > 0 {
+                hasher.update(b"extra_query_dir");
+            }
+            if !queries_dir.is_dir() {
+                continue;
+            }
+            logger.rerun_if_changed(queries_dir);
             // Collect all SQL files and sort them for deterministic hashing
             let mut sql_files = Vec::new();
             for module_entry in fs::read_dir(queries_dir)? {
                 let module_entry = module_entry?;
                 let module_path = module_entry.path();
                 if module_path.is_dir() {
-                    println!("cargo:rerun-if-changed={}", module_path.display());
+                    logger.rerun_if_changed(&module_path);
                     for sql_entry in fs::read_dir(&module_path)? {
                         let sql_entry = sql_entry?;
                         let sql_path = sql_entry.path();
                         if sql_path.extension().and_then(|e| e.to_str()) == Some("sql") {
-                            println!("cargo:rerun-if-changed={}", sql_path.display());
+                            logger.rerun_if_changed(&sql_path);
                             sql_files.push(sql_path);
                         }
                     }
+                    let module_config = module_path.join(MODULE_CONFIG_FILE_NAME);
+                    logger.rerun_if_changed(&module_config);
+                    if module_config.is_file() {
+                        sql_files.push(module_config);
+                    }
                     let output_module_path = output_path.join(module_path.file_name().unwrap());
-                    println!("cargo:rerun-if-changed={}.rs", output_module_path.display());
+                    logger.rerun_if_changed(&output_module_path.with_extension("rs"));
                 }
             }
 
             // Sort for deterministic hashing
             sql_files.sort();
 
-            // Hash each SQL file
+            // Hash each SQL file with its metadata file, and module configuration
             for sql_file in sql_files {
                 let sql_contents = fs::read(&sql_file)?;
                 hasher.update(&sql_contents);
+                if sql_file.extension().and_then(|e| e.to_str()) != Some("sql") {
+                    continue;
+                }
+                for feature in cfg_block_features(&String::from_utf8_lossy(&sql_contents)) {
+                    cfg_features.insert(feature);
+                }
+                if let Some(sidecar_file) = sidecar_file(&sql_file) {
+                    logger.rerun_if_changed(&sidecar_file);
+                    hasher.update(b"sidecar");
+                    hasher.update(fs::read(&sidecar_file)?);
+                }
+            }
+        }
+
+        // Enabling TypeScript output requires a regeneration to create the files
+        if let Some(emit_ts) = &defaults.emit_ts {
+            hasher.update(emit_ts.as_bytes());
+        }
+        if let Some(emit_proto) = &defaults.emit_proto {
+            hasher.update(b"emit_proto");
+            hasher.update(emit_proto.as_bytes());
+        }
+        if let Some(migrations) = &defaults.cache_invalidation_migrations {
+            hasher.update(b"cache_invalidation_migrations");
+            hasher.update(migrations.as_bytes());
+        }
+
+        // Combined variants add analyzed variants and generated tests
+        if defaults.max_variant_combinations > 0 {
+            hasher.update(b"max_variant_combinations");
+            hasher.update(defaults.max_variant_combinations.to_le_bytes());
+        }
+
+        // Plugins change the written code without changing the queries
+        for version in context.plugin_versions() {
+            hasher.update(b"plugin");
+            hasher.update(version.as_bytes());
+        }
+
+        // Derives of the generated structs change with auto_derives
+        if defaults.auto_derives {
+            hasher.update(b"auto_derives");
+        }
+
+        // Blocking wrappers are generated for every query by default
+        if defaults.blocking {
+            hasher.update(b"blocking");
+        }
+
+        // Cancellable variants are generated for every query by default
+        if defaults.cancellable {
+            hasher.update(b"cancellable");
+        }
+
+        // Every read-only query is checked for cardinality drift by default
+        if defaults.cardinality_drift {
+            hasher.update(b"cardinality_drift");
+        }
+
+        // LIMIT 1 is added to every possible_one query by default
+        if defaults.limit_possible_one {
+            hasher.update(b"limit_possible_one");
+        }
+
+        // The recorded schema version is only read when generating, a different expected version
+        // requires generating against a matching database
+        if let Some(schema_version_query) = &defaults.schema_version_query {
+            hasher.update(b"schema_version_query");
+            hasher.update(schema_version_query.as_bytes());
+        }
+        if let Some(expected_schema_version) = &defaults.expected_schema_version {
+            hasher.update(b"expected_schema_version");
+            hasher.update(expected_schema_version.as_bytes());
+        }
+
+        // The dialect changes the analysis and the generated error classification
+        if defaults.dialect == Dialect::Cockroach {
+            hasher.update(b"dialect_cockroach");
+        }
+
+        // The sqlx version changes the signatures of the generated Encode implementations
+        match defaults.sqlx_version {
+            SqlxVersion::V0_7 => hasher.update(b"sqlx_version_0_7"),
+            SqlxVersion::V0_8 => {}
+            SqlxVersion::Feature => hasher.update(b"sqlx_version_feature"),
+        }
+
+        // Formatting changes the SQL of every query
+        if defaults.format_sql {
+            hasher.update(b"format_sql");
+        }
+
+        // Recorded parameters of every function are cut to the default limits
+        if let Some(max_param_length) = defaults.telemetry.max_param_length {
+            hasher.update(b"max_param_length");
+            hasher.update(max_param_length.to_le_bytes());
+        }
+        if let Some(max_param_elements) = defaults.telemetry.max_param_elements {
+            hasher.update(b"max_param_elements");
+            hasher.update(max_param_elements.to_le_bytes());
+        }
+
+        // Every function checks the default slow query threshold
+        if let Some(slow_query_ms) = defaults.slow_query_ms {
+            hasher.update(b"slow_query_ms");
+            hasher.update(slow_query_ms.to_le_bytes());
+        }
+
+        // Switching to module crates or changing their manifests requires a regeneration
+        if let Some(module_crates) = &defaults.module_crates {
+            hasher.update(module_crates.name.as_bytes());
+            hasher.update(module_crates.dependencies.as_bytes());
+        }
+
+        // Moving the shared helpers in or out of the generated tree requires a regeneration
+        if let Some(vendored_runtime) = &defaults.vendored_runtime {
+            hasher.update(b"vendored_runtime");
+            hasher.update(vendored_runtime.file.as_bytes());
+            hasher.update(vendored_runtime.module_path.as_bytes());
+        }
+
+        // History functions are generated for the tables found when generating
+        if let Some(history_queries) = &defaults.history_queries {
+            hasher.update(b"history_queries");
+            hasher.update(history_queries.module.as_bytes());
+            hasher.update(history_queries.suffix.as_bytes());
+            hasher.update(history_queries.time_column.as_bytes());
+        }
+
+        // Suggested timeouts are reported or written when generating
+        if let Some(suggest_timeouts) = &defaults.suggest_timeouts {
+            hasher.update(b"suggest_timeouts");
+            hasher.update(suggest_timeouts.ms_per_cost.to_le_bytes());
+            hasher.update(suggest_timeouts.headroom.to_le_bytes());
+            hasher.update(suggest_timeouts.min_ms.to_le_bytes());
+            hasher.update(suggest_timeouts.max_ms.to_le_bytes());
+            hasher.update([suggest_timeouts.write_file as u8]);
+        }
+
+        // Mandatory predicates are checked in and injected into the SQL of the queries
+        for rule in &defaults.mandatory_predicates {
+            hasher.update(b"mandatory_predicate");
+            for table in &rule.tables {
+                hasher.update(table.as_bytes());
+                hasher.update(b",");
             }
+            hasher.update(rule.predicate.as_bytes());
+            hasher.update([rule.inject as u8]);
+        }
+
+        // Splitting modules into more files requires a regeneration
+        if let Some(max_queries_per_file) = defaults.max_queries_per_file {
+            hasher.update(max_queries_per_file.to_le_bytes());
+        }
+
+        // Defined values are substituted into the SQL of the queries
+        for (name, value) in &defaults.defines {
+            hasher.update(name.as_bytes());
+            hasher.update(b"=");
+            hasher.update(value.as_bytes());
+            hasher.update(b"\n");
+        }
+
+        // Enabled features select the #[cfg(...)] blocks of the SQL, other features of the build
+        // leave the generated code unchanged
+        let features: std::collections::BTreeSet<String> = defaults
+            .features
+            .iter()
+            .map(|feature| feature.to_ascii_lowercase().replace('-', "_"))
+            .filter(|feature| cfg_features.contains(feature))
+            .collect();
+        for feature in &features {
+            hasher.update(b"feature=");
+            hasher.update(feature.as_bytes());
+            hasher.update(b"\n");
         }
 
         let result = hasher.finalize();
@@ -148,17 +851,25 @@ impl AutoModel {
             hash_u64 |= (byte as u64) << (i * 8);
         }
         let source_hash = hash_u64;
-        // Check if generated code is up to date
-        if Self::is_generated_mod_rs_code_up_to_date(source_hash, &mod_file).unwrap_or(false) {
-            println!("cargo:info=Skipping code generation as everything is up to date");
+        // Check if generated code is up to date, including the vendored helpers it depends on
+        if let Some(vendored_runtime) = &defaults.vendored_runtime {
+            logger.rerun_if_changed(Path::new(&vendored_runtime.file));
+        }
+        if Self::is_generated_mod_rs_code_up_to_date(source_hash, &mod_file).unwrap_or(false)
+            && defaults
+                .vendored_runtime
+                .as_ref()
+                .is_none_or(|vendored_runtime| vendored_runtime.exists())
+        {
+            logger.info("Skipping code generation as everything is up to date");
 
             // Output warnings from file even when skipping build
-            let warn_file = output_path.join("automodel.warn");
+            let warn_file = output_path.join(WARN_FILE_NAME);
             if warn_file.exists() {
                 if let Ok(warn_content) = fs::read_to_string(&warn_file) {
                     for warning in warn_content.lines() {
                         if !warning.is_empty() {
-                            println!("cargo:warning={}", warning);
+                            logger.warning(warning);
                         }
                     }
                 }
@@ -168,11 +879,16 @@ impl AutoModel {
         }
 
         let database_url = database_url_cb().map_err(|e| {
-            println!("cargo:error={}", e);
+            logger.error(&e);
             std::io::Error::new(std::io::ErrorKind::NotConnected, e)
         })?;
 
-        let automodel = AutoModel::new(queries_dir, defaults).await?;
+        let automodel = AutoModel::new(queries_dir, defaults)
+            .await?
+            .with_context(context.clone())
+            .with_history_queries(&database_url)
+            .await?;
+        context.update_summary(|summary| summary.scan = started.elapsed());
         automodel
             .generate_to_directory(&database_url, output_dir, source_hash)
             .await?;
@@ -180,6 +896,36 @@ impl AutoModel {
         Ok(())
     }
 
+    /// Add the history queries of `history_queries`, introspecting the database for the tables
+    /// with a history table
+    async fn with_history_queries(mut self, database_url: &str) -> Result<Self> {
+        let Some(history_queries) = &self.defaults.history_queries else {
+            return Ok(self);
+        };
+        let client = self
+            .connect_for_analysis(
+                database_url,
+                self.defaults.transaction_pooling,
+                self.defaults.dialect,
+            )
+            .await?;
+        for query in history_queries.queries(&client, &self.defaults).await? {
+            if self
+                .queries
+                .iter()
+                .any(|existing| existing.module == query.module && existing.name == query.name)
+            {
+                anyhow::bail!(
+                    "history query {}::{} conflicts with the query of the same name",
+                    query.module,
+                    query.name
+                );
+            }
+            self.queries.push(query);
+        }
+        Ok(self)
+    }
+
     /// Get all unique module names from the loaded queries
     fn get_modules(&self) -> Vec<String> {
         let mut modules: Vec<String> = self
@@ -193,6 +939,40 @@ impl AutoModel {
         modules
     }
 
+    /// Scope of every module whose queries are not compiled into every build
+    /// Fails for modules mixing scopes, and for scoped modules with `module_crates`
+    fn module_scopes(&self) -> Result<std::collections::HashMap<String, QueryScope>> {
+        let mut module_scopes = std::collections::HashMap::new();
+        for module in self.get_modules() {
+            let mut queries = self.queries.iter().filter(|query| query.module == module);
+            let Some(first) = queries.next() else {
+                continue;
+            };
+            if let Some(other) = queries.find(|query| query.scope != first.scope) {
+                anyhow::bail!(
+                    "Module '{}' mixes queries of scope {} ({}) and {} ({}), move the test or bench queries into their own module",
+                    module,
+                    first.scope.name(),
+                    first.name,
+                    other.scope.name(),
+                    other.name
+                );
+            }
+            if first.scope == QueryScope::All {
+                continue;
+            }
+            if self.defaults.module_crates.is_some() {
+                anyhow::bail!(
+                    "Module '{}' has queries of scope {}, which cannot be combined with module_crates",
+                    module,
+                    first.scope.name()
+                );
+            }
+            module_scopes.insert(module, first.scope);
+        }
+        Ok(module_scopes)
+    }
+
     /// Check if generated code is up to date by comparing file hash
     fn is_generated_mod_rs_code_up_to_date<Q: AsRef<Path>>(
         source_hash: u64,
@@ -228,116 +1008,1432 @@ impl AutoModel {
     ) -> anyhow::Result<()> {
         use std::fs;
         use std::path::Path;
-        use std::time::Duration;
 
         let output_path = Path::new(output_dir);
         let modules = self.get_modules();
+        let module_scopes = self.module_scopes()?;
+
+        if self.defaults.max_queries_per_file == Some(0) {
+            anyhow::bail!("max_queries_per_file must be greater than 0");
+        }
 
         // Create output directory
         fs::create_dir_all(output_path)?;
 
-        Self::cleanup_unused_files(output_path, &modules)?;
-
-        // Parse connection string and configure timeouts
-        let mut config: tokio_postgres::Config = database_url.parse()?;
-        config.connect_timeout(Duration::from_secs(10));
-
-        // Connect with NoTls - users should add ?sslmode=disable to their connection string
-        // For TLS support, the dependency on postgres-native-tls or tokio-postgres-rustls would be needed
-        let (client, connection) = config.connect(tokio_postgres::NoTls).await?;
-
-        // Spawn the connection task
-        tokio::spawn(async move {
-            if let Err(e) = connection.await {
-                eprintln!("Connection error: {}", e);
+        if let Some(vendored_runtime) = &self.defaults.vendored_runtime {
+            if self.defaults.module_crates.is_some() {
+                anyhow::bail!(
+                    "vendored_runtime cannot be combined with module_crates, whose modules depend on the generated runtime crate"
+                );
             }
-        });
+            vendored_runtime.validate(output_path)?;
+        }
 
-        // Temporarily disable sequential scans to force index usage in analysis
-        // This helps detect queries that would benefit from indexes even with empty/small tables
-        client.execute("SET enable_seqscan = false", &[]).await?;
+        match &self.defaults.module_crates {
+            Some(module_crates) => {
+                module_crates.validate()?;
+                ModuleCratesConfig::remove_stale_module_crates(output_path, &modules)?;
+            }
+            None => Self::cleanup_unused_files(output_path, &modules)?,
+        }
 
-        // Enforce queries with full path, including schemas
-        client.execute("SET search_path TO ''", &[]).await?;
+        // Analysis snapshot is only maintained when analysis is time-boxed or limited to some
+        // queries, which then reuse the cached analysis of the others
+        let uses_snapshot = self.defaults.query_analysis_timeout.is_some()
+            || self.defaults.analysis_budget.is_some()
+            || !self.defaults.only.is_empty();
+        let snapshot = if uses_snapshot {
+            AnalysisSnapshot::load(output_path)
+        } else {
+            AnalysisSnapshot::default()
+        };
 
         // PHASE 1: Analyze all queries and collect information
-        let analyzed_queries = self.analyze_all_queries(&client).await?;
+        let analysis_started = std::time::Instant::now();
+        let (analyzed_queries, stale_queries, environment) = self
+            .analyze_with_connections(database_url, &snapshot)
+            .await?;
+        self.context.update_summary(|summary| {
+            summary.analysis = analysis_started.elapsed();
+            summary.environment = Some(environment.clone());
+        });
+
+        // Enforce performance checks and mandatory predicates before writing any code
+        self.check_fail_on(&analyzed_queries)?;
+        self.check_mandatory_predicates(&analyzed_queries)?;
+        let cached_tables = cache_invalidation::cached_tables(&analyzed_queries)?;
 
         // Collect all warnings
         let mut all_warnings = Vec::new();
 
+        // Summarize queries which fell back to stale analysis data
+        let stale_queries: Vec<&String> = stale_queries
+            .iter()
+            .filter(|query_name| {
+                !analyzed_queries.iter().any(|analyzed| {
+                    analyzed.definition.name == **query_name
+                        && analyzed
+                            .definition
+                            .allow_diagnostics
+                            .contains(&DiagnosticCode::StaleAnalysis)
+                })
+            })
+            .collect();
+        if !stale_queries.is_empty() {
+            self.context.logger().warning(&format!(
+                "[{} {}] {} queries exceeded the analysis time limit and used cached analysis: {}",
+                DiagnosticCode::StaleAnalysis,
+                DiagnosticCode::StaleAnalysis.name(),
+                stale_queries.len(),
+                stale_queries
+                    .iter()
+                    .map(|query_name| query_name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+            for query_name in &stale_queries {
+                all_warnings.push(Diagnostic::new(
+                    DiagnosticCode::StaleAnalysis,
+                    format!(
+                        "Query '{}' used stale cached analysis because the analysis time limit was exceeded",
+                        query_name
+                    ),
+                ));
+            }
+        }
+
         // PHASE 2: Generate code from analyzed queries (no DB access)
+        // Time not spent generating code is spent writing it
+        let output_started = std::time::Instant::now();
+        let mut codegen_time = Duration::ZERO;
+        let mut edit_guard = EditGuard::new(self.defaults.overwrite_edited_files);
+        let mut registry = std::collections::BTreeMap::new();
+        // Without rustfmt the code is written as generated, so that build scripts still succeed
+        let rustfmt_available = rustfmt_available();
+        if !rustfmt_available {
+            self.context.logger().warning(
+                "rustfmt is not available, the generated code is written unformatted and differs from the code generated with rustfmt (install it with `rustup component add rustfmt` or set RUSTFMT)",
+            );
+        }
+        let format_generated_code = |code: &str| {
+            if rustfmt_available {
+                format_generated_code(code)
+            } else {
+                Ok(code.to_string())
+            }
+        };
         for module in &modules {
-            let (module_code, module_warnings) =
-                crate::codegen::generate_code_for_module(&analyzed_queries, module)?;
-            let module_file = output_path.join(format!("{}.rs", module));
-            fs::write(&module_file, &module_code)?;
+            let codegen_started = std::time::Instant::now();
+            let module_config = self.module_configs.get(module).cloned().unwrap_or_default();
+            let generated_module = crate::codegen::generate_code_for_module(
+                &analyzed_queries,
+                module,
+                &module_config,
+                self.defaults.max_queries_per_file,
+            )?;
+            let module_code = self
+                .context
+                .transform(module, format_generated_code(&generated_module.code)?);
+            let parts: Vec<(String, String)> = generated_module
+                .parts
+                .into_iter()
+                .map(|(file_name, code)| {
+                    Ok((
+                        file_name,
+                        self.context
+                            .transform(module, format_generated_code(&code)?),
+                    ))
+                })
+                .collect::<Result<_>>()?;
+
+            // Values interpolated into the SQL at runtime must be literals, placeholder numbers or
+            // validated by the shared helpers, whichever feature or plugin generated them
+            let module_sources: Vec<&str> = std::iter::once(module_code.as_str())
+                .chain(parts.iter().map(|(_, code)| code.as_str()))
+                .collect();
+            let audit = audit_generated_sql(&module_sources.join("\n"));
+            if !audit.is_safe() {
+                let findings: Vec<String> =
+                    audit.findings.iter().map(ToString::to_string).collect();
+                anyhow::bail!(
+                    "Generated code of module '{}' interpolates unvalidated values into SQL:\n  {}",
+                    module,
+                    findings.join("\n  ")
+                );
+            }
+            codegen_time += codegen_started.elapsed();
+            match &self.defaults.module_crates {
+                Some(module_crates) => module_crates.write_module_crate(
+                    output_path,
+                    module,
+                    &module_code,
+                    &parts,
+                    &mut edit_guard,
+                )?,
+                None => {
+                    let module_file = output_path.join(format!("{}.rs", module));
+                    edit_guard.write(&module_file, &module_code)?;
+                    let module_dir = output_path.join(module);
+                    for (file_name, code) in &parts {
+                        edit_guard.write(&module_dir.join(file_name), code)?;
+                    }
+                    remove_stale_module_parts(&module_dir, &parts)?;
+                }
+            }
 
             // Output warnings for this module
-            for warning in &module_warnings {
-                println!("cargo:warning={}", warning);
+            for warning in &generated_module.warnings {
+                self.context.logger().warning(&warning.to_string());
+            }
+            all_warnings.extend(generated_module.warnings);
+
+            for (query, params) in generated_module.signatures {
+                let file = analyzed_queries
+                    .iter()
+                    .find(|analyzed| {
+                        analyzed.module() == module && analyzed.definition.name == query
+                    })
+                    .and_then(|analyzed| analyzed.definition.source_file.as_ref())
+                    .map(|file| file.to_string_lossy().replace('\\', "/"));
+                let params = params
+                    .into_iter()
+                    .map(|(name, rust_type)| query_registry::RegisteredParam { name, rust_type })
+                    .collect();
+                registry.insert(
+                    format!("{}::{}", module, query),
+                    query_registry::RegisteredQuery { file, params },
+                );
+            }
+        }
+        query_registry::write_registry(output_path, &registry)?;
+
+        // TypeScript interfaces come from the same analysis as the Rust code
+        if let Some(ts_dir) = &self.defaults.emit_ts {
+            Self::write_module_files(
+                &analyzed_queries,
+                &modules,
+                Path::new(ts_dir),
+                "ts",
+                TYPESCRIPT_HEADER,
+                generate_typescript_for_module,
+                &mut edit_guard,
+            )?;
+        }
+        if let Some(proto_dir) = &self.defaults.emit_proto {
+            Self::write_module_files(
+                &analyzed_queries,
+                &modules,
+                Path::new(proto_dir),
+                "proto",
+                PROTO_HEADER,
+                generate_proto_for_module,
+                &mut edit_guard,
+            )?;
+        }
+
+        // Triggers notifying the listeners of the generated code come from the same analysis
+        if let Some(migrations) = &self.defaults.cache_invalidation_migrations {
+            cache_invalidation::write_migrations(Path::new(migrations), &cached_tables)?;
+        }
+
+        // The source hash is only updated once every file is written, so generation keeps
+        // failing until the edited files are resolved
+        edit_guard.check()?;
+
+        if uses_snapshot {
+            AnalysisSnapshot::from_analyzed(&analyzed_queries).save(output_path)?;
+        }
+
+        // Create the main mod.rs file, or the runtime and umbrella crates
+        let codegen_started = std::time::Instant::now();
+        let plan_sampling = analyzed_queries.iter().any(|analyzed| {
+            analyzed.definition.plan_sampling
+                && !analyzed.is_mutation
+                && analyzed.definition.shard_by.is_none()
+        });
+        let large_lists = analyzed_queries.iter().any(|analyzed| {
+            analyzed.definition.large_list_strategy == LargeListStrategy::TempTable
+        });
+        let sharding = analyzed_queries
+            .iter()
+            .any(|analyzed| analyzed.definition.shard_by.is_some());
+        let batching = analyzed_queries
+            .iter()
+            .any(|analyzed| analyzed.definition.batch.is_some());
+        let blocking = analyzed_queries
+            .iter()
+            .any(|analyzed| analyzed.definition.blocking);
+        let cancellation = analyzed_queries
+            .iter()
+            .any(|analyzed| analyzed.definition.cancellable);
+        let sessions = analyzed_queries
+            .iter()
+            .any(|analyzed| analyzed.definition.requires_session);
+        let cascade_previews = analyzed_queries
+            .iter()
+            .any(|analyzed| analyzed.type_info.cascade_preview.is_some());
+        let cardinality_drift = analyzed_queries
+            .iter()
+            .any(|analyzed| analyzed.type_info.estimated_rows.is_some());
+        let cache_invalidation = analyzed_queries
+            .iter()
+            .any(|analyzed| analyzed.definition.cache_invalidation);
+        let query_cache_channels: Vec<String> = analyzed_queries
+            .iter()
+            .filter_map(|analyzed| analyzed.definition.cache_invalidate_on.as_ref())
+            .map(|invalidate_on| invalidate_on.channel().to_string())
+            .collect::<std::collections::BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let feature_flags = analyzed_queries
+            .iter()
+            .any(|analyzed| analyzed.definition.feature_flag.is_some());
+        let numeric_conversions = analyzed_queries.iter().any(|analyzed| {
+            analyzed
+                .type_info
+                .output_types
+                .iter()
+                .any(|col| col.rust_type.is_numeric_as_i64)
+        });
+        let money = analyzed_queries.iter().any(|analyzed| {
+            let type_info = &analyzed.type_info;
+            type_info
+                .input_types
+                .iter()
+                .chain(type_info.output_types.iter().map(|col| &col.rust_type))
+                .any(|rust_type| rust_type.currency.is_some())
+        });
+        let char_flags = analyzed_queries.iter().any(|analyzed| {
+            let type_info = &analyzed.type_info;
+            type_info
+                .input_types
+                .iter()
+                .chain(type_info.output_types.iter().map(|col| &col.rust_type))
+                .any(|rust_type| rust_type.char_flag.is_some())
+        });
+        let array_elements = analyzed_queries.iter().any(|analyzed| {
+            let type_info = &analyzed.type_info;
+            type_info
+                .input_types
+                .iter()
+                .chain(type_info.output_types.iter().map(|col| &col.rust_type))
+                .any(|rust_type| rust_type.array_element.is_some())
+        });
+        let param_limits = analyzed_queries.iter().any(|analyzed| {
+            let telemetry = &analyzed.definition.telemetry;
+            telemetry.limits_params()
+                && telemetry
+                    .include_params
+                    .as_ref()
+                    .is_some_and(|include_params| !include_params.is_empty())
+        });
+        let full_text_search = analyzed_queries.iter().any(|analyzed| {
+            let type_info = &analyzed.type_info;
+            type_info
+                .input_types
+                .iter()
+                .chain(type_info.output_types.iter().map(|col| &col.rust_type))
+                .any(|rust_type| rust_type.rust_type.contains("full_text_search::"))
+        });
+        let mut shared_items = generate_shared_items(SharedItems {
+            plan_sampling,
+            large_lists,
+            sharding,
+            batching,
+            full_text_search,
+            blocking,
+            cancellation,
+            sessions,
+            cascade_previews,
+            cardinality_drift,
+            cache_invalidation,
+            query_cache_channels,
+            feature_flags,
+            numeric_conversions,
+            money,
+            char_flags,
+            array_elements,
+            param_limits,
+            dialect: self.defaults.dialect,
+            sqlx_version: self.defaults.sqlx_version,
+        });
+        if let Some(schema_version) = &environment.schema_version {
+            shared_items.push('\n');
+            shared_items.push_str(&generate_schema_version_const(schema_version));
+        }
+        // Shared items written on their own are formatted here, those of mod.rs with it below
+        let shared_items =
+            if self.defaults.module_crates.is_some() || self.defaults.vendored_runtime.is_some() {
+                format_generated_code(&shared_items)?
+            } else {
+                shared_items
+            };
+        codegen_time += codegen_started.elapsed();
+        match &self.defaults.module_crates {
+            Some(module_crates) => {
+                module_crates.write_runtime_crate(output_path, &shared_items)?;
+                module_crates.write_umbrella_crate(
+                    output_path,
+                    &modules,
+                    source_hash,
+                    &environment,
+                )?;
+            }
+            None => {
+                // Vendored helpers are re-exported where the generated modules expect them
+                let root_items = match &self.defaults.vendored_runtime {
+                    Some(vendored_runtime) => {
+                        vendored_runtime.write(&shared_items, &mut edit_guard)?;
+                        vendored_runtime.reexport()
+                    }
+                    None => shared_items,
+                };
+                let mod_file = output_path.join("mod.rs");
+                let codegen_started = std::time::Instant::now();
+                let mod_content = format_generated_code(&generate_root_module(
+                    &modules,
+                    &module_scopes,
+                    source_hash,
+                    &environment,
+                    &root_items,
+                ))?;
+                codegen_time += codegen_started.elapsed();
+                edit_guard.write(&mod_file, &mod_content)?;
+                edit_guard.check()?;
             }
-            all_warnings.extend(module_warnings);
         }
 
-        // Create the main mod.rs file
-        let mod_file = output_path.join("mod.rs");
-        let mod_content = generate_root_module(&modules, source_hash);
-        fs::write(&mod_file, &mod_content)?;
+        // Suggested timeouts are reported with the other warnings or written to their own file
+        match &self.defaults.suggest_timeouts {
+            Some(suggest_timeouts) => {
+                for warning in suggest_timeouts.warnings(&analyzed_queries) {
+                    self.context.logger().warning(&warning.to_string());
+                    all_warnings.push(warning);
+                }
+                suggest_timeouts.save(&analyzed_queries, output_path)?;
+            }
+            None => {
+                let _ = fs::remove_file(output_path.join(TIMEOUT_SUGGESTIONS_FILE_NAME));
+            }
+        }
 
         // Write all warnings to automodel.warn file only if there are warnings
-        let warn_file = output_path.join("automodel.warn");
+        let warn_file = output_path.join(WARN_FILE_NAME);
         if !all_warnings.is_empty() {
-            let warn_content = all_warnings.join("\n");
+            let warn_content = all_warnings
+                .iter()
+                .map(|warning| warning.to_string())
+                .collect::<Vec<_>>()
+                .join("\n");
             fs::write(&warn_file, &warn_content)?;
         } else {
             // Delete the file if it exists from a previous run with warnings
             let _ = fs::remove_file(&warn_file);
         }
 
+        self.context.update_summary(|summary| {
+            summary.codegen = codegen_time;
+            summary.write = output_started.elapsed().saturating_sub(codegen_time);
+        });
+        let summary = self.context.build_summary();
+        self.context
+            .logger()
+            .info(&format!("Build timing: {}", summary.phases_line()));
+        // Build scripts keep the summary next to their other outputs
+        if let Some(out_dir) = std::env::var_os("OUT_DIR") {
+            summary.save(Path::new(&out_dir))?;
+        }
+
         Ok(())
     }
 
-    /// PHASE 1: Analyze all queries and extract complete information
-    /// This phase interacts with the database to collect all needed information
-    async fn analyze_all_queries(
-        &self,
+    /// Analyze every query against the database without generating code
+    /// Returns the types, constraints, plans and warnings code generation is based on, for
+    /// building custom generators or documentation; performance checks of `fail_on` are not
+    /// enforced, and queries exceeding the analysis time limit fail as no snapshot is used
+    pub async fn analyze(&self, database_url: &str) -> Result<Vec<QueryAnalysis>> {
+        let mut automodel = self.clone().with_history_queries(database_url).await?;
+        automodel.record_plan_estimates = true;
+        let (analyzed_queries, _, _) = automodel
+            .analyze_with_connections(database_url, &AnalysisSnapshot::default())
+            .await?;
+        Ok(analyzed_queries
+            .iter()
+            .map(|analyzed| QueryAnalysis::from_runtime(analyzed, query_warnings(analyzed)))
+            .collect())
+    }
+
+    /// Record the current plan estimates as the `baseline` of the read-only queries declaring one,
+    /// or of every read-only query with `all`, rewriting the metadata blocks of their SQL files
+    /// Explicit tolerances are kept; returns the SQL files which were changed
+    pub async fn update_baselines(
+        &self,
+        database_url: &str,
+        all: bool,
+    ) -> Result<Vec<std::path::PathBuf>> {
+        let mut automodel = self.clone();
+        automodel.record_plan_estimates = all;
+        let (analyzed_queries, _, _) = automodel
+            .analyze_with_connections(database_url, &AnalysisSnapshot::default())
+            .await?;
+
+        let mut updated = Vec::new();
+        for analyzed in &analyzed_queries {
+            let (Some(path), Some(estimates)) = (
+                &analyzed.definition.source_file,
+                &analyzed.type_info.plan_estimates,
+            ) else {
+                continue;
+            };
+            if let Some(sidecar_file) = sidecar_file(path) {
+                let sidecar = std::fs::read_to_string(&sidecar_file)?;
+                if sidecar.lines().any(|line| line.starts_with("baseline:")) {
+                    anyhow::bail!(
+                        "{}: the baseline is set in the metadata file, update it there",
+                        sidecar_file.display()
+                    );
+                }
+            }
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read SQL file: {}", path.display()))?;
+            let updated_content = set_baseline(&content, estimates)
+                .with_context(|| format!("{}: failed to update the baseline", path.display()))?;
+            if updated_content != content {
+                std::fs::write(path, updated_content)?;
+                updated.push(path.clone());
+            }
+        }
+        Ok(updated)
+    }
+
+    /// Connect to the database and analyze all queries
+    /// Returns the analyzed queries, the names of those restored from the snapshot and the
+    /// environment of the analysis, with the schema version read with `schema_version_query`
+    async fn analyze_with_connections(
+        &self,
+        database_url: &str,
+        snapshot: &AnalysisSnapshot,
+    ) -> Result<(
+        Vec<QueryDefinitionRuntime>,
+        Vec<String>,
+        AnalysisEnvironment,
+    )> {
+        let concurrency = self
+            .defaults
+            .analysis_concurrency
+            .unwrap_or(DEFAULT_ANALYSIS_CONCURRENCY);
+        let connections = self.defaults.analysis_connections.unwrap_or(1);
+        if concurrency == 0 || connections == 0 {
+            anyhow::bail!("analysis_concurrency and analysis_connections must be greater than 0");
+        }
+
+        let transaction_pooling = self.defaults.transaction_pooling;
+        let dialect = self.defaults.dialect;
+
+        // More connections than concurrently analyzed queries would stay idle
+        let mut clients = Vec::new();
+        for _ in 0..connections.min(concurrency) {
+            clients.push(
+                self.connect_for_analysis(database_url, transaction_pooling, dialect)
+                    .await?,
+            );
+        }
+        // A query holds its connection for the whole transaction in transaction pooling mode, and
+        // for its whole analysis with a time limit, so no more queries are analyzed at a time
+        // than there are connections to check out
+        let concurrency = if self.holds_analysis_connections() {
+            clients.len()
+        } else {
+            concurrency
+        };
+
+        // Refuse to generate queries the server (or declared production server) can't run,
+        // or to analyze against a database with another schema version than expected
+        if transaction_pooling {
+            clients[0].batch_execute("BEGIN").await?;
+        }
+        let version_check = match self.check_server_version(&clients[0]).await {
+            Ok(()) => self.check_schema_version(&clients[0]).await,
+            Err(e) => Err(e),
+        };
+        // The settings are read as the analyzed queries see them, which set them per transaction
+        // in transaction pooling mode
+        let environment = match version_check {
+            Ok(schema_version) => {
+                if transaction_pooling {
+                    clients[0]
+                        .batch_execute(&Self::analysis_settings_sql(dialect, &[]))
+                        .await?;
+                }
+                AnalysisEnvironment::read(&clients[0], dialect, schema_version).await
+            }
+            Err(e) => Err(e),
+        };
+        if transaction_pooling {
+            clients[0].batch_execute("ROLLBACK").await?;
+        }
+        let environment = environment?;
+
+        // Without the shared cache every query is prepared, as when the cache cannot be opened
+        let analysis_cache = if self.defaults.shared_analysis_cache {
+            match SharedAnalysisCache::open(&SharedAnalysisCache::default_dir(), &clients[0]).await
+            {
+                Ok(cache) => Some(cache),
+                Err(e) => {
+                    self.context
+                        .logger()
+                        .warning(&format!("Shared analysis cache disabled: {:#}", e));
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        // Queries with their own search_path are analyzed on a dedicated connection per
+        // search_path, as setting it on a shared connection would affect concurrent queries
+        // In transaction pooling mode the search_path is set in the transaction of the query
+        let mut search_path_clients = std::collections::HashMap::new();
+        for query in &self.queries {
+            if transaction_pooling
+                || query.search_path.is_empty()
+                || search_path_clients.contains_key(&query.search_path)
+            {
+                continue;
+            }
+            let client = self
+                .connect_for_analysis(database_url, false, dialect)
+                .await?;
+            client
+                .batch_execute(&format!(
+                    "SET search_path TO {}",
+                    search_path_list(&query.search_path)
+                ))
+                .await?;
+            search_path_clients.insert(query.search_path.clone(), client);
+        }
+
+        let (analyzed_queries, stale_queries) = self
+            .analyze_all_queries(
+                &clients,
+                &search_path_clients,
+                concurrency,
+                snapshot,
+                analysis_cache.as_ref(),
+            )
+            .await?;
+        Ok((analyzed_queries, stale_queries, environment))
+    }
+
+    /// Open a database connection configured for query analysis
+    /// Connections through a transaction pooler are configured per query by `analyze_query_on`
+    async fn connect_for_analysis(
+        &self,
+        database_url: &str,
+        transaction_pooling: bool,
+        dialect: Dialect,
+    ) -> Result<tokio_postgres::Client> {
+        // Parse connection string and configure timeouts
+        let mut config: tokio_postgres::Config = database_url.parse()?;
+        config.connect_timeout(Duration::from_secs(10));
+
+        // Connect with NoTls - users should add ?sslmode=disable to their connection string
+        // For TLS support, the dependency on postgres-native-tls or tokio-postgres-rustls would be needed
+        let (client, connection) = config.connect(tokio_postgres::NoTls).await?;
+
+        // Spawn the connection task
+        let context = self.context.clone();
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                context
+                    .logger()
+                    .warning(&format!("Connection error: {}", e));
+            }
+        });
+
+        if transaction_pooling {
+            return Ok(client);
+        }
+
+        // Temporarily disable sequential scans to force index usage in analysis
+        // This helps detect queries that would benefit from indexes even with empty/small tables
+        if dialect.disables_seqscan() {
+            client.execute("SET enable_seqscan = false", &[]).await?;
+        }
+
+        // Enforce queries with full path, including schemas
+        client.execute("SET search_path TO ''", &[]).await?;
+
+        Ok(client)
+    }
+
+    /// Check that the analysis server and the declared production server satisfy
+    /// the minimum PostgreSQL version of every query
+    /// Returns an error listing all incompatible queries
+    async fn check_server_version(&self, client: &tokio_postgres::Client) -> Result<()> {
+        let row = client
+            .query_one(
+                "SELECT current_setting('server_version'), current_setting('server_version_num')::int",
+                &[],
+            )
+            .await?;
+        let server_version: String = row.get(0);
+        let server_version_num: i32 = row.get(1);
+        let server_major = (server_version_num / 10000) as u32;
+
+        let mut incompatible = Vec::new();
+        for query in &self.queries {
+            let Some(min_version) = query.min_pg_version else {
+                continue;
+            };
+            if server_major < min_version {
+                incompatible.push(format!(
+                    "  - {}::{} requires PostgreSQL {}, but the analysis server is {}",
+                    query.module, query.name, min_version, server_version
+                ));
+            } else if let Some(production_version) = self.defaults.production_pg_version {
+                if production_version < min_version {
+                    incompatible.push(format!(
+                        "  - {}::{} requires PostgreSQL {}, but the production server is declared as {}",
+                        query.module, query.name, min_version, production_version
+                    ));
+                }
+            }
+        }
+
+        if !incompatible.is_empty() {
+            anyhow::bail!(
+                "{} queries require a newer PostgreSQL version:\n{}",
+                incompatible.len(),
+                incompatible.join("\n")
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Read the schema version with `schema_version_query` and check it against
+    /// `expected_schema_version`
+    /// Returns None when no schema version query is configured
+    async fn check_schema_version(
+        &self,
         client: &tokio_postgres::Client,
-    ) -> Result<Vec<QueryDefinitionRuntime>> {
+    ) -> Result<Option<String>> {
+        let Some(schema_version_query) = &self.defaults.schema_version_query else {
+            if self.defaults.expected_schema_version.is_some() {
+                anyhow::bail!("expected_schema_version requires schema_version_query");
+            }
+            return Ok(None);
+        };
+
+        // The simple query protocol returns the value as text whatever its type
+        let schema_version = client
+            .simple_query(schema_version_query)
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to read the schema version: {}",
+                    schema_version_query
+                )
+            })?
+            .into_iter()
+            .find_map(|message| match message {
+                tokio_postgres::SimpleQueryMessage::Row(row) => {
+                    Some(row.get(0).map(str::to_string))
+                }
+                _ => None,
+            })
+            .flatten();
+        let Some(schema_version) = schema_version else {
+            anyhow::bail!(
+                "Schema version query returned no value: {}",
+                schema_version_query
+            );
+        };
+
+        if let Some(expected) = &self.defaults.expected_schema_version {
+            if *expected != schema_version {
+                anyhow::bail!(
+                    "The analysis database has schema version '{}' but '{}' is expected, generate against a database with the expected schema or update expected_schema_version",
+                    schema_version,
+                    expected
+                );
+            }
+        }
+        Ok(Some(schema_version))
+    }
+
+    /// Fail generation if performance analysis results violate the configured `fail_on` rules
+    /// Queries can opt out of individual checks with `allow`
+    fn check_fail_on(&self, analyzed_queries: &[QueryDefinitionRuntime]) -> Result<()> {
+        if self.defaults.fail_on.is_empty() {
+            return Ok(());
+        }
+
+        let mut violations = Vec::new();
+        for analyzed in analyzed_queries {
+            let query = &analyzed.definition;
+
+            for rule in &self.defaults.fail_on {
+                if query.allow.contains(&rule.check()) {
+                    continue;
+                }
+                // Baselines are checked for every query declaring one, with or without ensure_indexes
+                if let FailOn::Baseline = rule {
+                    if let (Some(baseline), Some(estimates)) =
+                        (&query.baseline, &analyzed.type_info.plan_estimates)
+                    {
+                        for regression in baseline.regressions(estimates) {
+                            violations.push(format!(
+                                "  - {}::{} {}",
+                                query.module, query.name, regression
+                            ));
+                        }
+                    }
+                    continue;
+                }
+                let Some(perf) = &analyzed.performance_analysis else {
+                    continue;
+                };
+                let violation = match rule {
+                    FailOn::SeqScan if perf.has_sequential_scan => {
+                        let mut tables = perf.sequential_scan_tables.clone();
+                        tables.sort();
+                        tables.dedup();
+                        Some(format!("performs sequential scan on {}", tables.join(", ")))
+                    }
+                    FailOn::MissingIndex if !perf.missing_index_tables.is_empty() => Some(format!(
+                        "filters sequentially scanned {} without an index",
+                        perf.missing_index_tables.join(", ")
+                    )),
+                    FailOn::Subquery if !perf.subquery_rewrites.is_empty() => Some(format!(
+                        "runs subqueries which could be rewritten as joins: {}",
+                        perf.subquery_rewrites.join(", ")
+                    )),
+                    FailOn::CostAbove(limit) => match perf.total_cost {
+                        Some(cost) if cost > *limit => Some(format!(
+                            "has estimated cost {:.2} above the limit of {}",
+                            cost, limit
+                        )),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                if let Some(violation) = violation {
+                    violations.push(format!(
+                        "  - {}::{} {}",
+                        query.module, query.name, violation
+                    ));
+                }
+            }
+        }
+
+        if !violations.is_empty() {
+            anyhow::bail!(
+                "{} performance check violations (add `allow: [...]` to the query metadata for intentional exceptions):\n{}",
+                violations.len(),
+                violations.join("\n")
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Fail generation if queries touch the tables of a mandatory predicate without applying it
+    /// The tables are those of the analyzed query plans, so tables reached through views count
+    fn check_mandatory_predicates(
+        &self,
+        analyzed_queries: &[QueryDefinitionRuntime],
+    ) -> Result<()> {
+        let mut violations = Vec::new();
+        for analyzed in analyzed_queries {
+            let query = &analyzed.definition;
+            if !mandatory_predicates::is_checked_statement(&query.sql) {
+                continue;
+            }
+            for rule in &self.defaults.mandatory_predicates {
+                let tables = rule.touched_tables(&analyzed.type_info.tables);
+                if !tables.is_empty() && !rule.is_applied(&query.sql) {
+                    violations.push(format!(
+                        "  - {}::{} touches {} without `{}`",
+                        query.module,
+                        query.name,
+                        tables.join(", "),
+                        rule.predicate
+                    ));
+                }
+            }
+        }
+
+        if !violations.is_empty() {
+            anyhow::bail!(
+                "{} queries are missing mandatory predicates (add the predicate outside conditional blocks, or set `inject` on the rule):\n{}",
+                violations.len(),
+                violations.join("\n")
+            );
+        }
+
+        Ok(())
+    }
+
+    /// PHASE 1: Analyze all queries and extract complete information
+    /// This phase interacts with the database to collect all needed information
+    /// Returns the analyzed queries and the names of queries which fell back to the snapshot
+    async fn analyze_all_queries(
+        &self,
+        clients: &[tokio_postgres::Client],
+        search_path_clients: &std::collections::HashMap<Vec<String>, tokio_postgres::Client>,
+        concurrency: usize,
+        snapshot: &AnalysisSnapshot,
+        analysis_cache: Option<&SharedAnalysisCache>,
+    ) -> Result<(Vec<QueryDefinitionRuntime>, Vec<String>)> {
         use futures::stream::{self, StreamExt};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let total = self.queries.len();
+        let analyzed_count = AtomicUsize::new(0);
+        let analyzed_count = &analyzed_count;
+
+        let deadline = self
+            .defaults
+            .analysis_budget
+            .map(|budget| tokio::time::Instant::now() + budget);
+        let query_timeout = self.defaults.query_analysis_timeout;
+
+        // Queries are analyzed in the configured order, the most recently modified first with
+        // `AnalysisOrder::RecentlyModified` (the sort is stable, so ties keep the file order)
+        let mut order: Vec<usize> = (0..total).collect();
+        if self.defaults.analysis_order == AnalysisOrder::RecentlyModified {
+            order.sort_by_key(|&i| std::cmp::Reverse(self.queries[i].modified));
+        }
+
+        // Queries are spread over the connections in turn; at most `concurrency` are analyzed
+        // at the same time, the next query only starts when one of them completes
+        // In transaction pooling mode and with time limits each query checks out a connection no
+        // other query uses, the dedicated connection of its search_path if it has one
+        let idle_clients = IdleConnections::new(clients);
+        let idle_clients = &idle_clients;
+        let idle_search_path_clients: std::collections::HashMap<_, _> = search_path_clients
+            .iter()
+            .map(|(search_path, client)| {
+                (
+                    search_path,
+                    IdleConnections::new(std::slice::from_ref(client)),
+                )
+            })
+            .collect();
+        let idle_search_path_clients = &idle_search_path_clients;
+        let mut analyses = stream::iter(order)
+            .map(|i| async move {
+                let query = &self.queries[i];
+                let started = std::time::Instant::now();
+                // Queries not selected by `only` reuse their cached analysis when it is current
+                let cached = if self.defaults.only.is_empty()
+                    || self
+                        .defaults
+                        .only
+                        .iter()
+                        .any(|entry| only_entry_selects(entry, query))
+                {
+                    None
+                } else {
+                    snapshot.restore(query)
+                };
+                let is_cached = cached.is_some();
+                let result = match cached {
+                    Some(analyzed_query) => Ok((analyzed_query, false)),
+                    None => {
+                        let checked_out;
+                        let client = if self.holds_analysis_connections() {
+                            checked_out = idle_search_path_clients
+                                .get(&query.search_path)
+                                .unwrap_or(idle_clients)
+                                .checkout()
+                                .await;
+                            &*checked_out
+                        } else {
+                            search_path_clients
+                                .get(&query.search_path)
+                                .unwrap_or(&clients[i % clients.len()])
+                        };
+                        self.analyze_query_within_limits(
+                            client,
+                            query,
+                            deadline,
+                            query_timeout,
+                            snapshot,
+                            analysis_cache,
+                        )
+                        .await
+                    }
+                };
+                let elapsed = started.elapsed();
+                let count = analyzed_count.fetch_add(1, Ordering::Relaxed) + 1;
+                self.context.logger().info(&format!(
+                    "Analyzed {} of {} queries ({}, {}ms)",
+                    count,
+                    total,
+                    query.name,
+                    elapsed.as_millis()
+                ));
+                (i, result, elapsed, is_cached)
+            })
+            .buffer_unordered(concurrency);
+
+        let mut results: Vec<(usize, (QueryDefinitionRuntime, bool))> = Vec::with_capacity(total);
+        let mut timings = Vec::with_capacity(total);
+        let mut errors = Vec::new();
+        while let Some((i, result, elapsed, is_cached)) = analyses.next().await {
+            let is_stale = matches!(result, Ok((_, true)));
+            timings.push((i, elapsed, is_cached || is_stale));
+            match result {
+                Ok(analysis) => results.push((i, analysis)),
+                // Dropping the stream cancels the analyses in progress
+                Err(e) if self.defaults.fail_fast => return Err(e),
+                Err(e) => errors.push((i, e)),
+            }
+        }
+        drop(analyses);
+
+        timings.sort_by_key(|(i, _, _)| *i);
+        self.context.update_summary(|summary| {
+            summary.queries = timings
+                .iter()
+                .map(|(i, duration, cached)| QueryTiming {
+                    module: self.queries[*i].module.clone(),
+                    name: self.queries[*i].name.clone(),
+                    duration: *duration,
+                    cached: *cached,
+                })
+                .collect();
+        });
 
-        // Process queries in parallel batches of 40
-        let analyzed_queries: Vec<QueryDefinitionRuntime> = stream::iter(&self.queries)
-            .map(|query| async move {
-                println!("cargo:info=Analyzing query '{}'", query.name);
+        errors.sort_by_key(|(i, _)| *i);
+        if errors.len() == 1 {
+            return Err(errors.remove(0).1);
+        }
+        if !errors.is_empty() {
+            anyhow::bail!(
+                "{} queries failed analysis:\n{}",
+                errors.len(),
+                errors
+                    .iter()
+                    .map(|(i, e)| format!(
+                        "  - {}::{}: {}",
+                        self.queries[*i].module, self.queries[*i].name, e
+                    ))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            );
+        }
 
-                // Extract type information (input/output types, parsed SQL)
-                let type_info =
-                    extract_query_types(client, &query.sql, query.types.as_ref()).await?;
+        // The analyzed queries keep the order of the SQL files
+        results.sort_by_key(|(i, _)| *i);
+        let results: Vec<(QueryDefinitionRuntime, bool)> =
+            results.into_iter().map(|(_, analysis)| analysis).collect();
 
-                // Analyze query with EXPLAIN to detect mutation and optionally get performance data
-                // EXPLAIN fails on mutations (INSERT/UPDATE/DELETE), so we use that to detect them
-                // This also pre-computes EXPLAIN params during the analysis phase
-                let analysis_result = Self::analyze_query_with_explain(client, query).await?;
+        let stale_queries = results
+            .iter()
+            .filter(|(_, is_stale)| *is_stale)
+            .map(|(analyzed, _)| analyzed.definition.name.clone())
+            .collect();
+        let analyzed_queries = results.into_iter().map(|(analyzed, _)| analyzed).collect();
 
-                let analyzed_query = QueryDefinitionRuntime::new(
-                    query.clone(),
+        Ok((analyzed_queries, stale_queries))
+    }
+
+    /// Whether a query holds its analysis connection alone: in a transaction in transaction
+    /// pooling mode, and with time limits so that cancelling the statement of a query exceeding
+    /// its limit cannot cancel the statement of another query
+    fn holds_analysis_connections(&self) -> bool {
+        self.defaults.transaction_pooling
+            || self.defaults.query_analysis_timeout.is_some()
+            || self.defaults.analysis_budget.is_some()
+    }
+
+    /// Analyze a single query within its time limit
+    /// The analysis must hold the connection alone, see `holds_analysis_connections`
+    /// Returns the analysis and whether it is stale, i.e. restored from the snapshot after a timeout
+    async fn analyze_query_within_limits(
+        &self,
+        client: &tokio_postgres::Client,
+        query: &QueryDefinition,
+        deadline: Option<tokio::time::Instant>,
+        query_timeout: Option<Duration>,
+        snapshot: &AnalysisSnapshot,
+        analysis_cache: Option<&SharedAnalysisCache>,
+    ) -> Result<(QueryDefinitionRuntime, bool)> {
+        // The time limit is the per-query timeout capped by what's left of the budget
+        let remaining_budget = deadline
+            .map(|deadline| deadline.saturating_duration_since(tokio::time::Instant::now()));
+        let time_limit = match (query_timeout, remaining_budget) {
+            (Some(timeout), Some(remaining)) => Some(timeout.min(remaining)),
+            (timeout, remaining) => timeout.or(remaining),
+        };
+
+        let Some(time_limit) = time_limit else {
+            return Ok((
+                self.analyze_query_on(client, query, analysis_cache).await?,
+                false,
+            ));
+        };
+
+        match tokio::time::timeout(
+            time_limit,
+            self.analyze_query_on(client, query, analysis_cache),
+        )
+        .await
+        {
+            Ok(analyzed_query) => Ok((analyzed_query?, false)),
+            Err(_) => {
+                // Dropping the analysis leaves its statement running on the server, so it is
+                // cancelled, and the connection only reused once the server processed the
+                // cancellation; the ROLLBACK ends the transaction the statement may have aborted
+                client
+                    .cancel_token()
+                    .cancel_query(tokio_postgres::NoTls)
+                    .await
+                    .with_context(|| {
+                        format!(
+                            "Failed to cancel the analysis of query '{}' after it exceeded its time limit",
+                            query.name
+                        )
+                    })?;
+                client.batch_execute("ROLLBACK").await?;
+                let analyzed_query = snapshot.restore(query).ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Analysis of query '{}' exceeded the time limit of {:?} and no cached analysis is available",
+                        query.name,
+                        time_limit
+                    )
+                })?;
+                Ok((analyzed_query, true))
+            }
+        }
+    }
+
+    /// Analyze a single query with the analysis settings in effect on the connection
+    /// Behind a transaction pooler the query is analyzed in a transaction applying the settings,
+    /// otherwise the session settings are checked, as a pooler not configured as such drops them
+    async fn analyze_query_on(
+        &self,
+        client: &tokio_postgres::Client,
+        query: &QueryDefinition,
+        analysis_cache: Option<&SharedAnalysisCache>,
+    ) -> Result<QueryDefinitionRuntime> {
+        // CockroachDB connections have no planner settings which a pooler could drop
+        if !self.defaults.transaction_pooling && !query.dialect.disables_seqscan() {
+            return self.analyze_query(client, query, analysis_cache).await;
+        }
+        if !self.defaults.transaction_pooling {
+            let enable_seqscan = client
+                .simple_query("SHOW enable_seqscan")
+                .await?
+                .into_iter()
+                .find_map(|message| match message {
+                    tokio_postgres::SimpleQueryMessage::Row(row) => row.get(0).map(str::to_string),
+                    _ => None,
+                });
+            if enable_seqscan.as_deref() != Some("off") {
+                anyhow::bail!(
+                    "The analysis settings of the database connection were lost before analyzing query '{}', which happens behind a connection pooler in transaction pooling mode such as PgBouncer: analyze against a direct database connection, or set DefaultsConfig::transaction_pooling (--transaction-pooling)",
+                    query.name
+                );
+            }
+            return self
+                .analyze_query(client, query, analysis_cache)
+                .await
+                .map_err(with_pooling_hint);
+        }
+
+        // A transaction left open by an analysis which exceeded its time limit is rolled back first
+        client
+            .batch_execute(&format!(
+                "ROLLBACK; BEGIN; {}",
+                Self::analysis_settings_sql(query.dialect, &query.search_path)
+            ))
+            .await?;
+        let analyzed_query = self.analyze_query(client, query, analysis_cache).await;
+        client.batch_execute("ROLLBACK").await?;
+        analyzed_query
+    }
+
+    /// Statements applying the analysis settings to the current transaction, for connections
+    /// through a transaction pooler
+    fn analysis_settings_sql(dialect: Dialect, search_path: &[String]) -> String {
+        let search_path = if search_path.is_empty() {
+            "''".to_string()
+        } else {
+            search_path_list(search_path)
+        };
+        let enable_seqscan = if dialect.disables_seqscan() {
+            "SET LOCAL enable_seqscan = false; "
+        } else {
+            ""
+        };
+        format!("{}SET LOCAL search_path TO {}", enable_seqscan, search_path)
+    }
+
+    /// Analyze a single query against the database
+    /// The type information of the statement is read from and stored in the shared analysis cache
+    /// when it is enabled
+    async fn analyze_query(
+        &self,
+        client: &tokio_postgres::Client,
+        query: &QueryDefinition,
+        analysis_cache: Option<&SharedAnalysisCache>,
+    ) -> Result<QueryDefinitionRuntime> {
+        self.context
+            .logger()
+            .info(&format!("Analyzing query '{}'", query.name));
+
+        // Sharded queries are analyzed against the canonical shard table,
+        // the generated code keeps the placeholder and substitutes the shard at runtime
+        let definition = query;
+        let canonical_query = with_canonical_shard(query);
+        let query = &canonical_query;
+
+        // Scripts of several statements run with the simple query protocol cannot be prepared
+        // as a whole, so they are only checked statement by statement
+        if !query.prepare {
+            let statements = split_sql_statements(&query.sql);
+            if statements.len() > 1 {
+                let type_info = extract_script_types(client, &query.name, &statements).await?;
+                return Ok(QueryDefinitionRuntime::new(
+                    definition.clone(),
                     type_info,
-                    analysis_result.is_mutation,
-                    analysis_result.constraints,
-                    analysis_result.performance_analysis,
-                    analysis_result.explain_params,
+                    true,
+                    Vec::new(),
+                    None,
+                    Vec::new(),
+                ));
+            }
+        }
+
+        // The archive table is checked first, as preparing the statement fails with a less
+        // helpful error when its columns do not match
+        if let Some(archive_tables) = &query.archive_to {
+            verify_archive_table(client, &query.name, archive_tables).await?;
+        }
+
+        // Extract type information (input/output types, parsed SQL)
+        let cached_type_info = analysis_cache
+            .and_then(|cache| cache.get(&query.sql, query.types.as_ref(), &query.search_path));
+        let mut type_info = match cached_type_info {
+            Some(type_info) => type_info,
+            None => {
+                let type_info = extract_query_types(
+                    client,
+                    self.context.enum_types(),
+                    &query.sql,
+                    query.types.as_ref(),
+                )
+                .await?;
+                if let Some(cache) = analysis_cache {
+                    // A cache which cannot be written only costs the next build a prepare
+                    if let Err(e) = cache.put(
+                        &query.sql,
+                        query.types.as_ref(),
+                        &query.search_path,
+                        &type_info,
+                    ) {
+                        self.context.logger().warning(&format!(
+                            "Failed to write the shared analysis cache for query '{}': {:#}",
+                            query.name, e
+                        ));
+                    }
+                }
+                type_info
+            }
+        };
+        // Currency columns are resolved first, numeric_as leaves them as Money
+        if !query.currency_columns.is_empty() {
+            let param_names = parse_parameter_names_from_sql(&query.sql);
+            apply_currency_columns(
+                &query.name,
+                &query.currency_columns,
+                &param_names,
+                &mut type_info,
+            )?;
+        }
+        if let Some(numeric_as) = &query.numeric_as {
+            apply_numeric_as(&query.name, numeric_as, &mut type_info.output_types)?;
+        }
+        if !query.char_flags.is_empty() {
+            let param_names = parse_parameter_names_from_sql(&query.sql);
+            apply_char_flags(&query.name, &query.char_flags, &param_names, &mut type_info)?;
+        }
+        if !query.check_values.is_empty() {
+            let param_names = parse_parameter_names_from_sql(&query.sql);
+            apply_check_values(
+                client,
+                &query.name,
+                &query.check_values,
+                &param_names,
+                &mut type_info,
+            )
+            .await?;
+        }
+        if let Some(types) = &query.types {
+            let param_names = parse_parameter_names_from_sql(&query.sql);
+            apply_array_element_types(&query.name, types, &param_names, &mut type_info)?;
+        }
+
+        if query.cascade_preview {
+            type_info.cascade_preview =
+                Some(extract_cascade_preview(client, &query.name, &query.sql).await?);
+        }
+        if query.id_only {
+            type_info.id_only = Some(
+                extract_id_only_query(
+                    client,
+                    self.context.enum_types(),
+                    &query.name,
+                    &query.sql,
+                    &type_info.output_types,
+                    query.types.as_ref(),
+                    query.dialect,
+                )
+                .await?,
+            );
+        }
+
+        if let Some(retrying) = &query.retrying {
+            type_info.retry_lookups = extract_retry_lookups(
+                client,
+                self.context.enum_types(),
+                &query.name,
+                &query.sql,
+                retrying.constraints(),
+                query.types.as_ref(),
+                query.dialect,
+            )
+            .await?;
+        }
+
+        if query.bulk_update {
+            let (converted_sql, param_names, _label) = &query.sql_variants[0];
+            let statement = client.prepare(converted_sql).await?;
+            type_info.bulk_update = Some(
+                extract_bulk_update_query(
+                    client,
+                    &query.name,
+                    &query.sql,
+                    param_names,
+                    statement.params(),
+                )
+                .await?,
+            );
+        }
+
+        // Check configured analysis parameter values against the extracted types
+        validate_analysis_params(query, &type_info)?;
+        validate_type_mapping_columns(query, &type_info)?;
+        validate_flatten_columns(query, &type_info)?;
+        validate_sensitivity_fields(query, &type_info)?;
+
+        // Check the schema properties the query relies on
+        schema_assumptions::verify_schema_assumptions(client, query).await?;
+
+        // Every combination of conditional blocks which is analyzed must be valid and infer the
+        // same type for a parameter as the other variants, since one function binds them all
+        if query.sql_variants.len() > 1 {
+            let mut param_types: std::collections::HashMap<&str, (String, &str)> =
+                std::collections::HashMap::new();
+            for (converted_sql, param_names, label) in &query.sql_variants {
+                let statement = match client.prepare(converted_sql).await {
+                    Ok(statement) => statement,
+                    Err(e) => anyhow::bail!(
+                        "Query '{}' ({}) is not valid: {} ({})",
+                        query.name,
+                        label,
+                        e.as_db_error().map_or_else(
+                            || e.to_string(),
+                            |db_error| db_error.message().to_string()
+                        ),
+                        converted_sql
+                    ),
+                };
+                for (param_name, param_type) in param_names.iter().zip(statement.params()) {
+                    let param_name = param_name.trim_end_matches('?');
+                    match param_types.get(param_name) {
+                        Some((first_type, first_label)) if *first_type != param_type.name() => {
+                            anyhow::bail!(
+                                "Query '{}' infers type {} for parameter '{}' in {} but {} in {}, add an explicit cast",
+                                query.name,
+                                param_type.name(),
+                                param_name,
+                                label,
+                                first_type,
+                                first_label
+                            );
+                        }
+                        Some(_) => {}
+                        None => {
+                            param_types.insert(param_name, (param_type.name().to_string(), label));
+                        }
+                    }
+                }
+            }
+        }
+
+        // Branches of #[cfg(...)] blocks not selected for this build must stay valid too
+        for (converted_sql, label) in &query.cfg_branches {
+            if let Err(e) = client.prepare(converted_sql).await {
+                anyhow::bail!(
+                    "Query '{}' is not valid with {} ({}): {}",
+                    query.name,
+                    label,
+                    e.as_db_error()
+                        .map_or_else(|| e.to_string(), |db_error| db_error.message().to_string()),
+                    converted_sql
                 );
+            }
+        }
 
-                Ok::<_, anyhow::Error>(analyzed_query)
-            })
-            .buffered(40) // Process up to 40 queries in parallel while preserving order
-            .collect::<Vec<_>>()
-            .await
-            .into_iter()
-            .collect::<Result<Vec<_>>>()?;
+        // Analyze query with EXPLAIN to detect mutation and optionally get performance data
+        // EXPLAIN fails on mutations (INSERT/UPDATE/DELETE), so we use that to detect them
+        // This also pre-computes EXPLAIN params during the analysis phase
+        let analysis_result = self.analyze_query_with_explain(client, query).await?;
+
+        // The planner estimate generated functions compare the rows they return with
+        if query.cardinality_drift
+            && !analysis_result.is_mutation
+            && !type_info.output_types.is_empty()
+            && matches!(
+                query.expect,
+                ExpectedResult::Multiple | ExpectedResult::AtLeastOne
+            )
+        {
+            type_info.estimated_rows = self
+                .estimate_result_rows(client, query, &analysis_result.explain_params)
+                .await?;
+        }
 
-        Ok(analyzed_queries)
+        type_info.tables = self.plan_tables(client, definition, query).await;
+
+        // The estimates compared with the query's baseline, converted to suggested timeouts and
+        // checked for possible_one queries returning several rows without a LIMIT
+        if !analysis_result.is_mutation
+            && (query.baseline.is_some()
+                || self.record_plan_estimates
+                || self.defaults.suggest_timeouts.is_some()
+                || (query.expect == ExpectedResult::PossibleOne
+                    && !crate::types_extractor::has_top_level_limit(&query.sql)))
+        {
+            type_info.plan_estimates = self
+                .plan_estimates(client, query, &analysis_result.explain_params)
+                .await;
+        }
+
+        Ok(QueryDefinitionRuntime::new(
+            definition.clone(),
+            type_info,
+            analysis_result.is_mutation,
+            analysis_result.constraints,
+            analysis_result.performance_analysis,
+            analysis_result.explain_params,
+        ))
     }
 
     /// Analyze query with EXPLAIN to detect mutations and optionally collect performance data
@@ -345,6 +2441,7 @@ impl AutoModel {
     /// - For potential read-only queries: runs EXPLAIN to verify and optionally collect performance
     /// - If EXPLAIN fails on what looks like a SELECT: treat as mutation (edge case)
     async fn analyze_query_with_explain(
+        &self,
         client: &tokio_postgres::Client,
         query: &QueryDefinition,
     ) -> Result<QueryAnalysisResult> {
@@ -367,7 +2464,14 @@ impl AutoModel {
             let (converted_sql, _param_names, _label) = &query.sql_variants[0];
             let constraints = match client.prepare(converted_sql).await {
                 Ok(statement) => {
-                    match extract_constraints_from_statement(client, &statement, &query.sql).await {
+                    match extract_constraints_from_statement(
+                        client,
+                        &statement,
+                        &query.sql,
+                        query.dialect,
+                    )
+                    .await
+                    {
                         Ok(constraints) => constraints,
                         Err(_e) => {
                             // Silently skip constraint extraction errors
@@ -376,10 +2480,10 @@ impl AutoModel {
                     }
                 }
                 Err(e) => {
-                    println!(
-                        "cargo:info=Failed to prepare statement for constraint extraction for query '{}': {}",
+                    self.context.logger().info(&format!(
+                        "Failed to prepare statement for constraint extraction for query '{}': {}",
                         query.name, e
-                    );
+                    ));
                     Vec::new()
                 }
             };
@@ -401,12 +2505,16 @@ impl AutoModel {
                 match client.prepare(converted_sql).await {
                     Ok(statement) => {
                         let param_types = statement.params();
-                        match Self::prepare_explain_params_for_variant(
-                            client,
-                            converted_sql,
-                            param_types,
-                        )
-                        .await
+                        match self
+                            .prepare_explain_params_for_variant(
+                                client,
+                                converted_sql,
+                                param_types,
+                                param_names,
+                                &query.analysis_params,
+                                query.dialect,
+                            )
+                            .await
                         {
                             Ok(params) => explain_params.push(Some(params)),
                             Err(_) => explain_params.push(None),
@@ -420,10 +2528,12 @@ impl AutoModel {
         // Looks like a SELECT or read-only query - verify with EXPLAIN
         let explain_result = if query.ensure_indexes {
             // Run full performance analysis (which includes EXPLAIN)
-            Self::analyze_query_performance(client, query, &explain_params).await
+            self.analyze_query_performance(client, query, &explain_params)
+                .await
         } else {
             // Just run a simple EXPLAIN to verify it's read-only
-            Self::detect_mutation_via_explain(client, query, &explain_params).await
+            self.detect_mutation_via_explain(client, query, &explain_params)
+                .await
         };
 
         match explain_result {
@@ -457,6 +2567,7 @@ impl AutoModel {
     /// Detect if query is a mutation by attempting EXPLAIN (lightweight version)
     /// Returns PerformanceAnalysis with minimal data if EXPLAIN succeeds, otherwise returns error
     async fn detect_mutation_via_explain(
+        &self,
         client: &tokio_postgres::Client,
         query: &QueryDefinition,
         explain_params: &[Option<ExplainParams>],
@@ -473,9 +2584,12 @@ impl AutoModel {
                     match client.prepare(converted_sql).await {
                         Ok(statement) => {
                             let param_types = statement.params();
-                            let (dummy_params, _) =
-                                crate::types_extractor::create_dummy_params(client, param_types)
-                                    .await?;
+                            let (dummy_params, _) = crate::types_extractor::create_dummy_params(
+                                client,
+                                self.context.enum_types(),
+                                param_types,
+                            )
+                            .await?;
                             let param_refs: Vec<&(dyn tokio_postgres::types::ToSql + Sync)> =
                                 dummy_params.iter().map(|p| p.as_ref()).collect();
                             client.query(params.explain_sql.as_str(), &param_refs).await
@@ -490,8 +2604,12 @@ impl AutoModel {
                         Ok(statement) => {
                             let param_types = statement.params();
                             let (all_dummy_params, _) =
-                                crate::types_extractor::create_dummy_params(client, param_types)
-                                    .await?;
+                                crate::types_extractor::create_dummy_params(
+                                    client,
+                                    self.context.enum_types(),
+                                    param_types,
+                                )
+                                .await?;
 
                             // Filter to only non-special params
                             let mut non_special_dummy_params = Vec::new();
@@ -517,7 +2635,7 @@ impl AutoModel {
         } else {
             // No parameters, execute directly
             let (converted_sql, _, _) = &query.sql_variants[0];
-            let explain_sql = format!("EXPLAIN (FORMAT TEXT, ANALYZE false) {}", converted_sql);
+            let explain_sql = format!("{} {}", query.dialect.explain_prefix(), converted_sql);
             client.query(&explain_sql, &[]).await
         };
 
@@ -528,6 +2646,11 @@ impl AutoModel {
                     query_name: query.name.clone(),
                     has_sequential_scan: false,
                     sequential_scan_tables: Vec::new(),
+                    missing_index_tables: Vec::new(),
+                    subquery_rewrites: Vec::new(),
+                    index_suggestions: Vec::new(),
+                    ignored_indexes: Vec::new(),
+                    total_cost: None,
                     warnings: Vec::new(),
                     query_plan: None,
                 })
@@ -554,6 +2677,14 @@ impl AutoModel {
             let file_name = entry.file_name();
             let file_name_str = file_name.to_string_lossy();
 
+            // Part files of split modules which no longer exist
+            if entry.path().is_dir() {
+                if !current_modules.iter().any(|m| *m == file_name_str) {
+                    remove_stale_module_parts(&entry.path(), &[])?;
+                }
+                continue;
+            }
+
             // Skip mod.rs and non-.rs files
             if file_name_str == "mod.rs" || !file_name_str.ends_with(".rs") {
                 continue;
@@ -572,19 +2703,92 @@ impl AutoModel {
         Ok(())
     }
 
+    /// Write the TypeScript interfaces or proto messages of every module to
+    /// `{dir}/{module}.{extension}`
+    /// Generated files of modules without output (or which no longer exist) are removed
+    fn write_module_files(
+        analyzed_queries: &[QueryDefinitionRuntime],
+        modules: &[String],
+        dir: &Path,
+        extension: &str,
+        header: &str,
+        generate: fn(&[QueryDefinitionRuntime], &str) -> Option<String>,
+        edit_guard: &mut EditGuard,
+    ) -> Result<()> {
+        use std::fs;
+
+        fs::create_dir_all(dir)?;
+        let mut written_files = std::collections::HashSet::new();
+        for module in modules {
+            if let Some(code) = generate(analyzed_queries, module) {
+                let file_name = format!("{}.{}", module, extension);
+                edit_guard.write(&dir.join(&file_name), &code)?;
+                written_files.insert(file_name);
+            }
+        }
+
+        // Only files carrying the generated header are removed, other files are left alone
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if !file_name.ends_with(&format!(".{}", extension))
+                || written_files.contains(&file_name)
+            {
+                continue;
+            }
+            let is_generated = fs::read_to_string(entry.path())
+                .map(|content| content.starts_with(header))
+                .unwrap_or(false);
+            if is_generated {
+                fs::remove_file(entry.path())?;
+            }
+        }
+
+        Ok(())
+    }
+
     /// Prepare EXPLAIN parameters for a single query variant (done once during Phase 1)
     /// Returns ExplainParams to be stored and reused
+    /// Parameters with configured analysis values are inlined instead of dummy values
     async fn prepare_explain_params_for_variant(
+        &self,
         client: &tokio_postgres::Client,
         converted_sql: &str,
         param_types: &[tokio_postgres::types::Type],
+        param_names: &[String],
+        analysis_params: &std::collections::HashMap<String, serde_yaml::Value>,
+        dialect: Dialect,
     ) -> Result<ExplainParams> {
-        let (_dummy_params, special_params) =
-            crate::types_extractor::create_dummy_params(client, param_types).await?;
+        let (_dummy_params, mut special_params) = crate::types_extractor::create_dummy_params(
+            client,
+            self.context.enum_types(),
+            param_types,
+        )
+        .await?;
+
+        // Inline configured analysis values, keeping the type name resolved for special params
+        for (param_idx, param_type) in param_types.iter().enumerate() {
+            let Some(param_name) = param_names.get(param_idx) else {
+                continue;
+            };
+            let Some(value) = analysis_params.get(param_name.trim_end_matches('?')) else {
+                continue;
+            };
+            let literal = analysis_param_literal(value).replace('\'', "''");
+            if let Some(special) = special_params
+                .iter_mut()
+                .find(|(idx, _, _)| *idx == param_idx)
+            {
+                special.2 = literal;
+            } else {
+                special_params.push((param_idx, param_type.name().to_string(), literal));
+            }
+        }
+        special_params.sort_by_key(|(idx, _, _)| *idx);
 
         // Build the EXPLAIN query with special param replacements
         let explain_sql = if special_params.is_empty() {
-            format!("EXPLAIN (FORMAT TEXT, ANALYZE false) {}", converted_sql)
+            format!("{} {}", dialect.explain_prefix(), converted_sql)
         } else {
             // Replace special parameters with casted values and renumber remaining params
             let mut modified_sql = converted_sql.to_string();
@@ -621,34 +2825,197 @@ impl AutoModel {
                 }
             }
 
-            // Replace temporary placeholders with final ones
-            for (new_num, _) in param_mapping.iter().enumerate() {
-                let temp_placeholder = format!("__PARAM_{}__", new_num + 1);
-                let final_placeholder = format!("${}", new_num + 1);
-                modified_sql = modified_sql.replace(&temp_placeholder, &final_placeholder);
+            // Replace temporary placeholders with final ones
+            for (new_num, _) in param_mapping.iter().enumerate() {
+                let temp_placeholder = format!("__PARAM_{}__", new_num + 1);
+                let final_placeholder = format!("${}", new_num + 1);
+                modified_sql = modified_sql.replace(&temp_placeholder, &final_placeholder);
+            }
+
+            format!("{} {}", dialect.explain_prefix(), modified_sql)
+        };
+
+        Ok(ExplainParams {
+            explain_sql,
+            special_params: special_params
+                .into_iter()
+                .map(|(param_index, _, _)| param_index)
+                .collect(),
+        })
+    }
+    /// Planner estimate of the rows returned by the base variant of a read-only query
+    /// Rounded to its order of magnitude, so that the generated code does not change with every
+    /// statistics update
+    async fn estimate_result_rows(
+        &self,
+        client: &tokio_postgres::Client,
+        query: &QueryDefinition,
+        explain_params: &[Option<ExplainParams>],
+    ) -> Result<Option<f64>> {
+        let (converted_sql, param_names, _label) = &query.sql_variants[0];
+        let (_, _, _, query_plan) = self
+            .analyze_single_query(
+                client,
+                converted_sql,
+                param_names,
+                &query.name,
+                explain_params.first().and_then(Option::as_ref),
+                query.dialect,
+            )
+            .await?;
+        Ok(plan_estimated_rows(&query_plan).map(|rows| 10f64.powf(rows.max(1.0).log10().round())))
+    }
+
+    /// Estimated cost of the most expensive variant and rows of the base variant of a read-only
+    /// query, None when no variant could be explained
+    async fn plan_estimates(
+        &self,
+        client: &tokio_postgres::Client,
+        query: &QueryDefinition,
+        explain_params: &[Option<ExplainParams>],
+    ) -> Option<PlanEstimates> {
+        let mut estimates: Option<PlanEstimates> = None;
+        for (i, (converted_sql, param_names, _label)) in query.sql_variants.iter().enumerate() {
+            let Ok((_, _, _, query_plan)) = self
+                .analyze_single_query(
+                    client,
+                    converted_sql,
+                    param_names,
+                    &query.name,
+                    explain_params.get(i).and_then(Option::as_ref),
+                    query.dialect,
+                )
+                .await
+            else {
+                continue;
+            };
+            let estimates = estimates.get_or_insert_with(PlanEstimates::default);
+            if let Some(cost) = query_plan.lines().next().and_then(plan_total_cost) {
+                estimates.total_cost =
+                    Some(estimates.total_cost.map_or(cost, |max: f64| max.max(cost)));
+            }
+            if i == 0 {
+                estimates.rows = plan_estimated_rows(&query_plan);
+            }
+        }
+        estimates
+    }
+
+    /// Tables read or modified by the query, collected from the plans of all its variants
+    /// Tables of sharded queries keep their `${shard}` placeholder, variants whose plan fails
+    /// contribute no tables
+    async fn plan_tables(
+        &self,
+        client: &tokio_postgres::Client,
+        definition: &QueryDefinition,
+        query: &QueryDefinition,
+    ) -> Vec<String> {
+        let mut tables = std::collections::BTreeSet::new();
+        for (converted_sql, param_names, _label) in &query.sql_variants {
+            let Ok(statement) = client.prepare(converted_sql).await else {
+                continue;
+            };
+            let Ok(params) = self
+                .prepare_explain_params_for_variant(
+                    client,
+                    converted_sql,
+                    statement.params(),
+                    param_names,
+                    &query.analysis_params,
+                    query.dialect,
+                )
+                .await
+            else {
+                continue;
+            };
+            let Ok((dummy_params, _)) = crate::types_extractor::create_dummy_params(
+                client,
+                self.context.enum_types(),
+                statement.params(),
+            )
+            .await
+            else {
+                continue;
+            };
+            let param_refs: Vec<&(dyn tokio_postgres::types::ToSql + Sync)> = dummy_params
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !params.special_params.contains(i))
+                .map(|(_, param)| param.as_ref())
+                .collect();
+
+            match query.dialect {
+                Dialect::Postgres => {
+                    let explain_sql = params.explain_sql.replacen(
+                        query.dialect.explain_prefix(),
+                        "EXPLAIN (FORMAT JSON, VERBOSE)",
+                        1,
+                    );
+                    if let Ok(row) = client.query_one(explain_sql.as_str(), &param_refs).await {
+                        if let Ok(plan) = row.try_get::<_, serde_json::Value>(0) {
+                            collect_plan_relations(&plan, &mut tables);
+                        }
+                    }
+                }
+                Dialect::Cockroach => {
+                    if let Ok(rows) = client.query(params.explain_sql.as_str(), &param_refs).await {
+                        for row in rows {
+                            let line: String = row.get(0);
+                            tables.extend(cockroach_plan_table(&line));
+                        }
+                    }
+                }
             }
+        }
 
-            format!("EXPLAIN (FORMAT TEXT, ANALYZE false) {}", modified_sql)
-        };
-
-        Ok(ExplainParams {
-            explain_sql,
-            special_params: special_params
-                .into_iter()
-                .map(|(param_index, _, _)| param_index)
+        // The tables of sharded queries were planned for the canonical shard
+        let shard_tables: Vec<(String, String)> = match &query.canonical_shard {
+            Some(shard) if query.shard_by.is_some() => sharded_table_pattern()
+                .find_iter(&definition.sql)
+                .map(|table| {
+                    (
+                        table.as_str().replace(SHARD_PLACEHOLDER, shard),
+                        table.as_str().to_string(),
+                    )
+                })
                 .collect(),
-        })
+            _ => Vec::new(),
+        };
+        tables
+            .into_iter()
+            .map(|table| {
+                shard_tables
+                    .iter()
+                    .find(|(canonical, _)| *canonical == table)
+                    .map_or(table, |(_, placeholder)| placeholder.clone())
+            })
+            .collect::<std::collections::BTreeSet<_>>()
+            .into_iter()
+            .collect()
     }
+
     /// Analyze query performance using EXPLAIN (full analysis with query plan)
     async fn analyze_query_performance(
+        &self,
         client: &tokio_postgres::Client,
         query: &QueryDefinition,
         explain_params: &[Option<ExplainParams>],
     ) -> Result<PerformanceAnalysis> {
         let mut has_sequential_scan = false;
         let mut sequential_scan_tables = Vec::new();
+        let mut missing_index_tables = Vec::new();
+        let mut subquery_rewrites = Vec::new();
+        let mut total_cost: Option<f64> = None;
         let mut warnings = Vec::new();
         let mut full_query_plan = String::new();
+        let has_not_in_subquery = not_in_subquery_pattern().is_match(&query.sql);
+        let nullable_not_in_columns = Self::nullable_not_in_columns(client, &query.sql).await;
+        let mut index_suggestions = Vec::new();
+        let trigram_support = if query.sql.to_uppercase().contains("LIKE") {
+            Self::trigram_support(client).await
+        } else {
+            TrigramSupport::Unavailable
+        };
 
         // Analyze each variant from pre-processed sql_variants
         for (i, (converted_sql, param_names, variant_label)) in
@@ -656,13 +3023,14 @@ impl AutoModel {
         {
             let variant_name = format!("{} ({})", query.name, variant_label);
 
-            let (variant_has_seq_scan, variant_tables, variant_warnings, variant_plan) =
-                Self::analyze_single_query(
+            let (variant_has_seq_scan, variant_tables, variant_warnings, variant_plan) = self
+                .analyze_single_query(
                     client,
                     converted_sql,
                     param_names,
                     &variant_name,
                     explain_params.get(i).and_then(|p| p.as_ref()),
+                    query.dialect,
                 )
                 .await?;
 
@@ -670,7 +3038,54 @@ impl AutoModel {
                 has_sequential_scan = true;
                 sequential_scan_tables.extend(variant_tables);
             }
-            warnings.extend(variant_warnings);
+            // Utility statements of queries with prepare: false, e.g. SHOW, cannot be explained
+            warnings.extend(
+                variant_warnings.into_iter().filter(|warning| {
+                    query.prepare || warning.code != DiagnosticCode::ExplainFailed
+                }),
+            );
+
+            let plan_lines: Vec<&str> = variant_plan.lines().collect();
+            let filtered_tables = match query.dialect {
+                Dialect::Postgres => filtered_sequential_scan_tables(&plan_lines),
+                Dialect::Cockroach => cockroach_full_scans(&plan_lines)
+                    .into_iter()
+                    .filter_map(|(table, filtered)| filtered.then_some(table))
+                    .collect(),
+            };
+            for table in filtered_tables {
+                if !missing_index_tables.contains(&table) {
+                    missing_index_tables.push(table);
+                }
+            }
+            // Variants usually share their subqueries, so each is reported once
+            for (evidence, warning) in subquery_rewrite_warnings(
+                &query.name,
+                &plan_lines,
+                has_not_in_subquery,
+                &nullable_not_in_columns,
+            ) {
+                if !subquery_rewrites.contains(&evidence) {
+                    subquery_rewrites.push(evidence);
+                    warnings.push(Diagnostic::new(DiagnosticCode::SubqueryRewrite, warning));
+                }
+            }
+            for (statements, warning) in
+                like_index_advice(&query.name, &plan_lines, trigram_support)
+            {
+                let warning = Diagnostic::new(DiagnosticCode::LikePattern, warning);
+                if !warnings.contains(&warning) {
+                    warnings.push(warning);
+                }
+                for statement in statements {
+                    if !index_suggestions.contains(&statement) {
+                        index_suggestions.push(statement);
+                    }
+                }
+            }
+            if let Some(cost) = plan_lines.first().and_then(|line| plan_total_cost(line)) {
+                total_cost = Some(total_cost.map_or(cost, |max: f64| max.max(cost)));
+            }
 
             // Append variant plan to full plan
             if i > 0 {
@@ -682,10 +3097,47 @@ impl AutoModel {
             full_query_plan.push_str(&variant_plan);
         }
 
+        // Partial and expression indexes of the scanned tables are reported with the reason the
+        // plan ignored them, the warnings of a table are dropped when such an index covers the
+        // query, as conditions on its parameters only fail to match the dummy values of EXPLAIN
+        let mut ignored_indexes = Vec::new();
+        if query.dialect == Dialect::Postgres && !sequential_scan_tables.is_empty() {
+            let indexes = Self::candidate_indexes(client, &sequential_scan_tables).await;
+            let base_sql = query
+                .sql_variants
+                .first()
+                .map_or(query.sql.as_str(), |(sql, _, _)| sql.as_str());
+            for (table, covers, reason) in ignored_index_reasons(base_sql, &indexes) {
+                if covers {
+                    let on_table = format!("table '{}'", table);
+                    sequential_scan_tables.retain(|scanned| *scanned != table);
+                    missing_index_tables.retain(|scanned| *scanned != table);
+                    warnings.retain(|warning| {
+                        !matches!(
+                            warning.code,
+                            DiagnosticCode::SeqScan | DiagnosticCode::UnindexedFilter
+                        ) || !warning.message.contains(&on_table)
+                    });
+                } else {
+                    warnings.push(Diagnostic::new(
+                        DiagnosticCode::SeqScan,
+                        format!("Query '{}' does not use {}", query.name, reason),
+                    ));
+                }
+                ignored_indexes.push(reason);
+            }
+            has_sequential_scan = !sequential_scan_tables.is_empty();
+        }
+
         Ok(PerformanceAnalysis {
             query_name: query.name.clone(),
             has_sequential_scan,
             sequential_scan_tables,
+            missing_index_tables,
+            subquery_rewrites,
+            index_suggestions,
+            ignored_indexes,
+            total_cost,
             warnings,
             query_plan: if full_query_plan.is_empty() {
                 None
@@ -695,17 +3147,116 @@ impl AutoModel {
         })
     }
 
+    /// Nullable columns selected by `NOT IN (SELECT column FROM table ...)` subqueries, as
+    /// "table.column"; NOT IN returns no rows as soon as such a subquery yields a NULL
+    /// Columns which cannot be resolved are left out, as this only refines the warnings
+    async fn nullable_not_in_columns(client: &tokio_postgres::Client, sql: &str) -> Vec<String> {
+        let mut columns = Vec::new();
+        for captures in not_in_subquery_pattern().captures_iter(sql) {
+            let (Some(column), Some(table)) = (captures.get(1), captures.get(2)) else {
+                continue;
+            };
+            let (column, table) = (column.as_str(), table.as_str());
+            let nullable = client
+                .query_opt(
+                    "SELECT NOT a.attnotnull FROM pg_attribute a
+                     WHERE a.attrelid = to_regclass($1) AND a.attname = $2 AND NOT a.attisdropped",
+                    &[&table, &column],
+                )
+                .await;
+            if let Ok(Some(row)) = nullable {
+                let name = format!("{}.{}", table, column);
+                if row.get::<_, bool>(0) && !columns.contains(&name) {
+                    columns.push(name);
+                }
+            }
+        }
+        columns
+    }
+
+    /// Partial and expression indexes of the given tables, named as in query plans
+    /// Lookup failures are treated as no such index, as this only refines the warnings
+    async fn candidate_indexes(
+        client: &tokio_postgres::Client,
+        tables: &[String],
+    ) -> Vec<CandidateIndex> {
+        let rows = client
+            .query(
+                "SELECT c.relname::text, ic.relname::text, pg_get_expr(i.indpred, i.indrelid), \
+                        pg_get_indexdef(i.indexrelid), \
+                        ARRAY(SELECT a.attname::text FROM pg_catalog.pg_attribute a \
+                              WHERE a.attrelid = i.indrelid AND a.attnum > 0 AND NOT a.attisdropped), \
+                        ARRAY(SELECT a.attname::text FROM pg_catalog.pg_attribute a \
+                              WHERE a.attrelid = i.indrelid AND a.attnum = ANY(i.indkey)) \
+                 FROM pg_catalog.pg_index i \
+                 JOIN pg_catalog.pg_class c ON c.oid = i.indrelid \
+                 JOIN pg_catalog.pg_class ic ON ic.oid = i.indexrelid \
+                 JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace \
+                 WHERE c.relname = ANY($1) AND (i.indpred IS NOT NULL OR i.indexprs IS NOT NULL) \
+                   AND n.nspname NOT IN ('pg_catalog', 'information_schema') \
+                 ORDER BY c.relname, ic.relname",
+                &[&tables],
+            )
+            .await;
+        let Ok(rows) = rows else {
+            return Vec::new();
+        };
+        rows.iter()
+            .map(|row| {
+                let predicate: Option<String> = row.get(2);
+                let expressions = index_expressions(row.get(3));
+                let table_columns: Vec<String> = row.get(4);
+                let mut columns: Vec<String> = row.get(5);
+                // Columns of expressions and predicates are only found in their text
+                let mut referenced = condition_tokens(predicate.as_deref().unwrap_or(""));
+                for expression in &expressions {
+                    referenced.extend(condition_tokens(expression));
+                }
+                for column in table_columns {
+                    if referenced.contains(&column) && !columns.contains(&column) {
+                        columns.push(column);
+                    }
+                }
+                CandidateIndex {
+                    table: row.get(0),
+                    name: row.get(1),
+                    predicate,
+                    expressions,
+                    columns,
+                }
+            })
+            .collect()
+    }
+
+    /// Whether trigram indexes can be created on the analysis server
+    /// Lookup failures are treated as the extension being available but not installed
+    async fn trigram_support(client: &tokio_postgres::Client) -> TrigramSupport {
+        let installed = client
+            .query_opt(
+                "SELECT installed_version IS NOT NULL FROM pg_available_extensions WHERE name = 'pg_trgm'",
+                &[],
+            )
+            .await;
+        match installed {
+            Ok(Some(row)) if row.get::<_, bool>(0) => TrigramSupport::Installed,
+            Ok(None) => TrigramSupport::Unavailable,
+            _ => TrigramSupport::Available,
+        }
+    }
+
     /// Analyze a single SQL query variant
     /// sql: already converted to positional parameters ($1, $2, etc.)
     /// param_names: list of parameter names in order
     /// explain_params: pre-computed EXPLAIN SQL and special params
     async fn analyze_single_query(
+        &self,
         client: &tokio_postgres::Client,
         sql: &str,
         param_names: &[String],
         query_name: &str,
         explain_params: Option<&ExplainParams>,
-    ) -> Result<(bool, Vec<String>, Vec<String>, String)> {
+        dialect: Dialect,
+    ) -> Result<(bool, Vec<String>, Vec<Diagnostic>, String)> {
         let mut has_sequential_scan = false;
         let mut sequential_scan_tables = Vec::new();
         let mut warnings = Vec::new();
@@ -719,9 +3270,12 @@ impl AutoModel {
                     match client.prepare(sql).await {
                         Ok(statement) => {
                             let param_types = statement.params();
-                            let (dummy_params, _) =
-                                crate::types_extractor::create_dummy_params(client, param_types)
-                                    .await?;
+                            let (dummy_params, _) = crate::types_extractor::create_dummy_params(
+                                client,
+                                self.context.enum_types(),
+                                param_types,
+                            )
+                            .await?;
                             let param_refs: Vec<&(dyn tokio_postgres::types::ToSql + Sync)> =
                                 dummy_params.iter().map(|p| p.as_ref()).collect();
                             client.query(params.explain_sql.as_str(), &param_refs).await
@@ -739,8 +3293,12 @@ impl AutoModel {
                         Ok(statement) => {
                             let param_types = statement.params();
                             let (all_dummy_params, _) =
-                                crate::types_extractor::create_dummy_params(client, param_types)
-                                    .await?;
+                                crate::types_extractor::create_dummy_params(
+                                    client,
+                                    self.context.enum_types(),
+                                    param_types,
+                                )
+                                .await?;
 
                             // Filter to only non-special params
                             let mut non_special_dummy_params = Vec::new();
@@ -768,13 +3326,16 @@ impl AutoModel {
                     Ok(statement) => {
                         let param_types = statement.params();
                         let (dummy_params, special_params) =
-                            crate::types_extractor::create_dummy_params(client, param_types)
-                                .await?;
+                            crate::types_extractor::create_dummy_params(
+                                client,
+                                self.context.enum_types(),
+                                param_types,
+                            )
+                            .await?;
 
                         if special_params.is_empty() {
                             // No special params, use dummy params directly
-                            let explain_sql =
-                                format!("EXPLAIN (FORMAT TEXT, ANALYZE false) {}", sql);
+                            let explain_sql = format!("{} {}", dialect.explain_prefix(), sql);
                             let param_refs: Vec<&(dyn tokio_postgres::types::ToSql + Sync)> =
                                 dummy_params.iter().map(|p| p.as_ref()).collect();
                             client.query(&explain_sql, &param_refs).await
@@ -792,7 +3353,7 @@ impl AutoModel {
                                     modified_sql.replace(&param_placeholder, &casted_value);
                             }
                             let explain_sql =
-                                format!("EXPLAIN (FORMAT TEXT, ANALYZE false) {}", modified_sql);
+                                format!("{} {}", dialect.explain_prefix(), modified_sql);
                             client.query(&explain_sql, &[]).await
                         }
                     }
@@ -800,7 +3361,10 @@ impl AutoModel {
                         return Ok((
                             false,
                             Vec::new(),
-                            vec![format!("Query '{}' had EXPLAIN failed", query_name)],
+                            vec![Diagnostic::new(
+                                DiagnosticCode::ExplainFailed,
+                                format!("Query '{}' had EXPLAIN failed", query_name),
+                            )],
                             String::new(),
                         ));
                     }
@@ -808,15 +3372,41 @@ impl AutoModel {
             }
         } else {
             // No parameters, execute directly
-            let explain_sql = format!("EXPLAIN (FORMAT TEXT, ANALYZE false) {}", sql);
+            let explain_sql = format!("{} {}", dialect.explain_prefix(), sql);
             client.query(&explain_sql, &[]).await
         };
 
         let Ok(rows) = query_result else {
-            let warning = format!("Query '{}' had EXPLAIN failed", query_name);
+            let warning = Diagnostic::new(
+                DiagnosticCode::ExplainFailed,
+                format!("Query '{}' had EXPLAIN failed", query_name),
+            );
             return Ok((false, Vec::new(), vec![warning], String::new()));
         };
 
+        // CockroachDB returns EXPLAIN as a tree of nodes, where full scans are sequential scans
+        if dialect == Dialect::Cockroach {
+            let plan: Vec<String> = rows.iter().map(|row| row.get(0)).collect();
+            let plan_lines: Vec<&str> = plan.iter().map(String::as_str).collect();
+            for (table_name, _) in cockroach_full_scans(&plan_lines) {
+                has_sequential_scan = true;
+                warnings.push(Diagnostic::new(
+                    DiagnosticCode::SeqScan,
+                    format!(
+                        "Query '{}' performs sequential scan on table '{}'",
+                        query_name, table_name
+                    ),
+                ));
+                sequential_scan_tables.push(table_name);
+            }
+            return Ok((
+                has_sequential_scan,
+                sequential_scan_tables,
+                warnings,
+                plan.join("\n"),
+            ));
+        }
+
         // PostgreSQL returns EXPLAIN as text lines
         for row in rows {
             let plan_line: String = row.get(0);
@@ -834,9 +3424,12 @@ impl AutoModel {
 
                     sequential_scan_tables.push(table_name.to_string());
 
-                    let warning = format!(
-                        "Query '{}' performs sequential scan on table '{}'",
-                        query_name, table_name
+                    let warning = Diagnostic::new(
+                        DiagnosticCode::SeqScan,
+                        format!(
+                            "Query '{}' performs sequential scan on table '{}'",
+                            query_name, table_name
+                        ),
                     );
                     warnings.push(warning);
                 }
@@ -848,14 +3441,21 @@ impl AutoModel {
             } else if plan_line.contains("Filter:") || plan_line.contains("Sort") {
                 // These operations on large tables might benefit from indexes
                 // But only report if we haven't already flagged a sequential scan
-                if !has_sequential_scan && plan_line.contains("Filter:") {
+                // LIKE and ILIKE filters get specific advice from like_index_advice
+                if !has_sequential_scan
+                    && plan_line.contains("Filter:")
+                    && !like_filter_pattern().is_match(&plan_line)
+                {
                     if let Some(on_pos) = plan_line.find(" on ") {
                         let after_on = &plan_line[on_pos + 4..];
                         let table_name = after_on.split_whitespace().next().unwrap_or("unknown");
 
-                        let warning = format!(
-                            "Query '{}' uses filtering on table '{}' - verify appropriate indexes exist",
-                            query_name, table_name
+                        let warning = Diagnostic::new(
+                            DiagnosticCode::UnindexedFilter,
+                            format!(
+                                "Query '{}' uses filtering on table '{}' - verify appropriate indexes exist",
+                                query_name, table_name
+                            ),
                         );
                         warnings.push(warning);
                     }
@@ -872,3 +3472,770 @@ impl AutoModel {
         ))
     }
 }
+
+/// Tables which are sequentially scanned with a Filter applied to their rows
+/// Such scans could usually be served by an index on the filtered columns
+fn filtered_sequential_scan_tables(plan_lines: &[&str]) -> Vec<String> {
+    let indent_of = |line: &str| line.len() - line.trim_start().len();
+    let mut tables = Vec::new();
+
+    for (i, line) in plan_lines.iter().enumerate() {
+        let Some(on_pos) = line.find("Seq Scan on ") else {
+            continue;
+        };
+        let table_name = line[on_pos + "Seq Scan on ".len()..]
+            .split_whitespace()
+            .next()
+            .unwrap_or("unknown");
+
+        // Node details are indented deeper than the node line until the next child node
+        let node_indent = indent_of(line);
+        let has_filter = plan_lines[i + 1..]
+            .iter()
+            .take_while(|detail| {
+                indent_of(detail) > node_indent && !detail.trim_start().starts_with("->")
+            })
+            .any(|detail| detail.trim_start().starts_with("Filter:"));
+
+        if has_filter && !tables.iter().any(|t| t == table_name) {
+            tables.push(table_name.to_string());
+        }
+    }
+
+    tables
+}
+
+/// Tables fully scanned in a CockroachDB plan, with whether the rows of the scan are filtered
+/// Scans are `• scan` nodes with `spans: FULL SCAN`, filtered by a `• filter` parent node
+fn cockroach_full_scans(plan_lines: &[&str]) -> Vec<(String, bool)> {
+    // Nodes are nested by the column of their bullet, tree characters being one char each
+    let node_of = |line: &str| {
+        let column = line.chars().position(|ch| ch == '•')?;
+        let name = line.split('•').nth(1)?.trim().to_string();
+        Some((column, name))
+    };
+    let mut scans: Vec<(String, bool)> = Vec::new();
+
+    for (i, line) in plan_lines.iter().enumerate() {
+        let Some((column, name)) = node_of(line) else {
+            continue;
+        };
+        if name != "scan" {
+            continue;
+        }
+        let details: Vec<&str> = plan_lines[i + 1..]
+            .iter()
+            .take_while(|detail| node_of(detail).is_none())
+            .map(|detail| detail.trim_start_matches(|ch: char| ch.is_whitespace() || ch == '│'))
+            .collect();
+        let is_full_scan = details
+            .iter()
+            .any(|detail| detail.starts_with("spans: FULL SCAN"));
+        let Some(table) = details
+            .iter()
+            .find_map(|detail| detail.strip_prefix("table: "))
+            .map(|table| table.split('@').next().unwrap_or(table).to_string())
+        else {
+            continue;
+        };
+        if !is_full_scan {
+            continue;
+        }
+
+        let parent = plan_lines[..i]
+            .iter()
+            .rev()
+            .filter_map(|line| node_of(line))
+            .find(|(parent_column, _)| *parent_column < column);
+        let filtered = matches!(parent, Some((_, name)) if name == "filter");
+        match scans.iter_mut().find(|(scanned, _)| *scanned == table) {
+            Some(scan) => scan.1 |= filtered,
+            None => scans.push((table, filtered)),
+        }
+    }
+
+    scans
+}
+
+/// Pattern matching `NOT IN (SELECT column FROM table` subqueries, capturing the column and
+/// table when the subquery selects a plain column
+fn not_in_subquery_pattern() -> &'static regex::Regex {
+    static PATTERN: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+    PATTERN.get_or_init(|| {
+        regex::Regex::new(
+            r"(?i)\bNOT\s+IN\s*\(\s*SELECT\b(?:\s+(?:DISTINCT\s+)?(?:\w+\.)?(\w+)\s+FROM\s+((?:\w+\.)?\w+))?",
+        )
+        .unwrap()
+    })
+}
+
+/// Subqueries of a plan which could be rewritten as joins, as (evidence, warning) pairs
+/// NOT IN subqueries are filters PostgreSQL cannot turn into anti-joins, and subplans which are
+/// not hashed are correlated subqueries run again for every row of their parent node
+fn subquery_rewrite_warnings(
+    query_name: &str,
+    plan_lines: &[&str],
+    has_not_in_subquery: bool,
+    nullable_not_in_columns: &[String],
+) -> Vec<(String, String)> {
+    let indent_of = |line: &str| line.len() - line.trim_start().len();
+    let mut rewrites = Vec::new();
+
+    for (i, line) in plan_lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+
+        // PostgreSQL 17 and later show the filter as `NOT (ANY (x = (hashed SubPlan 1).col1))`
+        if has_not_in_subquery
+            && trimmed.starts_with("Filter:")
+            && trimmed.contains("NOT (")
+            && trimmed.contains("SubPlan ")
+        {
+            let evidence = format!("NOT IN subquery `{}`", trimmed);
+            let mut warning = format!(
+                "Query '{}' uses NOT IN with a subquery, which PostgreSQL cannot run as an anti-join (`{}`); rewrite it as NOT EXISTS",
+                query_name, trimmed
+            );
+            if !nullable_not_in_columns.is_empty() {
+                warning.push_str(&format!(
+                    ". {} can be NULL, and NOT IN returns no rows as soon as its subquery yields a NULL",
+                    nullable_not_in_columns.join(", ")
+                ));
+            }
+            rewrites.push((evidence, warning));
+            continue;
+        }
+
+        let Some(subplan) = trimmed
+            .strip_prefix("SubPlan ")
+            .filter(|number| number.chars().all(|c| c.is_ascii_digit()))
+        else {
+            continue;
+        };
+        let hashed = format!("hashed SubPlan {}", subplan);
+        if plan_lines.iter().any(|other| other.contains(&hashed)) {
+            continue;
+        }
+
+        // The parent node is the closest less indented line above the subplan
+        let subplan_indent = indent_of(line);
+        let parent = plan_lines[..i]
+            .iter()
+            .rev()
+            .find(|other| indent_of(other) < subplan_indent)
+            .map_or("", |other| other.trim_start());
+        let parent = parent.trim_start_matches("->").trim_start();
+        let parent = parent.split("  (cost=").next().unwrap_or(parent);
+
+        let evidence = format!("correlated SubPlan {} under `{}`", subplan, parent);
+        let warning = format!(
+            "Query '{}' runs correlated subquery SubPlan {} once per row of `{}`; rewrite it as a JOIN, a LATERAL join or EXISTS",
+            query_name, subplan, parent
+        );
+        rewrites.push((evidence, warning));
+    }
+
+    rewrites
+}
+
+/// Partial or expression index of a sequentially scanned table
+#[derive(Debug, Clone)]
+struct CandidateIndex {
+    table: String,
+    name: String,
+    /// Predicate of partial indexes, e.g. `((state)::text = 'open'::text)`
+    predicate: Option<String>,
+    /// Indexed expressions, e.g. `lower(email)`, empty when only columns are indexed
+    expressions: Vec<String>,
+    /// Columns the index keys, expressions and predicate refer to
+    columns: Vec<String>,
+}
+
+/// Tokens of SQL conditions compared by `ignored_index_reasons`, lowercase without casts,
+/// table qualifiers, quotes and parentheses, so that `((state)::text = 'open'::text)` from
+/// the catalog and `t.state = 'open'` from the query give the same tokens
+fn condition_tokens(sql: &str) -> Vec<String> {
+    static CAST: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+    let cast = CAST.get_or_init(|| {
+        regex::Regex::new(
+            r"::\s*[a-z_][a-z0-9_]*(\s+(varying|precision|with time zone|without time zone))?(\[\])?",
+        )
+        .unwrap()
+    });
+    static QUALIFIER: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+    let qualifier =
+        QUALIFIER.get_or_init(|| regex::Regex::new(r"\b[a-z_][a-z0-9_]*\.([a-z_])").unwrap());
+    static TOKEN: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+    let token = TOKEN.get_or_init(|| {
+        regex::Regex::new(
+            r"'(?:[^']|'')*'|\$\d+|[a-z_][a-z0-9_]*|\d+(?:\.\d+)?|<>|!=|<=|>=|~~\*?|[=<>+\-*/%|]",
+        )
+        .unwrap()
+    });
+
+    let sql = sql.to_lowercase().replace('"', "");
+    let sql = cast.replace_all(&sql, "");
+    let sql = qualifier.replace_all(&sql, "$1");
+    token
+        .find_iter(&sql)
+        .map(|found| match found.as_str() {
+            "like" => "~~".to_string(),
+            "ilike" => "~~*".to_string(),
+            "!=" => "<>".to_string(),
+            other => other.to_string(),
+        })
+        .collect()
+}
+
+/// Whether `needle` appears as a contiguous run of `haystack`
+fn contains_tokens(haystack: &[String], needle: &[String]) -> bool {
+    !needle.is_empty()
+        && haystack
+            .windows(needle.len())
+            .any(|window| window == needle)
+}
+
+/// Expressions of an index from `pg_get_indexdef`, e.g. `lower(email)` from
+/// `CREATE INDEX ... USING btree (lower(email))`, leaving out plain columns
+fn index_expressions(indexdef: &str) -> Vec<String> {
+    let Some(start) = indexdef
+        .find(" USING ")
+        .and_then(|using| indexdef[using..].find('(').map(|paren| using + paren + 1))
+    else {
+        return Vec::new();
+    };
+    let mut expressions = Vec::new();
+    let mut depth = 0;
+    let mut item_start = start;
+    for (offset, ch) in indexdef[start..].char_indices() {
+        let position = start + offset;
+        match ch {
+            '(' => depth += 1,
+            ')' if depth > 0 => depth -= 1,
+            ',' | ')' if depth == 0 => {
+                let item = indexdef[item_start..position].trim();
+                // Operator classes and sort orders follow the expression
+                let expression = match item.rfind(')') {
+                    Some(end) => &item[..=end],
+                    None => item,
+                };
+                if expression.contains('(') {
+                    expressions.push(expression.to_string());
+                }
+                if ch == ')' {
+                    break;
+                }
+                item_start = position + 1;
+            }
+            _ => {}
+        }
+    }
+    expressions
+}
+
+/// Why the plan did not use each partial or expression index of the sequentially scanned
+/// tables, as (table, whether the index covers the query, reason)
+/// Only indexes on columns the query compares after its first WHERE are considered, e.g.
+/// `email` in `lower(email) = $1`. A partial index covers the query when every condition of its predicate is a
+/// condition of the query, which is only proven for queries without OR, and each indexed
+/// expression is used by the query; the sequential scan then comes from the parameter values
+/// of the analysis
+fn ignored_index_reasons(sql: &str, indexes: &[CandidateIndex]) -> Vec<(String, bool, String)> {
+    let query_tokens: Vec<String> = condition_tokens(sql)
+        .into_iter()
+        .skip_while(|token| token != "where")
+        .collect();
+    let has_or = query_tokens.iter().any(|token| token == "or");
+    const COMPARISONS: &[&str] = &[
+        "=", "<>", "<", ">", "<=", ">=", "~~", "~~*", "is", "in", "between", "any",
+    ];
+    let is_compared = |column: &String| {
+        query_tokens.windows(2).any(|pair| {
+            (pair[0] == *column && COMPARISONS.contains(&pair[1].as_str()))
+                || (pair[1] == *column && COMPARISONS.contains(&pair[0].as_str()))
+        })
+    };
+
+    let mut reasons = Vec::new();
+    for index in indexes {
+        if !index.columns.iter().any(is_compared) {
+            continue;
+        }
+        let missing_expression = index
+            .expressions
+            .iter()
+            .find(|expression| !contains_tokens(&query_tokens, &condition_tokens(expression)));
+        let reason = match (&index.predicate, missing_expression) {
+            (Some(predicate), _)
+                if has_or
+                    || !condition_tokens(predicate)
+                        .split(|token| token == "and")
+                        .all(|condition| contains_tokens(&query_tokens, condition)) =>
+            {
+                (
+                    false,
+                    format!(
+                        "partial index '{}' on table '{}', its predicate `{}` is not implied by the conditions of the query",
+                        index.name, index.table, predicate
+                    ),
+                )
+            }
+            (_, Some(expression)) => (
+                false,
+                format!(
+                    "expression index '{}' on table '{}', the query does not use its expression `{}`",
+                    index.name, index.table, expression
+                ),
+            ),
+            (Some(predicate), None) => (
+                true,
+                format!(
+                    "partial index '{}' on table '{}', which covers the conditions of the query with its predicate `{}` and is ignored for the parameter values of the analysis",
+                    index.name, index.table, predicate
+                ),
+            ),
+            (None, None) => (
+                false,
+                format!(
+                    "expression index '{}' on table '{}', which the query uses but the plan ignores for the parameter values of the analysis",
+                    index.name, index.table
+                ),
+            ),
+        };
+        reasons.push((index.table.clone(), reason.0, reason.1));
+    }
+    reasons
+}
+
+/// Availability of the pg_trgm extension, whose GIN indexes serve LIKE and ILIKE patterns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TrigramSupport {
+    /// The extension is installed in the database
+    Installed,
+    /// The extension can be installed with CREATE EXTENSION
+    Available,
+    /// The extension is not available on the server
+    Unavailable,
+}
+
+/// Pattern matching LIKE (`~~`) and ILIKE (`~~*`) comparisons of a column in plan filters,
+/// e.g. `((name)::text ~~* 'john%'::text)`, capturing the column, operator and pattern
+fn like_filter_pattern() -> &'static regex::Regex {
+    static PATTERN: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+    PATTERN.get_or_init(|| {
+        regex::Regex::new(r"(\w+)\)?(?:::[\w ]+?)?\s+(~~\*?)\s+('(?:[^']|'')*'|\$\d+)").unwrap()
+    })
+}
+
+/// Index advice for LIKE and ILIKE filters of sequentially scanned tables, as pairs of the
+/// suggested statements and the warning
+/// B-tree indexes cannot serve ILIKE or LIKE patterns with a leading wildcard, trigram GIN
+/// indexes can as long as the pattern holds at least 3 consecutive characters
+fn like_index_advice(
+    query_name: &str,
+    plan_lines: &[&str],
+    trigram_support: TrigramSupport,
+) -> Vec<(Vec<String>, String)> {
+    let indent_of = |line: &str| line.len() - line.trim_start().len();
+    let mut advice = Vec::new();
+
+    for (i, line) in plan_lines.iter().enumerate() {
+        if !line.trim_start().starts_with("Filter:") {
+            continue;
+        }
+        // Only filters applied by a sequential scan, other scans already use an index
+        let filter_indent = indent_of(line);
+        let Some(table) = plan_lines[..i]
+            .iter()
+            .rev()
+            .find(|other| indent_of(other) < filter_indent)
+            .and_then(|node| node.split("Seq Scan on ").nth(1))
+            .and_then(|rest| rest.split_whitespace().next())
+        else {
+            continue;
+        };
+
+        for captures in like_filter_pattern().captures_iter(line) {
+            let (column, operator, pattern) = (&captures[1], &captures[2], &captures[3]);
+            let operator = if operator == "~~*" { "ILIKE" } else { "LIKE" };
+            // Parameters without analysis_params values are only known at runtime, they appear
+            // as `$1` in generic plans and as the dummy value in custom plans
+            let literal = pattern
+                .strip_prefix('\'')
+                .and_then(|p| p.strip_suffix('\''))
+                .map(|p| p.replace("''", "'"))
+                .filter(|p| p != crate::types_extractor::DUMMY_TEXT);
+            let matched = match &literal {
+                Some(_) => format!("{}.{} with {} {}", table, column, operator, pattern),
+                None => format!(
+                    "{}.{} with {} and a pattern only known at runtime",
+                    table, column, operator
+                ),
+            };
+
+            if let Some(literal) = &literal {
+                let leading_wildcard = literal.starts_with('%') || literal.starts_with('_');
+                if operator == "LIKE" && !leading_wildcard {
+                    continue;
+                }
+                let longest_run = literal
+                    .split(['%', '_'])
+                    .map(|run| run.chars().count())
+                    .max()
+                    .unwrap_or(0);
+                if longest_run < 3 {
+                    advice.push((
+                        Vec::new(),
+                        format!(
+                            "Query '{}' matches {}, which no index can serve as the pattern has fewer than 3 consecutive characters",
+                            query_name, matched
+                        ),
+                    ));
+                    continue;
+                }
+            }
+
+            if trigram_support == TrigramSupport::Unavailable {
+                advice.push((
+                    Vec::new(),
+                    format!(
+                        "Query '{}' matches {}, which only a trigram index can serve, but the pg_trgm extension is not available on the server",
+                        query_name, matched
+                    ),
+                ));
+                continue;
+            }
+
+            let mut statements = Vec::new();
+            if trigram_support == TrigramSupport::Available {
+                statements.push("CREATE EXTENSION IF NOT EXISTS pg_trgm;".to_string());
+            }
+            statements.push(format!(
+                "CREATE INDEX {}_{}_trgm_idx ON {} USING gin ({} gin_trgm_ops);",
+                table, column, table, column
+            ));
+            advice.push((
+                statements.clone(),
+                format!(
+                    "Query '{}' matches {}, which a B-tree index cannot serve; add a trigram index: {}",
+                    query_name,
+                    matched,
+                    statements.join(" ")
+                ),
+            ));
+        }
+    }
+
+    advice
+}
+
+/// Extract the estimated total cost from a plan line like `Seq Scan on users  (cost=0.00..1.50 rows=50 width=64)`
+fn plan_total_cost(plan_line: &str) -> Option<f64> {
+    let cost_start = plan_line.find("cost=")? + "cost=".len();
+    let range = plan_line[cost_start..].split_whitespace().next()?;
+    let (_, total) = range.split_once("..")?;
+    let total: f64 = total.parse().ok()?;
+    // Analysis runs with enable_seqscan disabled, which adds a fixed penalty per disabled node
+    Some(total % DISABLED_NODE_COST)
+}
+
+/// Cost PostgreSQL adds to plan nodes disabled by planner settings such as enable_seqscan
+const DISABLED_NODE_COST: f64 = 1.0e10;
+
+/// Convert a configured analysis parameter value into an SQL literal (without quotes)
+pub(crate) fn analysis_param_literal(value: &serde_yaml::Value) -> String {
+    match value {
+        serde_yaml::Value::String(s) => s.clone(),
+        serde_yaml::Value::Number(n) => n.to_string(),
+        serde_yaml::Value::Bool(b) => b.to_string(),
+        other => serde_yaml::to_string(other)
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+    }
+}
+
+/// Rows estimated for the top node of a text EXPLAIN plan, e.g. `rows=42`, or of a CockroachDB
+/// plan, whose first node reports `estimated row count: 1,042`
+fn plan_estimated_rows(query_plan: &str) -> Option<f64> {
+    if let Some(rows) = query_plan.lines().next()?.split("rows=").nth(1) {
+        return rows
+            .split(|ch: char| !ch.is_ascii_digit())
+            .next()?
+            .parse()
+            .ok();
+    }
+    let rows = query_plan.split("estimated row count: ").nth(1)?;
+    rows.split(|ch: char| !ch.is_ascii_digit() && ch != ',')
+        .next()?
+        .replace(',', "")
+        .parse()
+        .ok()
+}
+
+/// Add a hint to errors caused by prepared statements lost behind a transaction pooler
+fn with_pooling_hint(error: anyhow::Error) -> anyhow::Error {
+    let lost_statement = error.chain().any(|cause| {
+        cause
+            .downcast_ref::<tokio_postgres::Error>()
+            .and_then(|e| e.code())
+            == Some(&tokio_postgres::error::SqlState::INVALID_SQL_STATEMENT_NAME)
+    });
+    if lost_statement {
+        error.context("Prepared statements of the analysis connection were lost, which happens behind a connection pooler in transaction pooling mode such as PgBouncer: analyze against a direct database connection, or set DefaultsConfig::transaction_pooling (--transaction-pooling)")
+    } else {
+        error
+    }
+}
+
+/// Add the relations of a JSON plan and its sub-plans, qualified outside the public schema
+fn collect_plan_relations(
+    plan: &serde_json::Value,
+    tables: &mut std::collections::BTreeSet<String>,
+) {
+    match plan {
+        serde_json::Value::Array(items) => {
+            for item in items {
+                collect_plan_relations(item, tables);
+            }
+        }
+        serde_json::Value::Object(node) => {
+            if let Some(relation) = node.get("Relation Name").and_then(|name| name.as_str()) {
+                match node.get("Schema").and_then(|schema| schema.as_str()) {
+                    Some(schema) if schema != "public" => {
+                        tables.insert(format!("{}.{}", schema, relation));
+                    }
+                    _ => {
+                        tables.insert(relation.to_string());
+                    }
+                }
+            }
+            for value in node.values() {
+                collect_plan_relations(value, tables);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Table of a CockroachDB plan line, e.g. `table: users@users_pkey` or `into: users(id, name)`
+fn cockroach_plan_table(line: &str) -> Option<String> {
+    let line = line.trim();
+    let table = line
+        .strip_prefix("table: ")
+        .or_else(|| line.strip_prefix("into: "))
+        .or_else(|| line.strip_prefix("from: "))?;
+    let end = table.find(['@', '(', ' ']).unwrap_or(table.len());
+    Some(table[..end].to_string()).filter(|table| !table.is_empty())
+}
+
+/// Table names containing the `${shard}` placeholder, e.g. `orders_${shard}`
+fn sharded_table_pattern() -> &'static regex::Regex {
+    static PATTERN: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+    PATTERN.get_or_init(|| regex::Regex::new(r"[A-Za-z0-9_]*\$\{shard\}[A-Za-z0-9_]*").unwrap())
+}
+
+/// Copy of the query with `${shard}` replaced by its canonical shard for analysis
+/// Queries without `shard_by` are returned unchanged
+fn with_canonical_shard(query: &QueryDefinition) -> QueryDefinition {
+    let mut query = query.clone();
+    let (Some(_), Some(shard)) = (&query.shard_by, &query.canonical_shard) else {
+        return query;
+    };
+    query.sql = query.sql.replace(SHARD_PLACEHOLDER, shard);
+    for (converted_sql, _, _) in &mut query.sql_variants {
+        *converted_sql = converted_sql.replace(SHARD_PLACEHOLDER, shard);
+    }
+    for (converted_sql, _) in &mut query.cfg_branches {
+        *converted_sql = converted_sql.replace(SHARD_PLACEHOLDER, shard);
+    }
+    query
+}
+
+/// Remove generated part files of a split module which are not among `parts`,
+/// and the module directory once it is empty
+pub(crate) fn remove_stale_module_parts(
+    module_dir: &Path,
+    parts: &[(String, String)],
+) -> std::io::Result<()> {
+    let Ok(entries) = std::fs::read_dir(module_dir) else {
+        return Ok(());
+    };
+    for entry in entries {
+        let path = entry?.path();
+        let is_current = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| parts.iter().any(|(file_name, _)| file_name == name));
+        let is_generated = std::fs::read_to_string(&path)
+            .map(|content| content.starts_with(GENERATED_PART_HEADER))
+            .unwrap_or(false);
+        if !is_current && is_generated {
+            std::fs::remove_file(&path)?;
+        }
+    }
+    if std::fs::read_dir(module_dir)?.next().is_none() {
+        std::fs::remove_dir(module_dir)?;
+    }
+    Ok(())
+}
+
+/// Check that every `table.column` key of the query's type mappings matches a parameter or an
+/// output column, keys without a table are checked when parsing the SQL file
+fn validate_type_mapping_columns(query: &QueryDefinition, type_info: &QueryTypeInfo) -> Result<()> {
+    let Some(types) = &query.types else {
+        return Ok(());
+    };
+
+    let param_names = parse_parameter_names_from_sql(&query.sql);
+    let mut keys: Vec<&String> = types.keys().filter(|key| key.contains('.')).collect();
+    keys.sort();
+    for key in keys {
+        let field = key
+            .trim_end_matches("[]")
+            .rsplit('.')
+            .next()
+            .unwrap_or_default();
+        let is_used = param_names
+            .iter()
+            .any(|param| param.trim_end_matches('?') == field)
+            || type_info.output_types.iter().any(|col| col.name == field);
+        if !is_used {
+            anyhow::bail!(
+                "Query '{}' of module '{}' types key '{}' matches no parameter or output column",
+                query.name,
+                query.module,
+                key
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Whether an entry of `only`, "module" or "module/query", selects a query
+fn only_entry_selects(entry: &str, query: &QueryDefinition) -> bool {
+    match entry.split_once('/') {
+        Some((module, name)) => query.module == module && query.name == name,
+        None => query.module == entry,
+    }
+}
+
+/// Check that every `flatten` entry names an output column with a custom type mapping
+fn validate_flatten_columns(query: &QueryDefinition, type_info: &QueryTypeInfo) -> Result<()> {
+    for column in &query.flatten {
+        let Some(output) = type_info
+            .output_types
+            .iter()
+            .find(|col| &col.name == column)
+        else {
+            anyhow::bail!(
+                "Query '{}' of module '{}' flatten column '{}' matches no output column",
+                query.name,
+                query.module,
+                column
+            );
+        };
+        if !output.rust_type.needs_json_wrapper {
+            anyhow::bail!(
+                "Query '{}' of module '{}' flatten column '{}' requires a custom type in types",
+                query.name,
+                query.module,
+                column
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Check that every `sensitivity` key names a parameter or an output column
+fn validate_sensitivity_fields(query: &QueryDefinition, type_info: &QueryTypeInfo) -> Result<()> {
+    let param_names = parse_parameter_names_from_sql(&query.sql);
+    for field in query.sensitivity.keys() {
+        let is_used = param_names
+            .iter()
+            .any(|param| param.trim_end_matches('?') == field)
+            || type_info.output_types.iter().any(|col| &col.name == field);
+        if !is_used {
+            anyhow::bail!(
+                "Query '{}' of module '{}' sensitivity key '{}' matches no parameter or output column",
+                query.name,
+                query.module,
+                field
+            );
+        }
+    }
+    if query.redact_debug && query.sensitivity.is_empty() {
+        anyhow::bail!(
+            "Query '{}' of module '{}' sets redact_debug, which requires sensitivity",
+            query.name,
+            query.module
+        );
+    }
+    Ok(())
+}
+
+/// Check that configured analysis parameters exist and match the extracted parameter types
+fn validate_analysis_params(query: &QueryDefinition, type_info: &QueryTypeInfo) -> Result<()> {
+    if query.analysis_params.is_empty() {
+        return Ok(());
+    }
+
+    let param_names = parse_parameter_names_from_sql(&query.sql);
+    let mut param_names_sorted: Vec<_> = query.analysis_params.keys().collect();
+    param_names_sorted.sort();
+
+    for name in param_names_sorted {
+        let value = &query.analysis_params[name];
+        let Some(param_index) = param_names
+            .iter()
+            .position(|p| p.trim_end_matches('?') == name)
+        else {
+            anyhow::bail!(
+                "Query '{}' analysis_params.{}: no such parameter in the query",
+                query.name,
+                name
+            );
+        };
+        let Some(rust_type) = type_info.input_types.get(param_index) else {
+            continue;
+        };
+
+        let expected = match rust_type.rust_type.as_str() {
+            "bool" => "a boolean",
+            "i8"
+            | "i16"
+            | "i32"
+            | "i64"
+            | "u32"
+            | "u64"
+            | "f32"
+            | "f64"
+            | "rust_decimal::Decimal" => "a number",
+            _ => "a string",
+        };
+        let matches = match value {
+            serde_yaml::Value::Bool(_) => expected == "a boolean",
+            serde_yaml::Value::Number(n) => {
+                expected == "a number"
+                    && (!n.is_f64()
+                        || matches!(
+                            rust_type.rust_type.as_str(),
+                            "f32" | "f64" | "rust_decimal::Decimal"
+                        ))
+            }
+            serde_yaml::Value::String(_) => expected == "a string",
+            _ => false,
+        };
+        if !matches {
+            anyhow::bail!(
+                "Query '{}' analysis_params.{}: expected {} for parameter of type {}, got {}",
+                query.name,
+                name,
+                expected,
+                rust_type.rust_type,
+                serde_yaml::to_string(value).unwrap_or_default().trim()
+            );
+        }
+    }
+
+    Ok(())
+}
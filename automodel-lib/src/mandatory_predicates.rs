@@ -0,0 +1,211 @@
+/// Predicates every query touching some tables must apply, e.g. `tenant_id = #{tenant_id}` to
+/// enforce the data isolation of multi-tenant schemas across all generated queries
+use crate::types_extractor::top_level_matches;
+use anyhow::Result;
+use std::sync::OnceLock;
+
+/// Rule requiring a predicate in every query reading, updating or deleting rows of some tables
+///
+/// Queries whose analyzed plan touches one of the tables fail generation unless their SQL applies
+/// the predicate outside conditional blocks; with `inject` the predicate is added to the WHERE
+/// clause of queries mentioning the tables instead
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MandatoryPredicate {
+    /// Tables the rule applies to, e.g. ["orders", "invoices"]
+    /// Schema-qualified names match the table of any schema with that name
+    pub tables: Vec<String>,
+    /// Condition the queries must apply, e.g. "tenant_id = #{tenant_id}"
+    /// Matches the SQL ignoring case, whitespace and table qualifiers of the column, e.g.
+    /// `o.tenant_id = #{tenant_id}`
+    pub predicate: String,
+    /// Whether the predicate is added to the top-level WHERE clause of queries mentioning the
+    /// tables without applying it, instead of failing generation
+    /// Defaults to false
+    pub inject: bool,
+}
+
+impl MandatoryPredicate {
+    /// Fail if the tables or the predicate cannot be used
+    pub(crate) fn validate(&self) -> Result<()> {
+        if self.predicate.trim().is_empty() {
+            anyhow::bail!("mandatory_predicates predicate must not be empty");
+        }
+        if self.tables.is_empty() {
+            anyhow::bail!(
+                "mandatory_predicates rule '{}' must list at least one table",
+                self.predicate
+            );
+        }
+        let is_valid_name = |part: &str| {
+            part.chars()
+                .next()
+                .is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '_')
+                && part
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+        };
+        if let Some(table) = self.tables.iter().find(|table| {
+            let parts: Vec<&str> = table.split('.').collect();
+            parts.len() > 2 || !parts.iter().all(|part| is_valid_name(part))
+        }) {
+            anyhow::bail!(
+                "mandatory_predicates rule '{}' table '{}' is not a table name",
+                self.predicate,
+                table
+            );
+        }
+        if self.predicate.contains("#[") || self.predicate.contains("?}") {
+            anyhow::bail!(
+                "mandatory_predicates predicate '{}' cannot use conditional blocks or optional parameters",
+                self.predicate
+            );
+        }
+        Ok(())
+    }
+
+    /// Names of the tables without their schema, in lowercase as in query plans
+    fn table_names(&self) -> impl Iterator<Item = String> + '_ {
+        self.tables.iter().map(|table| {
+            table
+                .rsplit('.')
+                .next()
+                .unwrap_or(table)
+                .to_ascii_lowercase()
+        })
+    }
+
+    /// Tables of the rule among the tables of a query plan
+    pub(crate) fn touched_tables(&self, plan_tables: &[String]) -> Vec<String> {
+        self.table_names()
+            .filter(|table| plan_tables.iter().any(|plan_table| plan_table == table))
+            .collect()
+    }
+
+    /// Whether the SQL applies the predicate outside conditional blocks
+    pub(crate) fn is_applied(&self, sql: &str) -> bool {
+        predicate_pattern(&self.predicate)
+            .is_match(&crate::sqlfile_parser::remove_conditional_blocks(sql))
+    }
+
+    /// Whether the SQL mentions one of the tables of the rule
+    fn is_mentioned(&self, sql: &str) -> bool {
+        self.table_names().any(|table| {
+            regex::Regex::new(&format!(
+                r"(?i)(?:^|[^a-z0-9_.])(?:[a-z_][a-z0-9_]*\.)?{}(?:$|[^a-z0-9_])",
+                regex::escape(&table)
+            ))
+            .is_ok_and(|pattern| pattern.is_match(sql))
+        })
+    }
+
+    /// Add the predicate to the top-level WHERE clause of the SQL, or add a WHERE clause
+    fn inject(&self, sql: &str) -> Result<String> {
+        static WHERE: OnceLock<regex::Regex> = OnceLock::new();
+        let where_pattern = WHERE.get_or_init(|| regex::Regex::new(r"(?i)\bWHERE\b").unwrap());
+        static CLAUSE_END: OnceLock<regex::Regex> = OnceLock::new();
+        let clause_end = CLAUSE_END.get_or_init(|| {
+            regex::Regex::new(
+                r"(?i)\b(GROUP\s+BY|ORDER\s+BY|HAVING|WINDOW|LIMIT|OFFSET|FETCH|FOR\s+(UPDATE|NO\s+KEY|SHARE|KEY)|RETURNING|ON\s+CONFLICT)\b",
+            )
+            .unwrap()
+        });
+        static SET_OPERATION: OnceLock<regex::Regex> = OnceLock::new();
+        let set_operation = SET_OPERATION
+            .get_or_init(|| regex::Regex::new(r"(?i)\b(UNION|INTERSECT|EXCEPT)\b").unwrap());
+
+        if sql.contains("#[") {
+            anyhow::bail!("cannot be injected into conditional queries, add it to the query");
+        }
+        if !top_level_matches(set_operation, sql).is_empty() {
+            anyhow::bail!(
+                "cannot be injected into queries combining SELECTs with UNION, INTERSECT or EXCEPT, add it to each SELECT"
+            );
+        }
+
+        let sql = sql.trim_end().trim_end_matches(';').trim_end();
+        let wheres = top_level_matches(where_pattern, sql);
+        let injected = match wheres.as_slice() {
+            [] => {
+                let position = top_level_matches(clause_end, sql)
+                    .first()
+                    .map_or(sql.len(), |found| found.start);
+                format!(
+                    "{}\nWHERE {}\n{}",
+                    sql[..position].trim_end(),
+                    self.predicate,
+                    &sql[position..]
+                )
+            }
+            [where_range] => {
+                let rest = &sql[where_range.end..];
+                let clause_len = top_level_matches(clause_end, rest)
+                    .first()
+                    .map_or(rest.len(), |found| found.start);
+                format!(
+                    "{} {} AND ({})\n{}",
+                    &sql[..where_range.end],
+                    self.predicate,
+                    rest[..clause_len].trim(),
+                    &rest[clause_len..]
+                )
+            }
+            _ => anyhow::bail!("cannot be injected into queries with several top-level WHERE clauses, add it to the query"),
+        };
+        Ok(injected.trim_end().to_string())
+    }
+}
+
+/// Whether the rules apply to the statement: INSERTs only write the rows they are given
+pub(crate) fn is_checked_statement(sql: &str) -> bool {
+    static INSERT: OnceLock<regex::Regex> = OnceLock::new();
+    let insert = INSERT.get_or_init(|| regex::Regex::new(r"(?is)^\s*INSERT\b").unwrap());
+    !insert.is_match(sql)
+}
+
+/// Inject the predicates of the rules with `inject` into a query mentioning their tables
+/// without applying them
+pub(crate) fn inject_mandatory_predicates(
+    sql: &str,
+    rules: &[MandatoryPredicate],
+) -> Result<String> {
+    let mut sql = sql.to_string();
+    if !is_checked_statement(&sql) {
+        return Ok(sql);
+    }
+    for rule in rules {
+        if rule.inject && rule.is_mentioned(&sql) && !rule.is_applied(&sql) {
+            sql = rule.inject(&sql).map_err(|e| {
+                anyhow::anyhow!(
+                    "is missing the mandatory predicate `{}`, which {}",
+                    rule.predicate,
+                    e
+                )
+            })?;
+        }
+    }
+    Ok(sql)
+}
+
+/// Pattern matching a predicate ignoring case, whitespace and a table qualifier before it
+fn predicate_pattern(predicate: &str) -> regex::Regex {
+    static TOKEN: OnceLock<regex::Regex> = OnceLock::new();
+    let token = TOKEN.get_or_init(|| regex::Regex::new(r"[A-Za-z0-9_]+|\S").unwrap());
+
+    let tokens: Vec<String> = token
+        .find_iter(predicate)
+        .map(|found| regex::escape(found.as_str()))
+        .collect();
+    let ends_with_word = predicate
+        .trim_end()
+        .ends_with(|ch: char| ch.is_ascii_alphanumeric() || ch == '_');
+    regex::Regex::new(&format!(
+        r"(?i)(?:^|[^a-z0-9_.])(?:[a-z_][a-z0-9_]*\.)?{}{}",
+        tokens.join(r"\s*"),
+        if ends_with_word {
+            "(?:$|[^a-z0-9_])"
+        } else {
+            ""
+        }
+    ))
+    .expect("escaped tokens form a valid pattern")
+}
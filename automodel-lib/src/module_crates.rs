@@ -0,0 +1,254 @@
+/// Output mode writing every module into its own crate
+/// Module crates compile in parallel and cargo only rebuilds the ones whose queries changed
+use crate::analysis_environment::AnalysisEnvironment;
+use crate::edit_guard::EditGuard;
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// First line of generated Cargo.toml files, used to recognize them when cleaning up
+const CARGO_TOML_HEADER: &str =
+    "# This file was automatically generated by AutoModel. Do not edit manually.";
+
+/// Directory of the module crates, relative to the output directory
+const MODULES_DIR: &str = "modules";
+
+/// Directory of the runtime crate, relative to the output directory
+const RUNTIME_DIR: &str = "runtime";
+
+/// Write each module into its own crate instead of a single module tree
+///
+/// The output directory becomes a crate named `name` which re-exports every module, so
+/// `use app_db as generated;` keeps paths like `generated::users::get_user_by_id` working:
+/// - `{output}/` - umbrella crate `{name}`
+/// - `{output}/runtime/` - `{name}-runtime` with the error types and helpers shared by all modules
+/// - `{output}/modules/{module}/` - `{name}-{module}` with the module's generated functions
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModuleCratesConfig {
+    /// Package name of the umbrella crate, e.g. "app-db"
+    pub name: String,
+    /// Lines of the `[dependencies]` section shared by the runtime and module crates,
+    /// e.g. `sqlx = { workspace = true }`
+    pub dependencies: String,
+}
+
+impl ModuleCratesConfig {
+    /// Fail if the crate name cannot be used as a package name
+    pub(crate) fn validate(&self) -> Result<()> {
+        let is_valid = self
+            .name
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic())
+            && self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !is_valid {
+            anyhow::bail!(
+                "module_crates name '{}' must start with a letter and contain only ASCII letters, digits, '-' and '_'",
+                self.name
+            );
+        }
+        Ok(())
+    }
+
+    /// Path of the umbrella crate's lib.rs, which holds the source hash
+    pub(crate) fn lib_file(output_path: &Path) -> std::path::PathBuf {
+        output_path.join("src").join("lib.rs")
+    }
+
+    fn runtime_package(&self) -> String {
+        format!("{}-runtime", self.name)
+    }
+
+    fn module_package(&self, module: &str) -> String {
+        format!("{}-{}", self.name, module)
+    }
+
+    /// Write the crate of a single module
+    pub(crate) fn write_module_crate(
+        &self,
+        output_path: &Path,
+        module: &str,
+        module_code: &str,
+        parts: &[(String, String)],
+        edit_guard: &mut EditGuard,
+    ) -> Result<()> {
+        let crate_path = output_path.join(MODULES_DIR).join(module);
+        let dependencies = format!(
+            "{} = {{ path = \"../../{}\" }}\n{}",
+            self.runtime_package(),
+            RUNTIME_DIR,
+            self.dependencies.trim()
+        );
+        write_if_changed(
+            &crate_path.join("Cargo.toml"),
+            &cargo_toml(
+                &self.module_package(module),
+                &[self.runtime_package()],
+                &dependencies,
+            ),
+        )?;
+
+        // Generated functions refer to the shared items as `super::...`, which resolves to the
+        // crate root when the module is a child of lib.rs
+        let lib = format!(
+            "// This file was automatically generated by AutoModel. Do not edit manually.\n\n\
+             pub use {}::*;\n\n\
+             mod {module};\n\
+             pub use {module}::*;\n",
+            crate_ident(&self.runtime_package()),
+            module = module,
+        );
+        write_if_changed(&crate_path.join("src").join("lib.rs"), &lib)?;
+        edit_guard.write(
+            &crate_path.join("src").join(format!("{}.rs", module)),
+            module_code,
+        )?;
+
+        // Part files of split modules
+        let module_dir = crate_path.join("src").join(module);
+        for (file_name, code) in parts {
+            edit_guard.write(&module_dir.join(file_name), code)?;
+        }
+        crate::remove_stale_module_parts(&module_dir, parts)?;
+        Ok(())
+    }
+
+    /// Write the runtime crate holding the items shared by all modules
+    /// Crate-private helpers are made public, as the modules now live in other crates
+    pub(crate) fn write_runtime_crate(&self, output_path: &Path, shared_items: &str) -> Result<()> {
+        let crate_path = output_path.join(RUNTIME_DIR);
+        write_if_changed(
+            &crate_path.join("Cargo.toml"),
+            &cargo_toml(&self.runtime_package(), &[], self.dependencies.trim()),
+        )?;
+
+        let lib = format!(
+            "// This file was automatically generated by AutoModel. Do not edit manually.\n\n{}",
+            shared_items.replace("pub(crate) ", "pub ")
+        );
+        write_if_changed(&crate_path.join("src").join("lib.rs"), &lib)
+    }
+
+    /// Write the umbrella crate re-exporting the runtime and every module
+    pub(crate) fn write_umbrella_crate(
+        &self,
+        output_path: &Path,
+        modules: &[String],
+        source_hash: u64,
+        environment: &AnalysisEnvironment,
+    ) -> Result<()> {
+        let mut dependencies = format!(
+            "{} = {{ path = \"{}\" }}\n",
+            self.runtime_package(),
+            RUNTIME_DIR
+        );
+        let mut feature_packages = vec![self.runtime_package()];
+        let mut lib = format!(
+            "// AUTOMODEL_HASH: {}\n\
+             // This file was automatically generated by AutoModel. Do not edit manually.\n\
+             {}\n\
+             pub use {}::*;\n\n",
+            source_hash,
+            environment.comment(),
+            crate_ident(&self.runtime_package())
+        );
+        for module in modules {
+            let package = self.module_package(module);
+            dependencies.push_str(&format!(
+                "{} = {{ path = \"{}/{}\" }}\n",
+                package, MODULES_DIR, module
+            ));
+            feature_packages.push(package.clone());
+            lib.push_str(&format!(
+                "pub use {} as {};\n",
+                crate_ident(&package),
+                module
+            ));
+        }
+
+        write_if_changed(
+            &output_path.join("Cargo.toml"),
+            &cargo_toml(&self.name, &feature_packages, dependencies.trim()),
+        )?;
+        write_if_changed(&Self::lib_file(output_path), &lib)
+    }
+
+    /// Remove generated module crates of modules which no longer exist
+    pub(crate) fn remove_stale_module_crates(output_path: &Path, modules: &[String]) -> Result<()> {
+        for crate_path in Self::stale_module_crates(output_path, modules)? {
+            fs::remove_dir_all(&crate_path)?;
+        }
+        Ok(())
+    }
+
+    /// Directories of the generated module crates of modules which no longer exist
+    pub(crate) fn stale_module_crates(
+        output_path: &Path,
+        modules: &[String],
+    ) -> Result<Vec<PathBuf>> {
+        let Ok(entries) = fs::read_dir(output_path.join(MODULES_DIR)) else {
+            return Ok(Vec::new());
+        };
+        let mut stale = Vec::new();
+        for entry in entries {
+            let crate_path = entry?.path();
+            let is_current = crate_path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| modules.iter().any(|module| module == name));
+            let is_generated = fs::read_to_string(crate_path.join("Cargo.toml"))
+                .map(|content| content.starts_with(CARGO_TOML_HEADER))
+                .unwrap_or(false);
+            if !is_current && is_generated {
+                stale.push(crate_path);
+            }
+        }
+        stale.sort();
+        Ok(stale)
+    }
+}
+
+/// Cargo.toml of a generated crate
+/// Every crate has a `dynamic` feature, enabling the `to_map()`/`from_map()` conversions, a
+/// `blocking` feature, enabling the `*_blocking` wrappers, and a `cancellation` feature, enabling
+/// the `*_cancellable` variants, all forwarded to `feature_packages`
+fn cargo_toml(package: &str, feature_packages: &[String], dependencies: &str) -> String {
+    let forwarded = |feature: &str| {
+        feature_packages
+            .iter()
+            .map(|package| format!("\"{}/{}\"", package, feature))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    format!(
+        "{}\n[package]\nname = \"{}\"\nversion = \"0.1.0\"\nedition = \"2021\"\npublish = false\n\n\
+         [features]\ndynamic = [{}]\nblocking = [{}]\ncancellation = [{}]\n\n[dependencies]\n{}\n",
+        CARGO_TOML_HEADER,
+        package,
+        forwarded("dynamic"),
+        forwarded("blocking"),
+        forwarded("cancellation"),
+        dependencies
+    )
+}
+
+/// Name of a package in Rust paths
+fn crate_ident(package: &str) -> String {
+    package.replace('-', "_")
+}
+
+/// Write a file unless it already has the given content
+/// Keeps the modification time of unchanged files, so cargo does not rebuild their crates
+fn write_if_changed(path: &Path, content: &str) -> Result<()> {
+    if fs::read_to_string(path).is_ok_and(|existing| existing == content) {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, content)?;
+    Ok(())
+}
@@ -0,0 +1,159 @@
+/// Public view of the analysis results, for tools built on top of the analysis engine
+/// Mirrors what code generation sees, without exposing the internal runtime structures
+use crate::diagnostics::Diagnostic;
+use crate::query_definition::PlanEstimates;
+use crate::query_definition_rt::QueryDefinitionRuntime;
+use crate::types_extractor::{parse_parameter_names_from_sql, RustType};
+use serde::Serialize;
+
+/// Analysis of a single query, as returned by `AutoModel::analyze`
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryAnalysis {
+    /// Module of the query, i.e. its directory under the queries directory
+    pub module: String,
+    /// Query name, i.e. the SQL file name without its numeric prefix
+    pub name: String,
+    /// Description from the query metadata
+    pub description: Option<String>,
+    /// SQL with `#{name}` parameters and conditional blocks as written in the file
+    pub sql: String,
+    /// Parameters in the order they first appear in the SQL
+    pub parameters: Vec<AnalyzedParameter>,
+    /// Result columns in SELECT order, empty for queries returning no rows
+    pub columns: Vec<AnalyzedColumn>,
+    /// Whether the query modifies data (INSERT/UPDATE/DELETE)
+    pub is_mutation: bool,
+    /// Constraints the query can violate, only detected for mutations
+    pub constraints: Vec<AnalyzedConstraint>,
+    /// Tables read or modified by the query according to its plans, sorted
+    pub tables: Vec<String>,
+    /// Execution plan from EXPLAIN, only for queries with `ensure_indexes` enabled
+    pub query_plan: Option<String>,
+    /// Statements creating the indexes suggested by the analysis, only for queries with
+    /// `ensure_indexes` enabled
+    pub index_suggestions: Vec<String>,
+    /// Partial and expression indexes of sequentially scanned tables which the plan did not use,
+    /// each with the reason, only for queries with `ensure_indexes` enabled
+    pub ignored_indexes: Vec<String>,
+    /// Estimated cost and rows of the plan, only for read-only queries
+    pub plan_estimates: Option<PlanEstimates>,
+    /// Performance, type and naming warnings, as reported during code generation
+    pub warnings: Vec<Diagnostic>,
+}
+
+/// Query parameter with its analyzed type
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalyzedParameter {
+    /// Parameter name without the `?` suffix of conditional parameters
+    pub name: String,
+    /// Rust type without `Option<>`, e.g. "i32" or "crate::models::UserProfile"
+    pub rust_type: String,
+    /// Whether the parameter accepts NULL
+    pub is_nullable: bool,
+    /// Whether the parameter is only used in conditional blocks (`#{name?}`)
+    pub is_optional: bool,
+    /// Variants of PostgreSQL enum parameters
+    pub enum_variants: Option<Vec<String>>,
+}
+
+/// Result column with its analyzed type
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalyzedColumn {
+    /// Column name as returned by PostgreSQL
+    pub name: String,
+    /// Rust type without `Option<>`, e.g. "String" or "chrono::DateTime<chrono::Utc>"
+    pub rust_type: String,
+    /// Whether the column can be NULL
+    pub is_nullable: bool,
+    /// Variants of PostgreSQL enum columns
+    pub enum_variants: Option<Vec<String>>,
+}
+
+/// Database constraint a mutation can violate
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalyzedConstraint {
+    /// Constraint name
+    pub name: String,
+    /// Constraint type: unique, primary_key, foreign_key, check or not_null
+    pub constraint_type: String,
+    /// Table the constraint belongs to
+    pub table_name: String,
+}
+
+impl QueryAnalysis {
+    pub(crate) fn from_runtime(
+        analyzed: &QueryDefinitionRuntime,
+        warnings: Vec<Diagnostic>,
+    ) -> Self {
+        let definition = &analyzed.definition;
+        let param_names = parse_parameter_names_from_sql(&definition.sql);
+        let parameters = param_names
+            .iter()
+            .zip(&analyzed.type_info.input_types)
+            .map(|(name, rust_type)| AnalyzedParameter {
+                name: name.trim_end_matches('?').to_string(),
+                rust_type: rust_type.rust_type.clone(),
+                is_nullable: rust_type.is_nullable,
+                is_optional: rust_type.is_optional,
+                enum_variants: enum_variants(rust_type),
+            })
+            .collect();
+        let columns = analyzed
+            .type_info
+            .output_types
+            .iter()
+            .map(|col| AnalyzedColumn {
+                name: col.name.clone(),
+                rust_type: col.rust_type.rust_type.clone(),
+                is_nullable: col.rust_type.is_nullable,
+                enum_variants: enum_variants(&col.rust_type),
+            })
+            .collect();
+        let constraints = analyzed
+            .constraints
+            .iter()
+            .map(|constraint| AnalyzedConstraint {
+                name: constraint.name.clone(),
+                constraint_type: constraint.constraint_type.clone(),
+                table_name: constraint.table_name.clone(),
+            })
+            .collect();
+
+        Self {
+            module: definition.module.clone(),
+            name: definition.name.clone(),
+            description: definition.description.clone(),
+            sql: definition.sql.clone(),
+            parameters,
+            columns,
+            is_mutation: analyzed.is_mutation,
+            constraints,
+            tables: analyzed.type_info.tables.clone(),
+            query_plan: analyzed
+                .performance_analysis
+                .as_ref()
+                .and_then(|perf| perf.query_plan.clone()),
+            index_suggestions: analyzed
+                .performance_analysis
+                .as_ref()
+                .map(|perf| perf.index_suggestions.clone())
+                .unwrap_or_default(),
+            ignored_indexes: analyzed
+                .performance_analysis
+                .as_ref()
+                .map(|perf| perf.ignored_indexes.clone())
+                .unwrap_or_default(),
+            plan_estimates: analyzed.type_info.plan_estimates,
+            warnings,
+        }
+    }
+}
+
+/// Enum variants of a type, None for enums serialized as JSON by a custom type mapping
+fn enum_variants(rust_type: &RustType) -> Option<Vec<String>> {
+    if rust_type.needs_json_wrapper {
+        None
+    } else {
+        rust_type.enum_variants.clone()
+    }
+}
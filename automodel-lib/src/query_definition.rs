@@ -1,5 +1,5 @@
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap};
 
 /// Parameters type configuration - can be either a boolean or a struct name
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -65,6 +65,68 @@ impl Default for ConditionsType {
     }
 }
 
+/// Filter type configuration - can be either a boolean or an enum name
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub(crate) enum FilterType {
+    /// Auto-generate a new enum with name {QueryName}Filter
+    Enabled(bool),
+    /// Generate an enum with the given name
+    Named(String),
+}
+
+impl FilterType {
+    pub fn is_enabled(&self) -> bool {
+        match self {
+            FilterType::Enabled(b) => *b,
+            FilterType::Named(_) => true,
+        }
+    }
+
+    pub fn get_enum_name(&self) -> Option<&str> {
+        match self {
+            FilterType::Enabled(_) => None,
+            FilterType::Named(name) => Some(name.as_str()),
+        }
+    }
+}
+
+impl Default for FilterType {
+    fn default() -> Self {
+        FilterType::Enabled(false)
+    }
+}
+
+/// Cache invalidation configuration - can be either a channel name or a channel with the
+/// argument its notifications name
+/// Generates a `{query}_cached` function whose cache the task of `cache_invalidation::spawn`
+/// clears when the channel is notified
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub(crate) enum CacheInvalidateOn {
+    /// Every notification on the channel clears all cached results, e.g. "user_events"
+    Channel(String),
+    /// A notification on the channel clears the results cached for the value of the `key`
+    /// argument given as payload, or all of them when the payload is empty
+    Keyed { channel: String, key: String },
+}
+
+impl CacheInvalidateOn {
+    pub fn channel(&self) -> &str {
+        match self {
+            CacheInvalidateOn::Channel(channel) => channel,
+            CacheInvalidateOn::Keyed { channel, .. } => channel,
+        }
+    }
+
+    pub fn key(&self) -> Option<&str> {
+        match self {
+            CacheInvalidateOn::Channel(_) => None,
+            CacheInvalidateOn::Keyed { key, .. } => Some(key),
+        }
+    }
+}
+
 /// Expected result type for a query
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -77,6 +139,374 @@ pub(crate) enum ExpectedResult {
     AtLeastOne,
     /// Multiple rows expected (uses query, returns Vec which may be empty)
     Multiple,
+    /// Exactly one row, inserted with the companion `insert` query when missing (get-or-create)
+    ExactlyOneOrInsert,
+}
+
+/// Shape of the value returned for each row of a multi-column query
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ReturnShape {
+    /// Named struct ({QueryName}Item or return_type)
+    #[default]
+    Struct,
+    /// Tuple of column values in SELECT order, e.g. (i32, String)
+    Tuple,
+}
+
+/// Chunked iteration configuration
+/// Generates `for_each_*`/`try_for_each_*` helpers that page through the results by key
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub(crate) struct IterateConfig {
+    /// Number of rows fetched and passed to the callback per chunk
+    /// Defaults to 1000
+    #[serde(default = "default_iterate_chunk_size")]
+    pub chunk_size: u32,
+    /// Output column used to order the rows and to resume after the last row of each chunk
+    /// Must be unique and non-nullable
+    pub key: String,
+}
+
+fn default_iterate_chunk_size() -> u32 {
+    1000
+}
+
+/// Plan estimates recorded from a previous analysis, e.g. `baseline: { total_cost: 125.5, rows: 10 }`
+/// Current estimates exceeding them by more than the tolerance are reported as plan regressions
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub(crate) struct PlanBaseline {
+    /// Estimated total cost of the most expensive variant
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_cost: Option<f64>,
+    /// Estimated rows returned by the base variant
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rows: Option<f64>,
+    /// Allowed relative increase of the estimates, e.g. 0.5 for 50%
+    /// Defaults to DefaultsConfig::baseline_tolerance
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tolerance: Option<f64>,
+}
+
+/// Tolerance of baselines when neither the query nor `DefaultsConfig::baseline_tolerance` sets one
+pub(crate) const DEFAULT_BASELINE_TOLERANCE: f64 = 0.5;
+
+impl PlanBaseline {
+    /// Descriptions of the estimates exceeding the baseline by more than its tolerance
+    pub(crate) fn regressions(&self, estimates: &PlanEstimates) -> Vec<String> {
+        let tolerance = self.tolerance.unwrap_or(DEFAULT_BASELINE_TOLERANCE);
+        [
+            ("cost", self.total_cost, estimates.total_cost),
+            ("rows", self.rows, estimates.rows),
+        ]
+        .into_iter()
+        .filter_map(|(name, recorded, current)| match (recorded, current) {
+            (Some(recorded), Some(current)) if current > recorded * (1.0 + tolerance) => {
+                let round = |value: f64| (value * 100.0).round() / 100.0;
+                Some(format!(
+                    "estimated {} {} exceeds the baseline of {} by more than {}%",
+                    name,
+                    round(current),
+                    round(recorded),
+                    round(tolerance * 100.0)
+                ))
+            }
+            _ => None,
+        })
+        .collect()
+    }
+}
+
+/// Estimates of the current plan compared with a `baseline` and recorded by `update_baselines`
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct PlanEstimates {
+    /// Estimated total cost of the most expensive variant, None with CockroachDB
+    pub total_cost: Option<f64>,
+    /// Estimated rows returned by the base variant
+    pub rows: Option<f64>,
+}
+
+/// Group commit batching configuration for single-row inserts
+/// Generates a batcher which coalesces concurrent calls into multi-row INSERT statements
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub(crate) struct BatchConfig {
+    /// Maximum number of rows per statement
+    /// Lowered automatically to stay within PostgreSQL's limit of 65535 bind parameters
+    /// Defaults to 100
+    #[serde(default = "default_batch_max_size")]
+    pub max_size: u32,
+    /// Maximum time in milliseconds a call waits for other calls to join its batch
+    /// Defaults to 5
+    #[serde(default = "default_batch_max_delay_ms")]
+    pub max_delay_ms: u64,
+}
+
+fn default_batch_max_size() -> u32 {
+    100
+}
+
+fn default_batch_max_delay_ms() -> u64 {
+    5
+}
+
+/// Code generated for the values allowed by a CHECK constraint like `status IN ('a', 'b')`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum CheckValuesStyle {
+    /// Rust enum named after the table and column (e.g. `TicketsPriority`) used instead of String
+    Enum,
+    /// `&[&str]` constant (e.g. `TICKETS_STATE_VALUES`), parameters are checked against it before binding
+    Const,
+}
+
+/// Rust type of a CHAR(1) or "char" flag column, written as `bool("Y", "N")` with the codes of
+/// true and false, or as an enum path with the code of each variant, e.g.
+/// `crate::models::Status("A" = Active, "I" = Inactive)`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub struct CharFlag {
+    /// `bool` or the path of a `Copy + PartialEq` enum
+    pub rust_type: String,
+    /// Codes of the column with the Rust value of each, e.g. ("Y", "true") or
+    /// ("A", "crate::models::Status::Active")
+    pub codes: Vec<(String, String)>,
+}
+
+impl TryFrom<String> for CharFlag {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let invalid = || {
+            format!(
+                "'{}' is neither bool(\"Y\", \"N\") nor Enum(\"A\" = Variant, ...)",
+                value
+            )
+        };
+        let (rust_type, args) = value
+            .trim()
+            .strip_suffix(')')
+            .and_then(|value| value.split_once('('))
+            .ok_or_else(invalid)?;
+        let rust_type = rust_type.trim();
+        let code = |code: &str| {
+            code.trim()
+                .strip_prefix('"')
+                .and_then(|code| code.strip_suffix('"'))
+                .filter(|code| !code.is_empty() && !code.contains(['"', '\\']))
+                .map(str::to_string)
+        };
+        let args: Vec<&str> = args.split(',').collect();
+
+        let codes = if rust_type == "bool" {
+            match args.as_slice() {
+                [true_code, false_code] => vec![
+                    (code(true_code).ok_or_else(invalid)?, "true".to_string()),
+                    (code(false_code).ok_or_else(invalid)?, "false".to_string()),
+                ],
+                _ => return Err(invalid()),
+            }
+        } else {
+            let is_path = rust_type.split("::").enumerate().all(|(i, segment)| {
+                crate::sqlfile_parser::is_valid_rust_identifier(segment)
+                    || (i == 0 && matches!(segment, "crate" | "super" | "self"))
+            });
+            if !is_path {
+                return Err(invalid());
+            }
+            args.iter()
+                .map(|arg| {
+                    let (value_code, variant) = arg.split_once('=').ok_or_else(invalid)?;
+                    let variant = variant.trim();
+                    if !crate::sqlfile_parser::is_valid_rust_identifier(variant) {
+                        return Err(invalid());
+                    }
+                    Ok((
+                        code(value_code).ok_or_else(invalid)?,
+                        format!("{}::{}", rust_type, variant),
+                    ))
+                })
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        if let Some(duplicate) = codes
+            .iter()
+            .enumerate()
+            .find(|(i, (value_code, _))| codes[..*i].iter().any(|(other, _)| other == value_code))
+        {
+            return Err(format!(
+                "'{}' maps code \"{}\" twice",
+                value, duplicate.1 .0
+            ));
+        }
+        Ok(Self {
+            rust_type: rust_type.to_string(),
+            codes,
+        })
+    }
+}
+
+impl From<CharFlag> for String {
+    fn from(flag: CharFlag) -> Self {
+        let codes: Vec<String> = if flag.rust_type == "bool" {
+            flag.codes
+                .iter()
+                .map(|(code, _)| format!("{:?}", code))
+                .collect()
+        } else {
+            flag.codes
+                .iter()
+                .map(|(code, value)| {
+                    let variant = value.rsplit("::").next().unwrap_or(value);
+                    format!("{:?} = {}", code, variant)
+                })
+                .collect()
+        };
+        format!("{}({})", flag.rust_type, codes.join(", "))
+    }
+}
+
+/// Classification of a parameter or column holding sensitive data
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Sensitivity {
+    /// Personally identifiable information, e.g. emails or names
+    Pii,
+    /// Credentials and other secrets, e.g. password hashes or API tokens
+    Secret,
+}
+
+impl Sensitivity {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Sensitivity::Pii => "pii",
+            Sensitivity::Secret => "secret",
+        }
+    }
+}
+
+/// Rust type used for NUMERIC output columns
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum NumericType {
+    /// Exact `rust_decimal::Decimal` (default)
+    #[default]
+    Decimal,
+    /// `f64`, converted from the decoded Decimal (may lose precision)
+    F64,
+    /// `i64`, converted from the decoded Decimal, failing with `NumericOverflow` when the value is
+    /// not an integer or out of range, e.g. for `SUM()` of bigint columns
+    I64,
+}
+
+/// `numeric_as` option: one type for all NUMERIC output columns or per-column types
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub(crate) enum NumericAs {
+    /// Applies to every NUMERIC output column of the query
+    Query(NumericType),
+    /// Key: output column name
+    Columns(HashMap<String, NumericType>),
+}
+
+/// How array parameters compared with `= ANY(...)` are passed to the server
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum LargeListStrategy {
+    /// Bind the whole array as a single parameter (default)
+    #[default]
+    Bind,
+    /// COPY the values into a temporary table and join against it, for lists of thousands of entries
+    /// The generated function takes a `&mut sqlx::PgConnection` and runs in its own transaction
+    TempTable,
+}
+
+/// Builds a query's function is compiled into, set with `scope`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum QueryScope {
+    /// Every build (default)
+    #[default]
+    All,
+    /// Unit tests only, the module is declared with `#[cfg(test)]`
+    Test,
+    /// Builds with the `bench` cargo feature only, the module is declared with
+    /// `#[cfg(feature = "bench")]`
+    Bench,
+}
+
+impl QueryScope {
+    /// Name as written in the metadata, e.g. "test"
+    pub fn name(&self) -> &'static str {
+        match self {
+            QueryScope::All => "all",
+            QueryScope::Test => "test",
+            QueryScope::Bench => "bench",
+        }
+    }
+
+    /// Condition of the `#[cfg(...)]` attribute of modules in this scope
+    pub fn cfg(&self) -> Option<&'static str> {
+        match self {
+            QueryScope::All => None,
+            QueryScope::Test => Some("test"),
+            QueryScope::Bench => Some("feature = \"bench\""),
+        }
+    }
+}
+
+/// `retrying` option of INSERT ... RETURNING queries
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub(crate) enum Retrying {
+    /// Look up the conflicting row of every unique constraint whose columns are all inserted
+    /// from parameters
+    Enabled(bool),
+    /// Look up the conflicting row of the given unique constraints only, other unique
+    /// violations are returned as errors
+    Constraints { constraints: Vec<String> },
+}
+
+impl Retrying {
+    pub fn is_enabled(&self) -> bool {
+        match self {
+            Retrying::Enabled(b) => *b,
+            Retrying::Constraints { .. } => true,
+        }
+    }
+
+    /// Constraints the conflicting row is looked up for, empty for all covered constraints
+    pub fn constraints(&self) -> &[String] {
+        match self {
+            Retrying::Enabled(_) => &[],
+            Retrying::Constraints { constraints } => constraints,
+        }
+    }
+}
+
+/// Tables of a DELETE query with `archive_to`, whose deleted rows are moved to the archive table
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub(crate) struct ArchiveTables {
+    /// Table the rows are deleted from, as written in the query
+    pub source: String,
+    /// Table the deleted rows are inserted into, e.g. "public.users_archive"
+    pub archive: String,
+}
+
+/// Module-level configuration loaded from `queries/{module}/module.yaml`
+/// Applies to the whole generated module file
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub(crate) struct ModuleConfig {
+    /// Extra paths imported at the top of the generated module, e.g. `crate::models::*`
+    /// Allows `types:` mappings to use short type names
+    #[serde(default)]
+    pub imports: Vec<String>,
+    /// Inner attributes applied to the generated module, e.g. `allow(clippy::too_many_arguments)`
+    #[serde(default)]
+    pub attributes: Vec<String>,
+    /// Whether a `Dao<'c>` struct is generated, borrowing a connection or transaction and
+    /// calling the module's query functions on it
+    /// Defaults to false
+    #[serde(default)]
+    pub dao: bool,
 }
 
 /// OpenTelemetry instrumentation level
@@ -93,6 +523,269 @@ pub enum TelemetryLevel {
     Trace,
 }
 
+/// Performance check that can be enforced with `fail_on` and allowed per query
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PerformanceCheck {
+    /// Query plan contains a sequential scan
+    SeqScan,
+    /// Query plan filters rows of a sequentially scanned table (an index could serve the filter)
+    MissingIndex,
+    /// Estimated query plan cost exceeds the configured limit
+    Cost,
+    /// Query plan runs a NOT IN or correlated subquery which could be rewritten as a join
+    Subquery,
+    /// Plan estimates exceed the recorded `baseline` by more than its tolerance
+    Baseline,
+}
+
+/// Performance analysis result which fails code generation
+/// Parsed from `seq_scan`, `missing_index`, `subquery`, `baseline` or `cost>N`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FailOn {
+    /// Fail if any query performs a sequential scan
+    SeqScan,
+    /// Fail if any query filters a sequentially scanned table
+    MissingIndex,
+    /// Fail if any query runs a subquery which could be rewritten as a join
+    Subquery,
+    /// Fail if the estimated total cost of any query exceeds the limit
+    CostAbove(f64),
+    /// Fail if the plan estimates of any query regressed beyond its `baseline`
+    Baseline,
+}
+
+impl FailOn {
+    /// Per-query `allow` entry which exempts a query from this check
+    pub fn check(&self) -> PerformanceCheck {
+        match self {
+            FailOn::SeqScan => PerformanceCheck::SeqScan,
+            FailOn::MissingIndex => PerformanceCheck::MissingIndex,
+            FailOn::Subquery => PerformanceCheck::Subquery,
+            FailOn::CostAbove(_) => PerformanceCheck::Cost,
+            FailOn::Baseline => PerformanceCheck::Baseline,
+        }
+    }
+}
+
+impl std::str::FromStr for FailOn {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s {
+            "seq_scan" => Ok(FailOn::SeqScan),
+            "missing_index" => Ok(FailOn::MissingIndex),
+            "subquery" => Ok(FailOn::Subquery),
+            "baseline" => Ok(FailOn::Baseline),
+            _ => {
+                let limit = s
+                    .strip_prefix("cost>")
+                    .ok_or_else(|| {
+                        format!(
+                            "Invalid fail-on rule '{}', expected seq_scan, missing_index, subquery, baseline or cost>N",
+                            s
+                        )
+                    })?
+                    .trim();
+                limit
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|limit| limit.is_finite() && *limit >= 0.0)
+                    .map(FailOn::CostAbove)
+                    .ok_or_else(|| format!("Invalid cost limit '{}' in fail-on rule", limit))
+            }
+        }
+    }
+}
+
+/// Database the queries are analyzed against and generated for
+/// Parsed from `postgres` or `cockroach`
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Dialect {
+    /// PostgreSQL
+    #[default]
+    Postgres,
+    /// CockroachDB, which speaks the PostgreSQL protocol with its own planner and error codes
+    Cockroach,
+}
+
+impl Dialect {
+    /// Prefix of the EXPLAIN statements showing the estimated plan of a query as text
+    pub(crate) fn explain_prefix(&self) -> &'static str {
+        match self {
+            Dialect::Postgres => "EXPLAIN (FORMAT TEXT, ANALYZE false)",
+            Dialect::Cockroach => "EXPLAIN",
+        }
+    }
+
+    /// Whether analysis connections disable sequential scans, to detect queries which would
+    /// benefit from indexes even with small tables
+    /// CockroachDB has no such setting and reports full scans as planned
+    pub(crate) fn disables_seqscan(&self) -> bool {
+        matches!(self, Dialect::Postgres)
+    }
+}
+
+impl std::str::FromStr for Dialect {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "postgres" => Ok(Dialect::Postgres),
+            "cockroach" => Ok(Dialect::Cockroach),
+            other => Err(format!(
+                "Invalid dialect '{}', expected postgres or cockroach",
+                other
+            )),
+        }
+    }
+}
+
+/// Major version of sqlx the generated code compiles against
+/// Parsed from `0.7`, `0.8` or `feature`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SqlxVersion {
+    /// sqlx 0.7, whose `Encode` cannot fail and which has no `sqlx::Error::InvalidArgument`
+    V0_7,
+    /// sqlx 0.8
+    #[default]
+    V0_8,
+    /// Both majors, sqlx 0.7 when the crate of the generated code enables its `sqlx-0-7` feature
+    Feature,
+}
+
+impl SqlxVersion {
+    /// Whether the generated code goes through the `sqlx_compat` module of the shared items
+    pub(crate) fn needs_compat(&self) -> bool {
+        !matches!(self, SqlxVersion::V0_8)
+    }
+}
+
+impl std::str::FromStr for SqlxVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "0.7" => Ok(SqlxVersion::V0_7),
+            "0.8" => Ok(SqlxVersion::V0_8),
+            "feature" => Ok(SqlxVersion::Feature),
+            other => Err(format!(
+                "Invalid sqlx version '{}', expected 0.7, 0.8 or feature",
+                other
+            )),
+        }
+    }
+}
+
+/// Order in which queries are analyzed
+/// Parsed from `files` or `recently_modified`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AnalysisOrder {
+    /// Order of the SQL files, by module directory and file name
+    #[default]
+    Files,
+    /// Most recently modified SQL files first, so that errors in the queries being worked on
+    /// surface before the others are analyzed
+    RecentlyModified,
+}
+
+impl std::str::FromStr for AnalysisOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "files" => Ok(AnalysisOrder::Files),
+            "recently_modified" => Ok(AnalysisOrder::RecentlyModified),
+            other => Err(format!(
+                "Invalid analysis order '{}', expected files or recently_modified",
+                other
+            )),
+        }
+    }
+}
+
+/// Property of a column expected by a query, listed in its `assumes` metadata
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ColumnAssumption {
+    /// Column allows NULL
+    Nullable,
+    /// Column is NOT NULL
+    NotNull,
+    /// A unique index or constraint covers exactly this column
+    Unique,
+    /// Column is the leading column of an index
+    Indexed,
+    /// Column type, matched against the type name (`int4`) or its SQL spelling (`integer`)
+    Type(String),
+}
+
+/// Schema assumption of a query, e.g. `users.email: unique`
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SchemaAssumption {
+    /// Table name, optionally schema-qualified (e.g. `users` or `public.users`)
+    /// Unqualified tables are looked up in the `public` schema
+    pub table: String,
+    /// Column name
+    pub column: String,
+    /// Expected column properties
+    pub expectations: Vec<ColumnAssumption>,
+}
+
+impl std::fmt::Display for ColumnAssumption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ColumnAssumption::Nullable => write!(f, "nullable"),
+            ColumnAssumption::NotNull => write!(f, "not_null"),
+            ColumnAssumption::Unique => write!(f, "unique"),
+            ColumnAssumption::Indexed => write!(f, "indexed"),
+            ColumnAssumption::Type(type_name) => write!(f, "{}", type_name),
+        }
+    }
+}
+
+impl SchemaAssumption {
+    /// Parse an `assumes` entry like `users.age: nullable int4`
+    /// Words other than nullable, not_null, unique and indexed are read as the column type
+    pub fn parse(key: &str, value: &str) -> Result<Self, String> {
+        let (table, column) = key
+            .trim()
+            .rsplit_once('.')
+            .filter(|(table, column)| !table.is_empty() && !column.is_empty())
+            .ok_or_else(|| format!("'{}' must be written as table.column", key))?;
+
+        let mut expectations = Vec::new();
+        let mut type_words: Vec<&str> = Vec::new();
+        for word in value.split_whitespace() {
+            match word {
+                "nullable" => expectations.push(ColumnAssumption::Nullable),
+                "not_null" => expectations.push(ColumnAssumption::NotNull),
+                "unique" => expectations.push(ColumnAssumption::Unique),
+                "indexed" => expectations.push(ColumnAssumption::Indexed),
+                _ => type_words.push(word),
+            }
+        }
+        if !type_words.is_empty() {
+            expectations.push(ColumnAssumption::Type(type_words.join(" ")));
+        }
+        if expectations.is_empty() {
+            return Err(format!("'{}' lists no expectations", key));
+        }
+        if expectations.contains(&ColumnAssumption::Nullable)
+            && expectations.contains(&ColumnAssumption::NotNull)
+        {
+            return Err(format!("'{}' cannot be both nullable and not_null", key));
+        }
+
+        Ok(SchemaAssumption {
+            table: table.to_string(),
+            column: column.to_string(),
+            expectations,
+        })
+    }
+}
+
 impl Default for TelemetryLevel {
     fn default() -> Self {
         TelemetryLevel::None
@@ -106,20 +799,30 @@ impl Default for ExpectedResult {
 }
 
 /// Represents a single SQL query definition from the YAML file
-#[derive(Debug, Clone)]
+/// Serializes to its effective configuration, as printed by `AutoModel::effective_config`
+#[derive(Debug, Clone, Serialize)]
 pub(crate) struct QueryDefinition {
     /// The name of the query, which will be used as the function name
+    #[serde(skip)]
     pub name: String,
     /// The SQL query string (original with #{param} syntax)
+    #[serde(skip)]
     pub sql: String,
     /// Pre-processed SQL variants with positional parameters ($1, $2, etc.)
     /// Each variant represents: (converted_sql, param_names, variant_label)
     /// - Base variant has all conditional blocks removed
     /// - Additional variants include each conditional block separately
+    #[serde(skip)]
     pub sql_variants: Vec<(String, Vec<String>, String)>,
+    /// SQL of the `#[cfg(feature = "...")]` branches not selected for this build, with every
+    /// feature flipped on its own, as (converted_sql, label)
+    /// Only checked to be valid, the generated code uses the selected branch
+    #[serde(skip)]
+    pub cfg_branches: Vec<(String, String)>,
     /// Optional description of what the query does
     pub description: Option<String>,
     /// Module name where this function should be generated
+    #[serde(skip)]
     pub module: String,
     /// Expected result type - controls fetch method and error handling
     /// Defaults to "exactly_one" if not specified
@@ -127,7 +830,13 @@ pub(crate) struct QueryDefinition {
     /// Optional per-query field type mappings
     /// Key: field name (e.g., "profile", "metadata", "status")
     /// Value: Rust type to use (e.g., "crate::models::UserProfile", "MyStruct")
+    #[serde(serialize_with = "serialize_sorted_types")]
     pub types: Option<HashMap<String, String>>,
+    /// PostgreSQL types of parameters whose type is inferred wrongly or cannot be inferred
+    /// Every use of the parameter is cast to the type, e.g. `#{user_id}::uuid`, so that the
+    /// function signature takes the matching Rust type
+    /// Defaults to empty (parameter types are inferred by PostgreSQL)
+    pub param_types: BTreeMap<String, String>,
     /// Optional telemetry configuration for this query
     pub telemetry: QueryTelemetryConfig,
     /// Whether to analyze this query's performance (overrides global setting)
@@ -149,10 +858,20 @@ pub(crate) struct QueryDefinition {
     /// Ignored if conditions_type is enabled
     /// Defaults to false
     pub parameters_type: ParametersType,
+    /// Type of enum filtering the rows by the conditions of the WHERE clause
+    /// When true, each `column op #{param}` condition becomes a variant of a {QueryName}Filter
+    /// enum and the function takes `Vec<{QueryName}Filter>`, combining the given filters with AND
+    /// When a string, generates an enum with the given name
+    /// Defaults to false
+    pub filter_type: FilterType,
     /// Type of struct to use for return values
     /// When None or not specified, uses default {QueryName}Item naming
     /// When Some(name), uses or generates a struct with the given name
     pub return_type: Option<String>,
+    /// Shape of the returned rows for multi-column results
+    /// When Tuple, rows are returned as tuples in column order instead of a struct
+    /// Defaults to Struct
+    pub return_shape: ReturnShape,
     /// Type of constraint enum to use for errors
     /// When None or not specified, uses default {QueryName}Constraints naming
     /// When Some(name), uses or generates a constraint enum with the given name
@@ -170,14 +889,269 @@ pub(crate) struct QueryDefinition {
     /// e.g., ["serde::Serialize", "serde::Deserialize"]
     /// Empty vec means no additional derives
     pub return_type_derives: Vec<String>,
+    /// JSON columns with a `types:` mapping whose fields are serialized inline in the result
+    /// struct with `#[serde(flatten)]`, e.g. [profile]
+    /// Requires serde::Serialize or serde::Deserialize in `return_type_derives`
+    /// Defaults to empty
+    pub flatten: Vec<String>,
     /// Additional derive traits to add to the error constraint enum
     /// e.g., ["serde::Serialize", "serde::Deserialize"]
     /// Empty vec means no additional derives
     pub error_type_derives: Vec<String>,
+    /// Whether the Params, Record and Item structs derive PartialEq, Eq, Hash, PartialOrd and Ord
+    /// when every field type implements them
+    /// Defaults to DefaultsConfig::auto_derives
+    pub auto_derives: bool,
+    /// Whether to generate `to_map()`/`from_map()` conversions on the Params and Item structs
+    /// The generated impls are gated behind the `dynamic` feature of the consuming crate
+    /// Defaults to false
+    pub dynamic: bool,
+    /// Minimum PostgreSQL major version required by this query (e.g. 15 for MERGE)
+    /// Defaults to the global setting (None means no requirement)
+    pub min_pg_version: Option<u32>,
+    /// Chunked iteration configuration
+    /// When set, `for_each_*`/`try_for_each_*` helpers are generated in addition to the query function
+    /// Defaults to None
+    pub iterate: Option<IterateConfig>,
+    /// Realistic parameter values to bind when running EXPLAIN instead of dummy values
+    /// Key: parameter name (without '?' suffix)
+    /// Value: YAML scalar, type-checked against the extracted parameter type
+    /// Empty map means dummy values are used for all parameters
+    #[serde(serialize_with = "serialize_sorted")]
+    pub analysis_params: HashMap<String, serde_yaml::Value>,
+    /// Performance checks from `fail_on` which are intentionally not enforced for this query
+    /// Defaults to empty (all configured checks apply)
+    pub allow: Vec<PerformanceCheck>,
+    /// Plan estimates of a previous analysis, compared with the current plan
+    /// Only applies to read-only queries
+    /// Defaults to None (no regression check)
+    pub baseline: Option<PlanBaseline>,
+    /// Diagnostic codes whose warnings are not reported for this query, e.g. AM003
+    /// Listed in `allow` together with the performance checks
+    /// Defaults to empty (all warnings are reported)
+    pub allow_diagnostics: Vec<crate::diagnostics::DiagnosticCode>,
+    /// Rust type for NUMERIC output columns, for the whole query or per column
+    /// Defaults to None (NUMERIC columns are rust_decimal::Decimal)
+    pub numeric_as: Option<NumericAs>,
+    /// ISO 4217 currency of NUMERIC output columns and parameters, e.g. "EUR"
+    /// Key: output column or parameter name, generated as `Money` of that currency
+    /// Defaults to empty (NUMERIC columns are rust_decimal::Decimal)
+    pub currency_columns: BTreeMap<String, String>,
+    /// Rust type of CHAR(1) or "char" flag output columns and parameters, e.g. `bool("Y", "N")`
+    /// Key: output column or parameter name, converted from and to the codes of the column
+    /// Defaults to empty (CHAR(1) columns are String, "char" columns i8)
+    pub char_flags: BTreeMap<String, CharFlag>,
+    /// Whether the generated function can sample its query plan at runtime with EXPLAIN ANALYZE
+    /// Only applies to read-only queries
+    /// Defaults to DefaultsConfig::plan_sampling
+    pub plan_sampling: bool,
+    /// Whether the generated function reports calls returning an order of magnitude more or
+    /// fewer rows than the planner estimated at generation time
+    /// Only applies to read-only `multiple` and `at_least_one` queries
+    /// Defaults to DefaultsConfig::cardinality_drift
+    pub cardinality_drift: bool,
+    /// Whether to generate a `{query}_blocking` wrapper for callers without a tokio runtime
+    /// The wrapper is gated behind the `blocking` feature of the consuming crate
+    /// Defaults to DefaultsConfig::blocking
+    pub blocking: bool,
+    /// Whether to generate a `{query}_cancellable` variant taking a pool and a tokio-util
+    /// `CancellationToken`, which cancels the query on the server when the token is cancelled
+    /// The variant is gated behind the `cancellation` feature of the consuming crate
+    /// Defaults to DefaultsConfig::cancellable
+    pub cancellable: bool,
+    /// Whether the generated function takes a `Session` pinning a pooled connection instead of
+    /// any executor, for multi-step flows relying on temp tables or session settings
+    /// Defaults to false
+    pub requires_session: bool,
+    /// Whether DELETE queries also get a `{query}_preview` function counting the rows they would
+    /// delete and the rows referencing them through foreign keys
+    /// Defaults to false
+    pub cascade_preview: bool,
+    /// Tables of a DELETE query moving the deleted rows into an archive table in the same
+    /// statement, with the function returning the archived rows
+    /// Defaults to None
+    pub archive_to: Option<ArchiveTables>,
+    /// Whether INSERT ... RETURNING queries also get a `{query}_id_only` function returning only
+    /// the primary key of the inserted row, skipping the decoding of the other columns
+    /// Defaults to false
+    pub id_only: bool,
+    /// Whether INSERT ... RETURNING queries also get a `{query}_retrying` function running the
+    /// insert in a savepoint and, on a unique violation caused by a concurrent insert, returning
+    /// the conflicting row looked up by the values of the violated constraint
+    /// Defaults to None
+    pub retrying: Option<Retrying>,
+    /// Builds the function is compiled into, `test` or `bench` keeping fixture and setup queries
+    /// out of production binaries; all queries of a module must have the same scope
+    /// Defaults to all
+    pub scope: QueryScope,
+    /// Whether single-row `UPDATE ... WHERE ...` queries also get a `{query}_bulk` function
+    /// taking a Vec of `{Query}BulkRecord` records, updating the row of every record with one
+    /// statement joining `UNNEST` of an array per parameter
+    /// Defaults to false
+    pub bulk_update: bool,
+    /// Whether the generated function prepares the statement, false runs it with the simple query
+    /// protocol, e.g. for `SET` commands, scripts of several statements or connection poolers
+    /// which cannot keep prepared statements
+    /// Defaults to true
+    pub prepare: bool,
+    /// Whether the result struct is mirrored as a proto3 message in the `{module}.proto` file
+    /// written to `DefaultsConfig::emit_proto`
+    /// Defaults to false
+    pub proto: bool,
+    /// Whether the application caches the results of the query, so that its tables get triggers
+    /// notifying `cache_invalidation::CHANNEL` in the migrations written to
+    /// `DefaultsConfig::cache_invalidation_migrations`
+    /// Only applies to read-only queries
+    /// Defaults to false
+    pub cache_invalidation: bool,
+    /// NOTIFY channel clearing the results cached by the generated `{query}_cached` function
+    /// Only applies to read-only queries
+    /// Defaults to None (no cached function)
+    pub cache_invalidate_on: Option<CacheInvalidateOn>,
+    /// Runtime flag the generated function checks before running the query, returning
+    /// `FeatureDisabled` while the flag is off, e.g. "new_billing"
+    /// Defaults to None (no flag check)
+    pub feature_flag: Option<String>,
+    /// Whether the generated function is also gated on the cargo feature named after the
+    /// `feature_flag`, with a stub returning `FeatureDisabled` when the feature is not enabled
+    /// Defaults to false
+    pub feature_flag_cfg: bool,
+    /// Execution time in milliseconds above which the generated function emits a
+    /// `tracing::warn!` slow query event, with the parameters of `telemetry.include_params`
+    /// Defaults to DefaultsConfig::slow_query_ms (None means no slow query events)
+    pub slow_query_ms: Option<u64>,
+    /// How array parameters used with `= ANY(#{param})` are passed to the server
+    /// Defaults to Bind
+    pub large_list_strategy: LargeListStrategy,
+    /// Schema properties the query relies on, verified against the database during analysis
+    /// Defaults to empty (nothing is verified)
+    #[serde(serialize_with = "serialize_assumes")]
+    pub assumes: Vec<SchemaAssumption>,
+    /// Name of the function argument selecting the table shard substituted for `${shard}`
+    /// When set, the generated function validates the key and formats it into the SQL at runtime
+    /// Defaults to None (the query is not sharded)
+    pub shard_by: Option<String>,
+    /// Shard substituted for `${shard}` when analyzing the query, e.g. "0" for `orders_0`
+    /// Defaults to DefaultsConfig::canonical_shard
+    pub canonical_shard: Option<String>,
+    /// Group commit batching for single-row inserts
+    /// When set, `{query}_batcher`/`{query}_batched` are generated in addition to the query function
+    /// Defaults to None
+    pub batch: Option<BatchConfig>,
+    /// Text columns whose CHECK constraint values are exposed as a Rust enum or constant
+    /// Key: table.column, matched by column name against the parameters and output columns
+    /// Defaults to empty
+    pub check_values: BTreeMap<String, CheckValuesStyle>,
+    /// Function applied to each result row before it is returned, `fn(Item) -> Item`
+    /// e.g. "crate::mappers::redact_user"
+    /// Defaults to None
+    pub map_row: Option<String>,
+    /// Fallible function applied to each result row, `fn(Item) -> Result<Item, E>`
+    /// Errors are returned as `sqlx::Error::Decode`, so E must convert into `Box<dyn Error + Send + Sync>`
+    /// Defaults to None
+    pub try_map_row: Option<String>,
+    /// Query of the same module inserting the row when `expect: exactly_one_or_insert` finds none
+    /// Required by and only allowed with `expect: exactly_one_or_insert`
+    /// Defaults to None
+    pub insert: Option<String>,
+    /// Unit of work of the module the query is a step of, e.g. "registration"
+    /// The tagged queries of a module run in file order in one transaction of the generated
+    /// `perform_{unit}` function
+    /// Defaults to None
+    pub unit_of_work: Option<String>,
+    /// Schemas set as the search_path when analyzing the query, e.g. [public, extensions]
+    /// The connections running the generated function need the same search_path
+    /// Defaults to empty (the search_path of the analysis connection)
+    pub search_path: Vec<String>,
+    /// Whether the generated function sets the search_path itself, running the statement in a
+    /// transaction after `SET LOCAL search_path`, instead of relying on the connection
+    /// Requires search_path; the function then takes an executor it can begin a transaction on
+    /// Defaults to false
+    pub search_path_at_runtime: bool,
+    /// Message of the `NotFound` error returned instead of `RowNotFound` when an `exactly_one` or
+    /// `at_least_one` query finds no rows, e.g. "user {user_id} missing"
+    /// `{param}` placeholders are replaced with the Debug representation of the parameter values
+    /// Defaults to None (RowNotFound)
+    pub not_found_message: Option<String>,
+    /// Parameters and output columns holding sensitive data, e.g. { email: pii, token: secret }
+    /// Their struct fields are documented with the classification and listed in the module's
+    /// `SENSITIVE_FIELDS`, and they are never recorded in tracing spans
+    /// Defaults to empty
+    pub sensitivity: BTreeMap<String, Sensitivity>,
+    /// Whether structs with sensitive fields implement Debug printing `[redacted]` for them
+    /// instead of deriving it
+    /// Defaults to false
+    pub redact_debug: bool,
+    /// Whether to generate a test running the query in two interleaved SERIALIZABLE transactions,
+    /// asserting that serialization failures occur and that retrying them completes
+    /// Requires analysis_params values for all parameters, which are inlined into the SQL
+    /// Defaults to false
+    pub isolation_sensitive: bool,
+    /// Database the query is analyzed against, from DefaultsConfig::dialect
+    pub dialect: Dialect,
+    /// Major version of sqlx the generated code compiles against, from DefaultsConfig::sqlx_version
+    #[serde(skip)]
+    pub sqlx_version: SqlxVersion,
+    /// Last modification time of the SQL file, for analyzing recently modified files first
+    #[serde(skip)]
+    pub modified: Option<std::time::SystemTime>,
+    /// SQL file the query was loaded from, None for queries generated from introspection
+    #[serde(skip)]
+    pub source_file: Option<std::path::PathBuf>,
+}
+
+/// Quoted schemas of a search_path, for `SET search_path TO`
+pub(crate) fn search_path_list(search_path: &[String]) -> String {
+    search_path
+        .iter()
+        .map(|schema| format!("\"{}\"", schema.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Serialize a map with its keys in sorted order
+fn serialize_sorted<V: Serialize, S: Serializer>(
+    map: &HashMap<String, V>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+}
+
+/// Serialize type mappings with their fields in sorted order
+fn serialize_sorted_types<S: Serializer>(
+    types: &Option<HashMap<String, String>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    types
+        .as_ref()
+        .map(|types| types.iter().collect::<BTreeMap<_, _>>())
+        .serialize(serializer)
+}
+
+/// Serialize schema assumptions in their metadata form, e.g. `users.email: unique not_null`
+fn serialize_assumes<S: Serializer>(
+    assumes: &[SchemaAssumption],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    assumes
+        .iter()
+        .map(|assumption| {
+            let expectations: Vec<String> = assumption
+                .expectations
+                .iter()
+                .map(|expectation| expectation.to_string())
+                .collect();
+            (
+                format!("{}.{}", assumption.table, assumption.column),
+                expectations.join(" "),
+            )
+        })
+        .collect::<BTreeMap<_, _>>()
+        .serialize(serializer)
 }
 
 /// Per-query telemetry configuration
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize)]
 pub(crate) struct QueryTelemetryConfig {
     /// Override global telemetry level for this query
     pub level: TelemetryLevel,
@@ -187,4 +1161,17 @@ pub(crate) struct QueryTelemetryConfig {
     /// Whether to include the SQL query as a field in the span
     /// Defaults to false
     pub include_sql: bool,
+    /// Characters of the Debug representation of a recorded parameter kept
+    /// Defaults to DefaultsTelemetryConfig::max_param_length
+    pub max_param_length: Option<usize>,
+    /// Elements of a recorded list parameter kept
+    /// Defaults to DefaultsTelemetryConfig::max_param_elements
+    pub max_param_elements: Option<usize>,
+}
+
+impl QueryTelemetryConfig {
+    /// Whether recorded parameters are cut to a length or number of elements
+    pub fn limits_params(&self) -> bool {
+        self.max_param_length.is_some() || self.max_param_elements.is_some()
+    }
 }
@@ -1,10 +1,12 @@
 /// Structures for holding complete query analysis results from Phase 1
 /// This separates query analysis (DB interaction) from code generation
+use crate::diagnostics::Diagnostic;
 use crate::query_definition::QueryDefinition;
 use crate::types_extractor::{ConstraintInfo, QueryTypeInfo};
+use serde::{Deserialize, Serialize};
 
 /// Pre-computed EXPLAIN query parameters for a single query variant
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExplainParams {
     /// The EXPLAIN SQL query with special params inlined and remaining params renumbered
     pub explain_sql: String,
@@ -59,7 +61,7 @@ pub struct QueryDefinitionRuntime {
 }
 
 /// Performance analysis results from EXPLAIN
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub struct PerformanceAnalysis {
     /// Query name for warnings
@@ -71,8 +73,31 @@ pub struct PerformanceAnalysis {
     /// Tables that are being sequentially scanned
     pub sequential_scan_tables: Vec<String>,
 
+    /// Sequentially scanned tables whose rows are filtered, which suggests a missing index
+    #[serde(default)]
+    pub missing_index_tables: Vec<String>,
+
+    /// Subqueries which could be rewritten as joins, as the plan evidence of each
+    /// e.g. "NOT IN subquery `Filter: (NOT (hashed SubPlan 1))`"
+    #[serde(default)]
+    pub subquery_rewrites: Vec<String>,
+
+    /// Statements creating the indexes suggested by the analysis, e.g. trigram indexes for
+    /// ILIKE filters of sequentially scanned tables
+    #[serde(default)]
+    pub index_suggestions: Vec<String>,
+
+    /// Partial and expression indexes of sequentially scanned tables which the plan did not
+    /// use, each with the reason, e.g. "partial index 'idx' on table 't', its predicate ..."
+    #[serde(default)]
+    pub ignored_indexes: Vec<String>,
+
+    /// Highest estimated total cost of the query plan across all variants
+    #[serde(default)]
+    pub total_cost: Option<f64>,
+
     /// Other performance warnings
-    pub warnings: Vec<String>,
+    pub warnings: Vec<Diagnostic>,
 
     /// Full query execution plan from EXPLAIN
     pub query_plan: Option<String>,
@@ -0,0 +1,44 @@
+/// Signatures of the generated query functions, written next to the generated code so that the
+/// `#[automodel::query]` macro checks bound methods against what was generated instead of
+/// re-reading the SQL
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// File name of the registry inside the output directory
+pub const QUERY_REGISTRY_FILE_NAME: &str = "automodel.queries.json";
+
+/// Generated function of a query, keyed by "module::query" in the registry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RegisteredQuery {
+    /// SQL file of the query as found when generating, e.g.
+    /// "queries/users/07_find_user_by_email.sql", None for queries generated from introspection
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Arguments of the function after its executor, in order
+    pub params: Vec<RegisteredParam>,
+}
+
+/// Argument of a generated query function
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RegisteredParam {
+    /// Argument name, e.g. "email"
+    pub name: String,
+    /// Rust type as generated, e.g. "String"
+    #[serde(rename = "type")]
+    pub rust_type: String,
+}
+
+/// Write the registry into the output directory, only rewriting the file when it changes
+pub(crate) fn write_registry(
+    output_path: &Path,
+    registry: &BTreeMap<String, RegisteredQuery>,
+) -> anyhow::Result<()> {
+    let file = output_path.join(QUERY_REGISTRY_FILE_NAME);
+    let content = serde_json::to_string_pretty(registry)? + "\n";
+    if std::fs::read_to_string(&file).ok().as_deref() == Some(content.as_str()) {
+        return Ok(());
+    }
+    std::fs::write(&file, content)?;
+    Ok(())
+}
@@ -0,0 +1,126 @@
+/// Verification of the schema assumptions listed in a query's `assumes` metadata
+use crate::query_definition::{ColumnAssumption, QueryDefinition, SchemaAssumption};
+use crate::types_extractor::qualify_table;
+use anyhow::Result;
+
+/// Column properties looked up in the system catalogs
+const COLUMN_INTROSPECTION_SQL: &str = r"
+SELECT
+    NOT a.attnotnull AS is_nullable,
+    t.typname::text AS type_name,
+    format_type(a.atttypid, a.atttypmod) AS type_with_modifier,
+    format_type(a.atttypid, NULL) AS type_without_modifier,
+    EXISTS (
+        SELECT 1 FROM pg_index i
+        WHERE i.indrelid = a.attrelid
+          AND i.indisunique
+          AND i.indnkeyatts = 1
+          AND i.indkey[0] = a.attnum
+          AND i.indpred IS NULL
+    ) AS is_unique,
+    EXISTS (
+        SELECT 1 FROM pg_index i
+        WHERE i.indrelid = a.attrelid
+          AND i.indkey[0] = a.attnum
+    ) AS is_indexed
+FROM pg_attribute a
+JOIN pg_type t ON t.oid = a.atttypid
+WHERE a.attrelid = to_regclass($1::text)
+  AND a.attname = $2::text
+  AND a.attnum > 0
+  AND NOT a.attisdropped";
+
+/// Check every `assumes` entry of the query against the database schema
+/// Fails with one line per violated assumption
+pub async fn verify_schema_assumptions(
+    client: &tokio_postgres::Client,
+    query: &QueryDefinition,
+) -> Result<()> {
+    let mut violations = Vec::new();
+    for assumption in &query.assumes {
+        violations.extend(check_assumption(client, assumption).await?);
+    }
+
+    if !violations.is_empty() {
+        anyhow::bail!(
+            "Query '{}' schema assumptions do not hold:\n{}",
+            query.name,
+            violations.join("\n")
+        );
+    }
+    Ok(())
+}
+
+/// Violations of a single assumption, formatted as `  - table.column: ...`
+async fn check_assumption(
+    client: &tokio_postgres::Client,
+    assumption: &SchemaAssumption,
+) -> Result<Vec<String>> {
+    let location = format!("{}.{}", assumption.table, assumption.column);
+
+    let table = qualify_table(&assumption.table);
+
+    let table_exists: bool = client
+        .query_one("SELECT to_regclass($1::text) IS NOT NULL", &[&table])
+        .await?
+        .get(0);
+    if !table_exists {
+        return Ok(vec![format!(
+            "  - {}: table '{}' does not exist",
+            location, assumption.table
+        )]);
+    }
+
+    let Some(row) = client
+        .query_opt(COLUMN_INTROSPECTION_SQL, &[&table, &assumption.column])
+        .await?
+    else {
+        return Ok(vec![format!(
+            "  - {}: column '{}' does not exist",
+            location, assumption.column
+        )]);
+    };
+
+    let is_nullable: bool = row.get("is_nullable");
+    let type_names: [String; 3] = [
+        row.get("type_name"),
+        row.get("type_with_modifier"),
+        row.get("type_without_modifier"),
+    ];
+    let is_unique: bool = row.get("is_unique");
+    let is_indexed: bool = row.get("is_indexed");
+
+    let mut violations = Vec::new();
+    for expectation in &assumption.expectations {
+        let violation = match expectation {
+            ColumnAssumption::Nullable if !is_nullable => {
+                Some("expected nullable, but the column is NOT NULL".to_string())
+            }
+            ColumnAssumption::NotNull if is_nullable => {
+                Some("expected not_null, but the column allows NULL".to_string())
+            }
+            ColumnAssumption::Unique if !is_unique => Some(
+                "expected unique, but no unique index or constraint covers only this column"
+                    .to_string(),
+            ),
+            ColumnAssumption::Indexed if !is_indexed => {
+                Some("expected indexed, but no index starts with this column".to_string())
+            }
+            ColumnAssumption::Type(expected)
+                if !type_names
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(expected)) =>
+            {
+                Some(format!(
+                    "expected type {}, but the column is {} ({})",
+                    expected, type_names[0], type_names[1]
+                ))
+            }
+            _ => None,
+        };
+        if let Some(violation) = violation {
+            violations.push(format!("  - {}: {}", location, violation));
+        }
+    }
+    Ok(violations)
+}
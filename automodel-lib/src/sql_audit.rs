@@ -0,0 +1,833 @@
+/// Audit of the SQL the generated functions assemble at runtime, so that a feature interpolating
+/// values which are not validated into a query fails generation instead of shipping an injection
+/// path
+///
+/// Every argument of `sqlx::query`, `sqlx::query_as`, `sqlx::query_scalar` and `sqlx::raw_sql`
+/// is traced back through the assignments of its function, and must be built only from string
+/// literals, numbers such as placeholder indices, and the output of the validating shared helpers
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+/// Shared helpers returning SQL, with the number of leading arguments holding SQL text
+/// Their other arguments are validated by the helper, e.g. the shard key of `shard_sql`
+const VALIDATED_HELPERS: [(&str, usize); 2] = [
+    ("super::sharding::shard_sql", 1),
+    ("super::batching::renumber_params", 2),
+];
+
+/// Parameter types holding numbers, which cannot alter the SQL they are formatted into
+const NUMBER_TYPES: [&str; 12] = [
+    "usize", "isize", "u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64", "u128", "i128",
+];
+
+/// Methods returning a number whatever their receiver holds
+const NUMBER_METHODS: [&str; 3] = ["len", "count", "is_empty"];
+
+/// Methods returning text built only from their receiver and arguments
+const TEXT_METHODS: [&str; 9] = [
+    "to_string",
+    "to_owned",
+    "clone",
+    "as_str",
+    "as_ref",
+    "into",
+    "trim",
+    "replace",
+    "replacen",
+];
+
+/// Deepest chain of assignments and helper functions followed for one argument
+const MAX_DEPTH: usize = 32;
+
+/// Result of auditing generated code
+#[derive(Debug, Clone, Default)]
+pub struct SqlAudit {
+    /// Functions passing SQL assembled at runtime to sqlx, e.g. for conditional blocks or shards
+    pub dynamic_sql_functions: Vec<String>,
+    /// Values reaching the SQL of a query which could not be traced to literals, numbers or the
+    /// validating shared helpers
+    pub findings: Vec<SqlAuditFinding>,
+}
+
+/// Value reaching the SQL of a query without validation
+#[derive(Debug, Clone, PartialEq)]
+pub struct SqlAuditFinding {
+    /// Function passing the SQL to sqlx
+    pub function: String,
+    /// Expression the value comes from, with string literals shown as `""`
+    pub expression: String,
+}
+
+impl std::fmt::Display for SqlAuditFinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "function '{}' passes `{}` into its SQL",
+            self.function, self.expression
+        )
+    }
+}
+
+impl SqlAudit {
+    /// Whether no value reaches the SQL without validation
+    pub fn is_safe(&self) -> bool {
+        self.findings.is_empty()
+    }
+}
+
+/// Audit the SQL assembly of generated Rust code, e.g. a module with its part files
+/// Functions of `#[cfg(test)]` modules are skipped, they only run against test databases
+pub fn audit_generated_sql(code: &str) -> SqlAudit {
+    let masked = mask_literals(code);
+    let functions = functions(&masked);
+    let auditor = Auditor {
+        code: &masked,
+        functions: &functions,
+    };
+
+    let mut audit = SqlAudit::default();
+    for function in functions.iter().filter(|function| !function.is_test) {
+        let mut is_dynamic = false;
+        for argument in sink_arguments(function.body) {
+            if argument.trim() != "\"\"" {
+                is_dynamic = true;
+            }
+            let mut visiting = HashSet::new();
+            if let Err(expression) = auditor.check(argument, function.body, &mut visiting, 0) {
+                let finding = SqlAuditFinding {
+                    function: function.name.to_string(),
+                    expression: compact(expression),
+                };
+                if !audit.findings.contains(&finding) {
+                    audit.findings.push(finding);
+                }
+            }
+        }
+        if is_dynamic
+            && !audit
+                .dynamic_sql_functions
+                .iter()
+                .any(|name| name == function.name)
+        {
+            audit.dynamic_sql_functions.push(function.name.to_string());
+        }
+    }
+    audit
+}
+
+/// Function of the audited code
+struct Function<'a> {
+    name: &'a str,
+    /// Code between the parentheses of the parameters
+    parameters: &'a str,
+    /// Code between the braces of the body
+    body: &'a str,
+    /// Whether the function is inside a `#[cfg(test)]` module
+    is_test: bool,
+}
+
+/// Traces the values reaching SQL through the masked code
+struct Auditor<'a> {
+    code: &'a str,
+    functions: &'a [Function<'a>],
+}
+
+impl<'a> Auditor<'a> {
+    /// Check that an expression of a function body only holds validated text
+    /// Returns the innermost expression which is not validated
+    fn check(
+        &self,
+        expr: &'a str,
+        body: &'a str,
+        visiting: &mut HashSet<String>,
+        depth: usize,
+    ) -> Result<(), &'a str> {
+        if depth > MAX_DEPTH {
+            return Err(expr);
+        }
+        let expr = strip_prefix_operators(expr.trim());
+        let expr = expr.trim_end_matches('?').trim();
+        if expr.is_empty() {
+            return Err(expr);
+        }
+
+        let operands = split_binary(expr);
+        if operands.len() > 1 {
+            for operand in operands {
+                self.check(operand, body, visiting, depth + 1)?;
+            }
+            return Ok(());
+        }
+
+        if expr == "\"\"" || expr == "' '" || expr.starts_with(|ch: char| ch.is_ascii_digit()) {
+            return Ok(());
+        }
+        if expr.starts_with('(') && closing(expr, 0) == Some(expr.len() - 1) {
+            return self.check(&expr[1..expr.len() - 1], body, visiting, depth + 1);
+        }
+        // Conditions and scrutinees choose between the branches without reaching the SQL
+        if expr.starts_with("if ") && expr.ends_with('}') {
+            for branch in branches(expr) {
+                for result in results(branch) {
+                    self.check(result, body, visiting, depth + 1)?;
+                }
+            }
+            return Ok(());
+        }
+        if expr.starts_with("match ") && expr.ends_with('}') {
+            for branch in match_arms(expr) {
+                for result in results(branch) {
+                    self.check(result, body, visiting, depth + 1)?;
+                }
+            }
+            return Ok(());
+        }
+
+        let Some(chain) = parse_chain(expr) else {
+            return Err(expr);
+        };
+
+        // Numbers do not depend on the receiver of their method
+        if chain
+            .methods
+            .iter()
+            .any(|(method, _)| NUMBER_METHODS.contains(method))
+        {
+            return Ok(());
+        }
+        // Methods of the generated code, e.g. the conditions of `conditions_type` filters, only
+        // pass their receiver into their results as `self`
+        let mut is_receiver_returned = true;
+        for (method, arguments) in &chain.methods {
+            if TEXT_METHODS.contains(method) {
+                for argument in arguments {
+                    self.check(argument, body, visiting, depth + 1)?;
+                }
+            } else {
+                self.check_call(method, expr, arguments, body, visiting, depth)?;
+                is_receiver_returned = false;
+            }
+        }
+        if !is_receiver_returned {
+            return Ok(());
+        }
+
+        match (chain.is_macro, &chain.arguments) {
+            (true, Some(arguments)) => {
+                // Formatted text is validated if its template is a literal and its values are
+                if chain.path != "format" || arguments.first().map(|a| a.trim()) != Some("\"\"") {
+                    return Err(expr);
+                }
+                for argument in &arguments[1..] {
+                    let value = match argument.split_once('=') {
+                        Some((name, value)) if is_identifier(name.trim()) => value,
+                        _ => argument,
+                    };
+                    self.check(value, body, visiting, depth + 1)?;
+                }
+                Ok(())
+            }
+            (true, None) => Err(expr),
+            (false, Some(arguments)) => {
+                self.check_call(chain.path, expr, arguments, body, visiting, depth)
+            }
+            (false, None) => self.check_value(chain.path, expr, body, visiting, depth),
+        }
+    }
+
+    /// Check the text returned by a call
+    fn check_call(
+        &self,
+        path: &'a str,
+        expr: &'a str,
+        arguments: &[&'a str],
+        body: &'a str,
+        visiting: &mut HashSet<String>,
+        depth: usize,
+    ) -> Result<(), &'a str> {
+        if let Some((_, sql_arguments)) =
+            VALIDATED_HELPERS.iter().find(|(helper, _)| *helper == path)
+        {
+            for argument in arguments.iter().take(*sql_arguments) {
+                self.check(argument, body, visiting, depth + 1)?;
+            }
+            return Ok(());
+        }
+        if matches!(
+            path,
+            "String::from" | "String::new" | "String::with_capacity"
+        ) {
+            for argument in arguments {
+                self.check(argument, body, visiting, depth + 1)?;
+            }
+            return Ok(());
+        }
+
+        // Functions of the generated code return validated text if all their results are
+        let candidates: Vec<&Function> = self
+            .functions
+            .iter()
+            .filter(|function| function.name == path)
+            .collect();
+        if candidates.is_empty() {
+            return Err(expr);
+        }
+        // Recursive functions are checked once
+        if !visiting.insert(format!("fn {}", path)) {
+            return Ok(());
+        }
+        for function in candidates {
+            let mut function_visiting = visiting.clone();
+            for result in results(function.body) {
+                self.check(result, function.body, &mut function_visiting, depth + 1)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check a variable, from all its assignments in the function body, or a constant
+    fn check_value(
+        &self,
+        path: &'a str,
+        expr: &'a str,
+        body: &'a str,
+        visiting: &mut HashSet<String>,
+        depth: usize,
+    ) -> Result<(), &'a str> {
+        let name = path.rsplit("::").next().unwrap_or(path);
+        if name
+            .chars()
+            .all(|ch| ch.is_ascii_uppercase() || ch.is_ascii_digit() || ch == '_')
+        {
+            let constant = constant_value(self.code, name).ok_or(expr)?;
+            return self.check(constant, body, visiting, depth + 1);
+        }
+        if !is_identifier(path) {
+            return Err(expr);
+        }
+        // Assignments of a variable referring to itself are checked once
+        if !visiting.insert(path.to_string()) {
+            return Ok(());
+        }
+        let assignments = assignments(body, path);
+        if assignments.is_empty() {
+            // Integer parameters only hold numbers, other parameters and bindings hold values of
+            // unknown origin
+            let is_number = self
+                .functions
+                .iter()
+                .find(|function| std::ptr::eq(function.body, body))
+                .and_then(|function| parameter_type(function.parameters, path))
+                .is_some_and(|ty| NUMBER_TYPES.contains(&ty));
+            return if is_number { Ok(()) } else { Err(expr) };
+        }
+        for assignment in assignments {
+            self.check(assignment, body, visiting, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// Postfix chain of an expression, e.g. `final_sql.replacen("", "", 1)`
+struct Chain<'e> {
+    /// Path of the variable, function or macro, e.g. `super::sharding::shard_sql` or `format`
+    path: &'e str,
+    is_macro: bool,
+    /// Arguments of the call of the path, None if it is not called
+    arguments: Option<Vec<&'e str>>,
+    /// Methods called on the result with their arguments
+    methods: Vec<(&'e str, Vec<&'e str>)>,
+}
+
+/// Parse a path with an optional call followed by method calls, None for other expressions
+fn parse_chain(expr: &str) -> Option<Chain<'_>> {
+    let bytes = expr.as_bytes();
+    let mut end = 0;
+    while end < bytes.len() {
+        let ch = bytes[end] as char;
+        if ch.is_ascii_alphanumeric() || ch == '_' {
+            end += 1;
+        } else if expr[end..].starts_with("::<") {
+            end = closing(expr, end + 2)? + 1;
+        } else if expr[end..].starts_with("::") {
+            end += 2;
+        } else {
+            break;
+        }
+    }
+    if end == 0 {
+        return None;
+    }
+    let path = &expr[..end];
+    let mut rest = &expr[end..];
+    let is_macro = rest.starts_with('!');
+    if is_macro {
+        rest = &rest[1..];
+    }
+
+    let mut offset = expr.len() - rest.len();
+    let arguments = if rest.starts_with('(') {
+        let close = closing(expr, offset)?;
+        let arguments = split_top_level(&expr[offset + 1..close]);
+        offset = close + 1;
+        Some(arguments)
+    } else {
+        None
+    };
+
+    let mut methods = Vec::new();
+    loop {
+        let rest = expr[offset..].trim_start();
+        offset = expr.len() - rest.len();
+        if let Some(after) = rest.strip_prefix('?') {
+            offset = expr.len() - after.len();
+            continue;
+        }
+        let Some(after_dot) = rest.strip_prefix('.') else {
+            break;
+        };
+        let name_len = after_dot
+            .find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_'))
+            .unwrap_or(after_dot.len());
+        let name_start = offset + 1;
+        let name = &expr[name_start..name_start + name_len];
+        offset = name_start + name_len;
+        if expr[offset..].starts_with('(') {
+            let close = closing(expr, offset)?;
+            methods.push((name, split_top_level(&expr[offset + 1..close])));
+            offset = close + 1;
+        } else {
+            // Fields hold values of unknown origin
+            return None;
+        }
+    }
+    if !expr[offset..].trim().is_empty() {
+        return None;
+    }
+    Some(Chain {
+        path,
+        is_macro,
+        arguments,
+        methods,
+    })
+}
+
+/// Arguments of the sqlx calls taking SQL text in a function body
+fn sink_arguments(body: &str) -> Vec<&str> {
+    static SINK: OnceLock<regex::Regex> = OnceLock::new();
+    let sink = SINK.get_or_init(|| {
+        regex::Regex::new(r"\bsqlx::(?:query|query_as|query_scalar|raw_sql)\b\s*(?:::\s*<)?")
+            .unwrap()
+    });
+    let mut arguments = Vec::new();
+    for found in sink.find_iter(body) {
+        let mut open = found.end();
+        if found.as_str().ends_with('<') {
+            let Some(close) = closing(body, open - 1) else {
+                continue;
+            };
+            open = close + 1;
+        }
+        let Some(open) = body[open..].find('(').map(|i| open + i) else {
+            continue;
+        };
+        if let Some(close) = closing(body, open) {
+            if let Some(first) = split_top_level(&body[open + 1..close]).first() {
+                arguments.push(*first);
+            }
+        }
+    }
+    arguments
+}
+
+/// Values assigned to a variable in a function body: `let` bindings, assignments, `push_str`
+/// and `push` arguments and the iterated values of `for` loops
+fn assignments<'b>(body: &'b str, name: &str) -> Vec<&'b str> {
+    let name = regex::escape(name);
+    let patterns = [
+        format!(r"\blet\s+(?:mut\s+)?{}\s*(?::[^=;]*)?=", name),
+        format!(r"(?:^|[;{{}}\s]){}\s*\+?=", name),
+        format!(r"\bfor\s+{}\s+in\b", name),
+    ];
+    let mut values = Vec::new();
+    for (i, pattern) in patterns.iter().enumerate() {
+        let Ok(pattern) = regex::Regex::new(pattern) else {
+            continue;
+        };
+        for found in pattern.find_iter(body) {
+            let start = found.end();
+            // Comparisons and match arms are not assignments
+            if body[start..].starts_with(['=', '>']) {
+                continue;
+            }
+            let terminator = if i == 2 { '{' } else { ';' };
+            let end = top_level_find(body, start, terminator).unwrap_or(body.len());
+            values.push(&body[start..end]);
+        }
+    }
+    let push = regex::Regex::new(&format!(r"\b{}\s*\.\s*push(?:_str)?\s*\(", name)).unwrap();
+    for found in push.find_iter(body) {
+        if let Some(close) = closing(body, found.end() - 1) {
+            values.extend(split_top_level(&body[found.end()..close]).first());
+        }
+    }
+    values
+}
+
+/// Bodies of the branches of an `if` expression, e.g. `""` and `""` of
+/// `if index == 0 { "" } else { "" }`
+fn branches(expr: &str) -> Vec<&str> {
+    let mut branches = Vec::new();
+    let mut offset = 0;
+    while let Some(open) = top_level_find(expr, offset, '{') {
+        let Some(close) = closing(expr, open) else {
+            break;
+        };
+        branches.push(&expr[open + 1..close]);
+        offset = close + 1;
+    }
+    branches
+}
+
+/// Values of the arms of a `match` expression, e.g. `format!("", index)` of
+/// `match self { Self::EmailEq(_) => format!("", index), }`
+fn match_arms(expr: &str) -> Vec<&str> {
+    let Some(open) = top_level_find(expr, 0, '{') else {
+        return Vec::new();
+    };
+    let Some(close) = closing(expr, open) else {
+        return Vec::new();
+    };
+    split_top_level(&expr[open + 1..close])
+        .into_iter()
+        .filter_map(|arm| {
+            let arrow = arm.find("=>")?;
+            let value = arm[arrow + 2..].trim();
+            // Block arms are checked through their results
+            Some(if value.starts_with('{') && value.ends_with('}') {
+                &value[1..value.len() - 1]
+            } else {
+                value
+            })
+        })
+        .collect()
+}
+
+/// Type of a parameter of a function, e.g. `usize` for `index` of `(&self, index: usize)`
+fn parameter_type<'p>(parameters: &'p str, name: &str) -> Option<&'p str> {
+    split_top_level(parameters)
+        .into_iter()
+        .find_map(|parameter| {
+            let (pattern, ty) = parameter.split_once(':')?;
+            let pattern = pattern.trim().trim_start_matches("mut ").trim();
+            (pattern == name).then(|| ty.trim())
+        })
+}
+
+/// Value of a `const NAME: T = value;` item of the code
+fn constant_value<'c>(code: &'c str, name: &str) -> Option<&'c str> {
+    let pattern =
+        regex::Regex::new(&format!(r"\bconst\s+{}\s*:[^=;]*=", regex::escape(name))).ok()?;
+    let start = pattern.find(code)?.end();
+    let end = top_level_find(code, start, ';')?;
+    Some(&code[start..end])
+}
+
+/// Values returned by a function body: its tail expression and `return` values
+fn results(body: &str) -> Vec<&str> {
+    let mut results = Vec::new();
+    let pattern = regex::Regex::new(r"\breturn\b").unwrap();
+    for found in pattern.find_iter(body) {
+        let end = top_level_find(body, found.end(), ';').unwrap_or(body.len());
+        results.push(&body[found.end()..end]);
+    }
+    // The tail expression follows the last statement or block at the top level
+    let mut depth = 0usize;
+    let mut tail_start = 0;
+    for (i, ch) in body.char_indices() {
+        match ch {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            '}' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    tail_start = i + 1;
+                }
+            }
+            ';' if depth == 0 => tail_start = i + 1,
+            _ => {}
+        }
+    }
+    let tail = body[tail_start..].trim();
+    if !tail.is_empty() {
+        results.push(tail);
+    }
+    results
+}
+
+/// Functions of the masked code with their bodies
+fn functions(masked: &str) -> Vec<Function<'_>> {
+    static FUNCTION: OnceLock<regex::Regex> = OnceLock::new();
+    let function =
+        FUNCTION.get_or_init(|| regex::Regex::new(r"\bfn\s+([A-Za-z_][A-Za-z0-9_]*)").unwrap());
+    static TEST_MODULE: OnceLock<regex::Regex> = OnceLock::new();
+    let test_module = TEST_MODULE.get_or_init(|| {
+        regex::Regex::new(r"#\[cfg\(test\)\]\s*(?:pub(?:\([^)]*\))?\s+)?mod\s+[A-Za-z0-9_]+\s*\{")
+            .unwrap()
+    });
+
+    let test_ranges: Vec<(usize, usize)> = test_module
+        .find_iter(masked)
+        .filter_map(|found| {
+            let open = found.end() - 1;
+            closing(masked, open).map(|close| (open, close))
+        })
+        .collect();
+
+    let mut functions = Vec::new();
+    for captures in function.captures_iter(masked) {
+        let whole = captures.get(0).unwrap();
+        let Some(params_open) = masked[whole.end()..].find('(').map(|i| whole.end() + i) else {
+            continue;
+        };
+        let Some(params_close) = closing(masked, params_open) else {
+            continue;
+        };
+        // Declarations without a body end with `;` before any `{`
+        let Some(open) = masked[params_close..]
+            .find(['{', ';'])
+            .map(|i| params_close + i)
+            .filter(|open| masked.as_bytes()[*open] == b'{')
+        else {
+            continue;
+        };
+        let Some(close) = closing(masked, open) else {
+            continue;
+        };
+        functions.push(Function {
+            name: captures.get(1).unwrap().as_str(),
+            parameters: &masked[params_open + 1..params_close],
+            body: &masked[open + 1..close],
+            is_test: test_ranges
+                .iter()
+                .any(|(start, end)| *start < whole.start() && whole.start() < *end),
+        });
+    }
+    functions
+}
+
+/// The code with comments removed, string literals replaced by `""` and char literals by `' '`
+fn mask_literals(code: &str) -> String {
+    let chars: Vec<char> = code.chars().collect();
+    let mut masked = String::with_capacity(code.len());
+    let is_ident = |ch: Option<&char>| ch.is_some_and(|ch| ch.is_alphanumeric() || *ch == '_');
+    let mut i = 0;
+    while i < chars.len() {
+        let ch = chars[i];
+        let next = chars.get(i + 1).copied();
+        let after_ident = i > 0 && is_ident(chars.get(i - 1));
+
+        if ch == '/' && next == Some('/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            continue;
+        }
+        if ch == '/' && next == Some('*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i += 2;
+            continue;
+        }
+
+        // Raw strings: r"..." and r#"..."#, optionally as byte strings
+        let raw_start = match (ch, next) {
+            ('r', Some('"' | '#')) if !after_ident => Some(i + 1),
+            ('b', Some('r')) if !after_ident && matches!(chars.get(i + 2), Some('"' | '#')) => {
+                Some(i + 2)
+            }
+            _ => None,
+        };
+        if let Some(start) = raw_start {
+            let hashes = chars[start..].iter().take_while(|ch| **ch == '#').count();
+            if chars.get(start + hashes) == Some(&'"') {
+                let mut j = start + hashes + 1;
+                while j < chars.len() {
+                    if chars[j] == '"'
+                        && chars[j + 1..]
+                            .iter()
+                            .take(hashes)
+                            .filter(|ch| **ch == '#')
+                            .count()
+                            == hashes
+                    {
+                        break;
+                    }
+                    j += 1;
+                }
+                masked.push_str("\"\"");
+                i = j + 1 + hashes;
+                continue;
+            }
+        }
+
+        // Strings and byte strings with escapes
+        if ch == '"' || (ch == 'b' && next == Some('"') && !after_ident) {
+            let mut j = if ch == 'b' { i + 2 } else { i + 1 };
+            while j < chars.len() && chars[j] != '"' {
+                if chars[j] == '\\' {
+                    j += 1;
+                }
+                j += 1;
+            }
+            masked.push_str("\"\"");
+            i = j + 1;
+            continue;
+        }
+
+        // Char literals, as opposed to lifetimes such as 'c
+        if ch == '\'' {
+            if next == Some('\\') {
+                let mut j = i + 2;
+                while j < chars.len() && chars[j] != '\'' {
+                    j += 1;
+                }
+                masked.push_str("' '");
+                i = j + 1;
+                continue;
+            }
+            if chars.get(i + 2) == Some(&'\'') {
+                masked.push_str("' '");
+                i += 3;
+                continue;
+            }
+        }
+
+        masked.push(ch);
+        i += 1;
+    }
+    masked
+}
+
+/// Index of the bracket closing the one at `open`, angle brackets of generic arguments included
+fn closing(code: &str, open: usize) -> Option<usize> {
+    let (opening, closing): (&[char], &[char]) = if code[open..].starts_with('<') {
+        (&['<'], &['>'])
+    } else {
+        (&['(', '[', '{'], &[')', ']', '}'])
+    };
+    let mut depth = 0usize;
+    for (i, ch) in code[open..].char_indices() {
+        if opening.contains(&ch) {
+            depth += 1;
+        } else if closing.contains(&ch) {
+            depth = depth.checked_sub(1)?;
+            if depth == 0 {
+                return Some(open + i);
+            }
+        }
+    }
+    None
+}
+
+/// Index of the first `terminator` at the top level from `start`
+fn top_level_find(code: &str, start: usize, terminator: char) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, ch) in code[start..].char_indices() {
+        if ch == terminator && depth == 0 {
+            return Some(start + i);
+        }
+        match ch {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth = depth.checked_sub(1)?,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Comma-separated items at the top level
+fn split_top_level(code: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, ch) in code.char_indices() {
+        match ch {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                items.push(&code[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if !code[start..].trim().is_empty() {
+        items.push(&code[start..]);
+    }
+    items
+}
+
+/// Operands of the binary operators `+ - * /` and ranges at the top level
+fn split_binary(expr: &str) -> Vec<&str> {
+    let mut operands = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    let bytes = expr.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let ch = bytes[i] as char;
+        match ch {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            '+' | '-' | '*' | '/' | '.' if depth == 0 => {
+                let is_range = ch == '.' && bytes.get(i + 1) == Some(&b'.');
+                let left = expr[start..i].trim_end();
+                let is_binary =
+                    !left.is_empty() && !left.ends_with(['+', '-', '*', '/', '&', '=', '(', ',']);
+                if (ch != '.' || is_range) && is_binary {
+                    operands.push(&expr[start..i]);
+                    i += if is_range { 2 } else { 1 };
+                    start = i;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    operands.push(&expr[start..]);
+    operands
+}
+
+/// Expression without leading references, dereferences and `mut`
+fn strip_prefix_operators(mut expr: &str) -> &str {
+    loop {
+        let stripped = expr
+            .strip_prefix('&')
+            .or_else(|| expr.strip_prefix('*'))
+            .or_else(|| expr.strip_prefix('-'))
+            .or_else(|| expr.strip_prefix("mut "))
+            .map(str::trim_start);
+        match stripped {
+            Some(stripped) => expr = stripped,
+            None => return expr,
+        }
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '_')
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+}
+
+/// Expression on a single line for reports
+fn compact(expr: &str) -> String {
+    expr.split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,285 @@
+/// Formatting of the SQL of queries, so that the SQL in doc comments, telemetry fields and
+/// generated query strings is laid out consistently, and of the SQL files themselves
+/// The formatter is only compiled with the `sql-format` feature
+use crate::sqlfile_parser::is_define_name;
+use crate::types_extractor::SHARD_PLACEHOLDER;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Prefix of the identifiers standing in for parameters, shard placeholders and defines
+const PLACEHOLDER_PREFIX: &str = "automodel_placeholder_";
+
+/// Identifiers standing in for the brackets of conditional blocks
+const BLOCK_OPEN: &str = "automodel_block_open";
+const BLOCK_CLOSE: &str = "automodel_block_close";
+
+/// Format the SQL of a query
+/// Parameters, conditional blocks, shard placeholders and defines are kept as written, and SQL
+/// the formatter would change beyond whitespace and keyword case is only trimmed
+pub fn format_sql(sql: &str) -> Result<String> {
+    let (protected, placeholders) = protect_placeholders(sql);
+    let formatted = format_protected(&protected)?;
+    if sql_tokens(&formatted) != sql_tokens(&protected) {
+        return Ok(sql.trim().to_string());
+    }
+    Ok(restore_placeholders(formatted.trim(), &placeholders))
+}
+
+#[cfg(feature = "sql-format")]
+fn format_protected(sql: &str) -> Result<String> {
+    Ok(sqlformat::format(
+        sql,
+        &sqlformat::QueryParams::None,
+        sqlformat::FormatOptions {
+            indent: sqlformat::Indent::Spaces(4),
+            uppercase: false,
+            lines_between_queries: 1,
+        },
+    ))
+}
+
+#[cfg(not(feature = "sql-format"))]
+fn format_protected(_sql: &str) -> Result<String> {
+    anyhow::bail!("SQL formatting requires the `sql-format` feature of automodel")
+}
+
+/// Replace the AutoModel syntax the formatter does not know with plain identifiers
+/// Returns the SQL and the replaced text of each `automodel_placeholder_N`
+fn protect_placeholders(sql: &str) -> (String, Vec<String>) {
+    let mut protected = String::with_capacity(sql.len());
+    let mut placeholders = Vec::new();
+    // Whether each open bracket starts a conditional block
+    let mut brackets = Vec::new();
+    let mut rest = sql;
+    while let Some(ch) = rest.chars().next() {
+        let placeholder_len = if rest.starts_with("#{") {
+            rest.find('}').map(|end| end + 1)
+        } else if rest.starts_with(SHARD_PLACEHOLDER) {
+            Some(SHARD_PLACEHOLDER.len())
+        } else if let Some(after) = rest.strip_prefix("@@") {
+            after
+                .find("@@")
+                .filter(|len| is_define_name(&after[..*len]))
+                .map(|len| len + 4)
+        } else {
+            None
+        };
+        if let Some(len) = placeholder_len {
+            protected.push_str(&format!("{}{}", PLACEHOLDER_PREFIX, placeholders.len()));
+            placeholders.push(rest[..len].to_string());
+            rest = &rest[len..];
+            continue;
+        }
+
+        if rest.starts_with("#[") {
+            brackets.push(true);
+            protected.push_str(&format!(" {} ", BLOCK_OPEN));
+            rest = &rest[2..];
+            continue;
+        }
+        match ch {
+            '[' => brackets.push(false),
+            ']' if brackets.pop() == Some(true) => {
+                protected.push_str(&format!(" {} ", BLOCK_CLOSE));
+                rest = &rest[1..];
+                continue;
+            }
+            _ => {}
+        }
+        protected.push(ch);
+        rest = &rest[ch.len_utf8()..];
+    }
+    (protected, placeholders)
+}
+
+/// Put the replaced text back into formatted SQL
+/// Conditional blocks start on their own line, at the indentation of the line they were on, and
+/// are kept on that line unless they contain comments
+fn restore_placeholders(sql: &str, placeholders: &[String]) -> String {
+    static OPEN: OnceLock<regex::Regex> = OnceLock::new();
+    static CLOSE: OnceLock<regex::Regex> = OnceLock::new();
+    static PLACEHOLDER: OnceLock<regex::Regex> = OnceLock::new();
+    let open = OPEN.get_or_init(|| regex::Regex::new(&format!(r"{}\s*", BLOCK_OPEN)).unwrap());
+    let close = CLOSE.get_or_init(|| regex::Regex::new(&format!(r"\s*{}", BLOCK_CLOSE)).unwrap());
+    let placeholder = PLACEHOLDER
+        .get_or_init(|| regex::Regex::new(&format!(r"{}(\d+)", PLACEHOLDER_PREFIX)).unwrap());
+
+    let sql = close.replace_all(sql, "]");
+    let mut restored = String::with_capacity(sql.len());
+    let mut last = 0;
+    for found in open.find_iter(&sql) {
+        restored.push_str(&sql[last..found.start()]);
+        last = found.end();
+        let line_start = restored.rfind('\n').map_or(0, |i| i + 1);
+        let line = &restored[line_start..];
+        if !line.trim().is_empty() {
+            let indent: String = line.chars().take_while(|c| c.is_whitespace()).collect();
+            restored.truncate(restored.trim_end().len());
+            restored.push('\n');
+            restored.push_str(&indent);
+        }
+        restored.push_str("#[");
+    }
+    restored.push_str(&sql[last..]);
+    let restored = join_block_lines(&restored);
+
+    placeholder
+        .replace_all(&restored, |captures: &regex::Captures| {
+            captures[1]
+                .parse::<usize>()
+                .ok()
+                .and_then(|i| placeholders.get(i))
+                .cloned()
+                .unwrap_or_else(|| captures[0].to_string())
+        })
+        .to_string()
+}
+
+/// Join the lines of conditional blocks without comments, which the formatter splits like
+/// the rest of the query, e.g. `#[, name = #{name?}]` of conditional UPDATE statements
+fn join_block_lines(sql: &str) -> String {
+    let mut joined = String::with_capacity(sql.len());
+    let mut rest = sql;
+    while let Some(start) = rest.find("#[") {
+        joined.push_str(&rest[..start]);
+        let mut depth = 0;
+        let end = rest[start + 1..].char_indices().find_map(|(i, ch)| {
+            match ch {
+                '[' => depth += 1,
+                ']' => depth -= 1,
+                _ => return None,
+            }
+            (depth == 0).then_some(start + 1 + i + 1)
+        });
+        let Some(end) = end else {
+            break;
+        };
+        let block = &rest[start..end];
+        if block.contains("--") {
+            joined.push_str(block);
+        } else {
+            joined.push_str(&block.split_whitespace().collect::<Vec<_>>().join(" "));
+        }
+        rest = &rest[end..];
+    }
+    joined.push_str(rest);
+    joined
+}
+
+/// Tokens of SQL for checking that formatting only changed whitespace and keyword case
+/// Words are lowercased, as PostgreSQL folds unquoted identifiers and keywords
+fn sql_tokens(sql: &str) -> Vec<String> {
+    const OPERATOR_CHARS: &str = "+-*/<>=~!@#%^&|`?:";
+    let chars: Vec<char> = sql.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let ch = chars[i];
+        let start = i;
+        if ch.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        if ch == '-' && chars.get(i + 1) == Some(&'-') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            let comment: String = chars[start..i].iter().collect();
+            tokens.push(comment.trim_end().to_string());
+            continue;
+        }
+        if ch == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i < chars.len() && !(chars[i - 1] == '*' && chars[i] == '/') {
+                i += 1;
+            }
+            i = (i + 1).min(chars.len());
+        } else if ch == '\'' || ch == '"' {
+            i += 1;
+            while i < chars.len() && chars[i] != ch {
+                i += 1;
+            }
+            i = (i + 1).min(chars.len());
+        } else if ch.is_alphanumeric() || ch == '_' || ch == '$' {
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '$')
+            {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            tokens.push(word.to_lowercase());
+            continue;
+        } else if OPERATOR_CHARS.contains(ch) {
+            while i < chars.len() && OPERATOR_CHARS.contains(chars[i]) {
+                i += 1;
+            }
+        } else {
+            i += 1;
+        }
+        tokens.push(chars[start..i].iter().collect());
+    }
+    tokens
+}
+
+/// Format the SQL of every query file in a queries directory, keeping the metadata block and
+/// `-- @define` lines as written
+/// Returns the files whose SQL is not formatted, which are rewritten unless `check` is set
+pub fn format_query_files(queries_dir: &Path, check: bool) -> Result<Vec<PathBuf>> {
+    let mut sql_files = Vec::new();
+    let module_dirs = std::fs::read_dir(queries_dir).with_context(|| {
+        format!(
+            "Failed to read queries directory: {}",
+            queries_dir.display()
+        )
+    })?;
+    for module_entry in module_dirs {
+        let module_path = module_entry?.path();
+        if !module_path.is_dir() {
+            continue;
+        }
+        for sql_entry in std::fs::read_dir(&module_path)? {
+            let sql_path = sql_entry?.path();
+            if sql_path.extension().and_then(|e| e.to_str()) == Some("sql") {
+                sql_files.push(sql_path);
+            }
+        }
+    }
+    sql_files.sort();
+
+    let mut unformatted = Vec::new();
+    for sql_path in sql_files {
+        let content = std::fs::read_to_string(&sql_path)
+            .with_context(|| format!("Failed to read SQL file: {}", sql_path.display()))?;
+
+        // The SQL starts after the last metadata or define line
+        let mut header_len = 0;
+        let mut offset = 0;
+        for line in content.split_inclusive('\n') {
+            offset += line.len();
+            let trimmed = line.trim();
+            if trimmed == "-- @automodel"
+                || trimmed == "-- @end"
+                || trimmed.starts_with("-- @define ")
+            {
+                header_len = offset;
+            }
+        }
+        let (header, sql) = content.split_at(header_len);
+        let sql = format_sql(sql)
+            .with_context(|| format!("Failed to format SQL file: {}", sql_path.display()))?;
+        let formatted = if header.is_empty() {
+            format!("{}\n", sql)
+        } else {
+            format!("{}\n{}\n", header, sql)
+        };
+
+        if formatted != content {
+            if !check {
+                std::fs::write(&sql_path, formatted)?;
+            }
+            unformatted.push(sql_path);
+        }
+    }
+    Ok(unformatted)
+}
@@ -1,11 +1,14 @@
-use crate::query_definition::QueryDefinition;
+use crate::query_definition::{ModuleConfig, QueryDefinition};
+use crate::utils::{is_rust_keyword, sorted_entries};
 use anyhow::{Context, Result};
 use std::path::Path;
 use tokio::fs;
 
 /// Generate SQL query variants for analysis by handling conditional syntax
+/// Up to `max_combinations` variants combining several conditional blocks follow the base and
+/// single-block variants, with fewer blocks first
 /// Returns list of (sql, variant_label) tuples
-fn generate_query_variants(sql: &str) -> Vec<(String, String)> {
+fn generate_query_variants(sql: &str, max_combinations: usize) -> Vec<(String, String)> {
     let mut variants = Vec::new();
 
     // First variant: remove all conditional blocks #[...]
@@ -20,11 +23,77 @@ fn generate_query_variants(sql: &str) -> Vec<(String, String)> {
         variants.push((variant_sql, format!("variant {}", i + 1)));
     }
 
+    variants.extend(combined_conditional_variants(sql, max_combinations));
     variants
 }
 
+/// Variants including two or more conditional blocks, as at runtime when several optional
+/// parameters are set, labeled with the included blocks, e.g. "variants 1+3"
+/// Combinations are enumerated by increasing size until `max_combinations` are generated
+fn combined_conditional_variants(sql: &str, max_combinations: usize) -> Vec<(String, String)> {
+    let blocks = conditional_block_spans(sql);
+    let mut variants = Vec::new();
+
+    for size in 2..=blocks.len() {
+        // Indexes of the included blocks, advanced in lexicographic order
+        let mut included: Vec<usize> = (0..size).collect();
+        loop {
+            if variants.len() >= max_combinations {
+                return variants;
+            }
+
+            // Replace the included blocks by their content from the end, keeping the positions
+            // of the earlier blocks valid, then drop the other blocks
+            let mut variant = sql.to_string();
+            for &i in included.iter().rev() {
+                let (start, end) = blocks[i];
+                variant.replace_range(start..end, &sql[start + 2..end - 1]);
+            }
+            let variant = remove_conditional_blocks(&variant);
+            if !variant.trim().is_empty() {
+                let label = included
+                    .iter()
+                    .map(|i| (i + 1).to_string())
+                    .collect::<Vec<_>>()
+                    .join("+");
+                variants.push((variant, format!("variants {}", label)));
+            }
+
+            // Next combination: bump the last index which can still move right
+            let Some(pos) = (0..size)
+                .rev()
+                .find(|&pos| included[pos] < blocks.len() - size + pos)
+            else {
+                break;
+            };
+            included[pos] += 1;
+            for next in pos + 1..size {
+                included[next] = included[next - 1] + 1;
+            }
+        }
+    }
+
+    variants
+}
+
+/// Byte ranges of the conditional blocks #[...] of the SQL, including their markers
+fn conditional_block_spans(sql: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut pos = 0;
+    while let Some(start) = sql[pos..].find("#[") {
+        let start_pos = pos + start;
+        let Some(end) = sql[start_pos..].find("]") else {
+            break;
+        };
+        let end_pos = start_pos + end + 1;
+        spans.push((start_pos, end_pos));
+        pos = end_pos;
+    }
+    spans
+}
+
 /// Remove all conditional blocks #[...] from SQL
-fn remove_conditional_blocks(sql: &str) -> String {
+pub(crate) fn remove_conditional_blocks(sql: &str) -> String {
     let mut result = sql.to_string();
 
     // Remove #[...] blocks using simple string replacement
@@ -74,7 +143,7 @@ fn extract_conditional_variants(sql: &str) -> Vec<String> {
 }
 
 /// Validates that a module name is a valid Rust identifier
-fn validate_module_name(module_name: &str) -> Result<(), String> {
+pub(crate) fn validate_module_name(module_name: &str) -> Result<(), String> {
     if module_name.is_empty() {
         return Err("Module name cannot be empty".to_string());
     }
@@ -119,7 +188,7 @@ fn validate_module_name(module_name: &str) -> Result<(), String> {
 }
 
 /// Check if a string is a valid Rust identifier
-fn is_valid_rust_identifier(name: &str) -> bool {
+pub(crate) fn is_valid_rust_identifier(name: &str) -> bool {
     if name.is_empty() {
         return false;
     }
@@ -143,63 +212,6 @@ fn is_valid_rust_identifier(name: &str) -> bool {
     !is_rust_keyword(name)
 }
 
-/// Check if a string is a Rust keyword
-fn is_rust_keyword(name: &str) -> bool {
-    matches!(
-        name,
-        "as" | "break"
-            | "const"
-            | "continue"
-            | "crate"
-            | "else"
-            | "enum"
-            | "extern"
-            | "false"
-            | "fn"
-            | "for"
-            | "if"
-            | "impl"
-            | "in"
-            | "let"
-            | "loop"
-            | "match"
-            | "mod"
-            | "move"
-            | "mut"
-            | "pub"
-            | "ref"
-            | "return"
-            | "self"
-            | "Self"
-            | "static"
-            | "struct"
-            | "super"
-            | "trait"
-            | "true"
-            | "type"
-            | "unsafe"
-            | "use"
-            | "where"
-            | "while"
-            | "async"
-            | "await"
-            | "dyn"
-            | "abstract"
-            | "become"
-            | "box"
-            | "do"
-            | "final"
-            | "macro"
-            | "override"
-            | "priv"
-            | "typeof"
-            | "unsized"
-            | "virtual"
-            | "yield"
-            | "try"
-    )
-}
-
 /// Parse SQL file with embedded YAML metadata in comments
 /// Expected format:
 /// ```sql
@@ -212,6 +224,8 @@ fn is_rust_keyword(name: &str) -> bool {
 ///
 /// UPDATE users SET profile = #{profile} WHERE id = #{user_id}
 /// ```
+/// The metadata may also be written to a sidecar `{query}.yaml` file next to the SQL file, whose
+/// keys replace those of the metadata block
 async fn parse_sql_file(
     path: &Path,
     module: &str,
@@ -221,14 +235,104 @@ async fn parse_sql_file(
     let content = fs::read_to_string(path)
         .await
         .with_context(|| format!("Failed to read SQL file: {}", path.display()))?;
+    let mut modified = fs::metadata(path)
+        .await
+        .and_then(|file| file.modified())
+        .ok();
+
+    let sidecar_path = sidecar_file(path);
+    let sidecar = match &sidecar_path {
+        Some(sidecar_path) => {
+            let sidecar_content = fs::read_to_string(sidecar_path).await.with_context(|| {
+                format!("Failed to read metadata file: {}", sidecar_path.display())
+            })?;
+            let sidecar_modified = fs::metadata(sidecar_path)
+                .await
+                .and_then(|file| file.modified())
+                .ok();
+            modified = modified.max(sidecar_modified);
+            Some((sidecar_path.as_path(), sidecar_content))
+        }
+        None => None,
+    };
+
+    let mut query = parse_sql_source(
+        path,
+        &content,
+        module,
+        name,
+        defaults,
+        modified,
+        sidecar
+            .as_ref()
+            .map(|(path, content)| (*path, content.as_str())),
+    )?;
+    query.source_file = Some(path.to_path_buf());
+    Ok(query)
+}
+
+/// Sidecar metadata file of a SQL file, `{query}.yaml` in the same directory, if it exists
+/// The module configuration is never the sidecar of a `module.sql` query
+pub(crate) fn sidecar_file(sql_path: &Path) -> Option<std::path::PathBuf> {
+    let sidecar_path = sql_path.with_extension("yaml");
+    let is_module_config = sidecar_path
+        .file_name()
+        .is_some_and(|file_name| file_name == MODULE_CONFIG_FILE_NAME);
+    (!is_module_config && sidecar_path.is_file()).then_some(sidecar_path)
+}
+
+/// Metadata block merged with the metadata of a sidecar file, whose top-level keys replace those
+/// of the block
+fn merge_sidecar_metadata(block_yaml: &str, sidecar_yaml: &str) -> Result<serde_yaml::Value> {
+    let as_mapping = |value: serde_yaml::Value, source: &str| match value {
+        serde_yaml::Value::Null => Ok(serde_yaml::Mapping::new()),
+        serde_yaml::Value::Mapping(mapping) => Ok(mapping),
+        _ => anyhow::bail!("{} must be a mapping of metadata keys", source),
+    };
+    let mut metadata = as_mapping(
+        serde_yaml::from_str(block_yaml).context("Failed to parse the metadata block")?,
+        "The metadata block",
+    )?;
+    let sidecar = as_mapping(serde_yaml::from_str(sidecar_yaml)?, "The metadata file")?;
+    for (key, value) in sidecar {
+        metadata.insert(key, value);
+    }
+    Ok(serde_yaml::Value::Mapping(metadata))
+}
 
+/// Parse the content of a SQL file with its metadata block into a QueryDefinition
+/// `path` is only used in error messages, queries generated from introspection pass the table
+/// `sidecar` is the path and content of the sidecar metadata file of the SQL file
+pub(crate) fn parse_sql_source(
+    path: &Path,
+    content: &str,
+    module: &str,
+    name: &str,
+    defaults: crate::DefaultsConfig,
+    modified: Option<std::time::SystemTime>,
+    sidecar: Option<(&Path, &str)>,
+) -> Result<QueryDefinition> {
     let mut in_metadata = false;
     let mut yaml_lines = Vec::new();
     let mut sql_lines = Vec::new();
+    let mut file_defines = std::collections::BTreeMap::new();
 
     for line in content.lines() {
         let trimmed = line.trim();
 
+        if let Some(define) = trimmed.strip_prefix("-- @define ") {
+            let (define_name, value) = parse_define(define)
+                .map_err(|e| anyhow::anyhow!("{}: invalid @define: {}", path.display(), e))?;
+            if file_defines.insert(define_name.clone(), value).is_some() {
+                anyhow::bail!(
+                    "{}: @define {} is defined twice",
+                    path.display(),
+                    define_name
+                );
+            }
+            continue;
+        }
+
         if trimmed == "-- @automodel" {
             in_metadata = true;
             continue;
@@ -270,6 +374,18 @@ async fn parse_sql_file(
         pub include_params: Option<Vec<String>>,
         #[serde(default)]
         pub include_sql: Option<bool>,
+        #[serde(default)]
+        pub max_param_length: Option<usize>,
+        #[serde(default)]
+        pub max_param_elements: Option<usize>,
+    }
+
+    // Entries of `allow` are performance checks or diagnostic codes
+    #[derive(serde::Deserialize)]
+    #[serde(untagged)]
+    enum AllowEntry {
+        Check(crate::query_definition::PerformanceCheck),
+        Diagnostic(crate::diagnostics::DiagnosticCode),
     }
 
     // Create a temporary QueryDefinition with minimal info
@@ -282,6 +398,8 @@ async fn parse_sql_file(
         #[serde(default)]
         types: Option<std::collections::HashMap<String, String>>,
         #[serde(default)]
+        param_types: std::collections::BTreeMap<String, String>,
+        #[serde(default)]
         telemetry: TelemetryMetadata,
         #[serde(default)]
         ensure_indexes: Option<bool>,
@@ -292,8 +410,12 @@ async fn parse_sql_file(
         #[serde(default)]
         parameters_type: Option<crate::query_definition::ParametersType>,
         #[serde(default)]
+        filter_type: Option<crate::query_definition::FilterType>,
+        #[serde(default)]
         return_type: Option<String>,
         #[serde(default)]
+        return_shape: Option<crate::query_definition::ReturnShape>,
+        #[serde(default)]
         error_type: Option<String>,
         #[serde(default)]
         conditions_type_derives: Vec<String>,
@@ -302,30 +424,486 @@ async fn parse_sql_file(
         #[serde(default)]
         return_type_derives: Vec<String>,
         #[serde(default)]
+        flatten: Vec<String>,
+        #[serde(default)]
         error_type_derives: Vec<String>,
+        #[serde(default)]
+        auto_derives: Option<bool>,
+        #[serde(default)]
+        dynamic: Option<bool>,
+        #[serde(default)]
+        min_pg_version: Option<u32>,
+        #[serde(default)]
+        iterate: Option<crate::query_definition::IterateConfig>,
+        #[serde(default)]
+        analysis_params: std::collections::HashMap<String, serde_yaml::Value>,
+        #[serde(default)]
+        allow: Vec<AllowEntry>,
+        #[serde(default)]
+        numeric_as: Option<crate::query_definition::NumericAs>,
+        #[serde(default)]
+        currency_columns: std::collections::BTreeMap<String, String>,
+        #[serde(default)]
+        char_flags: std::collections::BTreeMap<String, crate::query_definition::CharFlag>,
+        #[serde(default)]
+        plan_sampling: Option<bool>,
+        #[serde(default)]
+        cardinality_drift: Option<bool>,
+        #[serde(default)]
+        limit_possible_one: Option<bool>,
+        #[serde(default)]
+        blocking: Option<bool>,
+        #[serde(default)]
+        cancellable: Option<bool>,
+        #[serde(default)]
+        slow_query_ms: Option<u64>,
+        #[serde(default)]
+        requires_session: bool,
+        #[serde(default)]
+        cascade_preview: bool,
+        #[serde(default)]
+        archive_to: Option<String>,
+        #[serde(default)]
+        id_only: bool,
+        #[serde(default)]
+        retrying: Option<crate::query_definition::Retrying>,
+        #[serde(default)]
+        scope: crate::query_definition::QueryScope,
+        #[serde(default)]
+        bulk_update: bool,
+        #[serde(default)]
+        prepare: Option<bool>,
+        #[serde(default)]
+        proto: bool,
+        #[serde(default)]
+        cache_invalidation: bool,
+        #[serde(default)]
+        cache_invalidate_on: Option<crate::query_definition::CacheInvalidateOn>,
+        #[serde(default)]
+        max_variant_combinations: Option<usize>,
+        #[serde(default)]
+        feature_flag: Option<String>,
+        #[serde(default)]
+        feature_flag_cfg: bool,
+        #[serde(default)]
+        large_list_strategy: crate::query_definition::LargeListStrategy,
+        #[serde(default)]
+        assumes: std::collections::BTreeMap<String, String>,
+        #[serde(default)]
+        shard_by: Option<String>,
+        #[serde(default)]
+        canonical_shard: Option<String>,
+        #[serde(default)]
+        batch: Option<crate::query_definition::BatchConfig>,
+        #[serde(default)]
+        check_values: std::collections::BTreeMap<String, crate::query_definition::CheckValuesStyle>,
+        #[serde(default)]
+        map_row: Option<String>,
+        #[serde(default)]
+        try_map_row: Option<String>,
+        #[serde(default)]
+        insert: Option<String>,
+        #[serde(default)]
+        unit_of_work: Option<String>,
+        #[serde(default)]
+        search_path: Vec<String>,
+        #[serde(default)]
+        search_path_at_runtime: bool,
+        #[serde(default)]
+        not_found_message: Option<String>,
+        #[serde(default)]
+        sensitivity: std::collections::BTreeMap<String, crate::query_definition::Sensitivity>,
+        #[serde(default)]
+        redact_debug: bool,
+        #[serde(default)]
+        isolation_sensitive: bool,
+        #[serde(default)]
+        baseline: Option<crate::query_definition::PlanBaseline>,
     }
 
-    let metadata: QueryMetadata = if yaml_str.trim().is_empty() {
+    let metadata: QueryMetadata = match sidecar {
+        Some((sidecar_path, sidecar_yaml)) => merge_sidecar_metadata(&yaml_str, sidecar_yaml)
+            .and_then(|metadata| Ok(serde_yaml::from_value(metadata)?))
+            .with_context(|| {
+                format!(
+                    "Failed to parse YAML metadata in {} for query '{}'",
+                    sidecar_path.display(),
+                    name
+                )
+            })?,
         // No metadata provided, use defaults
-        serde_yaml::from_str("{}").unwrap()
-    } else {
-        serde_yaml::from_str(&yaml_str).with_context(|| {
+        None if yaml_str.trim().is_empty() => serde_yaml::from_str("{}").unwrap(),
+        None => serde_yaml::from_str(&yaml_str).with_context(|| {
             format!(
                 "Failed to parse YAML metadata in SQL file for query '{}'",
                 name
             )
-        })?
+        })?,
     };
 
     // Combine SQL lines and trim
-    let sql = sql_lines.join("\n").trim().to_string();
+    let raw_sql = sql_lines.join("\n").trim().to_string();
+    let max_variant_combinations = metadata
+        .max_variant_combinations
+        .unwrap_or(defaults.max_variant_combinations);
+    let prepare_sql = |sql: &str| -> Result<String> {
+        let sql = substitute_defines(sql, &file_defines, &defaults.defines)?;
+        let sql = if defaults.format_sql {
+            crate::sql_format::format_sql(&sql)?
+        } else {
+            sql
+        };
+        apply_param_types(&sql, &metadata.param_types)
+    };
+    // Mandatory predicates are injected before the options of the query check its statement
+    let inject_predicates = |sql: String| -> Result<String> {
+        crate::mandatory_predicates::inject_mandatory_predicates(
+            &sql,
+            &defaults.mandatory_predicates,
+        )
+    };
+    // Queries expecting at most one row stop reading at the first one
+    let limit_possible_one = metadata.expect
+        == Some(crate::query_definition::ExpectedResult::PossibleOne)
+        && metadata
+            .limit_possible_one
+            .unwrap_or(defaults.limit_possible_one);
+    let limit_rows = |sql: String| -> Result<String> {
+        if !limit_possible_one {
+            return Ok(sql);
+        }
+        Ok(crate::types_extractor::limit_one_sql(&sql).unwrap_or(sql))
+    };
+    // Deleted rows are moved to the archive table by the statement itself
+    let archive_sql = |sql: &str| -> Result<(Option<String>, String)> {
+        let Some(archive_table) = &metadata.archive_to else {
+            return Ok((None, sql.to_string()));
+        };
+        let (source, rewritten_sql) =
+            crate::types_extractor::archive_delete_sql(sql, archive_table).ok_or_else(|| {
+                anyhow::anyhow!(
+                "uses archive_to, which requires a `DELETE FROM table ...` statement without USING"
+            )
+            })?;
+        Ok((Some(source), rewritten_sql))
+    };
+
+    // Blocks of features not enabled for this build are removed, the SQL with each feature
+    // flipped on its own is kept to check that the other branches stay valid
+    let feature_enabled = |feature: &str| {
+        defaults
+            .features
+            .iter()
+            .any(|enabled| normalize_feature(enabled) == normalize_feature(feature))
+    };
+    let sql = resolve_cfg_blocks(&raw_sql, &mut |feature| feature_enabled(feature))
+        .and_then(|sql| prepare_sql(sql.trim()))
+        .and_then(inject_predicates)
+        .and_then(limit_rows)
+        .map_err(|e| anyhow::anyhow!("{}: query '{}' {}", path.display(), name, e))?;
+    let mut cfg_features = Vec::new();
+    resolve_cfg_blocks(&raw_sql, &mut |feature| {
+        if !cfg_features.contains(&feature.to_string()) {
+            cfg_features.push(feature.to_string());
+        }
+        true
+    })?;
+    let mut cfg_branches: Vec<(String, String)> = Vec::new();
+    for cfg_feature in &cfg_features {
+        let flipped = !feature_enabled(cfg_feature);
+        let branch_sql = resolve_cfg_blocks(&raw_sql, &mut |feature| {
+            if feature == cfg_feature {
+                flipped
+            } else {
+                feature_enabled(feature)
+            }
+        })
+        .and_then(|sql| prepare_sql(sql.trim()))
+        .and_then(inject_predicates)
+        .and_then(limit_rows)
+        .and_then(|sql| archive_sql(&sql).map(|(_, sql)| sql))
+        .map_err(|e| anyhow::anyhow!("{}: query '{}' {}", path.display(), name, e))?;
+        let branch_label = format!(
+            "feature \"{}\" {}",
+            cfg_feature,
+            if flipped { "enabled" } else { "disabled" }
+        );
+        for (variant_sql, variant_label) in
+            generate_query_variants(&branch_sql, max_variant_combinations)
+        {
+            let (converted_sql, _) =
+                crate::types_extractor::convert_named_params_to_positional(&variant_sql);
+            if !cfg_branches
+                .iter()
+                .any(|(existing, _)| *existing == converted_sql)
+            {
+                cfg_branches.push((
+                    converted_sql,
+                    format!("{}, {}", branch_label, variant_label),
+                ));
+            }
+        }
+    }
 
     if sql.is_empty() {
         anyhow::bail!("SQL file contains no SQL query for '{}'", name);
     }
 
+    let return_shape = metadata.return_shape.unwrap_or_default();
+    let cancellable = metadata.cancellable.unwrap_or(defaults.cancellable);
+    let set_options: Vec<QueryOption> = QueryOption::ALL
+        .iter()
+        .copied()
+        .filter(|option| match option {
+            QueryOption::Multiunzip => metadata.multiunzip.unwrap_or(false),
+            QueryOption::Iterate => metadata.iterate.is_some(),
+            QueryOption::Batch => metadata.batch.is_some(),
+            QueryOption::ShardBy => metadata.shard_by.is_some(),
+            QueryOption::TempTable => {
+                metadata.large_list_strategy
+                    == crate::query_definition::LargeListStrategy::TempTable
+            }
+            QueryOption::RequiresSession => metadata.requires_session,
+            QueryOption::ExactlyOneOrInsert => {
+                metadata.expect == Some(crate::query_definition::ExpectedResult::ExactlyOneOrInsert)
+            }
+            QueryOption::FilterType => metadata
+                .filter_type
+                .as_ref()
+                .is_some_and(|filter_type| filter_type.is_enabled()),
+            QueryOption::ConditionsType => metadata
+                .conditions_type
+                .as_ref()
+                .is_some_and(|conditions_type| conditions_type.is_enabled()),
+            QueryOption::ParametersType => metadata
+                .parameters_type
+                .as_ref()
+                .is_some_and(|parameters_type| parameters_type.is_enabled()),
+            QueryOption::CascadePreview => metadata.cascade_preview,
+            QueryOption::IdOnly => metadata.id_only,
+            QueryOption::BulkUpdate => metadata.bulk_update,
+            QueryOption::Unprepared => metadata.prepare == Some(false),
+            QueryOption::Retrying => metadata
+                .retrying
+                .as_ref()
+                .is_some_and(|retrying| retrying.is_enabled()),
+            QueryOption::ArchiveTo => metadata.archive_to.is_some(),
+            QueryOption::NotFoundMessage => metadata.not_found_message.is_some(),
+            QueryOption::CheckValues => !metadata.check_values.is_empty(),
+            QueryOption::CheckValuesConst => metadata
+                .check_values
+                .values()
+                .any(|style| *style == crate::query_definition::CheckValuesStyle::Const),
+            QueryOption::CurrencyParams => metadata
+                .currency_columns
+                .keys()
+                .any(|column| is_sql_param(&sql, column)),
+            QueryOption::CharFlagParams => metadata
+                .char_flags
+                .keys()
+                .any(|column| is_sql_param(&sql, column)),
+            QueryOption::ArrayElementParams => metadata
+                .types
+                .iter()
+                .flatten()
+                .filter_map(|(key, _)| key.strip_suffix("[]"))
+                .any(|key| is_sql_param(&sql, key.rsplit('.').next().unwrap_or_default())),
+            QueryOption::Cancellable => cancellable,
+            QueryOption::CacheInvalidation => metadata.cache_invalidation,
+            QueryOption::CacheInvalidateOn => metadata.cache_invalidate_on.is_some(),
+            QueryOption::UnitOfWork => metadata.unit_of_work.is_some(),
+            QueryOption::FeatureFlag => metadata.feature_flag.is_some(),
+            QueryOption::SearchPathAtRuntime => metadata.search_path_at_runtime,
+            QueryOption::IsolationSensitive => metadata.isolation_sensitive,
+            QueryOption::TupleShape => return_shape == crate::query_definition::ReturnShape::Tuple,
+            QueryOption::ReturnType => metadata.return_type.is_some(),
+            QueryOption::ReturnTypeDerives => !metadata.return_type_derives.is_empty(),
+            QueryOption::Flatten => !metadata.flatten.is_empty(),
+            QueryOption::Cockroach => {
+                defaults.dialect == crate::query_definition::Dialect::Cockroach
+            }
+        })
+        .collect();
+    check_option_conflicts(name, &set_options)?;
+
+    if !metadata.flatten.is_empty() {
+        let derives_serde = metadata.return_type_derives.iter().any(|derive| {
+            let derive = derive.rsplit("::").next().unwrap_or_default();
+            derive == "Serialize" || derive == "Deserialize"
+        });
+        if !derives_serde {
+            anyhow::bail!(
+                "Query '{}' uses flatten, which requires serde::Serialize or serde::Deserialize in return_type_derives",
+                name
+            );
+        }
+    }
+
+    if let Some(iterate) = &metadata.iterate {
+        if iterate.chunk_size == 0 {
+            anyhow::bail!("Query '{}' iterate.chunk_size must be greater than 0", name);
+        }
+    }
+
+    if metadata.large_list_strategy == crate::query_definition::LargeListStrategy::TempTable {
+        validate_large_list_query(name, &sql)?;
+    }
+
+    if let Some(batch) = &metadata.batch {
+        if batch.max_size == 0 {
+            anyhow::bail!("Query '{}' batch.max_size must be greater than 0", name);
+        }
+        validate_batch_query(name, &sql, metadata.expect.clone().unwrap_or_default())?;
+    }
+
+    let canonical_shard = metadata
+        .canonical_shard
+        .clone()
+        .or(defaults.canonical_shard.clone());
+    validate_sharded_query(
+        name,
+        &sql,
+        metadata.shard_by.as_deref(),
+        canonical_shard.as_deref(),
+    )?;
+
+    if let Some(invalidate_on) = &metadata.cache_invalidate_on {
+        validate_cache_invalidate_on(name, &sql, invalidate_on)?;
+    }
+
+    if metadata.cascade_preview {
+        validate_cascade_preview_query(name, &sql)?;
+    }
+
+    if let Some(archive_table) = &metadata.archive_to {
+        validate_archive_to_query(name, &sql, archive_table)?;
+    }
+
+    if metadata.id_only {
+        validate_id_only_query(name, &sql)?;
+    }
+
+    if metadata
+        .retrying
+        .as_ref()
+        .is_some_and(|retrying| retrying.is_enabled())
+    {
+        validate_retrying_query(name, &sql)?;
+    }
+
+    if metadata.bulk_update {
+        validate_bulk_update_query(name, &sql)?;
+    }
+
+    if metadata.prepare == Some(false) {
+        validate_unprepared_query(name, &sql)?;
+    }
+
+    if metadata
+        .filter_type
+        .as_ref()
+        .is_some_and(|filter_type| filter_type.is_enabled())
+    {
+        validate_filter_query(name, &sql)?;
+    }
+
+    validate_feature_flag(
+        name,
+        metadata.feature_flag.as_deref(),
+        metadata.feature_flag_cfg,
+    )?;
+
+    validate_check_values(name, &metadata.check_values)?;
+
+    validate_currency_columns(name, &metadata.currency_columns)?;
+
+    validate_array_element_types(name, metadata.types.as_ref())?;
+
+    validate_map_row(
+        name,
+        metadata.map_row.as_deref(),
+        metadata.try_map_row.as_deref(),
+    )?;
+
+    validate_get_or_create_query(name, metadata.expect.as_ref(), metadata.insert.as_deref())?;
+
+    if let Some(unit) = &metadata.unit_of_work {
+        validate_unit_of_work_query(name, unit)?;
+    }
+
+    validate_parameter_references(
+        path,
+        name,
+        &sql,
+        metadata.shard_by.as_deref(),
+        metadata.telemetry.include_params.as_deref(),
+        metadata.types.as_ref(),
+    )?;
+
+    if let Some(message) = &metadata.not_found_message {
+        validate_not_found_message(
+            name,
+            &sql,
+            message,
+            metadata.expect.clone().unwrap_or_default(),
+            metadata.shard_by.as_deref(),
+            metadata
+                .conditions_type
+                .as_ref()
+                .is_some_and(|conditions_type| conditions_type.is_enabled()),
+        )?;
+    }
+
+    if metadata
+        .search_path
+        .iter()
+        .any(|schema| schema.trim().is_empty())
+    {
+        anyhow::bail!("Query '{}' search_path contains an empty schema name", name);
+    }
+
+    if metadata.search_path_at_runtime && metadata.search_path.is_empty() {
+        anyhow::bail!(
+            "Query '{}' sets search_path_at_runtime, which requires search_path",
+            name
+        );
+    }
+
+    if let Some(baseline) = &metadata.baseline {
+        let values = [baseline.total_cost, baseline.rows, baseline.tolerance];
+        if values
+            .iter()
+            .flatten()
+            .any(|value| !value.is_finite() || *value < 0.0)
+        {
+            anyhow::bail!(
+                "Query '{}' baseline values must be non-negative numbers",
+                name
+            );
+        }
+        if baseline.total_cost.is_none() && baseline.rows.is_none() {
+            anyhow::bail!(
+                "Query '{}' baseline must set total_cost or rows, record them with `automodel baseline`",
+                name
+            );
+        }
+    }
+
+    let assumes = metadata
+        .assumes
+        .iter()
+        .map(|(key, value)| crate::query_definition::SchemaAssumption::parse(key, value))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| anyhow::anyhow!("Query '{}' has an invalid assumes entry: {}", name, e))?;
+
+    let (archive_source, sql) =
+        archive_sql(&sql).map_err(|e| anyhow::anyhow!("Query '{}' {}", name, e))?;
+    let archive_to = archive_source
+        .zip(metadata.archive_to.clone())
+        .map(|(source, archive)| crate::query_definition::ArchiveTables { source, archive });
+
     // Generate SQL variants and convert to positional parameters at parse time
-    let sql_variants_raw = generate_query_variants(&sql);
+    let sql_variants_raw = generate_query_variants(&sql, max_variant_combinations);
     let sql_variants: Vec<(String, Vec<String>, String)> = sql_variants_raw
         .into_iter()
         .map(|(variant_sql, variant_label)| {
@@ -335,14 +913,20 @@ async fn parse_sql_file(
         })
         .collect();
 
+    if metadata.isolation_sensitive {
+        validate_isolation_sensitive(name, &sql_variants[0].1, &metadata.analysis_params)?;
+    }
+
     Ok(QueryDefinition {
         name: name.to_string(),
         sql,
         sql_variants,
+        cfg_branches,
         description: metadata.description,
         module: module.to_string(),
         expect: metadata.expect.unwrap_or_default(),
         types: metadata.types,
+        param_types: metadata.param_types,
         telemetry: crate::query_definition::QueryTelemetryConfig {
             level: metadata.telemetry.level.unwrap_or(defaults.telemetry.level),
             include_params: metadata.telemetry.include_params,
@@ -350,75 +934,1456 @@ async fn parse_sql_file(
                 .telemetry
                 .include_sql
                 .unwrap_or(defaults.telemetry.include_sql),
+            max_param_length: metadata
+                .telemetry
+                .max_param_length
+                .or(defaults.telemetry.max_param_length),
+            max_param_elements: metadata
+                .telemetry
+                .max_param_elements
+                .or(defaults.telemetry.max_param_elements),
         },
         ensure_indexes: metadata.ensure_indexes.unwrap_or(defaults.ensure_indexes),
         multiunzip: metadata.multiunzip.unwrap_or(false),
         conditions_type: metadata.conditions_type.unwrap_or_default(),
         parameters_type: metadata.parameters_type.unwrap_or_default(),
+        filter_type: metadata.filter_type.unwrap_or_default(),
         return_type: metadata.return_type,
+        return_shape,
         error_type: metadata.error_type,
         conditions_type_derives: metadata.conditions_type_derives,
         parameters_type_derives: metadata.parameters_type_derives,
         return_type_derives: metadata.return_type_derives,
+        flatten: metadata.flatten,
         error_type_derives: metadata.error_type_derives,
+        auto_derives: metadata.auto_derives.unwrap_or(defaults.auto_derives),
+        dynamic: metadata.dynamic.unwrap_or(false),
+        min_pg_version: metadata.min_pg_version.or(defaults.min_pg_version),
+        iterate: metadata.iterate,
+        analysis_params: metadata.analysis_params,
+        allow: metadata
+            .allow
+            .iter()
+            .filter_map(|entry| match entry {
+                AllowEntry::Check(check) => Some(*check),
+                AllowEntry::Diagnostic(_) => None,
+            })
+            .collect(),
+        allow_diagnostics: metadata
+            .allow
+            .iter()
+            .filter_map(|entry| match entry {
+                AllowEntry::Diagnostic(code) => Some(*code),
+                AllowEntry::Check(_) => None,
+            })
+            .collect(),
+        numeric_as: metadata.numeric_as,
+        currency_columns: metadata.currency_columns,
+        char_flags: metadata.char_flags,
+        // Sampled plans are fetched with a prepared EXPLAIN
+        plan_sampling: metadata.prepare != Some(false)
+            && metadata.plan_sampling.unwrap_or(defaults.plan_sampling),
+        cardinality_drift: metadata
+            .cardinality_drift
+            .unwrap_or(defaults.cardinality_drift),
+        blocking: metadata.blocking.unwrap_or(defaults.blocking),
+        cancellable,
+        slow_query_ms: metadata
+            .slow_query_ms
+            .or(defaults.slow_query_ms)
+            .filter(|ms| *ms > 0),
+        requires_session: metadata.requires_session,
+        cascade_preview: metadata.cascade_preview,
+        archive_to,
+        id_only: metadata.id_only,
+        retrying: metadata.retrying.filter(|retrying| retrying.is_enabled()),
+        scope: metadata.scope,
+        bulk_update: metadata.bulk_update,
+        prepare: metadata.prepare.unwrap_or(true),
+        proto: metadata.proto,
+        cache_invalidation: metadata.cache_invalidation,
+        cache_invalidate_on: metadata.cache_invalidate_on,
+        feature_flag: metadata.feature_flag,
+        feature_flag_cfg: metadata.feature_flag_cfg,
+        large_list_strategy: metadata.large_list_strategy,
+        assumes,
+        shard_by: metadata.shard_by,
+        canonical_shard,
+        batch: metadata.batch,
+        check_values: metadata.check_values,
+        map_row: metadata.map_row,
+        try_map_row: metadata.try_map_row,
+        insert: metadata.insert,
+        unit_of_work: metadata.unit_of_work,
+        search_path: metadata.search_path,
+        search_path_at_runtime: metadata.search_path_at_runtime,
+        not_found_message: metadata.not_found_message,
+        sensitivity: metadata.sensitivity,
+        redact_debug: metadata.redact_debug,
+        isolation_sensitive: metadata.isolation_sensitive,
+        baseline: metadata
+            .baseline
+            .map(|baseline| crate::query_definition::PlanBaseline {
+                tolerance: baseline.tolerance.or(defaults.baseline_tolerance),
+                ..baseline
+            }),
+        dialect: defaults.dialect,
+        sqlx_version: defaults.sqlx_version,
+        modified,
+        source_file: None,
     })
 }
 
-/// Scan for SQL files in a queries directory and load them as QueryDefinitions
-/// Directory structure: queries/{module}/{query_name}.sql
-pub async fn scan_sql_files(
-    queries_dir: &Path,
-    defaults: crate::DefaultsConfig,
-) -> Result<Vec<QueryDefinition>> {
-    let mut queries = Vec::new();
-
-    // Check if queries directory exists
-    if !queries_dir.exists() {
-        return Ok(queries);
-    }
+/// Metadata option taking part in the conflicts of `OPTION_CONFLICTS`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum QueryOption {
+    Multiunzip,
+    Iterate,
+    Batch,
+    ShardBy,
+    TempTable,
+    RequiresSession,
+    ExactlyOneOrInsert,
+    FilterType,
+    ConditionsType,
+    ParametersType,
+    CascadePreview,
+    IdOnly,
+    BulkUpdate,
+    Unprepared,
+    Retrying,
+    ArchiveTo,
+    NotFoundMessage,
+    CheckValues,
+    /// A `check_values` entry with the `const` style
+    CheckValuesConst,
+    /// A `currency_columns` entry naming a parameter
+    CurrencyParams,
+    /// A `char_flags` entry naming a parameter
+    CharFlagParams,
+    /// A `name[]` key of `types` naming a parameter
+    ArrayElementParams,
+    Cancellable,
+    CacheInvalidation,
+    CacheInvalidateOn,
+    UnitOfWork,
+    FeatureFlag,
+    SearchPathAtRuntime,
+    IsolationSensitive,
+    TupleShape,
+    ReturnType,
+    ReturnTypeDerives,
+    Flatten,
+    /// The CockroachDB dialect, which has no LISTEN
+    Cockroach,
+}
 
-    // Collect all SQL file paths first, then sort them
-    let mut all_sql_files = Vec::new();
+impl QueryOption {
+    const ALL: &'static [QueryOption] = &[
+        QueryOption::Multiunzip,
+        QueryOption::Iterate,
+        QueryOption::Batch,
+        QueryOption::ShardBy,
+        QueryOption::TempTable,
+        QueryOption::RequiresSession,
+        QueryOption::ExactlyOneOrInsert,
+        QueryOption::FilterType,
+        QueryOption::ConditionsType,
+        QueryOption::ParametersType,
+        QueryOption::CascadePreview,
+        QueryOption::IdOnly,
+        QueryOption::BulkUpdate,
+        QueryOption::Unprepared,
+        QueryOption::Retrying,
+        QueryOption::ArchiveTo,
+        QueryOption::NotFoundMessage,
+        QueryOption::CheckValues,
+        QueryOption::CheckValuesConst,
+        QueryOption::CurrencyParams,
+        QueryOption::CharFlagParams,
+        QueryOption::ArrayElementParams,
+        QueryOption::Cancellable,
+        QueryOption::CacheInvalidation,
+        QueryOption::CacheInvalidateOn,
+        QueryOption::UnitOfWork,
+        QueryOption::FeatureFlag,
+        QueryOption::SearchPathAtRuntime,
+        QueryOption::IsolationSensitive,
+        QueryOption::TupleShape,
+        QueryOption::ReturnType,
+        QueryOption::ReturnTypeDerives,
+        QueryOption::Flatten,
+        QueryOption::Cockroach,
+    ];
 
-    // Read all module directories
-    let mut module_dirs = fs::read_dir(queries_dir).await.with_context(|| {
-        format!(
-            "Failed to read queries directory: {}",
-            queries_dir.display()
-        )
-    })?;
+    /// The option as written in the metadata block
+    fn name(self) -> &'static str {
+        match self {
+            QueryOption::Multiunzip => "multiunzip",
+            QueryOption::Iterate => "iterate",
+            QueryOption::Batch => "batch",
+            QueryOption::ShardBy => "shard_by",
+            QueryOption::TempTable => "large_list_strategy: temp_table",
+            QueryOption::RequiresSession => "requires_session",
+            QueryOption::ExactlyOneOrInsert => "expect: exactly_one_or_insert",
+            QueryOption::FilterType => "filter_type",
+            QueryOption::ConditionsType => "conditions_type",
+            QueryOption::ParametersType => "parameters_type",
+            QueryOption::CascadePreview => "cascade_preview",
+            QueryOption::IdOnly => "id_only",
+            QueryOption::BulkUpdate => "bulk_update",
+            QueryOption::Unprepared => "prepare: false",
+            QueryOption::Retrying => "retrying",
+            QueryOption::ArchiveTo => "archive_to",
+            QueryOption::NotFoundMessage => "not_found_message",
+            QueryOption::CheckValues => "check_values",
+            QueryOption::CheckValuesConst => "check_values: const",
+            QueryOption::CurrencyParams => "currency_columns for parameters",
+            QueryOption::CharFlagParams => "char_flags for parameters",
+            QueryOption::ArrayElementParams => "types of array elements for parameters",
+            QueryOption::Cancellable => "cancellable",
+            QueryOption::CacheInvalidation => "cache_invalidation",
+            QueryOption::CacheInvalidateOn => "cache_invalidate_on",
+            QueryOption::UnitOfWork => "unit_of_work",
+            QueryOption::FeatureFlag => "feature_flag",
+            QueryOption::SearchPathAtRuntime => "search_path_at_runtime",
+            QueryOption::IsolationSensitive => "isolation_sensitive",
+            QueryOption::TupleShape => "return_shape: tuple",
+            QueryOption::ReturnType => "return_type",
+            QueryOption::ReturnTypeDerives => "return_type_derives",
+            QueryOption::Flatten => "flatten",
+            QueryOption::Cockroach => "dialect: cockroach",
+        }
+    }
+}
 
-    while let Some(module_entry) = module_dirs.next_entry().await? {
-        let module_path = module_entry.path();
+/// Options which cannot be combined with the first option of each entry
+/// Options binding parameters outside of the function arguments (multiunzip, iterate, batch,
+/// conditions_type, ...) conflict with the options converting or checking those arguments
+const OPTION_CONFLICTS: &[(QueryOption, &[QueryOption])] = {
+    use QueryOption::*;
+    &[
+        (TupleShape, &[ReturnType, ReturnTypeDerives]),
+        (Flatten, &[TupleShape]),
+        (TempTable, &[Multiunzip, Iterate, ConditionsType]),
+        (
+            Batch,
+            &[Multiunzip, Iterate, ShardBy, TempTable, ConditionsType],
+        ),
+        (ShardBy, &[Iterate, TempTable]),
+        // Cancellable variants run the query function on a connection of their own
+        (Cancellable, &[RequiresSession, ExactlyOneOrInsert]),
+        // Triggers are created on the tables of the query, which sharded queries only know at
+        // runtime
+        (CacheInvalidation, &[ShardBy, Cockroach]),
+        // Cached functions key their results by the arguments of the plain query function
+        (
+            CacheInvalidateOn,
+            &[
+                Multiunzip,
+                Iterate,
+                Batch,
+                ShardBy,
+                RequiresSession,
+                TempTable,
+                ExactlyOneOrInsert,
+                FilterType,
+                ConditionsType,
+                ParametersType,
+                Cockroach,
+            ],
+        ),
+        // Iteration, batching and get-or-create helpers run on executors, pools and transactions
+        (RequiresSession, &[Iterate, Batch, ExactlyOneOrInsert]),
+        (
+            CascadePreview,
+            &[
+                Multiunzip,
+                Iterate,
+                Batch,
+                ShardBy,
+                TempTable,
+                ConditionsType,
+                ParametersType,
+            ],
+        ),
+        (
+            ArchiveTo,
+            &[
+                Multiunzip,
+                Iterate,
+                Batch,
+                ShardBy,
+                CascadePreview,
+                IdOnly,
+                BulkUpdate,
+                Unprepared,
+                TempTable,
+                FilterType,
+            ],
+        ),
+        (
+            IdOnly,
+            &[
+                Multiunzip,
+                Iterate,
+                Batch,
+                ShardBy,
+                CascadePreview,
+                ExactlyOneOrInsert,
+                TempTable,
+                ConditionsType,
+                ParametersType,
+            ],
+        ),
+        (
+            Retrying,
+            &[
+                Multiunzip,
+                Iterate,
+                Batch,
+                ShardBy,
+                ExactlyOneOrInsert,
+                TempTable,
+                ConditionsType,
+                ParametersType,
+            ],
+        ),
+        (
+            BulkUpdate,
+            &[
+                Multiunzip,
+                Iterate,
+                Batch,
+                ShardBy,
+                IdOnly,
+                ExactlyOneOrInsert,
+                TempTable,
+                FilterType,
+                ConditionsType,
+                ParametersType,
+            ],
+        ),
+        (
+            Unprepared,
+            &[
+                Multiunzip,
+                Iterate,
+                Batch,
+                ShardBy,
+                CascadePreview,
+                IdOnly,
+                BulkUpdate,
+                ExactlyOneOrInsert,
+                FilterType,
+                ConditionsType,
+                ParametersType,
+            ],
+        ),
+        (
+            FilterType,
+            &[
+                Multiunzip,
+                Iterate,
+                Batch,
+                ShardBy,
+                CascadePreview,
+                IdOnly,
+                NotFoundMessage,
+                CheckValues,
+                ExactlyOneOrInsert,
+                TempTable,
+                ConditionsType,
+                ParametersType,
+            ],
+        ),
+        (FeatureFlag, &[Iterate, Batch, CascadePreview]),
+        (
+            CheckValuesConst,
+            &[Multiunzip, Iterate, Batch, ConditionsType],
+        ),
+        (
+            CurrencyParams,
+            &[Multiunzip, Iterate, Batch, ConditionsType],
+        ),
+        (
+            CharFlagParams,
+            &[
+                Multiunzip,
+                Iterate,
+                Batch,
+                CascadePreview,
+                BulkUpdate,
+                Retrying,
+                ConditionsType,
+                ParametersType,
+            ],
+        ),
+        (
+            ArrayElementParams,
+            &[
+                Multiunzip,
+                Iterate,
+                Batch,
+                CascadePreview,
+                BulkUpdate,
+                Retrying,
+                ConditionsType,
+                ParametersType,
+            ],
+        ),
+        (
+            ExactlyOneOrInsert,
+            &[
+                Multiunzip,
+                Iterate,
+                Batch,
+                ShardBy,
+                TempTable,
+                ConditionsType,
+                ParametersType,
+            ],
+        ),
+        (
+            UnitOfWork,
+            &[
+                Multiunzip,
+                Iterate,
+                Batch,
+                ShardBy,
+                RequiresSession,
+                ExactlyOneOrInsert,
+                FilterType,
+                ConditionsType,
+                ParametersType,
+            ],
+        ),
+        (NotFoundMessage, &[Multiunzip]),
+        // The search_path is set in a transaction begun on the executor of the generated
+        // function, which iteration, batching and sessions do not run the statement on
+        (
+            SearchPathAtRuntime,
+            &[RequiresSession, Iterate, Batch, TempTable],
+        ),
+        (IsolationSensitive, &[ShardBy]),
+    ]
+};
 
-        if !module_path.is_dir() {
+/// Fail for the first entry of `OPTION_CONFLICTS` whose option is set together with options it
+/// cannot be combined with, listing those options
+fn check_option_conflicts(name: &str, set_options: &[QueryOption]) -> Result<()> {
+    for (option, conflicts) in OPTION_CONFLICTS {
+        if !set_options.contains(option) {
             continue;
         }
-
-        let module_name = module_path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .ok_or_else(|| anyhow::anyhow!("Invalid module directory name"))?
-            .to_string();
-
-        // Validate module name
-        validate_module_name(&module_name).map_err(|e| {
-            anyhow::anyhow!("Invalid module directory name '{}': {}", module_name, e)
-        })?;
-
-        // Read all SQL files in the module directory
-        let mut sql_files_in_module = fs::read_dir(&module_path).await.with_context(|| {
-            format!("Failed to read module directory: {}", module_path.display())
-        })?;
-
-        while let Some(sql_entry) = sql_files_in_module.next_entry().await? {
-            let sql_path = sql_entry.path();
-
-            if sql_path.extension().and_then(|e| e.to_str()) != Some("sql") {
-                continue;
-            }
-
-            all_sql_files.push((sql_path, module_name.clone()));
+        let conflicting: Vec<&str> = conflicts
+            .iter()
+            .filter(|conflict| set_options.contains(conflict))
+            .map(|conflict| conflict.name())
+            .collect();
+        if !conflicting.is_empty() {
+            anyhow::bail!(
+                "Query '{}' uses {}, which cannot be combined with {}",
+                name,
+                option.name(),
+                conflicting.join(", ")
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Validate the channel and key of a query with `cache_invalidate_on`
+/// The channel is listened to by name, and the key must be a parameter of the query
+fn validate_cache_invalidate_on(
+    name: &str,
+    sql: &str,
+    invalidate_on: &crate::query_definition::CacheInvalidateOn,
+) -> Result<()> {
+    let channel = invalidate_on.channel();
+    if channel.is_empty()
+        || channel.len() > 63
+        || !channel
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'_')
+    {
+        anyhow::bail!(
+            "Query '{}' cache_invalidate_on channel '{}' must be 1 to 63 ASCII letters, digits or underscores",
+            name,
+            channel
+        );
+    }
+    if let Some(key) = invalidate_on.key() {
+        let params = crate::types_extractor::parse_parameter_names_from_sql(sql);
+        if !params
+            .iter()
+            .any(|param| param.trim_end_matches('?') == key)
+        {
+            anyhow::bail!(
+                "Query '{}' cache_invalidate_on key '{}' is not a parameter of the query",
+                name,
+                key
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Check that a query with `large_list_strategy: temp_table` can be rewritten to join temp tables
+fn validate_large_list_query(name: &str, sql: &str) -> Result<()> {
+    if sql.contains("#[") {
+        anyhow::bail!(
+            "Query '{}' uses large_list_strategy: temp_table, which is not supported for conditional queries",
+            name
+        );
+    }
+
+    let list_params = crate::types_extractor::large_list_params(sql);
+    if list_params.is_empty() {
+        anyhow::bail!(
+            "Query '{}' uses large_list_strategy: temp_table but has no `= ANY(#{{param}})` array parameter",
+            name
+        );
+    }
+
+    // Every use of a list parameter must be replaced by the temp table join
+    let rewritten_sql = crate::types_extractor::rewrite_large_list_params(sql, &list_params);
+    let remaining_params = crate::types_extractor::parse_parameter_names_from_sql(&rewritten_sql);
+    for param in &list_params {
+        if remaining_params.contains(param) {
+            anyhow::bail!(
+                "Query '{}' uses large_list_strategy: temp_table, but parameter '{}' is not only used as `= ANY(#{{{}}})`",
+                name,
+                param,
+                param
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Check that a query with `batch` is a single-row INSERT whose rows can be concatenated
+fn validate_batch_query(
+    name: &str,
+    sql: &str,
+    expect: crate::query_definition::ExpectedResult,
+) -> Result<()> {
+    if sql.contains("#[") {
+        anyhow::bail!(
+            "Query '{}' uses batch, which is not supported for conditional queries",
+            name
+        );
+    }
+    if expect != crate::query_definition::ExpectedResult::ExactlyOne {
+        anyhow::bail!(
+            "Query '{}' uses batch, which requires expect: exactly_one",
+            name
+        );
+    }
+
+    let values_row = if sql.trim_start().to_uppercase().starts_with("INSERT") {
+        crate::types_extractor::single_values_row(sql)
+    } else {
+        None
+    };
+    let Some(values_row) = values_row else {
+        anyhow::bail!(
+            "Query '{}' uses batch, which requires an INSERT of a single `VALUES (...)` row",
+            name
+        );
+    };
+
+    // Every row of the batch binds its own parameters, so they may only appear in the VALUES row
+    let outside_params = crate::types_extractor::parse_parameter_names_from_sql(&format!(
+        "{}{}",
+        &sql[..values_row.start],
+        &sql[values_row.end..]
+    ));
+    if let Some(param) = outside_params.first() {
+        anyhow::bail!(
+            "Query '{}' uses batch, but parameter '{}' is used outside the VALUES row",
+            name,
+            param.trim_end_matches('?')
+        );
+    }
+    Ok(())
+}
+
+/// Check that `shard_by` and the `${shard}` placeholder are used together
+fn validate_sharded_query(
+    name: &str,
+    sql: &str,
+    shard_by: Option<&str>,
+    canonical_shard: Option<&str>,
+) -> Result<()> {
+    use crate::types_extractor::{is_valid_shard, SHARD_PLACEHOLDER};
+
+    let has_placeholder = sql.contains(SHARD_PLACEHOLDER);
+    let Some(shard_by) = shard_by else {
+        if has_placeholder {
+            anyhow::bail!(
+                "Query '{}' uses {} but does not set shard_by",
+                name,
+                SHARD_PLACEHOLDER
+            );
+        }
+        return Ok(());
+    };
+
+    if !is_valid_rust_identifier(shard_by) {
+        anyhow::bail!(
+            "Query '{}' shard_by '{}' is not a valid Rust identifier",
+            name,
+            shard_by
+        );
+    }
+    if !has_placeholder {
+        anyhow::bail!(
+            "Query '{}' sets shard_by but its SQL does not contain {}, e.g. `FROM orders_{}`",
+            name,
+            SHARD_PLACEHOLDER,
+            SHARD_PLACEHOLDER
+        );
+    }
+    if sql.contains("#[") {
+        anyhow::bail!(
+            "Query '{}' uses shard_by, which is not supported for conditional queries",
+            name
+        );
+    }
+
+    // The shard key is a separate argument and is never bound as a query parameter
+    let params = crate::types_extractor::parse_parameter_names_from_sql(sql);
+    if params
+        .iter()
+        .any(|param| param.trim_end_matches('?') == shard_by)
+    {
+        anyhow::bail!(
+            "Query '{}' shard_by '{}' conflicts with the query parameter of the same name",
+            name,
+            shard_by
+        );
+    }
+
+    match canonical_shard {
+        None => anyhow::bail!(
+            "Query '{}' uses shard_by but no canonical_shard is configured to analyze it against",
+            name
+        ),
+        Some(shard) if !is_valid_shard(shard) => anyhow::bail!(
+            "Query '{}' canonical_shard '{}' may only contain ASCII letters, digits and underscores",
+            name,
+            shard
+        ),
+        Some(_) => Ok(()),
+    }
+}
+
+/// Check that `check_values` keys name a column
+fn validate_check_values(
+    name: &str,
+    check_values: &std::collections::BTreeMap<String, crate::query_definition::CheckValuesStyle>,
+) -> Result<()> {
+    for key in check_values.keys() {
+        let is_column = key
+            .rsplit_once('.')
+            .is_some_and(|(table, column)| !table.is_empty() && !column.is_empty());
+        if !is_column {
+            anyhow::bail!(
+                "Query '{}' check_values entry '{}' must be written as table.column",
+                name,
+                key
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Check that `currency_columns` values are ISO 4217 codes
+fn validate_currency_columns(
+    name: &str,
+    currency_columns: &std::collections::BTreeMap<String, String>,
+) -> Result<()> {
+    for (column, currency) in currency_columns {
+        if currency.len() != 3 || !currency.chars().all(|ch| ch.is_ascii_uppercase()) {
+            anyhow::bail!(
+                "Query '{}' currency_columns.{}: '{}' is not an ISO 4217 currency code such as EUR",
+                name,
+                column,
+                currency
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Check that the `name[]` keys of `types` map array elements to a type
+fn validate_array_element_types(
+    name: &str,
+    types: Option<&std::collections::HashMap<String, String>>,
+) -> Result<()> {
+    for (key, element_type) in types
+        .map(sorted_entries)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(key, _)| key.ends_with("[]"))
+    {
+        if element_type.trim().is_empty() || element_type.starts_with("Vec<") {
+            anyhow::bail!(
+                "Query '{}' types.{}: '{}' must be the element type, e.g. crate::models::Tag",
+                name,
+                key,
+                element_type
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Whether `column` is used as a parameter of the SQL, e.g. `#{email}` or `#{email?}`
+fn is_sql_param(sql: &str, column: &str) -> bool {
+    sql.contains(&format!("#{{{}}}", column)) || sql.contains(&format!("#{{{}?}}", column))
+}
+
+/// Check that at most one of `map_row` and `try_map_row` is set and that it names a function path
+fn validate_map_row(name: &str, map_row: Option<&str>, try_map_row: Option<&str>) -> Result<()> {
+    let (key, path) = match (map_row, try_map_row) {
+        (None, None) => return Ok(()),
+        (Some(_), Some(_)) => anyhow::bail!(
+            "Query '{}' sets both map_row and try_map_row, only one can be applied",
+            name
+        ),
+        (Some(path), None) => ("map_row", path),
+        (None, Some(path)) => ("try_map_row", path),
+    };
+
+    // Paths are resolved from the generated module, e.g. `crate::mappers::redact_user`
+    let is_path = path.split("::").enumerate().all(|(i, segment)| {
+        is_valid_rust_identifier(segment)
+            || (i == 0 && matches!(segment, "crate" | "super" | "self"))
+    });
+    if !is_path {
+        anyhow::bail!(
+            "Query '{}' {} '{}' is not a Rust function path, e.g. crate::mappers::redact_user",
+            name,
+            key,
+            path
+        );
+    }
+    Ok(())
+}
+
+/// Split the `NAME value` of a `-- @define NAME value` line
+fn parse_define(define: &str) -> Result<(String, String)> {
+    let define = define.trim();
+    let (name, value) = define
+        .split_once(char::is_whitespace)
+        .unwrap_or((define, ""));
+    if !is_define_name(name) {
+        anyhow::bail!(
+            "'{}' is not a valid name, use letters, digits and underscores",
+            name
+        );
+    }
+    let value = value.trim();
+    if value.is_empty() {
+        anyhow::bail!("{} has no value", name);
+    }
+    Ok((name.to_string(), value.to_string()))
+}
+
+/// Start of a block of SQL included only when a cargo feature is enabled or disabled
+const CFG_START: &str = "#[cfg(";
+
+/// End of a `#[cfg(...)]` block
+const CFG_END: &str = "#[end]";
+
+/// Feature name as cargo passes it to build scripts, lowercase with `-` replaced by `_`
+fn normalize_feature(feature: &str) -> String {
+    feature.to_ascii_lowercase().replace('-', "_")
+}
+
+/// Normalized features of the `#[cfg(...)]` blocks of a SQL file, in order of appearance
+/// Files with malformed blocks return the features found so far, their errors are reported when
+/// the queries are parsed
+pub(crate) fn cfg_block_features(sql: &str) -> Vec<String> {
+    let mut features = Vec::new();
+    let _ = resolve_cfg_blocks(sql, &mut |feature| {
+        let feature = normalize_feature(feature);
+        if !features.contains(&feature) {
+            features.push(feature);
+        }
+        true
+    });
+    features
+}
+
+/// Keep or remove the `#[cfg(feature = "name")] ... #[end]` and
+/// `#[cfg(not(feature = "name"))] ... #[end]` blocks of the SQL
+/// `enabled` is called with the feature of every block in order and tells whether it is enabled
+fn resolve_cfg_blocks(sql: &str, enabled: &mut dyn FnMut(&str) -> bool) -> Result<String> {
+    let mut result = String::with_capacity(sql.len());
+    let mut rest = sql;
+    while let Some(start) = rest.find(CFG_START) {
+        if rest[..start].contains(CFG_END) {
+            anyhow::bail!("uses {} without a #[cfg(...)] block before it", CFG_END);
+        }
+        let after = &rest[start + CFG_START.len()..];
+        let close = after
+            .find(")]")
+            .ok_or_else(|| anyhow::anyhow!("has a #[cfg( block without a closing )]"))?;
+        let (feature, when_enabled) = parse_cfg_condition(&after[..close])?;
+        let body = skip_blank_line(&after[close + 2..]);
+        let end = body.find(CFG_END).ok_or_else(|| {
+            anyhow::anyhow!(
+                "has a #[cfg({})] block without {}",
+                &after[..close],
+                CFG_END
+            )
+        })?;
+        if body[..end].contains(CFG_START) {
+            anyhow::bail!("has nested #[cfg(...)] blocks, which are not supported");
+        }
+        // Markers on lines of their own are removed with their line
+        result.push_str(trim_blank_line_end(&rest[..start]));
+        let content = trim_blank_line_end(&body[..end]);
+        if enabled(&feature) == when_enabled {
+            result.push_str(content);
+        }
+        rest = &body[end + CFG_END.len()..];
+        if content.is_empty() || content.ends_with('\n') {
+            rest = skip_blank_line(rest);
+        }
+    }
+    if rest.contains(CFG_END) {
+        anyhow::bail!("uses {} without a #[cfg(...)] block before it", CFG_END);
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Text after the rest of the current line when it is blank, e.g. after a `#[cfg(...)]` marker
+fn skip_blank_line(text: &str) -> &str {
+    match text.split_once('\n') {
+        Some((line, next)) if line.trim().is_empty() => next,
+        _ => text,
+    }
+}
+
+/// Text without the whitespace starting its last line, e.g. before a `#[cfg(...)]` marker
+fn trim_blank_line_end(text: &str) -> &str {
+    let line_start = text.rfind('\n').map_or(0, |i| i + 1);
+    if text[line_start..].trim().is_empty() {
+        &text[..line_start]
+    } else {
+        text
+    }
+}
+
+/// Feature of a `#[cfg(...)]` condition and whether the block is included when it is enabled
+fn parse_cfg_condition(condition: &str) -> Result<(String, bool)> {
+    let condition = condition.trim();
+    let (predicate, when_enabled) = match condition
+        .strip_prefix("not(")
+        .and_then(|inner| inner.strip_suffix(')'))
+    {
+        Some(inner) => (inner.trim(), false),
+        None => (condition, true),
+    };
+    let feature = predicate
+        .strip_prefix("feature")
+        .and_then(|value| value.trim_start().strip_prefix('='))
+        .and_then(|value| value.trim().strip_prefix('"'))
+        .and_then(|value| value.strip_suffix('"'))
+        .filter(|feature| {
+            !feature.is_empty()
+                && feature
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+        })
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "uses #[cfg({})], only feature = \"name\" and not(feature = \"name\") are supported",
+                condition
+            )
+        })?;
+    Ok((feature.to_string(), when_enabled))
+}
+
+/// Whether a name can be used as `@@NAME@@` in the SQL
+pub(crate) fn is_define_name(name: &str) -> bool {
+    !name.is_empty() && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_')
+}
+
+/// Replace `@@NAME@@` with the value of NAME, taken from DefaultsConfig::defines when set there
+/// and from the `-- @define` lines of the file otherwise
+fn substitute_defines(
+    sql: &str,
+    file_defines: &std::collections::BTreeMap<String, String>,
+    defines: &std::collections::BTreeMap<String, String>,
+) -> Result<String> {
+    let mut result = String::with_capacity(sql.len());
+    let mut rest = sql;
+    while let Some(start) = rest.find("@@") {
+        let after = &rest[start + 2..];
+        let Some(len) = after
+            .find("@@")
+            .filter(|len| is_define_name(&after[..*len]))
+        else {
+            // Not a define, e.g. the `@@` operator of full-text search
+            result.push_str(&rest[..start + 2]);
+            rest = after;
+            continue;
+        };
+        let define_name = &after[..len];
+        let value = defines
+            .get(define_name)
+            .or_else(|| file_defines.get(define_name))
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "uses @@{}@@, which is not defined, add `-- @define {} <value>` or set it in DefaultsConfig::defines",
+                    define_name,
+                    define_name
+                )
+            })?;
+        result.push_str(&rest[..start]);
+        result.push_str(value);
+        rest = &after[len + 2..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Cast every use of the parameters listed in `param_types` to their declared PostgreSQL type,
+/// e.g. `#{user_id}` to `#{user_id}::uuid`, for parameters whose type PostgreSQL infers wrongly
+/// or cannot infer
+fn apply_param_types(
+    sql: &str,
+    param_types: &std::collections::BTreeMap<String, String>,
+) -> Result<String> {
+    static TYPE_NAME: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+    let type_name = TYPE_NAME.get_or_init(|| {
+        regex::Regex::new(r"^[A-Za-z_][A-Za-z0-9_. ]*(\(\d+(,\s*\d+)?\))?(\[\])*$").unwrap()
+    });
+
+    let param_names = crate::types_extractor::parse_parameter_names_from_sql(sql);
+    for (param, pg_type) in param_types {
+        if !param_names
+            .iter()
+            .any(|name| name.trim_end_matches('?') == param)
+        {
+            anyhow::bail!(
+                "param_types key '{}' is not a parameter of the query",
+                param
+            );
+        }
+        if !type_name.is_match(pg_type.trim()) {
+            anyhow::bail!(
+                "param_types declares '{}' for parameter '{}', which is not a PostgreSQL type name",
+                pg_type,
+                param
+            );
+        }
+    }
+    if param_types.is_empty() {
+        return Ok(sql.to_string());
+    }
+
+    let mut result = String::with_capacity(sql.len());
+    let mut rest = sql;
+    while let Some(start) = rest.find("#{") {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        let placeholder = &rest[start..start + len + 1];
+        result.push_str(&rest[..start]);
+        result.push_str(placeholder);
+        if let Some(pg_type) = param_types.get(placeholder[2..len].trim_end_matches('?')) {
+            result.push_str("::");
+            result.push_str(pg_type.trim());
+        }
+        rest = &rest[start + len + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Check that `expect: exactly_one_or_insert` and `insert` are used together
+fn validate_get_or_create_query(
+    name: &str,
+    expect: Option<&crate::query_definition::ExpectedResult>,
+    insert: Option<&str>,
+) -> Result<()> {
+    let is_get_or_create =
+        expect == Some(&crate::query_definition::ExpectedResult::ExactlyOneOrInsert);
+    match insert {
+        None if is_get_or_create => anyhow::bail!(
+            "Query '{}' uses expect: exactly_one_or_insert, which requires `insert` naming the query inserting missing rows",
+            name
+        ),
+        None => return Ok(()),
+        Some(_) if !is_get_or_create => anyhow::bail!(
+            "Query '{}' sets insert, which requires expect: exactly_one_or_insert",
+            name
+        ),
+        Some(insert) if insert == name => anyhow::bail!(
+            "Query '{}' insert must name another query of the module",
+            name
+        ),
+        Some(_) => {}
+    }
+    Ok(())
+}
+
+/// Validate a query with `unit_of_work`
+/// Steps are called with plain arguments inside the transaction of the unit
+fn validate_unit_of_work_query(name: &str, unit: &str) -> Result<()> {
+    if !is_valid_rust_identifier(unit) {
+        anyhow::bail!(
+            "Query '{}' unit_of_work '{}' is not a valid Rust identifier",
+            name,
+            unit
+        );
+    }
+    Ok(())
+}
+
+/// Validate the `feature_flag` of a query
+/// Flag names are used as cargo feature names by `feature_flag_cfg`, and only the query function
+/// itself checks the flag
+fn validate_feature_flag(
+    name: &str,
+    feature_flag: Option<&str>,
+    feature_flag_cfg: bool,
+) -> Result<()> {
+    let Some(flag) = feature_flag else {
+        if feature_flag_cfg {
+            anyhow::bail!(
+                "Query '{}' sets feature_flag_cfg, which requires a feature_flag",
+                name
+            );
+        }
+        return Ok(());
+    };
+    let is_valid = !flag.is_empty()
+        && flag
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_' || ch == '-');
+    if !is_valid {
+        anyhow::bail!(
+            "Query '{}' has an invalid feature_flag '{}', flags may only contain ASCII letters, digits, '_' and '-'",
+            name,
+            flag
+        );
+    }
+    Ok(())
+}
+
+/// Validate a query with `id_only`
+/// The primary key is looked up during analysis, only the shape of the statement is checked here
+fn validate_id_only_query(name: &str, sql: &str) -> Result<()> {
+    if sql.contains("#[") {
+        anyhow::bail!(
+            "Query '{}' uses id_only, which is not supported for conditional queries",
+            name
+        );
+    }
+    if crate::types_extractor::insert_returning_parts(sql).is_none() {
+        anyhow::bail!(
+            "Query '{}' uses id_only, which requires an `INSERT INTO table ... RETURNING ...` statement",
+            name
+        );
+    }
+    Ok(())
+}
+
+/// Validate a query with `retrying`
+/// The unique constraints are looked up during analysis, only the shape of the statement is
+/// checked here
+fn validate_retrying_query(name: &str, sql: &str) -> Result<()> {
+    if sql.contains("#[") {
+        anyhow::bail!(
+            "Query '{}' uses retrying, which is not supported for conditional queries",
+            name
+        );
+    }
+    if crate::types_extractor::insert_returning_parts(sql).is_none()
+        || crate::types_extractor::single_values_row(sql).is_none()
+    {
+        anyhow::bail!(
+            "Query '{}' uses retrying, which requires an `INSERT INTO table (...) VALUES (...) RETURNING ...` statement",
+            name
+        );
+    }
+    Ok(())
+}
+
+/// Validate a query with `archive_to`
+/// The columns of the archive table are checked during analysis, only the shape of the statement
+/// and the table name are checked here
+fn validate_archive_to_query(name: &str, sql: &str, archive_table: &str) -> Result<()> {
+    let is_valid_name = |part: &str| {
+        part.chars()
+            .next()
+            .is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '_')
+            && part
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+    };
+    let parts: Vec<&str> = archive_table.split('.').collect();
+    if parts.len() > 2 || !parts.iter().all(|part| is_valid_name(part)) {
+        anyhow::bail!(
+            "Query '{}' archive_to '{}' is not a table name, e.g. public.users_archive",
+            name,
+            archive_table
+        );
+    }
+    if sql.contains("#[") {
+        anyhow::bail!(
+            "Query '{}' uses archive_to, which is not supported for conditional queries",
+            name
+        );
+    }
+    if crate::types_extractor::delete_statement_parts(sql).is_none() {
+        anyhow::bail!(
+            "Query '{}' uses archive_to, which requires a `DELETE FROM table ...` statement without USING",
+            name
+        );
+    }
+    Ok(())
+}
+
+/// Validate a query with `bulk_update`
+/// The parameter types are looked up during analysis, only the shape of the statement is checked here
+fn validate_bulk_update_query(name: &str, sql: &str) -> Result<()> {
+    if sql.contains("#[") {
+        anyhow::bail!(
+            "Query '{}' uses bulk_update, which is not supported for conditional queries",
+            name
+        );
+    }
+    crate::types_extractor::bulk_update_where_position(sql)
+        .map_err(|e| anyhow::anyhow!("Query '{}' uses bulk_update, which {}", name, e))?;
+    Ok(())
+}
+
+/// Validate a query with `prepare: false`
+/// The simple query protocol cannot bind parameters, so the SQL must be complete
+fn validate_unprepared_query(name: &str, sql: &str) -> Result<()> {
+    if sql.contains("#[") {
+        anyhow::bail!(
+            "Query '{}' uses prepare: false, which is not supported for conditional queries",
+            name
+        );
+    }
+    if !crate::types_extractor::parse_parameter_names_from_sql(sql).is_empty() {
+        anyhow::bail!(
+            "Query '{}' uses prepare: false, which cannot bind parameters with the simple query protocol",
+            name
+        );
+    }
+    Ok(())
+}
+
+/// Validate a query with `filter_type`
+/// The conditions of its WHERE clause become the variants of the filter enum
+fn validate_filter_query(name: &str, sql: &str) -> Result<()> {
+    if sql.contains("#[") {
+        anyhow::bail!(
+            "Query '{}' uses filter_type, which is not supported for conditional queries",
+            name
+        );
+    }
+    crate::types_extractor::filter_query_parts(sql)
+        .map_err(|e| anyhow::anyhow!("Query '{}' uses filter_type, which {}", name, e))?;
+    Ok(())
+}
+
+/// Validate a query with `cascade_preview`
+/// The preview counts the rows matched by the WHERE clause of the DELETE, so every parameter
+/// must be used there
+fn validate_cascade_preview_query(name: &str, sql: &str) -> Result<()> {
+    if sql.contains("#[") {
+        anyhow::bail!(
+            "Query '{}' uses cascade_preview, which is not supported for conditional queries",
+            name
+        );
+    }
+    let Some((_, where_clause)) = crate::types_extractor::delete_statement_parts(sql) else {
+        anyhow::bail!(
+            "Query '{}' uses cascade_preview, which requires a `DELETE FROM table WHERE ...` statement without USING",
+            name
+        );
+    };
+
+    let where_params = crate::types_extractor::parse_parameter_names_from_sql(where_clause);
+    if let Some(param) = crate::types_extractor::parse_parameter_names_from_sql(sql)
+        .into_iter()
+        .find(|param| !where_params.contains(param))
+    {
+        anyhow::bail!(
+            "Query '{}' uses cascade_preview, which requires parameter '{}' to be used in the WHERE clause",
+            name,
+            param
+        );
+    }
+    Ok(())
+}
+
+/// Check that `telemetry.include_params` and `types` keys without a table name the query's
+/// parameters, as unknown names are otherwise silently ignored
+/// `table.column` keys can also refer to output columns and are checked after analysis
+fn validate_parameter_references(
+    path: &Path,
+    name: &str,
+    sql: &str,
+    shard_by: Option<&str>,
+    include_params: Option<&[String]>,
+    types: Option<&std::collections::HashMap<String, String>>,
+) -> Result<()> {
+    let param_names: Vec<String> = crate::types_extractor::parse_parameter_names_from_sql(sql)
+        .iter()
+        .map(|param| param.trim_end_matches('?').to_string())
+        .chain(shard_by.map(str::to_string))
+        .collect();
+
+    for param in include_params.unwrap_or_default() {
+        if !param_names.contains(param) {
+            anyhow::bail!(
+                "{}: query '{}' telemetry.include_params lists '{}', which is not a parameter of the query",
+                path.display(),
+                name,
+                param
+            );
+        }
+    }
+
+    for (key, _) in types.map(sorted_entries).unwrap_or_default() {
+        let field = key.trim_end_matches("[]");
+        if !field.contains('.') && !param_names.iter().any(|param| param == field) {
+            anyhow::bail!(
+                "{}: query '{}' types key '{}' is not a parameter of the query, output columns are mapped with `table.column` keys",
+                path.display(),
+                name,
+                key
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Check that `not_found_message` is used with an expectation failing on missing rows, and that
+/// its placeholders name parameters available as function arguments
+/// Check that the SQL of an `isolation_sensitive` query can be run by its generated test
+/// param_names: parameters of the query without its conditional blocks, which is what the test runs
+fn validate_isolation_sensitive(
+    name: &str,
+    param_names: &[String],
+    analysis_params: &std::collections::HashMap<String, serde_yaml::Value>,
+) -> Result<()> {
+    for param in param_names {
+        let param = param.trim_end_matches('?');
+        if !analysis_params.contains_key(param) {
+            anyhow::bail!(
+                "Query '{}' is isolation_sensitive, which requires an analysis_params value for parameter '{}'",
+                name,
+                param
+            );
+        }
+    }
+    Ok(())
+}
+
+fn validate_not_found_message(
+    name: &str,
+    sql: &str,
+    message: &str,
+    expect: crate::query_definition::ExpectedResult,
+    shard_by: Option<&str>,
+    conditions_type: bool,
+) -> Result<()> {
+    use crate::query_definition::ExpectedResult;
+
+    if !matches!(
+        expect,
+        ExpectedResult::ExactlyOne | ExpectedResult::AtLeastOne
+    ) {
+        anyhow::bail!(
+            "Query '{}' sets not_found_message, which requires expect: exactly_one or at_least_one",
+            name
+        );
+    }
+    let (_, placeholders) = crate::utils::parse_message_template(message)
+        .map_err(|e| anyhow::anyhow!("Query '{}' has an invalid not_found_message: {}", name, e))?;
+
+    // Conditional parameters of conditions_type queries are only available in the old/new structs
+    let param_names = crate::types_extractor::parse_parameter_names_from_sql(sql);
+    for placeholder in &placeholders {
+        let is_param = param_names.iter().any(|param| {
+            param.trim_end_matches('?') == placeholder && !(conditions_type && param.ends_with('?'))
+        });
+        if !is_param && shard_by != Some(placeholder.as_str()) {
+            anyhow::bail!(
+                "Query '{}' not_found_message placeholder '{{{}}}' is not a parameter of the query",
+                name,
+                placeholder
+            );
+        }
+    }
+    Ok(())
+}
+
+/// File name of the optional module configuration inside each module directory
+pub const MODULE_CONFIG_FILE_NAME: &str = "module.yaml";
+
+/// Load module configurations from `queries/{module}/module.yaml` files
+/// Modules without a configuration file are not included in the result
+pub async fn scan_module_configs(
+    queries_dir: &Path,
+) -> Result<std::collections::HashMap<String, ModuleConfig>> {
+    let mut configs = std::collections::HashMap::new();
+    let mut errors = Vec::new();
+
+    if !queries_dir.exists() {
+        return Ok(configs);
+    }
+
+    let mut module_dirs = fs::read_dir(queries_dir).await.with_context(|| {
+        format!(
+            "Failed to read queries directory: {}",
+            queries_dir.display()
+        )
+    })?;
+
+    while let Some(module_entry) = module_dirs.next_entry().await? {
+        let config_path = module_entry.path().join(MODULE_CONFIG_FILE_NAME);
+        if !config_path.is_file() {
+            continue;
+        }
+
+        let module_name = module_entry.file_name().to_string_lossy().to_string();
+        let config = match load_module_config(&config_path, &module_name).await {
+            Ok(config) => config,
+            Err(e) => {
+                errors.push(file_error(&config_path, &e));
+                continue;
+            }
+        };
+
+        configs.insert(module_name, config);
+    }
+
+    match errors.len() {
+        0 => Ok(configs),
+        1 => anyhow::bail!("{}", errors[0]),
+        count => anyhow::bail!(
+            "{} errors in module configurations of {}:\n{}",
+            count,
+            queries_dir.display(),
+            errors.join("\n")
+        ),
+    }
+}
+
+/// Load and validate one `module.yaml` file
+async fn load_module_config(config_path: &Path, module_name: &str) -> Result<ModuleConfig> {
+    let content = fs::read_to_string(config_path)
+        .await
+        .with_context(|| format!("Failed to read {}", config_path.display()))?;
+    let config: ModuleConfig = serde_yaml::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", config_path.display()))?;
+
+    if config
+        .imports
+        .iter()
+        .chain(config.attributes.iter())
+        .any(|entry| entry.trim().is_empty())
+    {
+        anyhow::bail!(
+            "Module '{}' configuration contains an empty import or attribute",
+            module_name
+        );
+    }
+
+    Ok(config)
+}
+
+/// Scan for SQL files in a queries directory and load them as QueryDefinitions
+/// Directory structure: queries/{module}/{query_name}.sql
+pub async fn scan_sql_files(
+    queries_dir: &Path,
+    defaults: crate::DefaultsConfig,
+) -> Result<Vec<QueryDefinition>> {
+    let mut queries = Vec::new();
+
+    // Check if queries directory exists
+    if !queries_dir.exists() {
+        return Ok(queries);
+    }
+
+    // Collect all SQL file paths first, then sort them
+    let mut all_sql_files = Vec::new();
+    // Errors of all files, reported together so that they can be fixed in one iteration
+    let mut errors = Vec::new();
+
+    // Read all module directories
+    let mut module_dirs = fs::read_dir(queries_dir).await.with_context(|| {
+        format!(
+            "Failed to read queries directory: {}",
+            queries_dir.display()
+        )
+    })?;
+
+    while let Some(module_entry) = module_dirs.next_entry().await? {
+        let module_path = module_entry.path();
+
+        if !module_path.is_dir() {
+            continue;
+        }
+
+        let module_name = module_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| anyhow::anyhow!("Invalid module directory name"))?
+            .to_string();
+
+        // Validate module name
+        if let Err(e) = validate_module_name(&module_name) {
+            errors.push(format!(
+                "{}: Invalid module directory name '{}': {}",
+                module_path.display(),
+                module_name,
+                e
+            ));
+            continue;
+        }
+
+        // Read all SQL files in the module directory
+        let mut sql_files_in_module = fs::read_dir(&module_path).await.with_context(|| {
+            format!("Failed to read module directory: {}", module_path.display())
+        })?;
+
+        while let Some(sql_entry) = sql_files_in_module.next_entry().await? {
+            let sql_path = sql_entry.path();
+
+            // Metadata files without their SQL file are mistakes, e.g. a renamed query
+            if sql_path.extension().and_then(|e| e.to_str()) == Some("yaml")
+                && sql_entry.file_name() != MODULE_CONFIG_FILE_NAME
+                && !sql_path.with_extension("sql").is_file()
+            {
+                errors.push(format!(
+                    "{}: Metadata file has no SQL file {}",
+                    sql_path.display(),
+                    sql_path.with_extension("sql").display()
+                ));
+                continue;
+            }
+
+            if sql_path.extension().and_then(|e| e.to_str()) != Some("sql") {
+                continue;
+            }
+
+            all_sql_files.push((sql_path, module_name.clone()));
         }
     }
 
@@ -447,16 +2412,278 @@ pub async fn scan_sql_files(
 
         // Validate query name
         if !is_valid_rust_identifier(&query_name) {
-            anyhow::bail!(
-                "SQL file name '{}' is not a valid Rust function name. Use only alphanumeric characters and underscores, and start with a letter or underscore.",
+            errors.push(format!(
+                "{}: SQL file name '{}' is not a valid Rust function name. Use only alphanumeric characters and underscores, and start with a letter or underscore.",
+                sql_path.display(),
                 query_name
+            ));
+            continue;
+        }
+
+        match parse_sql_file(&sql_path, &module_name, &query_name, defaults.clone()).await {
+            Ok(query_def) => queries.push(query_def),
+            Err(e) => errors.push(file_error(&sql_path, &e)),
+        }
+    }
+
+    match errors.len() {
+        0 => Ok(queries),
+        1 => anyhow::bail!("{}", errors[0]),
+        count => anyhow::bail!(
+            "{} errors in queries directory {}:\n{}",
+            count,
+            queries_dir.display(),
+            errors.join("\n")
+        ),
+    }
+}
+
+/// Error message of a file, prefixed with its path unless the message already starts with it
+fn file_error(path: &Path, error: &anyhow::Error) -> String {
+    let message = format!("{:#}", error);
+    let prefix = format!("{}:", path.display());
+    if message.starts_with(&prefix) {
+        message
+    } else {
+        format!("{} {}", prefix, message)
+    }
+}
+
+/// Content of a SQL file with the `baseline` entry of its metadata block set to the estimates,
+/// keeping an explicit tolerance; the block is added when the file has none
+pub(crate) fn set_baseline(
+    content: &str,
+    estimates: &crate::query_definition::PlanEstimates,
+) -> Result<String> {
+    let lines: Vec<&str> = content.lines().collect();
+    let start = lines.iter().position(|line| line.trim() == "-- @automodel");
+    let end = start.and_then(|start| {
+        lines[start..]
+            .iter()
+            .position(|line| line.trim() == "-- @end")
+            .map(|offset| start + offset)
+    });
+    let (Some(start), Some(end)) = (start, end) else {
+        let entry = baseline_entry("--    ", estimates, None);
+        return Ok(format!("-- @automodel\n{}\n-- @end\n\n{}", entry, content));
+    };
+
+    // Indentation of a metadata line after its `--`, None for empty lines
+    let yaml_indent = |line: &str| {
+        let yaml = line.trim().strip_prefix("--")?;
+        let key = yaml.trim_start();
+        (!key.is_empty()).then(|| yaml.len() - key.len())
+    };
+    let key_indent = lines[start + 1..end]
+        .iter()
+        .filter_map(|line| yaml_indent(line))
+        .min();
+    let prefix = lines[start + 1..end]
+        .iter()
+        .find(|line| yaml_indent(line) == key_indent && key_indent.is_some())
+        .map(|line| {
+            let yaml_start = line.len() - line.trim_start().len() + 2 + key_indent.unwrap_or(0);
+            line[..yaml_start].to_string()
+        })
+        .unwrap_or_else(|| "--    ".to_string());
+
+    let entry_start = (start + 1..end).find(|&i| {
+        yaml_indent(lines[i]) == key_indent
+            && lines[i].trim()[2..].trim_start().starts_with("baseline:")
+    });
+    let mut result: Vec<String> = Vec::new();
+    match entry_start {
+        Some(entry_start) => {
+            let entry_end = (entry_start + 1..end)
+                .find(|&i| yaml_indent(lines[i]).is_some_and(|indent| Some(indent) <= key_indent))
+                .unwrap_or(end);
+            let entry_yaml = lines[entry_start..entry_end]
+                .iter()
+                .map(|line| line.trim().strip_prefix("--").unwrap_or(""))
+                .collect::<Vec<_>>()
+                .join("\n");
+            #[derive(serde::Deserialize)]
+            struct Entry {
+                baseline: crate::query_definition::PlanBaseline,
+            }
+            let entry: Entry = serde_yaml::from_str(&entry_yaml)
+                .context("Failed to parse the baseline of the metadata block")?;
+            result.extend(lines[..entry_start].iter().map(|line| line.to_string()));
+            result.push(baseline_entry(&prefix, estimates, entry.baseline.tolerance));
+            result.extend(lines[entry_end..].iter().map(|line| line.to_string()));
+        }
+        None => {
+            result.extend(lines[..end].iter().map(|line| line.to_string()));
+            result.push(baseline_entry(&prefix, estimates, None));
+            result.extend(lines[end..].iter().map(|line| line.to_string()));
+        }
+    }
+
+    let mut updated = result.join("\n");
+    if content.ends_with('\n') {
+        updated.push('\n');
+    }
+    Ok(updated)
+}
+
+/// Metadata line of a baseline, e.g. `--    baseline: { total_cost: 125.5, rows: 10 }`
+fn baseline_entry(
+    prefix: &str,
+    estimates: &crate::query_definition::PlanEstimates,
+    tolerance: Option<f64>,
+) -> String {
+    let values: Vec<String> = [
+        (
+            "total_cost",
+            estimates
+                .total_cost
+                .map(|cost| (cost * 100.0).round() / 100.0),
+        ),
+        ("rows", estimates.rows.map(f64::round)),
+        ("tolerance", tolerance),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| value.map(|value| format!("{}: {}", name, value)))
+    .collect();
+    format!("{}baseline: {{ {} }}", prefix, values.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(metadata: &str, sql: &str) -> Result<QueryDefinition> {
+        let content = format!("-- @automodel\n{}-- @end\n\n{}\n", metadata, sql);
+        parse_sql_source(
+            Path::new("queries/accounts/01_query.sql"),
+            &content,
+            "accounts",
+            "query",
+            crate::DefaultsConfig::default(),
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn every_conflict_pair_is_rejected() {
+        for (option, conflicts) in OPTION_CONFLICTS {
+            for conflict in *conflicts {
+                let error = check_option_conflicts("query", &[*conflict, *option]).unwrap_err();
+                assert_eq!(
+                    error.to_string(),
+                    format!(
+                        "Query 'query' uses {}, which cannot be combined with {}",
+                        option.name(),
+                        conflict.name()
+                    )
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn conflicts_are_listed_in_one_direction() {
+        for (option, conflicts) in OPTION_CONFLICTS {
+            assert!(
+                !conflicts.contains(option),
+                "{:?} conflicts with itself",
+                option
             );
+            for conflict in *conflicts {
+                let reversed = OPTION_CONFLICTS
+                    .iter()
+                    .any(|(other, others)| other == conflict && others.contains(option));
+                assert!(
+                    !reversed,
+                    "{:?} and {:?} are listed twice",
+                    option, conflict
+                );
+            }
+        }
+        for (i, (option, _)) in OPTION_CONFLICTS.iter().enumerate() {
+            let repeated = OPTION_CONFLICTS[i + 1..]
+                .iter()
+                .any(|(other, _)| other == option);
+            assert!(!repeated, "{:?} has several entries", option);
         }
+    }
 
-        let query_def =
-            parse_sql_file(&sql_path, &module_name, &query_name, defaults.clone()).await?;
-        queries.push(query_def);
+    #[test]
+    fn every_option_of_the_conflicts_is_detected() {
+        for (option, conflicts) in OPTION_CONFLICTS {
+            for option in conflicts.iter().chain([option]) {
+                assert!(
+                    QueryOption::ALL.contains(option),
+                    "{:?} is not in ALL",
+                    option
+                );
+            }
+        }
     }
 
-    Ok(queries)
+    #[test]
+    fn compatible_options_are_accepted() {
+        for option in QueryOption::ALL {
+            check_option_conflicts("query", &[*option]).unwrap();
+        }
+        check_option_conflicts(
+            "query",
+            &[
+                QueryOption::Iterate,
+                QueryOption::Multiunzip,
+                QueryOption::CheckValues,
+                QueryOption::Cancellable,
+            ],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn only_the_set_conflicts_are_listed() {
+        let error = check_option_conflicts(
+            "query",
+            &[
+                QueryOption::Iterate,
+                QueryOption::Cancellable,
+                QueryOption::ConditionsType,
+                QueryOption::Batch,
+            ],
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Query 'query' uses batch, which cannot be combined with iterate, conditions_type"
+        );
+    }
+
+    #[test]
+    fn conflicts_are_detected_from_the_metadata() {
+        let error = parse(
+            "--    expect: exactly_one\n--    requires_session: true\n--    cancellable: true\n",
+            "SELECT 1 AS one",
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Query 'query' uses cancellable, which cannot be combined with requires_session"
+        );
+
+        // Flags of output columns are converted after the rows are fetched
+        let flags = "--    char_flags:\n--      is_active: bool(\"Y\", \"N\")\n";
+        parse(
+            &format!("{}--    multiunzip: true\n", flags),
+            "SELECT is_active FROM public.legacy_accounts",
+        )
+        .unwrap();
+        let error = parse(
+            &format!("{}--    multiunzip: true\n", flags),
+            "SELECT id FROM public.legacy_accounts WHERE is_active = #{is_active}",
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Query 'query' uses char_flags for parameters, which cannot be combined with multiunzip"
+        );
+    }
 }
@@ -0,0 +1,177 @@
+/// Statement timeouts suggested from the plan estimates of read-only queries, so that teams can
+/// adopt per-query timeouts instead of one cluster-wide default
+use crate::diagnostics::{Diagnostic, DiagnosticCode};
+use crate::query_definition_rt::QueryDefinitionRuntime;
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// File name of the suggestions inside the output directory, written with `write_file`
+pub const TIMEOUT_SUGGESTIONS_FILE_NAME: &str = "automodel.timeouts.json";
+
+/// Conversion of plan estimates into suggested statement timeouts
+///
+/// The estimated cost of the most expensive variant, or the cost recorded in the query's
+/// `baseline` when higher, is converted to milliseconds, multiplied by the headroom and rounded
+/// up to 1, 2 or 5 times a power of ten
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeoutSuggestionsConfig {
+    /// Milliseconds a unit of planner cost takes on the production server, calibrate it by
+    /// dividing the duration of a slow query by its estimated cost
+    /// Defaults to 0.01
+    pub ms_per_cost: f64,
+    /// Factor applied to the estimated duration, as estimates are often off by an order of
+    /// magnitude
+    /// Defaults to 10
+    pub headroom: f64,
+    /// Lowest suggested timeout in milliseconds
+    /// Defaults to 100
+    pub min_ms: u64,
+    /// Highest suggested timeout in milliseconds
+    /// Defaults to 30000
+    pub max_ms: u64,
+    /// Whether suggestions are written to `automodel.timeouts.json` in the output directory
+    /// instead of reported as warnings
+    /// Defaults to false
+    pub write_file: bool,
+}
+
+impl Default for TimeoutSuggestionsConfig {
+    fn default() -> Self {
+        Self {
+            ms_per_cost: 0.01,
+            headroom: 10.0,
+            min_ms: 100,
+            max_ms: 30_000,
+            write_file: false,
+        }
+    }
+}
+
+/// Suggested timeout of a query with the estimates it is based on
+#[derive(Debug, Clone, Serialize)]
+pub struct TimeoutSuggestion {
+    /// Suggested statement timeout in milliseconds
+    pub timeout_ms: u64,
+    /// Estimated total cost of the most expensive variant of the current plan
+    pub total_cost: f64,
+    /// Total cost recorded in the query's `baseline`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub baseline_cost: Option<f64>,
+    /// Estimated rows returned by the base variant
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rows: Option<f64>,
+}
+
+impl TimeoutSuggestionsConfig {
+    /// Fail if the conversion cannot produce timeouts
+    pub(crate) fn validate(&self) -> Result<()> {
+        if !(self.ms_per_cost > 0.0 && self.headroom > 0.0) {
+            anyhow::bail!("suggest_timeouts ms_per_cost and headroom must be positive");
+        }
+        if self.min_ms == 0 || self.min_ms > self.max_ms {
+            anyhow::bail!("suggest_timeouts min_ms must be positive and at most max_ms");
+        }
+        Ok(())
+    }
+
+    /// Suggested timeout of a read-only query, None for mutations and queries whose plan could
+    /// not be estimated or which allow the `timeout-suggestion` warning
+    pub(crate) fn suggest(&self, analyzed: &QueryDefinitionRuntime) -> Option<TimeoutSuggestion> {
+        if analyzed
+            .definition
+            .allow_diagnostics
+            .contains(&DiagnosticCode::TimeoutSuggestion)
+        {
+            return None;
+        }
+        let estimates = analyzed.type_info.plan_estimates.as_ref()?;
+        let total_cost = estimates.total_cost?;
+        let baseline_cost = analyzed
+            .definition
+            .baseline
+            .as_ref()
+            .and_then(|baseline| baseline.total_cost);
+        let cost = baseline_cost.map_or(total_cost, |baseline| baseline.max(total_cost));
+
+        let estimated_ms = cost * self.ms_per_cost * self.headroom;
+        let timeout_ms = round_up_to_series(estimated_ms).clamp(self.min_ms, self.max_ms);
+        Some(TimeoutSuggestion {
+            timeout_ms,
+            total_cost,
+            baseline_cost,
+            rows: estimates.rows,
+        })
+    }
+
+    /// Warnings of the suggested timeouts, empty with `write_file`
+    pub(crate) fn warnings(&self, analyzed_queries: &[QueryDefinitionRuntime]) -> Vec<Diagnostic> {
+        if self.write_file {
+            return Vec::new();
+        }
+        analyzed_queries
+            .iter()
+            .filter_map(|analyzed| {
+                let suggestion = self.suggest(analyzed)?;
+                let baseline = suggestion
+                    .baseline_cost
+                    .map(|cost| format!(", baseline cost {:.2}", cost))
+                    .unwrap_or_default();
+                Some(Diagnostic::new(
+                    DiagnosticCode::TimeoutSuggestion,
+                    format!(
+                        "Query '{}' has estimated cost {:.2}{}, suggested timeout_ms: {}",
+                        analyzed.definition.name,
+                        suggestion.total_cost,
+                        baseline,
+                        suggestion.timeout_ms
+                    ),
+                ))
+            })
+            .collect()
+    }
+
+    /// Write the suggestions of every query to the output directory with `write_file`, keyed by
+    /// "module::query", or remove the file of a previous run otherwise
+    pub(crate) fn save(
+        &self,
+        analyzed_queries: &[QueryDefinitionRuntime],
+        output_dir: &Path,
+    ) -> Result<()> {
+        let file = output_dir.join(TIMEOUT_SUGGESTIONS_FILE_NAME);
+        if !self.write_file {
+            let _ = std::fs::remove_file(&file);
+            return Ok(());
+        }
+        let suggestions: BTreeMap<String, TimeoutSuggestion> = analyzed_queries
+            .iter()
+            .filter_map(|analyzed| {
+                let suggestion = self.suggest(analyzed)?;
+                Some((
+                    format!(
+                        "{}::{}",
+                        analyzed.definition.module, analyzed.definition.name
+                    ),
+                    suggestion,
+                ))
+            })
+            .collect();
+        std::fs::write(&file, serde_json::to_string_pretty(&suggestions)? + "\n")?;
+        Ok(())
+    }
+}
+
+/// Smallest value of the 1, 2, 5 series (1, 2, 5, 10, 20, 50, ...) at least `value`
+fn round_up_to_series(value: f64) -> u64 {
+    let mut step = 1u64;
+    loop {
+        for factor in [1, 2, 5] {
+            let candidate = step.saturating_mul(factor);
+            if candidate as f64 >= value || candidate == u64::MAX {
+                return candidate;
+            }
+        }
+        step = step.saturating_mul(10);
+    }
+}
@@ -1,29 +1,33 @@
 use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::sync::OnceLock;
 use tokio::sync::Mutex;
 use tokio_postgres::types::Type as PgType;
 use tokio_postgres::Statement;
 
-use crate::utils::to_pascal_case;
+use crate::query_definition::{ArchiveTables, CheckValuesStyle, Dialect, NumericAs, NumericType};
+use crate::utils::{sorted_entries, to_pascal_case};
 
-// Global cache for enum type information to avoid repeated database queries
-static ENUM_CACHE: OnceLock<Mutex<HashMap<u32, Option<EnumTypeInfo>>>> = OnceLock::new();
+/// Cache of enum type information to avoid repeated database queries, held by the
+/// `GenerationContext` as oids are only meaningful for one database
+/// Key: type oid, None for types which are not enums
+#[derive(Debug, Default)]
+pub struct EnumTypeCache(Mutex<HashMap<u32, Option<EnumTypeInfo>>>);
 
 /// Constraint information extracted from database schema
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConstraintInfo {
     /// Constraint name
     pub name: String,
     /// Constraint type: unique, primary_key, foreign_key, check, not_null
-    #[allow(unused)]
     pub constraint_type: String,
     /// Table name
     pub table_name: String,
 }
 
 /// Information about a SQL query's input and output types
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueryTypeInfo {
     /// Input parameter types
     pub input_types: Vec<RustType>,
@@ -31,10 +35,102 @@ pub struct QueryTypeInfo {
     pub output_types: Vec<OutputColumn>,
     /// Parsed SQL with conditional blocks (if any)
     pub parsed_sql: Option<ParsedSql>,
+    /// Allowed values of columns configured with `check_values: const`
+    #[serde(default)]
+    pub value_checks: Vec<ValueCheck>,
+    /// Counting query of DELETE queries configured with `cascade_preview`
+    #[serde(default)]
+    pub cascade_preview: Option<CascadePreview>,
+    /// Planner estimate of the rows returned, for queries with `cardinality_drift`
+    #[serde(default)]
+    pub estimated_rows: Option<f64>,
+    /// INSERT returning only the primary key, for queries with `id_only`
+    #[serde(default)]
+    pub id_only: Option<IdOnlyQuery>,
+    /// Lookups of the conflicting row per unique constraint, for queries with `retrying`
+    #[serde(default)]
+    pub retry_lookups: Vec<RetryLookup>,
+    /// UPDATE of a list of records joined from UNNEST arrays, for queries with `bulk_update`
+    #[serde(default)]
+    pub bulk_update: Option<BulkUpdateQuery>,
+    /// Tables read or modified by the query according to its plans, e.g. "users" or
+    /// "audit.events" outside the public schema, sorted
+    #[serde(default)]
+    pub tables: Vec<String>,
+    /// Planner estimates of read-only queries with a `baseline`, or of every read-only query when
+    /// baselines are recorded
+    #[serde(default)]
+    pub plan_estimates: Option<crate::query_definition::PlanEstimates>,
+}
+
+/// INSERT with its RETURNING clause rewritten to the primary key, emitted as the
+/// `{query}_id_only` function of queries with `id_only`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdOnlyQuery {
+    /// INSERT with `#{name}` parameters returning the primary key columns
+    pub sql: String,
+    /// Primary key columns in key order
+    pub output_types: Vec<OutputColumn>,
+}
+
+/// SELECT of the row conflicting with an INSERT on a unique constraint, run by the
+/// `{query}_retrying` function of queries with `retrying` after a unique violation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryLookup {
+    /// Name of the unique constraint or index, as reported by unique violations
+    pub constraint: String,
+    /// SELECT of the RETURNING clause of the INSERT from its table, with `#{name}` parameters
+    /// comparing the columns of the constraint with the values inserted into them
+    pub sql: String,
+}
+
+/// UPDATE of a query with `bulk_update` rewritten to update one row per record, emitted as the
+/// `{query}_bulk` function taking a Vec of records
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkUpdateQuery {
+    /// UPDATE joined with `UNNEST` of one `#{name}::type[]` array per parameter of the query, in
+    /// the order of the parameters, with the parameters replaced by the columns of the UNNEST
+    pub sql: String,
+}
+
+/// Query counting the rows a DELETE removes and the rows referencing them, emitted as the
+/// `{query}_preview` function of queries with `cascade_preview`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CascadePreview {
+    /// SELECT with `#{name}` parameters returning the count of deleted rows followed by the count
+    /// of referencing rows of each foreign key
+    pub sql: String,
+    /// Foreign keys in the order of their counts
+    pub foreign_keys: Vec<ReferencingForeignKey>,
+}
+
+/// Foreign key referencing the rows removed by a DELETE, directly or through cascaded deletes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReferencingForeignKey {
+    /// Schema-qualified referencing table
+    pub table: String,
+    /// Constraint name
+    pub constraint: String,
+    /// Variant of the generated `OnDelete` enum, e.g. "Cascade"
+    pub on_delete: String,
+}
+
+/// Values allowed by the CHECK constraint of a text column, emitted as a `&[&str]` constant
+/// Parameters named like the column are checked against the constant before binding
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValueCheck {
+    /// Column name, matched against the parameter names
+    pub column: String,
+    /// Constrained column as written in the query metadata (table.column)
+    pub location: String,
+    /// Name of the generated constant, e.g. `TICKETS_STATE_VALUES`
+    pub const_name: String,
+    /// Allowed values in constraint order
+    pub values: Vec<String>,
 }
 
 /// Represents a Rust type mapping from PostgreSQL types
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RustType {
     /// The Rust type name (e.g., "i32", "String", "Option<i64>")
     pub rust_type: String,
@@ -48,6 +144,40 @@ pub struct RustType {
     pub enum_variants: Option<Vec<String>>,
     /// If this is an enum type, contains the original PostgreSQL type name
     pub pg_type_name: Option<String>,
+    /// Whether this is an array of an enum type (rust_type is `Vec<Enum>`,
+    /// enum_variants and pg_type_name describe the element type)
+    pub is_enum_array: bool,
+    /// Whether this is a NUMERIC column decoded as `rust_decimal::Decimal` and converted to f64
+    /// (rust_type is `f64`), enabled with `numeric_as: f64`
+    #[serde(default)]
+    pub is_numeric_as_f64: bool,
+    /// Whether this is a NUMERIC column decoded as `rust_decimal::Decimal` and converted to i64
+    /// with an overflow check (rust_type is `i64`), enabled with `numeric_as: i64`
+    #[serde(default)]
+    pub is_numeric_as_i64: bool,
+    /// ISO 4217 code of a NUMERIC column or parameter generated as `super::money::Money` (or an
+    /// array of it), enabled with `currency_columns`
+    #[serde(default)]
+    pub currency: Option<String>,
+    /// Codes of a CHAR(1) or "char" column or parameter converted to bool or an enum, enabled
+    /// with `char_flags`
+    #[serde(default)]
+    pub char_flag: Option<CharFlagColumn>,
+    /// Rust type of the elements of a text array column or parameter (rust_type is
+    /// `Vec<Element>`), converted with `FromStr` and `Display`, enabled with a `name[]` key of
+    /// `types`
+    #[serde(default)]
+    pub array_element: Option<String>,
+}
+
+/// Flag column or parameter of `char_flags` with the type its codes are decoded as
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CharFlagColumn {
+    /// Rust type and codes of the flag
+    pub flag: crate::query_definition::CharFlag,
+    /// Type of the codes in the database, `String` for CHAR(1) and text columns or `i8` for the
+    /// single-byte "char" type
+    pub code_type: String,
 }
 
 /// Information about a PostgreSQL enum type
@@ -60,7 +190,7 @@ pub struct EnumTypeInfo {
 }
 
 /// Represents an output column with its name and type
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OutputColumn {
     /// Column name
     pub name: String,
@@ -69,7 +199,7 @@ pub struct OutputColumn {
 }
 
 /// Represents a conditional block in a SQL query
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConditionalBlock {
     /// The SQL content inside the conditional block
     pub sql_content: String,
@@ -78,7 +208,7 @@ pub struct ConditionalBlock {
 }
 
 /// Parsed SQL with conditional blocks separated
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ParsedSql {
     /// Base SQL with conditional blocks removed and placeholders inserted
     pub base_sql: String,
@@ -91,6 +221,7 @@ pub struct ParsedSql {
 /// Extract type information from a prepared SQL statement
 pub async fn extract_query_types(
     client: &tokio_postgres::Client,
+    enum_types: &EnumTypeCache,
     sql: &str,
     field_type_mappings: Option<&HashMap<String, String>>,
 ) -> Result<QueryTypeInfo> {
@@ -111,9 +242,16 @@ pub async fn extract_query_types(
     })?;
 
     // Extract types
-    let input_types =
-        extract_input_types(&client, &statement, &param_names, field_type_mappings).await?;
-    let output_types = extract_output_types(&client, &statement, field_type_mappings).await?;
+    let input_types = extract_input_types(
+        &client,
+        enum_types,
+        &statement,
+        &param_names,
+        field_type_mappings,
+    )
+    .await?;
+    let output_types =
+        extract_output_types(&client, enum_types, &statement, field_type_mappings).await?;
 
     let has_conditionals = !parsed_sql.conditional_blocks.is_empty();
 
@@ -125,6 +263,106 @@ pub async fn extract_query_types(
         } else {
             None
         },
+        value_checks: Vec::new(),
+        cascade_preview: None,
+        estimated_rows: None,
+        id_only: None,
+        retry_lookups: Vec::new(),
+        bulk_update: None,
+        tables: Vec::new(),
+        plan_estimates: None,
+    })
+}
+
+/// Statements of a SQL script separated by top-level semicolons, skipping empty statements
+/// Semicolons in string literals, quoted identifiers, dollar-quoted strings and comments do not
+/// separate statements
+pub fn split_sql_statements(sql: &str) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut start = 0;
+    let mut pos = 0;
+    let bytes = sql.as_bytes();
+
+    while pos < bytes.len() {
+        let rest = &sql[pos..];
+        pos += match bytes[pos] {
+            b'\'' | b'"' => {
+                let quote = bytes[pos] as char;
+                rest[1..].find(quote).map_or(rest.len(), |end| end + 2)
+            }
+            b'-' if rest.starts_with("--") => rest.find('\n').unwrap_or(rest.len()),
+            b'/' if rest.starts_with("/*") => rest.find("*/").map_or(rest.len(), |end| end + 2),
+            b'$' => {
+                // Dollar quote tags are `$$` or `$tag$`, other dollars are parameters like `$1`
+                let tag_len = rest[1..]
+                    .find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_'))
+                    .filter(|&len| rest[1 + len..].starts_with('$'))
+                    .filter(|_| !rest[1..].starts_with(|ch: char| ch.is_ascii_digit()))
+                    .map(|len| len + 2);
+                match tag_len {
+                    Some(tag_len) => {
+                        let tag = &rest[..tag_len];
+                        rest[tag_len..]
+                            .find(tag)
+                            .map_or(rest.len(), |end| tag_len + end + tag_len)
+                    }
+                    None => 1,
+                }
+            }
+            b';' => {
+                statements.push(sql[start..pos].trim().to_string());
+                start = pos + 1;
+                1
+            }
+            _ => rest.chars().next().map_or(1, char::len_utf8),
+        };
+    }
+    statements.push(sql[start..].trim().to_string());
+    statements.retain(|statement| !statement.is_empty());
+    statements
+}
+
+/// Check every statement of a script of several statements, which cannot be prepared as a
+/// whole, by preparing each statement on its own without running it
+/// Scripts run with the simple query protocol and return no rows, so the type information is
+/// empty
+pub async fn extract_script_types(
+    client: &tokio_postgres::Client,
+    query_name: &str,
+    statements: &[String],
+) -> Result<QueryTypeInfo> {
+    for (i, statement) in statements.iter().enumerate() {
+        let prepared = client.prepare(statement).await.map_err(|e| {
+            anyhow::anyhow!(
+                "Query '{}' statement {} is not valid: {} ({})",
+                query_name,
+                i + 1,
+                e.as_db_error()
+                    .map_or_else(|| e.to_string(), |db_error| db_error.message().to_string()),
+                statement
+            )
+        })?;
+        if !prepared.columns().is_empty() {
+            anyhow::bail!(
+                "Query '{}' statement {} returns rows, which scripts of several statements cannot return; move it into a query of its own",
+                query_name,
+                i + 1
+            );
+        }
+    }
+
+    Ok(QueryTypeInfo {
+        input_types: Vec::new(),
+        output_types: Vec::new(),
+        parsed_sql: None,
+        value_checks: Vec::new(),
+        cascade_preview: None,
+        estimated_rows: None,
+        id_only: None,
+        retry_lookups: Vec::new(),
+        bulk_update: None,
+        tables: Vec::new(),
+        plan_estimates: None,
     })
 }
 
@@ -134,6 +372,7 @@ pub async fn extract_constraints_from_statement(
     client: &tokio_postgres::Client,
     statement: &Statement,
     sql: &str,
+    dialect: Dialect,
 ) -> Result<Vec<ConstraintInfo>> {
     let mut constraints = Vec::new();
     let mut table_oids = HashSet::new();
@@ -165,7 +404,7 @@ pub async fn extract_constraints_from_statement(
 
     // Query constraints for each table
     for table_oid in table_oids {
-        let table_constraints = query_table_constraints(client, table_oid).await?;
+        let table_constraints = query_table_constraints(client, table_oid, dialect).await?;
         constraints.extend(table_constraints);
     }
 
@@ -243,17 +482,38 @@ async fn get_table_oid_by_name(
     }
 }
 
+/// Condition leaving out hidden columns, for catalog queries over `pg_attribute a` of the table
+/// `pg_class t`; CockroachDB adds a hidden `rowid` primary key to tables declared without one
+fn visible_column_condition(dialect: Dialect) -> &'static str {
+    match dialect {
+        Dialect::Postgres => "",
+        Dialect::Cockroach => {
+            r#"
+                AND NOT EXISTS (
+                    SELECT 1 FROM information_schema.columns ic
+                    JOIN pg_namespace n ON n.nspname = ic.table_schema
+                    WHERE n.oid = t.relnamespace
+                        AND ic.table_name = t.relname
+                        AND ic.column_name = a.attname
+                        AND ic.is_hidden = 'YES'
+                )"#
+        }
+    }
+}
+
 /// Query all constraints for a given table OID
 async fn query_table_constraints(
     client: &tokio_postgres::Client,
     table_oid: u32,
+    dialect: Dialect,
 ) -> Result<Vec<ConstraintInfo>> {
     let mut constraints = Vec::new();
 
     // Query unique and primary key constraints
     let rows = client
         .query(
-            r#"
+            &format!(
+                r#"
             SELECT 
                 c.conname as constraint_name,
                 c.contype::text as constraint_type,
@@ -264,9 +524,11 @@ async fn query_table_constraints(
             JOIN LATERAL unnest(c.conkey) WITH ORDINALITY AS u(attnum, attposition) ON true
             JOIN pg_attribute a ON a.attrelid = t.oid AND a.attnum = u.attnum
             WHERE c.conrelid = $1 
-                AND c.contype IN ('u', 'p', 'f', 'c')
+                AND c.contype IN ('u', 'p', 'f', 'c'){}
             GROUP BY c.conname, c.contype, t.relname, c.confrelid, c.confkey
             "#,
+                visible_column_condition(dialect)
+            ),
             &[&table_oid],
         )
         .await?;
@@ -331,7 +593,8 @@ async fn query_table_constraints(
     // Query NOT NULL constraints
     let nn_rows = client
         .query(
-            r#"
+            &format!(
+                r#"
             SELECT 
                 a.attname as column_name,
                 t.relname as table_name
@@ -340,8 +603,10 @@ async fn query_table_constraints(
             WHERE a.attrelid = $1 
                 AND a.attnotnull = true
                 AND a.attnum > 0
-                AND NOT a.attisdropped
+                AND NOT a.attisdropped{}
             "#,
+                visible_column_condition(dialect)
+            ),
             &[&table_oid],
         )
         .await?;
@@ -363,6 +628,7 @@ async fn query_table_constraints(
 /// Extract input parameter types from a prepared statement
 async fn extract_input_types(
     client: &tokio_postgres::Client,
+    enum_types: &EnumTypeCache,
     statement: &Statement,
     param_names: &[String],
     field_type_mappings: Option<&HashMap<String, String>>,
@@ -382,7 +648,7 @@ async fn extract_input_types(
             param_name
         };
 
-        let mut rust_type = pg_type_to_rust_type(client, param_type, false).await?; // Always get base type
+        let mut rust_type = pg_type_to_rust_type(client, enum_types, param_type, false).await?; // Always get base type
 
         // Check if there's a custom type mapping for this parameter
         if let Some(mappings) = field_type_mappings {
@@ -403,6 +669,12 @@ async fn extract_input_types(
                     needs_json_wrapper: true, // Custom input parameters need JSON serialization
                     enum_variants: None,
                     pg_type_name: None,
+                    is_enum_array: false,
+                    is_numeric_as_f64: false,
+                    is_numeric_as_i64: false,
+                    currency: None,
+                    char_flag: None,
+                    array_element: None,
                 };
             } else if is_optional_param {
                 // If it's an optional parameter but no custom type, mark as nullable
@@ -463,10 +735,10 @@ async fn get_column_nullability(
 /// Get enum type information from PostgreSQL system catalogs with caching
 pub async fn get_enum_type_info(
     client: &tokio_postgres::Client,
+    enum_types: &EnumTypeCache,
     type_oid: u32,
 ) -> Result<Option<EnumTypeInfo>> {
-    // Initialize cache if not already done
-    let cache = ENUM_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    let cache = &enum_types.0;
 
     // Check cache first
     {
@@ -513,9 +785,34 @@ pub async fn get_enum_type_info(
     Ok(result)
 }
 
+/// Get enum type information for the element type of an array type
+/// Returns None if the type is not an array or its element type is not an enum
+pub async fn get_enum_array_element_info(
+    client: &tokio_postgres::Client,
+    enum_types: &EnumTypeCache,
+    type_oid: u32,
+) -> Result<Option<EnumTypeInfo>> {
+    // Resolve the element type via pg_type.typelem (only for true array types)
+    let rows = client
+        .query(
+            "SELECT typelem FROM pg_type WHERE oid = $1 AND typcategory = 'A' AND typelem <> 0",
+            &[&type_oid],
+        )
+        .await?;
+
+    match rows.first() {
+        Some(row) => {
+            let element_oid: u32 = row.get(0);
+            get_enum_type_info(client, enum_types, element_oid).await
+        }
+        None => Ok(None),
+    }
+}
+
 /// Extract output column types from a prepared statement
 async fn extract_output_types(
     client: &tokio_postgres::Client,
+    enum_types: &EnumTypeCache,
     statement: &Statement,
     field_type_mappings: Option<&HashMap<String, String>>,
 ) -> Result<Vec<OutputColumn>> {
@@ -528,7 +825,8 @@ async fn extract_output_types(
     for (i, column) in columns.iter().enumerate() {
         let column_name = column.name();
         let is_nullable = nullability_info.get(i).copied().unwrap_or(true); // Default to nullable if unknown
-        let base_rust_type = pg_type_to_rust_type(client, column.type_(), is_nullable).await?;
+        let base_rust_type =
+            pg_type_to_rust_type(client, enum_types, column.type_(), is_nullable).await?;
 
         // Check if there's a custom type mapping for this field
         // Note: Since we only have the column name here, we can't determine the exact table
@@ -548,6 +846,12 @@ async fn extract_output_types(
                     needs_json_wrapper: true, // Custom types need JSON wrapper
                     enum_variants: None,
                     pg_type_name: None,
+                    is_enum_array: false,
+                    is_numeric_as_f64: false,
+                    is_numeric_as_i64: false,
+                    currency: None,
+                    char_flag: None,
+                    array_element: None,
                 }
             } else {
                 base_rust_type
@@ -568,6 +872,7 @@ async fn extract_output_types(
 /// Convert PostgreSQL type to Rust type
 async fn pg_type_to_rust_type(
     client: &tokio_postgres::Client,
+    enum_types: &EnumTypeCache,
     pg_type: &PgType,
     is_nullable: bool,
 ) -> Result<RustType> {
@@ -589,6 +894,8 @@ async fn pg_type_to_rust_type(
         PgType::FLOAT8_ARRAY => "Vec<f64>",
         PgType::NUMERIC => "rust_decimal::Decimal",
         PgType::NUMERIC_ARRAY => "Vec<rust_decimal::Decimal>",
+        PgType::MONEY => "sqlx::postgres::types::PgMoney",
+        PgType::MONEY_ARRAY => "Vec<sqlx::postgres::types::PgMoney>",
         PgType::REGPROC => "u32",
         PgType::OID => "u32",
         PgType::TID => "(u32, u32)",
@@ -689,9 +996,13 @@ async fn pg_type_to_rust_type(
         PgType::LINE => "sqlx::postgres::types::PgLine",
         PgType::LINE_ARRAY => "Vec<sqlx::postgres::types::PgLine>",
 
+        // Full-text search types - sqlx has no support for them, use the generated wrappers
+        PgType::TS_VECTOR => "super::full_text_search::TsVector",
+        PgType::TS_VECTOR_ARRAY => "Vec<super::full_text_search::TsVector>",
+        PgType::TSQUERY => "super::full_text_search::TsQuery",
+        PgType::TSQUERY_ARRAY => "Vec<super::full_text_search::TsQuery>",
+
         // Special & System Types
-        PgType::TSQUERY => "String",
-        PgType::TSQUERY_ARRAY => "Vec<String>",
         PgType::REGCONFIG => "u32",
         PgType::REGDICTIONARY => "u32",
         PgType::REGNAMESPACE => "u32",
@@ -730,7 +1041,7 @@ async fn pg_type_to_rust_type(
         // Enum types and fallback
         _ => {
             // Check if this is an enum type by trying to get enum info
-            if let Some(enum_info) = get_enum_type_info(client, pg_type.oid()).await? {
+            if let Some(enum_info) = get_enum_type_info(client, enum_types, pg_type.oid()).await? {
                 // Extract just the type name without schema for Rust enum name
                 let type_name_only = enum_info
                     .type_name
@@ -745,6 +1056,37 @@ async fn pg_type_to_rust_type(
                     needs_json_wrapper: false,
                     enum_variants: Some(enum_info.variants),
                     pg_type_name: Some(enum_info.type_name), // Keep fully-qualified for SQL
+                    is_enum_array: false,
+                    is_numeric_as_f64: false,
+                    is_numeric_as_i64: false,
+                    currency: None,
+                    char_flag: None,
+                    array_element: None,
+                });
+            }
+            // Check if this is an array of an enum type
+            if let Some(enum_info) =
+                get_enum_array_element_info(client, enum_types, pg_type.oid()).await?
+            {
+                let type_name_only = enum_info
+                    .type_name
+                    .rsplit('.')
+                    .next()
+                    .unwrap_or(&enum_info.type_name);
+                let enum_name = to_pascal_case(type_name_only);
+                return Ok(RustType {
+                    rust_type: format!("Vec<{}>", enum_name),
+                    is_nullable,
+                    is_optional: false,
+                    needs_json_wrapper: false,
+                    enum_variants: Some(enum_info.variants),
+                    pg_type_name: Some(enum_info.type_name), // Element type, fully-qualified
+                    is_enum_array: true,
+                    is_numeric_as_f64: false,
+                    is_numeric_as_i64: false,
+                    currency: None,
+                    char_flag: None,
+                    array_element: None,
                 });
             }
             return Ok(RustType {
@@ -754,6 +1096,12 @@ async fn pg_type_to_rust_type(
                 needs_json_wrapper: false,
                 enum_variants: None,
                 pg_type_name: None,
+                is_enum_array: false,
+                is_numeric_as_f64: false,
+                is_numeric_as_i64: false,
+                currency: None,
+                char_flag: None,
+                array_element: None,
             });
         }
     };
@@ -765,13 +1113,20 @@ async fn pg_type_to_rust_type(
         needs_json_wrapper: false,
         enum_variants: None,
         pg_type_name: None,
+        is_enum_array: false,
+        is_numeric_as_f64: false,
+        is_numeric_as_i64: false,
+        currency: None,
+        char_flag: None,
+        array_element: None,
     })
 }
 
 /// Parse SQL to extract meaningful parameter names from named parameters
+/// Each distinct parameter is listed once, in order of first appearance
 pub fn parse_parameter_names_from_sql(sql: &str) -> Vec<String> {
     // Look for named parameters in the format #{param_name}
-    let mut param_names = Vec::new();
+    let mut param_names: Vec<String> = Vec::new();
     let mut chars = sql.chars().peekable();
 
     while let Some(ch) = chars.next() {
@@ -783,7 +1138,9 @@ pub fn parse_parameter_names_from_sql(sql: &str) -> Vec<String> {
                 // Read until we find the closing brace
                 while let Some(inner_ch) = chars.next() {
                     if inner_ch == '}' {
-                        if !param_name.is_empty() {
+                        if !param_name.is_empty()
+                            && named_param_position(&param_names, &param_name).is_none()
+                        {
                             param_names.push(param_name);
                         }
                         break;
@@ -898,12 +1255,647 @@ fn reconstruct_full_sql(parsed_sql: &ParsedSql) -> String {
     result
 }
 
+/// Pattern matching `= ANY(#{param})` comparisons, capturing the parameter name
+fn large_list_pattern() -> &'static regex::Regex {
+    static PATTERN: OnceLock<regex::Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| regex::Regex::new(r"(?i)=\s*ANY\s*\(\s*#\{(\w+)\}\s*\)").unwrap())
+}
+
+/// Name of the temp table holding the values of a list parameter
+pub fn large_list_table_name(param_name: &str) -> String {
+    format!("automodel_list_{}", param_name)
+}
+
+/// Unique names of parameters compared with `= ANY(#{param})`, in order of first appearance
+pub fn large_list_params(sql: &str) -> Vec<String> {
+    let mut params: Vec<String> = Vec::new();
+    for captures in large_list_pattern().captures_iter(sql) {
+        let param = captures[1].to_string();
+        if !params.contains(&param) {
+            params.push(param);
+        }
+    }
+    params
+}
+
+/// Replace `= ANY(#{param})` with `IN (SELECT value FROM automodel_list_param)` for the given parameters
+pub fn rewrite_large_list_params(sql: &str, params: &[String]) -> String {
+    large_list_pattern()
+        .replace_all(sql, |captures: &regex::Captures| {
+            let param = &captures[1];
+            if params.iter().any(|p| p == param) {
+                format!("IN (SELECT value FROM {})", large_list_table_name(param))
+            } else {
+                captures[0].to_string()
+            }
+        })
+        .to_string()
+}
+
+/// Byte range of the single `VALUES (...)` row of an INSERT, including the parentheses
+/// Returns None if the query has no VALUES row or inserts more than one row
+pub fn single_values_row(sql: &str) -> Option<std::ops::Range<usize>> {
+    static PATTERN: OnceLock<regex::Regex> = OnceLock::new();
+    let pattern = PATTERN.get_or_init(|| regex::Regex::new(r"(?i)\bVALUES\s*\(").unwrap());
+    let found = pattern.find(sql)?;
+    let start = found.end() - 1;
+
+    // Find the matching closing parenthesis, skipping string literals
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut end = None;
+    for (i, ch) in sql[start..].char_indices() {
+        match ch {
+            '\'' => in_string = !in_string,
+            '(' if !in_string => depth += 1,
+            ')' if !in_string => {
+                depth -= 1;
+                if depth == 0 {
+                    end = Some(start + i + 1);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let end = end?;
+
+    if sql[end..].trim_start().starts_with(',') || pattern.is_match(&sql[end..]) {
+        return None;
+    }
+    Some(start..end)
+}
+
+/// Target and WHERE clause of a `DELETE FROM table [alias] [WHERE ...] [RETURNING ...]`
+/// The WHERE clause is empty for deletes without one
+/// Returns None for other statements and for deletes with USING
+pub fn delete_statement_parts(sql: &str) -> Option<(&str, &str)> {
+    static PATTERN: OnceLock<regex::Regex> = OnceLock::new();
+    let pattern = PATTERN.get_or_init(|| {
+        regex::Regex::new(
+            r"(?is)^\s*DELETE\s+FROM\s+(.+?)(?:\s+(WHERE\s.*?))?(?:\s+RETURNING\s.*)?;?\s*$",
+        )
+        .unwrap()
+    });
+    static USING: OnceLock<regex::Regex> = OnceLock::new();
+    let using = USING.get_or_init(|| regex::Regex::new(r"(?i)\bUSING\b").unwrap());
+
+    let captures = pattern.captures(sql)?;
+    let target = captures.get(1)?.as_str();
+    if using.is_match(target) || target.contains('(') {
+        return None;
+    }
+    Some((target, captures.get(2).map_or("", |m| m.as_str())))
+}
+
+/// Rewrite a `DELETE FROM table [alias] [WHERE ...] [RETURNING ...]` into a statement moving
+/// the deleted rows into the archive table and returning the archived rows
+/// The RETURNING clause of the DELETE applies to the inserted rows, whose table is aliased as the
+/// alias of the DELETE or the name of its table; without one every archived column is returned
+/// Returns the deleted table with the rewritten statement, or None for other statements
+pub fn archive_delete_sql(sql: &str, archive_table: &str) -> Option<(String, String)> {
+    static RETURNING: OnceLock<regex::Regex> = OnceLock::new();
+    let returning = RETURNING.get_or_init(|| regex::Regex::new(r"(?i)\bRETURNING\b").unwrap());
+
+    let (target, where_clause) = delete_statement_parts(sql)?;
+    let mut words = target
+        .split_whitespace()
+        .skip_while(|word| word.eq_ignore_ascii_case("ONLY"));
+    let table = words.next()?;
+    let alias = match words.next() {
+        Some(word) if word.eq_ignore_ascii_case("AS") => words.next()?,
+        Some(word) => word,
+        None => table.rsplit('.').next().unwrap_or(table),
+    };
+
+    // The last top-level RETURNING keyword of the DELETE, if any
+    let returning_clause = returning
+        .find_iter(sql)
+        .map(|found| found.end())
+        .filter(|end| {
+            let before = &sql[..*end];
+            before.matches('(').count() == before.matches(')').count()
+        })
+        .last()
+        .map(|end| sql[end..].trim().trim_end_matches(';').trim_end())
+        .unwrap_or("*");
+
+    let mut deleted_rows = format!("DELETE FROM {}", target);
+    if !where_clause.is_empty() {
+        deleted_rows.push(' ');
+        deleted_rows.push_str(where_clause);
+    }
+    let rewritten = format!(
+        "WITH automodel_archived AS (\n    {} RETURNING *\n)\nINSERT INTO {} AS {}\nSELECT * FROM automodel_archived\nRETURNING {}",
+        deleted_rows, archive_table, alias, returning_clause
+    );
+    Some((table.to_string(), rewritten))
+}
+
+/// Columns of a table in their order: name, type, whether the column is NOT NULL without a
+/// default, and whether a value cannot be inserted into it (GENERATED ALWAYS columns)
+const TABLE_COLUMNS_SQL: &str = "
+SELECT a.attname::text, format_type(a.atttypid, a.atttypmod),
+       a.attnotnull AND NOT a.atthasdef,
+       a.attidentity::text = 'a' OR a.attgenerated::text <> ''
+FROM pg_catalog.pg_attribute a
+WHERE a.attrelid = $1 AND a.attnum > 0 AND NOT a.attisdropped
+ORDER BY a.attnum";
+
+/// Table name for `to_regclass`, qualified with `public` when it has no schema, as analysis
+/// runs with an empty search_path
+pub(crate) fn qualify_table(table: &str) -> String {
+    if table.contains('.') {
+        table.to_string()
+    } else {
+        format!("public.{}", table)
+    }
+}
+
+/// Check that the archive table of a query with `archive_to` can hold the deleted rows
+/// The archive table must start with the columns of the deleted table, with the same names and
+/// types in the same order, and its other columns must be nullable or have a default
+pub async fn verify_archive_table(
+    client: &tokio_postgres::Client,
+    query_name: &str,
+    tables: &ArchiveTables,
+) -> Result<()> {
+    let mut columns = Vec::new();
+    for table in [&tables.source, &tables.archive] {
+        let qualified_table = qualify_table(table);
+        let row = client
+            .query_one("SELECT to_regclass($1::text)::oid", &[&qualified_table])
+            .await?;
+        let Some(table_oid) = row.get::<_, Option<u32>>(0) else {
+            anyhow::bail!(
+                "Query '{}' uses archive_to, but table '{}' does not exist",
+                query_name,
+                table
+            );
+        };
+        let table_columns: Vec<(String, String, bool, bool)> = client
+            .query(TABLE_COLUMNS_SQL, &[&table_oid])
+            .await?
+            .iter()
+            .map(|row| (row.get(0), row.get(1), row.get(2), row.get(3)))
+            .collect();
+        columns.push(table_columns);
+    }
+    let (source_columns, archive_columns) = (&columns[0], &columns[1]);
+
+    for (i, (name, data_type, _, _)) in source_columns.iter().enumerate() {
+        match archive_columns.get(i) {
+            Some((archive_name, archive_type, _, is_generated))
+                if archive_name == name && archive_type == data_type =>
+            {
+                if *is_generated {
+                    anyhow::bail!(
+                        "Query '{}' uses archive_to, but column '{}' of archive table '{}' is GENERATED ALWAYS and cannot receive the deleted values",
+                        query_name,
+                        name,
+                        tables.archive
+                    );
+                }
+            }
+            Some((archive_name, archive_type, _, _)) => anyhow::bail!(
+                "Query '{}' uses archive_to, but column {} of archive table '{}' is '{}' {} where '{}' has '{}' {}; the archive table must start with the columns of the table in the same order",
+                query_name,
+                i + 1,
+                tables.archive,
+                archive_name,
+                archive_type,
+                tables.source,
+                name,
+                data_type
+            ),
+            None => anyhow::bail!(
+                "Query '{}' uses archive_to, but archive table '{}' has no column for '{}' {} of '{}'",
+                query_name,
+                tables.archive,
+                name,
+                data_type,
+                tables.source
+            ),
+        }
+    }
+    if let Some((name, ..)) = archive_columns[source_columns.len()..]
+        .iter()
+        .find(|(_, _, is_required, _)| *is_required)
+    {
+        anyhow::bail!(
+            "Query '{}' uses archive_to, but column '{}' of archive table '{}' is NOT NULL without a default, so the archived rows cannot be inserted",
+            query_name,
+            name,
+            tables.archive
+        );
+    }
+    Ok(())
+}
+
+/// Table and RETURNING clause position of an `INSERT INTO table ... RETURNING ...`
+/// The position is the byte offset of the last top-level RETURNING keyword
+/// Returns None for other statements and for inserts without RETURNING
+pub fn insert_returning_parts(sql: &str) -> Option<(&str, usize)> {
+    static PATTERN: OnceLock<regex::Regex> = OnceLock::new();
+    let pattern =
+        PATTERN.get_or_init(|| regex::Regex::new(r"(?is)^\s*INSERT\s+INTO\s+([^\s(]+)").unwrap());
+    static RETURNING: OnceLock<regex::Regex> = OnceLock::new();
+    let returning = RETURNING.get_or_init(|| regex::Regex::new(r"(?i)\bRETURNING\b").unwrap());
+
+    let table = pattern.captures(sql)?.get(1)?.as_str();
+    let position = returning
+        .find_iter(sql)
+        .map(|found| found.start())
+        .filter(|start| {
+            let before = &sql[..*start];
+            before.matches('(').count() == before.matches(')').count()
+        })
+        .last()?;
+    Some((table, position))
+}
+
+/// Byte offset of the single top-level WHERE keyword of an `UPDATE table SET ... WHERE ...`
+/// without a FROM clause, as required by `bulk_update`
+pub fn bulk_update_where_position(sql: &str) -> Result<usize> {
+    static UPDATE: OnceLock<regex::Regex> = OnceLock::new();
+    let update = UPDATE.get_or_init(|| regex::Regex::new(r"(?is)^\s*UPDATE\s").unwrap());
+    static WHERE: OnceLock<regex::Regex> = OnceLock::new();
+    let where_pattern = WHERE.get_or_init(|| regex::Regex::new(r"(?i)\bWHERE\b").unwrap());
+    static FROM: OnceLock<regex::Regex> = OnceLock::new();
+    let from_pattern = FROM.get_or_init(|| regex::Regex::new(r"(?i)\bFROM\b").unwrap());
+
+    if !update.is_match(sql) {
+        anyhow::bail!("requires an `UPDATE table SET ... WHERE ...` statement");
+    }
+    let where_start = match top_level_matches(where_pattern, sql).as_slice() {
+        [] => anyhow::bail!("requires a WHERE clause matching the row of each record"),
+        [where_range] => where_range.start,
+        _ => anyhow::bail!("supports a single top-level WHERE clause"),
+    };
+    if !top_level_matches(from_pattern, &sql[..where_start]).is_empty() {
+        anyhow::bail!("does not support UPDATE ... FROM, the records are joined with FROM UNNEST");
+    }
+    Ok(where_start)
+}
+
+/// Build the UPDATE of a query configured with `bulk_update`, joining `UNNEST` of one array per
+/// parameter, cast to the array of the parameter's type, and updating the row of each record
+/// `param_names` and `param_types` are those of the prepared single-row statement
+pub async fn extract_bulk_update_query(
+    client: &tokio_postgres::Client,
+    query_name: &str,
+    sql: &str,
+    param_names: &[String],
+    param_types: &[PgType],
+) -> Result<BulkUpdateQuery> {
+    let where_start = bulk_update_where_position(sql)
+        .map_err(|e| anyhow::anyhow!("Query '{}' uses bulk_update, which {}", query_name, e))?;
+    if param_names.is_empty() {
+        anyhow::bail!(
+            "Query '{}' uses bulk_update, which requires parameters to take from the records",
+            query_name
+        );
+    }
+
+    let mut arrays = Vec::new();
+    let mut columns = Vec::new();
+    for (name, pg_type) in param_names.iter().zip(param_types) {
+        if matches!(pg_type.kind(), tokio_postgres::types::Kind::Array(_)) {
+            anyhow::bail!(
+                "Query '{}' uses bulk_update, which does not support array parameter '{}'",
+                query_name,
+                name
+            );
+        }
+        let row = client
+            .query_one("SELECT format_type($1::oid, NULL)", &[&pg_type.oid()])
+            .await?;
+        let type_name: String = row.get(0);
+        arrays.push(format!("#{{{}}}::{}[]", name, type_name));
+        columns.push(format!("bulk_{}", name));
+    }
+
+    // Parameters outside the UNNEST read the column of the record, prefixed so that unqualified
+    // columns of the updated table stay unambiguous
+    let record_columns = |part: &str| {
+        param_names.iter().fold(part.to_string(), |part, name| {
+            part.replace(&format!("#{{{}}}", name), &format!("bulk.bulk_{}", name))
+        })
+    };
+    let bulk_sql = format!(
+        "{}\nFROM UNNEST({}) AS bulk({})\n{}",
+        record_columns(sql[..where_start].trim_end()),
+        arrays.join(", "),
+        columns.join(", "),
+        record_columns(&sql[where_start..])
+    );
+
+    let (converted_sql, _) = convert_named_params_to_positional(&bulk_sql);
+    if let Err(e) = client.prepare(&converted_sql).await {
+        anyhow::bail!(
+            "Query '{}' uses bulk_update, but its UNNEST update is not valid: {}",
+            query_name,
+            e.as_db_error()
+                .map_or_else(|| e.to_string(), |db_error| db_error.message().to_string())
+        );
+    }
+    Ok(BulkUpdateQuery { sql: bulk_sql })
+}
+
+/// Condition of the WHERE clause of a query with `filter_type`, e.g. `u.age >= #{min_age}`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterCondition {
+    /// Column as written in the SQL, e.g. `u.age`
+    pub column: String,
+    /// Variant name of the condition, e.g. `AgeGte`
+    pub variant: String,
+    /// Name of the parameter compared with the column
+    pub param: String,
+    /// SQL of the condition before and after the parameter
+    pub sql_before_param: String,
+    pub sql_after_param: String,
+}
+
+/// SQL of a query with `filter_type` split around the conditions of its WHERE clause
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterQueryParts {
+    /// SQL before the WHERE keyword
+    pub before: String,
+    /// Conditions of the WHERE clause, combined with AND
+    pub conditions: Vec<FilterCondition>,
+    /// SQL after the WHERE clause, e.g. ORDER BY and LIMIT
+    pub after: String,
+}
+
+/// Byte offsets of the matches of a pattern outside parentheses and string literals
+pub(crate) fn top_level_matches(pattern: &regex::Regex, sql: &str) -> Vec<std::ops::Range<usize>> {
+    pattern
+        .find_iter(sql)
+        .filter(|found| {
+            let before = &sql[..found.start()];
+            before.matches('(').count() == before.matches(')').count()
+                && before.matches('\'').count().is_multiple_of(2)
+        })
+        .map(|found| found.range())
+        .collect()
+}
+
+/// Whether the SQL limits its rows with a top-level LIMIT or FETCH clause, including clauses of
+/// conditional blocks
+pub(crate) fn has_top_level_limit(sql: &str) -> bool {
+    static LIMIT: OnceLock<regex::Regex> = OnceLock::new();
+    let limit = LIMIT.get_or_init(|| regex::Regex::new(r"(?i)\b(LIMIT|FETCH)\b").unwrap());
+    !top_level_matches(limit, sql).is_empty()
+}
+
+/// SQL of a read-only SELECT with `LIMIT 1` added before its locking clause or at its end, None
+/// for statements which may modify rows and queries which already limit their rows
+pub(crate) fn limit_one_sql(sql: &str) -> Option<String> {
+    static READ_ONLY: OnceLock<regex::Regex> = OnceLock::new();
+    let read_only = READ_ONLY
+        .get_or_init(|| regex::Regex::new(r"(?is)^\s*(SELECT|WITH|VALUES|TABLE)\b").unwrap());
+    static MODIFYING: OnceLock<regex::Regex> = OnceLock::new();
+    let modifying = MODIFYING
+        .get_or_init(|| regex::Regex::new(r"(?i)\b(INSERT|UPDATE|DELETE|MERGE)\b").unwrap());
+    static LOCKING: OnceLock<regex::Regex> = OnceLock::new();
+    let locking = LOCKING.get_or_init(|| {
+        regex::Regex::new(r"(?i)\bFOR\s+(UPDATE|NO\s+KEY\s+UPDATE|SHARE|KEY\s+SHARE)\b").unwrap()
+    });
+
+    // Locking clauses name UPDATE too, they are removed before looking for modifying statements
+    let sql = sql.trim_end().trim_end_matches(';').trim_end();
+    if !read_only.is_match(sql)
+        || modifying.is_match(&locking.replace_all(sql, ""))
+        || has_top_level_limit(sql)
+    {
+        return None;
+    }
+    let lock = top_level_matches(locking, sql).first().cloned();
+    // A LIMIT before a locking clause of a conditional block would end up inside the block
+    if lock.is_some() && sql.contains("#[") {
+        return None;
+    }
+    let position = lock.map_or(sql.len(), |found| found.start);
+    Some(
+        format!(
+            "{}\nLIMIT 1\n{}",
+            sql[..position].trim_end(),
+            &sql[position..]
+        )
+        .trim_end()
+        .to_string(),
+    )
+}
+
+/// Split the SQL of a query with `filter_type` into the SQL around its top-level WHERE clause
+/// and the conditions of the clause
+/// Every condition must compare a column with a parameter used nowhere else, e.g.
+/// `email = #{email}`, `age >= #{min_age}`, `name ILIKE #{pattern}` or `id = ANY(#{ids})`
+pub fn filter_query_parts(sql: &str) -> Result<FilterQueryParts> {
+    static WHERE: OnceLock<regex::Regex> = OnceLock::new();
+    let where_pattern = WHERE.get_or_init(|| regex::Regex::new(r"(?i)\bWHERE\b").unwrap());
+    static CLAUSE_END: OnceLock<regex::Regex> = OnceLock::new();
+    let clause_end = CLAUSE_END.get_or_init(|| {
+        regex::Regex::new(
+            r"(?i)\b(GROUP\s+BY|ORDER\s+BY|HAVING|WINDOW|LIMIT|OFFSET|FETCH|FOR\s+(UPDATE|NO\s+KEY|SHARE|KEY)|RETURNING|UNION|INTERSECT|EXCEPT)\b",
+        )
+        .unwrap()
+    });
+    static AND: OnceLock<regex::Regex> = OnceLock::new();
+    let and_pattern = AND.get_or_init(|| regex::Regex::new(r"(?i)\bAND\b").unwrap());
+    static CONDITION: OnceLock<regex::Regex> = OnceLock::new();
+    let condition_pattern = CONDITION.get_or_init(|| {
+        regex::Regex::new(
+            r"(?is)^([a-z_][a-z0-9_]*(?:\.[a-z_][a-z0-9_]*)?)\s*(=|<>|!=|<=|>=|<|>|NOT\s+ILIKE\b|NOT\s+LIKE\b|ILIKE\b|LIKE\b)\s*(ANY\s*\(\s*)?#\{([a-z_][a-z0-9_]*)\}(\s*::\s*[a-z_][a-z0-9_ ]*(?:\[\])?)?(\s*\))?$",
+        )
+        .unwrap()
+    });
+
+    let wheres = top_level_matches(where_pattern, sql);
+    let where_range = match wheres.as_slice() {
+        [] => anyhow::bail!("requires a WHERE clause with the filter conditions"),
+        [where_range] => where_range.clone(),
+        _ => anyhow::bail!("supports a single top-level WHERE clause"),
+    };
+    let rest = &sql[where_range.end..];
+    let clause_len = top_level_matches(clause_end, rest)
+        .first()
+        .map_or(rest.trim_end().trim_end_matches(';').len(), |found| {
+            found.start
+        });
+    let clause = &rest[..clause_len];
+
+    let mut conditions: Vec<FilterCondition> = Vec::new();
+    let mut start = 0;
+    for range in top_level_matches(and_pattern, clause)
+        .into_iter()
+        .chain(std::iter::once(clause.len()..clause.len()))
+    {
+        let condition = clause[start..range.start].trim();
+        start = range.end;
+
+        let captures = condition_pattern.captures(condition).ok_or_else(|| {
+            anyhow::anyhow!(
+                "requires WHERE conditions like `column = #{{param}}` combined with AND, found `{}`",
+                condition
+            )
+        })?;
+        let column = captures[1].to_string();
+        let operator = captures[2].split_whitespace().collect::<Vec<_>>().join(" ");
+        let param = captures[4].to_string();
+        let is_any = captures.get(3).is_some();
+        if is_any != captures.get(6).is_some() || (is_any && operator != "=") {
+            anyhow::bail!(
+                "supports ANY only as `column = ANY(#{{param}})`, found `{}`",
+                condition
+            );
+        }
+        let suffix = match operator.to_uppercase().as_str() {
+            "=" if is_any => "In",
+            "=" => "Eq",
+            "<>" | "!=" => "Ne",
+            "<" => "Lt",
+            "<=" => "Lte",
+            ">" => "Gt",
+            ">=" => "Gte",
+            "LIKE" => "Like",
+            "ILIKE" => "ILike",
+            "NOT LIKE" => "NotLike",
+            _ => "NotILike",
+        };
+        let column_name = column.rsplit('.').next().unwrap_or(&column);
+        let variant = format!("{}{}", to_pascal_case(column_name), suffix);
+        if conditions
+            .iter()
+            .any(|existing| existing.variant == variant)
+        {
+            anyhow::bail!("has more than one `{}` condition", variant);
+        }
+        if conditions.iter().any(|existing| existing.param == param) {
+            anyhow::bail!("uses parameter '{}' in more than one condition", param);
+        }
+
+        let placeholder = format!("#{{{}}}", param);
+        let (sql_before_param, sql_after_param) = condition
+            .split_once(&placeholder)
+            .expect("the condition contains its parameter");
+        conditions.push(FilterCondition {
+            column,
+            variant,
+            param,
+            sql_before_param: sql_before_param.to_string(),
+            sql_after_param: sql_after_param.to_string(),
+        });
+    }
+
+    let before = sql[..where_range.start].trim_end().to_string();
+    let after = match rest[clause_len..].trim() {
+        "" => String::new(),
+        after => format!("\n{}", after),
+    };
+    let outside_params = parse_parameter_names_from_sql(&format!("{}\n{}", before, after));
+    if let Some(condition) = conditions
+        .iter()
+        .find(|condition| outside_params.contains(&condition.param))
+    {
+        anyhow::bail!(
+            "requires the parameter '{}' of a filter condition to be used only there",
+            condition.param
+        );
+    }
+
+    Ok(FilterQueryParts {
+        before,
+        conditions,
+        after,
+    })
+}
+
+/// Output columns of the top-level SELECT list computed by a SUM() or COUNT() aggregate, as
+/// (output column name, aggregate function), e.g. ("total", "SUM") for `SUM(amount) AS total`
+/// Columns are named as PostgreSQL names them: by their alias, or by the outermost function
+pub fn aggregate_output_columns(sql: &str) -> Vec<(String, String)> {
+    static SELECT: OnceLock<regex::Regex> = OnceLock::new();
+    let select_pattern =
+        SELECT.get_or_init(|| regex::Regex::new(r"(?i)\bSELECT\b(\s+DISTINCT\b)?").unwrap());
+    static FROM: OnceLock<regex::Regex> = OnceLock::new();
+    let from_pattern = FROM.get_or_init(|| regex::Regex::new(r"(?i)\bFROM\b").unwrap());
+    static COMMA: OnceLock<regex::Regex> = OnceLock::new();
+    let comma_pattern = COMMA.get_or_init(|| regex::Regex::new(",").unwrap());
+    static AGGREGATE: OnceLock<regex::Regex> = OnceLock::new();
+    let aggregate_pattern =
+        AGGREGATE.get_or_init(|| regex::Regex::new(r"(?i)\b(SUM|COUNT)\s*\(").unwrap());
+    static ALIAS: OnceLock<regex::Regex> = OnceLock::new();
+    let alias_pattern = ALIAS.get_or_init(|| {
+        regex::Regex::new(r#"(?is)(?:\bAS\s+|[)\s])"?([a-z_][a-z0-9_]*)"?$"#).unwrap()
+    });
+    static FUNCTION: OnceLock<regex::Regex> = OnceLock::new();
+    let function_pattern =
+        FUNCTION.get_or_init(|| regex::Regex::new(r"(?i)^([a-z_][a-z0-9_]*)\s*\(").unwrap());
+
+    let Some(select) = top_level_matches(select_pattern, sql).into_iter().next() else {
+        return Vec::new();
+    };
+    let rest = &sql[select.end..];
+    let list_len = top_level_matches(from_pattern, rest)
+        .first()
+        .map_or(rest.len(), |found| found.start);
+    let list = &rest[..list_len];
+
+    let mut columns = Vec::new();
+    let mut start = 0;
+    for range in top_level_matches(comma_pattern, list)
+        .into_iter()
+        .chain(std::iter::once(list.len()..list.len()))
+    {
+        let item = list[start..range.start].trim();
+        start = range.end;
+        let Some(aggregate) = aggregate_pattern.captures(item) else {
+            continue;
+        };
+        let name = match alias_pattern.captures(item) {
+            Some(alias) if !item.ends_with(')') => alias[1].to_string(),
+            _ => match function_pattern.captures(item) {
+                Some(function) => function[1].to_lowercase(),
+                None => continue,
+            },
+        };
+        columns.push((name, aggregate[1].to_uppercase()));
+    }
+    columns
+}
+
+/// Placeholder for the table shard in the SQL of queries with `shard_by`, e.g. `orders_${shard}`
+pub const SHARD_PLACEHOLDER: &str = "${shard}";
+
+/// Maximum shard length, keeping suffixed table names within PostgreSQL's 63 byte identifier limit
+pub const MAX_SHARD_LEN: usize = 32;
+
+/// Whether a shard can be formatted into a table name without quoting
+/// Mirrors the runtime check of the generated `sharding::shard_sql`
+pub fn is_valid_shard(shard: &str) -> bool {
+    !shard.is_empty()
+        && shard.len() <= MAX_SHARD_LEN
+        && shard
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'_')
+}
+
+/// Position of a named parameter in a list of parameter names, ignoring the `?` suffix
+fn named_param_position(param_names: &[String], param_name: &str) -> Option<usize> {
+    let clean_name = param_name.trim_end_matches('?');
+    param_names
+        .iter()
+        .position(|name| name.trim_end_matches('?') == clean_name)
+}
+
 /// Convert SQL with named parameters ${param} to positional parameters $1, $2, etc.
+/// Repeated parameters reuse the placeholder of their first appearance, so each distinct
+/// parameter is bound once
 pub fn convert_named_params_to_positional(sql: &str) -> (String, Vec<String>) {
-    let mut param_names = Vec::new();
+    let mut param_names: Vec<String> = Vec::new();
     let mut result_sql = String::new();
     let mut chars = sql.chars().peekable();
-    let mut param_counter = 1;
 
     while let Some(ch) = chars.next() {
         if ch == '#' {
@@ -915,9 +1907,14 @@ pub fn convert_named_params_to_positional(sql: &str) -> (String, Vec<String>) {
                 while let Some(inner_ch) = chars.next() {
                     if inner_ch == '}' {
                         if !param_name.is_empty() {
-                            param_names.push(param_name);
-                            result_sql.push_str(&format!("${}", param_counter));
-                            param_counter += 1;
+                            let position = match named_param_position(&param_names, &param_name) {
+                                Some(index) => index + 1,
+                                None => {
+                                    param_names.push(param_name);
+                                    param_names.len()
+                                }
+                            };
+                            result_sql.push_str(&format!("${}", position));
                         }
                         break;
                     } else {
@@ -941,47 +1938,46 @@ pub fn convert_named_params_to_positional(sql: &str) -> (String, Vec<String>) {
     }
 }
 
-/// Extract all unique enum types from input and output types
-pub fn extract_enum_types(
-    input_types: &[RustType],
-    output_types: &[OutputColumn],
-) -> Vec<(String, Vec<String>, String)> {
-    let mut enum_types = std::collections::HashMap::new();
-
-    // Check input types for enums
-    for input_type in input_types {
-        if let Some(ref variants) = input_type.enum_variants {
-            if let Some(ref pg_type_name) = input_type.pg_type_name {
-                enum_types.insert(
-                    input_type.rust_type.clone(),
-                    (variants.clone(), pg_type_name.clone()),
-                );
-            }
+/// Enum types among the given types by Rust enum name, with their variants and PostgreSQL type
+/// Ordered by name so that regenerations emit the enums in a stable order
+pub(crate) fn sorted_enum_types<'a>(
+    types: impl IntoIterator<Item = &'a RustType>,
+) -> std::collections::BTreeMap<String, (Vec<String>, String)> {
+    let mut enum_types = std::collections::BTreeMap::new();
+    for rust_type in types {
+        if let (Some(variants), Some(pg_type_name)) =
+            (&rust_type.enum_variants, &rust_type.pg_type_name)
+        {
+            enum_types.insert(
+                enum_name_of(rust_type),
+                (variants.clone(), pg_type_name.clone()),
+            );
         }
     }
+    enum_types
+}
 
-    // Check output types for enums
-    for output_col in output_types {
-        if let Some(ref variants) = output_col.rust_type.enum_variants {
-            if let Some(ref pg_type_name) = output_col.rust_type.pg_type_name {
-                enum_types.insert(
-                    output_col.rust_type.rust_type.clone(),
-                    (variants.clone(), pg_type_name.clone()),
-                );
-            }
-        }
+/// Get the Rust enum name of an enum or enum array type
+pub(crate) fn enum_name_of(rust_type: &RustType) -> String {
+    if rust_type.is_enum_array {
+        rust_type
+            .rust_type
+            .trim_start_matches("Vec<")
+            .trim_end_matches('>')
+            .to_string()
+    } else {
+        rust_type.rust_type.clone()
     }
-
-    enum_types
-        .into_iter()
-        .map(|(rust_name, (variants, pg_name))| (rust_name, variants, pg_name))
-        .collect()
 }
 
+/// Value bound to text parameters without analysis_params values when running EXPLAIN
+pub(crate) const DUMMY_TEXT: &str = "dummy";
+
 /// Create dummy parameter values for EXPLAIN queries
 /// Returns (dummy_params, special_params) where special_params contains info about enums and numeric types
 pub async fn create_dummy_params(
     client: &tokio_postgres::Client,
+    enum_types: &EnumTypeCache,
     param_types: &[tokio_postgres::types::Type],
 ) -> Result<(
     Vec<Box<dyn tokio_postgres::types::ToSql + Sync>>,
@@ -994,7 +1990,8 @@ pub async fn create_dummy_params(
 
     for param_type in param_types {
         // Check if this is an enum type and get actual enum values
-        if let Ok(Some(enum_info)) = get_enum_type_info(client, param_type.oid()).await {
+        if let Ok(Some(enum_info)) = get_enum_type_info(client, enum_types, param_type.oid()).await
+        {
             special_params.push((
                 dummy_params.len(),
                 enum_info.type_name.clone(),
@@ -1004,9 +2001,26 @@ pub async fn create_dummy_params(
             continue;
         }
 
-        // Handle numeric type specially - PostgreSQL is strict about numeric conversion
-        if param_type.name() == "numeric" {
-            special_params.push((dummy_params.len(), "numeric".to_string(), "0".to_string()));
+        // Arrays of enums are passed as an empty array literal
+        if let Ok(Some(enum_info)) =
+            get_enum_array_element_info(client, enum_types, param_type.oid()).await
+        {
+            special_params.push((
+                dummy_params.len(),
+                format!("{}[]", enum_info.type_name),
+                "{}".to_string(),
+            ));
+            dummy_params.push(Box::new("ENUM_ARRAY_PLACEHOLDER".to_string()));
+            continue;
+        }
+
+        // Handle numeric types specially - PostgreSQL is strict about numeric conversion
+        if matches!(param_type.name(), "numeric" | "money") {
+            special_params.push((
+                dummy_params.len(),
+                param_type.name().to_string(),
+                "0".to_string(),
+            ));
             dummy_params.push(Box::new("NUMERIC_PLACEHOLDER".to_string()));
             continue;
         }
@@ -1045,6 +2059,24 @@ pub async fn create_dummy_params(
             continue;
         }
 
+        // Full-text search types have no ToSql implementation, use literals which parse as both
+        let text_search_default = match param_type.name() {
+            "tsvector" => Some(("tsvector", "dummy")),
+            "tsquery" => Some(("tsquery", "dummy")),
+            "_tsvector" => Some(("tsvector[]", "{}")),
+            "_tsquery" => Some(("tsquery[]", "{}")),
+            _ => None,
+        };
+        if let Some((type_name, default_value)) = text_search_default {
+            special_params.push((
+                dummy_params.len(),
+                type_name.to_string(),
+                default_value.to_string(),
+            ));
+            dummy_params.push(Box::new("TEXT_SEARCH_PLACEHOLDER".to_string()));
+            continue;
+        }
+
         // Handle built-in PostgreSQL types
         let dummy_value: Box<dyn tokio_postgres::types::ToSql + Sync> = match param_type {
             // Boolean & Numeric Types
@@ -1063,7 +2095,7 @@ pub async fn create_dummy_params(
             | &Type::BPCHAR
             | &Type::NAME
             | &Type::XML
-            | &Type::UNKNOWN => Box::new("dummy".to_string()),
+            | &Type::UNKNOWN => Box::new(DUMMY_TEXT.to_string()),
 
             // Binary & Bit Types
             &Type::BYTEA => Box::new(vec![0u8]),
@@ -1104,10 +2136,867 @@ pub async fn create_dummy_params(
             &Type::UUID_ARRAY => Box::new(Vec::<uuid::Uuid>::new()),
 
             // Fallback for unknown types - use string
-            _ => Box::new("dummy".to_string()),
+            _ => Box::new(DUMMY_TEXT.to_string()),
         };
         dummy_params.push(dummy_value);
     }
 
     Ok((dummy_params, special_params))
 }
+
+/// Apply the `numeric_as` query option to the output columns
+/// NUMERIC columns selected by the option are exposed as f64 or i64 instead of rust_decimal::Decimal
+pub fn apply_numeric_as(
+    query_name: &str,
+    numeric_as: &NumericAs,
+    output_types: &mut [OutputColumn],
+) -> Result<()> {
+    let is_decimal = |col: &OutputColumn| {
+        col.rust_type.rust_type == "rust_decimal::Decimal" && !col.rust_type.needs_json_wrapper
+    };
+
+    match numeric_as {
+        NumericAs::Query(numeric_type) => {
+            for col in output_types.iter_mut().filter(|col| is_decimal(col)) {
+                set_numeric_type(&mut col.rust_type, *numeric_type);
+            }
+        }
+        NumericAs::Columns(columns) => {
+            let mut column_names: Vec<_> = columns.keys().collect();
+            column_names.sort();
+            for column_name in column_names {
+                let Some(col) = output_types.iter_mut().find(|col| &col.name == column_name) else {
+                    anyhow::bail!(
+                        "Query '{}' numeric_as.{}: no such output column",
+                        query_name,
+                        column_name
+                    );
+                };
+                if !is_decimal(col) {
+                    anyhow::bail!(
+                        "Query '{}' numeric_as.{}: column is {}, not NUMERIC",
+                        query_name,
+                        column_name,
+                        col.rust_type.rust_type
+                    );
+                }
+                set_numeric_type(&mut col.rust_type, columns[column_name]);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn set_numeric_type(rust_type: &mut RustType, numeric_type: NumericType) {
+    match numeric_type {
+        NumericType::Decimal => {}
+        NumericType::F64 => {
+            rust_type.rust_type = "f64".to_string();
+            rust_type.is_numeric_as_f64 = true;
+        }
+        NumericType::I64 => {
+            rust_type.rust_type = "i64".to_string();
+            rust_type.is_numeric_as_i64 = true;
+        }
+    }
+}
+
+/// Apply the `char_flags` query option to the output columns and parameters
+/// CHAR(1), text and "char" columns and parameters selected by the option are generated as bool
+/// or the enum of the flag, converted from and to their codes
+pub fn apply_char_flags(
+    query_name: &str,
+    char_flags: &std::collections::BTreeMap<String, crate::query_definition::CharFlag>,
+    param_names: &[String],
+    type_info: &mut QueryTypeInfo,
+) -> Result<()> {
+    let to_flag = |name: &str,
+                   rust_type: &mut RustType,
+                   flag: &crate::query_definition::CharFlag| {
+        let code_type = rust_type.rust_type.clone();
+        if code_type != "String" && code_type != "i8" {
+            anyhow::bail!(
+                "Query '{}' char_flags.{}: {} is {}, not CHAR(1), text or \"char\"",
+                query_name,
+                name,
+                name,
+                code_type
+            );
+        }
+        if rust_type.needs_json_wrapper || rust_type.enum_variants.is_some() {
+            anyhow::bail!(
+                "Query '{}' char_flags.{}: column has a `types` mapping or is an enum",
+                query_name,
+                name
+            );
+        }
+        if code_type == "i8"
+            && flag
+                .codes
+                .iter()
+                .any(|(code, _)| code.len() != 1 || !code.is_ascii())
+        {
+            anyhow::bail!(
+                "Query '{}' char_flags.{}: codes of \"char\" columns must be single ASCII characters",
+                query_name,
+                name
+            );
+        }
+        rust_type.rust_type = flag.rust_type.clone();
+        rust_type.char_flag = Some(CharFlagColumn {
+            flag: flag.clone(),
+            code_type,
+        });
+        Ok(())
+    };
+
+    for (name, flag) in char_flags {
+        let mut found = false;
+        for (i, param_name) in param_names.iter().enumerate() {
+            if param_name.trim_end_matches('?') != name {
+                continue;
+            }
+            if let Some(rust_type) = type_info.input_types.get_mut(i) {
+                to_flag(name, rust_type, flag)?;
+                found = true;
+            }
+        }
+        for col in type_info
+            .output_types
+            .iter_mut()
+            .filter(|col| &col.name == name)
+        {
+            to_flag(name, &mut col.rust_type, flag)?;
+            found = true;
+        }
+        if !found {
+            anyhow::bail!(
+                "Query '{}' char_flags.{}: no such parameter or output column",
+                query_name,
+                name
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Apply the `name[]` keys of the `types` query option to the text array columns and parameters
+/// Their elements are parsed with `FromStr` on decode and formatted with `Display` on bind, so
+/// they are generated as vectors of the mapped type instead of `Vec<String>`
+/// Keys without a table apply to parameters only, like the other `types` keys
+pub fn apply_array_element_types(
+    query_name: &str,
+    types: &HashMap<String, String>,
+    param_names: &[String],
+    type_info: &mut QueryTypeInfo,
+) -> Result<()> {
+    let to_elements = |key: &str, rust_type: &mut RustType, element_type: &str| {
+        if rust_type.rust_type != "Vec<String>"
+            || rust_type.needs_json_wrapper
+            || rust_type.enum_variants.is_some()
+        {
+            anyhow::bail!(
+                "Query '{}' types.{}: {} is not a text array without other conversions",
+                query_name,
+                key,
+                rust_type.rust_type
+            );
+        }
+        rust_type.rust_type = format!("Vec<{}>", element_type);
+        rust_type.array_element = Some(element_type.to_string());
+        Ok(())
+    };
+
+    for (key, element_type) in sorted_entries(types)
+        .into_iter()
+        .filter(|(key, _)| key.ends_with("[]"))
+    {
+        let field = key.trim_end_matches("[]");
+        let (column, has_table) = match field.rsplit_once('.') {
+            Some((_, column)) => (column, true),
+            None => (field, false),
+        };
+        for (i, param_name) in param_names.iter().enumerate() {
+            if param_name.trim_end_matches('?') != column {
+                continue;
+            }
+            if let Some(rust_type) = type_info.input_types.get_mut(i) {
+                to_elements(key, rust_type, element_type)?;
+            }
+        }
+        if has_table {
+            for col in type_info
+                .output_types
+                .iter_mut()
+                .filter(|col| col.name == column)
+            {
+                to_elements(key, &mut col.rust_type, element_type)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Apply the `currency_columns` query option to the output columns and parameters
+/// NUMERIC columns and parameters selected by the option are generated as `Money` amounts of
+/// their currency instead of rust_decimal::Decimal
+pub fn apply_currency_columns(
+    query_name: &str,
+    currency_columns: &std::collections::BTreeMap<String, String>,
+    param_names: &[String],
+    type_info: &mut QueryTypeInfo,
+) -> Result<()> {
+    let to_money = |name: &str, rust_type: &mut RustType, currency: &str| -> Result<()> {
+        let money_type = match rust_type.rust_type.as_str() {
+            "rust_decimal::Decimal" => "super::money::Money",
+            "Vec<rust_decimal::Decimal>" => "Vec<super::money::Money>",
+            _ => anyhow::bail!(
+                "Query '{}' currency_columns.{}: {} is {}, not NUMERIC",
+                query_name,
+                name,
+                name,
+                rust_type.rust_type
+            ),
+        };
+        if rust_type.needs_json_wrapper {
+            anyhow::bail!(
+                "Query '{}' currency_columns.{}: column has a `types` mapping",
+                query_name,
+                name
+            );
+        }
+        rust_type.rust_type = money_type.to_string();
+        rust_type.currency = Some(currency.to_string());
+        Ok(())
+    };
+
+    for (name, currency) in currency_columns {
+        let mut found = false;
+        for (i, param_name) in param_names.iter().enumerate() {
+            if param_name.trim_end_matches('?') != name {
+                continue;
+            }
+            if let Some(rust_type) = type_info.input_types.get_mut(i) {
+                to_money(name, rust_type, currency)?;
+                found = true;
+            }
+        }
+        for col in type_info
+            .output_types
+            .iter_mut()
+            .filter(|col| &col.name == name)
+        {
+            to_money(name, &mut col.rust_type, currency)?;
+            found = true;
+        }
+        if !found {
+            anyhow::bail!(
+                "Query '{}' currency_columns.{}: no such parameter or output column",
+                query_name,
+                name
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Column type and CHECK constraints of a column, one row per single-column CHECK constraint
+const CHECK_CONSTRAINT_SQL: &str = r"
+SELECT
+    t.typname::text AS type_name,
+    pg_get_constraintdef(c.oid) AS definition
+FROM pg_attribute a
+JOIN pg_type t ON t.oid = a.atttypid
+LEFT JOIN pg_constraint c
+    ON c.conrelid = a.attrelid
+   AND c.contype = 'c'
+   AND c.conkey = ARRAY[a.attnum]
+WHERE a.attrelid = to_regclass($1::text)
+  AND a.attname = $2::text
+  AND a.attnum > 0
+  AND NOT a.attisdropped
+ORDER BY c.conname";
+
+/// Apply the `check_values` query option
+/// Parameters and output columns named like a configured column become a generated enum,
+/// or are checked against a generated constant before binding
+pub async fn apply_check_values(
+    client: &tokio_postgres::Client,
+    query_name: &str,
+    check_values: &std::collections::BTreeMap<String, CheckValuesStyle>,
+    param_names: &[String],
+    type_info: &mut QueryTypeInfo,
+) -> Result<()> {
+    for (key, style) in check_values {
+        let Some((table, column)) = key.rsplit_once('.') else {
+            continue;
+        };
+
+        let qualified_table = qualify_table(table);
+        let rows = client
+            .query(CHECK_CONSTRAINT_SQL, &[&qualified_table, &column])
+            .await?;
+        let Some(first_row) = rows.first() else {
+            anyhow::bail!(
+                "Query '{}' check_values.{}: no such column",
+                query_name,
+                key
+            );
+        };
+        let type_name: String = first_row.get("type_name");
+        if type_name != "text" && type_name != "varchar" {
+            anyhow::bail!(
+                "Query '{}' check_values.{}: column is {}, not text or varchar",
+                query_name,
+                key,
+                type_name
+            );
+        }
+        let Some(values) = rows.iter().find_map(|row| {
+            row.get::<_, Option<String>>("definition")
+                .and_then(|definition| check_constraint_values(&definition))
+        }) else {
+            anyhow::bail!(
+                "Query '{}' check_values.{}: column has no CHECK constraint of the form `{} IN ('a', 'b')`",
+                query_name,
+                key,
+                column
+            );
+        };
+
+        let unqualified_table = table.rsplit('.').next().unwrap_or(table);
+        let is_text = |rust_type: &RustType| {
+            !rust_type.needs_json_wrapper
+                && (rust_type.rust_type == "String" || rust_type.rust_type == "Vec<String>")
+        };
+        let matching_params: Vec<usize> = param_names
+            .iter()
+            .enumerate()
+            .filter(|(_, name)| name.trim_end_matches('?') == column)
+            .map(|(i, _)| i)
+            .filter(|i| type_info.input_types.get(*i).is_some_and(is_text))
+            .collect();
+        let matching_columns: Vec<usize> = type_info
+            .output_types
+            .iter()
+            .enumerate()
+            .filter(|(_, col)| col.name == column && is_text(&col.rust_type))
+            .map(|(i, _)| i)
+            .collect();
+        if matching_params.is_empty() && matching_columns.is_empty() {
+            anyhow::bail!(
+                "Query '{}' check_values.{}: no text parameter or output column named '{}'",
+                query_name,
+                key,
+                column
+            );
+        }
+
+        match style {
+            CheckValuesStyle::Enum => {
+                let mut variant_names = HashSet::new();
+                for value in &values {
+                    let variant_name = to_pascal_case(value);
+                    let is_identifier = variant_name
+                        .chars()
+                        .next()
+                        .is_some_and(|c| c.is_ascii_alphabetic())
+                        && variant_name.chars().all(|c| c.is_ascii_alphanumeric());
+                    if !is_identifier || !variant_names.insert(variant_name) {
+                        anyhow::bail!(
+                            "Query '{}' check_values.{}: value '{}' does not map to a unique enum variant, use const instead",
+                            query_name,
+                            key,
+                            value
+                        );
+                    }
+                }
+
+                let enum_name = format!(
+                    "{}{}",
+                    to_pascal_case(unqualified_table),
+                    to_pascal_case(column)
+                );
+                let to_enum = |rust_type: &mut RustType| {
+                    rust_type.is_enum_array = rust_type.rust_type == "Vec<String>";
+                    rust_type.rust_type = if rust_type.is_enum_array {
+                        format!("Vec<{}>", enum_name)
+                    } else {
+                        enum_name.clone()
+                    };
+                    rust_type.enum_variants = Some(values.clone());
+                    rust_type.pg_type_name = Some(type_name.clone());
+                };
+                for i in matching_params {
+                    to_enum(&mut type_info.input_types[i]);
+                }
+                for i in matching_columns {
+                    to_enum(&mut type_info.output_types[i].rust_type);
+                }
+            }
+            CheckValuesStyle::Const => {
+                type_info.value_checks.push(ValueCheck {
+                    column: column.to_string(),
+                    location: key.clone(),
+                    const_name: format!("{}_{}_VALUES", unqualified_table, column).to_uppercase(),
+                    values,
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Values of a CHECK constraint definition of the form `column IN ('a', 'b')`
+/// PostgreSQL stores it as `CHECK ((column = ANY (ARRAY['a'::text, 'b'::text])))`
+/// Returns None for any other kind of constraint
+fn check_constraint_values(definition: &str) -> Option<Vec<String>> {
+    // Separate the string literals from the rest of the expression
+    let mut values = Vec::new();
+    let mut expression = String::new();
+    let mut chars = definition.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\'' {
+            expression.push(c);
+            continue;
+        }
+        let mut value = String::new();
+        loop {
+            match chars.next()? {
+                '\'' if chars.peek() == Some(&'\'') => {
+                    chars.next();
+                    value.push('\'');
+                }
+                '\'' => break,
+                c => value.push(c),
+            }
+        }
+        values.push(value);
+    }
+
+    let expression = expression.to_uppercase();
+    let is_in_list = expression.contains("= ANY (")
+        && expression.contains("ARRAY[")
+        && ![" AND ", " OR ", "NOT ", "<", ">"]
+            .iter()
+            .any(|operator| expression.contains(operator));
+    (is_in_list && !values.is_empty()).then_some(values)
+}
+
+/// Foreign keys referencing a table, with the column names quoted for use in SQL
+/// Partitions of a partitioned table are skipped as they repeat its foreign keys
+const REFERENCING_FOREIGN_KEYS_SQL: &str = r"
+SELECT
+    c.conname::text AS constraint_name,
+    c.conrelid AS table_oid,
+    quote_ident(n.nspname) || '.' || quote_ident(t.relname) AS table_name,
+    c.confdeltype::text AS on_delete,
+    array_agg(quote_ident(a.attname) ORDER BY k.ord) AS columns,
+    array_agg(quote_ident(fa.attname) ORDER BY k.ord) AS referenced_columns
+FROM pg_constraint c
+JOIN pg_class t ON t.oid = c.conrelid
+JOIN pg_namespace n ON n.oid = t.relnamespace
+CROSS JOIN LATERAL unnest(c.conkey, c.confkey) WITH ORDINALITY AS k(attnum, fattnum, ord)
+JOIN pg_attribute a ON a.attrelid = c.conrelid AND a.attnum = k.attnum
+JOIN pg_attribute fa ON fa.attrelid = c.confrelid AND fa.attnum = k.fattnum
+WHERE c.contype = 'f'
+  AND c.confrelid = $1
+  AND c.conparentid = 0
+GROUP BY c.oid, c.conname, c.conrelid, n.nspname, t.relname, c.confdeltype
+ORDER BY table_name, constraint_name";
+
+/// Levels of ON DELETE CASCADE foreign keys followed from the deleted table
+const MAX_CASCADE_DEPTH: usize = 4;
+
+/// Build the counting query of a DELETE configured with `cascade_preview`
+/// Foreign keys referencing the deleted rows are counted, and those with ON DELETE CASCADE are
+/// followed to the rows referencing the cascaded rows, up to MAX_CASCADE_DEPTH levels
+pub async fn extract_cascade_preview(
+    client: &tokio_postgres::Client,
+    query_name: &str,
+    sql: &str,
+) -> Result<CascadePreview> {
+    let Some((target, where_clause)) = delete_statement_parts(sql) else {
+        anyhow::bail!(
+            "Query '{}' uses cascade_preview, which requires a `DELETE FROM table WHERE ...` statement without USING",
+            query_name
+        );
+    };
+
+    let table = target
+        .split_whitespace()
+        .find(|word| !word.eq_ignore_ascii_case("ONLY"))
+        .unwrap_or(target);
+    let qualified_table = qualify_table(table);
+    let row = client
+        .query_one("SELECT to_regclass($1::text)::oid", &[&qualified_table])
+        .await?;
+    let Some(table_oid) = row.get::<_, Option<u32>>(0) else {
+        anyhow::bail!(
+            "Query '{}' uses cascade_preview, but table '{}' does not exist",
+            query_name,
+            table
+        );
+    };
+
+    let mut deleted_rows = format!("SELECT * FROM {}", target);
+    if !where_clause.is_empty() {
+        deleted_rows.push(' ');
+        deleted_rows.push_str(where_clause);
+    }
+    let mut ctes = vec![format!("automodel_deleted AS (\n    {}\n)", deleted_rows)];
+    let mut foreign_keys = Vec::new();
+    // Tables whose affected rows are held by a CTE, with the tables of their cascade path
+    let mut pending = vec![("automodel_deleted".to_string(), table_oid, vec![table_oid])];
+    let mut next = 0;
+    while next < pending.len() {
+        let (parent, parent_oid, path) = pending[next].clone();
+        next += 1;
+        for row in client
+            .query(REFERENCING_FOREIGN_KEYS_SQL, &[&parent_oid])
+            .await?
+        {
+            let referencing_oid: u32 = row.get("table_oid");
+            let referencing_table: String = row.get("table_name");
+            let columns: Vec<String> = row.get("columns");
+            let referenced_columns: Vec<String> = row.get("referenced_columns");
+            let on_delete = match row.get::<_, String>("on_delete").as_str() {
+                "r" => "Restrict",
+                "c" => "Cascade",
+                "n" => "SetNull",
+                "d" => "SetDefault",
+                _ => "NoAction",
+            };
+
+            let cte = format!("automodel_referencing_{}", foreign_keys.len() + 1);
+            ctes.push(format!(
+                "{} AS (\n    SELECT * FROM {} WHERE ({}) IN (SELECT {} FROM {})\n)",
+                cte,
+                referencing_table,
+                columns.join(", "),
+                referenced_columns.join(", "),
+                parent
+            ));
+            if on_delete == "Cascade"
+                && path.len() < MAX_CASCADE_DEPTH
+                && !path.contains(&referencing_oid)
+            {
+                let mut cascade_path = path.clone();
+                cascade_path.push(referencing_oid);
+                pending.push((cte, referencing_oid, cascade_path));
+            }
+            foreign_keys.push(ReferencingForeignKey {
+                table: referencing_table,
+                constraint: row.get("constraint_name"),
+                on_delete: on_delete.to_string(),
+            });
+        }
+    }
+
+    let counts: Vec<String> = std::iter::once("automodel_deleted".to_string())
+        .chain((1..=foreign_keys.len()).map(|i| format!("automodel_referencing_{}", i)))
+        .map(|cte| format!("    (SELECT count(*) FROM {})", cte))
+        .collect();
+    let preview_sql = format!("WITH {}\nSELECT\n{}", ctes.join(",\n"), counts.join(",\n"));
+
+    // The counting query must be valid for the parameters of the DELETE
+    let (converted_sql, _) = convert_named_params_to_positional(&preview_sql);
+    client.prepare(&converted_sql).await.with_context(|| {
+        format!(
+            "Query '{}' cascade_preview query is invalid:\n{}",
+            query_name, preview_sql
+        )
+    })?;
+
+    Ok(CascadePreview {
+        sql: preview_sql,
+        foreign_keys,
+    })
+}
+
+/// Query of the primary key columns of a table in key order, quoted for use in SQL
+fn primary_key_columns_sql(dialect: Dialect) -> String {
+    format!(
+        r"
+SELECT a.attname::text, quote_ident(a.attname)
+FROM pg_index i
+JOIN pg_class t ON t.oid = i.indrelid
+JOIN pg_attribute a ON a.attrelid = i.indrelid AND a.attnum = ANY(i.indkey)
+WHERE i.indrelid = $1 AND i.indisprimary{}
+ORDER BY array_position(i.indkey::int2[], a.attnum)
+",
+        visible_column_condition(dialect)
+    )
+}
+
+/// Query of the unique indexes of a table without predicate or expressions, with their key
+/// columns in index order, also quoted for use in SQL
+fn unique_index_columns_sql(dialect: Dialect) -> String {
+    format!(
+        r"
+SELECT ic.relname::text, array_agg(a.attname::text ORDER BY k.position),
+    array_agg(quote_ident(a.attname) ORDER BY k.position)
+FROM pg_index i
+JOIN pg_class ic ON ic.oid = i.indexrelid
+JOIN pg_class t ON t.oid = i.indrelid
+CROSS JOIN LATERAL unnest(i.indkey::int2[]) WITH ORDINALITY AS k(attnum, position)
+JOIN pg_attribute a ON a.attrelid = i.indrelid AND a.attnum = k.attnum
+WHERE i.indrelid = $1 AND i.indisunique AND i.indpred IS NULL AND i.indexprs IS NULL
+    AND k.position <= i.indnkeyatts{}
+GROUP BY ic.relname
+ORDER BY ic.relname
+",
+        visible_column_condition(dialect)
+    )
+}
+
+/// Split a list at its top-level commas, outside parentheses and string literals
+fn split_top_level_commas(list: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut start = 0;
+    for (i, ch) in list.char_indices() {
+        match ch {
+            '\'' => in_string = !in_string,
+            '(' if !in_string => depth += 1,
+            ')' if !in_string => depth -= 1,
+            ',' if !in_string && depth == 0 => {
+                parts.push(list[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(list[start..].trim());
+    parts
+}
+
+/// Build the lookups of the rows conflicting with the INSERT of a query configured with
+/// `retrying`, one per unique constraint of the inserted table whose columns are all inserted from
+/// plain parameters, restricted to `constraints` when not empty
+pub async fn extract_retry_lookups(
+    client: &tokio_postgres::Client,
+    enum_types: &EnumTypeCache,
+    query_name: &str,
+    sql: &str,
+    constraints: &[String],
+    field_type_mappings: Option<&HashMap<String, String>>,
+    dialect: Dialect,
+) -> Result<Vec<RetryLookup>> {
+    static TARGET: OnceLock<regex::Regex> = OnceLock::new();
+    let target = TARGET.get_or_init(|| {
+        regex::Regex::new(r"(?is)^\s*INSERT\s+INTO\s+([^\s(]+)(?:\s+AS\s+([a-z_][a-z0-9_]*))?\s*\(([^()]*)\)\s*VALUES\b")
+            .unwrap()
+    });
+    static PARAMETER: OnceLock<regex::Regex> = OnceLock::new();
+    let parameter = PARAMETER.get_or_init(|| {
+        regex::Regex::new(r"(?is)^#\{[a-z_][a-z0-9_]*\}(\s*::\s*[a-z_][a-z0-9_ .]*(?:\[\])?)?$")
+            .unwrap()
+    });
+
+    let (Some(captures), Some((_, returning_start)), Some(values_row)) = (
+        target.captures(sql),
+        insert_returning_parts(sql),
+        single_values_row(sql),
+    ) else {
+        anyhow::bail!(
+            "Query '{}' uses retrying, which requires an `INSERT INTO table (...) VALUES (...) RETURNING ...` statement",
+            query_name
+        );
+    };
+    let table = captures.get(1).map_or("", |found| found.as_str());
+    let alias = captures.get(2).map(|found| found.as_str());
+    let columns: Vec<String> = split_top_level_commas(&captures[3])
+        .iter()
+        .map(
+            |column| match column.strip_prefix('"').and_then(|c| c.strip_suffix('"')) {
+                Some(quoted) => quoted.to_string(),
+                None => column.to_lowercase(),
+            },
+        )
+        .collect();
+    let values = split_top_level_commas(&sql[values_row.start + 1..values_row.end - 1]);
+    if columns.len() != values.len() {
+        anyhow::bail!(
+            "Query '{}' uses retrying, but it inserts {} values into {} columns",
+            query_name,
+            values.len(),
+            columns.len()
+        );
+    }
+    let returning = sql[returning_start + "RETURNING".len()..]
+        .trim()
+        .trim_end_matches(';')
+        .trim_end();
+
+    let qualified_table = qualify_table(table);
+    let row = client
+        .query_one("SELECT to_regclass($1::text)::oid", &[&qualified_table])
+        .await?;
+    let Some(table_oid) = row.get::<_, Option<u32>>(0) else {
+        anyhow::bail!(
+            "Query '{}' uses retrying, but table '{}' does not exist",
+            query_name,
+            table
+        );
+    };
+    let unique_indexes: Vec<(String, Vec<String>, Vec<String>)> = client
+        .query(&unique_index_columns_sql(dialect), &[&table_oid])
+        .await?
+        .iter()
+        .map(|row| (row.get(0), row.get(1), row.get(2)))
+        .collect();
+    for constraint in constraints {
+        if !unique_indexes.iter().any(|(name, _, _)| name == constraint) {
+            anyhow::bail!(
+                "Query '{}' uses retrying, but '{}' is not a unique constraint of table '{}'",
+                query_name,
+                constraint,
+                table
+            );
+        }
+    }
+
+    let mut lookups = Vec::new();
+    for (constraint, key_columns, quoted_columns) in &unique_indexes {
+        if !constraints.is_empty() && !constraints.contains(constraint) {
+            continue;
+        }
+        // Every column of the constraint must be inserted from a parameter
+        let conditions: Option<Vec<String>> = key_columns
+            .iter()
+            .zip(quoted_columns)
+            .map(|(key_column, quoted_column)| {
+                let position = columns.iter().position(|column| column == key_column)?;
+                parameter
+                    .is_match(values[position])
+                    .then(|| format!("{} = {}", quoted_column, values[position]))
+            })
+            .collect();
+        let Some(conditions) = conditions else {
+            if constraints.contains(constraint) {
+                anyhow::bail!(
+                    "Query '{}' uses retrying, but not every column of constraint '{}' is inserted from a parameter",
+                    query_name,
+                    constraint
+                );
+            }
+            continue;
+        };
+        let lookup_sql = format!(
+            "SELECT {}\nFROM {}{}\nWHERE {}",
+            returning,
+            table,
+            alias
+                .map(|alias| format!(" AS {}", alias))
+                .unwrap_or_default(),
+            conditions.join(" AND ")
+        );
+        extract_query_types(client, enum_types, &lookup_sql, field_type_mappings)
+            .await
+            .with_context(|| {
+                format!(
+                    "Query '{}' retrying lookup for constraint '{}' is invalid:\n{}",
+                    query_name, constraint, lookup_sql
+                )
+            })?;
+        lookups.push(RetryLookup {
+            constraint: constraint.clone(),
+            sql: lookup_sql,
+        });
+    }
+    if lookups.is_empty() {
+        anyhow::bail!(
+            "Query '{}' uses retrying, but table '{}' has no unique constraint whose columns are all inserted from parameters",
+            query_name,
+            table
+        );
+    }
+    Ok(lookups)
+}
+
+/// Build the INSERT of a query configured with `id_only`, with its RETURNING clause rewritten
+/// to the primary key of the inserted table
+/// Fails for queries already returning only the primary key, given their `output_types`
+pub async fn extract_id_only_query(
+    client: &tokio_postgres::Client,
+    enum_types: &EnumTypeCache,
+    query_name: &str,
+    sql: &str,
+    output_types: &[OutputColumn],
+    field_type_mappings: Option<&HashMap<String, String>>,
+    dialect: Dialect,
+) -> Result<IdOnlyQuery> {
+    let Some((table, returning_start)) = insert_returning_parts(sql) else {
+        anyhow::bail!(
+            "Query '{}' uses id_only, which requires an `INSERT INTO table ... RETURNING ...` statement",
+            query_name
+        );
+    };
+
+    let qualified_table = qualify_table(table);
+    let row = client
+        .query_one("SELECT to_regclass($1::text)::oid", &[&qualified_table])
+        .await?;
+    let Some(table_oid) = row.get::<_, Option<u32>>(0) else {
+        anyhow::bail!(
+            "Query '{}' uses id_only, but table '{}' does not exist",
+            query_name,
+            table
+        );
+    };
+    let key_columns: Vec<(String, String)> = client
+        .query(&primary_key_columns_sql(dialect), &[&table_oid])
+        .await?
+        .iter()
+        .map(|row| (row.get(0), row.get(1)))
+        .collect();
+    if key_columns.is_empty() {
+        anyhow::bail!(
+            "Query '{}' uses id_only, but table '{}' has no primary key",
+            query_name,
+            table
+        );
+    }
+
+    let returns_key = output_types.len() == key_columns.len()
+        && output_types
+            .iter()
+            .zip(&key_columns)
+            .all(|(column, (name, _))| column.name == *name);
+    if returns_key {
+        anyhow::bail!(
+            "Query '{}' uses id_only, but it already returns only the primary key of table '{}'",
+            query_name,
+            table
+        );
+    }
+
+    let returning = key_columns
+        .iter()
+        .map(|(_, quoted)| quoted.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let id_only_sql = format!(
+        "{}\nRETURNING {}",
+        sql[..returning_start].trim_end(),
+        returning
+    );
+    if parse_parameter_names_from_sql(&id_only_sql) != parse_parameter_names_from_sql(sql) {
+        anyhow::bail!(
+            "Query '{}' uses id_only, which requires the RETURNING clause to have no parameters",
+            query_name
+        );
+    }
+
+    let id_only_types =
+        extract_query_types(client, enum_types, &id_only_sql, field_type_mappings).await?;
+    Ok(IdOnlyQuery {
+        sql: id_only_sql,
+        output_types: id_only_types.output_types,
+    })
+}
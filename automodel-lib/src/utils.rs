@@ -31,3 +31,106 @@ pub(crate) fn to_snake_case(s: &str) -> String {
 
     result
 }
+
+/// Check if a string is a Rust keyword
+pub(crate) fn is_rust_keyword(name: &str) -> bool {
+    matches!(
+        name,
+        "as" | "break"
+            | "const"
+            | "continue"
+            | "crate"
+            | "else"
+            | "enum"
+            | "extern"
+            | "false"
+            | "fn"
+            | "for"
+            | "if"
+            | "impl"
+            | "in"
+            | "let"
+            | "loop"
+            | "match"
+            | "mod"
+            | "move"
+            | "mut"
+            | "pub"
+            | "ref"
+            | "return"
+            | "self"
+            | "Self"
+            | "static"
+            | "struct"
+            | "super"
+            | "trait"
+            | "true"
+            | "type"
+            | "unsafe"
+            | "use"
+            | "where"
+            | "while"
+            | "async"
+            | "await"
+            | "dyn"
+            | "abstract"
+            | "become"
+            | "box"
+            | "do"
+            | "final"
+            | "macro"
+            | "override"
+            | "priv"
+            | "typeof"
+            | "unsized"
+            | "virtual"
+            | "yield"
+            | "try"
+    )
+}
+
+/// Escape a name for use as a Rust identifier
+/// Keywords become raw identifiers (`r#type`), except those which cannot be raw
+/// identifiers (`self`, `Self`, `super`, `crate`), which get a `_` suffix
+pub(crate) fn to_rust_ident(name: &str) -> String {
+    match name {
+        "self" | "Self" | "super" | "crate" => format!("{}_", name),
+        _ if is_rust_keyword(name) => format!("r#{}", name),
+        _ => name.to_string(),
+    }
+}
+
+/// Entries of a map in the order of their keys, so that the entry an error reports does not
+/// depend on the iteration order of the map
+pub(crate) fn sorted_entries<K: Ord, V>(map: &std::collections::HashMap<K, V>) -> Vec<(&K, &V)> {
+    let mut entries: Vec<(&K, &V)> = map.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
+/// Split a message with `{name}` placeholders into a `format!` string with `{:?}` in place of
+/// each placeholder, and the placeholder names in order
+/// Braces always delimit placeholders, so the message cannot contain literal braces
+pub(crate) fn parse_message_template(message: &str) -> Result<(String, Vec<String>), String> {
+    let mut format_string = String::new();
+    let mut names = Vec::new();
+    let mut rest = message;
+    while let Some(start) = rest.find(['{', '}']) {
+        if rest[start..].starts_with('}') {
+            return Err(format!("unmatched '}}' in '{}'", message));
+        }
+        format_string.push_str(&rest[..start]);
+        let Some(len) = rest[start + 1..].find('}') else {
+            return Err(format!("unclosed '{{' in '{}'", message));
+        };
+        let name = rest[start + 1..start + 1 + len].trim();
+        if name.is_empty() {
+            return Err(format!("empty placeholder in '{}'", message));
+        }
+        format_string.push_str("{:?}");
+        names.push(name.to_string());
+        rest = &rest[start + len + 2..];
+    }
+    format_string.push_str(rest);
+    Ok((format_string, names))
+}
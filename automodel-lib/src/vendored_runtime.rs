@@ -0,0 +1,97 @@
+/// Output option writing the helpers shared by all generated modules (error types, error
+/// classification, observers, ...) into a module of the user's crate instead of the generated tree
+use crate::edit_guard::EditGuard;
+use anyhow::Result;
+use std::path::Path;
+
+/// Shared helpers written to a file of the user's crate instead of the generated `mod.rs`
+///
+/// The generated `mod.rs` re-exports the module with `pub use {module_path}::*;`, so generated
+/// functions and user code keep referring to the helpers through the generated tree
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VendoredRuntimeConfig {
+    /// File the helpers are written to, e.g. "src/automodel_rt.rs"
+    pub file: String,
+    /// Path of the file's module in the crate, e.g. "crate::automodel_rt"
+    pub module_path: String,
+}
+
+impl VendoredRuntimeConfig {
+    /// Configuration for a file of the crate's `src` directory, with the module path derived
+    /// from the file, e.g. "crate::db::automodel_rt" for "src/db/automodel_rt.rs"
+    pub fn for_file(file: &str) -> Result<Self> {
+        let normalized = file.replace('\\', "/");
+        let module_path = normalized
+            .strip_prefix("./")
+            .unwrap_or(&normalized)
+            .strip_prefix("src/")
+            .and_then(|path| path.strip_suffix(".rs"))
+            .map(|path| path.strip_suffix("/mod").unwrap_or(path))
+            .filter(|path| {
+                !path.is_empty()
+                    && path
+                        .split('/')
+                        .all(crate::sqlfile_parser::is_valid_rust_identifier)
+            })
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Cannot derive the module path of '{}', which is not a .rs file under src/ with a valid module name; set the module path explicitly",
+                    file
+                )
+            })?;
+        Ok(Self {
+            file: file.to_string(),
+            module_path: format!("crate::{}", module_path.replace('/', "::")),
+        })
+    }
+
+    /// Fail if the file or module path cannot be used with the output directory
+    pub(crate) fn validate(&self, output_path: &Path) -> Result<()> {
+        if !self.file.ends_with(".rs") {
+            anyhow::bail!("vendored_runtime file '{}' must be a .rs file", self.file);
+        }
+        if Path::new(&self.file).starts_with(output_path) {
+            anyhow::bail!(
+                "vendored_runtime file '{}' must be outside the output directory {}, whose unknown files are removed",
+                self.file,
+                output_path.display()
+            );
+        }
+        let is_valid_path = self
+            .module_path
+            .split("::")
+            .enumerate()
+            .all(|(i, segment)| {
+                (i == 0 && matches!(segment, "crate" | "super" | "self"))
+                    || crate::sqlfile_parser::is_valid_rust_identifier(segment)
+            });
+        if !is_valid_path {
+            anyhow::bail!(
+                "vendored_runtime module path '{}' is not a valid Rust path, e.g. crate::automodel_rt",
+                self.module_path
+            );
+        }
+        Ok(())
+    }
+
+    /// Whether the vendored file exists, generated code without it does not compile
+    pub(crate) fn exists(&self) -> bool {
+        Path::new(&self.file).is_file()
+    }
+
+    /// Write the shared helpers into the vendored file
+    pub(crate) fn write(&self, shared_items: &str, edit_guard: &mut EditGuard) -> Result<()> {
+        let content = format!(
+            "// This file was automatically generated by AutoModel. Do not edit manually.\n\
+             // Helpers shared by the generated modules, re-exported by their mod.rs\n\n{}",
+            shared_items
+        );
+        edit_guard.write(Path::new(&self.file), &content)?;
+        Ok(())
+    }
+
+    /// Item of the generated `mod.rs` re-exporting the vendored helpers
+    pub(crate) fn reexport(&self) -> String {
+        format!("pub use {}::*;\n", self.module_path)
+    }
+}
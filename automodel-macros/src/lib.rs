@@ -0,0 +1,255 @@
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{parse_macro_input, FnArg, LitStr, Pat, TraitItemFn};
+
+/// Bind a trait method to the function AutoModel generates for a SQL file
+///
+/// ```ignore
+/// trait UserStore {
+///     #[automodel::query(file = "queries/users/07_find_user_by_email.sql")]
+///     async fn find_user_by_email(
+///         &self,
+///         executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+///         email: String,
+///     ) -> Result<Option<FindUserByEmailItem>, ErrorReadOnly>;
+/// }
+/// ```
+///
+/// The method gets a default body calling `crate::generated::{module}::{query}`. The query of the
+/// SQL file is looked up in the `automodel.queries.json` registry written next to the generated
+/// code, which lists the arguments of every generated function.
+/// Use `generated = "crate::db"` if the generated code lives elsewhere, the registry is then read
+/// from `src/db`, or `registry = "path/to/automodel.queries.json"` for another location.
+///
+/// The first argument after the receiver is the executor; the remaining arguments must be named
+/// like the arguments of the generated function, in the same order. Argument and return types
+/// are checked by the compiler against the generated function, so the method fails to compile
+/// when its signature no longer matches the analyzed query.
+#[proc_macro_attribute]
+pub fn query(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut file: Option<LitStr> = None;
+    let mut generated: Option<LitStr> = None;
+    let mut registry: Option<LitStr> = None;
+    let attr_parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("file") {
+            file = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("generated") {
+            generated = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("registry") {
+            registry = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error(
+                "unsupported automodel::query argument, expected `file`, `generated` or `registry`",
+            ))
+        }
+    });
+    parse_macro_input!(attr with attr_parser);
+    let method = parse_macro_input!(item as TraitItemFn);
+
+    match expand_query(file, generated, registry, method.clone()) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => {
+            // Keep the declaration so that callers do not report follow-up errors
+            let error = error.to_compile_error();
+            quote!(#error #method).into()
+        }
+    }
+}
+
+fn expand_query(
+    file: Option<LitStr>,
+    generated: Option<LitStr>,
+    registry: Option<LitStr>,
+    mut method: TraitItemFn,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let file = file.ok_or_else(|| {
+        syn::Error::new(
+            Span::call_site(),
+            "missing `file = \"queries/<module>/<query>.sql\"` argument",
+        )
+    })?;
+    let generated: syn::Path = match &generated {
+        Some(path) => path.parse()?,
+        None => syn::parse_quote!(crate::generated),
+    };
+
+    if let Some(body) = &method.default {
+        return Err(syn::Error::new_spanned(
+            body,
+            "methods bound to a query must not have a body, it is generated",
+        ));
+    }
+    if method.sig.asyncness.is_none() {
+        return Err(syn::Error::new_spanned(
+            method.sig.fn_token,
+            "methods bound to a query must be `async`",
+        ));
+    }
+
+    // Resolve the SQL file and the registry relative to the crate using the macro
+    let manifest_dir =
+        std::path::PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default());
+    let sql_path = manifest_dir.join(file.value());
+    if !sql_path.is_file() {
+        return Err(syn::Error::new_spanned(
+            &file,
+            format!("SQL file {} does not exist", sql_path.display()),
+        ));
+    }
+    let registry_file = match &registry {
+        Some(registry) => registry.value(),
+        None => default_registry_file(&generated).ok_or_else(|| {
+            syn::Error::new(
+                Span::call_site(),
+                "generated code outside the crate needs `registry = \"path/to/automodel.queries.json\"`",
+            )
+        })?,
+    };
+    let registry_path = manifest_dir.join(&registry_file);
+    let registry_span = registry.as_ref().map_or_else(|| file.span(), LitStr::span);
+    let registry: serde_json::Map<String, serde_json::Value> =
+        std::fs::read_to_string(&registry_path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()))
+            .map_err(|e| {
+                syn::Error::new(
+                    registry_span,
+                    format!(
+                        "failed to read the query registry {}, generate the code first: {}",
+                        registry_path.display(),
+                        e
+                    ),
+                )
+            })?;
+    let (key, entry) = registry
+        .iter()
+        .find(|(_, entry)| {
+            entry["file"]
+                .as_str()
+                .is_some_and(|registered| same_file(registered, &file.value()))
+        })
+        .ok_or_else(|| {
+            syn::Error::new_spanned(
+                &file,
+                format!(
+                    "no generated function for this SQL file in {}, regenerate the code",
+                    registry_path.display()
+                ),
+            )
+        })?;
+    let (module, query_name) = key.split_once("::").ok_or_else(|| {
+        syn::Error::new_spanned(&file, format!("invalid registry entry '{}'", key))
+    })?;
+
+    // Split the arguments into the executor and the query parameters
+    let mut args = method.sig.inputs.iter().filter_map(|arg| match arg {
+        FnArg::Typed(arg) => Some(arg),
+        FnArg::Receiver(_) => None,
+    });
+    let executor = args.next().ok_or_else(|| {
+        syn::Error::new_spanned(
+            &method.sig,
+            "expected an executor argument, e.g. `executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>`",
+        )
+    })?;
+    let executor = argument_ident(executor)?;
+    let mut params = Vec::new();
+    for arg in args {
+        params.push(argument_ident(arg)?);
+    }
+
+    // The arguments of the generated function after its executor
+    let expected: Vec<(String, String)> = entry["params"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|param| {
+            Some((
+                param["name"].as_str()?.to_string(),
+                param["type"].as_str().unwrap_or_default().to_string(),
+            ))
+        })
+        .collect();
+    let actual: Vec<String> = params
+        .iter()
+        .map(|param| param.unraw().to_string())
+        .collect();
+    if actual.len() != expected.len()
+        || actual
+            .iter()
+            .zip(&expected)
+            .any(|(name, (expected, _))| name != expected)
+    {
+        let expected: Vec<String> = expected
+            .iter()
+            .map(|(name, rust_type)| format!("{}: {}", name, rust_type))
+            .collect();
+        return Err(syn::Error::new_spanned(
+            &method.sig.inputs,
+            format!(
+                "arguments do not match query {}::{}: expected executor followed by ({}), got ({})",
+                module,
+                query_name,
+                expected.join(", "),
+                actual.join(", ")
+            ),
+        ));
+    }
+
+    let module = format_ident!("{}", module);
+    let query_name = format_ident!("{}", query_name);
+    let sql_path = sql_path.to_string_lossy().to_string();
+    let registry_path = registry_path.to_string_lossy().to_string();
+    method.default = Some(syn::parse_quote!({
+        // Rebuild when the SQL file or the generated functions change
+        const _: &str = include_str!(#sql_path);
+        const _: &str = include_str!(#registry_path);
+        #generated::#module::#query_name(#executor, #(#params),*).await
+    }));
+    method.semi_token = None;
+
+    Ok(quote!(#method))
+}
+
+/// Registry of generated code inside the crate, e.g. "src/db/automodel.queries.json" for
+/// `crate::db`, None for paths outside the crate
+fn default_registry_file(generated: &syn::Path) -> Option<String> {
+    let mut segments = generated.segments.iter();
+    if segments.next()?.ident != "crate" {
+        return None;
+    }
+    let mut dir = String::from("src");
+    for segment in segments {
+        dir.push('/');
+        dir.push_str(&segment.ident.to_string());
+    }
+    Some(format!("{}/automodel.queries.json", dir))
+}
+
+/// Whether two paths relative to the crate name the same file, e.g. "./queries/a.sql" and
+/// "queries/a.sql"
+fn same_file(a: &str, b: &str) -> bool {
+    let components = |path: &str| -> Vec<String> {
+        std::path::Path::new(&path.replace('\\', "/"))
+            .components()
+            .filter(|component| !matches!(component, std::path::Component::CurDir))
+            .map(|component| component.as_os_str().to_string_lossy().to_string())
+            .collect()
+    };
+    components(a) == components(b)
+}
+
+fn argument_ident(arg: &syn::PatType) -> syn::Result<syn::Ident> {
+    match arg.pat.as_ref() {
+        Pat::Ident(pat) => Ok(pat.ident.clone()),
+        pat => Err(syn::Error::new_spanned(
+            pat,
+            "arguments of methods bound to a query must be plain identifiers",
+        )),
+    }
+}
@@ -0,0 +1,21 @@
+/// AutoModel configuration of the example, shared by build.rs and the tests checking which
+/// builds the generated code is up to date for
+fn automodel_defaults(features: Vec<String>) -> automodel::DefaultsConfig {
+    automodel::DefaultsConfig {
+        telemetry: automodel::DefaultsTelemetryConfig {
+            level: automodel::TelemetryLevel::Debug,
+            include_sql: true,
+            max_param_length: Some(256),
+            max_param_elements: Some(20),
+        },
+        ensure_indexes: true,
+        auto_derives: true,
+        emit_ts: Some("frontend/src/generated".to_string()),
+        emit_proto: Some("proto".to_string()),
+        cache_invalidation_migrations: Some("migrations".to_string()),
+        max_queries_per_file: Some(25),
+        history_queries: Some(Default::default()),
+        features,
+        ..Default::default()
+    }
+}
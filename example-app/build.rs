@@ -1,19 +1,14 @@
+include!("automodel_defaults.rs");
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let defaults = automodel::DefaultsConfig {
-        telemetry: automodel::DefaultsTelemetryConfig {
-            level: automodel::TelemetryLevel::Debug,
-            include_sql: true,
-        },
-        ensure_indexes: true,
-    };
+    let defaults = automodel_defaults(automodel::cargo_features());
     automodel::AutoModel::generate(
         || {
             if std::env::var("CI").is_err() {
-                std::env::var("AUTOMODEL_DATABASE_URL").map_err(|_| {
-                    "AUTOMODEL_DATABASE_URL environment variable must be set for code generation"
-                        .to_string()
-                })
+                automodel::DatabaseUrl::from_env("AUTOMODEL_DATABASE_URL")
+                    .with_dotenv()
+                    .resolve()
             } else {
                 Err(
                     "Detecting not up to date AutoModel generated code in CI environment"
@@ -0,0 +1,17 @@
+// This file was automatically generated by AutoModel. Do not edit manually.
+
+/** Row of `get_accounts_by_activity`: Get the accounts which are active or not, with their flags converted to bool and their kind to an enum */
+export interface GetAccountsByActivityItem {
+  id: number;
+  name: string;
+  is_active: boolean;
+  is_locked: boolean | null;
+  kind: unknown;
+}
+
+/** Row of `set_account_tags`: Replace the tags of an account, stored as text and converted to an enum element by element */
+export interface SetAccountTagsItem {
+  id: number;
+  tags: unknown[];
+}
+// AUTOMODEL_CONTENT_HASH: 215e4e952b989309
@@ -0,0 +1,48 @@
+// This file was automatically generated by AutoModel. Do not edit manually.
+
+/** Row of `get_all_types_test`: Get a row with all PostgreSQL types by ID */
+export interface GetAllTypesTestItem {
+  id: number;
+  bool_col: boolean | null;
+  char_col: string | null;
+  int2_col: number | null;
+  int4_col: number | null;
+  int8_col: number | null;
+  float4_col: number | null;
+  float8_col: number | null;
+  numeric_col: string | null;
+  name_col: string | null;
+  text_col: string | null;
+  varchar_col: string | null;
+  bpchar_col: string | null;
+  bytea_col: number[] | null;
+  bit_col: unknown | null;
+  varbit_col: unknown | null;
+  date_col: string | null;
+  time_col: string | null;
+  timestamp_col: string | null;
+  timestamptz_col: string | null;
+  interval_col: unknown | null;
+  timetz_col: unknown | null;
+  int4_range_col: unknown | null;
+  int8_range_col: unknown | null;
+  num_range_col: unknown | null;
+  ts_range_col: unknown | null;
+  tstz_range_col: unknown | null;
+  date_range_col: unknown | null;
+  inet_col: string | null;
+  cidr_col: string | null;
+  macaddr_col: string | null;
+  json_col: unknown | null;
+  jsonb_col: unknown | null;
+  uuid_col: string | null;
+  bool_array_col: boolean[] | null;
+  int4_array_col: number[] | null;
+  int8_array_col: number[] | null;
+  text_array_col: string[] | null;
+  float8_array_col: number[] | null;
+  int4_range_array_col: unknown[] | null;
+  date_range_array_col: unknown[] | null;
+  created_at: string | null;
+}
+// AUTOMODEL_CONTENT_HASH: a9dff0922594025a
@@ -0,0 +1,95 @@
+// This file was automatically generated by AutoModel. Do not edit manually.
+
+/** Row of `get_user_activity_summary`: Complex CTE query combining recent public.users with aggregate statistics */
+export interface GetUserActivitySummaryItem {
+  id: number;
+  name: string;
+  email: string;
+  created_at: string | null;
+  rank: number | null;
+  total_users: number | null;
+  weekly_users: number | null;
+  avg_age: number | null;
+}
+
+/** Row of `get_hierarchical_user_data`: Recursive CTE to build user hierarchy with referral relationships */
+export interface GetHierarchicalUserDataItem {
+  id: number | null;
+  name: string | null;
+  email: string | null;
+  referrer_id: number | null;
+  level: number | null;
+  path: number[] | null;
+  direct_referrals_count: number | null;
+}
+
+/** Row of `get_user_activity_with_posts`: Complex JOIN query with temporal filtering across multiple tables */
+export interface GetUserActivityWithPostsItem {
+  user_id: number;
+  name: string;
+  email: string;
+  user_created_at: string | null;
+  user_updated_at: string | null;
+  post_id: number;
+  title: string;
+  content: string | null;
+  post_created_at: string | null;
+  published_at: string | null;
+  comment_count: number | null;
+  hours_since_post: number | null;
+  post_date: string | null;
+}
+
+/** Row of `get_user_engagement_metrics`: Complex multi-CTE query calculating user engagement metrics with temporal analysis */
+export interface GetUserEngagementMetricsItem {
+  id: number;
+  name: string;
+  email: string;
+  created_at: string | null;
+  post_count: number | null;
+  comment_count: number | null;
+  last_post_date: string | null;
+  last_comment_date: string | null;
+  avg_publish_delay_hours: number | null;
+  engagement_score: number | null;
+  activity_status: string | null;
+  days_since_last_activity: number | null;
+  engagement_rank: number | null;
+  engagement_percentile: number | null;
+}
+
+/** Row of `get_time_series_user_registrations`: Time series analysis of user registrations with age demographics */
+export interface GetTimeSeriesUserRegistrationsItem {
+  period_start: string | null;
+  registrations_count: number | null;
+  young_adult_count: number | null;
+  middle_aged_count: number | null;
+  senior_count: number | null;
+  avg_age: number | null;
+  first_registration: string | null;
+  last_registration: string | null;
+  period_span_hours: number | null;
+}
+
+/** Row of `get_users_with_timezone_info`: Users with comprehensive timezone and temporal information */
+export interface GetUsersWithTimezoneInfoItem {
+  id: number;
+  name: string;
+  email: string;
+  created_at: string | null;
+  created_at_user_tz: string | null;
+  updated_at: string | null;
+  updated_at_user_tz: string | null;
+  account_age: unknown | null;
+  account_age_days: string | null;
+  created_day_of_week: number | null;
+  created_hour: number | null;
+  formatted_created_at: string | null;
+}
+
+/** Row of `get_user_count_and_avg_age`: Get user count and average age - uses default GetUserCountAndAvgAgeItem struct */
+export interface GetUserCountAndAvgAgeItem {
+  count: number | null;
+  avg_age: string | null;
+}
+// AUTOMODEL_CONTENT_HASH: 05b6a35e9f6c56bc
@@ -0,0 +1,13 @@
+// This file was automatically generated by AutoModel. Do not edit manually.
+
+/** Row of `get_tickets_history`: Changes of a public.tickets row recorded in public.tickets_history within a time range, oldest first */
+export interface GetTicketsHistoryItem {
+  history_id: number;
+  id: number;
+  title: string;
+  priority: string;
+  state: string;
+  operation: string;
+  changed_at: string;
+}
+// AUTOMODEL_CONTENT_HASH: 3143423228293743
@@ -0,0 +1,24 @@
+// This file was automatically generated by AutoModel. Do not edit manually.
+
+/** Row of `get_tenant_orders_since`: Get the orders of a tenant created after the given time from the tenant's shard table */
+export interface GetTenantOrdersSinceItem {
+  id: number;
+  customer_email: string;
+  total_cents: number;
+  created_at: string;
+}
+
+/** Row of `get_tenant_order_totals`: Count the orders of a tenant and sum their totals, checking the sum fits in i64 */
+export interface GetTenantOrderTotalsItem {
+  order_count: number | null;
+  revenue_cents: number | null;
+}
+
+/** Row of `get_invoices_from`: Get the invoices of at least the given amount, amounts are in euros and rejected in any other currency */
+export interface GetInvoicesFromItem {
+  id: number;
+  customer: string;
+  amount: { amount: string; currency: string };
+  fee: unknown;
+}
+// AUTOMODEL_CONTENT_HASH: e0ab12d274e664bc
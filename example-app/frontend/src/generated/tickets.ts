@@ -0,0 +1,53 @@
+// This file was automatically generated by AutoModel. Do not edit manually.
+
+export type TicketsPriority = "low" | "normal" | "high";
+
+/** Row of `insert_ticket`: Insert a support ticket, priority is a generated enum and state is checked against its allowed values */
+export interface InsertTicketItem {
+  id: number;
+  priority: TicketsPriority;
+  state: string;
+}
+
+/** Row of `get_tickets_by_priorities`: Get the tickets with any of the given priorities */
+export interface GetTicketsByPrioritiesItem {
+  id: number;
+  title: string;
+  priority: TicketsPriority;
+  state: string;
+}
+
+/** Row of `search_tickets`: Search tickets by title with free-form input such as `printer -jam` or `"out of paper"` */
+export interface SearchTicketsItem {
+  id: number;
+  title: string;
+  rank: number | null;
+}
+
+/** Row of `find_tickets_matching`: Get the tickets matching a prepared tsquery, with their search vectors */
+export interface FindTicketsMatchingItem {
+  id: number;
+  title: string;
+  search: string | null;
+}
+
+/** Row of `count_tickets_by_state`: Count the tickets in each state, while the ticket_stats feature is rolled out */
+export interface CountTicketsByStateItem {
+  state: string;
+  tickets: number | null;
+}
+
+/** Row of `list_open_tickets`: List the open tickets, the most urgent first when built with ticket_stats */
+export interface ListOpenTicketsItem {
+  id: number;
+  title: string;
+  priority: string;
+}
+
+/** Row of `archive_ticket`: Delete a closed ticket, keeping a copy in the archive table */
+export interface ArchiveTicketItem {
+  id: number;
+  title: string;
+  archived_at: string;
+}
+// AUTOMODEL_CONTENT_HASH: ca3c1b1fd011173a
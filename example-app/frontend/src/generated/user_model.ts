@@ -0,0 +1,10 @@
+// This file was automatically generated by AutoModel. Do not edit manually.
+
+/** Row of `create_user`: Insert a new user and return as UserModel */
+export interface UserModel {
+  id: number;
+  name: string;
+  email: string;
+  age: number | null;
+}
+// AUTOMODEL_CONTENT_HASH: bebdc848b45e2bd5
@@ -0,0 +1,320 @@
+// This file was automatically generated by AutoModel. Do not edit manually.
+
+export type UserStatus = "active" | "inactive" | "suspended" | "pending";
+
+/** Row of `insert_user`: Insert a new user with all fields and return the created user */
+export interface InsertUserItem {
+  id: number;
+  name: string;
+  email: string;
+  age: number | null;
+  created_at: string | null;
+}
+
+/** Row of `get_all_users`: Get all public.users with all their fields */
+export interface GetAllUsersItem {
+  id: number;
+  name: string;
+  email: string;
+  age: number | null;
+  profile: unknown | null;
+  created_at: string | null;
+  updated_at: string | null;
+}
+
+/** Row of `find_user_by_email`: Find a user by their email address */
+export interface FindUserByEmailItem {
+  id: number;
+  name: string;
+  email: string;
+  age: number | null;
+  profile: unknown | null;
+  created_at: string | null;
+  updated_at: string | null;
+}
+
+/** Row of `update_user_profile`: Update a user's profile by their ID */
+export interface UpdateUserProfileItem {
+  id: number;
+  name: string;
+  email: string;
+  age: number | null;
+  profile: unknown | null;
+  updated_at: string | null;
+}
+
+/** Row of `find_users_by_name_and_age`: Find public.users by name pattern with optional minimum age filter (using conditional syntax) */
+export interface FindUsersByNameAndAgeItem {
+  id: number;
+  name: string;
+  email: string;
+  age: number | null;
+}
+
+/** Row of `get_recent_users`: Get public.users created after a specific timestamp - expects at least one user */
+export interface GetRecentUsersItem {
+  id: number;
+  name: string;
+  email: string;
+  age: number | null;
+  profile: unknown | null;
+  created_at: string | null;
+  updated_at: string | null;
+}
+
+/** Row of `get_active_users_by_age_range`: Get active public.users within an age range - must return at least one user or fails */
+export interface GetActiveUsersByAgeRangeItem {
+  id: number;
+  name: string;
+  email: string;
+  age: number | null;
+  profile: unknown | null;
+  created_at: string | null;
+}
+
+/** Row of `search_users_by_name_pattern`: Search public.users by name pattern - expects at least one match */
+export interface SearchUsersByNamePatternItem {
+  id: number;
+  name: string;
+  email: string;
+}
+
+/** Row of `search_users_advanced`: Advanced user search with multiple optional filters using conditional syntax */
+export interface SearchUsersAdvancedItem {
+  id: number;
+  name: string;
+  email: string;
+  age: number | null;
+  created_at: string | null;
+}
+
+/** Row of `get_users_by_status`: Get public.users by their status (enum parameter and enum output) */
+export interface GetUsersByStatusItem {
+  id: number;
+  name: string;
+  email: string;
+  status: UserStatus | null;
+}
+
+/** Row of `update_user_status`: Update user status and return the new status */
+export interface UpdateUserStatusItem {
+  id: number;
+  status: UserStatus | null;
+}
+
+/** Row of `update_user_fields`: Update user fields conditionally - only updates fields that are provided (not None) */
+export interface UpdateUserFieldsItem {
+  id: number;
+  name: string;
+  email: string;
+  age: number | null;
+  updated_at: string | null;
+}
+
+/** Row of `update_user_fields_diff`: Update user fields using diff-based conditional updates - compares old and new structs */
+export interface UpdateUserFieldsDiffItem {
+  id: number;
+  name: string;
+  email: string;
+  age: number | null;
+  updated_at: string | null;
+}
+
+/** Row of `insert_user_structured`: Insert a new user using structured parameters - all params passed as a single struct */
+export interface InsertUserStructuredItem {
+  id: number;
+  name: string;
+  email: string;
+  age: number | null;
+  created_at: string | null;
+}
+
+/** Row of `get_all_users_with_star`: Get all public.users using SELECT * to fetch all columns */
+export interface GetAllUsersWithStarItem {
+  id: number;
+  name: string;
+  email: string;
+  status: UserStatus | null;
+  profile: unknown | null;
+  settings: unknown | null;
+  is_active: boolean | null;
+  age: number | null;
+  created_at: string | null;
+  updated_at: string | null;
+  referrer_id: number | null;
+}
+
+/** Row of `get_user_by_id_with_star`: Get a single user by ID using SELECT * to fetch all columns */
+export interface GetUserByIdWithStarItem {
+  id: number;
+  name: string;
+  email: string;
+  status: UserStatus | null;
+  profile: unknown | null;
+  settings: unknown | null;
+  is_active: boolean | null;
+  age: number | null;
+  created_at: string | null;
+  updated_at: string | null;
+  referrer_id: number | null;
+}
+
+/** Row of `get_user_by_id_and_email`: Get a user by ID and email - generates GetUserByIdAndEmailParams struct and GetUserByIdAndEmailItem return struct */
+export interface GetUserByIdAndEmailItem {
+  id: number;
+  name: string;
+  email: string;
+}
+
+/** Row of `delete_user_by_id_and_email`: Delete user by ID and email - reuses GetUserByIdAndEmailParams struct */
+export interface DeleteUserByIdAndEmailItem {
+  id: number;
+  email: string;
+}
+
+/** Row of `update_user_contact_info`: Update user contact info - reuses GetUserByIdAndEmailItem return struct as params */
+export interface UpdateUserContactInfoItem {
+  id: number;
+  name: string;
+  email: string;
+}
+
+/** Row of `update_user_profile_diff`: Update user profile with conditional name/email - generates UpdateUserProfileDiffParams */
+export interface UpdateUserProfileDiffItem {
+  id: number;
+  name: string;
+  email: string;
+  profile: unknown | null;
+  updated_at: string | null;
+}
+
+/** Row of `update_user_metadata_diff`: Update user metadata - reuses UpdateUserProfileDiffParams struct */
+export interface UpdateUserMetadataDiffItem {
+  id: number;
+  name: string;
+  email: string;
+  updated_at: string | null;
+}
+
+/** Row of `get_user_summary`: Get user summary - generates UserSummary return struct with custom name */
+export interface UserSummary {
+  id: number;
+  name: string;
+  email: string;
+}
+
+/** Row of `get_user_details`: Get user details with age and created_at - generates UserDetails return struct */
+export interface UserDetails {
+  id: number;
+  name: string;
+  email: string;
+  age: number | null;
+  created_at: string | null;
+}
+
+/** Row of `get_user_simple`: Simple user lookup by ID with detailed info */
+export interface GetUserSimpleItem {
+  id: number;
+  name: string;
+  email: string;
+  created_at: string | null;
+}
+
+/** Row of `test_custom_derives`: Test custom derive traits */
+export interface UserWithCustomDerives {
+  id: number;
+  name: string;
+  email: string;
+  age: number | null;
+}
+
+/** Row of `get_user_id_only`: Test single column with explicit return_type - should generate UserId struct */
+export interface UserId {
+  id: number;
+}
+
+/** Row of `list_users_for_maintenance`: List users at least the given age, with chunked iteration helpers for maintenance jobs */
+export interface ListUsersForMaintenanceItem {
+  id: number;
+  email: string;
+}
+
+/** Row of `find_users_by_keyword_names`: Find users with parameters and columns named after Rust keywords */
+export interface FindUsersByKeywordNamesItem {
+  id: number;
+  type: string;
+  ref: string;
+}
+
+/** Row of `update_user_keyword_names_diff`: Diff-based update with parameters named after Rust keywords */
+export interface UpdateUserKeywordNamesDiffItem {
+  id: number;
+  type: string;
+  updated_at: string | null;
+}
+
+/** Row of `get_users_by_ids`: Get users by a list of ids which may contain thousands of entries, joined through a temp table */
+export interface GetUsersByIdsItem {
+  id: number;
+  name: string;
+  email: string;
+}
+
+/** Row of `get_public_user_profile`: Get a user for public listings, with the email address redacted by a row mapper */
+export interface GetPublicUserProfileItem {
+  id: number;
+  name: string;
+  email: string;
+}
+
+/** Row of `find_users_matching_text`: Find users whose name or email contains the text, optionally within five years of an age */
+export interface FindUsersMatchingTextItem {
+  id: number;
+  name: string;
+  email: string;
+  age: number | null;
+}
+
+/** Row of `get_or_create_user_by_email`: Get the user with the email, creating it when no such user exists */
+export interface GetOrCreateUserByEmailItem {
+  id: number;
+  name: string;
+  email: string;
+  age: number | null;
+}
+
+/** Row of `find_users_with_similar_name`: Find users whose name is within an edit distance of the given name */
+export interface FindUsersWithSimilarNameItem {
+  id: number;
+  name: string;
+  distance: number | null;
+}
+
+/** Row of `find_users_by_theme`: Find users whose profile preferences use the given theme */
+export interface FindUsersByThemeItem {
+  id: number;
+  name: string;
+}
+
+/** Row of `get_user_profile_cards`: Get users with their profile fields inlined, for serializing as flat JSON objects */
+export interface GetUserProfileCardsItem {
+  id: number;
+  name: string;
+  [key: string]: unknown;
+}
+
+/** Row of `filter_users`: List public.users matching the given filters, e.g. by email or age */
+export interface FilterUsersItem {
+  id: number;
+  name: string;
+  email: string;
+  age: number | null;
+}
+
+/** Row of `update_user_ages`: Update the age and status of a user, update_user_ages_bulk updates many users at once */
+export interface UpdateUserAgesItem {
+  id: number;
+  age: number | null;
+  status: UserStatus | null;
+}
+// AUTOMODEL_CONTENT_HASH: f9957b0d93300e32
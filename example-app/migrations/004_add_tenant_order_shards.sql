@@ -0,0 +1,15 @@
+-- Migration: Add per-tenant order tables for the shard_by example
+-- Each tenant gets its own orders_{tenant} table with the same structure
+-- orders_0 is the canonical shard which queries with shard_by are analyzed against
+CREATE TABLE IF NOT EXISTS public.orders_0 (
+    id BIGSERIAL PRIMARY KEY,
+    customer_email TEXT NOT NULL,
+    total_cents BIGINT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS idx_orders_0_created_at ON public.orders_0(created_at);
+-- Shard of the "acme" tenant
+CREATE TABLE IF NOT EXISTS public.orders_acme (LIKE public.orders_0 INCLUDING ALL);
+INSERT INTO public.orders_acme (customer_email, total_cents)
+VALUES ('buyer@acme.example', 4200),
+    ('another@acme.example', 1999);
@@ -0,0 +1,9 @@
+-- Migration: Add a support tickets table for the check_values example
+-- priority and state are legacy text columns restricted by CHECK constraints instead of enum types
+CREATE TABLE IF NOT EXISTS public.tickets (
+    id SERIAL PRIMARY KEY,
+    title TEXT NOT NULL,
+    priority TEXT NOT NULL DEFAULT 'normal' CHECK (priority IN ('low', 'normal', 'high')),
+    state VARCHAR(16) NOT NULL DEFAULT 'open' CHECK (state IN ('open', 'waiting', 'closed')),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,5 @@
+-- Migration: Add full-text search over ticket titles
+ALTER TABLE public.tickets
+    ADD COLUMN IF NOT EXISTS search TSVECTOR GENERATED ALWAYS AS (to_tsvector('english', title)) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_tickets_search ON public.tickets USING GIN (search);
@@ -0,0 +1,4 @@
+-- Migration: Install fuzzystrmatch into a dedicated extensions schema
+CREATE SCHEMA IF NOT EXISTS extensions;
+
+CREATE EXTENSION IF NOT EXISTS fuzzystrmatch SCHEMA extensions;
@@ -0,0 +1,27 @@
+-- Migration: Record every change of a ticket in an audit history table
+-- history_queries generates get_tickets_history from it
+CREATE TABLE IF NOT EXISTS public.tickets_history (
+    history_id BIGSERIAL PRIMARY KEY,
+    id INTEGER NOT NULL,
+    title TEXT NOT NULL,
+    priority TEXT NOT NULL,
+    state VARCHAR(16) NOT NULL,
+    operation TEXT NOT NULL,
+    changed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_tickets_history_id_changed_at
+    ON public.tickets_history (id, changed_at);
+
+CREATE OR REPLACE FUNCTION public.record_ticket_history() RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO public.tickets_history (id, title, priority, state, operation)
+    VALUES (NEW.id, NEW.title, NEW.priority, NEW.state, TG_OP);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS tickets_history ON public.tickets;
+CREATE TRIGGER tickets_history
+    AFTER INSERT OR UPDATE ON public.tickets
+    FOR EACH ROW EXECUTE FUNCTION public.record_ticket_history();
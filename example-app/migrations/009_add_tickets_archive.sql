@@ -0,0 +1,12 @@
+-- Migration: Keep a copy of every deleted ticket for compliance
+-- archive_to moves the rows deleted by archive_ticket into this table, which starts with the
+-- columns of tickets in the same order
+CREATE TABLE IF NOT EXISTS public.tickets_archive (
+    id INTEGER NOT NULL,
+    title TEXT NOT NULL,
+    priority TEXT NOT NULL,
+    state VARCHAR(16) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    search TSVECTOR,
+    archived_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,9 @@
+-- Migration: Add an invoices table for the currency_columns example
+-- amount is a NUMERIC amount in euros, fee uses PostgreSQL's locale-dependent money type
+CREATE TABLE IF NOT EXISTS public.invoices (
+    id SERIAL PRIMARY KEY,
+    customer TEXT NOT NULL,
+    amount NUMERIC(12, 2) NOT NULL,
+    fee MONEY NOT NULL DEFAULT 0,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,9 @@
+-- Migration: Add a legacy accounts table for the char_flags example
+-- Flags are stored as Y/N codes in CHAR(1) columns, the account kind as a "char" code
+CREATE TABLE IF NOT EXISTS public.legacy_accounts (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    is_active CHAR(1) NOT NULL DEFAULT 'Y',
+    is_locked CHAR(1),
+    kind "char" NOT NULL DEFAULT 'P'
+);
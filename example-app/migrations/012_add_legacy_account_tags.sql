@@ -0,0 +1,3 @@
+-- Migration: Add tags to the legacy accounts for the array element types example
+-- Tags are stored as text, the generated code converts them to an enum
+ALTER TABLE public.legacy_accounts ADD COLUMN IF NOT EXISTS tags TEXT [] NOT NULL DEFAULT '{}';
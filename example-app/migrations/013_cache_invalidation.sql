@@ -0,0 +1,17 @@
+-- This file was automatically generated by AutoModel. Do not edit manually.
+-- Triggers notifying automodel_cache_invalidation when the tables of queries with cache_invalidation change
+-- Tables: users
+
+CREATE OR REPLACE FUNCTION public.automodel_cache_invalidation() RETURNS trigger
+LANGUAGE plpgsql AS $$
+BEGIN
+    PERFORM pg_notify('automodel_cache_invalidation', TG_ARGV[0]);
+    RETURN NULL;
+END;
+$$;
+
+-- users::get_all_user_statuses
+DROP TRIGGER IF EXISTS automodel_cache_invalidation ON public."users";
+CREATE TRIGGER automodel_cache_invalidation
+    AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON public."users"
+    FOR EACH STATEMENT EXECUTE FUNCTION public.automodel_cache_invalidation('users');
@@ -0,0 +1,11 @@
+-- @automodel
+--    description: Insert an account of a legacy schema storing its flags as Y/N and its kind as a "char" code
+--    expect: exactly_one
+--    char_flags:
+--      is_active: bool("Y", "N")
+--      kind: crate::models::AccountKind("P" = Personal, "B" = Business)
+-- @end
+
+INSERT INTO public.legacy_accounts (name, is_active, kind)
+VALUES (#{name}, #{is_active}, #{kind})
+RETURNING id
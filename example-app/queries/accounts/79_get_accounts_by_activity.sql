@@ -0,0 +1,14 @@
+-- @automodel
+--    description: Get the accounts which are active or not, with their flags converted to bool and their kind to an enum
+--    expect: multiple
+--    char_flags:
+--      active: bool("Y", "N")
+--      is_active: bool("Y", "N")
+--      is_locked: bool("Y", "N")
+--      kind: crate::models::AccountKind("P" = Personal, "B" = Business)
+-- @end
+
+SELECT id, name, is_active, is_locked, kind
+FROM public.legacy_accounts
+WHERE is_active = #{active}
+ORDER BY id
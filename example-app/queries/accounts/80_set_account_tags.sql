@@ -0,0 +1,11 @@
+-- @automodel
+--    description: Replace the tags of an account, stored as text and converted to an enum element by element
+--    expect: exactly_one
+--    types:
+--      legacy_accounts.tags[]: crate::models::AccountTag
+-- @end
+
+UPDATE public.legacy_accounts
+SET tags = #{tags}
+WHERE id = #{id}
+RETURNING id, tags
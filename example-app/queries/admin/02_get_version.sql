@@ -1,6 +1,7 @@
 -- @automodel
 --    description: Get PostgreSQL version
 --    expect: exactly_one
+--    blocking: true
 -- @end
 
 SELECT version() as pg_version
@@ -0,0 +1,7 @@
+-- @automodel
+--    description: Sleep on the server for a number of seconds, returning when it finished
+--    expect: exactly_one
+--    cancellable: true
+-- @end
+
+SELECT clock_timestamp() AS finished_at FROM pg_sleep(#{seconds})
@@ -0,0 +1,7 @@
+-- @automodel
+--    description: Refresh the planner statistics of the users and posts tables
+--    prepare: false
+-- @end
+
+ANALYZE public.users;
+ANALYZE public.posts
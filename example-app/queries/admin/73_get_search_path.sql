@@ -0,0 +1,7 @@
+-- @automodel
+--    description: Get the schema search path of the session
+--    expect: exactly_one
+--    prepare: false
+-- @end
+
+SHOW search_path
@@ -1,6 +1,7 @@
 -- @automodel
 --    description: Complex CTE query combining recent public.users with aggregate statistics
 --    expect: multiple
+--    baseline: { total_cost: 93.49, rows: 394 }
 -- @end
 
 WITH recent_users AS (
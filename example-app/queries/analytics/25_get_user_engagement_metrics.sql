@@ -2,6 +2,7 @@
 --    description: Complex multi-CTE query calculating user engagement metrics with temporal analysis
 --    expect: multiple
 -- @end
+-- @define ACTIVITY_WINDOW '3 months'
 
 WITH user_activity AS (
   SELECT 
@@ -16,9 +17,9 @@ WITH user_activity AS (
     AVG(EXTRACT(EPOCH FROM (p.published_at - p.created_at))::float8/3600) as avg_publish_delay_hours
   FROM public.users u
   LEFT JOIN public.posts p ON u.id = p.author_id 
-    AND p.created_at >= DATE_TRUNC('month', NOW()) - INTERVAL '3 months'
+    AND p.created_at >= DATE_TRUNC('month', NOW()) - INTERVAL @@ACTIVITY_WINDOW@@
   LEFT JOIN public.comments c ON u.id = c.author_id 
-    AND c.created_at >= DATE_TRUNC('month', NOW()) - INTERVAL '3 months'
+    AND c.created_at >= DATE_TRUNC('month', NOW()) - INTERVAL @@ACTIVITY_WINDOW@@
   GROUP BY u.id, u.name, u.email, u.created_at
 ),
 engagement_scores AS (
@@ -1,6 +1,8 @@
 -- @automodel
 --    description: Time series analysis of user registrations with age demographics
 --    expect: multiple
+--    numeric_as:
+--      avg_age: f64
 -- @end
 
 WITH time_series AS (
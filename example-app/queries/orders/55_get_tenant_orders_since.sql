@@ -0,0 +1,11 @@
+-- @automodel
+--    description: Get the orders of a tenant created after the given time from the tenant's shard table
+--    expect: multiple
+--    shard_by: tenant
+--    canonical_shard: "0"
+-- @end
+
+SELECT id, customer_email, total_cents, created_at
+FROM public.orders_${shard}
+WHERE created_at > #{since}
+ORDER BY created_at DESC
@@ -0,0 +1,11 @@
+-- @automodel
+--    description: Count the orders of a tenant and sum their totals, checking the sum fits in i64
+--    expect: exactly_one
+--    shard_by: tenant
+--    canonical_shard: "0"
+--    numeric_as:
+--      revenue_cents: i64
+-- @end
+
+SELECT COUNT(*) AS order_count, SUM(total_cents) AS revenue_cents
+FROM public.orders_${shard}
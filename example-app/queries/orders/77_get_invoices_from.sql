@@ -0,0 +1,12 @@
+-- @automodel
+--    description: Get the invoices of at least the given amount, amounts are in euros and rejected in any other currency
+--    expect: multiple
+--    currency_columns:
+--      amount: EUR
+--      min_amount: EUR
+-- @end
+
+SELECT id, customer, amount, fee
+FROM public.invoices
+WHERE amount >= #{min_amount}
+ORDER BY id
@@ -0,0 +1,11 @@
+-- @automodel
+--    description: Insert a support ticket, priority is a generated enum and state is checked against its allowed values
+--    expect: exactly_one
+--    check_values:
+--      tickets.priority: enum
+--      tickets.state: const
+-- @end
+
+INSERT INTO public.tickets (title, priority, state)
+VALUES (#{title}, #{priority}, #{state})
+RETURNING id, priority, state
@@ -0,0 +1,11 @@
+-- @automodel
+--    description: Get the tickets with any of the given priorities
+--    expect: multiple
+--    check_values:
+--      tickets.priority: enum
+-- @end
+
+SELECT id, title, priority, state
+FROM public.tickets
+WHERE priority = ANY(#{priority})
+ORDER BY id
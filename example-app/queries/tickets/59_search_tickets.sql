@@ -0,0 +1,9 @@
+-- @automodel
+--    description: Search tickets by title with free-form input such as `printer -jam` or `"out of paper"`
+--    expect: multiple
+-- @end
+
+SELECT id, title, ts_rank(search, websearch_to_tsquery('english', #{terms})) AS rank
+FROM public.tickets
+WHERE search @@ websearch_to_tsquery('english', #{terms})
+ORDER BY rank DESC, id
@@ -0,0 +1,9 @@
+-- @automodel
+--    description: Get the tickets matching a prepared tsquery, with their search vectors
+--    expect: multiple
+-- @end
+
+SELECT id, title, search
+FROM public.tickets
+WHERE search @@ #{terms}
+ORDER BY id
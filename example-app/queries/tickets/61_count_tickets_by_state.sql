@@ -0,0 +1,11 @@
+-- @automodel
+--    description: Count the tickets in each state, while the ticket_stats feature is rolled out
+--    expect: multiple
+--    feature_flag: ticket_stats
+--    feature_flag_cfg: true
+-- @end
+
+SELECT state, COUNT(*) AS tickets
+FROM public.tickets
+GROUP BY state
+ORDER BY state
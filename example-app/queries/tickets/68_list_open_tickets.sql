@@ -0,0 +1,14 @@
+-- @automodel
+--    description: List the open tickets, the most urgent first when built with ticket_stats
+--    expect: multiple
+-- @end
+
+SELECT id, title, priority
+FROM public.tickets
+WHERE state = 'open'
+#[cfg(feature = "ticket_stats")]
+ORDER BY CASE priority WHEN 'high' THEN 0 WHEN 'normal' THEN 1 ELSE 2 END, id
+#[end]
+#[cfg(not(feature = "ticket_stats"))]
+ORDER BY id
+#[end]
@@ -0,0 +1,9 @@
+-- @automodel
+--    description: Delete a closed ticket, keeping a copy in the archive table
+--    expect: possible_one
+--    archive_to: public.tickets_archive
+-- @end
+
+DELETE FROM public.tickets
+WHERE id = #{id} AND state = 'closed'
+RETURNING id, title, archived_at
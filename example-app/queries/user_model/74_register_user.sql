@@ -0,0 +1,9 @@
+-- @automodel
+--    description: Insert the user of a registration
+--    expect: exactly_one
+--    unit_of_work: registration
+-- @end
+
+INSERT INTO public.users (name, email)
+VALUES (#{name}, #{email})
+RETURNING id AS user_id
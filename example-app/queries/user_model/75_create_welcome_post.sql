@@ -0,0 +1,9 @@
+-- @automodel
+--    description: Insert the welcome post of a registered user
+--    expect: exactly_one
+--    unit_of_work: registration
+-- @end
+
+INSERT INTO public.posts (author_id, title, content)
+VALUES (#{user_id}, #{title}, #{content?})
+RETURNING id AS post_id
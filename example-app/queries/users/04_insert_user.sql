@@ -1,8 +1,10 @@
 -- @automodel
 --    description: Insert a new user with all fields and return the created user
 --    expect: exactly_one
+--    id_only: true
+--    retrying: true
 --    types:
---      profile: "crate::models::UserProfile"
+--      profile: "UserProfile"
 --    telemetry:
 --      level: trace
 --      include_params: [name, email, age]
@@ -1,9 +1,12 @@
 -- @automodel
 --    description: Get all public.users with all their fields
 --    expect: multiple
+--    proto: true
 --    types:
---      public.users.profile: "crate::models::UserProfile"
+--      public.users.profile: "UserProfile"
 --    ensure_indexes: true
+--    cardinality_drift: true
+--    allow: [AM001]            # Listing every user is expected to scan the table
 -- @end
 
 SELECT id, name, email, age, profile, created_at, updated_at 
@@ -2,11 +2,16 @@
 --    description: Find a user by their email address
 --    expect: possible_one
 --    types:
---      profile: "crate::models::UserProfile"
+--      public.users.profile: "UserProfile"
 --    telemetry:
 --      include_params: [email]
 --      include_sql: false
 --    ensure_indexes: false
+--    sensitivity:
+--      email: pii
+--    redact_debug: true
+--    assumes:
+--      users.email: unique not_null varchar
 -- @end
 
 SELECT id, name, email, age, profile, created_at, updated_at 
@@ -2,8 +2,8 @@
 --    description: Update a user's profile by their ID
 --    expect: exactly_one
 --    types:
---      public.users.profile: "crate::models::UserProfile"
---      profile: "crate::models::UserProfile"
+--      public.users.profile: "UserProfile"
+--      profile: "UserProfile"
 --    telemetry:
 --      include_params: []
 -- @end
@@ -2,7 +2,7 @@
 --    description: Get public.users created after a specific timestamp - expects at least one user
 --    expect: at_least_one
 --    types:
---      public.users.profile: "crate::models::UserProfile"
+--      public.users.profile: "UserProfile"
 -- @end
 
 SELECT id, name, email, age, profile, created_at, updated_at 
@@ -2,7 +2,7 @@
 --    description: Get active public.users within an age range - must return at least one user or fails
 --    expect: at_least_one
 --    types:
---      public.users.profile: "crate::models::UserProfile"
+--      public.users.profile: "UserProfile"
 -- @end
 
 SELECT id, name, email, age, profile, created_at 
@@ -1,6 +1,12 @@
 -- @automodel
 --    description: Search public.users by name pattern - expects at least one match
 --    expect: at_least_one
+--    plan_sampling: true
+--    slow_query_ms: 250
+--    telemetry:
+--      include_params: [pattern]
+--    analysis_params:
+--      pattern: "john%"
 -- @end
 
 SELECT id, name, email 
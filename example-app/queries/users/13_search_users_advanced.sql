@@ -1,6 +1,8 @@
 -- @automodel
 --    description: Advanced user search with multiple optional filters using conditional syntax
 --    expect: multiple
+--    plan_sampling: true
+--    max_variant_combinations: 4
 -- @end
 
 SELECT id, name, email, age, created_at 
@@ -1,6 +1,7 @@
 -- @automodel
 --    description: Get public.users by their status (enum parameter and enum output)
 --    expect: multiple
+--    proto: true
 -- @end
 
 SELECT id, name, email, status 
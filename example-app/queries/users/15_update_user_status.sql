@@ -1,6 +1,10 @@
 -- @automodel
 --    description: Update user status and return the new status
 --    expect: exactly_one
+--    isolation_sensitive: true
+--    analysis_params:
+--      new_status: active
+--      user_id: 1
 -- @end
 
 UPDATE public.users 
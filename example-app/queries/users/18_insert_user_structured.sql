@@ -2,6 +2,9 @@
 --    description: Insert a new user using structured parameters - all params passed as a single struct
 --    expect: exactly_one
 --    parameters_type: true
+--    batch:
+--      max_size: 50
+--      max_delay_ms: 5
 -- @end
 
 INSERT INTO public.users (name, email, age) 
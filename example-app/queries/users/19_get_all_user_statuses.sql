@@ -1,6 +1,7 @@
 -- @automodel
 --    description: Get all possible user statuses currently in use
 --    expect: multiple
+--    cache_invalidation: true
 -- @end
 
 SELECT DISTINCT status 
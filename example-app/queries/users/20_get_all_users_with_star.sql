@@ -2,7 +2,7 @@
 --    description: Get all public.users using SELECT * to fetch all columns
 --    expect: multiple
 --    types:
---      public.users.profile: "crate::models::UserProfile"
+--      public.users.profile: "UserProfile"
 -- @end
 
 SELECT * 
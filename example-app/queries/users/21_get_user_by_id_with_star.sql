@@ -2,7 +2,7 @@
 --    description: Get a single user by ID using SELECT * to fetch all columns
 --    expect: possible_one
 --    types:
---      public.users.profile: "crate::models::UserProfile"
+--      public.users.profile: "UserProfile"
 -- @end
 
 SELECT * 
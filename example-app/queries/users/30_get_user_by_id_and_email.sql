@@ -2,6 +2,7 @@
 --    description: Get a user by ID and email - generates GetUserByIdAndEmailParams struct and GetUserByIdAndEmailItem return struct
 --    expect: possible_one
 --    parameters_type: true
+--    dynamic: true
 -- @end
 
 SELECT id, name, email 
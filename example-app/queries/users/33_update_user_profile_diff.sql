@@ -3,8 +3,8 @@
 --    expect: exactly_one
 --    conditions_type: true
 --    types:
---      public.users.profile: "crate::models::UserProfile"
---      profile: "crate::models::UserProfile"
+--      public.users.profile: "UserProfile"
+--      profile: "UserProfile"
 -- @end
 
 UPDATE public.users 
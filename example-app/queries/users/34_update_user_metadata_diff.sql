@@ -3,7 +3,7 @@
 --    expect: exactly_one
 --    conditions_type: UpdateUserProfileDiffParams
 --    types:
---      profile: "crate::models::UserProfile"
+--      profile: "UserProfile"
 -- @end
 
 UPDATE public.users 
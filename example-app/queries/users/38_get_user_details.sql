@@ -2,6 +2,7 @@
 --    description: Get user details with age and created_at - generates UserDetails return struct
 --    expect: exactly_one
 --    return_type: UserDetails
+--    not_found_message: "user {user_id} missing"
 -- @end
 
 SELECT id, name, email, age, created_at 
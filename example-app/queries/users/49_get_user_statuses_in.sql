@@ -0,0 +1,8 @@
+-- @automodel
+--    description: Get the distinct statuses of users matching any of the given statuses (enum array parameter and enum array output)
+--    expect: exactly_one
+-- @end
+
+SELECT ARRAY_AGG(DISTINCT status ORDER BY status) AS statuses
+FROM public.users
+WHERE status = ANY(#{statuses})
@@ -0,0 +1,9 @@
+-- @automodel
+--    description: Get the name and email of a user as a tuple
+--    expect: possible_one
+--    return_shape: tuple
+-- @end
+
+SELECT name, email
+FROM public.users
+WHERE id = #{id}
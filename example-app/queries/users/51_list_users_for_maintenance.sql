@@ -0,0 +1,11 @@
+-- @automodel
+--    description: List users at least the given age, with chunked iteration helpers for maintenance jobs
+--    expect: multiple
+--    iterate:
+--      chunk_size: 100
+--      key: id
+-- @end
+
+SELECT id, email
+FROM public.users
+WHERE age >= #{min_age}
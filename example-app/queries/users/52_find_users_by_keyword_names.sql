@@ -0,0 +1,14 @@
+-- @automodel
+--    description: Find users with parameters and columns named after Rust keywords
+--    expect: multiple
+--    dynamic: true
+--    telemetry:
+--      level: trace
+--      include_params: [match]
+-- @end
+
+SELECT id, name AS type, email AS ref
+FROM public.users
+WHERE name = #{type}
+  AND id > #{match}
+#[AND email = #{ref?}]
@@ -0,0 +1,12 @@
+-- @automodel
+--    description: Diff-based update with parameters named after Rust keywords
+--    expect: exactly_one
+--    conditions_type: true
+-- @end
+
+UPDATE public.users
+SET updated_at = NOW()
+#[, name = #{type?}]
+#[, email = #{ref?}]
+WHERE id = #{match}
+RETURNING id, name AS type, updated_at
@@ -0,0 +1,11 @@
+-- @automodel
+--    description: Get users by a list of ids which may contain thousands of entries, joined through a temp table
+--    expect: multiple
+--    large_list_strategy: temp_table
+--    requires_session: true
+-- @end
+
+SELECT id, name, email
+FROM public.users
+WHERE id = ANY(#{ids})
+ORDER BY id
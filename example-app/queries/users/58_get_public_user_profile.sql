@@ -0,0 +1,9 @@
+-- @automodel
+--    description: Get a user for public listings, with the email address redacted by a row mapper
+--    expect: possible_one
+--    map_row: crate::mappers::redact_email
+-- @end
+
+SELECT id, name, email
+FROM public.users
+WHERE id = #{id}
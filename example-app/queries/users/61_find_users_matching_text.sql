@@ -0,0 +1,10 @@
+-- @automodel
+--    description: Find users whose name or email contains the text, optionally within five years of an age
+--    expect: multiple
+-- @end
+
+SELECT id, name, email, age
+FROM public.users
+WHERE (name ILIKE '%' || #{text} || '%' OR email ILIKE '%' || #{text} || '%')
+#[AND age BETWEEN #{around_age?} - 5 AND #{around_age?} + 5]
+ORDER BY id
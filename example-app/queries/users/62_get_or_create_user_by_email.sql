@@ -0,0 +1,9 @@
+-- @automodel
+--    description: Get the user with the email, creating it when no such user exists
+--    expect: exactly_one_or_insert
+--    insert: insert_user
+-- @end
+
+SELECT id, name, email, age
+FROM public.users
+WHERE email = #{email}
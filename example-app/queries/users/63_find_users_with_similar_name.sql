@@ -0,0 +1,10 @@
+-- @automodel
+--    description: Find users whose name is within an edit distance of the given name
+--    expect: multiple
+--    search_path: [public, extensions]
+-- @end
+
+SELECT id, name, levenshtein(name, #{name}) AS distance
+FROM users
+WHERE levenshtein(name, #{name}) <= #{max_distance}
+ORDER BY distance, id
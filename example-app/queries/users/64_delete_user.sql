@@ -0,0 +1,7 @@
+-- @automodel
+--    description: Delete a user, with a preview of the posts, comments and users referencing it
+--    cascade_preview: true
+-- @end
+
+DELETE FROM public.users
+WHERE id = #{id}
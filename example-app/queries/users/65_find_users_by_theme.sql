@@ -0,0 +1,11 @@
+-- @automodel
+--    description: Find users whose profile preferences use the given theme
+--    expect: multiple
+--    param_types:
+--      theme: text
+-- @end
+
+SELECT id, name
+FROM public.users
+WHERE profile @> jsonb_build_object('preferences', jsonb_build_object('theme', #{theme}))
+ORDER BY id
@@ -0,0 +1,14 @@
+-- @automodel
+--    description: Get users with their profile fields inlined, for serializing as flat JSON objects
+--    expect: multiple
+--    types:
+--      public.users.profile: "UserProfile"
+--    return_type_derives: [serde::Serialize]
+--    flatten: [profile]
+-- @end
+
+SELECT id, name, profile
+FROM public.users
+WHERE profile IS NOT NULL
+ORDER BY id
+LIMIT #{limit}
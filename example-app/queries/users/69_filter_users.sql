@@ -0,0 +1,15 @@
+-- @automodel
+--    description: List public.users matching the given filters, e.g. by email or age
+--    expect: multiple
+--    filter_type: UserFilter
+-- @end
+
+SELECT id, name, email, age
+FROM public.users
+WHERE email = #{email}
+  AND name ILIKE #{name_pattern}
+  AND age >= #{min_age}
+  AND age <= #{max_age}
+  AND status = ANY(#{statuses})
+ORDER BY id
+LIMIT #{limit}
@@ -0,0 +1,9 @@
+-- @automodel
+--    description: Update the age and status of a user, update_user_ages_bulk updates many users at once
+--    expect: possible_one
+--    bulk_update: true
+-- @end
+UPDATE public.users
+SET age = #{age}, status = #{status}, updated_at = NOW()
+WHERE id = #{id}
+RETURNING id, age, status
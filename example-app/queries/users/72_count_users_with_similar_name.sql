@@ -0,0 +1,10 @@
+-- @automodel
+--    description: Count users whose name is within an edit distance of the given name
+--    expect: exactly_one
+--    search_path: [public, extensions]
+--    search_path_at_runtime: true
+-- @end
+
+SELECT count(*) AS total
+FROM users
+WHERE levenshtein(name, #{name}) <= #{max_distance}
@@ -0,0 +1,9 @@
+-- @automodel
+--    description: Get the name of a user, cached until a notification on user_events names the user
+--    expect: possible_one
+--    cache_invalidate_on:
+--      channel: user_events
+--      key: id
+-- @end
+
+SELECT name FROM public.users WHERE id = #{id}
@@ -0,0 +1,252 @@
+// This file was automatically generated by AutoModel. Do not edit manually.
+
+#[allow(unused_imports)]
+pub use super::{classify_error, DbErrorKind};
+use sqlx::Row;
+
+/// Tables read or modified by the queries of this module
+pub const TABLES: &[&str] = &["legacy_accounts"];
+
+/// Prepare every query of this module on the connection, e.g. in `after_connect` of the pool
+/// The statements stay cached on the connection; failed queries no longer match the schema
+pub async fn warmup(executor: &mut sqlx::PgConnection) -> Vec<super::WarmupStatus> {
+    const QUERIES: &[(&str, &[&str], &[&str])] = &[
+        (
+            "insert_account",
+            &[r"INSERT INTO public.legacy_accounts (name, is_active, kind)
+        VALUES ($1, $2, $3)
+        RETURNING id"],
+            &["id"],
+        ),
+        (
+            "get_accounts_by_activity",
+            &[r"SELECT id, name, is_active, is_locked, kind
+        FROM public.legacy_accounts
+        WHERE is_active = $1
+        ORDER BY id"],
+            &["id", "name", "is_active", "is_locked", "kind"],
+        ),
+        (
+            "set_account_tags",
+            &[r"UPDATE public.legacy_accounts
+        SET tags = $1
+        WHERE id = $2
+        RETURNING id, tags"],
+            &["id", "tags"],
+        ),
+    ];
+    let mut report = Vec::with_capacity(QUERIES.len());
+    for &(query, variants, columns) in QUERIES {
+        let result = super::warmup_query(&mut *executor, variants, columns).await;
+        report.push(super::WarmupStatus { query, result });
+    }
+    report
+}
+
+/// Constraint violations specific to this query
+#[derive(Debug)]
+pub enum InsertAccountConstraints {
+    /// Constraint: legacy_accounts_pkey on table legacy_accounts
+    LegacyAccountsPkey,
+    /// Constraint: legacy_accounts_id_not_null on table legacy_accounts
+    LegacyAccountsIdNotNull,
+    /// Constraint: legacy_accounts_name_not_null on table legacy_accounts
+    LegacyAccountsNameNotNull,
+    /// Constraint: legacy_accounts_is_active_not_null on table legacy_accounts
+    LegacyAccountsIsActiveNotNull,
+    /// Constraint: legacy_accounts_kind_not_null on table legacy_accounts
+    LegacyAccountsKindNotNull,
+    /// Constraint: legacy_accounts_tags_not_null on table legacy_accounts
+    LegacyAccountsTagsNotNull,
+}
+
+impl TryFrom<super::ErrorConstraintInfo> for InsertAccountConstraints {
+    type Error = ();
+
+    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
+        match info.constraint_name.as_str() {
+            "legacy_accounts_pkey" => Ok(Self::LegacyAccountsPkey),
+            "legacy_accounts_id_not_null" => Ok(Self::LegacyAccountsIdNotNull),
+            "legacy_accounts_name_not_null" => Ok(Self::LegacyAccountsNameNotNull),
+            "legacy_accounts_is_active_not_null" => Ok(Self::LegacyAccountsIsActiveNotNull),
+            "legacy_accounts_kind_not_null" => Ok(Self::LegacyAccountsKindNotNull),
+            "legacy_accounts_tags_not_null" => Ok(Self::LegacyAccountsTagsNotNull),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Insert an account of a legacy schema storing its flags as Y/N and its kind as a "char" code
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "INSERT INTO public.legacy_accounts (name, is_active, kind)\nVALUES (#{name}, #{is_active}, #{kind})\nRETURNING id"
+    )
+)]
+pub async fn insert_account(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    name: String,
+    is_active: bool,
+    kind: crate::models::AccountKind,
+) -> Result<i32, super::Error<InsertAccountConstraints>> {
+    let is_active = super::char_flags::encode(is_active, &[("Y", true), ("N", false)]);
+    let kind = super::char_flags::encode_byte(
+        kind,
+        &[
+            ("P", crate::models::AccountKind::Personal),
+            ("B", crate::models::AccountKind::Business),
+        ],
+    );
+    let query = sqlx::query(
+        r"INSERT INTO public.legacy_accounts (name, is_active, kind)
+        VALUES ($1, $2, $3)
+        RETURNING id",
+    );
+    let query = query.bind(&name);
+    let query = query.bind(is_active);
+    let query = query.bind(kind);
+    let row = query.fetch_one(executor).await?;
+    Ok(row.try_get::<i32, _>("id")?)
+}
+
+#[derive(Debug, Clone)]
+pub struct GetAccountsByActivityItem {
+    pub id: i32,
+    pub name: String,
+    pub is_active: bool,
+    pub is_locked: Option<bool>,
+    pub kind: crate::models::AccountKind,
+}
+
+/// Get the accounts which are active or not, with their flags converted to bool and their kind to an enum
+///
+/// Tables: `legacy_accounts`
+///
+/// Query Plan:
+/// Index Scan using legacy_accounts_pkey on legacy_accounts
+///   Filter: (is_active = 'dummy'::bpchar)
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "SELECT id, name, is_active, is_locked, kind\nFROM public.legacy_accounts\nWHERE is_active = #{active}\nORDER BY id"
+    )
+)]
+pub async fn get_accounts_by_activity(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    active: bool,
+) -> Result<Vec<GetAccountsByActivityItem>, super::ErrorReadOnly> {
+    let active = super::char_flags::encode(active, &[("Y", true), ("N", false)]);
+    let query = sqlx::query(
+        r"SELECT id, name, is_active, is_locked, kind
+        FROM public.legacy_accounts
+        WHERE is_active = $1
+        ORDER BY id",
+    );
+    let query = query.bind(active);
+    let rows = query.fetch_all(executor).await?;
+    let result: Result<Vec<_>, sqlx::Error> = rows
+        .iter()
+        .map(|row| {
+            Ok(GetAccountsByActivityItem {
+                id: row.try_get::<i32, _>("id")?,
+                name: row.try_get::<String, _>("name")?,
+                is_active: super::char_flags::decode(
+                    "is_active",
+                    row.try_get::<String, _>("is_active")?,
+                    &[("Y", true), ("N", false)],
+                )?,
+                is_locked: row
+                    .try_get::<Option<String>, _>("is_locked")?
+                    .map(|code| {
+                        super::char_flags::decode("is_locked", code, &[("Y", true), ("N", false)])
+                    })
+                    .transpose()?,
+                kind: super::char_flags::decode(
+                    "kind",
+                    row.try_get::<i8, _>("kind")?,
+                    &[
+                        ("P", crate::models::AccountKind::Personal),
+                        ("B", crate::models::AccountKind::Business),
+                    ],
+                )?,
+            })
+        })
+        .collect();
+    result.map_err(Into::into)
+}
+
+/// Constraint violations specific to this query
+#[derive(Debug)]
+pub enum SetAccountTagsConstraints {
+    /// Constraint: legacy_accounts_pkey on table legacy_accounts
+    LegacyAccountsPkey,
+    /// Constraint: legacy_accounts_id_not_null on table legacy_accounts
+    LegacyAccountsIdNotNull,
+    /// Constraint: legacy_accounts_name_not_null on table legacy_accounts
+    LegacyAccountsNameNotNull,
+    /// Constraint: legacy_accounts_is_active_not_null on table legacy_accounts
+    LegacyAccountsIsActiveNotNull,
+    /// Constraint: legacy_accounts_kind_not_null on table legacy_accounts
+    LegacyAccountsKindNotNull,
+    /// Constraint: legacy_accounts_tags_not_null on table legacy_accounts
+    LegacyAccountsTagsNotNull,
+}
+
+impl TryFrom<super::ErrorConstraintInfo> for SetAccountTagsConstraints {
+    type Error = ();
+
+    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
+        match info.constraint_name.as_str() {
+            "legacy_accounts_pkey" => Ok(Self::LegacyAccountsPkey),
+            "legacy_accounts_id_not_null" => Ok(Self::LegacyAccountsIdNotNull),
+            "legacy_accounts_name_not_null" => Ok(Self::LegacyAccountsNameNotNull),
+            "legacy_accounts_is_active_not_null" => Ok(Self::LegacyAccountsIsActiveNotNull),
+            "legacy_accounts_kind_not_null" => Ok(Self::LegacyAccountsKindNotNull),
+            "legacy_accounts_tags_not_null" => Ok(Self::LegacyAccountsTagsNotNull),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SetAccountTagsItem {
+    pub id: i32,
+    pub tags: Vec<crate::models::AccountTag>,
+}
+
+/// Replace the tags of an account, stored as text and converted to an enum element by element
+///
+/// Tables: `legacy_accounts`
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "UPDATE public.legacy_accounts\nSET tags = #{tags}\nWHERE id = #{id}\nRETURNING id, tags"
+    )
+)]
+pub async fn set_account_tags(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    tags: Vec<crate::models::AccountTag>,
+    id: i32,
+) -> Result<SetAccountTagsItem, super::Error<SetAccountTagsConstraints>> {
+    let tags = super::array_elements::encode(&tags);
+    let query = sqlx::query(
+        r"UPDATE public.legacy_accounts
+        SET tags = $1
+        WHERE id = $2
+        RETURNING id, tags",
+    );
+    let query = query.bind(tags);
+    let query = query.bind(id);
+    let row = query.fetch_one(executor).await?;
+    let result: Result<_, sqlx::Error> = (|| {
+        Ok(SetAccountTagsItem {
+            id: row.try_get::<i32, _>("id")?,
+            tags: super::array_elements::decode("tags", row.try_get::<Vec<String>, _>("tags")?)?,
+        })
+    })();
+    result.map_err(Into::into)
+}
+// AUTOMODEL_CONTENT_HASH: 3a00cda8aecf0e49
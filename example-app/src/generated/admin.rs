@@ -1,16 +1,133 @@
 // This file was automatically generated by AutoModel. Do not edit manually.
 
+#[allow(unused_imports)]
+pub use super::{classify_error, DbErrorKind};
 use sqlx::Row;
 
+/// Tables read or modified by the queries of this module
+pub const TABLES: &[&str] = &["all_types_test"];
+
+/// Prepare every query of this module on the connection, e.g. in `after_connect` of the pool
+/// The statements stay cached on the connection; failed queries no longer match the schema
+pub async fn warmup(executor: &mut sqlx::PgConnection) -> Vec<super::WarmupStatus> {
+    const QUERIES: &[(&str, &[&str], &[&str])] = &[
+        (
+            "get_current_time",
+            &[r"SELECT NOW() as current_time"],
+            &["current_time"],
+        ),
+        (
+            "get_version",
+            &[r"SELECT version() as pg_version"],
+            &["pg_version"],
+        ),
+        (
+            "insert_all_types_test",
+            &[r"INSERT INTO public.all_types_test (
+         bool_col, char_col, int2_col, int4_col, int8_col, float4_col, float8_col, numeric_col,
+         name_col, text_col, varchar_col, bpchar_col, bytea_col, bit_col, varbit_col,
+         date_col, time_col, timestamp_col, timestamptz_col, interval_col, timetz_col,
+         int4_range_col, int8_range_col, num_range_col, ts_range_col, tstz_range_col, date_range_col,
+         inet_col, cidr_col, macaddr_col, json_col, jsonb_col, uuid_col,
+         bool_array_col, int4_array_col, int8_array_col, text_array_col, float8_array_col,
+         int4_range_array_col, date_range_array_col
+        ) VALUES (
+         $1, $2, $3, $4, $5, $6, $7, $8,
+         $9, $10, $11, $12, $13, $14, $15,
+         $16, $17, $18, $19, $20, $21,
+         $22, $23, $24, $25, $26, $27,
+         $28, $29, $30, $31, $32, $33,
+         $34, $35, $36, $37, $38,
+         $39, $40
+        )
+        RETURNING id"],
+            &["id"],
+        ),
+        (
+            "get_all_types_test",
+            &[r"SELECT
+         id, bool_col, char_col, int2_col, int4_col, int8_col, float4_col, float8_col, numeric_col,
+         name_col, text_col, varchar_col, bpchar_col, bytea_col, bit_col, varbit_col,
+         date_col, time_col, timestamp_col, timestamptz_col, interval_col, timetz_col,
+         int4_range_col, int8_range_col, num_range_col, ts_range_col, tstz_range_col, date_range_col,
+         inet_col, cidr_col, macaddr_col, json_col, jsonb_col, uuid_col,
+         bool_array_col, int4_array_col, int8_array_col, text_array_col, float8_array_col,
+         int4_range_array_col, date_range_array_col,
+         created_at
+        FROM public.all_types_test
+        WHERE id = $1"],
+            &[
+                "id",
+                "bool_col",
+                "char_col",
+                "int2_col",
+                "int4_col",
+                "int8_col",
+                "float4_col",
+                "float8_col",
+                "numeric_col",
+                "name_col",
+                "text_col",
+                "varchar_col",
+                "bpchar_col",
+                "bytea_col",
+                "bit_col",
+                "varbit_col",
+                "date_col",
+                "time_col",
+                "timestamp_col",
+                "timestamptz_col",
+                "interval_col",
+                "timetz_col",
+                "int4_range_col",
+                "int8_range_col",
+                "num_range_col",
+                "ts_range_col",
+                "tstz_range_col",
+                "date_range_col",
+                "inet_col",
+                "cidr_col",
+                "macaddr_col",
+                "json_col",
+                "jsonb_col",
+                "uuid_col",
+                "bool_array_col",
+                "int4_array_col",
+                "int8_array_col",
+                "text_array_col",
+                "float8_array_col",
+                "int4_range_array_col",
+                "date_range_array_col",
+                "created_at",
+            ],
+        ),
+        (
+            "sleep",
+            &[r"SELECT clock_timestamp() AS finished_at FROM pg_sleep($1)"],
+            &["finished_at"],
+        ),
+    ];
+    let mut report = Vec::with_capacity(QUERIES.len());
+    for &(query, variants, columns) in QUERIES {
+        let result = super::warmup_query(&mut *executor, variants, columns).await;
+        report.push(super::WarmupStatus { query, result });
+    }
+    report
+}
+
 /// Get the current server timestamp
 ///
 /// Query Plan:
 /// Result
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "SELECT NOW() as current_time"))]
-pub async fn get_current_time(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>) -> Result<Option<chrono::DateTime<chrono::Utc>>, super::ErrorReadOnly> {
-    let query = sqlx::query(
-        r"SELECT NOW() as current_time"
-    );
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(sql = "SELECT NOW() as current_time")
+)]
+pub async fn get_current_time(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+) -> Result<Option<chrono::DateTime<chrono::Utc>>, super::ErrorReadOnly> {
+    let query = sqlx::query(r"SELECT NOW() as current_time");
     let row = query.fetch_one(executor).await?;
     Ok(row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("current_time")?)
 }
@@ -19,15 +136,28 @@ pub async fn get_current_time(executor: impl sqlx::Executor<'_, Database = sqlx:
 ///
 /// Query Plan:
 /// Result
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "SELECT version() as pg_version"))]
-pub async fn get_version(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>) -> Result<Option<String>, super::ErrorReadOnly> {
-    let query = sqlx::query(
-        r"SELECT version() as pg_version"
-    );
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(sql = "SELECT version() as pg_version")
+)]
+pub async fn get_version(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+) -> Result<Option<String>, super::ErrorReadOnly> {
+    let query = sqlx::query(r"SELECT version() as pg_version");
     let row = query.fetch_one(executor).await?;
     Ok(row.try_get::<Option<String>, _>("pg_version")?)
 }
 
+/// Blocking version of [`get_version`], for callers without a tokio runtime
+/// Panics when called from async code running on a tokio runtime
+#[cfg(feature = "blocking")]
+pub fn get_version_blocking<'e>(
+    executor: impl sqlx::Executor<'e, Database = sqlx::Postgres>,
+) -> Result<Option<String>, super::ErrorReadOnly> {
+    super::blocking::block_on(get_version(executor))
+}
+
 /// Constraint violations specific to this query
 #[derive(Debug)]
 pub enum InsertAllTypesTestConstraints {
@@ -50,8 +180,56 @@ impl TryFrom<super::ErrorConstraintInfo> for InsertAllTypesTestConstraints {
 }
 
 /// Insert a row with all PostgreSQL types
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "INSERT INTO public.all_types_test (\n  bool_col, char_col, int2_col, int4_col, int8_col, float4_col, float8_col, numeric_col,\n  name_col, text_col, varchar_col, bpchar_col, bytea_col, bit_col, varbit_col,\n  date_col, time_col, timestamp_col, timestamptz_col, interval_col, timetz_col,\n  int4_range_col, int8_range_col, num_range_col, ts_range_col, tstz_range_col, date_range_col,\n  inet_col, cidr_col, macaddr_col, json_col, jsonb_col, uuid_col,\n  bool_array_col, int4_array_col, int8_array_col, text_array_col, float8_array_col,\n  int4_range_array_col, date_range_array_col\n) VALUES (\n  #{bool_col}, #{char_col}, #{int2_col}, #{int4_col}, #{int8_col}, #{float4_col}, #{float8_col}, #{numeric_col},\n  #{name_col}, #{text_col}, #{varchar_col}, #{bpchar_col}, #{bytea_col}, #{bit_col}, #{varbit_col},\n  #{date_col}, #{time_col}, #{timestamp_col}, #{timestamptz_col}, #{interval_col}, #{timetz_col},\n  #{int4_range_col}, #{int8_range_col}, #{num_range_col}, #{ts_range_col}, #{tstz_range_col}, #{date_range_col},\n  #{inet_col}, #{cidr_col}, #{macaddr_col}, #{json_col}, #{jsonb_col}, #{uuid_col},\n  #{bool_array_col}, #{int4_array_col}, #{int8_array_col}, #{text_array_col}, #{float8_array_col},\n  #{int4_range_array_col}, #{date_range_array_col}\n)\nRETURNING id"))]
-pub async fn insert_all_types_test(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, bool_col: bool, char_col: String, int2_col: i16, int4_col: i32, int8_col: i64, float4_col: f32, float8_col: f64, numeric_col: rust_decimal::Decimal, name_col: String, text_col: String, varchar_col: String, bpchar_col: String, bytea_col: Vec<u8>, bit_col: bit_vec::BitVec, varbit_col: bit_vec::BitVec, date_col: chrono::NaiveDate, time_col: chrono::NaiveTime, timestamp_col: chrono::NaiveDateTime, timestamptz_col: chrono::DateTime<chrono::Utc>, interval_col: sqlx::postgres::types::PgInterval, timetz_col: sqlx::postgres::types::PgTimeTz, int4_range_col: sqlx::postgres::types::PgRange<i32>, int8_range_col: sqlx::postgres::types::PgRange<i64>, num_range_col: sqlx::postgres::types::PgRange<rust_decimal::Decimal>, ts_range_col: sqlx::postgres::types::PgRange<chrono::NaiveDateTime>, tstz_range_col: sqlx::postgres::types::PgRange<chrono::DateTime<chrono::Utc>>, date_range_col: sqlx::postgres::types::PgRange<chrono::NaiveDate>, inet_col: std::net::IpAddr, cidr_col: std::net::IpAddr, macaddr_col: mac_address::MacAddress, json_col: serde_json::Value, jsonb_col: serde_json::Value, uuid_col: uuid::Uuid, bool_array_col: Vec<bool>, int4_array_col: Vec<i32>, int8_array_col: Vec<i64>, text_array_col: Vec<String>, float8_array_col: Vec<f64>, int4_range_array_col: Vec<sqlx::postgres::types::PgRange<i32>>, date_range_array_col: Vec<sqlx::postgres::types::PgRange<chrono::NaiveDate>>) -> Result<i32, super::Error<InsertAllTypesTestConstraints>> {
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "INSERT INTO public.all_types_test (\n  bool_col, char_col, int2_col, int4_col, int8_col, float4_col, float8_col, numeric_col,\n  name_col, text_col, varchar_col, bpchar_col, bytea_col, bit_col, varbit_col,\n  date_col, time_col, timestamp_col, timestamptz_col, interval_col, timetz_col,\n  int4_range_col, int8_range_col, num_range_col, ts_range_col, tstz_range_col, date_range_col,\n  inet_col, cidr_col, macaddr_col, json_col, jsonb_col, uuid_col,\n  bool_array_col, int4_array_col, int8_array_col, text_array_col, float8_array_col,\n  int4_range_array_col, date_range_array_col\n) VALUES (\n  #{bool_col}, #{char_col}, #{int2_col}, #{int4_col}, #{int8_col}, #{float4_col}, #{float8_col}, #{numeric_col},\n  #{name_col}, #{text_col}, #{varchar_col}, #{bpchar_col}, #{bytea_col}, #{bit_col}, #{varbit_col},\n  #{date_col}, #{time_col}, #{timestamp_col}, #{timestamptz_col}, #{interval_col}, #{timetz_col},\n  #{int4_range_col}, #{int8_range_col}, #{num_range_col}, #{ts_range_col}, #{tstz_range_col}, #{date_range_col},\n  #{inet_col}, #{cidr_col}, #{macaddr_col}, #{json_col}, #{jsonb_col}, #{uuid_col},\n  #{bool_array_col}, #{int4_array_col}, #{int8_array_col}, #{text_array_col}, #{float8_array_col},\n  #{int4_range_array_col}, #{date_range_array_col}\n)\nRETURNING id"
+    )
+)]
+pub async fn insert_all_types_test(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    bool_col: bool,
+    char_col: String,
+    int2_col: i16,
+    int4_col: i32,
+    int8_col: i64,
+    float4_col: f32,
+    float8_col: f64,
+    numeric_col: rust_decimal::Decimal,
+    name_col: String,
+    text_col: String,
+    varchar_col: String,
+    bpchar_col: String,
+    bytea_col: Vec<u8>,
+    bit_col: bit_vec::BitVec,
+    varbit_col: bit_vec::BitVec,
+    date_col: chrono::NaiveDate,
+    time_col: chrono::NaiveTime,
+    timestamp_col: chrono::NaiveDateTime,
+    timestamptz_col: chrono::DateTime<chrono::Utc>,
+    interval_col: sqlx::postgres::types::PgInterval,
+    timetz_col: sqlx::postgres::types::PgTimeTz,
+    int4_range_col: sqlx::postgres::types::PgRange<i32>,
+    int8_range_col: sqlx::postgres::types::PgRange<i64>,
+    num_range_col: sqlx::postgres::types::PgRange<rust_decimal::Decimal>,
+    ts_range_col: sqlx::postgres::types::PgRange<chrono::NaiveDateTime>,
+    tstz_range_col: sqlx::postgres::types::PgRange<chrono::DateTime<chrono::Utc>>,
+    date_range_col: sqlx::postgres::types::PgRange<chrono::NaiveDate>,
+    inet_col: std::net::IpAddr,
+    cidr_col: std::net::IpAddr,
+    macaddr_col: mac_address::MacAddress,
+    json_col: serde_json::Value,
+    jsonb_col: serde_json::Value,
+    uuid_col: uuid::Uuid,
+    bool_array_col: Vec<bool>,
+    int4_array_col: Vec<i32>,
+    int8_array_col: Vec<i64>,
+    text_array_col: Vec<String>,
+    float8_array_col: Vec<f64>,
+    int4_range_array_col: Vec<sqlx::postgres::types::PgRange<i32>>,
+    date_range_array_col: Vec<sqlx::postgres::types::PgRange<chrono::NaiveDate>>,
+) -> Result<i32, super::Error<InsertAllTypesTestConstraints>> {
     let query = sqlx::query(
         r"INSERT INTO public.all_types_test (
          bool_col, char_col, int2_col, int4_col, int8_col, float4_col, float8_col, numeric_col,
@@ -70,7 +248,7 @@ pub async fn insert_all_types_test(executor: impl sqlx::Executor<'_, Database =
          $34, $35, $36, $37, $38,
          $39, $40
         )
-        RETURNING id"
+        RETURNING id",
     );
     let query = query.bind(bool_col);
     let query = query.bind(&char_col);
@@ -116,7 +294,7 @@ pub async fn insert_all_types_test(executor: impl sqlx::Executor<'_, Database =
     Ok(row.try_get::<i32, _>("id")?)
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct GetAllTypesTestItem {
     pub id: i32,
     pub bool_col: Option<bool>,
@@ -164,11 +342,22 @@ pub struct GetAllTypesTestItem {
 
 /// Get a row with all PostgreSQL types by ID
 ///
+/// Tables: `all_types_test`
+///
 /// Query Plan:
 /// Index Scan using all_types_test_pkey on all_types_test
 ///   Index Cond: (id = 0)
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "SELECT\n  id, bool_col, char_col, int2_col, int4_col, int8_col, float4_col, float8_col, numeric_col,\n  name_col, text_col, varchar_col, bpchar_col, bytea_col, bit_col, varbit_col,\n  date_col, time_col, timestamp_col, timestamptz_col, interval_col, timetz_col,\n  int4_range_col, int8_range_col, num_range_col, ts_range_col, tstz_range_col, date_range_col,\n  inet_col, cidr_col, macaddr_col, json_col, jsonb_col, uuid_col,\n  bool_array_col, int4_array_col, int8_array_col, text_array_col, float8_array_col,\n  int4_range_array_col, date_range_array_col,\n  created_at\nFROM public.all_types_test\nWHERE id = #{id}"))]
-pub async fn get_all_types_test(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, id: i32) -> Result<GetAllTypesTestItem, super::ErrorReadOnly> {
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "SELECT\n  id, bool_col, char_col, int2_col, int4_col, int8_col, float4_col, float8_col, numeric_col,\n  name_col, text_col, varchar_col, bpchar_col, bytea_col, bit_col, varbit_col,\n  date_col, time_col, timestamp_col, timestamptz_col, interval_col, timetz_col,\n  int4_range_col, int8_range_col, num_range_col, ts_range_col, tstz_range_col, date_range_col,\n  inet_col, cidr_col, macaddr_col, json_col, jsonb_col, uuid_col,\n  bool_array_col, int4_array_col, int8_array_col, text_array_col, float8_array_col,\n  int4_range_array_col, date_range_array_col,\n  created_at\nFROM public.all_types_test\nWHERE id = #{id}"
+    )
+)]
+pub async fn get_all_types_test(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    id: i32,
+) -> Result<GetAllTypesTestItem, super::ErrorReadOnly> {
     let query = sqlx::query(
         r"SELECT
          id, bool_col, char_col, int2_col, int4_col, int8_col, float4_col, float8_col, numeric_col,
@@ -180,56 +369,151 @@ pub async fn get_all_types_test(executor: impl sqlx::Executor<'_, Database = sql
          int4_range_array_col, date_range_array_col,
          created_at
         FROM public.all_types_test
-        WHERE id = $1"
+        WHERE id = $1",
     );
     let query = query.bind(id);
     let row = query.fetch_one(executor).await?;
     let result: Result<_, sqlx::Error> = (|| {
         Ok(GetAllTypesTestItem {
-        id: row.try_get::<i32, _>("id")?,
-        bool_col: row.try_get::<Option<bool>, _>("bool_col")?,
-        char_col: row.try_get::<Option<String>, _>("char_col")?,
-        int2_col: row.try_get::<Option<i16>, _>("int2_col")?,
-        int4_col: row.try_get::<Option<i32>, _>("int4_col")?,
-        int8_col: row.try_get::<Option<i64>, _>("int8_col")?,
-        float4_col: row.try_get::<Option<f32>, _>("float4_col")?,
-        float8_col: row.try_get::<Option<f64>, _>("float8_col")?,
-        numeric_col: row.try_get::<Option<rust_decimal::Decimal>, _>("numeric_col")?,
-        name_col: row.try_get::<Option<String>, _>("name_col")?,
-        text_col: row.try_get::<Option<String>, _>("text_col")?,
-        varchar_col: row.try_get::<Option<String>, _>("varchar_col")?,
-        bpchar_col: row.try_get::<Option<String>, _>("bpchar_col")?,
-        bytea_col: row.try_get::<Option<Vec<u8>>, _>("bytea_col")?,
-        bit_col: row.try_get::<Option<bit_vec::BitVec>, _>("bit_col")?,
-        varbit_col: row.try_get::<Option<bit_vec::BitVec>, _>("varbit_col")?,
-        date_col: row.try_get::<Option<chrono::NaiveDate>, _>("date_col")?,
-        time_col: row.try_get::<Option<chrono::NaiveTime>, _>("time_col")?,
-        timestamp_col: row.try_get::<Option<chrono::NaiveDateTime>, _>("timestamp_col")?,
-        timestamptz_col: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("timestamptz_col")?,
-        interval_col: row.try_get::<Option<sqlx::postgres::types::PgInterval>, _>("interval_col")?,
-        timetz_col: row.try_get::<Option<sqlx::postgres::types::PgTimeTz>, _>("timetz_col")?,
-        int4_range_col: row.try_get::<Option<sqlx::postgres::types::PgRange<i32>>, _>("int4_range_col")?,
-        int8_range_col: row.try_get::<Option<sqlx::postgres::types::PgRange<i64>>, _>("int8_range_col")?,
-        num_range_col: row.try_get::<Option<sqlx::postgres::types::PgRange<rust_decimal::Decimal>>, _>("num_range_col")?,
-        ts_range_col: row.try_get::<Option<sqlx::postgres::types::PgRange<chrono::NaiveDateTime>>, _>("ts_range_col")?,
-        tstz_range_col: row.try_get::<Option<sqlx::postgres::types::PgRange<chrono::DateTime<chrono::Utc>>>, _>("tstz_range_col")?,
-        date_range_col: row.try_get::<Option<sqlx::postgres::types::PgRange<chrono::NaiveDate>>, _>("date_range_col")?,
-        inet_col: row.try_get::<Option<std::net::IpAddr>, _>("inet_col")?,
-        cidr_col: row.try_get::<Option<std::net::IpAddr>, _>("cidr_col")?,
-        macaddr_col: row.try_get::<Option<mac_address::MacAddress>, _>("macaddr_col")?,
-        json_col: row.try_get::<Option<serde_json::Value>, _>("json_col")?,
-        jsonb_col: row.try_get::<Option<serde_json::Value>, _>("jsonb_col")?,
-        uuid_col: row.try_get::<Option<uuid::Uuid>, _>("uuid_col")?,
-        bool_array_col: row.try_get::<Option<Vec<bool>>, _>("bool_array_col")?,
-        int4_array_col: row.try_get::<Option<Vec<i32>>, _>("int4_array_col")?,
-        int8_array_col: row.try_get::<Option<Vec<i64>>, _>("int8_array_col")?,
-        text_array_col: row.try_get::<Option<Vec<String>>, _>("text_array_col")?,
-        float8_array_col: row.try_get::<Option<Vec<f64>>, _>("float8_array_col")?,
-        int4_range_array_col: row.try_get::<Option<Vec<sqlx::postgres::types::PgRange<i32>>>, _>("int4_range_array_col")?,
-        date_range_array_col: row.try_get::<Option<Vec<sqlx::postgres::types::PgRange<chrono::NaiveDate>>>, _>("date_range_array_col")?,
-        created_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")?,
-    })
+            id: row.try_get::<i32, _>("id")?,
+            bool_col: row.try_get::<Option<bool>, _>("bool_col")?,
+            char_col: row.try_get::<Option<String>, _>("char_col")?,
+            int2_col: row.try_get::<Option<i16>, _>("int2_col")?,
+            int4_col: row.try_get::<Option<i32>, _>("int4_col")?,
+            int8_col: row.try_get::<Option<i64>, _>("int8_col")?,
+            float4_col: row.try_get::<Option<f32>, _>("float4_col")?,
+            float8_col: row.try_get::<Option<f64>, _>("float8_col")?,
+            numeric_col: row.try_get::<Option<rust_decimal::Decimal>, _>("numeric_col")?,
+            name_col: row.try_get::<Option<String>, _>("name_col")?,
+            text_col: row.try_get::<Option<String>, _>("text_col")?,
+            varchar_col: row.try_get::<Option<String>, _>("varchar_col")?,
+            bpchar_col: row.try_get::<Option<String>, _>("bpchar_col")?,
+            bytea_col: row.try_get::<Option<Vec<u8>>, _>("bytea_col")?,
+            bit_col: row.try_get::<Option<bit_vec::BitVec>, _>("bit_col")?,
+            varbit_col: row.try_get::<Option<bit_vec::BitVec>, _>("varbit_col")?,
+            date_col: row.try_get::<Option<chrono::NaiveDate>, _>("date_col")?,
+            time_col: row.try_get::<Option<chrono::NaiveTime>, _>("time_col")?,
+            timestamp_col: row.try_get::<Option<chrono::NaiveDateTime>, _>("timestamp_col")?,
+            timestamptz_col: row
+                .try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("timestamptz_col")?,
+            interval_col: row
+                .try_get::<Option<sqlx::postgres::types::PgInterval>, _>("interval_col")?,
+            timetz_col: row.try_get::<Option<sqlx::postgres::types::PgTimeTz>, _>("timetz_col")?,
+            int4_range_col: row
+                .try_get::<Option<sqlx::postgres::types::PgRange<i32>>, _>("int4_range_col")?,
+            int8_range_col: row
+                .try_get::<Option<sqlx::postgres::types::PgRange<i64>>, _>("int8_range_col")?,
+            num_range_col: row
+                .try_get::<Option<sqlx::postgres::types::PgRange<rust_decimal::Decimal>>, _>(
+                    "num_range_col",
+                )?,
+            ts_range_col: row
+                .try_get::<Option<sqlx::postgres::types::PgRange<chrono::NaiveDateTime>>, _>(
+                    "ts_range_col",
+                )?,
+            tstz_range_col: row.try_get::<Option<
+                sqlx::postgres::types::PgRange<chrono::DateTime<chrono::Utc>>,
+            >, _>("tstz_range_col")?,
+            date_range_col: row
+                .try_get::<Option<sqlx::postgres::types::PgRange<chrono::NaiveDate>>, _>(
+                    "date_range_col",
+                )?,
+            inet_col: row.try_get::<Option<std::net::IpAddr>, _>("inet_col")?,
+            cidr_col: row.try_get::<Option<std::net::IpAddr>, _>("cidr_col")?,
+            macaddr_col: row.try_get::<Option<mac_address::MacAddress>, _>("macaddr_col")?,
+            json_col: row.try_get::<Option<serde_json::Value>, _>("json_col")?,
+            jsonb_col: row.try_get::<Option<serde_json::Value>, _>("jsonb_col")?,
+            uuid_col: row.try_get::<Option<uuid::Uuid>, _>("uuid_col")?,
+            bool_array_col: row.try_get::<Option<Vec<bool>>, _>("bool_array_col")?,
+            int4_array_col: row.try_get::<Option<Vec<i32>>, _>("int4_array_col")?,
+            int8_array_col: row.try_get::<Option<Vec<i64>>, _>("int8_array_col")?,
+            text_array_col: row.try_get::<Option<Vec<String>>, _>("text_array_col")?,
+            float8_array_col: row.try_get::<Option<Vec<f64>>, _>("float8_array_col")?,
+            int4_range_array_col: row
+                .try_get::<Option<Vec<sqlx::postgres::types::PgRange<i32>>>, _>(
+                    "int4_range_array_col",
+                )?,
+            date_range_array_col: row.try_get::<Option<
+                Vec<sqlx::postgres::types::PgRange<chrono::NaiveDate>>,
+            >, _>("date_range_array_col")?,
+            created_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")?,
+        })
     })();
     result.map_err(Into::into)
 }
 
+/// Sleep on the server for a number of seconds, returning when it finished
+///
+/// Query Plan:
+/// Function Scan on pg_sleep
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(sql = "SELECT clock_timestamp() AS finished_at FROM pg_sleep(#{seconds})")
+)]
+pub async fn sleep(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    seconds: f64,
+) -> Result<Option<chrono::DateTime<chrono::Utc>>, super::ErrorReadOnly> {
+    let query = sqlx::query(r"SELECT clock_timestamp() AS finished_at FROM pg_sleep($1)");
+    let query = query.bind(seconds);
+    let row = query.fetch_one(executor).await?;
+    Ok(row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("finished_at")?)
+}
+
+/// Cancellable version of [`sleep`], running it on a connection of the pool
+/// When the token is cancelled first, the query is cancelled on the server, the connection is
+/// closed and `Cancelled` is returned
+#[cfg(feature = "cancellation")]
+pub async fn sleep_cancellable(
+    pool: &sqlx::PgPool,
+    token: &tokio_util::sync::CancellationToken,
+    seconds: f64,
+) -> Result<Option<chrono::DateTime<chrono::Utc>>, super::ErrorReadOnly> {
+    let mut connection = tokio::select! {
+        biased;
+        _ = token.cancelled() => return Err(super::ErrorReadOnly::Cancelled),
+        connection = pool.acquire() => connection?,
+    };
+    let backend_pid = super::cancellation::backend_pid(&mut connection).await?;
+    let result = tokio::select! {
+        biased;
+        _ = token.cancelled() => None,
+        result = sleep(&mut *connection, seconds) => Some(result),
+    };
+    match result {
+        Some(result) => result,
+        None => {
+            super::cancellation::cancel(pool, connection, backend_pid);
+            Err(super::ErrorReadOnly::Cancelled)
+        }
+    }
+}
+
+/// Refresh the planner statistics of the users and posts tables
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(sql = "ANALYZE public.users;\nANALYZE public.posts")
+)]
+pub async fn refresh_statistics(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+) -> Result<(), super::ErrorReadOnly> {
+    let query = sqlx::raw_sql(
+        r"ANALYZE public.users;
+        ANALYZE public.posts",
+    );
+    query.execute(executor).await?;
+    Ok(())
+}
+
+/// Get the schema search path of the session
+#[tracing::instrument(level = "debug", skip_all, fields(sql = "SHOW search_path"))]
+pub async fn get_search_path(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+) -> Result<Option<String>, super::ErrorReadOnly> {
+    let query = sqlx::raw_sql(r"SHOW search_path");
+    let row = query.fetch_one(executor).await?;
+    Ok(row.try_get::<Option<String>, _>("search_path")?)
+}
+// AUTOMODEL_CONTENT_HASH: ea6f5778faee0a91
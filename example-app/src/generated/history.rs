@@ -0,0 +1,95 @@
+// This file was automatically generated by AutoModel. Do not edit manually.
+
+#[allow(unused_imports)]
+pub use super::{classify_error, DbErrorKind};
+use sqlx::Row;
+
+/// Tables read or modified by the queries of this module
+pub const TABLES: &[&str] = &["tickets_history"];
+
+/// Prepare every query of this module on the connection, e.g. in `after_connect` of the pool
+/// The statements stay cached on the connection; failed queries no longer match the schema
+pub async fn warmup(executor: &mut sqlx::PgConnection) -> Vec<super::WarmupStatus> {
+    const QUERIES: &[(&str, &[&str], &[&str])] = &[(
+        "get_tickets_history",
+        &[r"SELECT * FROM public.tickets_history
+        WHERE id = $1 AND changed_at <@ $2::tstzrange
+        ORDER BY changed_at"],
+        &[
+            "history_id",
+            "id",
+            "title",
+            "priority",
+            "state",
+            "operation",
+            "changed_at",
+        ],
+    )];
+    let mut report = Vec::with_capacity(QUERIES.len());
+    for &(query, variants, columns) in QUERIES {
+        let result = super::warmup_query(&mut *executor, variants, columns).await;
+        report.push(super::WarmupStatus { query, result });
+    }
+    report
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GetTicketsHistoryItem {
+    pub history_id: i64,
+    pub id: i32,
+    pub title: String,
+    pub priority: String,
+    pub state: String,
+    pub operation: String,
+    pub changed_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Changes of a public.tickets row recorded in public.tickets_history within a time range, oldest first
+///
+/// Tables: `tickets_history`
+///
+/// Query Plan:
+/// Sort
+///   Sort Key: changed_at
+///   ->  Bitmap Heap Scan on tickets_history
+///         Recheck Cond: (id = 0)
+///         Filter: (changed_at <@ 'empty'::tstzrange)
+///         ->  Bitmap Index Scan on idx_tickets_history_id_changed_at
+///               Index Cond: (id = 0)
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "SELECT * FROM public.tickets_history\nWHERE id = #{id} AND changed_at <@ #{time_range}::tstzrange\nORDER BY changed_at"
+    )
+)]
+pub async fn get_tickets_history(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    id: i32,
+    time_range: sqlx::postgres::types::PgRange<chrono::DateTime<chrono::Utc>>,
+) -> Result<Vec<GetTicketsHistoryItem>, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT * FROM public.tickets_history
+        WHERE id = $1 AND changed_at <@ $2::tstzrange
+        ORDER BY changed_at",
+    );
+    let query = query.bind(id);
+    let query = query.bind(time_range);
+    let rows = query.fetch_all(executor).await?;
+    let result: Result<Vec<_>, sqlx::Error> = rows
+        .iter()
+        .map(|row| {
+            Ok(GetTicketsHistoryItem {
+                history_id: row.try_get::<i64, _>("history_id")?,
+                id: row.try_get::<i32, _>("id")?,
+                title: row.try_get::<String, _>("title")?,
+                priority: row.try_get::<String, _>("priority")?,
+                state: row.try_get::<String, _>("state")?,
+                operation: row.try_get::<String, _>("operation")?,
+                changed_at: row.try_get::<chrono::DateTime<chrono::Utc>, _>("changed_at")?,
+            })
+        })
+        .collect();
+    result.map_err(Into::into)
+}
+// AUTOMODEL_CONTENT_HASH: dbbb78c9cfad9f75
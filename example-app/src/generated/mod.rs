@@ -1,9 +1,15 @@
-// AUTOMODEL_HASH: 5405022927094861926
+// AUTOMODEL_HASH: 11525236023308149652
 // This file was automatically generated by AutoModel. Do not edit manually.
+// Analyzed against server version 15.18 (Debian 15.18-0+deb12u1) at 2026-10-16T18:10:37Z
+// Analysis settings: enable_seqscan = off, search_path = ""
 
+pub mod accounts;
 pub mod admin;
 pub mod analytics;
+pub mod history;
+pub mod orders;
 pub mod setup;
+pub mod tickets;
 pub mod user_model;
 pub mod users;
 
@@ -37,6 +43,27 @@ impl From<sqlx::error::ErrorKind> for ErrorConstraintKind {
     }
 }
 
+/// A `numeric_as: i64` column whose value is not an integer or is out of the range of i64
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumericOverflow {
+    /// Name of the column
+    pub column: &'static str,
+    /// The decoded value
+    pub value: String,
+}
+
+impl std::fmt::Display for NumericOverflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Value {} of column {} does not fit in i64",
+            self.value, self.column
+        )
+    }
+}
+
+impl std::error::Error for NumericOverflow {}
+
 /// Generic error type
 #[derive(Debug)]
 pub enum Error<C: TryFrom<ErrorConstraintInfo>> {
@@ -50,7 +77,19 @@ pub enum Error<C: TryFrom<ErrorConstraintInfo>> {
 
     /// Row not found error
     RowNotFound,
-    
+
+    /// Row not found, with the `not_found_message` of the query
+    NotFound(String),
+
+    /// The `feature_flag` of the query is switched off
+    FeatureDisabled(&'static str),
+
+    /// The cancellation token of a `_cancellable` function was cancelled before the query finished
+    Cancelled,
+
+    /// A `numeric_as: i64` column does not fit in i64
+    Overflow(NumericOverflow),
+
     /// System under stress, timeout
     PoolTimeout,
 
@@ -59,12 +98,19 @@ pub enum Error<C: TryFrom<ErrorConstraintInfo>> {
 
 impl<C: TryFrom<ErrorConstraintInfo>> From<sqlx::Error> for Error<C> {
     fn from(error: sqlx::Error) -> Self {
+        if let sqlx::Error::ColumnDecode { source, .. } = &error {
+            if let Some(overflow) = source.downcast_ref::<NumericOverflow>() {
+                return Self::Overflow(overflow.clone());
+            }
+        }
         match &error {
             sqlx::Error::RowNotFound => Self::RowNotFound,
             sqlx::Error::ColumnNotFound(col) => {
                 Self::InternalError(format!("Column not found: {}", col), error)
             }
-            sqlx::Error::Database(db_err) => {
+            sqlx::Error::Database(db_err)
+                if classify_error(&error) == DbErrorKind::ConstraintViolation =>
+            {
                 // Extract constraint name and table from error
                 let constraint_name = db_err.constraint().unwrap_or("").to_string();
                 let table_name = db_err.table().unwrap_or("").to_string();
@@ -77,6 +123,7 @@ impl<C: TryFrom<ErrorConstraintInfo>> From<sqlx::Error> for Error<C> {
                 };
                 Self::ConstraintViolation(violation.clone().try_into().ok(), violation)
             }
+            sqlx::Error::Database(_) => Self::InternalError("Database error".to_string(), error),
             sqlx::Error::Configuration(_) => {
                 Self::InternalError("Configuration error".to_string(), error)
             }
@@ -106,10 +153,9 @@ impl<C: TryFrom<ErrorConstraintInfo>> From<sqlx::Error> for Error<C> {
             sqlx::Error::PoolClosed => Self::InternalError("Pool closed".to_string(), error),
             sqlx::Error::WorkerCrashed => Self::InternalError("Worker crashed".to_string(), error),
             sqlx::Error::Migrate(_) => Self::InternalError("Migration error".to_string(), error),
-            sqlx::Error::InvalidSavePointStatement => Self::InternalError(
-                "Invalid save point statement".to_string(),
-                error,
-            ),
+            sqlx::Error::InvalidSavePointStatement => {
+                Self::InternalError("Invalid save point statement".to_string(), error)
+            }
             sqlx::Error::BeginFailed => Self::InternalError("Begin failed".to_string(), error),
             _ => Self::InternalError("Unknown sqlx error".to_string(), error),
         }
@@ -126,10 +172,18 @@ where
                 if let Some(c) = constraint {
                     write!(f, "Constraint violation: {:#?}", c)
                 } else {
-                    write!(f, "Unknown constraint violation: {} on table {}", info.constraint_name, info.table_name)
+                    write!(
+                        f,
+                        "Unknown constraint violation: {} on table {}",
+                        info.constraint_name, info.table_name
+                    )
                 }
             }
             Error::RowNotFound => write!(f, "Row not found"),
+            Error::NotFound(message) => write!(f, "Row not found: {}", message),
+            Error::FeatureDisabled(flag) => write!(f, "Feature disabled: {}", flag),
+            Error::Cancelled => write!(f, "Cancelled"),
+            Error::Overflow(overflow) => write!(f, "Overflow: {}", overflow),
             Error::PoolTimeout => write!(f, "Pool timeout"),
             Error::InternalError(msg, err) => {
                 write!(f, "Internal error: {}, caused by: {}", msg, err)
@@ -156,6 +210,18 @@ pub enum ErrorReadOnly {
     /// Row not found error
     RowNotFound,
 
+    /// Row not found, with the `not_found_message` of the query
+    NotFound(String),
+
+    /// The `feature_flag` of the query is switched off
+    FeatureDisabled(&'static str),
+
+    /// The cancellation token of a `_cancellable` function was cancelled before the query finished
+    Cancelled,
+
+    /// A `numeric_as: i64` column does not fit in i64
+    Overflow(NumericOverflow),
+
     /// System under stress, timeout
     PoolTimeout,
 
@@ -168,10 +234,14 @@ impl From<sqlx::Error> for ErrorReadOnly {
     }
 }
 
-impl Into<Error<ErrorConstraintInfo>> for ErrorReadOnly {
-    fn into(self) -> Error<ErrorConstraintInfo> {
-        match self {
+impl<C: TryFrom<ErrorConstraintInfo>> From<ErrorReadOnly> for Error<C> {
+    fn from(error: ErrorReadOnly) -> Self {
+        match error {
             ErrorReadOnly::RowNotFound => Error::RowNotFound,
+            ErrorReadOnly::NotFound(message) => Error::NotFound(message),
+            ErrorReadOnly::FeatureDisabled(flag) => Error::FeatureDisabled(flag),
+            ErrorReadOnly::Cancelled => Error::Cancelled,
+            ErrorReadOnly::Overflow(overflow) => Error::Overflow(overflow),
             ErrorReadOnly::PoolTimeout => Error::PoolTimeout,
             ErrorReadOnly::InternalError(msg, err) => Error::InternalError(msg, err),
         }
@@ -182,6 +252,10 @@ impl From<Error<ErrorConstraintInfo>> for ErrorReadOnly {
     fn from(error: Error<ErrorConstraintInfo>) -> Self {
         match error {
             Error::RowNotFound => Self::RowNotFound,
+            Error::NotFound(message) => Self::NotFound(message),
+            Error::FeatureDisabled(flag) => Self::FeatureDisabled(flag),
+            Error::Cancelled => Self::Cancelled,
+            Error::Overflow(overflow) => Self::Overflow(overflow),
             Error::PoolTimeout => Self::PoolTimeout,
             Error::InternalError(msg, err) => Self::InternalError(msg, err),
             Error::ConstraintViolation(c, info) => Self::InternalError(
@@ -199,6 +273,10 @@ impl std::fmt::Display for ErrorReadOnly {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ErrorReadOnly::RowNotFound => write!(f, "Row not found"),
+            ErrorReadOnly::NotFound(message) => write!(f, "Row not found: {}", message),
+            ErrorReadOnly::FeatureDisabled(flag) => write!(f, "Feature disabled: {}", flag),
+            ErrorReadOnly::Cancelled => write!(f, "Cancelled"),
+            ErrorReadOnly::Overflow(overflow) => write!(f, "Overflow: {}", overflow),
             ErrorReadOnly::PoolTimeout => write!(f, "Pool timeout"),
             ErrorReadOnly::InternalError(msg, err) => {
                 write!(f, "Internal error: {}, caused by: {}", msg, err)
@@ -216,3 +294,1505 @@ impl std::error::Error for ErrorReadOnly {
     }
 }
 
+/// Broad category of a database error, for retry and compensation logic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DbErrorKind {
+    /// Integrity constraint violated (SQLSTATE class 23)
+    ConstraintViolation,
+    /// Transaction aborted by a serialization failure or deadlock, safe to retry (40001, 40P01)
+    SerializationFailure,
+    /// Connection failed, was closed or the server is shutting down (class 08, 57P01-57P03)
+    ConnectionLoss,
+    /// Statement, lock or pool acquisition timed out (57014, 55P03, 25P03)
+    Timeout,
+    /// Any other error
+    Other,
+}
+
+/// Classify an error by its PostgreSQL SQLSTATE or connection failure
+pub fn classify_error(error: &sqlx::Error) -> DbErrorKind {
+    match error {
+        sqlx::Error::Database(db_err) => match db_err.code().as_deref() {
+            Some("40001") | Some("40P01") => DbErrorKind::SerializationFailure,
+            Some("57014") | Some("55P03") | Some("25P03") => DbErrorKind::Timeout,
+            Some("57P01") | Some("57P02") | Some("57P03") => DbErrorKind::ConnectionLoss,
+            Some(code) if code.starts_with("23") => DbErrorKind::ConstraintViolation,
+            Some(code) if code.starts_with("08") => DbErrorKind::ConnectionLoss,
+            _ => DbErrorKind::Other,
+        },
+        sqlx::Error::PoolTimedOut => DbErrorKind::Timeout,
+        sqlx::Error::Io(_)
+        | sqlx::Error::Tls(_)
+        | sqlx::Error::PoolClosed
+        | sqlx::Error::WorkerCrashed => DbErrorKind::ConnectionLoss,
+        _ => DbErrorKind::Other,
+    }
+}
+
+impl<C: TryFrom<ErrorConstraintInfo>> Error<C> {
+    /// Broad category of the error, see `classify_error`
+    pub fn kind(&self) -> DbErrorKind {
+        match self {
+            Error::ConstraintViolation(_, _) => DbErrorKind::ConstraintViolation,
+            Error::RowNotFound
+            | Error::NotFound(_)
+            | Error::FeatureDisabled(_)
+            | Error::Cancelled
+            | Error::Overflow(_) => DbErrorKind::Other,
+            Error::PoolTimeout => DbErrorKind::Timeout,
+            Error::InternalError(_, err) => classify_error(err),
+        }
+    }
+
+    /// Whether no row was found, with or without a `not_found_message`
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, Error::RowNotFound | Error::NotFound(_))
+    }
+
+    /// Replace RowNotFound with NotFound carrying the `not_found_message` of the query
+    pub fn with_not_found_message(self, message: String) -> Self {
+        match self {
+            Error::RowNotFound => Error::NotFound(message),
+            error => error,
+        }
+    }
+}
+
+impl ErrorReadOnly {
+    /// Broad category of the error, see `classify_error`
+    pub fn kind(&self) -> DbErrorKind {
+        match self {
+            ErrorReadOnly::RowNotFound
+            | ErrorReadOnly::NotFound(_)
+            | ErrorReadOnly::FeatureDisabled(_)
+            | ErrorReadOnly::Cancelled
+            | ErrorReadOnly::Overflow(_) => DbErrorKind::Other,
+            ErrorReadOnly::PoolTimeout => DbErrorKind::Timeout,
+            ErrorReadOnly::InternalError(_, err) => classify_error(err),
+        }
+    }
+
+    /// Whether no row was found, with or without a `not_found_message`
+    pub fn is_not_found(&self) -> bool {
+        matches!(
+            self,
+            ErrorReadOnly::RowNotFound | ErrorReadOnly::NotFound(_)
+        )
+    }
+
+    /// Replace RowNotFound with NotFound carrying the `not_found_message` of the query
+    pub fn with_not_found_message(self, message: String) -> Self {
+        match self {
+            ErrorReadOnly::RowNotFound => ErrorReadOnly::NotFound(message),
+            error => error,
+        }
+    }
+}
+
+/// Result of preparing a query with the `warmup` function of its module
+#[derive(Debug)]
+pub struct WarmupStatus {
+    /// Name of the query
+    pub query: &'static str,
+    /// Error of the first SQL variant of the query which failed to prepare, or a protocol error
+    /// when the result columns differ from those the code was generated for
+    pub result: Result<(), sqlx::Error>,
+}
+
+impl WarmupStatus {
+    /// Whether the query matches the schema of the database
+    pub fn is_ok(&self) -> bool {
+        self.result.is_ok()
+    }
+}
+
+/// Prepare the SQL variants of a query, checking the result columns of the first one
+pub async fn warmup_query(
+    executor: &mut sqlx::PgConnection,
+    variants: &[&'static str],
+    columns: &[&str],
+) -> Result<(), sqlx::Error> {
+    use sqlx::{Column, Executor, Statement};
+    for (i, sql) in variants.iter().enumerate() {
+        let statement = (&mut *executor).prepare(sql).await?;
+        if i == 0 {
+            let actual: Vec<&str> = statement.columns().iter().map(|c| c.name()).collect();
+            if actual != columns {
+                return Err(sqlx::Error::Protocol(format!(
+                    "result columns {:?} differ from the generated {:?}",
+                    actual, columns
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Pooled connection pinned for multi-step flows relying on session state, such as temp tables
+/// and session settings; functions generated with `requires_session` only accept a Session
+pub struct Session {
+    connection: sqlx::pool::PoolConnection<sqlx::Postgres>,
+}
+
+impl Session {
+    /// Pin a connection of the pool until the session is dropped
+    pub async fn acquire(pool: &sqlx::PgPool) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            connection: pool.acquire().await?,
+        })
+    }
+
+    /// Connection of the session, e.g. to create temp tables or set session settings
+    pub fn connection(&mut self) -> &mut sqlx::PgConnection {
+        &mut self.connection
+    }
+
+    /// Release the session, returning its connection
+    pub fn into_connection(self) -> sqlx::pool::PoolConnection<sqlx::Postgres> {
+        self.connection
+    }
+}
+
+/// Rows a DELETE would remove, and the rows of other tables referencing them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CascadeImpact {
+    /// Rows matched by the DELETE
+    pub deleted: i64,
+    /// Referencing rows per foreign key, including those referencing rows deleted by cascade
+    pub referencing: Vec<ReferencingRows>,
+}
+
+impl CascadeImpact {
+    /// Whether referencing rows make the DELETE fail (ON DELETE NO ACTION or RESTRICT)
+    pub fn is_blocked(&self) -> bool {
+        self.referencing.iter().any(|referencing| {
+            referencing.rows > 0
+                && matches!(
+                    referencing.on_delete,
+                    OnDelete::NoAction | OnDelete::Restrict
+                )
+        })
+    }
+
+    /// Rows the DELETE removes in total, including those deleted by cascade
+    pub fn total_deleted(&self) -> i64 {
+        self.deleted
+            + self
+                .referencing
+                .iter()
+                .filter(|referencing| referencing.on_delete == OnDelete::Cascade)
+                .map(|referencing| referencing.rows)
+                .sum::<i64>()
+    }
+}
+
+/// Rows of a table referencing deleted rows through a foreign key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReferencingRows {
+    /// Schema-qualified referencing table
+    pub table: &'static str,
+    /// Foreign key constraint name
+    pub constraint: &'static str,
+    /// What the foreign key does with the referencing rows
+    pub on_delete: OnDelete,
+    /// Number of referencing rows
+    pub rows: i64,
+}
+
+/// ON DELETE action of a foreign key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnDelete {
+    NoAction,
+    Restrict,
+    Cascade,
+    SetNull,
+    SetDefault,
+}
+
+/// Runtime state of the feature flags checked by generated functions
+/// Every flag is off until a provider switching it on is set with `set_provider()`
+pub mod feature_flags {
+    use std::sync::{Arc, RwLock};
+
+    /// Source of the state of feature flags, e.g. a configuration file or a flag service client
+    pub trait FeatureFlagProvider: Send + Sync {
+        /// Whether the flag is switched on
+        fn is_enabled(&self, flag: &str) -> bool;
+    }
+
+    impl<F: Fn(&str) -> bool + Send + Sync> FeatureFlagProvider for F {
+        fn is_enabled(&self, flag: &str) -> bool {
+            self(flag)
+        }
+    }
+
+    static PROVIDER: RwLock<Option<Arc<dyn FeatureFlagProvider>>> = RwLock::new(None);
+
+    /// Decide the feature flags of generated functions with `provider`
+    pub fn set_provider(provider: impl FeatureFlagProvider + 'static) {
+        if let Ok(mut current) = PROVIDER.write() {
+            *current = Some(Arc::new(provider));
+        }
+    }
+
+    /// Remove the provider, switching every flag off
+    pub fn clear_provider() {
+        if let Ok(mut current) = PROVIDER.write() {
+            *current = None;
+        }
+    }
+
+    /// Whether the flag is switched on by the current provider
+    pub fn is_enabled(flag: &str) -> bool {
+        let provider = PROVIDER.read().ok().and_then(|current| current.clone());
+        provider.is_some_and(|provider| provider.is_enabled(flag))
+    }
+}
+
+/// Runtime sampling of query plans with EXPLAIN ANALYZE
+/// Disabled until `enable()` is called
+pub mod plan_sampling {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, RwLock};
+
+    /// Query plan captured for a sampled call
+    #[derive(Debug, Clone)]
+    pub struct PlanSample {
+        /// Generated function which was sampled, e.g. "users::get_user_by_id"
+        pub query: &'static str,
+        /// Output of EXPLAIN (ANALYZE, FORMAT JSON) for the call's SQL and parameters
+        pub plan: serde_json::Value,
+    }
+
+    /// Callback receiving sampled query plans
+    pub type PlanObserver = Arc<dyn Fn(PlanSample) + Send + Sync>;
+
+    struct Sampler {
+        one_in: u64,
+        shadow: sqlx::PgPool,
+        observer: PlanObserver,
+    }
+
+    static SAMPLER: RwLock<Option<Sampler>> = RwLock::new(None);
+    static CALLS: AtomicU64 = AtomicU64::new(0);
+
+    /// Sample 1 in `one_in` calls of generated read-only functions
+    /// Sampled calls run EXPLAIN ANALYZE on the `shadow` pool before the query itself
+    /// and pass the plan to `observer`; sampling failures are ignored
+    pub fn enable(
+        shadow: sqlx::PgPool,
+        one_in: u64,
+        observer: impl Fn(PlanSample) + Send + Sync + 'static,
+    ) {
+        if let Ok(mut sampler) = SAMPLER.write() {
+            *sampler = Some(Sampler {
+                one_in: one_in.max(1),
+                shadow,
+                observer: Arc::new(observer),
+            });
+        }
+    }
+
+    /// Stop sampling query plans
+    pub fn disable() {
+        if let Ok(mut sampler) = SAMPLER.write() {
+            *sampler = None;
+        }
+    }
+
+    /// Decide whether the current call is sampled
+    /// Returns the shadow pool and observer for sampled calls
+    pub(crate) fn sample() -> Option<(sqlx::PgPool, PlanObserver)> {
+        let sampler = SAMPLER.read().ok()?;
+        let sampler = sampler.as_ref()?;
+        if !CALLS
+            .fetch_add(1, Ordering::Relaxed)
+            .is_multiple_of(sampler.one_in)
+        {
+            return None;
+        }
+        Some((sampler.shadow.clone(), sampler.observer.clone()))
+    }
+}
+
+/// Reporting of calls returning an order of magnitude more or fewer rows than the planner
+/// estimated when the code was generated, a sign that statistics or indexes need attention
+/// Disabled until `enable()` is called
+pub mod cardinality_drift {
+    use std::sync::{Arc, RwLock};
+
+    /// Call whose result size diverged from the planner estimate
+    #[derive(Debug, Clone)]
+    pub struct CardinalityDrift {
+        /// Generated function which was called, e.g. "users::get_all_users"
+        pub query: &'static str,
+        /// Rows estimated by the planner at generation time, rounded to their order of magnitude
+        pub estimated_rows: f64,
+        /// Rows returned by the call
+        pub actual_rows: usize,
+    }
+
+    /// Callback receiving cardinality drift reports
+    pub type DriftObserver = Arc<dyn Fn(CardinalityDrift) + Send + Sync>;
+
+    static OBSERVER: RwLock<Option<DriftObserver>> = RwLock::new(None);
+
+    /// Pass calls returning at least 10 times more or fewer rows than estimated to `observer`
+    pub fn enable(observer: impl Fn(CardinalityDrift) + Send + Sync + 'static) {
+        if let Ok(mut current) = OBSERVER.write() {
+            *current = Some(Arc::new(observer));
+        }
+    }
+
+    /// Stop reporting cardinality drift
+    pub fn disable() {
+        if let Ok(mut current) = OBSERVER.write() {
+            *current = None;
+        }
+    }
+
+    /// Report the call if its result size diverged from the estimate by an order of magnitude
+    /// Empty results count as one row, so that small estimates are not reported for them
+    pub(crate) fn record(query: &'static str, estimated_rows: f64, actual_rows: usize) {
+        let Some(observer) = OBSERVER.read().ok().and_then(|current| current.clone()) else {
+            return;
+        };
+        let estimated = estimated_rows.max(1.0);
+        let actual = (actual_rows as f64).max(1.0);
+        if actual >= estimated * 10.0 || estimated >= actual * 10.0 {
+            observer(CardinalityDrift {
+                query,
+                estimated_rows,
+                actual_rows,
+            });
+        }
+    }
+}
+
+/// Invalidation of the application caches of queries with `cache_invalidation`, notified by the
+/// triggers of the generated cache invalidation migrations, and of the caches of the `*_cached`
+/// functions of queries with `cache_invalidate_on`
+pub mod cache_invalidation {
+    /// Channel notified by the triggers, with the changed table as payload
+    pub const CHANNEL: &str = "automodel_cache_invalidation";
+
+    /// Channels of the queries with `cache_invalidate_on`, listened to as well
+    pub const QUERY_CHANNELS: &[&str] = &["user_events"];
+
+    /// Change invalidating cached query results
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum Invalidation {
+        /// Rows of the table changed, named as in the `TABLES` of the modules, e.g. "users"
+        Table(String),
+        /// One of the `QUERY_CHANNELS` was notified, with the payload naming the changed key or
+        /// empty
+        Channel { channel: String, payload: String },
+        /// The connection of the listener was lost, so changes may have been missed
+        All,
+    }
+
+    impl Invalidation {
+        /// Whether cached results of a query reading the tables, e.g. an entry of the
+        /// `CACHED_QUERIES` of a module, are stale
+        pub fn affects(&self, tables: &[&str]) -> bool {
+            match self {
+                Self::Table(table) => tables.contains(&table.as_str()),
+                Self::Channel { .. } => false,
+                Self::All => true,
+            }
+        }
+    }
+
+    /// Listener receiving the changes of the tables of cached queries and the notifications of
+    /// the `QUERY_CHANNELS` on a connection of its own
+    pub struct Listener {
+        listener: sqlx::postgres::PgListener,
+    }
+
+    impl Listener {
+        /// Listen to `CHANNEL` and the `QUERY_CHANNELS` on a connection taken from the pool
+        pub async fn connect(pool: &sqlx::PgPool) -> Result<Self, sqlx::Error> {
+            let mut listener = sqlx::postgres::PgListener::connect_with(pool).await?;
+            listener
+                .listen_all(std::iter::once(CHANNEL).chain(QUERY_CHANNELS.iter().copied()))
+                .await?;
+            Ok(Self { listener })
+        }
+
+        /// Wait for the next change, `Invalidation::All` when the connection was lost, which the
+        /// listener re-establishes on the next call
+        pub async fn recv(&mut self) -> Result<Invalidation, sqlx::Error> {
+            Ok(match self.listener.try_recv().await? {
+                Some(notification) if notification.channel() == CHANNEL => {
+                    Invalidation::Table(notification.payload().to_string())
+                }
+                Some(notification) => Invalidation::Channel {
+                    channel: notification.channel().to_string(),
+                    payload: notification.payload().to_string(),
+                },
+                None => Invalidation::All,
+            })
+        }
+    }
+
+    /// Results of a query with `cache_invalidate_on` cached by its `*_cached` function, keyed by
+    /// the arguments
+    /// The task of `spawn` clears the entries named by the notifications on the channel of the
+    /// query, every entry when one of the tables of the query is notified by the triggers of the
+    /// cache invalidation migrations, and every entry when its connection was lost
+    /// The cache is unbounded, it holds the result of every distinct arguments until they are
+    /// cleared
+    pub struct QueryCache<K, V> {
+        channel: &'static str,
+        /// Tables read by the query, named as in `Invalidation::Table`
+        tables: &'static [&'static str],
+        registered: std::sync::Once,
+        state: std::sync::Mutex<CacheState<K, V>>,
+    }
+
+    struct CacheState<K, V> {
+        /// Incremented by every invalidation, so that results read before it are not cached
+        generation: u64,
+        /// Cached results by arguments, with the text of the key argument notifications name
+        entries: std::collections::BTreeMap<K, (Option<String>, V)>,
+    }
+
+    impl<K: Ord + Send + 'static, V: Clone + Send + 'static> QueryCache<K, V> {
+        /// Empty cache of a query, cleared by the notifications on the channel and the changes of
+        /// the tables
+        pub const fn new(channel: &'static str, tables: &'static [&'static str]) -> Self {
+            Self {
+                channel,
+                tables,
+                registered: std::sync::Once::new(),
+                state: std::sync::Mutex::new(CacheState {
+                    generation: 0,
+                    entries: std::collections::BTreeMap::new(),
+                }),
+            }
+        }
+
+        /// Cached result of the arguments
+        pub fn get(&self, key: &K) -> Option<V> {
+            lock(&self.state)
+                .entries
+                .get(key)
+                .map(|(_, value)| value.clone())
+        }
+
+        /// Generation to pass to `insert`, read before running the query
+        /// The cache is registered with `invalidate` first, so that no invalidation happening
+        /// while the query runs is missed
+        pub fn generation(&'static self) -> u64 {
+            self.registered.call_once(|| lock(&CACHES).push(self));
+            lock(&self.state).generation
+        }
+
+        /// Cache the result of the arguments, unless an invalidation happened since the generation
+        /// was read; `invalidation_key` is the text of the argument notifications name
+        pub fn insert(&self, key: K, invalidation_key: Option<String>, generation: u64, value: V) {
+            let mut state = lock(&self.state);
+            if state.generation == generation {
+                state.entries.insert(key, (invalidation_key, value));
+            }
+        }
+
+        /// Remove every cached result
+        pub fn clear(&self) {
+            let mut state = lock(&self.state);
+            state.generation += 1;
+            state.entries.clear();
+        }
+    }
+
+    /// Cache cleared by `invalidate`
+    trait Invalidate {
+        fn invalidate(&self, invalidation: &Invalidation);
+    }
+
+    impl<K: Ord + Send + 'static, V: Clone + Send + 'static> Invalidate for QueryCache<K, V> {
+        fn invalidate(&self, invalidation: &Invalidation) {
+            match invalidation {
+                Invalidation::Channel { channel, payload } if channel == self.channel => {
+                    let mut state = lock(&self.state);
+                    state.generation += 1;
+                    if payload.is_empty() {
+                        state.entries.clear();
+                    } else {
+                        // Entries of caches without key are cleared by any notification
+                        state
+                            .entries
+                            .retain(|_, (key, _)| key.as_ref().is_some_and(|key| key != payload));
+                    }
+                }
+                invalidation if invalidation.affects(self.tables) => self.clear(),
+                _ => {}
+            }
+        }
+    }
+
+    /// Caches of the `*_cached` functions, registered when they first read their generation
+    static CACHES: std::sync::Mutex<Vec<&'static (dyn Invalidate + Sync)>> =
+        std::sync::Mutex::new(Vec::new());
+
+    fn lock<T>(mutex: &std::sync::Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+        mutex
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Clear the cached results of the `*_cached` functions affected by the change
+    pub fn invalidate(invalidation: &Invalidation) {
+        let caches = lock(&CACHES).clone();
+        for cache in caches {
+            cache.invalidate(invalidation);
+        }
+    }
+
+    /// Spawn the task shared by the caches of the `*_cached` functions, which listens on a
+    /// connection taken from the pool and clears the cached results the notifications name
+    /// Without the task, cached results are never cleared
+    pub async fn spawn(pool: &sqlx::PgPool) -> Result<tokio::task::JoinHandle<()>, sqlx::Error> {
+        let mut listener = Listener::connect(pool).await?;
+        Ok(tokio::spawn(async move {
+            loop {
+                match listener.recv().await {
+                    Ok(invalidation) => invalidate(&invalidation),
+                    // Notifications may be missed until the connection is re-established
+                    Err(_) => {
+                        invalidate(&Invalidation::All);
+                        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                    }
+                }
+            }
+        }))
+    }
+}
+
+/// Helpers for functions generated with `large_list_strategy: temp_table`
+pub(crate) mod large_list {
+    /// Encode values as the rows of a single-column COPY in text format
+    pub(crate) fn copy_data<T: std::fmt::Display>(values: &[T]) -> Vec<u8> {
+        let mut data = String::new();
+        for value in values {
+            for ch in value.to_string().chars() {
+                match ch {
+                    '\\' => data.push_str("\\\\"),
+                    '\n' => data.push_str("\\n"),
+                    '\r' => data.push_str("\\r"),
+                    '\t' => data.push_str("\\t"),
+                    ch => data.push(ch),
+                }
+            }
+            data.push('\n');
+        }
+        data.into_bytes()
+    }
+}
+
+/// Helpers for functions generated with `shard_by`
+pub(crate) mod sharding {
+    /// Maximum shard key length, keeping suffixed table names within PostgreSQL's identifier limit
+    const MAX_SHARD_KEY_LEN: usize = 32;
+
+    /// Substitute the shard key for `${shard}` in the SQL of a sharded query
+    /// Keys may only contain ASCII letters, digits and underscores, so they cannot alter the SQL
+    pub(crate) fn shard_sql(sql: &str, shard_key: &str) -> Result<String, sqlx::Error> {
+        let is_valid = !shard_key.is_empty()
+            && shard_key.len() <= MAX_SHARD_KEY_LEN
+            && shard_key
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'_');
+        if !is_valid {
+            return Err(sqlx::Error::InvalidArgument(format!(
+                "invalid shard key '{}'",
+                shard_key
+            )));
+        }
+        Ok(sql.replace("${shard}", shard_key))
+    }
+}
+
+/// Group commit batching for functions generated with `batch`
+pub mod batching {
+    use std::future::Future;
+    use std::time::Duration;
+    use tokio::sync::{mpsc, oneshot};
+
+    /// Handle to a background task which coalesces concurrent calls into batches
+    /// Clones share the task, which stops when the last handle is dropped
+    pub struct Batcher<T, R> {
+        sender: mpsc::UnboundedSender<(T, oneshot::Sender<R>)>,
+    }
+
+    impl<T, R> Clone for Batcher<T, R> {
+        fn clone(&self) -> Self {
+            Self {
+                sender: self.sender.clone(),
+            }
+        }
+    }
+
+    impl<T: Send + 'static, R: Send + 'static> Batcher<T, R> {
+        /// Spawn the batching task on the current tokio runtime
+        /// A batch is flushed when it holds `max_size` items or `max_delay` after its first item,
+        /// calls arriving while a batch is flushed are collected into the next one
+        pub fn spawn<F, Fut>(max_size: usize, max_delay: Duration, flush: F) -> Self
+        where
+            F: Fn(Vec<T>) -> Fut + Send + 'static,
+            Fut: Future<Output = Vec<R>> + Send + 'static,
+        {
+            let (sender, mut receiver) = mpsc::unbounded_channel::<(T, oneshot::Sender<R>)>();
+            tokio::spawn(async move {
+                while let Some(first) = receiver.recv().await {
+                    let deadline = tokio::time::Instant::now() + max_delay;
+                    let mut batch = vec![first];
+                    while batch.len() < max_size {
+                        match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                            Ok(Some(item)) => batch.push(item),
+                            _ => break,
+                        }
+                    }
+
+                    let (items, senders): (Vec<T>, Vec<oneshot::Sender<R>>) =
+                        batch.into_iter().unzip();
+                    let results = flush(items).await;
+                    for (sender, result) in senders.into_iter().zip(results) {
+                        // The caller may have stopped waiting for its result
+                        let _ = sender.send(result);
+                    }
+                }
+            });
+            Self { sender }
+        }
+
+        /// Queue an item and wait for the result of its batch
+        /// Returns None if the batching task is no longer running
+        pub async fn submit(&self, item: T) -> Option<R> {
+            let (sender, receiver) = oneshot::channel();
+            self.sender.send((item, sender)).ok()?;
+            receiver.await.ok()
+        }
+    }
+
+    /// Shift the positional parameters ($1, $2, ...) of a VALUES row by `offset`
+    pub(crate) fn renumber_params(row: &str, offset: usize) -> String {
+        let mut result = String::with_capacity(row.len() + 8);
+        let mut chars = row.chars().peekable();
+        while let Some(ch) = chars.next() {
+            result.push(ch);
+            if ch != '$' {
+                continue;
+            }
+            let mut digits = String::new();
+            while let Some(digit) = chars.peek().filter(|c| c.is_ascii_digit()) {
+                digits.push(*digit);
+                chars.next();
+            }
+            match digits.parse::<usize>() {
+                Ok(index) => result.push_str(&(index + offset).to_string()),
+                Err(_) => result.push_str(&digits),
+            }
+        }
+        result
+    }
+}
+
+/// PostgreSQL full-text search types for functions with tsvector or tsquery columns and parameters
+pub mod full_text_search {
+    type BoxDynError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+    const OPERAND: u8 = 1;
+    const OPERATOR: u8 = 2;
+    const OP_NOT: u8 = 1;
+    const OP_AND: u8 = 2;
+    const OP_OR: u8 = 3;
+    const OP_PHRASE: u8 = 4;
+    /// Highest lexeme position PostgreSQL stores, larger positions are clamped to it
+    const MAX_POSITION: u32 = 0x3fff;
+
+    /// A tsvector in PostgreSQL's text representation, e.g. "'fat':2 'rat':3A"
+    #[derive(Debug, Clone, PartialEq, Eq, Hash, Default, serde::Serialize, serde::Deserialize)]
+    #[serde(transparent)]
+    pub struct TsVector(pub String);
+
+    /// A tsquery in PostgreSQL's text representation, e.g. "'fat' & ( 'rat' | 'cat' )"
+    #[derive(Debug, Clone, PartialEq, Eq, Hash, Default, serde::Serialize, serde::Deserialize)]
+    #[serde(transparent)]
+    pub struct TsQuery(pub String);
+
+    impl std::fmt::Display for TsVector {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str(&self.0)
+        }
+    }
+
+    impl std::fmt::Display for TsQuery {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str(&self.0)
+        }
+    }
+
+    /// Parse free-form search input with `websearch_to_tsquery` and the given text search
+    /// configuration (e.g. "english"), for passing to queries with tsquery parameters
+    /// Quoted phrases, `or` and `-word` are supported and invalid input never fails
+    pub async fn websearch_to_tsquery<'e, E>(
+        executor: E,
+        config: &str,
+        input: &str,
+    ) -> Result<TsQuery, sqlx::Error>
+    where
+        E: sqlx::Executor<'e, Database = sqlx::Postgres>,
+    {
+        sqlx::query_scalar("SELECT websearch_to_tsquery($1::regconfig, $2)")
+            .bind(config)
+            .bind(input)
+            .fetch_one(executor)
+            .await
+    }
+
+    impl sqlx::Type<sqlx::Postgres> for TsVector {
+        fn type_info() -> sqlx::postgres::PgTypeInfo {
+            sqlx::postgres::PgTypeInfo::with_name("tsvector")
+        }
+    }
+
+    impl sqlx::postgres::PgHasArrayType for TsVector {
+        fn array_type_info() -> sqlx::postgres::PgTypeInfo {
+            sqlx::postgres::PgTypeInfo::array_of("tsvector")
+        }
+    }
+
+    impl<'r> sqlx::Decode<'r, sqlx::Postgres> for TsVector {
+        fn decode(value: sqlx::postgres::PgValueRef<'r>) -> Result<Self, BoxDynError> {
+            if value.format() == sqlx::postgres::PgValueFormat::Text {
+                return Ok(Self(value.as_str()?.to_string()));
+            }
+            let mut bytes = value.as_bytes()?;
+            let mut lexemes = Vec::new();
+            for _ in 0..read_u32(&mut bytes)? {
+                let mut lexeme = quote_lexeme(read_cstr(&mut bytes)?);
+                for i in 0..read_u16(&mut bytes)? {
+                    let position = read_u16(&mut bytes)?;
+                    lexeme.push(if i == 0 { ':' } else { ',' });
+                    lexeme.push_str(&(u32::from(position) & MAX_POSITION).to_string());
+                    match position >> 14 {
+                        3 => lexeme.push('A'),
+                        2 => lexeme.push('B'),
+                        1 => lexeme.push('C'),
+                        _ => {}
+                    }
+                }
+                lexemes.push(lexeme);
+            }
+            Ok(Self(lexemes.join(" ")))
+        }
+    }
+
+    /// Write a tsvector in PostgreSQL's binary format
+    /// The value is only written once it is parsed, so nothing is written when it is invalid
+    fn encode_tsvector(
+        text: &str,
+        buf: &mut sqlx::postgres::PgArgumentBuffer,
+    ) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        // PostgreSQL expects sorted unique lexemes, each with ascending unique positions
+        let mut lexemes: std::collections::BTreeMap<String, std::collections::BTreeMap<u32, u16>> =
+            std::collections::BTreeMap::new();
+        let mut chars = text.chars().peekable();
+        loop {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            if chars.peek().is_none() {
+                break;
+            }
+            let lexeme = parse_lexeme(&mut chars, |c| c.is_whitespace() || c == ':')?;
+            let positions = lexemes.entry(lexeme).or_default();
+            if chars.next_if_eq(&':').is_none() {
+                continue;
+            }
+            loop {
+                let mut digits = String::new();
+                while let Some(digit) = chars.next_if(|c| c.is_ascii_digit()) {
+                    digits.push(digit);
+                }
+                let position = digits
+                    .parse::<u32>()
+                    .map_err(|_| format!("invalid lexeme position in tsvector \"{}\"", text))?
+                    .clamp(1, MAX_POSITION);
+                let weight: u16 = match chars.next_if(|c| "ABCDabcd".contains(*c)) {
+                    Some('A' | 'a') => 3,
+                    Some('B' | 'b') => 2,
+                    Some('C' | 'c') => 1,
+                    _ => 0,
+                };
+                positions.insert(position, weight << 14 | position as u16);
+                if chars.next_if_eq(&',').is_none() {
+                    break;
+                }
+            }
+        }
+
+        buf.extend_from_slice(&(lexemes.len() as u32).to_be_bytes());
+        for (lexeme, positions) in lexemes {
+            buf.extend_from_slice(lexeme.as_bytes());
+            buf.push(0);
+            buf.extend_from_slice(&(positions.len() as u16).to_be_bytes());
+            for position in positions.values() {
+                buf.extend_from_slice(&position.to_be_bytes());
+            }
+        }
+        Ok(sqlx::encode::IsNull::No)
+    }
+
+    impl sqlx::Type<sqlx::Postgres> for TsQuery {
+        fn type_info() -> sqlx::postgres::PgTypeInfo {
+            sqlx::postgres::PgTypeInfo::with_name("tsquery")
+        }
+    }
+
+    impl sqlx::postgres::PgHasArrayType for TsQuery {
+        fn array_type_info() -> sqlx::postgres::PgTypeInfo {
+            sqlx::postgres::PgTypeInfo::array_of("tsquery")
+        }
+    }
+
+    impl<'r> sqlx::Decode<'r, sqlx::Postgres> for TsQuery {
+        fn decode(value: sqlx::postgres::PgValueRef<'r>) -> Result<Self, BoxDynError> {
+            if value.format() == sqlx::postgres::PgValueFormat::Text {
+                return Ok(Self(value.as_str()?.to_string()));
+            }
+            let mut bytes = value.as_bytes()?;
+            let mut items = Vec::new();
+            for _ in 0..read_u32(&mut bytes)? {
+                items.push(read_query_item(&mut bytes)?);
+            }
+
+            // Reversed, the items are in postfix order, so the text is built on a stack of the
+            // operands with the binding strength of their top operator, without recursion
+            let mut operands: Vec<(String, u8)> = Vec::new();
+            for item in items.into_iter().rev() {
+                let operand = match item {
+                    QueryItem::Operand {
+                        lexeme,
+                        weight,
+                        prefix,
+                    } => (operand_text(&lexeme, weight, prefix), 5),
+                    QueryItem::Operator {
+                        operator: OP_NOT, ..
+                    } => {
+                        let (operand, strength) =
+                            operands.pop().ok_or("missing tsquery operand")?;
+                        (format!("!{}", parenthesize(operand, strength < 4)), 4)
+                    }
+                    QueryItem::Operator { operator, distance } => {
+                        let (right, right_strength) =
+                            operands.pop().ok_or("missing tsquery operand")?;
+                        let (left, left_strength) =
+                            operands.pop().ok_or("missing tsquery operand")?;
+                        let (symbol, strength) = match operator {
+                            OP_AND => ("&".to_string(), 2),
+                            OP_OR => ("|".to_string(), 1),
+                            _ if distance == 1 => ("<->".to_string(), 3),
+                            _ => (format!("<{}>", distance), 3),
+                        };
+                        let text = format!(
+                            "{} {} {}",
+                            parenthesize(left, left_strength < strength),
+                            symbol,
+                            // `&` and `|` are associative, phrase operators are not
+                            parenthesize(
+                                right,
+                                right_strength < strength
+                                    || (operator == OP_PHRASE && right_strength == strength)
+                            )
+                        );
+                        (text, strength)
+                    }
+                };
+                operands.push(operand);
+            }
+            match (operands.pop(), operands.is_empty()) {
+                (Some((text, _)), true) => Ok(Self(text)),
+                (None, _) => Ok(Self(String::new())),
+                (Some(_), false) => Err("tsquery operand without operator".into()),
+            }
+        }
+    }
+
+    /// Write a tsquery in PostgreSQL's binary format
+    /// The value is only written once it is parsed, so nothing is written when it is invalid
+    fn encode_tsquery(
+        text: &str,
+        buf: &mut sqlx::postgres::PgArgumentBuffer,
+    ) -> Result<sqlx::encode::IsNull, BoxDynError> {
+        // PostgreSQL accepts empty tsqueries as text but rejects them in the binary format
+        if text.trim().is_empty() {
+            return Err("tsquery without lexemes".into());
+        }
+        let items = parse_query(text)?;
+        buf.extend_from_slice(&(items.len() as u32).to_be_bytes());
+        for item in items.iter().rev() {
+            item.write(buf);
+        }
+        Ok(sqlx::encode::IsNull::No)
+    }
+
+    type Chars<'a> = std::iter::Peekable<std::str::Chars<'a>>;
+
+    /// Item of a tsquery, which the wire format lists in prefix order with the right operand first
+    enum QueryItem {
+        Operand {
+            lexeme: String,
+            weight: u8,
+            prefix: bool,
+        },
+        Operator {
+            operator: u8,
+            distance: u16,
+        },
+    }
+
+    impl QueryItem {
+        fn write(&self, buf: &mut Vec<u8>) {
+            match self {
+                QueryItem::Operand {
+                    lexeme,
+                    weight,
+                    prefix,
+                } => {
+                    buf.extend_from_slice(&[OPERAND, *weight, u8::from(*prefix)]);
+                    buf.extend_from_slice(lexeme.as_bytes());
+                    buf.push(0);
+                }
+                QueryItem::Operator { operator, distance } => {
+                    buf.extend_from_slice(&[OPERATOR, *operator]);
+                    if *operator == OP_PHRASE {
+                        buf.extend_from_slice(&distance.to_be_bytes());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Parse a tsquery into its items in postfix order, with the shunting-yard algorithm so that
+    /// deeply nested values cannot overflow the stack
+    /// Operators from loosest to tightest binding: `|`, `&`, `<->` / `<N>`, `!`
+    fn parse_query(text: &str) -> Result<Vec<QueryItem>, BoxDynError> {
+        let mut chars = text.chars().peekable();
+        let mut items = Vec::new();
+        // Pending operators with their distance and binding strength, None for open parentheses
+        let mut operators: Vec<Option<(u8, u16, u8)>> = Vec::new();
+        let mut expects_operand = true;
+        while let Some(c) = skip_whitespace(&mut chars) {
+            if expects_operand {
+                match c {
+                    '!' => {
+                        chars.next();
+                        operators.push(Some((OP_NOT, 0, 4)));
+                    }
+                    '(' => {
+                        chars.next();
+                        operators.push(None);
+                    }
+                    _ => {
+                        items.push(parse_operand(&mut chars)?);
+                        expects_operand = false;
+                    }
+                }
+                continue;
+            }
+            chars.next();
+            let (operator, distance, strength) = match c {
+                ')' => {
+                    loop {
+                        match operators.pop() {
+                            Some(Some((operator, distance, _))) => {
+                                items.push(QueryItem::Operator { operator, distance })
+                            }
+                            Some(None) => break,
+                            None => return Err("unbalanced parenthesis in tsquery".into()),
+                        }
+                    }
+                    continue;
+                }
+                '|' => (OP_OR, 0, 1),
+                '&' => (OP_AND, 0, 2),
+                '<' => (OP_PHRASE, parse_distance(&mut chars)?, 3),
+                _ => return Err(format!("syntax error in tsquery \"{}\"", text).into()),
+            };
+            // Binary operators are left-associative, so the pending operators binding at least as
+            // tightly apply first
+            while let Some(Some((pending, pending_distance, pending_strength))) =
+                operators.last().copied()
+            {
+                if pending_strength < strength {
+                    break;
+                }
+                operators.pop();
+                items.push(QueryItem::Operator {
+                    operator: pending,
+                    distance: pending_distance,
+                });
+            }
+            operators.push(Some((operator, distance, strength)));
+            expects_operand = true;
+        }
+        if expects_operand {
+            return Err("unexpected end of full-text search value".into());
+        }
+        while let Some(pending) = operators.pop() {
+            let Some((operator, distance, _)) = pending else {
+                return Err("unbalanced parenthesis in tsquery".into());
+            };
+            items.push(QueryItem::Operator { operator, distance });
+        }
+        Ok(items)
+    }
+
+    /// Parse the distance of a phrase operator after its `<`, `-` for `<->`
+    fn parse_distance(chars: &mut Chars) -> Result<u16, BoxDynError> {
+        let distance = if chars.next_if_eq(&'-').is_some() {
+            1
+        } else {
+            let mut digits = String::new();
+            while let Some(digit) = chars.next_if(|c| c.is_ascii_digit()) {
+                digits.push(digit);
+            }
+            digits
+                .parse::<u16>()
+                .map_err(|_| "invalid distance in tsquery phrase operator")?
+        };
+        if chars.next_if_eq(&'>').is_none() {
+            return Err("unterminated tsquery phrase operator".into());
+        }
+        Ok(distance)
+    }
+
+    /// Parse a lexeme with its optional `:*` prefix flag and weights
+    fn parse_operand(chars: &mut Chars) -> Result<QueryItem, BoxDynError> {
+        let lexeme = parse_lexeme(chars, |c| c.is_whitespace() || ":&|!()<".contains(c))?;
+        let (mut weight, mut prefix) = (0, false);
+        if chars.next_if_eq(&':').is_some() {
+            while let Some(flag) = chars.next_if(|c| "*ABCDabcd".contains(*c)) {
+                match flag.to_ascii_uppercase() {
+                    '*' => prefix = true,
+                    'A' => weight |= 8,
+                    'B' => weight |= 4,
+                    'C' => weight |= 2,
+                    _ => weight |= 1,
+                }
+            }
+        }
+        Ok(QueryItem::Operand {
+            lexeme,
+            weight,
+            prefix,
+        })
+    }
+
+    /// Read a tsquery item from the wire format
+    fn read_query_item(bytes: &mut &[u8]) -> Result<QueryItem, BoxDynError> {
+        if read_u8(bytes)? == OPERAND {
+            let weight = read_u8(bytes)?;
+            let prefix = read_u8(bytes)? != 0;
+            let lexeme = read_cstr(bytes)?.to_string();
+            return Ok(QueryItem::Operand {
+                lexeme,
+                weight,
+                prefix,
+            });
+        }
+        let operator = read_u8(bytes)?;
+        let distance = match operator {
+            OP_NOT | OP_AND | OP_OR => 0,
+            OP_PHRASE => read_u16(bytes)?,
+            _ => return Err(format!("unknown tsquery operator {}", operator).into()),
+        };
+        Ok(QueryItem::Operator { operator, distance })
+    }
+
+    /// Text of a tsquery operand the way PostgreSQL prints it, e.g. "'rat':*AB"
+    fn operand_text(lexeme: &str, weight: u8, prefix: bool) -> String {
+        let mut text = quote_lexeme(lexeme);
+        if prefix || weight != 0 {
+            text.push(':');
+            if prefix {
+                text.push('*');
+            }
+            for (bit, letter) in [(8, 'A'), (4, 'B'), (2, 'C'), (1, 'D')] {
+                if weight & bit != 0 {
+                    text.push(letter);
+                }
+            }
+        }
+        text
+    }
+
+    fn parenthesize(text: String, needed: bool) -> String {
+        if needed {
+            format!("( {} )", text)
+        } else {
+            text
+        }
+    }
+
+    /// Parse a quoted ('it''s') or unquoted lexeme, with backslash escapes
+    fn parse_lexeme(
+        chars: &mut Chars,
+        is_delimiter: impl Fn(char) -> bool,
+    ) -> Result<String, BoxDynError> {
+        let mut lexeme = String::new();
+        if chars.next_if_eq(&'\'').is_some() {
+            loop {
+                match chars.next() {
+                    Some('\'') if chars.next_if_eq(&'\'').is_some() => lexeme.push('\''),
+                    Some('\'') => break,
+                    Some('\\') => lexeme.push(chars.next().ok_or("unterminated escape in lexeme")?),
+                    Some(c) => lexeme.push(c),
+                    None => return Err("unterminated quoted lexeme".into()),
+                }
+            }
+        } else {
+            while let Some(c) = chars.next_if(|c| !is_delimiter(*c)) {
+                if c == '\\' {
+                    lexeme.push(chars.next().ok_or("unterminated escape in lexeme")?);
+                } else {
+                    lexeme.push(c);
+                }
+            }
+        }
+        if lexeme.is_empty() {
+            return Err("empty lexeme in full-text search value".into());
+        }
+        Ok(lexeme)
+    }
+
+    /// Quote a lexeme the way PostgreSQL prints it
+    fn quote_lexeme(lexeme: &str) -> String {
+        format!("'{}'", lexeme.replace('\\', "\\\\").replace('\'', "''"))
+    }
+
+    fn skip_whitespace(chars: &mut Chars) -> Option<char> {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        chars.peek().copied()
+    }
+
+    fn read_bytes<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8], BoxDynError> {
+        if bytes.len() < len {
+            return Err("unexpected end of full-text search value".into());
+        }
+        let (head, tail) = bytes.split_at(len);
+        *bytes = tail;
+        Ok(head)
+    }
+
+    fn read_u8(bytes: &mut &[u8]) -> Result<u8, BoxDynError> {
+        Ok(read_bytes(bytes, 1)?[0])
+    }
+
+    fn read_u16(bytes: &mut &[u8]) -> Result<u16, BoxDynError> {
+        Ok(u16::from_be_bytes(read_bytes(bytes, 2)?.try_into()?))
+    }
+
+    fn read_u32(bytes: &mut &[u8]) -> Result<u32, BoxDynError> {
+        Ok(u32::from_be_bytes(read_bytes(bytes, 4)?.try_into()?))
+    }
+
+    fn read_cstr<'a>(bytes: &mut &'a [u8]) -> Result<&'a str, BoxDynError> {
+        let len = bytes
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or("unterminated lexeme")?;
+        let lexeme = read_bytes(bytes, len + 1)?;
+        Ok(std::str::from_utf8(&lexeme[..len])?)
+    }
+
+    impl<'q> sqlx::Encode<'q, sqlx::Postgres> for TsVector {
+        fn encode_by_ref(
+            &self,
+            buf: &mut sqlx::postgres::PgArgumentBuffer,
+        ) -> Result<sqlx::encode::IsNull, BoxDynError> {
+            encode_tsvector(&self.0, buf)
+        }
+    }
+
+    impl<'q> sqlx::Encode<'q, sqlx::Postgres> for TsQuery {
+        fn encode_by_ref(
+            &self,
+            buf: &mut sqlx::postgres::PgArgumentBuffer,
+        ) -> Result<sqlx::encode::IsNull, BoxDynError> {
+            encode_tsquery(&self.0, buf)
+        }
+    }
+}
+
+/// Runtime of the `*_blocking` wrappers generated with `blocking`, for callers without tokio
+#[cfg(feature = "blocking")]
+pub mod blocking {
+    use std::future::Future;
+    use std::sync::OnceLock;
+
+    static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+
+    /// Runtime the blocking wrappers run on, started on first use
+    /// Connections used with the wrappers must be opened on it too, e.g. with
+    /// `block_on(sqlx::PgPool::connect(url))`, as they are driven by the runtime they were opened on
+    pub fn runtime() -> &'static tokio::runtime::Runtime {
+        RUNTIME.get_or_init(|| {
+            tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build()
+                .expect("failed to start the runtime of the blocking wrappers")
+        })
+    }
+
+    /// Run a future to completion on the shared runtime
+    /// Panics when called from async code running on a tokio runtime
+    pub fn block_on<F: Future>(future: F) -> F::Output {
+        runtime().block_on(future)
+    }
+}
+
+/// Server-side cancellation of the `*_cancellable` variants generated with `cancellable`
+#[cfg(feature = "cancellation")]
+pub mod cancellation {
+    /// Server process running the queries of a connection, cancelled with `pg_cancel_backend`
+    pub async fn backend_pid(
+        connection: &mut sqlx::pool::PoolConnection<sqlx::Postgres>,
+    ) -> Result<Option<i32>, sqlx::Error> {
+        sqlx::query_scalar("SELECT pg_backend_pid()")
+            .fetch_one(&mut **connection)
+            .await
+            .map(Some)
+    }
+
+    /// Cancel the query running on a connection and close the connection, which is left in
+    /// the middle of the query, instead of returning it to the pool
+    /// Runs in the background so that the cancelled function returns right away
+    pub fn cancel(
+        pool: &sqlx::PgPool,
+        mut connection: sqlx::pool::PoolConnection<sqlx::Postgres>,
+        backend_pid: Option<i32>,
+    ) {
+        connection.close_on_drop();
+        let pool = pool.clone();
+        tokio::spawn(async move {
+            if let Some(backend_pid) = backend_pid {
+                let _ = sqlx::query("SELECT pg_cancel_backend($1)")
+                    .bind(backend_pid)
+                    .execute(&pool)
+                    .await;
+            }
+            drop(connection);
+        });
+    }
+}
+
+/// Checked conversions of NUMERIC columns, for `numeric_as: i64`
+pub mod numeric {
+    use rust_decimal::prelude::ToPrimitive;
+
+    /// Convert a decoded NUMERIC value to i64, failing with a column decode error holding
+    /// `NumericOverflow` when it is not an integer or out of range
+    pub fn to_i64(column: &'static str, value: rust_decimal::Decimal) -> Result<i64, sqlx::Error> {
+        value
+            .fract()
+            .is_zero()
+            .then(|| value.to_i64())
+            .flatten()
+            .ok_or_else(|| sqlx::Error::ColumnDecode {
+                index: column.to_string(),
+                source: Box::new(super::NumericOverflow {
+                    column,
+                    value: value.to_string(),
+                }),
+            })
+    }
+}
+
+/// Amounts of money, for the NUMERIC columns and parameters of `currency_columns`
+pub mod money {
+    /// An amount of money in a currency, stored as NUMERIC
+    /// Columns are decoded with the currency of their `currency_columns` entry, and parameters in
+    /// any other currency are rejected before the query runs
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize)]
+    pub struct Money {
+        pub amount: rust_decimal::Decimal,
+        /// ISO 4217 currency code, e.g. "EUR"
+        pub currency: &'static str,
+    }
+
+    impl Money {
+        pub fn new(amount: rust_decimal::Decimal, currency: &'static str) -> Self {
+            Self { amount, currency }
+        }
+    }
+
+    impl std::fmt::Display for Money {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "{} {}", self.amount, self.currency)
+        }
+    }
+
+    impl sqlx::Type<sqlx::Postgres> for Money {
+        fn type_info() -> sqlx::postgres::PgTypeInfo {
+            <rust_decimal::Decimal as sqlx::Type<sqlx::Postgres>>::type_info()
+        }
+    }
+
+    impl sqlx::postgres::PgHasArrayType for Money {
+        fn array_type_info() -> sqlx::postgres::PgTypeInfo {
+            <rust_decimal::Decimal as sqlx::postgres::PgHasArrayType>::array_type_info()
+        }
+    }
+
+    impl<'q> sqlx::Encode<'q, sqlx::Postgres> for Money {
+        fn encode_by_ref(
+            &self,
+            buf: &mut sqlx::postgres::PgArgumentBuffer,
+        ) -> Result<sqlx::encode::IsNull, Box<dyn std::error::Error + Send + Sync + 'static>>
+        {
+            sqlx::Encode::<sqlx::Postgres>::encode_by_ref(&self.amount, buf)
+        }
+    }
+}
+
+/// Conversions of flag codes, for the CHAR(1) and "char" columns and parameters of `char_flags`
+pub mod char_flags {
+    /// Code stored in a flag column, the text of CHAR(1) columns or the byte of "char" columns
+    pub trait Code: std::fmt::Debug {
+        fn matches(&self, code: &str) -> bool;
+    }
+
+    impl Code for String {
+        fn matches(&self, code: &str) -> bool {
+            // CHAR(n) values are padded with spaces
+            self.trim_end() == code
+        }
+    }
+
+    impl Code for i8 {
+        fn matches(&self, code: &str) -> bool {
+            code.as_bytes() == [*self as u8]
+        }
+    }
+
+    /// Value of the code of a column, failing with `ColumnDecode` for unknown codes
+    pub fn decode<C: Code, T: Copy>(
+        column: &str,
+        code: C,
+        values: &[(&str, T)],
+    ) -> Result<T, sqlx::Error> {
+        values
+            .iter()
+            .find(|(value_code, _)| code.matches(value_code))
+            .map(|(_, value)| *value)
+            .ok_or_else(|| sqlx::Error::ColumnDecode {
+                index: column.to_string(),
+                source: format!("unknown flag code {:?}", code).into(),
+            })
+    }
+
+    /// Code of a value bound to a CHAR(1) or text parameter
+    pub fn encode<T: PartialEq>(value: T, values: &[(&'static str, T)]) -> &'static str {
+        values
+            .iter()
+            .find(|(_, code_value)| *code_value == value)
+            .map_or("", |(code, _)| code)
+    }
+
+    /// Code of a value bound to a "char" parameter
+    pub fn encode_byte<T: PartialEq>(value: T, values: &[(&'static str, T)]) -> i8 {
+        encode(value, values).bytes().next().unwrap_or(0) as i8
+    }
+}
+
+/// Conversions of array elements, for the text array columns and parameters of `name[]` keys
+/// of `types`
+pub mod array_elements {
+    /// Elements of a text array column, failing with `ColumnDecode` for unparsable elements
+    pub fn decode<T>(column: &str, values: Vec<String>) -> Result<Vec<T>, sqlx::Error>
+    where
+        T: std::str::FromStr,
+        T::Err: std::fmt::Display,
+    {
+        values
+            .into_iter()
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|e: T::Err| sqlx::Error::ColumnDecode {
+                        index: column.to_string(),
+                        source: format!("invalid element {:?}: {}", value, e).into(),
+                    })
+            })
+            .collect()
+    }
+
+    /// Text of the elements bound to a text array parameter
+    pub fn encode<T: std::fmt::Display>(values: &[T]) -> Vec<String> {
+        values.iter().map(ToString::to_string).collect()
+    }
+}
+
+/// Formatting of the parameters recorded in spans and slow query events, cut to the telemetry
+/// `max_param_length` and `max_param_elements` of the query
+pub mod telemetry {
+    use std::fmt::Write;
+
+    /// Writer keeping the first `remaining` characters of the formatted value
+    struct Truncated {
+        text: String,
+        remaining: usize,
+        truncated: bool,
+    }
+
+    impl Write for Truncated {
+        fn write_str(&mut self, s: &str) -> std::fmt::Result {
+            for ch in s.chars() {
+                if self.remaining == 0 {
+                    self.truncated = true;
+                    // Stops formatting the rest of the value
+                    return Err(std::fmt::Error);
+                }
+                self.text.push(ch);
+                self.remaining -= 1;
+            }
+            Ok(())
+        }
+    }
+
+    /// Debug representation of a parameter, cut after `max_length` characters followed by "..."
+    pub fn param<T: std::fmt::Debug + ?Sized>(value: &T, max_length: Option<usize>) -> String {
+        let mut writer = Truncated {
+            text: String::new(),
+            remaining: max_length.unwrap_or(usize::MAX),
+            truncated: false,
+        };
+        let _ = write!(writer, "{:?}", value);
+        if writer.truncated {
+            writer.text.push_str("...");
+        }
+        writer.text
+    }
+
+    /// Debug representation of the first `max_elements` elements of a list parameter, followed
+    /// by the number of omitted elements, cut after `max_length` characters
+    pub fn list_param<T: std::fmt::Debug>(
+        values: &[T],
+        max_elements: Option<usize>,
+        max_length: Option<usize>,
+    ) -> String {
+        match max_elements {
+            Some(max_elements) if values.len() > max_elements => param(
+                &format_args!(
+                    "{:?} and {} more",
+                    &values[..max_elements],
+                    values.len() - max_elements
+                ),
+                max_length,
+            ),
+            _ => param(values, max_length),
+        }
+    }
+}
+// AUTOMODEL_CONTENT_HASH: 6e010b24e814141a
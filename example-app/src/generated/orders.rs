@@ -0,0 +1,187 @@
+// This file was automatically generated by AutoModel. Do not edit manually.
+
+#[allow(unused_imports)]
+pub use super::{classify_error, DbErrorKind};
+use sqlx::Row;
+
+/// Tables read or modified by the queries of this module
+pub const TABLES: &[&str] = &["invoices", "orders_${shard}"];
+
+/// Prepare every query of this module on the connection, e.g. in `after_connect` of the pool
+/// The statements stay cached on the connection; failed queries no longer match the schema
+pub async fn warmup(executor: &mut sqlx::PgConnection) -> Vec<super::WarmupStatus> {
+    const QUERIES: &[(&str, &[&str], &[&str])] = &[(
+        "get_invoices_from",
+        &[r"SELECT id, customer, amount, fee
+        FROM public.invoices
+        WHERE amount >= $1
+        ORDER BY id"],
+        &["id", "customer", "amount", "fee"],
+    )];
+    let mut report = Vec::with_capacity(QUERIES.len());
+    for &(query, variants, columns) in QUERIES {
+        let result = super::warmup_query(&mut *executor, variants, columns).await;
+        report.push(super::WarmupStatus { query, result });
+    }
+    report
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GetTenantOrdersSinceItem {
+    pub id: i64,
+    pub customer_email: String,
+    pub total_cents: i64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Get the orders of a tenant created after the given time from the tenant's shard table
+///
+/// Tables: `orders_${shard}`
+///
+/// Query Plan:
+/// Sort
+///   Sort Key: created_at DESC
+///   ->  Bitmap Heap Scan on orders_0
+///         Recheck Cond: (created_at > '1970-01-01 00:00:00+00'::timestamp with time zone)
+///         ->  Bitmap Index Scan on idx_orders_0_created_at
+///               Index Cond: (created_at > '1970-01-01 00:00:00+00'::timestamp with time zone)
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "SELECT id, customer_email, total_cents, created_at\nFROM public.orders_${shard}\nWHERE created_at > #{since}\nORDER BY created_at DESC"
+    )
+)]
+pub async fn get_tenant_orders_since(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    tenant: &str,
+    since: chrono::DateTime<chrono::Utc>,
+) -> Result<Vec<GetTenantOrdersSinceItem>, super::ErrorReadOnly> {
+    let sql = super::sharding::shard_sql(
+        r"SELECT id, customer_email, total_cents, created_at
+        FROM public.orders_${shard}
+        WHERE created_at > $1
+        ORDER BY created_at DESC",
+        tenant,
+    )?;
+    let query = sqlx::query(&sql);
+    let query = query.bind(since);
+    let rows = query.fetch_all(executor).await?;
+    let result: Result<Vec<_>, sqlx::Error> = rows
+        .iter()
+        .map(|row| {
+            Ok(GetTenantOrdersSinceItem {
+                id: row.try_get::<i64, _>("id")?,
+                customer_email: row.try_get::<String, _>("customer_email")?,
+                total_cents: row.try_get::<i64, _>("total_cents")?,
+                created_at: row.try_get::<chrono::DateTime<chrono::Utc>, _>("created_at")?,
+            })
+        })
+        .collect();
+    result.map_err(Into::into)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GetTenantOrderTotalsItem {
+    pub order_count: Option<i64>,
+    pub revenue_cents: Option<i64>,
+}
+
+/// Count the orders of a tenant and sum their totals, checking the sum fits in i64
+///
+/// Tables: `orders_${shard}`
+///
+/// Query Plan:
+/// Aggregate
+///   ->  Seq Scan on orders_0
+/// JIT:
+///   Functions: 3
+///   Options: Inlining true, Optimization true, Expressions true, Deforming true
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "SELECT COUNT(*) AS order_count, SUM(total_cents) AS revenue_cents\nFROM public.orders_${shard}"
+    )
+)]
+pub async fn get_tenant_order_totals(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    tenant: &str,
+) -> Result<GetTenantOrderTotalsItem, super::ErrorReadOnly> {
+    let sql = super::sharding::shard_sql(
+        r"SELECT COUNT(*) AS order_count, SUM(total_cents) AS revenue_cents
+        FROM public.orders_${shard}",
+        tenant,
+    )?;
+    let query = sqlx::query(&sql);
+    let row = query.fetch_one(executor).await?;
+    let result: Result<_, sqlx::Error> = (|| {
+        Ok(GetTenantOrderTotalsItem {
+            order_count: row.try_get::<Option<i64>, _>("order_count")?,
+            revenue_cents: row
+                .try_get::<Option<rust_decimal::Decimal>, _>("revenue_cents")?
+                .map(|v| super::numeric::to_i64("revenue_cents", v))
+                .transpose()?,
+        })
+    })();
+    result.map_err(Into::into)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GetInvoicesFromItem {
+    pub id: i32,
+    pub customer: String,
+    pub amount: super::money::Money,
+    pub fee: sqlx::postgres::types::PgMoney,
+}
+
+/// Get the invoices of at least the given amount, amounts are in euros and rejected in any other currency
+///
+/// Tables: `invoices`
+///
+/// Query Plan:
+/// Index Scan using invoices_pkey on invoices
+///   Filter: (amount >= '0'::numeric)
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "SELECT id, customer, amount, fee\nFROM public.invoices\nWHERE amount >= #{min_amount}\nORDER BY id"
+    )
+)]
+pub async fn get_invoices_from(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    min_amount: super::money::Money,
+) -> Result<Vec<GetInvoicesFromItem>, super::ErrorReadOnly> {
+    if let Some(amount) = std::iter::once(&min_amount).find(|amount| amount.currency != "EUR") {
+        return Err(sqlx::Error::InvalidArgument(format!(
+            "min_amount is in {}, not EUR",
+            amount.currency
+        ))
+        .into());
+    }
+    let query = sqlx::query(
+        r"SELECT id, customer, amount, fee
+        FROM public.invoices
+        WHERE amount >= $1
+        ORDER BY id",
+    );
+    let query = query.bind(min_amount);
+    let rows = query.fetch_all(executor).await?;
+    let result: Result<Vec<_>, sqlx::Error> = rows
+        .iter()
+        .map(|row| {
+            Ok(GetInvoicesFromItem {
+                id: row.try_get::<i32, _>("id")?,
+                customer: row.try_get::<String, _>("customer")?,
+                amount: super::money::Money::new(
+                    row.try_get::<rust_decimal::Decimal, _>("amount")?,
+                    "EUR",
+                ),
+                fee: row.try_get::<sqlx::postgres::types::PgMoney, _>("fee")?,
+            })
+        })
+        .collect();
+    result.map_err(Into::into)
+}
+// AUTOMODEL_CONTENT_HASH: 810bf05076857520
@@ -1,8 +1,46 @@
 // This file was automatically generated by AutoModel. Do not edit manually.
 
+#[allow(unused_imports)]
+pub use super::{classify_error, DbErrorKind};
+
+/// Tables read or modified by the queries of this module
+pub const TABLES: &[&str] = &[];
+
+/// Prepare every query of this module on the connection, e.g. in `after_connect` of the pool
+/// The statements stay cached on the connection; failed queries no longer match the schema
+pub async fn warmup(executor: &mut sqlx::PgConnection) -> Vec<super::WarmupStatus> {
+    const QUERIES: &[(&str, &[&str], &[&str])] = &[(
+        "create_users_table",
+        &[r"CREATE TABLE IF NOT EXISTS public.users (
+         id SERIAL PRIMARY KEY,
+         name TEXT NOT NULL,
+         email TEXT UNIQUE NOT NULL,
+         age INTEGER,
+         profile JSONB,
+         created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+         updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+        )"],
+        &[],
+    )];
+    let mut report = Vec::with_capacity(QUERIES.len());
+    for &(query, variants, columns) in QUERIES {
+        let result = super::warmup_query(&mut *executor, variants, columns).await;
+        report.push(super::WarmupStatus { query, result });
+    }
+    report
+}
+
 /// Create the public.users table with all necessary fields
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "CREATE TABLE IF NOT EXISTS public.users (\n  id SERIAL PRIMARY KEY,\n  name TEXT NOT NULL,\n  email TEXT UNIQUE NOT NULL,\n  age INTEGER,\n  profile JSONB,\n  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),\n  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()\n)"))]
-pub async fn create_users_table(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>) -> Result<(), super::ErrorReadOnly> {
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "CREATE TABLE IF NOT EXISTS public.users (\n  id SERIAL PRIMARY KEY,\n  name TEXT NOT NULL,\n  email TEXT UNIQUE NOT NULL,\n  age INTEGER,\n  profile JSONB,\n  created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),\n  updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()\n)"
+    )
+)]
+pub async fn create_users_table(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+) -> Result<(), super::ErrorReadOnly> {
     let query = sqlx::query(
         r"CREATE TABLE IF NOT EXISTS public.users (
          id SERIAL PRIMARY KEY,
@@ -12,9 +50,9 @@ pub async fn create_users_table(executor: impl sqlx::Executor<'_, Database = sql
          profile JSONB,
          created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
          updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
-        )"
+        )",
     );
     query.execute(executor).await?;
     Ok(())
 }
-
+// AUTOMODEL_CONTENT_HASH: 658d664fc42c056b
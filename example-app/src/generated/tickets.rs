@@ -0,0 +1,545 @@
+// This file was automatically generated by AutoModel. Do not edit manually.
+
+#[allow(unused_imports)]
+pub use super::{classify_error, DbErrorKind};
+use sqlx::Row;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum TicketsPriority {
+    Low,
+    Normal,
+    High,
+}
+
+impl std::str::FromStr for TicketsPriority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "low" => Ok(TicketsPriority::Low),
+            "normal" => Ok(TicketsPriority::Normal),
+            "high" => Ok(TicketsPriority::High),
+            _ => Err(format!("Invalid TicketsPriority variant: {}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for TicketsPriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            TicketsPriority::Low => "low",
+            TicketsPriority::Normal => "normal",
+            TicketsPriority::High => "high",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl sqlx::Type<sqlx::Postgres> for TicketsPriority {
+    fn type_info() -> sqlx::postgres::PgTypeInfo {
+        sqlx::postgres::PgTypeInfo::with_name("text")
+    }
+
+    fn compatible(ty: &sqlx::postgres::PgTypeInfo) -> bool {
+        *ty == Self::type_info() || *ty == sqlx::postgres::PgTypeInfo::with_name("text")
+    }
+}
+
+impl<'r> sqlx::Decode<'r, sqlx::Postgres> for TicketsPriority {
+    fn decode(
+        value: sqlx::postgres::PgValueRef<'r>,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        let s = <&str as sqlx::Decode<sqlx::Postgres>>::decode(value)?;
+        s.parse().map_err(|e| {
+            Box::new(std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+                as Box<dyn std::error::Error + Send + Sync + 'static>
+        })
+    }
+}
+
+impl<'q> sqlx::Encode<'q, sqlx::Postgres> for TicketsPriority {
+    fn encode_by_ref(
+        &self,
+        buf: &mut sqlx::postgres::PgArgumentBuffer,
+    ) -> Result<sqlx::encode::IsNull, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        <&str as sqlx::Encode<sqlx::Postgres>>::encode(&self.to_string(), buf)
+    }
+}
+
+impl sqlx::postgres::PgHasArrayType for TicketsPriority {
+    fn array_type_info() -> sqlx::postgres::PgTypeInfo {
+        sqlx::postgres::PgTypeInfo::array_of("text")
+    }
+
+    fn array_compatible(ty: &sqlx::postgres::PgTypeInfo) -> bool {
+        *ty == Self::array_type_info() || *ty == sqlx::postgres::PgTypeInfo::array_of("text")
+    }
+}
+
+/// Values allowed by the CHECK constraint of tickets.state
+pub const TICKETS_STATE_VALUES: &[&str] = &["open", "waiting", "closed"];
+
+/// Tables read or modified by the queries of this module
+pub const TABLES: &[&str] = &["tickets", "tickets_archive"];
+
+/// Prepare every query of this module on the connection, e.g. in `after_connect` of the pool
+/// The statements stay cached on the connection; failed queries no longer match the schema
+pub async fn warmup(executor: &mut sqlx::PgConnection) -> Vec<super::WarmupStatus> {
+    const QUERIES: &[(&str, &[&str], &[&str])] = &[
+        (
+            "insert_ticket",
+            &[r"INSERT INTO public.tickets (title, priority, state)
+        VALUES ($1, $2, $3)
+        RETURNING id, priority, state"],
+            &["id", "priority", "state"],
+        ),
+        (
+            "get_tickets_by_priorities",
+            &[r"SELECT id, title, priority, state
+        FROM public.tickets
+        WHERE priority = ANY($1)
+        ORDER BY id"],
+            &["id", "title", "priority", "state"],
+        ),
+        (
+            "search_tickets",
+            &[
+                r"SELECT id, title, ts_rank(search, websearch_to_tsquery('english', $1)) AS rank
+        FROM public.tickets
+        WHERE search @@ websearch_to_tsquery('english', $1)
+        ORDER BY rank DESC, id",
+            ],
+            &["id", "title", "rank"],
+        ),
+        (
+            "find_tickets_matching",
+            &[r"SELECT id, title, search
+        FROM public.tickets
+        WHERE search @@ $1
+        ORDER BY id"],
+            &["id", "title", "search"],
+        ),
+        (
+            "list_open_tickets",
+            &[r"SELECT id, title, priority
+        FROM public.tickets
+        WHERE state = 'open'
+        ORDER BY id"],
+            &["id", "title", "priority"],
+        ),
+        (
+            "archive_ticket",
+            &[r"WITH automodel_archived AS (
+          DELETE FROM public.tickets WHERE id = $1 AND state = 'closed' RETURNING *
+        )
+        INSERT INTO public.tickets_archive AS tickets
+        SELECT * FROM automodel_archived
+        RETURNING id, title, archived_at"],
+            &["id", "title", "archived_at"],
+        ),
+    ];
+    let mut report = Vec::with_capacity(QUERIES.len());
+    for &(query, variants, columns) in QUERIES {
+        let result = super::warmup_query(&mut *executor, variants, columns).await;
+        report.push(super::WarmupStatus { query, result });
+    }
+    report
+}
+
+/// Constraint violations specific to this query
+#[derive(Debug)]
+pub enum InsertTicketConstraints {
+    /// Constraint: tickets_pkey on table tickets
+    TicketsPkey,
+    /// Constraint: tickets_priority_check on table tickets
+    TicketsPriorityCheck,
+    /// Constraint: tickets_state_check on table tickets
+    TicketsStateCheck,
+    /// Constraint: tickets_id_not_null on table tickets
+    TicketsIdNotNull,
+    /// Constraint: tickets_title_not_null on table tickets
+    TicketsTitleNotNull,
+    /// Constraint: tickets_priority_not_null on table tickets
+    TicketsPriorityNotNull,
+    /// Constraint: tickets_state_not_null on table tickets
+    TicketsStateNotNull,
+    /// Constraint: tickets_created_at_not_null on table tickets
+    TicketsCreatedAtNotNull,
+}
+
+impl TryFrom<super::ErrorConstraintInfo> for InsertTicketConstraints {
+    type Error = ();
+
+    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
+        match info.constraint_name.as_str() {
+            "tickets_pkey" => Ok(Self::TicketsPkey),
+            "tickets_priority_check" => Ok(Self::TicketsPriorityCheck),
+            "tickets_state_check" => Ok(Self::TicketsStateCheck),
+            "tickets_id_not_null" => Ok(Self::TicketsIdNotNull),
+            "tickets_title_not_null" => Ok(Self::TicketsTitleNotNull),
+            "tickets_priority_not_null" => Ok(Self::TicketsPriorityNotNull),
+            "tickets_state_not_null" => Ok(Self::TicketsStateNotNull),
+            "tickets_created_at_not_null" => Ok(Self::TicketsCreatedAtNotNull),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct InsertTicketItem {
+    pub id: i32,
+    pub priority: TicketsPriority,
+    pub state: String,
+}
+
+/// Insert a support ticket, priority is a generated enum and state is checked against its allowed values
+///
+/// Tables: `tickets`
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "INSERT INTO public.tickets (title, priority, state)\nVALUES (#{title}, #{priority}, #{state})\nRETURNING id, priority, state"
+    )
+)]
+pub async fn insert_ticket(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    title: String,
+    priority: TicketsPriority,
+    state: String,
+) -> Result<InsertTicketItem, super::Error<InsertTicketConstraints>> {
+    if let Some(value) =
+        std::iter::once(&state).find(|value| !TICKETS_STATE_VALUES.contains(&value.as_str()))
+    {
+        return Err(sqlx::Error::InvalidArgument(format!(
+            "state '{}' is not one of {}",
+            value,
+            TICKETS_STATE_VALUES.join(", ")
+        ))
+        .into());
+    }
+    let query = sqlx::query(
+        r"INSERT INTO public.tickets (title, priority, state)
+        VALUES ($1, $2, $3)
+        RETURNING id, priority, state",
+    );
+    let query = query.bind(&title);
+    let query = query.bind(priority);
+    let query = query.bind(&state);
+    let row = query.fetch_one(executor).await?;
+    let result: Result<_, sqlx::Error> = (|| {
+        Ok(InsertTicketItem {
+            id: row.try_get::<i32, _>("id")?,
+            priority: row.try_get::<TicketsPriority, _>("priority")?,
+            state: row.try_get::<String, _>("state")?,
+        })
+    })();
+    result.map_err(Into::into)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GetTicketsByPrioritiesItem {
+    pub id: i32,
+    pub title: String,
+    pub priority: TicketsPriority,
+    pub state: String,
+}
+
+/// Get the tickets with any of the given priorities
+///
+/// Tables: `tickets`
+///
+/// Query Plan:
+/// Index Scan using tickets_pkey on tickets
+///   Filter: (priority = ANY ('{}'::text[]))
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "SELECT id, title, priority, state\nFROM public.tickets\nWHERE priority = ANY(#{priority})\nORDER BY id"
+    )
+)]
+pub async fn get_tickets_by_priorities(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    priority: Vec<TicketsPriority>,
+) -> Result<Vec<GetTicketsByPrioritiesItem>, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT id, title, priority, state
+        FROM public.tickets
+        WHERE priority = ANY($1)
+        ORDER BY id",
+    );
+    let query = query.bind(priority);
+    let rows = query.fetch_all(executor).await?;
+    let result: Result<Vec<_>, sqlx::Error> = rows
+        .iter()
+        .map(|row| {
+            Ok(GetTicketsByPrioritiesItem {
+                id: row.try_get::<i32, _>("id")?,
+                title: row.try_get::<String, _>("title")?,
+                priority: row.try_get::<TicketsPriority, _>("priority")?,
+                state: row.try_get::<String, _>("state")?,
+            })
+        })
+        .collect();
+    result.map_err(Into::into)
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct SearchTicketsItem {
+    pub id: i32,
+    pub title: String,
+    pub rank: Option<f32>,
+}
+
+/// Search tickets by title with free-form input such as `printer -jam` or `"out of paper"`
+///
+/// Tables: `tickets`
+///
+/// Query Plan:
+/// Sort
+///   Sort Key: (ts_rank(search, '''dummi'''::tsquery)) DESC, id
+///   ->  Bitmap Heap Scan on tickets
+///         Recheck Cond: (search @@ '''dummi'''::tsquery)
+///         ->  Bitmap Index Scan on idx_tickets_search
+///               Index Cond: (search @@ '''dummi'''::tsquery)
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "SELECT id, title, ts_rank(search, websearch_to_tsquery('english', #{terms})) AS rank\nFROM public.tickets\nWHERE search @@ websearch_to_tsquery('english', #{terms})\nORDER BY rank DESC, id"
+    )
+)]
+pub async fn search_tickets(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    terms: String,
+) -> Result<Vec<SearchTicketsItem>, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT id, title, ts_rank(search, websearch_to_tsquery('english', $1)) AS rank
+        FROM public.tickets
+        WHERE search @@ websearch_to_tsquery('english', $1)
+        ORDER BY rank DESC, id",
+    );
+    let query = query.bind(&terms);
+    let rows = query.fetch_all(executor).await?;
+    let result: Result<Vec<_>, sqlx::Error> = rows
+        .iter()
+        .map(|row| {
+            Ok(SearchTicketsItem {
+                id: row.try_get::<i32, _>("id")?,
+                title: row.try_get::<String, _>("title")?,
+                rank: row.try_get::<Option<f32>, _>("rank")?,
+            })
+        })
+        .collect();
+    result.map_err(Into::into)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FindTicketsMatchingItem {
+    pub id: i32,
+    pub title: String,
+    pub search: Option<super::full_text_search::TsVector>,
+}
+
+/// Get the tickets matching a prepared tsquery, with their search vectors
+///
+/// Tables: `tickets`
+///
+/// Query Plan:
+/// Index Scan using tickets_pkey on tickets
+///   Filter: (search @@ '''dummy'''::tsquery)
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "SELECT id, title, search\nFROM public.tickets\nWHERE search @@ #{terms}\nORDER BY id"
+    )
+)]
+pub async fn find_tickets_matching(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    terms: super::full_text_search::TsQuery,
+) -> Result<Vec<FindTicketsMatchingItem>, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT id, title, search
+        FROM public.tickets
+        WHERE search @@ $1
+        ORDER BY id",
+    );
+    let query = query.bind(terms);
+    let rows = query.fetch_all(executor).await?;
+    let result: Result<Vec<_>, sqlx::Error> = rows
+        .iter()
+        .map(|row| {
+            Ok(FindTicketsMatchingItem {
+                id: row.try_get::<i32, _>("id")?,
+                title: row.try_get::<String, _>("title")?,
+                search: row.try_get::<Option<super::full_text_search::TsVector>, _>("search")?,
+            })
+        })
+        .collect();
+    result.map_err(Into::into)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CountTicketsByStateItem {
+    pub state: String,
+    pub tickets: Option<i64>,
+}
+
+/// Count the tickets in each state, while the ticket_stats feature is rolled out
+///
+/// Tables: `tickets`
+///
+/// Query Plan:
+/// GroupAggregate
+///   Group Key: state
+///   ->  Sort
+///         Sort Key: state
+///         ->  Seq Scan on tickets
+/// JIT:
+///   Functions: 6
+///   Options: Inlining true, Optimization true, Expressions true, Deforming true
+#[cfg(feature = "ticket_stats")]
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "SELECT state, COUNT(*) AS tickets\nFROM public.tickets\nGROUP BY state\nORDER BY state"
+    )
+)]
+pub async fn count_tickets_by_state(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+) -> Result<Vec<CountTicketsByStateItem>, super::ErrorReadOnly> {
+    if !super::feature_flags::is_enabled("ticket_stats") {
+        return Err(super::ErrorReadOnly::FeatureDisabled("ticket_stats"));
+    }
+    let query = sqlx::query(
+        r"SELECT state, COUNT(*) AS tickets
+        FROM public.tickets
+        GROUP BY state
+        ORDER BY state",
+    );
+    let rows = query.fetch_all(executor).await?;
+    let result: Result<Vec<_>, sqlx::Error> = rows
+        .iter()
+        .map(|row| {
+            Ok(CountTicketsByStateItem {
+                state: row.try_get::<String, _>("state")?,
+                tickets: row.try_get::<Option<i64>, _>("tickets")?,
+            })
+        })
+        .collect();
+    result.map_err(Into::into)
+}
+
+/// Stub of `count_tickets_by_state` failing with `FeatureDisabled`, as the `ticket_stats` feature is not enabled
+#[cfg(not(feature = "ticket_stats"))]
+pub async fn count_tickets_by_state(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+) -> Result<Vec<CountTicketsByStateItem>, super::ErrorReadOnly> {
+    let _ = executor;
+    Err(super::ErrorReadOnly::FeatureDisabled("ticket_stats"))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ListOpenTicketsItem {
+    pub id: i32,
+    pub title: String,
+    pub priority: String,
+}
+
+/// List the open tickets, the most urgent first when built with ticket_stats
+///
+/// Tables: `tickets`
+///
+/// Query Plan:
+/// Index Scan using tickets_pkey on tickets
+///   Filter: ((state)::text = 'open'::text)
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "SELECT id, title, priority\nFROM public.tickets\nWHERE state = 'open'\nORDER BY id"
+    )
+)]
+pub async fn list_open_tickets(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+) -> Result<Vec<ListOpenTicketsItem>, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT id, title, priority
+        FROM public.tickets
+        WHERE state = 'open'
+        ORDER BY id",
+    );
+    let rows = query.fetch_all(executor).await?;
+    let result: Result<Vec<_>, sqlx::Error> = rows
+        .iter()
+        .map(|row| {
+            Ok(ListOpenTicketsItem {
+                id: row.try_get::<i32, _>("id")?,
+                title: row.try_get::<String, _>("title")?,
+                priority: row.try_get::<String, _>("priority")?,
+            })
+        })
+        .collect();
+    result.map_err(Into::into)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ArchiveTicketItem {
+    pub id: i32,
+    pub title: String,
+    pub archived_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Delete a closed ticket, keeping a copy in the archive table
+///
+/// Moves the deleted rows of `public.tickets` to `public.tickets_archive` and returns the archived rows
+///
+/// Tables: `tickets`, `tickets_archive`
+///
+/// Query Plan:
+/// Insert on tickets_archive tickets
+///   CTE automodel_archived
+///     ->  Delete on tickets tickets_1
+///           ->  Index Scan using tickets_pkey on tickets tickets_1
+///                 Index Cond: (id = 0)
+///                 Filter: ((state)::text = 'closed'::text)
+///   ->  CTE Scan on automodel_archived
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "WITH automodel_archived AS (\n    DELETE FROM public.tickets WHERE id = #{id} AND state = 'closed' RETURNING *\n)\nINSERT INTO public.tickets_archive AS tickets\nSELECT * FROM automodel_archived\nRETURNING id, title, archived_at"
+    )
+)]
+pub async fn archive_ticket(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    id: i32,
+) -> Result<Option<ArchiveTicketItem>, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"WITH automodel_archived AS (
+          DELETE FROM public.tickets WHERE id = $1 AND state = 'closed' RETURNING *
+        )
+        INSERT INTO public.tickets_archive AS tickets
+        SELECT * FROM automodel_archived
+        RETURNING id, title, archived_at",
+    );
+    let query = query.bind(id);
+    let row = query.fetch_optional(executor).await?;
+    match row {
+        Some(row) => {
+            let result: Result<_, sqlx::Error> = (|| {
+                Ok(ArchiveTicketItem {
+                    id: row.try_get::<i32, _>("id")?,
+                    title: row.try_get::<String, _>("title")?,
+                    archived_at: row.try_get::<chrono::DateTime<chrono::Utc>, _>("archived_at")?,
+                })
+            })();
+            result.map(Some).map_err(Into::into)
+        }
+        None => Ok(None),
+    }
+}
+// AUTOMODEL_CONTENT_HASH: 2fb1877ed393692b
@@ -1,7 +1,95 @@
 // This file was automatically generated by AutoModel. Do not edit manually.
 
+#[allow(unused_imports)]
+pub use super::{classify_error, DbErrorKind};
 use sqlx::Row;
 
+/// Tables read or modified by the queries of this module
+pub const TABLES: &[&str] = &["posts", "users"];
+
+/// Prepare every query of this module on the connection, e.g. in `after_connect` of the pool
+/// The statements stay cached on the connection; failed queries no longer match the schema
+pub async fn warmup(executor: &mut sqlx::PgConnection) -> Vec<super::WarmupStatus> {
+    const QUERIES: &[(&str, &[&str], &[&str])] = &[
+        (
+            "create_user",
+            &[r"INSERT INTO public.users (name, email, age) 
+        VALUES ($1, $2, $3) 
+        RETURNING id, name, email, age"],
+            &["id", "name", "email", "age"],
+        ),
+        (
+            "update_user_full",
+            &[r"UPDATE public.users 
+        SET name = $1, email = $2, age = $3 
+        WHERE id = $4 
+        RETURNING id, name, email, age"],
+            &["id", "name", "email", "age"],
+        ),
+        (
+            "update_user_partial",
+            &[
+                r"UPDATE public.users 
+        SET updated_at = NOW() 
+         
+         
+         
+        WHERE id = $1 
+        RETURNING id, name, email, age",
+                r"UPDATE public.users 
+        SET updated_at = NOW() 
+        , name = $1 
+         
+         
+        WHERE id = $2 
+        RETURNING id, name, email, age",
+                r"UPDATE public.users 
+        SET updated_at = NOW() 
+         
+        , email = $1 
+         
+        WHERE id = $2 
+        RETURNING id, name, email, age",
+                r"UPDATE public.users 
+        SET updated_at = NOW() 
+         
+         
+        , age = $1 
+        WHERE id = $2 
+        RETURNING id, name, email, age",
+            ],
+            &["id", "name", "email", "age"],
+        ),
+        (
+            "find_user_by_email",
+            &[r"SELECT id, name, email, age 
+        FROM public.users 
+        WHERE email = $1"],
+            &["id", "name", "email", "age"],
+        ),
+        (
+            "register_user",
+            &[r"INSERT INTO public.users (name, email)
+        VALUES ($1, $2)
+        RETURNING id AS user_id"],
+            &["user_id"],
+        ),
+        (
+            "create_welcome_post",
+            &[r"INSERT INTO public.posts (author_id, title, content)
+        VALUES ($1, $2, $3)
+        RETURNING id AS post_id"],
+            &["post_id"],
+        ),
+    ];
+    let mut report = Vec::with_capacity(QUERIES.len());
+    for &(query, variants, columns) in QUERIES {
+        let result = super::warmup_query(&mut *executor, variants, columns).await;
+        report.push(super::WarmupStatus { query, result });
+    }
+    report
+}
+
 /// Constraint violations specific to this query
 #[derive(Debug)]
 pub enum UserContentConstraints {
@@ -35,7 +123,7 @@ impl TryFrom<super::ErrorConstraintInfo> for UserContentConstraints {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct UserModel {
     pub id: i32,
     pub name: String,
@@ -44,12 +132,25 @@ pub struct UserModel {
 }
 
 /// Insert a new user and return as UserModel
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "INSERT INTO public.users (name, email, age) \nVALUES (#{name}, #{email}, #{age?}) \nRETURNING id, name, email, age"))]
-pub async fn create_user(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, name: String, email: String, age: Option<i32>) -> Result<UserModel, super::Error<UserContentConstraints>> {
+///
+/// Tables: `users`
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "INSERT INTO public.users (name, email, age) \nVALUES (#{name}, #{email}, #{age?}) \nRETURNING id, name, email, age"
+    )
+)]
+pub async fn create_user(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    name: String,
+    email: String,
+    age: Option<i32>,
+) -> Result<UserModel, super::Error<UserContentConstraints>> {
     let query = sqlx::query(
         r"INSERT INTO public.users (name, email, age) 
         VALUES ($1, $2, $3) 
-        RETURNING id, name, email, age"
+        RETURNING id, name, email, age",
     );
     let query = query.bind(&name);
     let query = query.bind(&email);
@@ -57,23 +158,34 @@ pub async fn create_user(executor: impl sqlx::Executor<'_, Database = sqlx::Post
     let row = query.fetch_one(executor).await?;
     let result: Result<_, sqlx::Error> = (|| {
         Ok(UserModel {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-        age: row.try_get::<Option<i32>, _>("age")?,
-    })
+            id: row.try_get::<i32, _>("id")?,
+            name: row.try_get::<String, _>("name")?,
+            email: row.try_get::<String, _>("email")?,
+            age: row.try_get::<Option<i32>, _>("age")?,
+        })
     })();
     result.map_err(Into::into)
 }
 
 /// Full update of user - reuses UserModel for both parameters and return type
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "UPDATE public.users \nSET name = #{name}, email = #{email}, age = #{age?} \nWHERE id = #{id} \nRETURNING id, name, email, age"))]
-pub async fn update_user_full(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, params: &UserModel) -> Result<UserModel, super::Error<UserContentConstraints>> {
+///
+/// Tables: `users`
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "UPDATE public.users \nSET name = #{name}, email = #{email}, age = #{age?} \nWHERE id = #{id} \nRETURNING id, name, email, age"
+    )
+)]
+pub async fn update_user_full(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    params: &UserModel,
+) -> Result<UserModel, super::Error<UserContentConstraints>> {
     let query = sqlx::query(
         r"UPDATE public.users 
         SET name = $1, email = $2, age = $3 
         WHERE id = $4 
-        RETURNING id, name, email, age"
+        RETURNING id, name, email, age",
     );
     let query = query.bind(&params.name);
     let query = query.bind(&params.email);
@@ -82,104 +194,135 @@ pub async fn update_user_full(executor: impl sqlx::Executor<'_, Database = sqlx:
     let row = query.fetch_one(executor).await?;
     let result: Result<_, sqlx::Error> = (|| {
         Ok(UserModel {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-        age: row.try_get::<Option<i32>, _>("age")?,
-    })
+            id: row.try_get::<i32, _>("id")?,
+            name: row.try_get::<String, _>("name")?,
+            email: row.try_get::<String, _>("email")?,
+            age: row.try_get::<Option<i32>, _>("age")?,
+        })
     })();
     result.map_err(Into::into)
 }
 
 /// Partial update using diff-based comparison - auto-generates params struct for old/new comparison
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "UPDATE public.users \nSET updated_at = NOW() \n#[, name = #{name?}] \n#[, email = #{email?}] \n#[, age = #{age?}] \nWHERE id = #{id} \nRETURNING id, name, email, age"))]
-pub async fn update_user_partial(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, old: &UserModel, new: &UserModel, id: i32) -> Result<UserModel, super::Error<UserContentConstraints>> {
-    let mut final_sql = r"UPDATE public.users 
-SET updated_at = NOW() 
-#[, name = #{name?}] 
-#[, email = #{email?}] 
-#[, age = #{age?}] 
-WHERE id = $1 
-RETURNING id, name, email, age".to_string();
-    let mut included_params = Vec::new();
-
+///
+/// Tables: `users`
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "UPDATE public.users \nSET updated_at = NOW() \n#[, name = #{name?}] \n#[, email = #{email?}] \n#[, age = #{age?}] \nWHERE id = #{id} \nRETURNING id, name, email, age"
+    )
+)]
+pub async fn update_user_partial(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    old: &UserModel,
+    new: &UserModel,
+    id: i32,
+) -> Result<UserModel, super::Error<UserContentConstraints>> {
+    let mut included_params: Vec<&str> = Vec::new();
     if old.name != new.name {
-        final_sql = final_sql.replace(r"#[, name = #{name?}]", r", name = #{name?}");
         included_params.push("name");
-    } else {
-        final_sql = final_sql.replace(r"#[, name = #{name?}]", "");
     }
-
     if old.email != new.email {
-        final_sql = final_sql.replace(r"#[, email = #{email?}]", r", email = #{email?}");
         included_params.push("email");
-    } else {
-        final_sql = final_sql.replace(r"#[, email = #{email?}]", "");
     }
-
     if old.age != new.age {
-        final_sql = final_sql.replace(r"#[, age = #{age?}]", r", age = #{age?}");
         included_params.push("age");
-    } else {
-        final_sql = final_sql.replace(r"#[, age = #{age?}]", "");
     }
-
-    #[allow(unused_assignments)]
-    let mut param_counter = 1;
-    final_sql = final_sql.replace(r"#{id}", &format!("${}", param_counter));
-    param_counter += 1;
-    if included_params.contains(&r"name") {
-        final_sql = final_sql.replace(r"#{name?}", &format!("${}", param_counter));
-        param_counter += 1;
-    }
-    if included_params.contains(&r"email") {
-        final_sql = final_sql.replace(r"#{email?}", &format!("${}", param_counter));
-        param_counter += 1;
-    }
-    if included_params.contains(&r"age") {
-        final_sql = final_sql.replace(r"#{age?}", &format!("${}", param_counter));
-        param_counter += 1;
-    }
-    let _ = param_counter; // Suppress unused assignment warning
+    let final_sql = update_user_partial_sql(&included_params);
 
     let mut query = sqlx::query(&final_sql);
 
-    query = query.bind(&id);
     if included_params.contains(&r"name") {
         query = query.bind(&new.name);
     }
-
     if included_params.contains(&r"email") {
         query = query.bind(&new.email);
     }
-
     if included_params.contains(&r"age") {
         query = query.bind(&new.age);
     }
+    query = query.bind(&id);
 
     let row = query.fetch_one(executor).await?;
     let result: Result<_, sqlx::Error> = (|| {
         Ok(UserModel {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-        age: row.try_get::<Option<i32>, _>("age")?,
-    })
+            id: row.try_get::<i32, _>("id")?,
+            name: row.try_get::<String, _>("name")?,
+            email: row.try_get::<String, _>("email")?,
+            age: row.try_get::<Option<i32>, _>("age")?,
+        })
     })();
     result.map_err(Into::into)
 }
 
+/// Assemble the SQL of `update_user_partial` for the given included conditional parameters
+fn update_user_partial_sql(included_params: &[&str]) -> String {
+    let mut final_sql = r"UPDATE public.users 
+SET updated_at = NOW() 
+#[, name = #{name?}] 
+#[, email = #{email?}] 
+#[, age = #{age?}] 
+WHERE id = #{id} 
+RETURNING id, name, email, age"
+        .to_string();
+
+    if included_params.contains(&"name") {
+        final_sql = final_sql.replacen(r"#[, name = #{name?}]", r", name = #{name?}", 1);
+    } else {
+        final_sql = final_sql.replacen(r"#[, name = #{name?}]", "", 1);
+    }
+    if included_params.contains(&"email") {
+        final_sql = final_sql.replacen(r"#[, email = #{email?}]", r", email = #{email?}", 1);
+    } else {
+        final_sql = final_sql.replacen(r"#[, email = #{email?}]", "", 1);
+    }
+    if included_params.contains(&"age") {
+        final_sql = final_sql.replacen(r"#[, age = #{age?}]", r", age = #{age?}", 1);
+    } else {
+        final_sql = final_sql.replacen(r"#[, age = #{age?}]", "", 1);
+    }
+
+    #[allow(unused_assignments)]
+    let mut param_counter = 1;
+    if included_params.contains(&"name") {
+        final_sql = final_sql.replace(r"#{name?}", &format!("${}", param_counter));
+        param_counter += 1;
+    }
+    if included_params.contains(&"email") {
+        final_sql = final_sql.replace(r"#{email?}", &format!("${}", param_counter));
+        param_counter += 1;
+    }
+    if included_params.contains(&"age") {
+        final_sql = final_sql.replace(r"#{age?}", &format!("${}", param_counter));
+        param_counter += 1;
+    }
+    final_sql = final_sql.replace(r"#{id}", &format!("${}", param_counter));
+    param_counter += 1;
+    let _ = param_counter; // Suppress unused assignment warning
+    final_sql
+}
+
 /// Select user by email - returns UserModel
 ///
+/// Tables: `users`
+///
 /// Query Plan:
 /// Index Scan using users_email_key on users
 ///   Index Cond: ((email)::text = 'dummy'::text)
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "SELECT id, name, email, age \nFROM public.users \nWHERE email = #{email}"))]
-pub async fn find_user_by_email(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, email: String) -> Result<Option<UserModel>, super::ErrorReadOnly> {
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(sql = "SELECT id, name, email, age \nFROM public.users \nWHERE email = #{email}")
+)]
+pub async fn find_user_by_email(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    email: String,
+) -> Result<Option<UserModel>, super::ErrorReadOnly> {
     let query = sqlx::query(
         r"SELECT id, name, email, age 
         FROM public.users 
-        WHERE email = $1"
+        WHERE email = $1",
     );
     let query = query.bind(&email);
     let row = query.fetch_optional(executor).await?;
@@ -187,15 +330,275 @@ pub async fn find_user_by_email(executor: impl sqlx::Executor<'_, Database = sql
         Some(row) => {
             let result: Result<_, sqlx::Error> = (|| {
                 Ok(UserModel {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-        age: row.try_get::<Option<i32>, _>("age")?,
-    })
+                    id: row.try_get::<i32, _>("id")?,
+                    name: row.try_get::<String, _>("name")?,
+                    email: row.try_get::<String, _>("email")?,
+                    age: row.try_get::<Option<i32>, _>("age")?,
+                })
             })();
             result.map(Some).map_err(Into::into)
-        },
+        }
         None => Ok(None),
     }
 }
 
+/// Constraint violations specific to this query
+#[derive(Debug)]
+pub enum RegisterUserConstraints {
+    /// Constraint: users_email_key on table users
+    UsersEmailKey,
+    /// Constraint: users_pkey on table users
+    UsersPkey,
+    /// Constraint: users_referrer_id_fkey on table users
+    UsersReferrerIdFkey,
+    /// Constraint: users_id_not_null on table users
+    UsersIdNotNull,
+    /// Constraint: users_name_not_null on table users
+    UsersNameNotNull,
+    /// Constraint: users_email_not_null on table users
+    UsersEmailNotNull,
+}
+
+impl TryFrom<super::ErrorConstraintInfo> for RegisterUserConstraints {
+    type Error = ();
+
+    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
+        match info.constraint_name.as_str() {
+            "users_email_key" => Ok(Self::UsersEmailKey),
+            "users_pkey" => Ok(Self::UsersPkey),
+            "users_referrer_id_fkey" => Ok(Self::UsersReferrerIdFkey),
+            "users_id_not_null" => Ok(Self::UsersIdNotNull),
+            "users_name_not_null" => Ok(Self::UsersNameNotNull),
+            "users_email_not_null" => Ok(Self::UsersEmailNotNull),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Insert the user of a registration
+///
+/// Tables: `users`
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "INSERT INTO public.users (name, email)\nVALUES (#{name}, #{email})\nRETURNING id AS user_id"
+    )
+)]
+pub async fn register_user(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    name: String,
+    email: String,
+) -> Result<i32, super::Error<RegisterUserConstraints>> {
+    let query = sqlx::query(
+        r"INSERT INTO public.users (name, email)
+        VALUES ($1, $2)
+        RETURNING id AS user_id",
+    );
+    let query = query.bind(&name);
+    let query = query.bind(&email);
+    let row = query.fetch_one(executor).await?;
+    Ok(row.try_get::<i32, _>("user_id")?)
+}
+
+/// Constraint violations specific to this query
+#[derive(Debug)]
+pub enum CreateWelcomePostConstraints {
+    /// Constraint: posts_author_id_fkey on table posts
+    PostsAuthorIdFkey,
+    /// Constraint: posts_pkey on table posts
+    PostsPkey,
+    /// Constraint: posts_id_not_null on table posts
+    PostsIdNotNull,
+    /// Constraint: posts_author_id_not_null on table posts
+    PostsAuthorIdNotNull,
+    /// Constraint: posts_title_not_null on table posts
+    PostsTitleNotNull,
+}
+
+impl TryFrom<super::ErrorConstraintInfo> for CreateWelcomePostConstraints {
+    type Error = ();
+
+    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
+        match info.constraint_name.as_str() {
+            "posts_author_id_fkey" => Ok(Self::PostsAuthorIdFkey),
+            "posts_pkey" => Ok(Self::PostsPkey),
+            "posts_id_not_null" => Ok(Self::PostsIdNotNull),
+            "posts_author_id_not_null" => Ok(Self::PostsAuthorIdNotNull),
+            "posts_title_not_null" => Ok(Self::PostsTitleNotNull),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Insert the welcome post of a registered user
+///
+/// Tables: `posts`
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "INSERT INTO public.posts (author_id, title, content)\nVALUES (#{user_id}, #{title}, #{content?})\nRETURNING id AS post_id"
+    )
+)]
+pub async fn create_welcome_post(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    user_id: i32,
+    title: String,
+    content: Option<String>,
+) -> Result<i32, super::Error<CreateWelcomePostConstraints>> {
+    let query = sqlx::query(
+        r"INSERT INTO public.posts (author_id, title, content)
+        VALUES ($1, $2, $3)
+        RETURNING id AS post_id",
+    );
+    let query = query.bind(user_id);
+    let query = query.bind(&title);
+    let query = query.bind(&content);
+    let row = query.fetch_one(executor).await?;
+    Ok(row.try_get::<i32, _>("post_id")?)
+}
+
+/// Arguments of the steps of [`perform_registration`] which are not results of earlier steps
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RegistrationParams {
+    pub name: String,
+    pub email: String,
+    pub title: String,
+    pub content: Option<String>,
+}
+
+/// Results of the steps of [`perform_registration`] returning a value
+#[derive(Debug, Clone)]
+pub struct RegistrationOutput {
+    pub register_user: i32,
+    pub create_welcome_post: i32,
+}
+
+/// Error of [`perform_registration`], by failing step; the transaction is rolled back
+#[derive(Debug)]
+pub enum RegistrationError {
+    /// Beginning or committing the transaction failed
+    Transaction(super::ErrorReadOnly),
+    /// Step `register_user` failed
+    RegisterUser(super::Error<RegisterUserConstraints>),
+    /// Step `create_welcome_post` failed
+    CreateWelcomePost(super::Error<CreateWelcomePostConstraints>),
+}
+
+impl std::fmt::Display for RegistrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Transaction(e) => write!(f, "perform_registration: transaction failed: {}", e),
+            Self::RegisterUser(e) => {
+                write!(f, "perform_registration: step register_user failed: {}", e)
+            }
+            Self::CreateWelcomePost(e) => write!(
+                f,
+                "perform_registration: step create_welcome_post failed: {}",
+                e
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RegistrationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Transaction(e) => Some(e),
+            Self::RegisterUser(e) => Some(e),
+            Self::CreateWelcomePost(e) => Some(e),
+        }
+    }
+}
+
+/// Unit of work `registration`: runs `register_user`, `create_welcome_post` in order in one transaction
+/// Called with a transaction, the unit runs in a savepoint of it, which is rolled back when a step fails
+pub async fn perform_registration(
+    executor: impl sqlx::Acquire<'_, Database = sqlx::Postgres>,
+    params: RegistrationParams,
+) -> Result<RegistrationOutput, RegistrationError> {
+    let mut tx = executor
+        .begin()
+        .await
+        .map_err(|e| RegistrationError::Transaction(e.into()))?;
+    let register_user = register_user(&mut *tx, params.name, params.email)
+        .await
+        .map_err(RegistrationError::RegisterUser)?;
+    let create_welcome_post =
+        create_welcome_post(&mut *tx, register_user, params.title, params.content)
+            .await
+            .map_err(RegistrationError::CreateWelcomePost)?;
+    tx.commit()
+        .await
+        .map_err(|e| RegistrationError::Transaction(e.into()))?;
+    Ok(RegistrationOutput {
+        register_user,
+        create_welcome_post,
+    })
+}
+#[cfg(test)]
+mod conditional_variant_tests {
+    use super::*;
+
+    fn normalize_sql(sql: &str) -> String {
+        sql.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    #[test]
+    fn update_user_partial_sql_variants() {
+        assert_eq!(
+            normalize_sql(&update_user_partial_sql(&[])),
+            normalize_sql(
+                r"UPDATE public.users 
+SET updated_at = NOW() 
+ 
+ 
+ 
+WHERE id = $1 
+RETURNING id, name, email, age"
+            ),
+            "update_user_partial (base)"
+        );
+        assert_eq!(
+            normalize_sql(&update_user_partial_sql(&["name"])),
+            normalize_sql(
+                r"UPDATE public.users 
+SET updated_at = NOW() 
+, name = $1 
+ 
+ 
+WHERE id = $2 
+RETURNING id, name, email, age"
+            ),
+            "update_user_partial (variant 1)"
+        );
+        assert_eq!(
+            normalize_sql(&update_user_partial_sql(&["email"])),
+            normalize_sql(
+                r"UPDATE public.users 
+SET updated_at = NOW() 
+ 
+, email = $1 
+ 
+WHERE id = $2 
+RETURNING id, name, email, age"
+            ),
+            "update_user_partial (variant 2)"
+        );
+        assert_eq!(
+            normalize_sql(&update_user_partial_sql(&["age"])),
+            normalize_sql(
+                r"UPDATE public.users 
+SET updated_at = NOW() 
+ 
+ 
+, age = $1 
+WHERE id = $2 
+RETURNING id, name, email, age"
+            ),
+            "update_user_partial (variant 3)"
+        );
+    }
+}
+// AUTOMODEL_CONTENT_HASH: 3fa483f61e4ecf52
@@ -1,8 +1,11 @@
 // This file was automatically generated by AutoModel. Do not edit manually.
 
+#[allow(unused_imports)]
+pub use super::{classify_error, DbErrorKind};
+use crate::models::UserProfile;
 use sqlx::Row;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum UserStatus {
     Active,
     Inactive,
@@ -12,7 +15,7 @@ pub enum UserStatus {
 
 impl std::str::FromStr for UserStatus {
     type Err = String;
-    
+
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "active" => Ok(UserStatus::Active),
@@ -40,287 +43,111 @@ impl sqlx::Type<sqlx::Postgres> for UserStatus {
     fn type_info() -> sqlx::postgres::PgTypeInfo {
         sqlx::postgres::PgTypeInfo::with_name("public.user_status")
     }
+
+    fn compatible(ty: &sqlx::postgres::PgTypeInfo) -> bool {
+        *ty == Self::type_info() || *ty == sqlx::postgres::PgTypeInfo::with_name("user_status")
+    }
 }
 
 impl<'r> sqlx::Decode<'r, sqlx::Postgres> for UserStatus {
-    fn decode(value: sqlx::postgres::PgValueRef<'r>) -> Result<Self, Box<dyn std::error::Error + Send + Sync + 'static>> {
+    fn decode(
+        value: sqlx::postgres::PgValueRef<'r>,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync + 'static>> {
         let s = <&str as sqlx::Decode<sqlx::Postgres>>::decode(value)?;
-        s.parse().map_err(|e| Box::new(std::io::Error::new(std::io::ErrorKind::InvalidData, e)) as Box<dyn std::error::Error + Send + Sync + 'static>)
+        s.parse().map_err(|e| {
+            Box::new(std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+                as Box<dyn std::error::Error + Send + Sync + 'static>
+        })
     }
 }
 
 impl<'q> sqlx::Encode<'q, sqlx::Postgres> for UserStatus {
-    fn encode_by_ref(&self, buf: &mut sqlx::postgres::PgArgumentBuffer) -> Result<sqlx::encode::IsNull, Box<dyn std::error::Error + Send + Sync + 'static>> {
+    fn encode_by_ref(
+        &self,
+        buf: &mut sqlx::postgres::PgArgumentBuffer,
+    ) -> Result<sqlx::encode::IsNull, Box<dyn std::error::Error + Send + Sync + 'static>> {
         <&str as sqlx::Encode<sqlx::Postgres>>::encode(&self.to_string(), buf)
     }
 }
 
-
-/// Constraint violations specific to this query
-#[derive(Debug)]
-pub enum InsertUserConstraints {
-    /// Constraint: users_email_key on table users
-    UsersEmailKey,
-    /// Constraint: users_pkey on table users
-    UsersPkey,
-    /// Constraint: users_referrer_id_fkey on table users
-    UsersReferrerIdFkey,
-    /// Constraint: users_id_not_null on table users
-    UsersIdNotNull,
-    /// Constraint: users_name_not_null on table users
-    UsersNameNotNull,
-    /// Constraint: users_email_not_null on table users
-    UsersEmailNotNull,
-}
-
-impl TryFrom<super::ErrorConstraintInfo> for InsertUserConstraints {
-    type Error = ();
-
-    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
-        match info.constraint_name.as_str() {
-            "users_email_key" => Ok(Self::UsersEmailKey),
-            "users_pkey" => Ok(Self::UsersPkey),
-            "users_referrer_id_fkey" => Ok(Self::UsersReferrerIdFkey),
-            "users_id_not_null" => Ok(Self::UsersIdNotNull),
-            "users_name_not_null" => Ok(Self::UsersNameNotNull),
-            "users_email_not_null" => Ok(Self::UsersEmailNotNull),
-            _ => Err(()),
-        }
+impl sqlx::postgres::PgHasArrayType for UserStatus {
+    fn array_type_info() -> sqlx::postgres::PgTypeInfo {
+        sqlx::postgres::PgTypeInfo::array_of("public.user_status")
     }
-}
-
-#[derive(Debug, Clone)]
-pub struct InsertUserItem {
-    pub id: i32,
-    pub name: String,
-    pub email: String,
-    pub age: Option<i32>,
-    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
-}
 
-/// Insert a new user with all fields and return the created user
-#[tracing::instrument(level = "trace", skip(executor, profile), fields(sql = "INSERT INTO public.users (name, email, age, profile)\nVALUES (#{name}, #{email}, #{age}, #{profile})\nRETURNING id, name, email, age, created_at"))]
-pub async fn insert_user(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, name: String, email: String, age: i32, profile: crate::models::UserProfile) -> Result<InsertUserItem, super::Error<InsertUserConstraints>> {
-    let query = sqlx::query(
-        r"INSERT INTO public.users (name, email, age, profile)
-        VALUES ($1, $2, $3, $4)
-        RETURNING id, name, email, age, created_at"
-    );
-    let query = query.bind(&name);
-    let query = query.bind(&email);
-    let query = query.bind(age);
-    let query = query.bind(serde_json::to_value(&profile).map_err(|e| sqlx::Error::Encode(Box::new(e)))?);
-    let row = query.fetch_one(executor).await?;
-    let result: Result<_, sqlx::Error> = (|| {
-        Ok(InsertUserItem {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-        age: row.try_get::<Option<i32>, _>("age")?,
-        created_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")?,
-    })
-    })();
-    result.map_err(Into::into)
+    fn array_compatible(ty: &sqlx::postgres::PgTypeInfo) -> bool {
+        *ty == Self::array_type_info() || *ty == sqlx::postgres::PgTypeInfo::array_of("user_status")
+    }
 }
 
-/// Constraint violations specific to this query
-#[derive(Debug)]
-pub enum InsertUsersBatchConstraints {
-    /// Constraint: users_email_key on table users
-    UsersEmailKey,
-    /// Constraint: users_pkey on table users
-    UsersPkey,
-    /// Constraint: users_referrer_id_fkey on table users
-    UsersReferrerIdFkey,
-    /// Constraint: users_id_not_null on table users
-    UsersIdNotNull,
-    /// Constraint: users_name_not_null on table users
-    UsersNameNotNull,
-    /// Constraint: users_email_not_null on table users
-    UsersEmailNotNull,
-}
+/// Tables read or modified by the queries of this module
+pub const TABLES: &[&str] = &["users"];
 
-impl TryFrom<super::ErrorConstraintInfo> for InsertUsersBatchConstraints {
-    type Error = ();
-
-    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
-        match info.constraint_name.as_str() {
-            "users_email_key" => Ok(Self::UsersEmailKey),
-            "users_pkey" => Ok(Self::UsersPkey),
-            "users_referrer_id_fkey" => Ok(Self::UsersReferrerIdFkey),
-            "users_id_not_null" => Ok(Self::UsersIdNotNull),
-            "users_name_not_null" => Ok(Self::UsersNameNotNull),
-            "users_email_not_null" => Ok(Self::UsersEmailNotNull),
-            _ => Err(()),
-        }
-    }
-}
+/// Queries with `cache_invalidation` and the tables whose changes invalidate their cached results
+pub const CACHED_QUERIES: &[(&str, &[&str])] = &[("get_all_user_statuses", &["users"])];
 
-#[derive(Debug, Clone)]
-pub struct InsertUsersBatchRecord {
-    pub name: String,
-    pub email: String,
-    pub age: i32,
-}
+/// Sensitive fields of the queries of this module, as (query, field, sensitivity)
+pub const SENSITIVE_FIELDS: &[(&str, &str, &str)] = &[("find_user_by_email", "email", "pii")];
 
-/// Insert multiple public.users using UNNEST pattern with multiunzip
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "INSERT INTO public.users (name, email, age)\nSELECT *\nFROM UNNEST(\n        #{name}::text [],\n        #{email}::text [],\n        #{age}::int4 []\n    )"))]
-pub async fn insert_users_batch(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, items: Vec<InsertUsersBatchRecord>) -> Result<(), super::Error<InsertUsersBatchConstraints>> {
-    use itertools::Itertools;
-    let query = sqlx::query(
-        r"INSERT INTO public.users (name, email, age)
+/// Prepare every query of this module on the connection, e.g. in `after_connect` of the pool
+/// The statements stay cached on the connection; failed queries no longer match the schema
+pub async fn warmup(executor: &mut sqlx::PgConnection) -> Vec<super::WarmupStatus> {
+    const QUERIES: &[(&str, &[&str], &[&str])] = &[
+        (
+            "insert_user",
+            &[r"INSERT INTO public.users (name, email, age, profile)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id, name, email, age, created_at"],
+            &["id", "name", "email", "age", "created_at"],
+        ),
+        (
+            "insert_users_batch",
+            &[r"INSERT INTO public.users (name, email, age)
         SELECT *
         FROM UNNEST(
             $1::text [],
             $2::text [],
             $3::int4 []
-          )"
-    );
-    let (name, email, age): (Vec<_>, Vec<_>, Vec<_>) =
-        items
-            .into_iter()
-            .map(|item| (item.name, item.email, item.age))
-            .multiunzip();
-    let query = query.bind(name);
-    let query = query.bind(email);
-    let query = query.bind(age);
-    query.execute(executor).await?;
-    Ok(())
-}
-
-#[derive(Debug, Clone)]
-pub struct GetAllUsersItem {
-    pub id: i32,
-    pub name: String,
-    pub email: String,
-    pub age: Option<i32>,
-    pub profile: Option<crate::models::UserProfile>,
-    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
-    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
-}
-
-/// Get all public.users with all their fields
-///
-/// Query Plan:
-/// Sort
-///   Sort Key: created_at DESC
-///   ->  Seq Scan on users
-/// JIT:
-///   Functions: 2
-///   Options: Inlining true, Optimization true, Expressions true, Deforming true
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "SELECT id, name, email, age, profile, created_at, updated_at \nFROM public.users \nORDER BY created_at DESC"))]
-pub async fn get_all_users(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>) -> Result<Vec<GetAllUsersItem>, super::ErrorReadOnly> {
-    let query = sqlx::query(
-        r"SELECT id, name, email, age, profile, created_at, updated_at 
+          )"],
+            &[],
+        ),
+        (
+            "get_all_users",
+            &[
+                r"SELECT id, name, email, age, profile, created_at, updated_at 
         FROM public.users 
-        ORDER BY created_at DESC"
-    );
-    let rows = query.fetch_all(executor).await?;
-    let result: Result<Vec<_>, sqlx::Error> = rows.iter().map(|row| {
-        Ok(GetAllUsersItem {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-        age: row.try_get::<Option<i32>, _>("age")?,
-        profile: row.try_get::<Option<serde_json::Value>, _>("profile")?
-            .map(|v| serde_json::from_value::<crate::models::UserProfile>(v)
-            .map_err(|e| sqlx::Error::Decode(Box::new(e))))
-            .transpose()?,
-        created_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")?,
-        updated_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("updated_at")?,
-    })
-    }).collect();
-    result.map_err(Into::into)
-}
-
-#[derive(Debug, Clone)]
-pub struct FindUserByEmailItem {
-    pub id: i32,
-    pub name: String,
-    pub email: String,
-    pub age: Option<i32>,
-    pub profile: Option<serde_json::Value>,
-    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
-    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
-}
-
-/// Find a user by their email address
-#[tracing::instrument(level = "debug", skip_all)]
-pub async fn find_user_by_email(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, email: String) -> Result<Option<FindUserByEmailItem>, super::ErrorReadOnly> {
-    let query = sqlx::query(
-        r"SELECT id, name, email, age, profile, created_at, updated_at 
+        ORDER BY created_at DESC",
+            ],
+            &[
+                "id",
+                "name",
+                "email",
+                "age",
+                "profile",
+                "created_at",
+                "updated_at",
+            ],
+        ),
+        (
+            "find_user_by_email",
+            &[
+                r"SELECT id, name, email, age, profile, created_at, updated_at 
         FROM public.users 
-        WHERE email = $1"
-    );
-    let query = query.bind(&email);
-    let row = query.fetch_optional(executor).await?;
-    match row {
-        Some(row) => {
-            let result: Result<_, sqlx::Error> = (|| {
-                Ok(FindUserByEmailItem {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-        age: row.try_get::<Option<i32>, _>("age")?,
-        profile: row.try_get::<Option<serde_json::Value>, _>("profile")?,
-        created_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")?,
-        updated_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("updated_at")?,
-    })
-            })();
-            result.map(Some).map_err(Into::into)
-        },
-        None => Ok(None),
-    }
-}
-
-/// Constraint violations specific to this query
-#[derive(Debug)]
-pub enum UpdateUserProfileConstraints {
-    /// Constraint: users_email_key on table users
-    UsersEmailKey,
-    /// Constraint: users_pkey on table users
-    UsersPkey,
-    /// Constraint: users_referrer_id_fkey on table users
-    UsersReferrerIdFkey,
-    /// Constraint: users_id_not_null on table users
-    UsersIdNotNull,
-    /// Constraint: users_name_not_null on table users
-    UsersNameNotNull,
-    /// Constraint: users_email_not_null on table users
-    UsersEmailNotNull,
-}
-
-impl TryFrom<super::ErrorConstraintInfo> for UpdateUserProfileConstraints {
-    type Error = ();
-
-    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
-        match info.constraint_name.as_str() {
-            "users_email_key" => Ok(Self::UsersEmailKey),
-            "users_pkey" => Ok(Self::UsersPkey),
-            "users_referrer_id_fkey" => Ok(Self::UsersReferrerIdFkey),
-            "users_id_not_null" => Ok(Self::UsersIdNotNull),
-            "users_name_not_null" => Ok(Self::UsersNameNotNull),
-            "users_email_not_null" => Ok(Self::UsersEmailNotNull),
-            _ => Err(()),
-        }
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct UpdateUserProfileItem {
-    pub id: i32,
-    pub name: String,
-    pub email: String,
-    pub age: Option<i32>,
-    pub profile: Option<crate::models::UserProfile>,
-    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
-}
-
-/// Update a user's profile by their ID
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "UPDATE public.users\nSET profile = #{profile}, updated_at = NOW() \nWHERE id = #{user_id} \nRETURNING id,\n    name,\n    email,\n    age,\n    profile,\n    updated_at"))]
-pub async fn update_user_profile(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, profile: crate::models::UserProfile, user_id: i32) -> Result<UpdateUserProfileItem, super::Error<UpdateUserProfileConstraints>> {
-    let query = sqlx::query(
-        r"UPDATE public.users
+        WHERE email = $1",
+            ],
+            &[
+                "id",
+                "name",
+                "email",
+                "age",
+                "profile",
+                "created_at",
+                "updated_at",
+            ],
+        ),
+        (
+            "update_user_profile",
+            &[r"UPDATE public.users
         SET profile = $1, updated_at = NOW() 
         WHERE id = $2 
         RETURNING id,
@@ -328,1618 +155,1052 @@ pub async fn update_user_profile(executor: impl sqlx::Executor<'_, Database = sq
           email,
           age,
           profile,
-          updated_at"
-    );
-    let query = query.bind(serde_json::to_value(&profile).map_err(|e| sqlx::Error::Encode(Box::new(e)))?);
-    let query = query.bind(user_id);
-    let row = query.fetch_one(executor).await?;
-    let result: Result<_, sqlx::Error> = (|| {
-        Ok(UpdateUserProfileItem {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-        age: row.try_get::<Option<i32>, _>("age")?,
-        profile: row.try_get::<Option<serde_json::Value>, _>("profile")?
-            .map(|v| serde_json::from_value::<crate::models::UserProfile>(v)
-            .map_err(|e| sqlx::Error::Decode(Box::new(e))))
-            .transpose()?,
-        updated_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("updated_at")?,
-    })
-    })();
-    result.map_err(Into::into)
-}
-
-#[derive(Debug, Clone)]
-pub struct FindUsersByNameAndAgeItem {
-    pub id: i32,
-    pub name: String,
-    pub email: String,
-    pub age: Option<i32>,
-}
-
-/// Find public.users by name pattern with optional minimum age filter (using conditional syntax)
-///
-/// Query Plan:
-/// === find_users_by_name_and_age (base) ===
-/// Seq Scan on users
-///   Filter: (((name)::text ~~* 'dummy'::text) AND ((name)::text = 'dummy'::text))
-/// JIT:
-///   Functions: 4
-///   Options: Inlining true, Optimization true, Expressions true, Deforming true
-/// 
-/// === find_users_by_name_and_age (variant 1) ===
-/// Bitmap Heap Scan on users
-///   Recheck Cond: (age >= 0)
-///   Filter: (((name)::text ~~* 'dummy'::text) AND ((name)::text = 'dummy'::text))
-///   ->  Bitmap Index Scan on idx_users_age_updated_at
-///         Index Cond: (age >= 0)
-/// 
-/// === find_users_by_name_and_age (variant 2) ===
-/// Index Scan using idx_users_age on users
-///   Index Cond: (age <= 0)
-///   Filter: (((name)::text ~~* 'dummy'::text) AND ((name)::text = 'dummy'::text))
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "SELECT id, name, email, age \nFROM public.users \nWHERE name ILIKE #{name_pattern} \n#[AND age >= #{min_age?}] \nAND name = #{name_exact} \n#[AND age <= #{max_age?}] \nORDER BY name"))]
-pub async fn find_users_by_name_and_age(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, name_pattern: String, min_age: Option<i32>, name_exact: String, max_age: Option<i32>) -> Result<Vec<FindUsersByNameAndAgeItem>, super::ErrorReadOnly> {
-    let mut final_sql = r"SELECT id, name, email, age 
-FROM public.users 
-WHERE name ILIKE $1 
-#[AND age >= #{min_age?}] 
-AND name = $2 
-#[AND age <= #{max_age?}] 
-ORDER BY name".to_string();
-    let mut included_params = Vec::new();
-
-    if min_age.is_some() {
-        final_sql = final_sql.replace(r"#[AND age >= #{min_age?}]", r"AND age >= #{min_age?}");
-        included_params.push("min_age");
-    } else {
-        final_sql = final_sql.replace(r"#[AND age >= #{min_age?}]", "");
-    }
-
-    if max_age.is_some() {
-        final_sql = final_sql.replace(r"#[AND age <= #{max_age?}]", r"AND age <= #{max_age?}");
-        included_params.push("max_age");
-    } else {
-        final_sql = final_sql.replace(r"#[AND age <= #{max_age?}]", "");
-    }
-
-    #[allow(unused_assignments)]
-    let mut param_counter = 1;
-    final_sql = final_sql.replace(r"#{name_pattern}", &format!("${}", param_counter));
-    param_counter += 1;
-    final_sql = final_sql.replace(r"#{name_exact}", &format!("${}", param_counter));
-    param_counter += 1;
-    if included_params.contains(&r"min_age") {
-        final_sql = final_sql.replace(r"#{min_age?}", &format!("${}", param_counter));
-        param_counter += 1;
-    }
-    if included_params.contains(&r"max_age") {
-        final_sql = final_sql.replace(r"#{max_age?}", &format!("${}", param_counter));
-        param_counter += 1;
-    }
-    let _ = param_counter; // Suppress unused assignment warning
-
-    let mut query = sqlx::query(&final_sql);
-
-    query = query.bind(&name_pattern);
-    query = query.bind(&name_exact);
-    if included_params.contains(&r"min_age") {
-        query = query.bind(min_age.as_ref().unwrap());
-    }
-
-    if included_params.contains(&r"max_age") {
-        query = query.bind(max_age.as_ref().unwrap());
-    }
-
-    let rows = query.fetch_all(executor).await?;
-    let result: Result<Vec<_>, sqlx::Error> = rows.iter().map(|row| {
-        Ok(FindUsersByNameAndAgeItem {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-        age: row.try_get::<Option<i32>, _>("age")?,
-    })
-    }).collect();
-    result.map_err(Into::into)
-}
-
-#[derive(Debug, Clone)]
-pub struct GetRecentUsersItem {
-    pub id: i32,
-    pub name: String,
-    pub email: String,
-    pub age: Option<i32>,
-    pub profile: Option<crate::models::UserProfile>,
-    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
-    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
-}
-
-/// Get public.users created after a specific timestamp - expects at least one user
-///
-/// Query Plan:
-/// Sort
-///   Sort Key: created_at DESC
-///   ->  Seq Scan on users
-///         Filter: (created_at > '1970-01-01 00:00:00+00'::timestamp with time zone)
-/// JIT:
-///   Functions: 4
-///   Options: Inlining true, Optimization true, Expressions true, Deforming true
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "SELECT id, name, email, age, profile, created_at, updated_at \nFROM public.users \nWHERE created_at > #{since} \nORDER BY created_at DESC"))]
-pub async fn get_recent_users(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, since: chrono::DateTime<chrono::Utc>) -> Result<Vec<GetRecentUsersItem>, super::ErrorReadOnly> {
-    let query = sqlx::query(
-        r"SELECT id, name, email, age, profile, created_at, updated_at 
+          updated_at"],
+            &["id", "name", "email", "age", "profile", "updated_at"],
+        ),
+        (
+            "find_users_by_name_and_age",
+            &[
+                r"SELECT id, name, email, age 
+        FROM public.users 
+        WHERE name ILIKE $1 
+         
+        AND name = $2 
+         
+        ORDER BY name",
+                r"SELECT id, name, email, age 
+        FROM public.users 
+        WHERE name ILIKE $1 
+        AND age >= $2 
+        AND name = $3 
+         
+        ORDER BY name",
+                r"SELECT id, name, email, age 
+        FROM public.users 
+        WHERE name ILIKE $1 
+         
+        AND name = $2 
+        AND age <= $3 
+        ORDER BY name",
+            ],
+            &["id", "name", "email", "age"],
+        ),
+        (
+            "get_recent_users",
+            &[
+                r"SELECT id, name, email, age, profile, created_at, updated_at 
         FROM public.users 
         WHERE created_at > $1 
-        ORDER BY created_at DESC"
-    );
-    let query = query.bind(since);
-    let rows = query.fetch_all(executor).await?;
-    if rows.is_empty() {
-        return Err(sqlx::Error::RowNotFound.into());
-    }
-    let result: Result<Vec<_>, sqlx::Error> = rows.iter().map(|row| {
-        Ok(GetRecentUsersItem {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-        age: row.try_get::<Option<i32>, _>("age")?,
-        profile: row.try_get::<Option<serde_json::Value>, _>("profile")?
-            .map(|v| serde_json::from_value::<crate::models::UserProfile>(v)
-            .map_err(|e| sqlx::Error::Decode(Box::new(e))))
-            .transpose()?,
-        created_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")?,
-        updated_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("updated_at")?,
-    })
-    }).collect();
-    result.map_err(Into::into)
-}
-
-#[derive(Debug, Clone)]
-pub struct GetActiveUsersByAgeRangeItem {
-    pub id: i32,
-    pub name: String,
-    pub email: String,
-    pub age: Option<i32>,
-    pub profile: Option<crate::models::UserProfile>,
-    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
-}
-
-/// Get active public.users within an age range - must return at least one user or fails
-///
-/// Query Plan:
-/// Index Scan using idx_users_age on users
-///   Index Cond: ((age >= 0) AND (age <= 0))
-///   Filter: (updated_at > (now - '30 days'::interval))
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "SELECT id, name, email, age, profile, created_at \nFROM public.users \nWHERE age BETWEEN #{min_age} AND #{max_age} \nAND updated_at > NOW() - INTERVAL '30 days'"))]
-pub async fn get_active_users_by_age_range(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, min_age: i32, max_age: i32) -> Result<Vec<GetActiveUsersByAgeRangeItem>, super::ErrorReadOnly> {
-    let query = sqlx::query(
-        r"SELECT id, name, email, age, profile, created_at 
+        ORDER BY created_at DESC",
+            ],
+            &[
+                "id",
+                "name",
+                "email",
+                "age",
+                "profile",
+                "created_at",
+                "updated_at",
+            ],
+        ),
+        (
+            "get_active_users_by_age_range",
+            &[r"SELECT id, name, email, age, profile, created_at 
         FROM public.users 
         WHERE age BETWEEN $1 AND $2 
-        AND updated_at > NOW() - INTERVAL '30 days'"
-    );
-    let query = query.bind(min_age);
-    let query = query.bind(max_age);
-    let rows = query.fetch_all(executor).await?;
-    if rows.is_empty() {
-        return Err(sqlx::Error::RowNotFound.into());
-    }
-    let result: Result<Vec<_>, sqlx::Error> = rows.iter().map(|row| {
-        Ok(GetActiveUsersByAgeRangeItem {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-        age: row.try_get::<Option<i32>, _>("age")?,
-        profile: row.try_get::<Option<serde_json::Value>, _>("profile")?
-            .map(|v| serde_json::from_value::<crate::models::UserProfile>(v)
-            .map_err(|e| sqlx::Error::Decode(Box::new(e))))
-            .transpose()?,
-        created_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")?,
-    })
-    }).collect();
-    result.map_err(Into::into)
-}
-
-#[derive(Debug, Clone)]
-pub struct SearchUsersByNamePatternItem {
-    pub id: i32,
-    pub name: String,
-    pub email: String,
-}
-
-/// Search public.users by name pattern - expects at least one match
-///
-/// Query Plan:
-/// Sort
-///   Sort Key: name
-///   ->  Seq Scan on users
-///         Filter: ((name)::text ~~* 'dummy'::text)
-/// JIT:
-///   Functions: 4
-///   Options: Inlining true, Optimization true, Expressions true, Deforming true
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "SELECT id, name, email \nFROM public.users \nWHERE name ILIKE #{pattern} \nORDER BY name"))]
-pub async fn search_users_by_name_pattern(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, pattern: String) -> Result<Vec<SearchUsersByNamePatternItem>, super::ErrorReadOnly> {
-    let query = sqlx::query(
-        r"SELECT id, name, email 
+        AND updated_at > NOW() - INTERVAL '30 days'"],
+            &["id", "name", "email", "age", "profile", "created_at"],
+        ),
+        (
+            "search_users_by_name_pattern",
+            &[r"SELECT id, name, email 
         FROM public.users 
         WHERE name ILIKE $1 
-        ORDER BY name"
-    );
-    let query = query.bind(&pattern);
-    let rows = query.fetch_all(executor).await?;
-    if rows.is_empty() {
-        return Err(sqlx::Error::RowNotFound.into());
-    }
-    let result: Result<Vec<_>, sqlx::Error> = rows.iter().map(|row| {
-        Ok(SearchUsersByNamePatternItem {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-    })
-    }).collect();
-    result.map_err(Into::into)
-}
-
-#[derive(Debug, Clone)]
-pub struct SearchUsersAdvancedItem {
-    pub id: i32,
-    pub name: String,
-    pub email: String,
-    pub age: Option<i32>,
-    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
-}
-
-/// Advanced user search with multiple optional filters using conditional syntax
-///
-/// Query Plan:
-/// === search_users_advanced (base) ===
-/// Sort
-///   Sort Key: created_at DESC
-///   ->  Seq Scan on users
-/// JIT:
-///   Functions: 2
-///   Options: Inlining true, Optimization true, Expressions true, Deforming true
-/// 
-/// === search_users_advanced (variant 1) ===
-/// Sort
-///   Sort Key: created_at DESC
-///   ->  Seq Scan on users
-///         Filter: ((name)::text ~~* 'dummy'::text)
-/// JIT:
-///   Functions: 4
-///   Options: Inlining true, Optimization true, Expressions true, Deforming true
-/// 
-/// === search_users_advanced (variant 2) ===
-/// Sort
-///   Sort Key: created_at DESC
-///   ->  Bitmap Heap Scan on users
-///         Recheck Cond: (age >= 0)
-///         ->  Bitmap Index Scan on idx_users_age_updated_at
-///               Index Cond: (age >= 0)
-/// 
-/// === search_users_advanced (variant 3) ===
-/// Sort
-///   Sort Key: created_at DESC
-///   ->  Seq Scan on users
-///         Filter: (created_at >= '1970-01-01 00:00:00+00'::timestamp with time zone)
-/// JIT:
-///   Functions: 4
-///   Options: Inlining true, Optimization true, Expressions true, Deforming true
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "SELECT id, name, email, age, created_at \nFROM public.users \nWHERE 1=1 \n#[AND name ILIKE #{name_pattern?}] \n#[AND age >= #{min_age?}] \n#[AND created_at >= #{since?}] \nORDER BY created_at DESC"))]
-pub async fn search_users_advanced(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, name_pattern: Option<String>, min_age: Option<i32>, since: Option<chrono::DateTime<chrono::Utc>>) -> Result<Vec<SearchUsersAdvancedItem>, super::ErrorReadOnly> {
-    let mut final_sql = r"SELECT id, name, email, age, created_at 
-FROM public.users 
-WHERE 1=1 
-#[AND name ILIKE #{name_pattern?}] 
-#[AND age >= #{min_age?}] 
-#[AND created_at >= #{since?}] 
-ORDER BY created_at DESC".to_string();
-    let mut included_params = Vec::new();
-
-    if name_pattern.is_some() {
-        final_sql = final_sql.replace(r"#[AND name ILIKE #{name_pattern?}]", r"AND name ILIKE #{name_pattern?}");
-        included_params.push("name_pattern");
-    } else {
-        final_sql = final_sql.replace(r"#[AND name ILIKE #{name_pattern?}]", "");
-    }
-
-    if min_age.is_some() {
-        final_sql = final_sql.replace(r"#[AND age >= #{min_age?}]", r"AND age >= #{min_age?}");
-        included_params.push("min_age");
-    } else {
-        final_sql = final_sql.replace(r"#[AND age >= #{min_age?}]", "");
-    }
-
-    if since.is_some() {
-        final_sql = final_sql.replace(r"#[AND created_at >= #{since?}]", r"AND created_at >= #{since?}");
-        included_params.push("since");
-    } else {
-        final_sql = final_sql.replace(r"#[AND created_at >= #{since?}]", "");
-    }
-
-    #[allow(unused_assignments)]
-    let mut param_counter = 1;
-    if included_params.contains(&r"name_pattern") {
-        final_sql = final_sql.replace(r"#{name_pattern?}", &format!("${}", param_counter));
-        param_counter += 1;
-    }
-    if included_params.contains(&r"min_age") {
-        final_sql = final_sql.replace(r"#{min_age?}", &format!("${}", param_counter));
-        param_counter += 1;
-    }
-    if included_params.contains(&r"since") {
-        final_sql = final_sql.replace(r"#{since?}", &format!("${}", param_counter));
-        param_counter += 1;
-    }
-    let _ = param_counter; // Suppress unused assignment warning
-
-    let mut query = sqlx::query(&final_sql);
-
-    if included_params.contains(&r"name_pattern") {
-        query = query.bind(name_pattern.as_ref().unwrap());
-    }
-
-    if included_params.contains(&r"min_age") {
-        query = query.bind(min_age.as_ref().unwrap());
-    }
-
-    if included_params.contains(&r"since") {
-        query = query.bind(since.as_ref().unwrap());
-    }
-
-    let rows = query.fetch_all(executor).await?;
-    let result: Result<Vec<_>, sqlx::Error> = rows.iter().map(|row| {
-        Ok(SearchUsersAdvancedItem {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-        age: row.try_get::<Option<i32>, _>("age")?,
-        created_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")?,
-    })
-    }).collect();
-    result.map_err(Into::into)
-}
-
-#[derive(Debug, Clone)]
-pub struct GetUsersByStatusItem {
-    pub id: i32,
-    pub name: String,
-    pub email: String,
-    pub status: Option<UserStatus>,
-}
-
-/// Get public.users by their status (enum parameter and enum output)
-///
-/// Query Plan:
-/// Sort
-///   Sort Key: name
-///   ->  Seq Scan on users
-///         Filter: (status = 'active'::public.user_status)
-/// JIT:
-///   Functions: 4
-///   Options: Inlining true, Optimization true, Expressions true, Deforming true
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "SELECT id, name, email, status \nFROM public.users \nWHERE status = #{user_status} \nORDER BY name"))]
-pub async fn get_users_by_status(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, user_status: UserStatus) -> Result<Vec<GetUsersByStatusItem>, super::ErrorReadOnly> {
-    let query = sqlx::query(
-        r"SELECT id, name, email, status 
+        ORDER BY name"],
+            &["id", "name", "email"],
+        ),
+        (
+            "search_users_advanced",
+            &[
+                r"SELECT id, name, email, age, created_at 
+        FROM public.users 
+        WHERE 1=1 
+         
+         
+         
+        ORDER BY created_at DESC",
+                r"SELECT id, name, email, age, created_at 
+        FROM public.users 
+        WHERE 1=1 
+        AND name ILIKE $1 
+         
+         
+        ORDER BY created_at DESC",
+                r"SELECT id, name, email, age, created_at 
+        FROM public.users 
+        WHERE 1=1 
+         
+        AND age >= $1 
+         
+        ORDER BY created_at DESC",
+                r"SELECT id, name, email, age, created_at 
+        FROM public.users 
+        WHERE 1=1 
+         
+         
+        AND created_at >= $1 
+        ORDER BY created_at DESC",
+                r"SELECT id, name, email, age, created_at 
+        FROM public.users 
+        WHERE 1=1 
+        AND name ILIKE $1 
+        AND age >= $2 
+         
+        ORDER BY created_at DESC",
+                r"SELECT id, name, email, age, created_at 
+        FROM public.users 
+        WHERE 1=1 
+        AND name ILIKE $1 
+         
+        AND created_at >= $2 
+        ORDER BY created_at DESC",
+                r"SELECT id, name, email, age, created_at 
+        FROM public.users 
+        WHERE 1=1 
+         
+        AND age >= $1 
+        AND created_at >= $2 
+        ORDER BY created_at DESC",
+                r"SELECT id, name, email, age, created_at 
+        FROM public.users 
+        WHERE 1=1 
+        AND name ILIKE $1 
+        AND age >= $2 
+        AND created_at >= $3 
+        ORDER BY created_at DESC",
+            ],
+            &["id", "name", "email", "age", "created_at"],
+        ),
+        (
+            "get_users_by_status",
+            &[r"SELECT id, name, email, status 
         FROM public.users 
         WHERE status = $1 
-        ORDER BY name"
-    );
-    let query = query.bind(user_status);
-    let rows = query.fetch_all(executor).await?;
-    let result: Result<Vec<_>, sqlx::Error> = rows.iter().map(|row| {
-        Ok(GetUsersByStatusItem {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-        status: row.try_get::<Option<UserStatus>, _>("status")?,
-    })
-    }).collect();
-    result.map_err(Into::into)
-}
-
-/// Constraint violations specific to this query
-#[derive(Debug)]
-pub enum UpdateUserStatusConstraints {
-    /// Constraint: users_email_key on table users
-    UsersEmailKey,
-    /// Constraint: users_pkey on table users
-    UsersPkey,
-    /// Constraint: users_referrer_id_fkey on table users
-    UsersReferrerIdFkey,
-    /// Constraint: users_id_not_null on table users
-    UsersIdNotNull,
-    /// Constraint: users_name_not_null on table users
-    UsersNameNotNull,
-    /// Constraint: users_email_not_null on table users
-    UsersEmailNotNull,
-}
-
-impl TryFrom<super::ErrorConstraintInfo> for UpdateUserStatusConstraints {
-    type Error = ();
-
-    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
-        match info.constraint_name.as_str() {
-            "users_email_key" => Ok(Self::UsersEmailKey),
-            "users_pkey" => Ok(Self::UsersPkey),
-            "users_referrer_id_fkey" => Ok(Self::UsersReferrerIdFkey),
-            "users_id_not_null" => Ok(Self::UsersIdNotNull),
-            "users_name_not_null" => Ok(Self::UsersNameNotNull),
-            "users_email_not_null" => Ok(Self::UsersEmailNotNull),
-            _ => Err(()),
-        }
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct UpdateUserStatusItem {
-    pub id: i32,
-    pub status: Option<UserStatus>,
-}
-
-/// Update user status and return the new status
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "UPDATE public.users \nSET status = #{new_status} \nWHERE id = #{user_id} \nRETURNING id, status"))]
-pub async fn update_user_status(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, new_status: UserStatus, user_id: i32) -> Result<UpdateUserStatusItem, super::Error<UpdateUserStatusConstraints>> {
-    let query = sqlx::query(
-        r"UPDATE public.users 
+        ORDER BY name"],
+            &["id", "name", "email", "status"],
+        ),
+        (
+            "update_user_status",
+            &[r"UPDATE public.users 
         SET status = $1 
         WHERE id = $2 
-        RETURNING id, status"
-    );
-    let query = query.bind(new_status);
-    let query = query.bind(user_id);
-    let row = query.fetch_one(executor).await?;
-    let result: Result<_, sqlx::Error> = (|| {
-        Ok(UpdateUserStatusItem {
-        id: row.try_get::<i32, _>("id")?,
-        status: row.try_get::<Option<UserStatus>, _>("status")?,
-    })
-    })();
-    result.map_err(Into::into)
-}
-
-/// Constraint violations specific to this query
-#[derive(Debug)]
-pub enum UpdateUserFieldsConstraints {
-    /// Constraint: users_email_key on table users
-    UsersEmailKey,
-    /// Constraint: users_pkey on table users
-    UsersPkey,
-    /// Constraint: users_referrer_id_fkey on table users
-    UsersReferrerIdFkey,
-    /// Constraint: users_id_not_null on table users
-    UsersIdNotNull,
-    /// Constraint: users_name_not_null on table users
-    UsersNameNotNull,
-    /// Constraint: users_email_not_null on table users
-    UsersEmailNotNull,
-}
-
-impl TryFrom<super::ErrorConstraintInfo> for UpdateUserFieldsConstraints {
-    type Error = ();
-
-    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
-        match info.constraint_name.as_str() {
-            "users_email_key" => Ok(Self::UsersEmailKey),
-            "users_pkey" => Ok(Self::UsersPkey),
-            "users_referrer_id_fkey" => Ok(Self::UsersReferrerIdFkey),
-            "users_id_not_null" => Ok(Self::UsersIdNotNull),
-            "users_name_not_null" => Ok(Self::UsersNameNotNull),
-            "users_email_not_null" => Ok(Self::UsersEmailNotNull),
-            _ => Err(()),
-        }
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct UpdateUserFieldsItem {
-    pub id: i32,
-    pub name: String,
-    pub email: String,
-    pub age: Option<i32>,
-    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
-}
-
-/// Update user fields conditionally - only updates fields that are provided (not None)
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "UPDATE public.users \nSET updated_at = NOW() \n#[, name = #{name?}] \n#[, email = #{email?}] \n#[, age = #{age?}] \nWHERE id = #{user_id} \nRETURNING id, name, email, age, updated_at"))]
-pub async fn update_user_fields(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, name: Option<String>, email: Option<String>, age: Option<i32>, user_id: i32) -> Result<UpdateUserFieldsItem, super::Error<UpdateUserFieldsConstraints>> {
-    let mut final_sql = r"UPDATE public.users 
-SET updated_at = NOW() 
-#[, name = #{name?}] 
-#[, email = #{email?}] 
-#[, age = #{age?}] 
-WHERE id = $1 
-RETURNING id, name, email, age, updated_at".to_string();
-    let mut included_params = Vec::new();
-
-    if name.is_some() {
-        final_sql = final_sql.replace(r"#[, name = #{name?}]", r", name = #{name?}");
-        included_params.push("name");
-    } else {
-        final_sql = final_sql.replace(r"#[, name = #{name?}]", "");
-    }
-
-    if email.is_some() {
-        final_sql = final_sql.replace(r"#[, email = #{email?}]", r", email = #{email?}");
-        included_params.push("email");
-    } else {
-        final_sql = final_sql.replace(r"#[, email = #{email?}]", "");
-    }
-
-    if age.is_some() {
-        final_sql = final_sql.replace(r"#[, age = #{age?}]", r", age = #{age?}");
-        included_params.push("age");
-    } else {
-        final_sql = final_sql.replace(r"#[, age = #{age?}]", "");
-    }
-
-    #[allow(unused_assignments)]
-    let mut param_counter = 1;
-    final_sql = final_sql.replace(r"#{user_id}", &format!("${}", param_counter));
-    param_counter += 1;
-    if included_params.contains(&r"name") {
-        final_sql = final_sql.replace(r"#{name?}", &format!("${}", param_counter));
-        param_counter += 1;
-    }
-    if included_params.contains(&r"email") {
-        final_sql = final_sql.replace(r"#{email?}", &format!("${}", param_counter));
-        param_counter += 1;
-    }
-    if included_params.contains(&r"age") {
-        final_sql = final_sql.replace(r"#{age?}", &format!("${}", param_counter));
-        param_counter += 1;
-    }
-    let _ = param_counter; // Suppress unused assignment warning
-
-    let mut query = sqlx::query(&final_sql);
-
-    query = query.bind(&user_id);
-    if included_params.contains(&r"name") {
-        query = query.bind(name.as_ref().unwrap());
-    }
-
-    if included_params.contains(&r"email") {
-        query = query.bind(email.as_ref().unwrap());
-    }
-
-    if included_params.contains(&r"age") {
-        query = query.bind(age.as_ref().unwrap());
-    }
-
-    let row = query.fetch_one(executor).await?;
-    let result: Result<_, sqlx::Error> = (|| {
-        Ok(UpdateUserFieldsItem {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-        age: row.try_get::<Option<i32>, _>("age")?,
-        updated_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("updated_at")?,
-    })
-    })();
-    result.map_err(Into::into)
-}
-
-/// Constraint violations specific to this query
-#[derive(Debug)]
-pub enum UpdateUserFieldsDiffConstraints {
-    /// Constraint: users_email_key on table users
-    UsersEmailKey,
-    /// Constraint: users_pkey on table users
-    UsersPkey,
-    /// Constraint: users_referrer_id_fkey on table users
-    UsersReferrerIdFkey,
-    /// Constraint: users_id_not_null on table users
-    UsersIdNotNull,
-    /// Constraint: users_name_not_null on table users
-    UsersNameNotNull,
-    /// Constraint: users_email_not_null on table users
-    UsersEmailNotNull,
-}
-
-impl TryFrom<super::ErrorConstraintInfo> for UpdateUserFieldsDiffConstraints {
-    type Error = ();
-
-    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
-        match info.constraint_name.as_str() {
-            "users_email_key" => Ok(Self::UsersEmailKey),
-            "users_pkey" => Ok(Self::UsersPkey),
-            "users_referrer_id_fkey" => Ok(Self::UsersReferrerIdFkey),
-            "users_id_not_null" => Ok(Self::UsersIdNotNull),
-            "users_name_not_null" => Ok(Self::UsersNameNotNull),
-            "users_email_not_null" => Ok(Self::UsersEmailNotNull),
-            _ => Err(()),
-        }
-    }
-}
-
-#[derive(Debug, Clone, PartialEq)]
-pub struct UpdateUserFieldsDiffParams {
-    pub name: String,
-    pub email: String,
-    pub age: i32,
-}
-
-#[derive(Debug, Clone)]
-pub struct UpdateUserFieldsDiffItem {
-    pub id: i32,
-    pub name: String,
-    pub email: String,
-    pub age: Option<i32>,
-    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
-}
-
-/// Update user fields using diff-based conditional updates - compares old and new structs
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "UPDATE public.users \nSET updated_at = NOW() \n#[, name = #{name?}] \n#[, email = #{email?}] \n#[, age = #{age?}] \nWHERE id = #{user_id} \nRETURNING id, name, email, age, updated_at"))]
-pub async fn update_user_fields_diff(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, old: &UpdateUserFieldsDiffParams, new: &UpdateUserFieldsDiffParams, user_id: i32) -> Result<UpdateUserFieldsDiffItem, super::Error<UpdateUserFieldsDiffConstraints>> {
-    let mut final_sql = r"UPDATE public.users 
-SET updated_at = NOW() 
-#[, name = #{name?}] 
-#[, email = #{email?}] 
-#[, age = #{age?}] 
-WHERE id = $1 
-RETURNING id, name, email, age, updated_at".to_string();
-    let mut included_params = Vec::new();
-
-    if old.name != new.name {
-        final_sql = final_sql.replace(r"#[, name = #{name?}]", r", name = #{name?}");
-        included_params.push("name");
-    } else {
-        final_sql = final_sql.replace(r"#[, name = #{name?}]", "");
-    }
-
-    if old.email != new.email {
-        final_sql = final_sql.replace(r"#[, email = #{email?}]", r", email = #{email?}");
-        included_params.push("email");
-    } else {
-        final_sql = final_sql.replace(r"#[, email = #{email?}]", "");
-    }
-
-    if old.age != new.age {
-        final_sql = final_sql.replace(r"#[, age = #{age?}]", r", age = #{age?}");
-        included_params.push("age");
-    } else {
-        final_sql = final_sql.replace(r"#[, age = #{age?}]", "");
-    }
-
-    #[allow(unused_assignments)]
-    let mut param_counter = 1;
-    final_sql = final_sql.replace(r"#{user_id}", &format!("${}", param_counter));
-    param_counter += 1;
-    if included_params.contains(&r"name") {
-        final_sql = final_sql.replace(r"#{name?}", &format!("${}", param_counter));
-        param_counter += 1;
-    }
-    if included_params.contains(&r"email") {
-        final_sql = final_sql.replace(r"#{email?}", &format!("${}", param_counter));
-        param_counter += 1;
-    }
-    if included_params.contains(&r"age") {
-        final_sql = final_sql.replace(r"#{age?}", &format!("${}", param_counter));
-        param_counter += 1;
-    }
-    let _ = param_counter; // Suppress unused assignment warning
-
-    let mut query = sqlx::query(&final_sql);
-
-    query = query.bind(&user_id);
-    if included_params.contains(&r"name") {
-        query = query.bind(&new.name);
-    }
-
-    if included_params.contains(&r"email") {
-        query = query.bind(&new.email);
-    }
-
-    if included_params.contains(&r"age") {
-        query = query.bind(&new.age);
-    }
-
-    let row = query.fetch_one(executor).await?;
-    let result: Result<_, sqlx::Error> = (|| {
-        Ok(UpdateUserFieldsDiffItem {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-        age: row.try_get::<Option<i32>, _>("age")?,
-        updated_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("updated_at")?,
-    })
-    })();
-    result.map_err(Into::into)
-}
-
-/// Constraint violations specific to this query
-#[derive(Debug)]
-pub enum InsertUserStructuredConstraints {
-    /// Constraint: users_email_key on table users
-    UsersEmailKey,
-    /// Constraint: users_pkey on table users
-    UsersPkey,
-    /// Constraint: users_referrer_id_fkey on table users
-    UsersReferrerIdFkey,
-    /// Constraint: users_id_not_null on table users
-    UsersIdNotNull,
-    /// Constraint: users_name_not_null on table users
-    UsersNameNotNull,
-    /// Constraint: users_email_not_null on table users
-    UsersEmailNotNull,
-}
-
-impl TryFrom<super::ErrorConstraintInfo> for InsertUserStructuredConstraints {
-    type Error = ();
-
-    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
-        match info.constraint_name.as_str() {
-            "users_email_key" => Ok(Self::UsersEmailKey),
-            "users_pkey" => Ok(Self::UsersPkey),
-            "users_referrer_id_fkey" => Ok(Self::UsersReferrerIdFkey),
-            "users_id_not_null" => Ok(Self::UsersIdNotNull),
-            "users_name_not_null" => Ok(Self::UsersNameNotNull),
-            "users_email_not_null" => Ok(Self::UsersEmailNotNull),
-            _ => Err(()),
-        }
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct InsertUserStructuredParams {
-    pub name: String,
-    pub email: String,
-    pub age: i32,
-}
-
-#[derive(Debug, Clone)]
-pub struct InsertUserStructuredItem {
-    pub id: i32,
-    pub name: String,
-    pub email: String,
-    pub age: Option<i32>,
-    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
-}
-
-/// Insert a new user using structured parameters - all params passed as a single struct
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "INSERT INTO public.users (name, email, age) \nVALUES (#{name}, #{email}, #{age}) \nRETURNING id, name, email, age, created_at"))]
-pub async fn insert_user_structured(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, params: &InsertUserStructuredParams) -> Result<InsertUserStructuredItem, super::Error<InsertUserStructuredConstraints>> {
-    let query = sqlx::query(
-        r"INSERT INTO public.users (name, email, age) 
+        RETURNING id, status"],
+            &["id", "status"],
+        ),
+        (
+            "update_user_fields",
+            &[
+                r"UPDATE public.users 
+        SET updated_at = NOW() 
+         
+         
+         
+        WHERE id = $1 
+        RETURNING id, name, email, age, updated_at",
+                r"UPDATE public.users 
+        SET updated_at = NOW() 
+        , name = $1 
+         
+         
+        WHERE id = $2 
+        RETURNING id, name, email, age, updated_at",
+                r"UPDATE public.users 
+        SET updated_at = NOW() 
+         
+        , email = $1 
+         
+        WHERE id = $2 
+        RETURNING id, name, email, age, updated_at",
+                r"UPDATE public.users 
+        SET updated_at = NOW() 
+         
+         
+        , age = $1 
+        WHERE id = $2 
+        RETURNING id, name, email, age, updated_at",
+            ],
+            &["id", "name", "email", "age", "updated_at"],
+        ),
+        (
+            "update_user_fields_diff",
+            &[
+                r"UPDATE public.users 
+        SET updated_at = NOW() 
+         
+         
+         
+        WHERE id = $1 
+        RETURNING id, name, email, age, updated_at",
+                r"UPDATE public.users 
+        SET updated_at = NOW() 
+        , name = $1 
+         
+         
+        WHERE id = $2 
+        RETURNING id, name, email, age, updated_at",
+                r"UPDATE public.users 
+        SET updated_at = NOW() 
+         
+        , email = $1 
+         
+        WHERE id = $2 
+        RETURNING id, name, email, age, updated_at",
+                r"UPDATE public.users 
+        SET updated_at = NOW() 
+         
+         
+        , age = $1 
+        WHERE id = $2 
+        RETURNING id, name, email, age, updated_at",
+            ],
+            &["id", "name", "email", "age", "updated_at"],
+        ),
+        (
+            "insert_user_structured",
+            &[r"INSERT INTO public.users (name, email, age) 
         VALUES ($1, $2, $3) 
-        RETURNING id, name, email, age, created_at"
-    );
-    let query = query.bind(&params.name);
-    let query = query.bind(&params.email);
-    let query = query.bind(params.age);
-    let row = query.fetch_one(executor).await?;
-    let result: Result<_, sqlx::Error> = (|| {
-        Ok(InsertUserStructuredItem {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-        age: row.try_get::<Option<i32>, _>("age")?,
-        created_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")?,
-    })
-    })();
-    result.map_err(Into::into)
-}
-
-/// Get all possible user statuses currently in use
-///
-/// Query Plan:
-/// Unique
-///   ->  Sort
-///         Sort Key: status
-///         ->  Seq Scan on users
-/// JIT:
-///   Functions: 3
-///   Options: Inlining true, Optimization true, Expressions true, Deforming true
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "SELECT DISTINCT status \nFROM public.users \nORDER BY status"))]
-pub async fn get_all_user_statuses(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>) -> Result<Vec<Option<UserStatus>>, super::ErrorReadOnly> {
-    let query = sqlx::query(
-        r"SELECT DISTINCT status 
+        RETURNING id, name, email, age, created_at"],
+            &["id", "name", "email", "age", "created_at"],
+        ),
+        (
+            "get_all_user_statuses",
+            &[r"SELECT DISTINCT status 
         FROM public.users 
-        ORDER BY status"
-    );
-    let rows = query.fetch_all(executor).await?;
-    let result: Result<Vec<_>, sqlx::Error> = rows.iter().map(|row| {
-        Ok(row.try_get::<Option<UserStatus>, _>("status")?)
-    }).collect();
-    result.map_err(Into::into)
-}
-
-#[derive(Debug, Clone)]
-pub struct GetAllUsersWithStarItem {
-    pub id: i32,
-    pub name: String,
-    pub email: String,
-    pub status: Option<UserStatus>,
-    pub profile: Option<crate::models::UserProfile>,
-    pub settings: Option<serde_json::Value>,
-    pub is_active: Option<bool>,
-    pub age: Option<i32>,
-    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
-    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
-    pub referrer_id: Option<i32>,
-}
-
-/// Get all public.users using SELECT * to fetch all columns
-///
-/// Query Plan:
-/// Sort
-///   Sort Key: created_at DESC
-///   ->  Seq Scan on users
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "SELECT * \nFROM public.users \nORDER BY created_at DESC"))]
-pub async fn get_all_users_with_star(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>) -> Result<Vec<GetAllUsersWithStarItem>, super::ErrorReadOnly> {
-    let query = sqlx::query(
-        r"SELECT * 
+        ORDER BY status"],
+            &["status"],
+        ),
+        (
+            "get_all_users_with_star",
+            &[r"SELECT * 
         FROM public.users 
-        ORDER BY created_at DESC"
-    );
-    let rows = query.fetch_all(executor).await?;
-    let result: Result<Vec<_>, sqlx::Error> = rows.iter().map(|row| {
-        Ok(GetAllUsersWithStarItem {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-        status: row.try_get::<Option<UserStatus>, _>("status")?,
-        profile: row.try_get::<Option<serde_json::Value>, _>("profile")?
-            .map(|v| serde_json::from_value::<crate::models::UserProfile>(v)
-            .map_err(|e| sqlx::Error::Decode(Box::new(e))))
-            .transpose()?,
-        settings: row.try_get::<Option<serde_json::Value>, _>("settings")?,
-        is_active: row.try_get::<Option<bool>, _>("is_active")?,
-        age: row.try_get::<Option<i32>, _>("age")?,
-        created_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")?,
-        updated_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("updated_at")?,
-        referrer_id: row.try_get::<Option<i32>, _>("referrer_id")?,
-    })
-    }).collect();
-    result.map_err(Into::into)
-}
-
-#[derive(Debug, Clone)]
-pub struct GetUserByIdWithStarItem {
-    pub id: i32,
-    pub name: String,
-    pub email: String,
-    pub status: Option<UserStatus>,
-    pub profile: Option<crate::models::UserProfile>,
-    pub settings: Option<serde_json::Value>,
-    pub is_active: Option<bool>,
-    pub age: Option<i32>,
-    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
-    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
-    pub referrer_id: Option<i32>,
-}
-
-/// Get a single user by ID using SELECT * to fetch all columns
-///
-/// Query Plan:
-/// Index Scan using users_pkey on users
-///   Index Cond: (id = 0)
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "SELECT * \nFROM public.users \nWHERE id = #{user_id}"))]
-pub async fn get_user_by_id_with_star(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, user_id: i32) -> Result<Option<GetUserByIdWithStarItem>, super::ErrorReadOnly> {
-    let query = sqlx::query(
-        r"SELECT * 
+        ORDER BY created_at DESC"],
+            &[
+                "id",
+                "name",
+                "email",
+                "status",
+                "profile",
+                "settings",
+                "is_active",
+                "age",
+                "created_at",
+                "updated_at",
+                "referrer_id",
+            ],
+        ),
+        (
+            "get_user_by_id_with_star",
+            &[r"SELECT * 
         FROM public.users 
-        WHERE id = $1"
-    );
-    let query = query.bind(user_id);
-    let row = query.fetch_optional(executor).await?;
-    match row {
-        Some(row) => {
-            let result: Result<_, sqlx::Error> = (|| {
-                Ok(GetUserByIdWithStarItem {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-        status: row.try_get::<Option<UserStatus>, _>("status")?,
-        profile: row.try_get::<Option<serde_json::Value>, _>("profile")?
-            .map(|v| serde_json::from_value::<crate::models::UserProfile>(v)
-            .map_err(|e| sqlx::Error::Decode(Box::new(e))))
-            .transpose()?,
-        settings: row.try_get::<Option<serde_json::Value>, _>("settings")?,
-        is_active: row.try_get::<Option<bool>, _>("is_active")?,
-        age: row.try_get::<Option<i32>, _>("age")?,
-        created_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")?,
-        updated_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("updated_at")?,
-        referrer_id: row.try_get::<Option<i32>, _>("referrer_id")?,
-    })
-            })();
-            result.map(Some).map_err(Into::into)
-        },
-        None => Ok(None),
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct GetUserByIdAndEmailParams {
-    pub id: i32,
-    pub email: String,
-}
-
-#[derive(Debug, Clone)]
-pub struct GetUserByIdAndEmailItem {
-    pub id: i32,
-    pub name: String,
-    pub email: String,
-}
-
-/// Get a user by ID and email - generates GetUserByIdAndEmailParams struct and GetUserByIdAndEmailItem return struct
-///
-/// Query Plan:
-/// Index Scan using users_email_key on users
-///   Index Cond: ((email)::text = 'dummy'::text)
-///   Filter: (id = 0)
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "SELECT id, name, email \nFROM public.users \nWHERE id = #{id} AND email = #{email}"))]
-pub async fn get_user_by_id_and_email(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, params: &GetUserByIdAndEmailParams) -> Result<Option<GetUserByIdAndEmailItem>, super::ErrorReadOnly> {
-    let query = sqlx::query(
-        r"SELECT id, name, email 
+        WHERE id = $1"],
+            &[
+                "id",
+                "name",
+                "email",
+                "status",
+                "profile",
+                "settings",
+                "is_active",
+                "age",
+                "created_at",
+                "updated_at",
+                "referrer_id",
+            ],
+        ),
+        (
+            "get_user_by_id_and_email",
+            &[r"SELECT id, name, email 
         FROM public.users 
-        WHERE id = $1 AND email = $2"
-    );
-    let query = query.bind(params.id);
-    let query = query.bind(&params.email);
-    let row = query.fetch_optional(executor).await?;
-    match row {
-        Some(row) => {
-            let result: Result<_, sqlx::Error> = (|| {
-                Ok(GetUserByIdAndEmailItem {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-    })
-            })();
-            result.map(Some).map_err(Into::into)
-        },
-        None => Ok(None),
-    }
-}
-
-/// Constraint violations specific to this query
-#[derive(Debug)]
-pub enum DeleteUserByIdAndEmailConstraints {
-    /// Constraint: users_email_key on table users
-    UsersEmailKey,
-    /// Constraint: users_pkey on table users
-    UsersPkey,
-    /// Constraint: users_referrer_id_fkey on table users
-    UsersReferrerIdFkey,
-    /// Constraint: users_id_not_null on table users
-    UsersIdNotNull,
-    /// Constraint: users_name_not_null on table users
-    UsersNameNotNull,
-    /// Constraint: users_email_not_null on table users
-    UsersEmailNotNull,
-}
-
-impl TryFrom<super::ErrorConstraintInfo> for DeleteUserByIdAndEmailConstraints {
-    type Error = ();
-
-    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
-        match info.constraint_name.as_str() {
-            "users_email_key" => Ok(Self::UsersEmailKey),
-            "users_pkey" => Ok(Self::UsersPkey),
-            "users_referrer_id_fkey" => Ok(Self::UsersReferrerIdFkey),
-            "users_id_not_null" => Ok(Self::UsersIdNotNull),
-            "users_name_not_null" => Ok(Self::UsersNameNotNull),
-            "users_email_not_null" => Ok(Self::UsersEmailNotNull),
-            _ => Err(()),
-        }
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct DeleteUserByIdAndEmailItem {
-    pub id: i32,
-    pub email: String,
-}
-
-/// Delete user by ID and email - reuses GetUserByIdAndEmailParams struct
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "DELETE FROM public.users \nWHERE id = #{id} AND email = #{email} \nRETURNING id, email"))]
-pub async fn delete_user_by_id_and_email(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, params: &GetUserByIdAndEmailParams) -> Result<DeleteUserByIdAndEmailItem, super::Error<DeleteUserByIdAndEmailConstraints>> {
-    let query = sqlx::query(
-        r"DELETE FROM public.users 
+        WHERE id = $1 AND email = $2"],
+            &["id", "name", "email"],
+        ),
+        (
+            "delete_user_by_id_and_email",
+            &[r"DELETE FROM public.users 
         WHERE id = $1 AND email = $2 
-        RETURNING id, email"
-    );
-    let query = query.bind(params.id);
-    let query = query.bind(&params.email);
-    let row = query.fetch_one(executor).await?;
-    let result: Result<_, sqlx::Error> = (|| {
-        Ok(DeleteUserByIdAndEmailItem {
-        id: row.try_get::<i32, _>("id")?,
-        email: row.try_get::<String, _>("email")?,
-    })
-    })();
-    result.map_err(Into::into)
-}
-
-/// Constraint violations specific to this query
-#[derive(Debug)]
-pub enum UpdateUserContactInfoConstraints {
-    /// Constraint: users_email_key on table users
-    UsersEmailKey,
-    /// Constraint: users_pkey on table users
-    UsersPkey,
-    /// Constraint: users_referrer_id_fkey on table users
-    UsersReferrerIdFkey,
-    /// Constraint: users_id_not_null on table users
-    UsersIdNotNull,
-    /// Constraint: users_name_not_null on table users
-    UsersNameNotNull,
-    /// Constraint: users_email_not_null on table users
-    UsersEmailNotNull,
-}
-
-impl TryFrom<super::ErrorConstraintInfo> for UpdateUserContactInfoConstraints {
-    type Error = ();
-
-    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
-        match info.constraint_name.as_str() {
-            "users_email_key" => Ok(Self::UsersEmailKey),
-            "users_pkey" => Ok(Self::UsersPkey),
-            "users_referrer_id_fkey" => Ok(Self::UsersReferrerIdFkey),
-            "users_id_not_null" => Ok(Self::UsersIdNotNull),
-            "users_name_not_null" => Ok(Self::UsersNameNotNull),
-            "users_email_not_null" => Ok(Self::UsersEmailNotNull),
-            _ => Err(()),
-        }
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct UpdateUserContactInfoItem {
-    pub id: i32,
-    pub name: String,
-    pub email: String,
-}
-
-/// Update user contact info - reuses GetUserByIdAndEmailItem return struct as params
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "UPDATE public.users \nSET name = #{name}, email = #{email} \nWHERE id = #{id} \nRETURNING id, name, email"))]
-pub async fn update_user_contact_info(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, params: &GetUserByIdAndEmailItem) -> Result<UpdateUserContactInfoItem, super::Error<UpdateUserContactInfoConstraints>> {
-    let query = sqlx::query(
-        r"UPDATE public.users 
+        RETURNING id, email"],
+            &["id", "email"],
+        ),
+        (
+            "update_user_contact_info",
+            &[r"UPDATE public.users 
         SET name = $1, email = $2 
         WHERE id = $3 
-        RETURNING id, name, email"
-    );
-    let query = query.bind(&params.name);
-    let query = query.bind(&params.email);
-    let query = query.bind(params.id);
-    let row = query.fetch_one(executor).await?;
-    let result: Result<_, sqlx::Error> = (|| {
-        Ok(UpdateUserContactInfoItem {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-    })
-    })();
-    result.map_err(Into::into)
-}
-
-/// Constraint violations specific to this query
-#[derive(Debug)]
-pub enum UpdateUserProfileDiffConstraints {
-    /// Constraint: users_email_key on table users
-    UsersEmailKey,
-    /// Constraint: users_pkey on table users
-    UsersPkey,
-    /// Constraint: users_referrer_id_fkey on table users
-    UsersReferrerIdFkey,
-    /// Constraint: users_id_not_null on table users
-    UsersIdNotNull,
-    /// Constraint: users_name_not_null on table users
-    UsersNameNotNull,
-    /// Constraint: users_email_not_null on table users
-    UsersEmailNotNull,
-}
-
-impl TryFrom<super::ErrorConstraintInfo> for UpdateUserProfileDiffConstraints {
-    type Error = ();
-
-    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
-        match info.constraint_name.as_str() {
-            "users_email_key" => Ok(Self::UsersEmailKey),
-            "users_pkey" => Ok(Self::UsersPkey),
-            "users_referrer_id_fkey" => Ok(Self::UsersReferrerIdFkey),
-            "users_id_not_null" => Ok(Self::UsersIdNotNull),
-            "users_name_not_null" => Ok(Self::UsersNameNotNull),
-            "users_email_not_null" => Ok(Self::UsersEmailNotNull),
-            _ => Err(()),
-        }
-    }
-}
-
-#[derive(Debug, Clone, PartialEq)]
-pub struct UpdateUserProfileDiffParams {
-    pub name: String,
-    pub email: String,
-}
-
-#[derive(Debug, Clone)]
-pub struct UpdateUserProfileDiffItem {
-    pub id: i32,
-    pub name: String,
-    pub email: String,
-    pub profile: Option<crate::models::UserProfile>,
-    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
-}
-
-/// Update user profile with conditional name/email - generates UpdateUserProfileDiffParams
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "UPDATE public.users \nSET profile = #{profile}, updated_at = NOW() \n#[, name = #{name?}] \n#[, email = #{email?}] \nWHERE id = #{user_id} \nRETURNING id, name, email, profile, updated_at"))]
-pub async fn update_user_profile_diff(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, old: &UpdateUserProfileDiffParams, new: &UpdateUserProfileDiffParams, profile: crate::models::UserProfile, user_id: i32) -> Result<UpdateUserProfileDiffItem, super::Error<UpdateUserProfileDiffConstraints>> {
-    let mut final_sql = r"UPDATE public.users 
-SET profile = $1, updated_at = NOW() 
-#[, name = #{name?}] 
-#[, email = #{email?}] 
-WHERE id = $2 
-RETURNING id, name, email, profile, updated_at".to_string();
-    let mut included_params = Vec::new();
-
-    if old.name != new.name {
-        final_sql = final_sql.replace(r"#[, name = #{name?}]", r", name = #{name?}");
-        included_params.push("name");
-    } else {
-        final_sql = final_sql.replace(r"#[, name = #{name?}]", "");
-    }
-
-    if old.email != new.email {
-        final_sql = final_sql.replace(r"#[, email = #{email?}]", r", email = #{email?}");
-        included_params.push("email");
-    } else {
-        final_sql = final_sql.replace(r"#[, email = #{email?}]", "");
-    }
-
-    #[allow(unused_assignments)]
-    let mut param_counter = 1;
-    final_sql = final_sql.replace(r"#{profile}", &format!("${}", param_counter));
-    param_counter += 1;
-    final_sql = final_sql.replace(r"#{user_id}", &format!("${}", param_counter));
-    param_counter += 1;
-    if included_params.contains(&r"name") {
-        final_sql = final_sql.replace(r"#{name?}", &format!("${}", param_counter));
-        param_counter += 1;
-    }
-    if included_params.contains(&r"email") {
-        final_sql = final_sql.replace(r"#{email?}", &format!("${}", param_counter));
-        param_counter += 1;
-    }
-    let _ = param_counter; // Suppress unused assignment warning
-
-    let mut query = sqlx::query(&final_sql);
-
-    let profile_json = serde_json::to_value(&profile).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-    query = query.bind(profile_json);
-    query = query.bind(&user_id);
-    if included_params.contains(&r"name") {
-        query = query.bind(&new.name);
-    }
-
-    if included_params.contains(&r"email") {
-        query = query.bind(&new.email);
-    }
-
-    let row = query.fetch_one(executor).await?;
-    let result: Result<_, sqlx::Error> = (|| {
-        Ok(UpdateUserProfileDiffItem {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-        profile: row.try_get::<Option<serde_json::Value>, _>("profile")?
-            .map(|v| serde_json::from_value::<crate::models::UserProfile>(v)
-            .map_err(|e| sqlx::Error::Decode(Box::new(e))))
-            .transpose()?,
-        updated_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("updated_at")?,
-    })
-    })();
-    result.map_err(Into::into)
-}
-
-/// Constraint violations specific to this query
-#[derive(Debug)]
-pub enum UpdateUserMetadataDiffConstraints {
-    /// Constraint: users_email_key on table users
-    UsersEmailKey,
-    /// Constraint: users_pkey on table users
-    UsersPkey,
-    /// Constraint: users_referrer_id_fkey on table users
-    UsersReferrerIdFkey,
-    /// Constraint: users_id_not_null on table users
-    UsersIdNotNull,
-    /// Constraint: users_name_not_null on table users
-    UsersNameNotNull,
-    /// Constraint: users_email_not_null on table users
-    UsersEmailNotNull,
-}
-
-impl TryFrom<super::ErrorConstraintInfo> for UpdateUserMetadataDiffConstraints {
-    type Error = ();
-
-    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
-        match info.constraint_name.as_str() {
-            "users_email_key" => Ok(Self::UsersEmailKey),
-            "users_pkey" => Ok(Self::UsersPkey),
-            "users_referrer_id_fkey" => Ok(Self::UsersReferrerIdFkey),
-            "users_id_not_null" => Ok(Self::UsersIdNotNull),
-            "users_name_not_null" => Ok(Self::UsersNameNotNull),
-            "users_email_not_null" => Ok(Self::UsersEmailNotNull),
-            _ => Err(()),
-        }
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct UpdateUserMetadataDiffItem {
-    pub id: i32,
-    pub name: String,
-    pub email: String,
-    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
-}
-
-/// Update user metadata - reuses UpdateUserProfileDiffParams struct
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "UPDATE public.users \nSET profile = #{profile}, updated_at = NOW() \n#[, name = #{name?}] \n#[, email = #{email?}] \nWHERE id = #{user_id} \nRETURNING id, name, email, updated_at"))]
-pub async fn update_user_metadata_diff(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, old: &UpdateUserProfileDiffParams, new: &UpdateUserProfileDiffParams, profile: crate::models::UserProfile, user_id: i32) -> Result<UpdateUserMetadataDiffItem, super::Error<UpdateUserMetadataDiffConstraints>> {
-    let mut final_sql = r"UPDATE public.users 
-SET profile = $1, updated_at = NOW() 
-#[, name = #{name?}] 
-#[, email = #{email?}] 
-WHERE id = $2 
-RETURNING id, name, email, updated_at".to_string();
-    let mut included_params = Vec::new();
-
-    if old.name != new.name {
-        final_sql = final_sql.replace(r"#[, name = #{name?}]", r", name = #{name?}");
-        included_params.push("name");
-    } else {
-        final_sql = final_sql.replace(r"#[, name = #{name?}]", "");
-    }
-
-    if old.email != new.email {
-        final_sql = final_sql.replace(r"#[, email = #{email?}]", r", email = #{email?}");
-        included_params.push("email");
-    } else {
-        final_sql = final_sql.replace(r"#[, email = #{email?}]", "");
-    }
-
-    #[allow(unused_assignments)]
-    let mut param_counter = 1;
-    final_sql = final_sql.replace(r"#{profile}", &format!("${}", param_counter));
-    param_counter += 1;
-    final_sql = final_sql.replace(r"#{user_id}", &format!("${}", param_counter));
-    param_counter += 1;
-    if included_params.contains(&r"name") {
-        final_sql = final_sql.replace(r"#{name?}", &format!("${}", param_counter));
-        param_counter += 1;
-    }
-    if included_params.contains(&r"email") {
-        final_sql = final_sql.replace(r"#{email?}", &format!("${}", param_counter));
-        param_counter += 1;
-    }
-    let _ = param_counter; // Suppress unused assignment warning
-
-    let mut query = sqlx::query(&final_sql);
-
-    let profile_json = serde_json::to_value(&profile).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
-    query = query.bind(profile_json);
-    query = query.bind(&user_id);
-    if included_params.contains(&r"name") {
-        query = query.bind(&new.name);
-    }
-
-    if included_params.contains(&r"email") {
-        query = query.bind(&new.email);
-    }
-
-    let row = query.fetch_one(executor).await?;
-    let result: Result<_, sqlx::Error> = (|| {
-        Ok(UpdateUserMetadataDiffItem {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-        updated_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("updated_at")?,
-    })
-    })();
-    result.map_err(Into::into)
-}
-
-#[derive(Debug, Clone)]
-pub struct UserSummary {
-    pub id: i32,
-    pub name: String,
-    pub email: String,
-}
-
-/// Get user summary - generates UserSummary return struct with custom name
-///
-/// Query Plan:
-/// Index Scan using users_pkey on users
-///   Index Cond: (id = 0)
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "SELECT id, name, email \nFROM public.users \nWHERE id = #{user_id}"))]
-pub async fn get_user_summary(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, user_id: i32) -> Result<UserSummary, super::ErrorReadOnly> {
-    let query = sqlx::query(
-        r"SELECT id, name, email 
+        RETURNING id, name, email"],
+            &["id", "name", "email"],
+        ),
+        (
+            "update_user_profile_diff",
+            &[
+                r"UPDATE public.users 
+        SET profile = $1, updated_at = NOW() 
+         
+         
+        WHERE id = $2 
+        RETURNING id, name, email, profile, updated_at",
+                r"UPDATE public.users 
+        SET profile = $1, updated_at = NOW() 
+        , name = $2 
+         
+        WHERE id = $3 
+        RETURNING id, name, email, profile, updated_at",
+                r"UPDATE public.users 
+        SET profile = $1, updated_at = NOW() 
+         
+        , email = $2 
+        WHERE id = $3 
+        RETURNING id, name, email, profile, updated_at",
+            ],
+            &["id", "name", "email", "profile", "updated_at"],
+        ),
+        (
+            "update_user_metadata_diff",
+            &[
+                r"UPDATE public.users 
+        SET profile = $1, updated_at = NOW() 
+         
+         
+        WHERE id = $2 
+        RETURNING id, name, email, updated_at",
+                r"UPDATE public.users 
+        SET profile = $1, updated_at = NOW() 
+        , name = $2 
+         
+        WHERE id = $3 
+        RETURNING id, name, email, updated_at",
+                r"UPDATE public.users 
+        SET profile = $1, updated_at = NOW() 
+         
+        , email = $2 
+        WHERE id = $3 
+        RETURNING id, name, email, updated_at",
+            ],
+            &["id", "name", "email", "updated_at"],
+        ),
+        (
+            "get_user_summary",
+            &[r"SELECT id, name, email 
         FROM public.users 
-        WHERE id = $1"
-    );
-    let query = query.bind(user_id);
-    let row = query.fetch_one(executor).await?;
-    let result: Result<_, sqlx::Error> = (|| {
-        Ok(UserSummary {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-    })
-    })();
-    result.map_err(Into::into)
-}
-
-/// Get user info by email - reuses UserSummary return struct
-///
-/// Query Plan:
-/// Index Scan using users_email_key on users
-///   Index Cond: ((email)::text = 'dummy'::text)
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "SELECT id, name, email \nFROM public.users \nWHERE email = #{email}"))]
-pub async fn get_user_info_by_email(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, email: String) -> Result<Option<UserSummary>, super::ErrorReadOnly> {
-    let query = sqlx::query(
-        r"SELECT id, name, email 
+        WHERE id = $1"],
+            &["id", "name", "email"],
+        ),
+        (
+            "get_user_info_by_email",
+            &[r"SELECT id, name, email 
         FROM public.users 
-        WHERE email = $1"
-    );
-    let query = query.bind(&email);
-    let row = query.fetch_optional(executor).await?;
-    match row {
-        Some(row) => {
-            let result: Result<_, sqlx::Error> = (|| {
-                Ok(UserSummary {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-    })
-            })();
-            result.map(Some).map_err(Into::into)
-        },
-        None => Ok(None),
-    }
-}
-
-/// Get all user summaries - reuses UserSummary return struct
-///
-/// Query Plan:
-/// Sort
-///   Sort Key: name
-///   ->  Seq Scan on users
-/// JIT:
-///   Functions: 2
-///   Options: Inlining true, Optimization true, Expressions true, Deforming true
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "SELECT id, name, email \nFROM public.users \nORDER BY name"))]
-pub async fn get_all_user_summaries(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>) -> Result<Vec<UserSummary>, super::ErrorReadOnly> {
-    let query = sqlx::query(
-        r"SELECT id, name, email 
+        WHERE email = $1"],
+            &["id", "name", "email"],
+        ),
+        (
+            "get_all_user_summaries",
+            &[r"SELECT id, name, email 
         FROM public.users 
-        ORDER BY name"
-    );
-    let rows = query.fetch_all(executor).await?;
-    let result: Result<Vec<_>, sqlx::Error> = rows.iter().map(|row| {
-        Ok(UserSummary {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-    })
-    }).collect();
-    result.map_err(Into::into)
-}
-
-#[derive(Debug, Clone)]
-pub struct UserDetails {
-    pub id: i32,
-    pub name: String,
-    pub email: String,
-    pub age: Option<i32>,
-    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
-}
-
-/// Get user details with age and created_at - generates UserDetails return struct
-///
-/// Query Plan:
-/// Index Scan using users_pkey on users
-///   Index Cond: (id = 0)
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "SELECT id, name, email, age, created_at \nFROM public.users \nWHERE id = #{user_id}"))]
-pub async fn get_user_details(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, user_id: i32) -> Result<UserDetails, super::ErrorReadOnly> {
-    let query = sqlx::query(
-        r"SELECT id, name, email, age, created_at 
+        ORDER BY name"],
+            &["id", "name", "email"],
+        ),
+        (
+            "get_user_details",
+            &[r"SELECT id, name, email, age, created_at 
         FROM public.users 
-        WHERE id = $1"
-    );
-    let query = query.bind(user_id);
-    let row = query.fetch_one(executor).await?;
-    let result: Result<_, sqlx::Error> = (|| {
-        Ok(UserDetails {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-        age: row.try_get::<Option<i32>, _>("age")?,
-        created_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")?,
-    })
-    })();
-    result.map_err(Into::into)
-}
-
-/// Search user details - reuses UserDetails return struct
-///
-/// Query Plan:
-/// Seq Scan on users
-///   Filter: ((name)::text ~~* 'dummy'::text)
-/// JIT:
-///   Functions: 4
-///   Options: Inlining true, Optimization true, Expressions true, Deforming true
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "SELECT id, name, email, age, created_at \nFROM public.users \nWHERE name ILIKE #{pattern}"))]
-pub async fn search_user_details(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, pattern: String) -> Result<Vec<UserDetails>, super::ErrorReadOnly> {
-    let query = sqlx::query(
-        r"SELECT id, name, email, age, created_at 
+        WHERE id = $1"],
+            &["id", "name", "email", "age", "created_at"],
+        ),
+        (
+            "search_user_details",
+            &[r"SELECT id, name, email, age, created_at 
         FROM public.users 
-        WHERE name ILIKE $1"
-    );
-    let query = query.bind(&pattern);
-    let rows = query.fetch_all(executor).await?;
-    let result: Result<Vec<_>, sqlx::Error> = rows.iter().map(|row| {
-        Ok(UserDetails {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-        age: row.try_get::<Option<i32>, _>("age")?,
-        created_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")?,
-    })
-    }).collect();
-    result.map_err(Into::into)
-}
-
-/// Find user by criteria - uses GetUserByIdAndEmailParams for params and UserSummary for return
-///
-/// Query Plan:
-/// Index Scan using users_email_key on users
-///   Index Cond: ((email)::text = 'dummy'::text)
-///   Filter: (id = 0)
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "SELECT id, name, email \nFROM public.users \nWHERE id = #{id} AND email = #{email}"))]
-pub async fn find_user_by_criteria(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, params: &GetUserByIdAndEmailParams) -> Result<Option<UserSummary>, super::ErrorReadOnly> {
-    let query = sqlx::query(
-        r"SELECT id, name, email 
+        WHERE name ILIKE $1"],
+            &["id", "name", "email", "age", "created_at"],
+        ),
+        (
+            "find_user_by_criteria",
+            &[r"SELECT id, name, email 
         FROM public.users 
-        WHERE id = $1 AND email = $2"
-    );
-    let query = query.bind(params.id);
-    let query = query.bind(&params.email);
-    let row = query.fetch_optional(executor).await?;
-    match row {
-        Some(row) => {
-            let result: Result<_, sqlx::Error> = (|| {
-                Ok(UserSummary {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-    })
-            })();
-            result.map(Some).map_err(Into::into)
-        },
-        None => Ok(None),
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct GetUserSimpleItem {
-    pub id: i32,
-    pub name: String,
-    pub email: String,
-    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
-}
-
-/// Simple user lookup by ID with detailed info
-///
-/// Query Plan:
-/// Index Scan using users_pkey on users
-///   Index Cond: (id = 0)
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "SELECT id, name, email, created_at\nFROM public.users\nWHERE id = #{user_id}"))]
-pub async fn get_user_simple(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, user_id: i32) -> Result<Option<GetUserSimpleItem>, super::ErrorReadOnly> {
-    let query = sqlx::query(
-        r"SELECT id, name, email, created_at
+        WHERE id = $1 AND email = $2"],
+            &["id", "name", "email"],
+        ),
+        (
+            "get_user_simple",
+            &[r"SELECT id, name, email, created_at
         FROM public.users
-        WHERE id = $1"
-    );
-    let query = query.bind(user_id);
-    let row = query.fetch_optional(executor).await?;
-    match row {
-        Some(row) => {
-            let result: Result<_, sqlx::Error> = (|| {
-                Ok(GetUserSimpleItem {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-        created_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")?,
-    })
-            })();
-            result.map(Some).map_err(Into::into)
-        },
-        None => Ok(None),
-    }
-}
-
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-pub struct TestCustomDerivesParams {
-    pub user_id: i32,
-}
-
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
-pub struct UserWithCustomDerives {
-    pub id: i32,
-    pub name: String,
-    pub email: String,
-    pub age: Option<i32>,
-}
-
-/// Test custom derive traits
-///
-/// Query Plan:
-/// Index Scan using users_pkey on users
-///   Index Cond: (id = 0)
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "SELECT id, name, email, age\nFROM public.users\nWHERE id = #{user_id}"))]
-pub async fn test_custom_derives(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, params: &TestCustomDerivesParams) -> Result<UserWithCustomDerives, super::ErrorReadOnly> {
-    let query = sqlx::query(
-        r"SELECT id, name, email, age
+        WHERE id = $1"],
+            &["id", "name", "email", "created_at"],
+        ),
+        (
+            "test_custom_derives",
+            &[r"SELECT id, name, email, age
         FROM public.users
-        WHERE id = $1"
-    );
-    let query = query.bind(params.user_id);
-    let row = query.fetch_one(executor).await?;
-    let result: Result<_, sqlx::Error> = (|| {
-        Ok(UserWithCustomDerives {
-        id: row.try_get::<i32, _>("id")?,
-        name: row.try_get::<String, _>("name")?,
-        email: row.try_get::<String, _>("email")?,
-        age: row.try_get::<Option<i32>, _>("age")?,
-    })
-    })();
-    result.map_err(Into::into)
-}
+        WHERE id = $1"],
+            &["id", "name", "email", "age"],
+        ),
+        (
+            "get_user_id_only",
+            &[r"SELECT id
+        FROM public.users
+        WHERE email = $1"],
+            &["id"],
+        ),
+        (
+            "get_user_id_raw",
+            &[r"SELECT id
+        FROM public.users
+        WHERE email = $1"],
+            &["id"],
+        ),
+        (
+            "get_user_statuses_in",
+            &[
+                r"SELECT ARRAY_AGG(DISTINCT status ORDER BY status) AS statuses
+        FROM public.users
+        WHERE status = ANY($1)",
+            ],
+            &["statuses"],
+        ),
+        (
+            "get_user_name_and_email",
+            &[r"SELECT name, email
+        FROM public.users
+        WHERE id = $1"],
+            &["name", "email"],
+        ),
+        (
+            "list_users_for_maintenance",
+            &[r"SELECT id, email
+        FROM public.users
+        WHERE age >= $1"],
+            &["id", "email"],
+        ),
+        (
+            "find_users_by_keyword_names",
+            &[
+                r"SELECT id, name AS type, email AS ref
+        FROM public.users
+        WHERE name = $1
+         AND id > $2",
+                r"SELECT id, name AS type, email AS ref
+        FROM public.users
+        WHERE name = $1
+         AND id > $2
+        AND email = $3",
+            ],
+            &["id", "type", "ref"],
+        ),
+        (
+            "update_user_keyword_names_diff",
+            &[
+                r"UPDATE public.users
+        SET updated_at = NOW()
+        
+        
+        WHERE id = $1
+        RETURNING id, name AS type, updated_at",
+                r"UPDATE public.users
+        SET updated_at = NOW()
+        , name = $1
+        
+        WHERE id = $2
+        RETURNING id, name AS type, updated_at",
+                r"UPDATE public.users
+        SET updated_at = NOW()
+        
+        , email = $1
+        WHERE id = $2
+        RETURNING id, name AS type, updated_at",
+            ],
+            &["id", "type", "updated_at"],
+        ),
+        (
+            "get_public_user_profile",
+            &[r"SELECT id, name, email
+        FROM public.users
+        WHERE id = $1"],
+            &["id", "name", "email"],
+        ),
+        (
+            "find_users_matching_text",
+            &[
+                r"SELECT id, name, email, age
+        FROM public.users
+        WHERE (name ILIKE '%' || $1 || '%' OR email ILIKE '%' || $1 || '%')
+        
+        ORDER BY id",
+                r"SELECT id, name, email, age
+        FROM public.users
+        WHERE (name ILIKE '%' || $1 || '%' OR email ILIKE '%' || $1 || '%')
+        AND age BETWEEN $2 - 5 AND $2 + 5
+        ORDER BY id",
+            ],
+            &["id", "name", "email", "age"],
+        ),
+        (
+            "get_or_create_user_by_email",
+            &[r"SELECT id, name, email, age
+        FROM public.users
+        WHERE email = $1"],
+            &["id", "name", "email", "age"],
+        ),
+        (
+            "find_users_with_similar_name",
+            &[r"SELECT id, name, levenshtein(name, $1) AS distance
+        FROM users
+        WHERE levenshtein(name, $1) <= $2
+        ORDER BY distance, id"],
+            &["id", "name", "distance"],
+        ),
+        (
+            "delete_user",
+            &[r"DELETE FROM public.users
+        WHERE id = $1"],
+            &[],
+        ),
+        (
+            "find_users_by_theme",
+            &[r"SELECT id, name
+        FROM public.users
+        WHERE profile @> jsonb_build_object('preferences', jsonb_build_object('theme', $1::text))
+        ORDER BY id"],
+            &["id", "name"],
+        ),
+        (
+            "get_user_profile_cards",
+            &[r"SELECT id, name, profile
+        FROM public.users
+        WHERE profile IS NOT NULL
+        ORDER BY id
+        LIMIT $1"],
+            &["id", "name", "profile"],
+        ),
+        (
+            "filter_users",
+            &[r"SELECT id, name, email, age
+        FROM public.users
+        WHERE email = $1
+         AND name ILIKE $2
+         AND age >= $3
+         AND age <= $4
+         AND status = ANY($5)
+        ORDER BY id
+        LIMIT $6"],
+            &["id", "name", "email", "age"],
+        ),
+        (
+            "update_user_ages",
+            &[r"UPDATE public.users
+        SET age = $1, status = $2, updated_at = NOW()
+        WHERE id = $3
+        RETURNING id, age, status"],
+            &["id", "age", "status"],
+        ),
+        (
+            "get_user_name",
+            &[r"SELECT name FROM public.users WHERE id = $1"],
+            &["name"],
+        ),
+    ];
+    let mut report = Vec::with_capacity(QUERIES.len());
+    for &(query, variants, columns) in QUERIES {
+        let result = super::warmup_query(&mut *executor, variants, columns).await;
+        report.push(super::WarmupStatus { query, result });
+    }
+    report
+}
+
+#[allow(unused_imports)]
+use super::*;
+
+mod queries_1;
+pub use queries_1::*;
+mod queries_2;
+pub use queries_2::*;
+
+/// Queries of this module on one connection or transaction, e.g.
+/// `let mut dao = Dao::new(&mut transaction);` to run several queries in `transaction`
+pub struct Dao<'c> {
+    conn: &'c mut sqlx::PgConnection,
+}
+
+impl<'c> Dao<'c> {
+    /// Borrow a connection, a pooled connection or a transaction
+    pub fn new(conn: &'c mut sqlx::PgConnection) -> Self {
+        Self { conn }
+    }
+
+    /// The borrowed connection, e.g. to run queries of other modules between the calls
+    pub fn connection(&mut self) -> &mut sqlx::PgConnection {
+        self.conn
+    }
+
+    /// Calls [`insert_user`] on the connection
+    pub async fn insert_user(
+        &mut self,
+        name: String,
+        email: String,
+        age: i32,
+        profile: UserProfile,
+    ) -> Result<InsertUserItem, super::Error<InsertUserConstraints>> {
+        insert_user(&mut *self.conn, name, email, age, profile).await
+    }
+
+    /// Calls [`insert_user_id_only`] on the connection
+    pub async fn insert_user_id_only(
+        &mut self,
+        name: String,
+        email: String,
+        age: i32,
+        profile: UserProfile,
+    ) -> Result<i32, super::Error<InsertUserConstraints>> {
+        insert_user_id_only(&mut *self.conn, name, email, age, profile).await
+    }
+
+    /// Calls [`insert_user_retrying`] on the connection
+    pub async fn insert_user_retrying(
+        &mut self,
+        name: String,
+        email: String,
+        age: i32,
+        profile: UserProfile,
+    ) -> Result<InsertUserItem, super::Error<InsertUserConstraints>> {
+        insert_user_retrying(&mut *self.conn, name, email, age, profile).await
+    }
+
+    /// Calls [`insert_users_batch`] on the connection
+    pub async fn insert_users_batch(
+        &mut self,
+        items: Vec<InsertUsersBatchRecord>,
+    ) -> Result<(), super::Error<InsertUsersBatchConstraints>> {
+        insert_users_batch(&mut *self.conn, items).await
+    }
+
+    /// Calls [`get_all_users`] on the connection
+    pub async fn get_all_users(&mut self) -> Result<Vec<GetAllUsersItem>, super::ErrorReadOnly> {
+        get_all_users(&mut *self.conn).await
+    }
+
+    /// Calls [`find_user_by_email`] on the connection
+    pub async fn find_user_by_email(
+        &mut self,
+        email: String,
+    ) -> Result<Option<FindUserByEmailItem>, super::ErrorReadOnly> {
+        find_user_by_email(&mut *self.conn, email).await
+    }
+
+    /// Calls [`update_user_profile`] on the connection
+    pub async fn update_user_profile(
+        &mut self,
+        profile: UserProfile,
+        user_id: i32,
+    ) -> Result<UpdateUserProfileItem, super::Error<UpdateUserProfileConstraints>> {
+        update_user_profile(&mut *self.conn, profile, user_id).await
+    }
+
+    /// Calls [`find_users_by_name_and_age`] on the connection
+    pub async fn find_users_by_name_and_age(
+        &mut self,
+        name_pattern: String,
+        min_age: Option<i32>,
+        name_exact: String,
+        max_age: Option<i32>,
+    ) -> Result<Vec<FindUsersByNameAndAgeItem>, super::ErrorReadOnly> {
+        find_users_by_name_and_age(&mut *self.conn, name_pattern, min_age, name_exact, max_age)
+            .await
+    }
+
+    /// Calls [`get_recent_users`] on the connection
+    pub async fn get_recent_users(
+        &mut self,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<GetRecentUsersItem>, super::ErrorReadOnly> {
+        get_recent_users(&mut *self.conn, since).await
+    }
+
+    /// Calls [`get_active_users_by_age_range`] on the connection
+    pub async fn get_active_users_by_age_range(
+        &mut self,
+        min_age: i32,
+        max_age: i32,
+    ) -> Result<Vec<GetActiveUsersByAgeRangeItem>, super::ErrorReadOnly> {
+        get_active_users_by_age_range(&mut *self.conn, min_age, max_age).await
+    }
+
+    /// Calls [`search_users_by_name_pattern`] on the connection
+    pub async fn search_users_by_name_pattern(
+        &mut self,
+        pattern: String,
+    ) -> Result<Vec<SearchUsersByNamePatternItem>, super::ErrorReadOnly> {
+        search_users_by_name_pattern(&mut *self.conn, pattern).await
+    }
+
+    /// Calls [`search_users_advanced`] on the connection
+    pub async fn search_users_advanced(
+        &mut self,
+        name_pattern: Option<String>,
+        min_age: Option<i32>,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<Vec<SearchUsersAdvancedItem>, super::ErrorReadOnly> {
+        search_users_advanced(&mut *self.conn, name_pattern, min_age, since).await
+    }
+
+    /// Calls [`get_users_by_status`] on the connection
+    pub async fn get_users_by_status(
+        &mut self,
+        user_status: UserStatus,
+    ) -> Result<Vec<GetUsersByStatusItem>, super::ErrorReadOnly> {
+        get_users_by_status(&mut *self.conn, user_status).await
+    }
+
+    /// Calls [`update_user_status`] on the connection
+    pub async fn update_user_status(
+        &mut self,
+        new_status: UserStatus,
+        user_id: i32,
+    ) -> Result<UpdateUserStatusItem, super::Error<UpdateUserStatusConstraints>> {
+        update_user_status(&mut *self.conn, new_status, user_id).await
+    }
+
+    /// Calls [`update_user_fields`] on the connection
+    pub async fn update_user_fields(
+        &mut self,
+        name: Option<String>,
+        email: Option<String>,
+        age: Option<i32>,
+        user_id: i32,
+    ) -> Result<UpdateUserFieldsItem, super::Error<UpdateUserFieldsConstraints>> {
+        update_user_fields(&mut *self.conn, name, email, age, user_id).await
+    }
+
+    /// Calls [`update_user_fields_diff`] on the connection
+    pub async fn update_user_fields_diff(
+        &mut self,
+        old: &UpdateUserFieldsDiffParams,
+        new: &UpdateUserFieldsDiffParams,
+        user_id: i32,
+    ) -> Result<UpdateUserFieldsDiffItem, super::Error<UpdateUserFieldsDiffConstraints>> {
+        update_user_fields_diff(&mut *self.conn, old, new, user_id).await
+    }
+
+    /// Calls [`insert_user_structured`] on the connection
+    pub async fn insert_user_structured(
+        &mut self,
+        params: &InsertUserStructuredParams,
+    ) -> Result<InsertUserStructuredItem, super::Error<InsertUserStructuredConstraints>> {
+        insert_user_structured(&mut *self.conn, params).await
+    }
+
+    /// Calls [`get_all_user_statuses`] on the connection
+    pub async fn get_all_user_statuses(
+        &mut self,
+    ) -> Result<Vec<Option<UserStatus>>, super::ErrorReadOnly> {
+        get_all_user_statuses(&mut *self.conn).await
+    }
+
+    /// Calls [`get_all_users_with_star`] on the connection
+    pub async fn get_all_users_with_star(
+        &mut self,
+    ) -> Result<Vec<GetAllUsersWithStarItem>, super::ErrorReadOnly> {
+        get_all_users_with_star(&mut *self.conn).await
+    }
+
+    /// Calls [`get_user_by_id_with_star`] on the connection
+    pub async fn get_user_by_id_with_star(
+        &mut self,
+        user_id: i32,
+    ) -> Result<Option<GetUserByIdWithStarItem>, super::ErrorReadOnly> {
+        get_user_by_id_with_star(&mut *self.conn, user_id).await
+    }
+
+    /// Calls [`get_user_by_id_and_email`] on the connection
+    pub async fn get_user_by_id_and_email(
+        &mut self,
+        params: &GetUserByIdAndEmailParams,
+    ) -> Result<Option<GetUserByIdAndEmailItem>, super::ErrorReadOnly> {
+        get_user_by_id_and_email(&mut *self.conn, params).await
+    }
+
+    /// Calls [`delete_user_by_id_and_email`] on the connection
+    pub async fn delete_user_by_id_and_email(
+        &mut self,
+        params: &GetUserByIdAndEmailParams,
+    ) -> Result<DeleteUserByIdAndEmailItem, super::Error<DeleteUserByIdAndEmailConstraints>> {
+        delete_user_by_id_and_email(&mut *self.conn, params).await
+    }
+
+    /// Calls [`update_user_contact_info`] on the connection
+    pub async fn update_user_contact_info(
+        &mut self,
+        params: &GetUserByIdAndEmailItem,
+    ) -> Result<UpdateUserContactInfoItem, super::Error<UpdateUserContactInfoConstraints>> {
+        update_user_contact_info(&mut *self.conn, params).await
+    }
+
+    /// Calls [`update_user_profile_diff`] on the connection
+    pub async fn update_user_profile_diff(
+        &mut self,
+        old: &UpdateUserProfileDiffParams,
+        new: &UpdateUserProfileDiffParams,
+        profile: UserProfile,
+        user_id: i32,
+    ) -> Result<UpdateUserProfileDiffItem, super::Error<UpdateUserProfileDiffConstraints>> {
+        update_user_profile_diff(&mut *self.conn, old, new, profile, user_id).await
+    }
+
+    /// Calls [`update_user_metadata_diff`] on the connection
+    pub async fn update_user_metadata_diff(
+        &mut self,
+        old: &UpdateUserProfileDiffParams,
+        new: &UpdateUserProfileDiffParams,
+        profile: UserProfile,
+        user_id: i32,
+    ) -> Result<UpdateUserMetadataDiffItem, super::Error<UpdateUserMetadataDiffConstraints>> {
+        update_user_metadata_diff(&mut *self.conn, old, new, profile, user_id).await
+    }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
-pub struct UserId {
-    pub id: i32,
-}
+    /// Calls [`get_user_summary`] on the connection
+    pub async fn get_user_summary(
+        &mut self,
+        user_id: i32,
+    ) -> Result<UserSummary, super::ErrorReadOnly> {
+        get_user_summary(&mut *self.conn, user_id).await
+    }
+
+    /// Calls [`get_user_info_by_email`] on the connection
+    pub async fn get_user_info_by_email(
+        &mut self,
+        email: String,
+    ) -> Result<Option<UserSummary>, super::ErrorReadOnly> {
+        get_user_info_by_email(&mut *self.conn, email).await
+    }
+
+    /// Calls [`get_all_user_summaries`] on the connection
+    pub async fn get_all_user_summaries(
+        &mut self,
+    ) -> Result<Vec<UserSummary>, super::ErrorReadOnly> {
+        get_all_user_summaries(&mut *self.conn).await
+    }
 
-/// Test single column with explicit return_type - should generate UserId struct
-///
-/// Query Plan:
-/// Index Scan using users_email_key on users
-///   Index Cond: ((email)::text = 'dummy'::text)
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "SELECT id\nFROM public.users\nWHERE email = #{email}"))]
-pub async fn get_user_id_only(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, email: String) -> Result<UserId, super::ErrorReadOnly> {
-    let query = sqlx::query(
-        r"SELECT id
-        FROM public.users
-        WHERE email = $1"
-    );
-    let query = query.bind(&email);
-    let row = query.fetch_one(executor).await?;
-    let result: Result<_, sqlx::Error> = (|| {
-        Ok(UserId {
-        id: row.try_get::<i32, _>("id")?,
-    })
-    })();
-    result.map_err(Into::into)
-}
+    /// Calls [`get_user_details`] on the connection
+    pub async fn get_user_details(
+        &mut self,
+        user_id: i32,
+    ) -> Result<UserDetails, super::ErrorReadOnly> {
+        get_user_details(&mut *self.conn, user_id).await
+    }
 
-/// Test single column without return_type - should return raw i32
-///
-/// Query Plan:
-/// Index Scan using users_email_key on users
-///   Index Cond: ((email)::text = 'dummy'::text)
-#[tracing::instrument(level = "debug", skip_all, fields(sql = "SELECT id\nFROM public.users\nWHERE email = #{email}"))]
-pub async fn get_user_id_raw(executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>, email: String) -> Result<i32, super::ErrorReadOnly> {
-    let query = sqlx::query(
-        r"SELECT id
-        FROM public.users
-        WHERE email = $1"
-    );
-    let query = query.bind(&email);
-    let row = query.fetch_one(executor).await?;
-    Ok(row.try_get::<i32, _>("id")?)
-}
+    /// Calls [`search_user_details`] on the connection
+    pub async fn search_user_details(
+        &mut self,
+        pattern: String,
+    ) -> Result<Vec<UserDetails>, super::ErrorReadOnly> {
+        search_user_details(&mut *self.conn, pattern).await
+    }
+
+    /// Calls [`find_user_by_criteria`] on the connection
+    pub async fn find_user_by_criteria(
+        &mut self,
+        params: &GetUserByIdAndEmailParams,
+    ) -> Result<Option<UserSummary>, super::ErrorReadOnly> {
+        find_user_by_criteria(&mut *self.conn, params).await
+    }
+
+    /// Calls [`get_user_simple`] on the connection
+    pub async fn get_user_simple(
+        &mut self,
+        user_id: i32,
+    ) -> Result<Option<GetUserSimpleItem>, super::ErrorReadOnly> {
+        get_user_simple(&mut *self.conn, user_id).await
+    }
+
+    /// Calls [`test_custom_derives`] on the connection
+    pub async fn test_custom_derives(
+        &mut self,
+        params: &TestCustomDerivesParams,
+    ) -> Result<UserWithCustomDerives, super::ErrorReadOnly> {
+        test_custom_derives(&mut *self.conn, params).await
+    }
+
+    /// Calls [`get_user_id_only`] on the connection
+    pub async fn get_user_id_only(
+        &mut self,
+        email: String,
+    ) -> Result<UserId, super::ErrorReadOnly> {
+        get_user_id_only(&mut *self.conn, email).await
+    }
+
+    /// Calls [`get_user_id_raw`] on the connection
+    pub async fn get_user_id_raw(&mut self, email: String) -> Result<i32, super::ErrorReadOnly> {
+        get_user_id_raw(&mut *self.conn, email).await
+    }
+
+    /// Calls [`get_user_statuses_in`] on the connection
+    pub async fn get_user_statuses_in(
+        &mut self,
+        statuses: Vec<UserStatus>,
+    ) -> Result<Option<Vec<UserStatus>>, super::ErrorReadOnly> {
+        get_user_statuses_in(&mut *self.conn, statuses).await
+    }
+
+    /// Calls [`get_user_name_and_email`] on the connection
+    pub async fn get_user_name_and_email(
+        &mut self,
+        id: i32,
+    ) -> Result<Option<(String, String)>, super::ErrorReadOnly> {
+        get_user_name_and_email(&mut *self.conn, id).await
+    }
+
+    /// Calls [`list_users_for_maintenance`] on the connection
+    pub async fn list_users_for_maintenance(
+        &mut self,
+        min_age: i32,
+    ) -> Result<Vec<ListUsersForMaintenanceItem>, super::ErrorReadOnly> {
+        list_users_for_maintenance(&mut *self.conn, min_age).await
+    }
+
+    /// Calls [`find_users_by_keyword_names`] on the connection
+    pub async fn find_users_by_keyword_names(
+        &mut self,
+        r#type: String,
+        r#match: i32,
+        r#ref: Option<String>,
+    ) -> Result<Vec<FindUsersByKeywordNamesItem>, super::ErrorReadOnly> {
+        find_users_by_keyword_names(&mut *self.conn, r#type, r#match, r#ref).await
+    }
+
+    /// Calls [`update_user_keyword_names_diff`] on the connection
+    pub async fn update_user_keyword_names_diff(
+        &mut self,
+        old: &UpdateUserKeywordNamesDiffParams,
+        new: &UpdateUserKeywordNamesDiffParams,
+        r#match: i32,
+    ) -> Result<UpdateUserKeywordNamesDiffItem, super::Error<UpdateUserKeywordNamesDiffConstraints>>
+    {
+        update_user_keyword_names_diff(&mut *self.conn, old, new, r#match).await
+    }
+
+    /// Calls [`get_public_user_profile`] on the connection
+    pub async fn get_public_user_profile(
+        &mut self,
+        id: i32,
+    ) -> Result<Option<GetPublicUserProfileItem>, super::ErrorReadOnly> {
+        get_public_user_profile(&mut *self.conn, id).await
+    }
+
+    /// Calls [`find_users_matching_text`] on the connection
+    pub async fn find_users_matching_text(
+        &mut self,
+        text: String,
+        around_age: Option<i32>,
+    ) -> Result<Vec<FindUsersMatchingTextItem>, super::ErrorReadOnly> {
+        find_users_matching_text(&mut *self.conn, text, around_age).await
+    }
+
+    /// Calls [`get_or_create_user_by_email`] on the connection
+    pub async fn get_or_create_user_by_email(
+        &mut self,
+        email: String,
+        name: String,
+        age: i32,
+        profile: UserProfile,
+    ) -> Result<GetOrCreateUserByEmailItem, super::Error<InsertUserConstraints>> {
+        get_or_create_user_by_email(&mut *self.conn, email, name, age, profile).await
+    }
+
+    /// Calls [`find_users_with_similar_name`] on the connection
+    pub async fn find_users_with_similar_name(
+        &mut self,
+        name: String,
+        max_distance: i32,
+    ) -> Result<Vec<FindUsersWithSimilarNameItem>, super::ErrorReadOnly> {
+        find_users_with_similar_name(&mut *self.conn, name, max_distance).await
+    }
+
+    /// Calls [`delete_user`] on the connection
+    pub async fn delete_user(
+        &mut self,
+        id: i32,
+    ) -> Result<(), super::Error<DeleteUserConstraints>> {
+        delete_user(&mut *self.conn, id).await
+    }
+
+    /// Calls [`delete_user_preview`] on the connection
+    pub async fn delete_user_preview(
+        &mut self,
+        id: i32,
+    ) -> Result<super::CascadeImpact, super::ErrorReadOnly> {
+        delete_user_preview(&mut *self.conn, id).await
+    }
+
+    /// Calls [`find_users_by_theme`] on the connection
+    pub async fn find_users_by_theme(
+        &mut self,
+        theme: String,
+    ) -> Result<Vec<FindUsersByThemeItem>, super::ErrorReadOnly> {
+        find_users_by_theme(&mut *self.conn, theme).await
+    }
+
+    /// Calls [`get_user_profile_cards`] on the connection
+    pub async fn get_user_profile_cards(
+        &mut self,
+        limit: i64,
+    ) -> Result<Vec<GetUserProfileCardsItem>, super::ErrorReadOnly> {
+        get_user_profile_cards(&mut *self.conn, limit).await
+    }
+
+    /// Calls [`filter_users`] on the connection
+    pub async fn filter_users(
+        &mut self,
+        filters: Vec<UserFilter>,
+        limit: i64,
+    ) -> Result<Vec<FilterUsersItem>, super::ErrorReadOnly> {
+        filter_users(&mut *self.conn, filters, limit).await
+    }
+
+    /// Calls [`update_user_ages`] on the connection
+    pub async fn update_user_ages(
+        &mut self,
+        age: i32,
+        status: UserStatus,
+        id: i32,
+    ) -> Result<Option<UpdateUserAgesItem>, super::Error<UpdateUserAgesConstraints>> {
+        update_user_ages(&mut *self.conn, age, status, id).await
+    }
+
+    /// Calls [`update_user_ages_bulk`] on the connection
+    pub async fn update_user_ages_bulk(
+        &mut self,
+        items: Vec<UpdateUserAgesBulkRecord>,
+    ) -> Result<Vec<UpdateUserAgesItem>, super::Error<UpdateUserAgesConstraints>> {
+        update_user_ages_bulk(&mut *self.conn, items).await
+    }
+
+    /// Calls [`count_users_with_similar_name`] on the connection
+    pub async fn count_users_with_similar_name(
+        &mut self,
+        name: String,
+        max_distance: i32,
+    ) -> Result<Option<i64>, super::ErrorReadOnly> {
+        count_users_with_similar_name(&mut *self.conn, name, max_distance).await
+    }
 
+    /// Calls [`get_user_name`] on the connection
+    pub async fn get_user_name(&mut self, id: i32) -> Result<Option<String>, super::ErrorReadOnly> {
+        get_user_name(&mut *self.conn, id).await
+    }
+
+    /// Calls [`get_user_name_cached`] on the connection
+    pub async fn get_user_name_cached(
+        &mut self,
+        id: i32,
+    ) -> Result<Option<String>, super::ErrorReadOnly> {
+        get_user_name_cached(&mut *self.conn, id).await
+    }
+}
+// AUTOMODEL_CONTENT_HASH: 3bd1106747e995bf
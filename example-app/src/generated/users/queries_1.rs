@@ -0,0 +1,2862 @@
+// This file was automatically generated by AutoModel. Do not edit manually.
+
+#[allow(unused_imports)]
+use super::*;
+
+/// Constraint violations specific to this query
+#[derive(Debug)]
+pub enum InsertUserConstraints {
+    /// Constraint: users_email_key on table users
+    UsersEmailKey,
+    /// Constraint: users_pkey on table users
+    UsersPkey,
+    /// Constraint: users_referrer_id_fkey on table users
+    UsersReferrerIdFkey,
+    /// Constraint: users_id_not_null on table users
+    UsersIdNotNull,
+    /// Constraint: users_name_not_null on table users
+    UsersNameNotNull,
+    /// Constraint: users_email_not_null on table users
+    UsersEmailNotNull,
+}
+
+impl TryFrom<super::ErrorConstraintInfo> for InsertUserConstraints {
+    type Error = ();
+
+    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
+        match info.constraint_name.as_str() {
+            "users_email_key" => Ok(Self::UsersEmailKey),
+            "users_pkey" => Ok(Self::UsersPkey),
+            "users_referrer_id_fkey" => Ok(Self::UsersReferrerIdFkey),
+            "users_id_not_null" => Ok(Self::UsersIdNotNull),
+            "users_name_not_null" => Ok(Self::UsersNameNotNull),
+            "users_email_not_null" => Ok(Self::UsersEmailNotNull),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct InsertUserItem {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+    pub age: Option<i32>,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Insert a new user with all fields and return the created user
+///
+/// Tables: `users`
+#[tracing::instrument(
+    level = "trace",
+    skip_all,
+    fields(
+        name = %super::telemetry::param(&name, Some(256)),
+        email = %super::telemetry::param(&email, Some(256)),
+        age = %super::telemetry::param(&age, Some(256)),
+        sql = "INSERT INTO public.users (name, email, age, profile)\nVALUES (#{name}, #{email}, #{age}, #{profile})\nRETURNING id, name, email, age, created_at",
+    )
+)]
+pub async fn insert_user(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    name: String,
+    email: String,
+    age: i32,
+    profile: UserProfile,
+) -> Result<InsertUserItem, super::Error<InsertUserConstraints>> {
+    let query = sqlx::query(
+        r"INSERT INTO public.users (name, email, age, profile)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id, name, email, age, created_at",
+    );
+    let query = query.bind(&name);
+    let query = query.bind(&email);
+    let query = query.bind(age);
+    let query =
+        query.bind(serde_json::to_value(&profile).map_err(|e| sqlx::Error::Encode(Box::new(e)))?);
+    let row = query.fetch_one(executor).await?;
+    let result: Result<_, sqlx::Error> = (|| {
+        Ok(InsertUserItem {
+            id: row.try_get::<i32, _>("id")?,
+            name: row.try_get::<String, _>("name")?,
+            email: row.try_get::<String, _>("email")?,
+            age: row.try_get::<Option<i32>, _>("age")?,
+            created_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")?,
+        })
+    })();
+    result.map_err(Into::into)
+}
+
+/// Same as [`insert_user`], returning only the primary key of the inserted row
+///
+/// Tables: `users`
+#[tracing::instrument(
+    level = "trace",
+    skip_all,
+    fields(
+        name = %super::telemetry::param(&name, Some(256)),
+        email = %super::telemetry::param(&email, Some(256)),
+        age = %super::telemetry::param(&age, Some(256)),
+        sql = "INSERT INTO public.users (name, email, age, profile)\nVALUES (#{name}, #{email}, #{age}, #{profile})\nRETURNING id",
+    )
+)]
+pub async fn insert_user_id_only(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    name: String,
+    email: String,
+    age: i32,
+    profile: UserProfile,
+) -> Result<i32, super::Error<InsertUserConstraints>> {
+    let query = sqlx::query(
+        r"INSERT INTO public.users (name, email, age, profile)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id",
+    );
+    let query = query.bind(&name);
+    let query = query.bind(&email);
+    let query = query.bind(age);
+    let query =
+        query.bind(serde_json::to_value(&profile).map_err(|e| sqlx::Error::Encode(Box::new(e)))?);
+    let row = query.fetch_one(executor).await?;
+    Ok(row.try_get::<i32, _>("id")?)
+}
+
+/// Look up the row conflicting with `insert_user` on `users_email_key`
+///
+/// Tables: `users`
+#[tracing::instrument(
+    level = "trace",
+    skip_all,
+    fields(
+        email = %super::telemetry::param(&email, Some(256)),
+        sql = "SELECT id, name, email, age, created_at\nFROM public.users\nWHERE email = #{email}",
+    )
+)]
+async fn insert_user_conflict_users_email_key(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    email: String,
+) -> Result<Option<InsertUserItem>, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT id, name, email, age, created_at
+        FROM public.users
+        WHERE email = $1",
+    );
+    let query = query.bind(&email);
+    let row = query.fetch_optional(executor).await?;
+    match row {
+        Some(row) => {
+            let result: Result<_, sqlx::Error> = (|| {
+                Ok(InsertUserItem {
+                    id: row.try_get::<i32, _>("id")?,
+                    name: row.try_get::<String, _>("name")?,
+                    email: row.try_get::<String, _>("email")?,
+                    age: row.try_get::<Option<i32>, _>("age")?,
+                    created_at: row
+                        .try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")?,
+                })
+            })();
+            result.map(Some).map_err(Into::into)
+        }
+        None => Ok(None),
+    }
+}
+
+/// Same as [`insert_user`], returning the row inserted concurrently by another transaction
+/// The insert runs in a savepoint, a unique violation of `users_email_key` rolls it back and
+/// returns the conflicting row instead, other errors are returned as they are
+pub async fn insert_user_retrying(
+    executor: &mut sqlx::PgConnection,
+    name: String,
+    email: String,
+    age: i32,
+    profile: UserProfile,
+) -> Result<InsertUserItem, super::Error<InsertUserConstraints>> {
+    let mut savepoint = sqlx::Connection::begin(&mut *executor).await?;
+    let (constraint, info) =
+        match insert_user(&mut *savepoint, name, email.clone(), age, profile).await {
+            Ok(row) => {
+                savepoint.commit().await?;
+                return Ok(row);
+            }
+            Err(super::Error::ConstraintViolation(constraint, info))
+                if info.kind == super::ErrorConstraintKind::UniqueViolation =>
+            {
+                savepoint.rollback().await?;
+                (constraint, info)
+            }
+            Err(e) => return Err(e),
+        };
+
+    let row = match info.constraint_name.as_str() {
+        "users_email_key" => insert_user_conflict_users_email_key(&mut *executor, email).await?,
+        _ => None,
+    };
+    row.ok_or(super::Error::ConstraintViolation(constraint, info))
+}
+
+/// Constraint violations specific to this query
+#[derive(Debug)]
+pub enum InsertUsersBatchConstraints {
+    /// Constraint: users_email_key on table users
+    UsersEmailKey,
+    /// Constraint: users_pkey on table users
+    UsersPkey,
+    /// Constraint: users_referrer_id_fkey on table users
+    UsersReferrerIdFkey,
+    /// Constraint: users_id_not_null on table users
+    UsersIdNotNull,
+    /// Constraint: users_name_not_null on table users
+    UsersNameNotNull,
+    /// Constraint: users_email_not_null on table users
+    UsersEmailNotNull,
+}
+
+impl TryFrom<super::ErrorConstraintInfo> for InsertUsersBatchConstraints {
+    type Error = ();
+
+    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
+        match info.constraint_name.as_str() {
+            "users_email_key" => Ok(Self::UsersEmailKey),
+            "users_pkey" => Ok(Self::UsersPkey),
+            "users_referrer_id_fkey" => Ok(Self::UsersReferrerIdFkey),
+            "users_id_not_null" => Ok(Self::UsersIdNotNull),
+            "users_name_not_null" => Ok(Self::UsersNameNotNull),
+            "users_email_not_null" => Ok(Self::UsersEmailNotNull),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct InsertUsersBatchRecord {
+    pub name: String,
+    pub email: String,
+    pub age: i32,
+}
+
+/// Insert multiple public.users using UNNEST pattern with multiunzip
+///
+/// Tables: `users`
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "INSERT INTO public.users (name, email, age)\nSELECT *\nFROM UNNEST(\n        #{name}::text [],\n        #{email}::text [],\n        #{age}::int4 []\n    )"
+    )
+)]
+pub async fn insert_users_batch(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    items: Vec<InsertUsersBatchRecord>,
+) -> Result<(), super::Error<InsertUsersBatchConstraints>> {
+    use itertools::Itertools;
+    let query = sqlx::query(
+        r"INSERT INTO public.users (name, email, age)
+        SELECT *
+        FROM UNNEST(
+            $1::text [],
+            $2::text [],
+            $3::int4 []
+          )",
+    );
+    let (name, email, age): (Vec<_>, Vec<_>, Vec<_>) = items
+        .into_iter()
+        .map(|item| (item.name, item.email, item.age))
+        .multiunzip();
+    let query = query.bind(name);
+    let query = query.bind(email);
+    let query = query.bind(age);
+    query.execute(executor).await?;
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+pub struct GetAllUsersItem {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+    pub age: Option<i32>,
+    pub profile: Option<UserProfile>,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Get all public.users with all their fields
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Sort
+///   Sort Key: created_at DESC
+///   ->  Seq Scan on users
+/// JIT:
+///   Functions: 2
+///   Options: Inlining true, Optimization true, Expressions true, Deforming true
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "SELECT id, name, email, age, profile, created_at, updated_at \nFROM public.users \nORDER BY created_at DESC"
+    )
+)]
+pub async fn get_all_users(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+) -> Result<Vec<GetAllUsersItem>, super::ErrorReadOnly> {
+    let result: Result<Vec<GetAllUsersItem>, super::ErrorReadOnly> = async move {
+        let query = sqlx::query(
+            r"SELECT id, name, email, age, profile, created_at, updated_at 
+        FROM public.users 
+        ORDER BY created_at DESC",
+        );
+        let rows = query.fetch_all(executor).await?;
+        let result: Result<Vec<_>, sqlx::Error> = rows
+            .iter()
+            .map(|row| {
+                Ok(GetAllUsersItem {
+                    id: row.try_get::<i32, _>("id")?,
+                    name: row.try_get::<String, _>("name")?,
+                    email: row.try_get::<String, _>("email")?,
+                    age: row.try_get::<Option<i32>, _>("age")?,
+                    profile: row
+                        .try_get::<Option<serde_json::Value>, _>("profile")?
+                        .map(|v| {
+                            serde_json::from_value::<UserProfile>(v)
+                                .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+                        })
+                        .transpose()?,
+                    created_at: row
+                        .try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")?,
+                    updated_at: row
+                        .try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("updated_at")?,
+                })
+            })
+            .collect();
+        result.map_err(Into::into)
+    }
+    .await;
+    if let Ok(rows) = &result {
+        super::cardinality_drift::record("users::get_all_users", 100.0, rows.len());
+    }
+    result
+}
+
+#[derive(Clone)]
+pub struct FindUserByEmailItem {
+    pub id: i32,
+    pub name: String,
+    /// Sensitive: pii
+    pub email: String,
+    pub age: Option<i32>,
+    pub profile: Option<UserProfile>,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl std::fmt::Debug for FindUserByEmailItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FindUserByEmailItem")
+            .field("id", &self.id)
+            .field("name", &self.name)
+            .field("email", &"[redacted]")
+            .field("age", &self.age)
+            .field("profile", &self.profile)
+            .field("created_at", &self.created_at)
+            .field("updated_at", &self.updated_at)
+            .finish()
+    }
+}
+
+/// Find a user by their email address
+///
+/// Tables: `users`
+#[tracing::instrument(level = "debug", skip_all)]
+pub async fn find_user_by_email(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    email: String,
+) -> Result<Option<FindUserByEmailItem>, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT id, name, email, age, profile, created_at, updated_at 
+        FROM public.users 
+        WHERE email = $1",
+    );
+    let query = query.bind(&email);
+    let row = query.fetch_optional(executor).await?;
+    match row {
+        Some(row) => {
+            let result: Result<_, sqlx::Error> = (|| {
+                Ok(FindUserByEmailItem {
+                    id: row.try_get::<i32, _>("id")?,
+                    name: row.try_get::<String, _>("name")?,
+                    email: row.try_get::<String, _>("email")?,
+                    age: row.try_get::<Option<i32>, _>("age")?,
+                    profile: row
+                        .try_get::<Option<serde_json::Value>, _>("profile")?
+                        .map(|v| {
+                            serde_json::from_value::<UserProfile>(v)
+                                .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+                        })
+                        .transpose()?,
+                    created_at: row
+                        .try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")?,
+                    updated_at: row
+                        .try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("updated_at")?,
+                })
+            })();
+            result.map(Some).map_err(Into::into)
+        }
+        None => Ok(None),
+    }
+}
+
+/// Constraint violations specific to this query
+#[derive(Debug)]
+pub enum UpdateUserProfileConstraints {
+    /// Constraint: users_email_key on table users
+    UsersEmailKey,
+    /// Constraint: users_pkey on table users
+    UsersPkey,
+    /// Constraint: users_referrer_id_fkey on table users
+    UsersReferrerIdFkey,
+    /// Constraint: users_id_not_null on table users
+    UsersIdNotNull,
+    /// Constraint: users_name_not_null on table users
+    UsersNameNotNull,
+    /// Constraint: users_email_not_null on table users
+    UsersEmailNotNull,
+}
+
+impl TryFrom<super::ErrorConstraintInfo> for UpdateUserProfileConstraints {
+    type Error = ();
+
+    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
+        match info.constraint_name.as_str() {
+            "users_email_key" => Ok(Self::UsersEmailKey),
+            "users_pkey" => Ok(Self::UsersPkey),
+            "users_referrer_id_fkey" => Ok(Self::UsersReferrerIdFkey),
+            "users_id_not_null" => Ok(Self::UsersIdNotNull),
+            "users_name_not_null" => Ok(Self::UsersNameNotNull),
+            "users_email_not_null" => Ok(Self::UsersEmailNotNull),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UpdateUserProfileItem {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+    pub age: Option<i32>,
+    pub profile: Option<UserProfile>,
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Update a user's profile by their ID
+///
+/// Tables: `users`
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "UPDATE public.users\nSET profile = #{profile}, updated_at = NOW() \nWHERE id = #{user_id} \nRETURNING id,\n    name,\n    email,\n    age,\n    profile,\n    updated_at"
+    )
+)]
+pub async fn update_user_profile(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    profile: UserProfile,
+    user_id: i32,
+) -> Result<UpdateUserProfileItem, super::Error<UpdateUserProfileConstraints>> {
+    let query = sqlx::query(
+        r"UPDATE public.users
+        SET profile = $1, updated_at = NOW() 
+        WHERE id = $2 
+        RETURNING id,
+          name,
+          email,
+          age,
+          profile,
+          updated_at",
+    );
+    let query =
+        query.bind(serde_json::to_value(&profile).map_err(|e| sqlx::Error::Encode(Box::new(e)))?);
+    let query = query.bind(user_id);
+    let row = query.fetch_one(executor).await?;
+    let result: Result<_, sqlx::Error> = (|| {
+        Ok(UpdateUserProfileItem {
+            id: row.try_get::<i32, _>("id")?,
+            name: row.try_get::<String, _>("name")?,
+            email: row.try_get::<String, _>("email")?,
+            age: row.try_get::<Option<i32>, _>("age")?,
+            profile: row
+                .try_get::<Option<serde_json::Value>, _>("profile")?
+                .map(|v| {
+                    serde_json::from_value::<UserProfile>(v)
+                        .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+                })
+                .transpose()?,
+            updated_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("updated_at")?,
+        })
+    })();
+    result.map_err(Into::into)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FindUsersByNameAndAgeItem {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+    pub age: Option<i32>,
+}
+
+/// Find public.users by name pattern with optional minimum age filter (using conditional syntax)
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// === find_users_by_name_and_age (base) ===
+/// Seq Scan on users
+///   Filter: (((name)::text ~~* 'dummy'::text) AND ((name)::text = 'dummy'::text))
+/// JIT:
+///   Functions: 4
+///   Options: Inlining true, Optimization true, Expressions true, Deforming true
+///
+/// === find_users_by_name_and_age (variant 1) ===
+/// Bitmap Heap Scan on users
+///   Recheck Cond: (age >= 0)
+///   Filter: (((name)::text ~~* 'dummy'::text) AND ((name)::text = 'dummy'::text))
+///   ->  Bitmap Index Scan on idx_users_age_updated_at
+///         Index Cond: (age >= 0)
+///
+/// === find_users_by_name_and_age (variant 2) ===
+/// Index Scan using idx_users_age on users
+///   Index Cond: (age <= 0)
+///   Filter: (((name)::text ~~* 'dummy'::text) AND ((name)::text = 'dummy'::text))
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "SELECT id, name, email, age \nFROM public.users \nWHERE name ILIKE #{name_pattern} \n#[AND age >= #{min_age?}] \nAND name = #{name_exact} \n#[AND age <= #{max_age?}] \nORDER BY name"
+    )
+)]
+pub async fn find_users_by_name_and_age(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    name_pattern: String,
+    min_age: Option<i32>,
+    name_exact: String,
+    max_age: Option<i32>,
+) -> Result<Vec<FindUsersByNameAndAgeItem>, super::ErrorReadOnly> {
+    let mut included_params: Vec<&str> = Vec::new();
+    if min_age.is_some() {
+        included_params.push("min_age");
+    }
+    if max_age.is_some() {
+        included_params.push("max_age");
+    }
+    let final_sql = find_users_by_name_and_age_sql(&included_params);
+
+    let mut query = sqlx::query(&final_sql);
+
+    query = query.bind(&name_pattern);
+    if included_params.contains(&r"min_age") {
+        query = query.bind(min_age.as_ref().unwrap());
+    }
+    query = query.bind(&name_exact);
+    if included_params.contains(&r"max_age") {
+        query = query.bind(max_age.as_ref().unwrap());
+    }
+
+    let rows = query.fetch_all(executor).await?;
+    let result: Result<Vec<_>, sqlx::Error> = rows
+        .iter()
+        .map(|row| {
+            Ok(FindUsersByNameAndAgeItem {
+                id: row.try_get::<i32, _>("id")?,
+                name: row.try_get::<String, _>("name")?,
+                email: row.try_get::<String, _>("email")?,
+                age: row.try_get::<Option<i32>, _>("age")?,
+            })
+        })
+        .collect();
+    result.map_err(Into::into)
+}
+
+/// Assemble the SQL of `find_users_by_name_and_age` for the given included conditional parameters
+fn find_users_by_name_and_age_sql(included_params: &[&str]) -> String {
+    let mut final_sql = r"SELECT id, name, email, age 
+FROM public.users 
+WHERE name ILIKE #{name_pattern} 
+#[AND age >= #{min_age?}] 
+AND name = #{name_exact} 
+#[AND age <= #{max_age?}] 
+ORDER BY name"
+        .to_string();
+
+    if included_params.contains(&"min_age") {
+        final_sql = final_sql.replacen(r"#[AND age >= #{min_age?}]", r"AND age >= #{min_age?}", 1);
+    } else {
+        final_sql = final_sql.replacen(r"#[AND age >= #{min_age?}]", "", 1);
+    }
+    if included_params.contains(&"max_age") {
+        final_sql = final_sql.replacen(r"#[AND age <= #{max_age?}]", r"AND age <= #{max_age?}", 1);
+    } else {
+        final_sql = final_sql.replacen(r"#[AND age <= #{max_age?}]", "", 1);
+    }
+
+    #[allow(unused_assignments)]
+    let mut param_counter = 1;
+    final_sql = final_sql.replace(r"#{name_pattern}", &format!("${}", param_counter));
+    param_counter += 1;
+    if included_params.contains(&"min_age") {
+        final_sql = final_sql.replace(r"#{min_age?}", &format!("${}", param_counter));
+        param_counter += 1;
+    }
+    final_sql = final_sql.replace(r"#{name_exact}", &format!("${}", param_counter));
+    param_counter += 1;
+    if included_params.contains(&"max_age") {
+        final_sql = final_sql.replace(r"#{max_age?}", &format!("${}", param_counter));
+        param_counter += 1;
+    }
+    let _ = param_counter; // Suppress unused assignment warning
+    final_sql
+}
+
+#[derive(Debug, Clone)]
+pub struct GetRecentUsersItem {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+    pub age: Option<i32>,
+    pub profile: Option<UserProfile>,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Get public.users created after a specific timestamp - expects at least one user
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Sort
+///   Sort Key: created_at DESC
+///   ->  Seq Scan on users
+///         Filter: (created_at > '1970-01-01 00:00:00+00'::timestamp with time zone)
+/// JIT:
+///   Functions: 4
+///   Options: Inlining true, Optimization true, Expressions true, Deforming true
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "SELECT id, name, email, age, profile, created_at, updated_at \nFROM public.users \nWHERE created_at > #{since} \nORDER BY created_at DESC"
+    )
+)]
+pub async fn get_recent_users(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    since: chrono::DateTime<chrono::Utc>,
+) -> Result<Vec<GetRecentUsersItem>, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT id, name, email, age, profile, created_at, updated_at 
+        FROM public.users 
+        WHERE created_at > $1 
+        ORDER BY created_at DESC",
+    );
+    let query = query.bind(since);
+    let rows = query.fetch_all(executor).await?;
+    if rows.is_empty() {
+        return Err(sqlx::Error::RowNotFound.into());
+    }
+    let result: Result<Vec<_>, sqlx::Error> = rows
+        .iter()
+        .map(|row| {
+            Ok(GetRecentUsersItem {
+                id: row.try_get::<i32, _>("id")?,
+                name: row.try_get::<String, _>("name")?,
+                email: row.try_get::<String, _>("email")?,
+                age: row.try_get::<Option<i32>, _>("age")?,
+                profile: row
+                    .try_get::<Option<serde_json::Value>, _>("profile")?
+                    .map(|v| {
+                        serde_json::from_value::<UserProfile>(v)
+                            .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+                    })
+                    .transpose()?,
+                created_at: row
+                    .try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")?,
+                updated_at: row
+                    .try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("updated_at")?,
+            })
+        })
+        .collect();
+    result.map_err(Into::into)
+}
+
+#[derive(Debug, Clone)]
+pub struct GetActiveUsersByAgeRangeItem {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+    pub age: Option<i32>,
+    pub profile: Option<UserProfile>,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Get active public.users within an age range - must return at least one user or fails
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Index Scan using idx_users_age on users
+///   Index Cond: ((age >= 0) AND (age <= 0))
+///   Filter: (updated_at > (now - '30 days'::interval))
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "SELECT id, name, email, age, profile, created_at \nFROM public.users \nWHERE age BETWEEN #{min_age} AND #{max_age} \nAND updated_at > NOW() - INTERVAL '30 days'"
+    )
+)]
+pub async fn get_active_users_by_age_range(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    min_age: i32,
+    max_age: i32,
+) -> Result<Vec<GetActiveUsersByAgeRangeItem>, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT id, name, email, age, profile, created_at 
+        FROM public.users 
+        WHERE age BETWEEN $1 AND $2 
+        AND updated_at > NOW() - INTERVAL '30 days'",
+    );
+    let query = query.bind(min_age);
+    let query = query.bind(max_age);
+    let rows = query.fetch_all(executor).await?;
+    if rows.is_empty() {
+        return Err(sqlx::Error::RowNotFound.into());
+    }
+    let result: Result<Vec<_>, sqlx::Error> = rows
+        .iter()
+        .map(|row| {
+            Ok(GetActiveUsersByAgeRangeItem {
+                id: row.try_get::<i32, _>("id")?,
+                name: row.try_get::<String, _>("name")?,
+                email: row.try_get::<String, _>("email")?,
+                age: row.try_get::<Option<i32>, _>("age")?,
+                profile: row
+                    .try_get::<Option<serde_json::Value>, _>("profile")?
+                    .map(|v| {
+                        serde_json::from_value::<UserProfile>(v)
+                            .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+                    })
+                    .transpose()?,
+                created_at: row
+                    .try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")?,
+            })
+        })
+        .collect();
+    result.map_err(Into::into)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SearchUsersByNamePatternItem {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+}
+
+/// Search public.users by name pattern - expects at least one match
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Sort
+///   Sort Key: name
+///   ->  Seq Scan on users
+///         Filter: ((name)::text ~~* 'john%'::text)
+/// JIT:
+///   Functions: 4
+///   Options: Inlining true, Optimization true, Expressions true, Deforming true
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        pattern = %super::telemetry::param(&pattern, Some(256)),
+        sql = "SELECT id, name, email \nFROM public.users \nWHERE name ILIKE #{pattern} \nORDER BY name",
+    )
+)]
+pub async fn search_users_by_name_pattern(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    pattern: String,
+) -> Result<Vec<SearchUsersByNamePatternItem>, super::ErrorReadOnly> {
+    let slow_query_params = format!("pattern={}", super::telemetry::param(&pattern, Some(256)));
+    let started = std::time::Instant::now();
+    let result: Result<Vec<SearchUsersByNamePatternItem>, super::ErrorReadOnly> = async move {
+        if let Some((shadow, observer)) = super::plan_sampling::sample() {
+            let mut explain = sqlx::query(
+                r"EXPLAIN (ANALYZE, FORMAT JSON)
+        SELECT id, name, email 
+        FROM public.users 
+        WHERE name ILIKE $1 
+        ORDER BY name",
+            );
+            explain = explain.bind(&pattern);
+            if let Ok(row) = explain.fetch_one(&shadow).await {
+                if let Ok(plan) = sqlx::Row::try_get::<serde_json::Value, _>(&row, 0) {
+                    observer(super::plan_sampling::PlanSample {
+                        query: "users::search_users_by_name_pattern",
+                        plan,
+                    });
+                }
+            }
+        }
+
+        let query = sqlx::query(
+            r"SELECT id, name, email 
+        FROM public.users 
+        WHERE name ILIKE $1 
+        ORDER BY name",
+        );
+        let query = query.bind(&pattern);
+        let rows = query.fetch_all(executor).await?;
+        if rows.is_empty() {
+            return Err(sqlx::Error::RowNotFound.into());
+        }
+        let result: Result<Vec<_>, sqlx::Error> = rows
+            .iter()
+            .map(|row| {
+                Ok(SearchUsersByNamePatternItem {
+                    id: row.try_get::<i32, _>("id")?,
+                    name: row.try_get::<String, _>("name")?,
+                    email: row.try_get::<String, _>("email")?,
+                })
+            })
+            .collect();
+        result.map_err(Into::into)
+    }
+    .await;
+    let elapsed = started.elapsed();
+    if elapsed >= std::time::Duration::from_millis(250) {
+        tracing::warn!(
+            query = "users::search_users_by_name_pattern",
+            elapsed_ms = elapsed.as_millis() as u64,
+            threshold_ms = 250u64,
+            params = %slow_query_params,
+            "slow query"
+        );
+    }
+    result
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SearchUsersAdvancedItem {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+    pub age: Option<i32>,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Advanced user search with multiple optional filters using conditional syntax
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// === search_users_advanced (base) ===
+/// Sort
+///   Sort Key: created_at DESC
+///   ->  Seq Scan on users
+/// JIT:
+///   Functions: 2
+///   Options: Inlining true, Optimization true, Expressions true, Deforming true
+///
+/// === search_users_advanced (variant 1) ===
+/// Sort
+///   Sort Key: created_at DESC
+///   ->  Seq Scan on users
+///         Filter: ((name)::text ~~* 'dummy'::text)
+/// JIT:
+///   Functions: 4
+///   Options: Inlining true, Optimization true, Expressions true, Deforming true
+///
+/// === search_users_advanced (variant 2) ===
+/// Sort
+///   Sort Key: created_at DESC
+///   ->  Bitmap Heap Scan on users
+///         Recheck Cond: (age >= 0)
+///         ->  Bitmap Index Scan on idx_users_age_updated_at
+///               Index Cond: (age >= 0)
+///
+/// === search_users_advanced (variant 3) ===
+/// Sort
+///   Sort Key: created_at DESC
+///   ->  Seq Scan on users
+///         Filter: (created_at >= '1970-01-01 00:00:00+00'::timestamp with time zone)
+/// JIT:
+///   Functions: 4
+///   Options: Inlining true, Optimization true, Expressions true, Deforming true
+///
+/// === search_users_advanced (variants 1+2) ===
+/// Sort
+///   Sort Key: created_at DESC
+///   ->  Bitmap Heap Scan on users
+///         Recheck Cond: (age >= 0)
+///         Filter: ((name)::text ~~* 'dummy'::text)
+///         ->  Bitmap Index Scan on idx_users_age
+///               Index Cond: (age >= 0)
+///
+/// === search_users_advanced (variants 1+3) ===
+/// Sort
+///   Sort Key: created_at DESC
+///   ->  Seq Scan on users
+///         Filter: (((name)::text ~~* 'dummy'::text) AND (created_at >= '1970-01-01 00:00:00+00'::timestamp with time zone))
+/// JIT:
+///   Functions: 4
+///   Options: Inlining true, Optimization true, Expressions true, Deforming true
+///
+/// === search_users_advanced (variants 2+3) ===
+/// Sort
+///   Sort Key: created_at DESC
+///   ->  Bitmap Heap Scan on users
+///         Recheck Cond: (age >= 0)
+///         Filter: (created_at >= '1970-01-01 00:00:00+00'::timestamp with time zone)
+///         ->  Bitmap Index Scan on idx_users_age
+///               Index Cond: (age >= 0)
+///
+/// === search_users_advanced (variants 1+2+3) ===
+/// Sort
+///   Sort Key: created_at DESC
+///   ->  Bitmap Heap Scan on users
+///         Recheck Cond: (age >= 0)
+///         Filter: (((name)::text ~~* 'dummy'::text) AND (created_at >= '1970-01-01 00:00:00+00'::timestamp with time zone))
+///         ->  Bitmap Index Scan on idx_users_age
+///               Index Cond: (age >= 0)
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "SELECT id, name, email, age, created_at \nFROM public.users \nWHERE 1=1 \n#[AND name ILIKE #{name_pattern?}] \n#[AND age >= #{min_age?}] \n#[AND created_at >= #{since?}] \nORDER BY created_at DESC"
+    )
+)]
+pub async fn search_users_advanced(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    name_pattern: Option<String>,
+    min_age: Option<i32>,
+    since: Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<Vec<SearchUsersAdvancedItem>, super::ErrorReadOnly> {
+    let mut included_params: Vec<&str> = Vec::new();
+    if name_pattern.is_some() {
+        included_params.push("name_pattern");
+    }
+    if min_age.is_some() {
+        included_params.push("min_age");
+    }
+    if since.is_some() {
+        included_params.push("since");
+    }
+    let final_sql = search_users_advanced_sql(&included_params);
+
+    if let Some((shadow, observer)) = super::plan_sampling::sample() {
+        let explain_sql = format!("EXPLAIN (ANALYZE, FORMAT JSON) {}", final_sql);
+        let mut explain = sqlx::query(&explain_sql);
+        if included_params.contains(&r"name_pattern") {
+            explain = explain.bind(name_pattern.as_ref().unwrap());
+        }
+        if included_params.contains(&r"min_age") {
+            explain = explain.bind(min_age.as_ref().unwrap());
+        }
+        if included_params.contains(&r"since") {
+            explain = explain.bind(since.as_ref().unwrap());
+        }
+        if let Ok(row) = explain.fetch_one(&shadow).await {
+            if let Ok(plan) = sqlx::Row::try_get::<serde_json::Value, _>(&row, 0) {
+                observer(super::plan_sampling::PlanSample {
+                    query: "users::search_users_advanced",
+                    plan,
+                });
+            }
+        }
+    }
+
+    let mut query = sqlx::query(&final_sql);
+
+    if included_params.contains(&r"name_pattern") {
+        query = query.bind(name_pattern.as_ref().unwrap());
+    }
+    if included_params.contains(&r"min_age") {
+        query = query.bind(min_age.as_ref().unwrap());
+    }
+    if included_params.contains(&r"since") {
+        query = query.bind(since.as_ref().unwrap());
+    }
+
+    let rows = query.fetch_all(executor).await?;
+    let result: Result<Vec<_>, sqlx::Error> = rows
+        .iter()
+        .map(|row| {
+            Ok(SearchUsersAdvancedItem {
+                id: row.try_get::<i32, _>("id")?,
+                name: row.try_get::<String, _>("name")?,
+                email: row.try_get::<String, _>("email")?,
+                age: row.try_get::<Option<i32>, _>("age")?,
+                created_at: row
+                    .try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")?,
+            })
+        })
+        .collect();
+    result.map_err(Into::into)
+}
+
+/// Assemble the SQL of `search_users_advanced` for the given included conditional parameters
+fn search_users_advanced_sql(included_params: &[&str]) -> String {
+    let mut final_sql = r"SELECT id, name, email, age, created_at 
+FROM public.users 
+WHERE 1=1 
+#[AND name ILIKE #{name_pattern?}] 
+#[AND age >= #{min_age?}] 
+#[AND created_at >= #{since?}] 
+ORDER BY created_at DESC"
+        .to_string();
+
+    if included_params.contains(&"name_pattern") {
+        final_sql = final_sql.replacen(
+            r"#[AND name ILIKE #{name_pattern?}]",
+            r"AND name ILIKE #{name_pattern?}",
+            1,
+        );
+    } else {
+        final_sql = final_sql.replacen(r"#[AND name ILIKE #{name_pattern?}]", "", 1);
+    }
+    if included_params.contains(&"min_age") {
+        final_sql = final_sql.replacen(r"#[AND age >= #{min_age?}]", r"AND age >= #{min_age?}", 1);
+    } else {
+        final_sql = final_sql.replacen(r"#[AND age >= #{min_age?}]", "", 1);
+    }
+    if included_params.contains(&"since") {
+        final_sql = final_sql.replacen(
+            r"#[AND created_at >= #{since?}]",
+            r"AND created_at >= #{since?}",
+            1,
+        );
+    } else {
+        final_sql = final_sql.replacen(r"#[AND created_at >= #{since?}]", "", 1);
+    }
+
+    #[allow(unused_assignments)]
+    let mut param_counter = 1;
+    if included_params.contains(&"name_pattern") {
+        final_sql = final_sql.replace(r"#{name_pattern?}", &format!("${}", param_counter));
+        param_counter += 1;
+    }
+    if included_params.contains(&"min_age") {
+        final_sql = final_sql.replace(r"#{min_age?}", &format!("${}", param_counter));
+        param_counter += 1;
+    }
+    if included_params.contains(&"since") {
+        final_sql = final_sql.replace(r"#{since?}", &format!("${}", param_counter));
+        param_counter += 1;
+    }
+    let _ = param_counter; // Suppress unused assignment warning
+    final_sql
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GetUsersByStatusItem {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+    pub status: Option<UserStatus>,
+}
+
+/// Get public.users by their status (enum parameter and enum output)
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Sort
+///   Sort Key: name
+///   ->  Seq Scan on users
+///         Filter: (status = 'active'::public.user_status)
+/// JIT:
+///   Functions: 4
+///   Options: Inlining true, Optimization true, Expressions true, Deforming true
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "SELECT id, name, email, status \nFROM public.users \nWHERE status = #{user_status} \nORDER BY name"
+    )
+)]
+pub async fn get_users_by_status(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    user_status: UserStatus,
+) -> Result<Vec<GetUsersByStatusItem>, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT id, name, email, status 
+        FROM public.users 
+        WHERE status = $1 
+        ORDER BY name",
+    );
+    let query = query.bind(user_status);
+    let rows = query.fetch_all(executor).await?;
+    let result: Result<Vec<_>, sqlx::Error> = rows
+        .iter()
+        .map(|row| {
+            Ok(GetUsersByStatusItem {
+                id: row.try_get::<i32, _>("id")?,
+                name: row.try_get::<String, _>("name")?,
+                email: row.try_get::<String, _>("email")?,
+                status: row.try_get::<Option<UserStatus>, _>("status")?,
+            })
+        })
+        .collect();
+    result.map_err(Into::into)
+}
+
+/// Constraint violations specific to this query
+#[derive(Debug)]
+pub enum UpdateUserStatusConstraints {
+    /// Constraint: users_email_key on table users
+    UsersEmailKey,
+    /// Constraint: users_pkey on table users
+    UsersPkey,
+    /// Constraint: users_referrer_id_fkey on table users
+    UsersReferrerIdFkey,
+    /// Constraint: users_id_not_null on table users
+    UsersIdNotNull,
+    /// Constraint: users_name_not_null on table users
+    UsersNameNotNull,
+    /// Constraint: users_email_not_null on table users
+    UsersEmailNotNull,
+}
+
+impl TryFrom<super::ErrorConstraintInfo> for UpdateUserStatusConstraints {
+    type Error = ();
+
+    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
+        match info.constraint_name.as_str() {
+            "users_email_key" => Ok(Self::UsersEmailKey),
+            "users_pkey" => Ok(Self::UsersPkey),
+            "users_referrer_id_fkey" => Ok(Self::UsersReferrerIdFkey),
+            "users_id_not_null" => Ok(Self::UsersIdNotNull),
+            "users_name_not_null" => Ok(Self::UsersNameNotNull),
+            "users_email_not_null" => Ok(Self::UsersEmailNotNull),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct UpdateUserStatusItem {
+    pub id: i32,
+    pub status: Option<UserStatus>,
+}
+
+/// Update user status and return the new status
+///
+/// Tables: `users`
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "UPDATE public.users \nSET status = #{new_status} \nWHERE id = #{user_id} \nRETURNING id, status"
+    )
+)]
+pub async fn update_user_status(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    new_status: UserStatus,
+    user_id: i32,
+) -> Result<UpdateUserStatusItem, super::Error<UpdateUserStatusConstraints>> {
+    let query = sqlx::query(
+        r"UPDATE public.users 
+        SET status = $1 
+        WHERE id = $2 
+        RETURNING id, status",
+    );
+    let query = query.bind(new_status);
+    let query = query.bind(user_id);
+    let row = query.fetch_one(executor).await?;
+    let result: Result<_, sqlx::Error> = (|| {
+        Ok(UpdateUserStatusItem {
+            id: row.try_get::<i32, _>("id")?,
+            status: row.try_get::<Option<UserStatus>, _>("status")?,
+        })
+    })();
+    result.map_err(Into::into)
+}
+
+/// Constraint violations specific to this query
+#[derive(Debug)]
+pub enum UpdateUserFieldsConstraints {
+    /// Constraint: users_email_key on table users
+    UsersEmailKey,
+    /// Constraint: users_pkey on table users
+    UsersPkey,
+    /// Constraint: users_referrer_id_fkey on table users
+    UsersReferrerIdFkey,
+    /// Constraint: users_id_not_null on table users
+    UsersIdNotNull,
+    /// Constraint: users_name_not_null on table users
+    UsersNameNotNull,
+    /// Constraint: users_email_not_null on table users
+    UsersEmailNotNull,
+}
+
+impl TryFrom<super::ErrorConstraintInfo> for UpdateUserFieldsConstraints {
+    type Error = ();
+
+    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
+        match info.constraint_name.as_str() {
+            "users_email_key" => Ok(Self::UsersEmailKey),
+            "users_pkey" => Ok(Self::UsersPkey),
+            "users_referrer_id_fkey" => Ok(Self::UsersReferrerIdFkey),
+            "users_id_not_null" => Ok(Self::UsersIdNotNull),
+            "users_name_not_null" => Ok(Self::UsersNameNotNull),
+            "users_email_not_null" => Ok(Self::UsersEmailNotNull),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct UpdateUserFieldsItem {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+    pub age: Option<i32>,
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Update user fields conditionally - only updates fields that are provided (not None)
+///
+/// Tables: `users`
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "UPDATE public.users \nSET updated_at = NOW() \n#[, name = #{name?}] \n#[, email = #{email?}] \n#[, age = #{age?}] \nWHERE id = #{user_id} \nRETURNING id, name, email, age, updated_at"
+    )
+)]
+pub async fn update_user_fields(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    name: Option<String>,
+    email: Option<String>,
+    age: Option<i32>,
+    user_id: i32,
+) -> Result<UpdateUserFieldsItem, super::Error<UpdateUserFieldsConstraints>> {
+    let mut included_params: Vec<&str> = Vec::new();
+    if name.is_some() {
+        included_params.push("name");
+    }
+    if email.is_some() {
+        included_params.push("email");
+    }
+    if age.is_some() {
+        included_params.push("age");
+    }
+    let final_sql = update_user_fields_sql(&included_params);
+
+    let mut query = sqlx::query(&final_sql);
+
+    if included_params.contains(&r"name") {
+        query = query.bind(name.as_ref().unwrap());
+    }
+    if included_params.contains(&r"email") {
+        query = query.bind(email.as_ref().unwrap());
+    }
+    if included_params.contains(&r"age") {
+        query = query.bind(age.as_ref().unwrap());
+    }
+    query = query.bind(&user_id);
+
+    let row = query.fetch_one(executor).await?;
+    let result: Result<_, sqlx::Error> = (|| {
+        Ok(UpdateUserFieldsItem {
+            id: row.try_get::<i32, _>("id")?,
+            name: row.try_get::<String, _>("name")?,
+            email: row.try_get::<String, _>("email")?,
+            age: row.try_get::<Option<i32>, _>("age")?,
+            updated_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("updated_at")?,
+        })
+    })();
+    result.map_err(Into::into)
+}
+
+/// Assemble the SQL of `update_user_fields` for the given included conditional parameters
+fn update_user_fields_sql(included_params: &[&str]) -> String {
+    let mut final_sql = r"UPDATE public.users 
+SET updated_at = NOW() 
+#[, name = #{name?}] 
+#[, email = #{email?}] 
+#[, age = #{age?}] 
+WHERE id = #{user_id} 
+RETURNING id, name, email, age, updated_at"
+        .to_string();
+
+    if included_params.contains(&"name") {
+        final_sql = final_sql.replacen(r"#[, name = #{name?}]", r", name = #{name?}", 1);
+    } else {
+        final_sql = final_sql.replacen(r"#[, name = #{name?}]", "", 1);
+    }
+    if included_params.contains(&"email") {
+        final_sql = final_sql.replacen(r"#[, email = #{email?}]", r", email = #{email?}", 1);
+    } else {
+        final_sql = final_sql.replacen(r"#[, email = #{email?}]", "", 1);
+    }
+    if included_params.contains(&"age") {
+        final_sql = final_sql.replacen(r"#[, age = #{age?}]", r", age = #{age?}", 1);
+    } else {
+        final_sql = final_sql.replacen(r"#[, age = #{age?}]", "", 1);
+    }
+
+    #[allow(unused_assignments)]
+    let mut param_counter = 1;
+    if included_params.contains(&"name") {
+        final_sql = final_sql.replace(r"#{name?}", &format!("${}", param_counter));
+        param_counter += 1;
+    }
+    if included_params.contains(&"email") {
+        final_sql = final_sql.replace(r"#{email?}", &format!("${}", param_counter));
+        param_counter += 1;
+    }
+    if included_params.contains(&"age") {
+        final_sql = final_sql.replace(r"#{age?}", &format!("${}", param_counter));
+        param_counter += 1;
+    }
+    final_sql = final_sql.replace(r"#{user_id}", &format!("${}", param_counter));
+    param_counter += 1;
+    let _ = param_counter; // Suppress unused assignment warning
+    final_sql
+}
+
+/// Constraint violations specific to this query
+#[derive(Debug)]
+pub enum UpdateUserFieldsDiffConstraints {
+    /// Constraint: users_email_key on table users
+    UsersEmailKey,
+    /// Constraint: users_pkey on table users
+    UsersPkey,
+    /// Constraint: users_referrer_id_fkey on table users
+    UsersReferrerIdFkey,
+    /// Constraint: users_id_not_null on table users
+    UsersIdNotNull,
+    /// Constraint: users_name_not_null on table users
+    UsersNameNotNull,
+    /// Constraint: users_email_not_null on table users
+    UsersEmailNotNull,
+}
+
+impl TryFrom<super::ErrorConstraintInfo> for UpdateUserFieldsDiffConstraints {
+    type Error = ();
+
+    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
+        match info.constraint_name.as_str() {
+            "users_email_key" => Ok(Self::UsersEmailKey),
+            "users_pkey" => Ok(Self::UsersPkey),
+            "users_referrer_id_fkey" => Ok(Self::UsersReferrerIdFkey),
+            "users_id_not_null" => Ok(Self::UsersIdNotNull),
+            "users_name_not_null" => Ok(Self::UsersNameNotNull),
+            "users_email_not_null" => Ok(Self::UsersEmailNotNull),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct UpdateUserFieldsDiffParams {
+    pub name: String,
+    pub email: String,
+    pub age: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct UpdateUserFieldsDiffItem {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+    pub age: Option<i32>,
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Update user fields using diff-based conditional updates - compares old and new structs
+///
+/// Tables: `users`
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "UPDATE public.users \nSET updated_at = NOW() \n#[, name = #{name?}] \n#[, email = #{email?}] \n#[, age = #{age?}] \nWHERE id = #{user_id} \nRETURNING id, name, email, age, updated_at"
+    )
+)]
+pub async fn update_user_fields_diff(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    old: &UpdateUserFieldsDiffParams,
+    new: &UpdateUserFieldsDiffParams,
+    user_id: i32,
+) -> Result<UpdateUserFieldsDiffItem, super::Error<UpdateUserFieldsDiffConstraints>> {
+    let mut included_params: Vec<&str> = Vec::new();
+    if old.name != new.name {
+        included_params.push("name");
+    }
+    if old.email != new.email {
+        included_params.push("email");
+    }
+    if old.age != new.age {
+        included_params.push("age");
+    }
+    let final_sql = update_user_fields_diff_sql(&included_params);
+
+    let mut query = sqlx::query(&final_sql);
+
+    if included_params.contains(&r"name") {
+        query = query.bind(&new.name);
+    }
+    if included_params.contains(&r"email") {
+        query = query.bind(&new.email);
+    }
+    if included_params.contains(&r"age") {
+        query = query.bind(&new.age);
+    }
+    query = query.bind(&user_id);
+
+    let row = query.fetch_one(executor).await?;
+    let result: Result<_, sqlx::Error> = (|| {
+        Ok(UpdateUserFieldsDiffItem {
+            id: row.try_get::<i32, _>("id")?,
+            name: row.try_get::<String, _>("name")?,
+            email: row.try_get::<String, _>("email")?,
+            age: row.try_get::<Option<i32>, _>("age")?,
+            updated_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("updated_at")?,
+        })
+    })();
+    result.map_err(Into::into)
+}
+
+/// Assemble the SQL of `update_user_fields_diff` for the given included conditional parameters
+fn update_user_fields_diff_sql(included_params: &[&str]) -> String {
+    let mut final_sql = r"UPDATE public.users 
+SET updated_at = NOW() 
+#[, name = #{name?}] 
+#[, email = #{email?}] 
+#[, age = #{age?}] 
+WHERE id = #{user_id} 
+RETURNING id, name, email, age, updated_at"
+        .to_string();
+
+    if included_params.contains(&"name") {
+        final_sql = final_sql.replacen(r"#[, name = #{name?}]", r", name = #{name?}", 1);
+    } else {
+        final_sql = final_sql.replacen(r"#[, name = #{name?}]", "", 1);
+    }
+    if included_params.contains(&"email") {
+        final_sql = final_sql.replacen(r"#[, email = #{email?}]", r", email = #{email?}", 1);
+    } else {
+        final_sql = final_sql.replacen(r"#[, email = #{email?}]", "", 1);
+    }
+    if included_params.contains(&"age") {
+        final_sql = final_sql.replacen(r"#[, age = #{age?}]", r", age = #{age?}", 1);
+    } else {
+        final_sql = final_sql.replacen(r"#[, age = #{age?}]", "", 1);
+    }
+
+    #[allow(unused_assignments)]
+    let mut param_counter = 1;
+    if included_params.contains(&"name") {
+        final_sql = final_sql.replace(r"#{name?}", &format!("${}", param_counter));
+        param_counter += 1;
+    }
+    if included_params.contains(&"email") {
+        final_sql = final_sql.replace(r"#{email?}", &format!("${}", param_counter));
+        param_counter += 1;
+    }
+    if included_params.contains(&"age") {
+        final_sql = final_sql.replace(r"#{age?}", &format!("${}", param_counter));
+        param_counter += 1;
+    }
+    final_sql = final_sql.replace(r"#{user_id}", &format!("${}", param_counter));
+    param_counter += 1;
+    let _ = param_counter; // Suppress unused assignment warning
+    final_sql
+}
+
+/// Constraint violations specific to this query
+#[derive(Debug)]
+pub enum InsertUserStructuredConstraints {
+    /// Constraint: users_email_key on table users
+    UsersEmailKey,
+    /// Constraint: users_pkey on table users
+    UsersPkey,
+    /// Constraint: users_referrer_id_fkey on table users
+    UsersReferrerIdFkey,
+    /// Constraint: users_id_not_null on table users
+    UsersIdNotNull,
+    /// Constraint: users_name_not_null on table users
+    UsersNameNotNull,
+    /// Constraint: users_email_not_null on table users
+    UsersEmailNotNull,
+}
+
+impl TryFrom<super::ErrorConstraintInfo> for InsertUserStructuredConstraints {
+    type Error = ();
+
+    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
+        match info.constraint_name.as_str() {
+            "users_email_key" => Ok(Self::UsersEmailKey),
+            "users_pkey" => Ok(Self::UsersPkey),
+            "users_referrer_id_fkey" => Ok(Self::UsersReferrerIdFkey),
+            "users_id_not_null" => Ok(Self::UsersIdNotNull),
+            "users_name_not_null" => Ok(Self::UsersNameNotNull),
+            "users_email_not_null" => Ok(Self::UsersEmailNotNull),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct InsertUserStructuredParams {
+    pub name: String,
+    pub email: String,
+    pub age: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct InsertUserStructuredItem {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+    pub age: Option<i32>,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Insert a new user using structured parameters - all params passed as a single struct
+///
+/// Tables: `users`
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "INSERT INTO public.users (name, email, age) \nVALUES (#{name}, #{email}, #{age}) \nRETURNING id, name, email, age, created_at"
+    )
+)]
+pub async fn insert_user_structured(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    params: &InsertUserStructuredParams,
+) -> Result<InsertUserStructuredItem, super::Error<InsertUserStructuredConstraints>> {
+    let query = sqlx::query(
+        r"INSERT INTO public.users (name, email, age) 
+        VALUES ($1, $2, $3) 
+        RETURNING id, name, email, age, created_at",
+    );
+    let query = query.bind(&params.name);
+    let query = query.bind(&params.email);
+    let query = query.bind(params.age);
+    let row = query.fetch_one(executor).await?;
+    let result: Result<_, sqlx::Error> = (|| {
+        Ok(InsertUserStructuredItem {
+            id: row.try_get::<i32, _>("id")?,
+            name: row.try_get::<String, _>("name")?,
+            email: row.try_get::<String, _>("email")?,
+            age: row.try_get::<Option<i32>, _>("age")?,
+            created_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")?,
+        })
+    })();
+    result.map_err(Into::into)
+}
+
+/// Background task coalescing concurrent `insert_user_structured` calls into multi-row INSERT statements
+pub type InsertUserStructuredBatcher = super::batching::Batcher<
+    InsertUserStructuredParams,
+    Result<InsertUserStructuredItem, super::Error<InsertUserStructuredConstraints>>,
+>;
+
+/// Spawn the batcher for `insert_user_structured` on the current tokio runtime
+/// A batch is written when it holds 50 rows or 5 ms after its first call
+pub fn insert_user_structured_batcher(pool: sqlx::PgPool) -> InsertUserStructuredBatcher {
+    super::batching::Batcher::spawn(50, std::time::Duration::from_millis(5), move |rows| {
+        let pool = pool.clone();
+        async move { insert_user_structured_batch(&pool, rows).await }
+    })
+}
+
+/// Same as `insert_user_structured`, but written together with concurrent calls queued on the batcher
+pub async fn insert_user_structured_batched(
+    batcher: &InsertUserStructuredBatcher,
+    params: InsertUserStructuredParams,
+) -> Result<InsertUserStructuredItem, super::Error<InsertUserStructuredConstraints>> {
+    batcher
+        .submit(params)
+        .await
+        .unwrap_or_else(|| Err(sqlx::Error::WorkerCrashed.into()))
+}
+
+/// Write a batch with one multi-row INSERT
+/// Falls back to one INSERT per row if the batch fails, so every caller gets its own result
+async fn insert_user_structured_batch(
+    pool: &sqlx::PgPool,
+    rows: Vec<InsertUserStructuredParams>,
+) -> Vec<Result<InsertUserStructuredItem, super::Error<InsertUserStructuredConstraints>>> {
+    if let Ok(results) = insert_user_structured_batch_statement(pool, &rows).await {
+        return results;
+    }
+    let mut results = Vec::with_capacity(rows.len());
+    for row in rows {
+        results.push(insert_user_structured(pool, &row).await);
+    }
+    results
+}
+
+/// Run the multi-row INSERT of a batch
+/// Fails unless the statement succeeds with a result for every row
+async fn insert_user_structured_batch_statement(
+    pool: &sqlx::PgPool,
+    rows: &[InsertUserStructuredParams],
+) -> Result<
+    Vec<Result<InsertUserStructuredItem, super::Error<InsertUserStructuredConstraints>>>,
+    sqlx::Error,
+> {
+    let mut sql = String::from("WITH ");
+    for i in 0..rows.len() {
+        if i > 0 {
+            sql.push_str(",\n");
+        }
+        sql.push_str(&format!("automodel_row_{} AS (\n", i));
+        sql.push_str(&super::batching::renumber_params(
+            r"INSERT INTO public.users (name, email, age) 
+        VALUES ($1, $2, $3) 
+        RETURNING id, name, email, age, created_at",
+            i * 3,
+        ));
+        sql.push_str(&format!("\n, {} AS automodel_ordinal)", i));
+    }
+    for i in 0..rows.len() {
+        if i > 0 {
+            sql.push_str("\nUNION ALL");
+        }
+        sql.push_str(&format!("\nSELECT * FROM automodel_row_{}", i));
+    }
+    sql.push_str("\nORDER BY automodel_ordinal");
+    let mut query = sqlx::query(&sql);
+    for row in rows {
+        query = query.bind(&row.name);
+        query = query.bind(&row.email);
+        query = query.bind(row.age);
+    }
+    let fetched = query.fetch_all(pool).await?;
+    if fetched.len() != rows.len() {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    let mut results = Vec::with_capacity(rows.len());
+    for (i, row) in fetched.iter().enumerate() {
+        let ordinal: i32 = row.try_get("automodel_ordinal")?;
+        if usize::try_from(ordinal).ok() != Some(i) {
+            return Err(sqlx::Error::RowNotFound);
+        }
+        let result: Result<InsertUserStructuredItem, sqlx::Error> = (|| {
+            Ok(InsertUserStructuredItem {
+                id: row.try_get::<i32, _>("id")?,
+                name: row.try_get::<String, _>("name")?,
+                email: row.try_get::<String, _>("email")?,
+                age: row.try_get::<Option<i32>, _>("age")?,
+                created_at: row
+                    .try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")?,
+            })
+        })();
+        results.push(result.map_err(Into::into));
+    }
+    Ok(results)
+}
+
+/// Get all possible user statuses currently in use
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Unique
+///   ->  Sort
+///         Sort Key: status
+///         ->  Seq Scan on users
+/// JIT:
+///   Functions: 3
+///   Options: Inlining true, Optimization true, Expressions true, Deforming true
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(sql = "SELECT DISTINCT status \nFROM public.users \nORDER BY status")
+)]
+pub async fn get_all_user_statuses(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+) -> Result<Vec<Option<UserStatus>>, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT DISTINCT status 
+        FROM public.users 
+        ORDER BY status",
+    );
+    let rows = query.fetch_all(executor).await?;
+    let result: Result<Vec<_>, sqlx::Error> = rows
+        .iter()
+        .map(|row| Ok(row.try_get::<Option<UserStatus>, _>("status")?))
+        .collect();
+    result.map_err(Into::into)
+}
+
+#[derive(Debug, Clone)]
+pub struct GetAllUsersWithStarItem {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+    pub status: Option<UserStatus>,
+    pub profile: Option<UserProfile>,
+    pub settings: Option<serde_json::Value>,
+    pub is_active: Option<bool>,
+    pub age: Option<i32>,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub referrer_id: Option<i32>,
+}
+
+/// Get all public.users using SELECT * to fetch all columns
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Sort
+///   Sort Key: created_at DESC
+///   ->  Seq Scan on users
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(sql = "SELECT * \nFROM public.users \nORDER BY created_at DESC")
+)]
+pub async fn get_all_users_with_star(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+) -> Result<Vec<GetAllUsersWithStarItem>, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT * 
+        FROM public.users 
+        ORDER BY created_at DESC",
+    );
+    let rows = query.fetch_all(executor).await?;
+    let result: Result<Vec<_>, sqlx::Error> = rows
+        .iter()
+        .map(|row| {
+            Ok(GetAllUsersWithStarItem {
+                id: row.try_get::<i32, _>("id")?,
+                name: row.try_get::<String, _>("name")?,
+                email: row.try_get::<String, _>("email")?,
+                status: row.try_get::<Option<UserStatus>, _>("status")?,
+                profile: row
+                    .try_get::<Option<serde_json::Value>, _>("profile")?
+                    .map(|v| {
+                        serde_json::from_value::<UserProfile>(v)
+                            .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+                    })
+                    .transpose()?,
+                settings: row.try_get::<Option<serde_json::Value>, _>("settings")?,
+                is_active: row.try_get::<Option<bool>, _>("is_active")?,
+                age: row.try_get::<Option<i32>, _>("age")?,
+                created_at: row
+                    .try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")?,
+                updated_at: row
+                    .try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("updated_at")?,
+                referrer_id: row.try_get::<Option<i32>, _>("referrer_id")?,
+            })
+        })
+        .collect();
+    result.map_err(Into::into)
+}
+
+#[derive(Debug, Clone)]
+pub struct GetUserByIdWithStarItem {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+    pub status: Option<UserStatus>,
+    pub profile: Option<UserProfile>,
+    pub settings: Option<serde_json::Value>,
+    pub is_active: Option<bool>,
+    pub age: Option<i32>,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub referrer_id: Option<i32>,
+}
+
+/// Get a single user by ID using SELECT * to fetch all columns
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Index Scan using users_pkey on users
+///   Index Cond: (id = 0)
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(sql = "SELECT * \nFROM public.users \nWHERE id = #{user_id}")
+)]
+pub async fn get_user_by_id_with_star(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    user_id: i32,
+) -> Result<Option<GetUserByIdWithStarItem>, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT * 
+        FROM public.users 
+        WHERE id = $1",
+    );
+    let query = query.bind(user_id);
+    let row = query.fetch_optional(executor).await?;
+    match row {
+        Some(row) => {
+            let result: Result<_, sqlx::Error> = (|| {
+                Ok(GetUserByIdWithStarItem {
+                    id: row.try_get::<i32, _>("id")?,
+                    name: row.try_get::<String, _>("name")?,
+                    email: row.try_get::<String, _>("email")?,
+                    status: row.try_get::<Option<UserStatus>, _>("status")?,
+                    profile: row
+                        .try_get::<Option<serde_json::Value>, _>("profile")?
+                        .map(|v| {
+                            serde_json::from_value::<UserProfile>(v)
+                                .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+                        })
+                        .transpose()?,
+                    settings: row.try_get::<Option<serde_json::Value>, _>("settings")?,
+                    is_active: row.try_get::<Option<bool>, _>("is_active")?,
+                    age: row.try_get::<Option<i32>, _>("age")?,
+                    created_at: row
+                        .try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")?,
+                    updated_at: row
+                        .try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("updated_at")?,
+                    referrer_id: row.try_get::<Option<i32>, _>("referrer_id")?,
+                })
+            })();
+            result.map(Some).map_err(Into::into)
+        }
+        None => Ok(None),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GetUserByIdAndEmailParams {
+    pub id: i32,
+    pub email: String,
+}
+
+#[cfg(feature = "dynamic")]
+impl GetUserByIdAndEmailParams {
+    /// Convert into a map of field names to JSON values
+    pub fn to_map(
+        &self,
+    ) -> Result<std::collections::HashMap<String, serde_json::Value>, serde_json::Error> {
+        let mut map = std::collections::HashMap::new();
+        map.insert("id".to_string(), serde_json::to_value(&self.id)?);
+        map.insert("email".to_string(), serde_json::to_value(&self.email)?);
+        Ok(map)
+    }
+
+    /// Build from a map of field names to JSON values (missing fields are treated as null)
+    pub fn from_map(
+        mut map: std::collections::HashMap<String, serde_json::Value>,
+    ) -> Result<Self, serde_json::Error> {
+        Ok(Self {
+            id: serde_json::from_value(map.remove("id").unwrap_or(serde_json::Value::Null))?,
+            email: serde_json::from_value(map.remove("email").unwrap_or(serde_json::Value::Null))?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GetUserByIdAndEmailItem {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+}
+
+#[cfg(feature = "dynamic")]
+impl GetUserByIdAndEmailItem {
+    /// Convert into a map of field names to JSON values
+    pub fn to_map(
+        &self,
+    ) -> Result<std::collections::HashMap<String, serde_json::Value>, serde_json::Error> {
+        let mut map = std::collections::HashMap::new();
+        map.insert("id".to_string(), serde_json::to_value(&self.id)?);
+        map.insert("name".to_string(), serde_json::to_value(&self.name)?);
+        map.insert("email".to_string(), serde_json::to_value(&self.email)?);
+        Ok(map)
+    }
+
+    /// Build from a map of field names to JSON values (missing fields are treated as null)
+    pub fn from_map(
+        mut map: std::collections::HashMap<String, serde_json::Value>,
+    ) -> Result<Self, serde_json::Error> {
+        Ok(Self {
+            id: serde_json::from_value(map.remove("id").unwrap_or(serde_json::Value::Null))?,
+            name: serde_json::from_value(map.remove("name").unwrap_or(serde_json::Value::Null))?,
+            email: serde_json::from_value(map.remove("email").unwrap_or(serde_json::Value::Null))?,
+        })
+    }
+}
+
+/// Get a user by ID and email - generates GetUserByIdAndEmailParams struct and GetUserByIdAndEmailItem return struct
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Index Scan using users_email_key on users
+///   Index Cond: ((email)::text = 'dummy'::text)
+///   Filter: (id = 0)
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "SELECT id, name, email \nFROM public.users \nWHERE id = #{id} AND email = #{email}"
+    )
+)]
+pub async fn get_user_by_id_and_email(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    params: &GetUserByIdAndEmailParams,
+) -> Result<Option<GetUserByIdAndEmailItem>, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT id, name, email 
+        FROM public.users 
+        WHERE id = $1 AND email = $2",
+    );
+    let query = query.bind(params.id);
+    let query = query.bind(&params.email);
+    let row = query.fetch_optional(executor).await?;
+    match row {
+        Some(row) => {
+            let result: Result<_, sqlx::Error> = (|| {
+                Ok(GetUserByIdAndEmailItem {
+                    id: row.try_get::<i32, _>("id")?,
+                    name: row.try_get::<String, _>("name")?,
+                    email: row.try_get::<String, _>("email")?,
+                })
+            })();
+            result.map(Some).map_err(Into::into)
+        }
+        None => Ok(None),
+    }
+}
+
+/// Constraint violations specific to this query
+#[derive(Debug)]
+pub enum DeleteUserByIdAndEmailConstraints {
+    /// Constraint: users_email_key on table users
+    UsersEmailKey,
+    /// Constraint: users_pkey on table users
+    UsersPkey,
+    /// Constraint: users_referrer_id_fkey on table users
+    UsersReferrerIdFkey,
+    /// Constraint: users_id_not_null on table users
+    UsersIdNotNull,
+    /// Constraint: users_name_not_null on table users
+    UsersNameNotNull,
+    /// Constraint: users_email_not_null on table users
+    UsersEmailNotNull,
+}
+
+impl TryFrom<super::ErrorConstraintInfo> for DeleteUserByIdAndEmailConstraints {
+    type Error = ();
+
+    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
+        match info.constraint_name.as_str() {
+            "users_email_key" => Ok(Self::UsersEmailKey),
+            "users_pkey" => Ok(Self::UsersPkey),
+            "users_referrer_id_fkey" => Ok(Self::UsersReferrerIdFkey),
+            "users_id_not_null" => Ok(Self::UsersIdNotNull),
+            "users_name_not_null" => Ok(Self::UsersNameNotNull),
+            "users_email_not_null" => Ok(Self::UsersEmailNotNull),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DeleteUserByIdAndEmailItem {
+    pub id: i32,
+    pub email: String,
+}
+
+/// Delete user by ID and email - reuses GetUserByIdAndEmailParams struct
+///
+/// Tables: `users`
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "DELETE FROM public.users \nWHERE id = #{id} AND email = #{email} \nRETURNING id, email"
+    )
+)]
+pub async fn delete_user_by_id_and_email(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    params: &GetUserByIdAndEmailParams,
+) -> Result<DeleteUserByIdAndEmailItem, super::Error<DeleteUserByIdAndEmailConstraints>> {
+    let query = sqlx::query(
+        r"DELETE FROM public.users 
+        WHERE id = $1 AND email = $2 
+        RETURNING id, email",
+    );
+    let query = query.bind(params.id);
+    let query = query.bind(&params.email);
+    let row = query.fetch_one(executor).await?;
+    let result: Result<_, sqlx::Error> = (|| {
+        Ok(DeleteUserByIdAndEmailItem {
+            id: row.try_get::<i32, _>("id")?,
+            email: row.try_get::<String, _>("email")?,
+        })
+    })();
+    result.map_err(Into::into)
+}
+
+/// Constraint violations specific to this query
+#[derive(Debug)]
+pub enum UpdateUserContactInfoConstraints {
+    /// Constraint: users_email_key on table users
+    UsersEmailKey,
+    /// Constraint: users_pkey on table users
+    UsersPkey,
+    /// Constraint: users_referrer_id_fkey on table users
+    UsersReferrerIdFkey,
+    /// Constraint: users_id_not_null on table users
+    UsersIdNotNull,
+    /// Constraint: users_name_not_null on table users
+    UsersNameNotNull,
+    /// Constraint: users_email_not_null on table users
+    UsersEmailNotNull,
+}
+
+impl TryFrom<super::ErrorConstraintInfo> for UpdateUserContactInfoConstraints {
+    type Error = ();
+
+    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
+        match info.constraint_name.as_str() {
+            "users_email_key" => Ok(Self::UsersEmailKey),
+            "users_pkey" => Ok(Self::UsersPkey),
+            "users_referrer_id_fkey" => Ok(Self::UsersReferrerIdFkey),
+            "users_id_not_null" => Ok(Self::UsersIdNotNull),
+            "users_name_not_null" => Ok(Self::UsersNameNotNull),
+            "users_email_not_null" => Ok(Self::UsersEmailNotNull),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct UpdateUserContactInfoItem {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+}
+
+/// Update user contact info - reuses GetUserByIdAndEmailItem return struct as params
+///
+/// Tables: `users`
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "UPDATE public.users \nSET name = #{name}, email = #{email} \nWHERE id = #{id} \nRETURNING id, name, email"
+    )
+)]
+pub async fn update_user_contact_info(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    params: &GetUserByIdAndEmailItem,
+) -> Result<UpdateUserContactInfoItem, super::Error<UpdateUserContactInfoConstraints>> {
+    let query = sqlx::query(
+        r"UPDATE public.users 
+        SET name = $1, email = $2 
+        WHERE id = $3 
+        RETURNING id, name, email",
+    );
+    let query = query.bind(&params.name);
+    let query = query.bind(&params.email);
+    let query = query.bind(params.id);
+    let row = query.fetch_one(executor).await?;
+    let result: Result<_, sqlx::Error> = (|| {
+        Ok(UpdateUserContactInfoItem {
+            id: row.try_get::<i32, _>("id")?,
+            name: row.try_get::<String, _>("name")?,
+            email: row.try_get::<String, _>("email")?,
+        })
+    })();
+    result.map_err(Into::into)
+}
+
+/// Constraint violations specific to this query
+#[derive(Debug)]
+pub enum UpdateUserProfileDiffConstraints {
+    /// Constraint: users_email_key on table users
+    UsersEmailKey,
+    /// Constraint: users_pkey on table users
+    UsersPkey,
+    /// Constraint: users_referrer_id_fkey on table users
+    UsersReferrerIdFkey,
+    /// Constraint: users_id_not_null on table users
+    UsersIdNotNull,
+    /// Constraint: users_name_not_null on table users
+    UsersNameNotNull,
+    /// Constraint: users_email_not_null on table users
+    UsersEmailNotNull,
+}
+
+impl TryFrom<super::ErrorConstraintInfo> for UpdateUserProfileDiffConstraints {
+    type Error = ();
+
+    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
+        match info.constraint_name.as_str() {
+            "users_email_key" => Ok(Self::UsersEmailKey),
+            "users_pkey" => Ok(Self::UsersPkey),
+            "users_referrer_id_fkey" => Ok(Self::UsersReferrerIdFkey),
+            "users_id_not_null" => Ok(Self::UsersIdNotNull),
+            "users_name_not_null" => Ok(Self::UsersNameNotNull),
+            "users_email_not_null" => Ok(Self::UsersEmailNotNull),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct UpdateUserProfileDiffParams {
+    pub name: String,
+    pub email: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct UpdateUserProfileDiffItem {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+    pub profile: Option<UserProfile>,
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Update user profile with conditional name/email - generates UpdateUserProfileDiffParams
+///
+/// Tables: `users`
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "UPDATE public.users \nSET profile = #{profile}, updated_at = NOW() \n#[, name = #{name?}] \n#[, email = #{email?}] \nWHERE id = #{user_id} \nRETURNING id, name, email, profile, updated_at"
+    )
+)]
+pub async fn update_user_profile_diff(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    old: &UpdateUserProfileDiffParams,
+    new: &UpdateUserProfileDiffParams,
+    profile: UserProfile,
+    user_id: i32,
+) -> Result<UpdateUserProfileDiffItem, super::Error<UpdateUserProfileDiffConstraints>> {
+    let mut included_params: Vec<&str> = Vec::new();
+    if old.name != new.name {
+        included_params.push("name");
+    }
+    if old.email != new.email {
+        included_params.push("email");
+    }
+    let final_sql = update_user_profile_diff_sql(&included_params);
+
+    let mut query = sqlx::query(&final_sql);
+
+    let profile_json =
+        serde_json::to_value(&profile).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+    query = query.bind(profile_json);
+    if included_params.contains(&r"name") {
+        query = query.bind(&new.name);
+    }
+    if included_params.contains(&r"email") {
+        query = query.bind(&new.email);
+    }
+    query = query.bind(&user_id);
+
+    let row = query.fetch_one(executor).await?;
+    let result: Result<_, sqlx::Error> = (|| {
+        Ok(UpdateUserProfileDiffItem {
+            id: row.try_get::<i32, _>("id")?,
+            name: row.try_get::<String, _>("name")?,
+            email: row.try_get::<String, _>("email")?,
+            profile: row
+                .try_get::<Option<serde_json::Value>, _>("profile")?
+                .map(|v| {
+                    serde_json::from_value::<UserProfile>(v)
+                        .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+                })
+                .transpose()?,
+            updated_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("updated_at")?,
+        })
+    })();
+    result.map_err(Into::into)
+}
+
+/// Assemble the SQL of `update_user_profile_diff` for the given included conditional parameters
+fn update_user_profile_diff_sql(included_params: &[&str]) -> String {
+    let mut final_sql = r"UPDATE public.users 
+SET profile = #{profile}, updated_at = NOW() 
+#[, name = #{name?}] 
+#[, email = #{email?}] 
+WHERE id = #{user_id} 
+RETURNING id, name, email, profile, updated_at"
+        .to_string();
+
+    if included_params.contains(&"name") {
+        final_sql = final_sql.replacen(r"#[, name = #{name?}]", r", name = #{name?}", 1);
+    } else {
+        final_sql = final_sql.replacen(r"#[, name = #{name?}]", "", 1);
+    }
+    if included_params.contains(&"email") {
+        final_sql = final_sql.replacen(r"#[, email = #{email?}]", r", email = #{email?}", 1);
+    } else {
+        final_sql = final_sql.replacen(r"#[, email = #{email?}]", "", 1);
+    }
+
+    #[allow(unused_assignments)]
+    let mut param_counter = 1;
+    final_sql = final_sql.replace(r"#{profile}", &format!("${}", param_counter));
+    param_counter += 1;
+    if included_params.contains(&"name") {
+        final_sql = final_sql.replace(r"#{name?}", &format!("${}", param_counter));
+        param_counter += 1;
+    }
+    if included_params.contains(&"email") {
+        final_sql = final_sql.replace(r"#{email?}", &format!("${}", param_counter));
+        param_counter += 1;
+    }
+    final_sql = final_sql.replace(r"#{user_id}", &format!("${}", param_counter));
+    param_counter += 1;
+    let _ = param_counter; // Suppress unused assignment warning
+    final_sql
+}
+
+/// Constraint violations specific to this query
+#[derive(Debug)]
+pub enum UpdateUserMetadataDiffConstraints {
+    /// Constraint: users_email_key on table users
+    UsersEmailKey,
+    /// Constraint: users_pkey on table users
+    UsersPkey,
+    /// Constraint: users_referrer_id_fkey on table users
+    UsersReferrerIdFkey,
+    /// Constraint: users_id_not_null on table users
+    UsersIdNotNull,
+    /// Constraint: users_name_not_null on table users
+    UsersNameNotNull,
+    /// Constraint: users_email_not_null on table users
+    UsersEmailNotNull,
+}
+
+impl TryFrom<super::ErrorConstraintInfo> for UpdateUserMetadataDiffConstraints {
+    type Error = ();
+
+    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
+        match info.constraint_name.as_str() {
+            "users_email_key" => Ok(Self::UsersEmailKey),
+            "users_pkey" => Ok(Self::UsersPkey),
+            "users_referrer_id_fkey" => Ok(Self::UsersReferrerIdFkey),
+            "users_id_not_null" => Ok(Self::UsersIdNotNull),
+            "users_name_not_null" => Ok(Self::UsersNameNotNull),
+            "users_email_not_null" => Ok(Self::UsersEmailNotNull),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct UpdateUserMetadataDiffItem {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Update user metadata - reuses UpdateUserProfileDiffParams struct
+///
+/// Tables: `users`
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "UPDATE public.users \nSET profile = #{profile}, updated_at = NOW() \n#[, name = #{name?}] \n#[, email = #{email?}] \nWHERE id = #{user_id} \nRETURNING id, name, email, updated_at"
+    )
+)]
+pub async fn update_user_metadata_diff(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    old: &UpdateUserProfileDiffParams,
+    new: &UpdateUserProfileDiffParams,
+    profile: UserProfile,
+    user_id: i32,
+) -> Result<UpdateUserMetadataDiffItem, super::Error<UpdateUserMetadataDiffConstraints>> {
+    let mut included_params: Vec<&str> = Vec::new();
+    if old.name != new.name {
+        included_params.push("name");
+    }
+    if old.email != new.email {
+        included_params.push("email");
+    }
+    let final_sql = update_user_metadata_diff_sql(&included_params);
+
+    let mut query = sqlx::query(&final_sql);
+
+    let profile_json =
+        serde_json::to_value(&profile).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+    query = query.bind(profile_json);
+    if included_params.contains(&r"name") {
+        query = query.bind(&new.name);
+    }
+    if included_params.contains(&r"email") {
+        query = query.bind(&new.email);
+    }
+    query = query.bind(&user_id);
+
+    let row = query.fetch_one(executor).await?;
+    let result: Result<_, sqlx::Error> = (|| {
+        Ok(UpdateUserMetadataDiffItem {
+            id: row.try_get::<i32, _>("id")?,
+            name: row.try_get::<String, _>("name")?,
+            email: row.try_get::<String, _>("email")?,
+            updated_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("updated_at")?,
+        })
+    })();
+    result.map_err(Into::into)
+}
+
+/// Assemble the SQL of `update_user_metadata_diff` for the given included conditional parameters
+fn update_user_metadata_diff_sql(included_params: &[&str]) -> String {
+    let mut final_sql = r"UPDATE public.users 
+SET profile = #{profile}, updated_at = NOW() 
+#[, name = #{name?}] 
+#[, email = #{email?}] 
+WHERE id = #{user_id} 
+RETURNING id, name, email, updated_at"
+        .to_string();
+
+    if included_params.contains(&"name") {
+        final_sql = final_sql.replacen(r"#[, name = #{name?}]", r", name = #{name?}", 1);
+    } else {
+        final_sql = final_sql.replacen(r"#[, name = #{name?}]", "", 1);
+    }
+    if included_params.contains(&"email") {
+        final_sql = final_sql.replacen(r"#[, email = #{email?}]", r", email = #{email?}", 1);
+    } else {
+        final_sql = final_sql.replacen(r"#[, email = #{email?}]", "", 1);
+    }
+
+    #[allow(unused_assignments)]
+    let mut param_counter = 1;
+    final_sql = final_sql.replace(r"#{profile}", &format!("${}", param_counter));
+    param_counter += 1;
+    if included_params.contains(&"name") {
+        final_sql = final_sql.replace(r"#{name?}", &format!("${}", param_counter));
+        param_counter += 1;
+    }
+    if included_params.contains(&"email") {
+        final_sql = final_sql.replace(r"#{email?}", &format!("${}", param_counter));
+        param_counter += 1;
+    }
+    final_sql = final_sql.replace(r"#{user_id}", &format!("${}", param_counter));
+    param_counter += 1;
+    let _ = param_counter; // Suppress unused assignment warning
+    final_sql
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct UserSummary {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+}
+
+/// Get user summary - generates UserSummary return struct with custom name
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Index Scan using users_pkey on users
+///   Index Cond: (id = 0)
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(sql = "SELECT id, name, email \nFROM public.users \nWHERE id = #{user_id}")
+)]
+pub async fn get_user_summary(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    user_id: i32,
+) -> Result<UserSummary, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT id, name, email 
+        FROM public.users 
+        WHERE id = $1",
+    );
+    let query = query.bind(user_id);
+    let row = query.fetch_one(executor).await?;
+    let result: Result<_, sqlx::Error> = (|| {
+        Ok(UserSummary {
+            id: row.try_get::<i32, _>("id")?,
+            name: row.try_get::<String, _>("name")?,
+            email: row.try_get::<String, _>("email")?,
+        })
+    })();
+    result.map_err(Into::into)
+}
+
+/// Get user info by email - reuses UserSummary return struct
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Index Scan using users_email_key on users
+///   Index Cond: ((email)::text = 'dummy'::text)
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(sql = "SELECT id, name, email \nFROM public.users \nWHERE email = #{email}")
+)]
+pub async fn get_user_info_by_email(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    email: String,
+) -> Result<Option<UserSummary>, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT id, name, email 
+        FROM public.users 
+        WHERE email = $1",
+    );
+    let query = query.bind(&email);
+    let row = query.fetch_optional(executor).await?;
+    match row {
+        Some(row) => {
+            let result: Result<_, sqlx::Error> = (|| {
+                Ok(UserSummary {
+                    id: row.try_get::<i32, _>("id")?,
+                    name: row.try_get::<String, _>("name")?,
+                    email: row.try_get::<String, _>("email")?,
+                })
+            })();
+            result.map(Some).map_err(Into::into)
+        }
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod conditional_variant_tests {
+    use super::*;
+
+    fn normalize_sql(sql: &str) -> String {
+        sql.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    #[test]
+    fn find_users_by_name_and_age_sql_variants() {
+        assert_eq!(
+            normalize_sql(&find_users_by_name_and_age_sql(&[])),
+            normalize_sql(
+                r"SELECT id, name, email, age 
+FROM public.users 
+WHERE name ILIKE $1 
+ 
+AND name = $2 
+ 
+ORDER BY name"
+            ),
+            "find_users_by_name_and_age (base)"
+        );
+        assert_eq!(
+            normalize_sql(&find_users_by_name_and_age_sql(&["min_age"])),
+            normalize_sql(
+                r"SELECT id, name, email, age 
+FROM public.users 
+WHERE name ILIKE $1 
+AND age >= $2 
+AND name = $3 
+ 
+ORDER BY name"
+            ),
+            "find_users_by_name_and_age (variant 1)"
+        );
+        assert_eq!(
+            normalize_sql(&find_users_by_name_and_age_sql(&["max_age"])),
+            normalize_sql(
+                r"SELECT id, name, email, age 
+FROM public.users 
+WHERE name ILIKE $1 
+ 
+AND name = $2 
+AND age <= $3 
+ORDER BY name"
+            ),
+            "find_users_by_name_and_age (variant 2)"
+        );
+    }
+
+    #[test]
+    fn search_users_advanced_sql_variants() {
+        assert_eq!(
+            normalize_sql(&search_users_advanced_sql(&[])),
+            normalize_sql(
+                r"SELECT id, name, email, age, created_at 
+FROM public.users 
+WHERE 1=1 
+ 
+ 
+ 
+ORDER BY created_at DESC"
+            ),
+            "search_users_advanced (base)"
+        );
+        assert_eq!(
+            normalize_sql(&search_users_advanced_sql(&["name_pattern"])),
+            normalize_sql(
+                r"SELECT id, name, email, age, created_at 
+FROM public.users 
+WHERE 1=1 
+AND name ILIKE $1 
+ 
+ 
+ORDER BY created_at DESC"
+            ),
+            "search_users_advanced (variant 1)"
+        );
+        assert_eq!(
+            normalize_sql(&search_users_advanced_sql(&["min_age"])),
+            normalize_sql(
+                r"SELECT id, name, email, age, created_at 
+FROM public.users 
+WHERE 1=1 
+ 
+AND age >= $1 
+ 
+ORDER BY created_at DESC"
+            ),
+            "search_users_advanced (variant 2)"
+        );
+        assert_eq!(
+            normalize_sql(&search_users_advanced_sql(&["since"])),
+            normalize_sql(
+                r"SELECT id, name, email, age, created_at 
+FROM public.users 
+WHERE 1=1 
+ 
+ 
+AND created_at >= $1 
+ORDER BY created_at DESC"
+            ),
+            "search_users_advanced (variant 3)"
+        );
+        assert_eq!(
+            normalize_sql(&search_users_advanced_sql(&["name_pattern", "min_age"])),
+            normalize_sql(
+                r"SELECT id, name, email, age, created_at 
+FROM public.users 
+WHERE 1=1 
+AND name ILIKE $1 
+AND age >= $2 
+ 
+ORDER BY created_at DESC"
+            ),
+            "search_users_advanced (variants 1+2)"
+        );
+        assert_eq!(
+            normalize_sql(&search_users_advanced_sql(&["name_pattern", "since"])),
+            normalize_sql(
+                r"SELECT id, name, email, age, created_at 
+FROM public.users 
+WHERE 1=1 
+AND name ILIKE $1 
+ 
+AND created_at >= $2 
+ORDER BY created_at DESC"
+            ),
+            "search_users_advanced (variants 1+3)"
+        );
+        assert_eq!(
+            normalize_sql(&search_users_advanced_sql(&["min_age", "since"])),
+            normalize_sql(
+                r"SELECT id, name, email, age, created_at 
+FROM public.users 
+WHERE 1=1 
+ 
+AND age >= $1 
+AND created_at >= $2 
+ORDER BY created_at DESC"
+            ),
+            "search_users_advanced (variants 2+3)"
+        );
+        assert_eq!(
+            normalize_sql(&search_users_advanced_sql(&[
+                "name_pattern",
+                "min_age",
+                "since"
+            ])),
+            normalize_sql(
+                r"SELECT id, name, email, age, created_at 
+FROM public.users 
+WHERE 1=1 
+AND name ILIKE $1 
+AND age >= $2 
+AND created_at >= $3 
+ORDER BY created_at DESC"
+            ),
+            "search_users_advanced (variants 1+2+3)"
+        );
+    }
+
+    #[test]
+    fn update_user_fields_sql_variants() {
+        assert_eq!(
+            normalize_sql(&update_user_fields_sql(&[])),
+            normalize_sql(
+                r"UPDATE public.users 
+SET updated_at = NOW() 
+ 
+ 
+ 
+WHERE id = $1 
+RETURNING id, name, email, age, updated_at"
+            ),
+            "update_user_fields (base)"
+        );
+        assert_eq!(
+            normalize_sql(&update_user_fields_sql(&["name"])),
+            normalize_sql(
+                r"UPDATE public.users 
+SET updated_at = NOW() 
+, name = $1 
+ 
+ 
+WHERE id = $2 
+RETURNING id, name, email, age, updated_at"
+            ),
+            "update_user_fields (variant 1)"
+        );
+        assert_eq!(
+            normalize_sql(&update_user_fields_sql(&["email"])),
+            normalize_sql(
+                r"UPDATE public.users 
+SET updated_at = NOW() 
+ 
+, email = $1 
+ 
+WHERE id = $2 
+RETURNING id, name, email, age, updated_at"
+            ),
+            "update_user_fields (variant 2)"
+        );
+        assert_eq!(
+            normalize_sql(&update_user_fields_sql(&["age"])),
+            normalize_sql(
+                r"UPDATE public.users 
+SET updated_at = NOW() 
+ 
+ 
+, age = $1 
+WHERE id = $2 
+RETURNING id, name, email, age, updated_at"
+            ),
+            "update_user_fields (variant 3)"
+        );
+    }
+
+    #[test]
+    fn update_user_fields_diff_sql_variants() {
+        assert_eq!(
+            normalize_sql(&update_user_fields_diff_sql(&[])),
+            normalize_sql(
+                r"UPDATE public.users 
+SET updated_at = NOW() 
+ 
+ 
+ 
+WHERE id = $1 
+RETURNING id, name, email, age, updated_at"
+            ),
+            "update_user_fields_diff (base)"
+        );
+        assert_eq!(
+            normalize_sql(&update_user_fields_diff_sql(&["name"])),
+            normalize_sql(
+                r"UPDATE public.users 
+SET updated_at = NOW() 
+, name = $1 
+ 
+ 
+WHERE id = $2 
+RETURNING id, name, email, age, updated_at"
+            ),
+            "update_user_fields_diff (variant 1)"
+        );
+        assert_eq!(
+            normalize_sql(&update_user_fields_diff_sql(&["email"])),
+            normalize_sql(
+                r"UPDATE public.users 
+SET updated_at = NOW() 
+ 
+, email = $1 
+ 
+WHERE id = $2 
+RETURNING id, name, email, age, updated_at"
+            ),
+            "update_user_fields_diff (variant 2)"
+        );
+        assert_eq!(
+            normalize_sql(&update_user_fields_diff_sql(&["age"])),
+            normalize_sql(
+                r"UPDATE public.users 
+SET updated_at = NOW() 
+ 
+ 
+, age = $1 
+WHERE id = $2 
+RETURNING id, name, email, age, updated_at"
+            ),
+            "update_user_fields_diff (variant 3)"
+        );
+    }
+
+    #[test]
+    fn update_user_profile_diff_sql_variants() {
+        assert_eq!(
+            normalize_sql(&update_user_profile_diff_sql(&[])),
+            normalize_sql(
+                r"UPDATE public.users 
+SET profile = $1, updated_at = NOW() 
+ 
+ 
+WHERE id = $2 
+RETURNING id, name, email, profile, updated_at"
+            ),
+            "update_user_profile_diff (base)"
+        );
+        assert_eq!(
+            normalize_sql(&update_user_profile_diff_sql(&["name"])),
+            normalize_sql(
+                r"UPDATE public.users 
+SET profile = $1, updated_at = NOW() 
+, name = $2 
+ 
+WHERE id = $3 
+RETURNING id, name, email, profile, updated_at"
+            ),
+            "update_user_profile_diff (variant 1)"
+        );
+        assert_eq!(
+            normalize_sql(&update_user_profile_diff_sql(&["email"])),
+            normalize_sql(
+                r"UPDATE public.users 
+SET profile = $1, updated_at = NOW() 
+ 
+, email = $2 
+WHERE id = $3 
+RETURNING id, name, email, profile, updated_at"
+            ),
+            "update_user_profile_diff (variant 2)"
+        );
+    }
+
+    #[test]
+    fn update_user_metadata_diff_sql_variants() {
+        assert_eq!(
+            normalize_sql(&update_user_metadata_diff_sql(&[])),
+            normalize_sql(
+                r"UPDATE public.users 
+SET profile = $1, updated_at = NOW() 
+ 
+ 
+WHERE id = $2 
+RETURNING id, name, email, updated_at"
+            ),
+            "update_user_metadata_diff (base)"
+        );
+        assert_eq!(
+            normalize_sql(&update_user_metadata_diff_sql(&["name"])),
+            normalize_sql(
+                r"UPDATE public.users 
+SET profile = $1, updated_at = NOW() 
+, name = $2 
+ 
+WHERE id = $3 
+RETURNING id, name, email, updated_at"
+            ),
+            "update_user_metadata_diff (variant 1)"
+        );
+        assert_eq!(
+            normalize_sql(&update_user_metadata_diff_sql(&["email"])),
+            normalize_sql(
+                r"UPDATE public.users 
+SET profile = $1, updated_at = NOW() 
+ 
+, email = $2 
+WHERE id = $3 
+RETURNING id, name, email, updated_at"
+            ),
+            "update_user_metadata_diff (variant 2)"
+        );
+    }
+}
+
+#[cfg(test)]
+mod isolation_tests {
+    use super::*;
+
+    /// Attempts of each transaction before the test fails
+    const MAX_ATTEMPTS: usize = 5;
+
+    /// Run the statement in two concurrent SERIALIZABLE transactions, retrying serialization
+    /// failures, and return the total number of retries
+    async fn interleaved_retries(sql: &str) -> usize {
+        let database_url =
+            std::env::var("DATABASE_URL").expect("DATABASE_URL must be set to run isolation tests");
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .max_connections(2)
+            .connect(&database_url)
+            .await
+            .expect("failed to connect to DATABASE_URL");
+        let (first, second) = tokio::join!(
+            run_with_retries(&pool, sql, std::time::Duration::from_millis(200)),
+            run_with_retries(&pool, sql, std::time::Duration::ZERO)
+        );
+        first + second
+    }
+
+    /// Run the statement in a SERIALIZABLE transaction until it commits, returning the retries
+    async fn run_with_retries(pool: &sqlx::PgPool, sql: &str, hold: std::time::Duration) -> usize {
+        for attempt in 0..MAX_ATTEMPTS {
+            let hold = if attempt == 0 {
+                hold
+            } else {
+                std::time::Duration::ZERO
+            };
+            match run_once(pool, sql, hold).await {
+                Ok(()) => return attempt,
+                Err(e) if classify_error(&e) == DbErrorKind::SerializationFailure => continue,
+                Err(e) => panic!("transaction failed: {}", e),
+            }
+        }
+        panic!("transaction did not commit after {} attempts", MAX_ATTEMPTS)
+    }
+
+    async fn run_once(
+        pool: &sqlx::PgPool,
+        sql: &str,
+        hold: std::time::Duration,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        sqlx::raw_sql("SET TRANSACTION ISOLATION LEVEL SERIALIZABLE")
+            .execute(&mut *tx)
+            .await?;
+        sqlx::raw_sql(sql).execute(&mut *tx).await?;
+        tokio::time::sleep(hold).await;
+        tx.commit().await
+    }
+
+    #[tokio::test]
+    #[ignore = "runs interleaved transactions against DATABASE_URL"]
+    async fn update_user_status_serialization_retries() {
+        let retries = interleaved_retries(
+            r"UPDATE public.users 
+SET status = 'active' 
+WHERE id = '1' 
+RETURNING id, status",
+        )
+        .await;
+        assert!(
+            retries > 0,
+            "update_user_status caused no serialization failure"
+        );
+    }
+}
+// AUTOMODEL_CONTENT_HASH: 59a7ca6f2d9fe00c
@@ -0,0 +1,1789 @@
+// This file was automatically generated by AutoModel. Do not edit manually.
+
+#[allow(unused_imports)]
+use super::*;
+
+/// Get all user summaries - reuses UserSummary return struct
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Sort
+///   Sort Key: name
+///   ->  Seq Scan on users
+/// JIT:
+///   Functions: 2
+///   Options: Inlining true, Optimization true, Expressions true, Deforming true
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(sql = "SELECT id, name, email \nFROM public.users \nORDER BY name")
+)]
+pub async fn get_all_user_summaries(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+) -> Result<Vec<UserSummary>, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT id, name, email 
+        FROM public.users 
+        ORDER BY name",
+    );
+    let rows = query.fetch_all(executor).await?;
+    let result: Result<Vec<_>, sqlx::Error> = rows
+        .iter()
+        .map(|row| {
+            Ok(UserSummary {
+                id: row.try_get::<i32, _>("id")?,
+                name: row.try_get::<String, _>("name")?,
+                email: row.try_get::<String, _>("email")?,
+            })
+        })
+        .collect();
+    result.map_err(Into::into)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct UserDetails {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+    pub age: Option<i32>,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Get user details with age and created_at - generates UserDetails return struct
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Index Scan using users_pkey on users
+///   Index Cond: (id = 0)
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "SELECT id, name, email, age, created_at \nFROM public.users \nWHERE id = #{user_id}"
+    )
+)]
+pub async fn get_user_details(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    user_id: i32,
+) -> Result<UserDetails, super::ErrorReadOnly> {
+    let not_found_message = format!("user {:?} missing", user_id);
+    let result: Result<UserDetails, super::ErrorReadOnly> = async move {
+        let query = sqlx::query(
+            r"SELECT id, name, email, age, created_at 
+        FROM public.users 
+        WHERE id = $1",
+        );
+        let query = query.bind(user_id);
+        let row = query.fetch_one(executor).await?;
+        let result: Result<_, sqlx::Error> = (|| {
+            Ok(UserDetails {
+                id: row.try_get::<i32, _>("id")?,
+                name: row.try_get::<String, _>("name")?,
+                email: row.try_get::<String, _>("email")?,
+                age: row.try_get::<Option<i32>, _>("age")?,
+                created_at: row
+                    .try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")?,
+            })
+        })();
+        result.map_err(Into::into)
+    }
+    .await;
+    result.map_err(|e| e.with_not_found_message(not_found_message))
+}
+
+/// Search user details - reuses UserDetails return struct
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Seq Scan on users
+///   Filter: ((name)::text ~~* 'dummy'::text)
+/// JIT:
+///   Functions: 4
+///   Options: Inlining true, Optimization true, Expressions true, Deforming true
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "SELECT id, name, email, age, created_at \nFROM public.users \nWHERE name ILIKE #{pattern}"
+    )
+)]
+pub async fn search_user_details(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    pattern: String,
+) -> Result<Vec<UserDetails>, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT id, name, email, age, created_at 
+        FROM public.users 
+        WHERE name ILIKE $1",
+    );
+    let query = query.bind(&pattern);
+    let rows = query.fetch_all(executor).await?;
+    let result: Result<Vec<_>, sqlx::Error> = rows
+        .iter()
+        .map(|row| {
+            Ok(UserDetails {
+                id: row.try_get::<i32, _>("id")?,
+                name: row.try_get::<String, _>("name")?,
+                email: row.try_get::<String, _>("email")?,
+                age: row.try_get::<Option<i32>, _>("age")?,
+                created_at: row
+                    .try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")?,
+            })
+        })
+        .collect();
+    result.map_err(Into::into)
+}
+
+/// Find user by criteria - uses GetUserByIdAndEmailParams for params and UserSummary for return
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Index Scan using users_email_key on users
+///   Index Cond: ((email)::text = 'dummy'::text)
+///   Filter: (id = 0)
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "SELECT id, name, email \nFROM public.users \nWHERE id = #{id} AND email = #{email}"
+    )
+)]
+pub async fn find_user_by_criteria(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    params: &GetUserByIdAndEmailParams,
+) -> Result<Option<UserSummary>, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT id, name, email 
+        FROM public.users 
+        WHERE id = $1 AND email = $2",
+    );
+    let query = query.bind(params.id);
+    let query = query.bind(&params.email);
+    let row = query.fetch_optional(executor).await?;
+    match row {
+        Some(row) => {
+            let result: Result<_, sqlx::Error> = (|| {
+                Ok(UserSummary {
+                    id: row.try_get::<i32, _>("id")?,
+                    name: row.try_get::<String, _>("name")?,
+                    email: row.try_get::<String, _>("email")?,
+                })
+            })();
+            result.map(Some).map_err(Into::into)
+        }
+        None => Ok(None),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GetUserSimpleItem {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Simple user lookup by ID with detailed info
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Index Scan using users_pkey on users
+///   Index Cond: (id = 0)
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(sql = "SELECT id, name, email, created_at\nFROM public.users\nWHERE id = #{user_id}")
+)]
+pub async fn get_user_simple(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    user_id: i32,
+) -> Result<Option<GetUserSimpleItem>, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT id, name, email, created_at
+        FROM public.users
+        WHERE id = $1",
+    );
+    let query = query.bind(user_id);
+    let row = query.fetch_optional(executor).await?;
+    match row {
+        Some(row) => {
+            let result: Result<_, sqlx::Error> = (|| {
+                Ok(GetUserSimpleItem {
+                    id: row.try_get::<i32, _>("id")?,
+                    name: row.try_get::<String, _>("name")?,
+                    email: row.try_get::<String, _>("email")?,
+                    created_at: row
+                        .try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("created_at")?,
+                })
+            })();
+            result.map(Some).map_err(Into::into)
+        }
+        None => Ok(None),
+    }
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
+pub struct TestCustomDerivesParams {
+    pub user_id: i32,
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
+pub struct UserWithCustomDerives {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+    pub age: Option<i32>,
+}
+
+/// Test custom derive traits
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Index Scan using users_pkey on users
+///   Index Cond: (id = 0)
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(sql = "SELECT id, name, email, age\nFROM public.users\nWHERE id = #{user_id}")
+)]
+pub async fn test_custom_derives(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    params: &TestCustomDerivesParams,
+) -> Result<UserWithCustomDerives, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT id, name, email, age
+        FROM public.users
+        WHERE id = $1",
+    );
+    let query = query.bind(params.user_id);
+    let row = query.fetch_one(executor).await?;
+    let result: Result<_, sqlx::Error> = (|| {
+        Ok(UserWithCustomDerives {
+            id: row.try_get::<i32, _>("id")?,
+            name: row.try_get::<String, _>("name")?,
+            email: row.try_get::<String, _>("email")?,
+            age: row.try_get::<Option<i32>, _>("age")?,
+        })
+    })();
+    result.map_err(Into::into)
+}
+
+#[derive(
+    Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
+pub struct UserId {
+    pub id: i32,
+}
+
+/// Test single column with explicit return_type - should generate UserId struct
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Index Scan using users_email_key on users
+///   Index Cond: ((email)::text = 'dummy'::text)
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(sql = "SELECT id\nFROM public.users\nWHERE email = #{email}")
+)]
+pub async fn get_user_id_only(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    email: String,
+) -> Result<UserId, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT id
+        FROM public.users
+        WHERE email = $1",
+    );
+    let query = query.bind(&email);
+    let row = query.fetch_one(executor).await?;
+    let result: Result<_, sqlx::Error> = (|| {
+        Ok(UserId {
+            id: row.try_get::<i32, _>("id")?,
+        })
+    })();
+    result.map_err(Into::into)
+}
+
+/// Test single column without return_type - should return raw i32
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Index Scan using users_email_key on users
+///   Index Cond: ((email)::text = 'dummy'::text)
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(sql = "SELECT id\nFROM public.users\nWHERE email = #{email}")
+)]
+pub async fn get_user_id_raw(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    email: String,
+) -> Result<i32, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT id
+        FROM public.users
+        WHERE email = $1",
+    );
+    let query = query.bind(&email);
+    let row = query.fetch_one(executor).await?;
+    Ok(row.try_get::<i32, _>("id")?)
+}
+
+/// Get the distinct statuses of users matching any of the given statuses (enum array parameter and enum array output)
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Aggregate
+///   ->  Seq Scan on users
+///         Filter: (status = ANY ('{}'::public.user_status[]))
+/// JIT:
+///   Functions: 5
+///   Options: Inlining true, Optimization true, Expressions true, Deforming true
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "SELECT ARRAY_AGG(DISTINCT status ORDER BY status) AS statuses\nFROM public.users\nWHERE status = ANY(#{statuses})"
+    )
+)]
+pub async fn get_user_statuses_in(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    statuses: Vec<UserStatus>,
+) -> Result<Option<Vec<UserStatus>>, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT ARRAY_AGG(DISTINCT status ORDER BY status) AS statuses
+        FROM public.users
+        WHERE status = ANY($1)",
+    );
+    let query = query.bind(statuses);
+    let row = query.fetch_one(executor).await?;
+    Ok(row.try_get::<Option<Vec<UserStatus>>, _>("statuses")?)
+}
+
+/// Get the name and email of a user as a tuple
+///
+/// Returns tuples of (`name`, `email`)
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Index Scan using users_pkey on users
+///   Index Cond: (id = 0)
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(sql = "SELECT name, email\nFROM public.users\nWHERE id = #{id}")
+)]
+pub async fn get_user_name_and_email(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    id: i32,
+) -> Result<Option<(String, String)>, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT name, email
+        FROM public.users
+        WHERE id = $1",
+    );
+    let query = query.bind(id);
+    let row = query.fetch_optional(executor).await?;
+    match row {
+        Some(row) => {
+            let result: Result<_, sqlx::Error> = (|| {
+                Ok((
+                    row.try_get::<String, _>("name")?,
+                    row.try_get::<String, _>("email")?,
+                ))
+            })();
+            result.map(Some).map_err(Into::into)
+        }
+        None => Ok(None),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ListUsersForMaintenanceItem {
+    pub id: i32,
+    pub email: String,
+}
+
+/// List users at least the given age, with chunked iteration helpers for maintenance jobs
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Bitmap Heap Scan on users
+///   Recheck Cond: (age >= 0)
+///   ->  Bitmap Index Scan on idx_users_age_updated_at
+///         Index Cond: (age >= 0)
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(sql = "SELECT id, email\nFROM public.users\nWHERE age >= #{min_age}")
+)]
+pub async fn list_users_for_maintenance(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    min_age: i32,
+) -> Result<Vec<ListUsersForMaintenanceItem>, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT id, email
+        FROM public.users
+        WHERE age >= $1",
+    );
+    let query = query.bind(min_age);
+    let rows = query.fetch_all(executor).await?;
+    let result: Result<Vec<_>, sqlx::Error> = rows
+        .iter()
+        .map(|row| {
+            Ok(ListUsersForMaintenanceItem {
+                id: row.try_get::<i32, _>("id")?,
+                email: row.try_get::<String, _>("email")?,
+            })
+        })
+        .collect();
+    result.map_err(Into::into)
+}
+
+/// Process the results of `list_users_for_maintenance` in chunks of up to 100 rows ordered by `id`
+/// Stops at the first error returned by the query or by `f`
+pub async fn try_for_each_list_users_for_maintenance<'c, E, F, Fut, FErr>(
+    executor: E,
+    min_age: i32,
+    mut f: F,
+) -> Result<(), FErr>
+where
+    E: sqlx::Executor<'c, Database = sqlx::Postgres> + Copy,
+    F: FnMut(Vec<ListUsersForMaintenanceItem>) -> Fut,
+    Fut: std::future::Future<Output = Result<(), FErr>>,
+    FErr: From<super::ErrorReadOnly>,
+{
+    let mut last_key: Option<i32> = None;
+    loop {
+        let mut query = if last_key.is_none() {
+            sqlx::query(
+                r"SELECT * FROM (
+        SELECT id, email
+        FROM public.users
+        WHERE age >= $1
+        ) AS iterate_chunk
+        ORDER BY iterate_chunk.id
+        LIMIT 100",
+            )
+        } else {
+            sqlx::query(
+                r"SELECT * FROM (
+        SELECT id, email
+        FROM public.users
+        WHERE age >= $1
+        ) AS iterate_chunk
+        WHERE iterate_chunk.id > $2
+        ORDER BY iterate_chunk.id
+        LIMIT 100",
+            )
+        };
+        query = query.bind(min_age);
+        if let Some(key) = last_key {
+            query = query.bind(key);
+        }
+
+        let rows = query
+            .fetch_all(executor)
+            .await
+            .map_err(|e| FErr::from(super::ErrorReadOnly::from(e)))?;
+        let chunk: Result<Vec<ListUsersForMaintenanceItem>, sqlx::Error> = rows
+            .iter()
+            .map(|row| {
+                Ok(ListUsersForMaintenanceItem {
+                    id: row.try_get::<i32, _>("id")?,
+                    email: row.try_get::<String, _>("email")?,
+                })
+            })
+            .collect();
+        let chunk = chunk.map_err(|e| FErr::from(super::ErrorReadOnly::from(e)))?;
+
+        let is_last_chunk = chunk.len() < 100;
+        last_key = chunk.last().map(|item| item.id);
+        if chunk.is_empty() {
+            return Ok(());
+        }
+        f(chunk).await?;
+        if is_last_chunk {
+            return Ok(());
+        }
+    }
+}
+
+/// Process the results of `list_users_for_maintenance` in chunks of up to 100 rows ordered by `id`
+pub async fn for_each_list_users_for_maintenance<'c, E, F, Fut>(
+    executor: E,
+    min_age: i32,
+    mut f: F,
+) -> Result<(), super::ErrorReadOnly>
+where
+    E: sqlx::Executor<'c, Database = sqlx::Postgres> + Copy,
+    F: FnMut(Vec<ListUsersForMaintenanceItem>) -> Fut,
+    Fut: std::future::Future<Output = ()>,
+{
+    try_for_each_list_users_for_maintenance(executor, min_age, |chunk| {
+        let fut = f(chunk);
+        async move {
+            fut.await;
+            Ok::<(), super::ErrorReadOnly>(())
+        }
+    })
+    .await
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FindUsersByKeywordNamesItem {
+    pub id: i32,
+    pub r#type: String,
+    pub r#ref: String,
+}
+
+#[cfg(feature = "dynamic")]
+impl FindUsersByKeywordNamesItem {
+    /// Convert into a map of field names to JSON values
+    pub fn to_map(
+        &self,
+    ) -> Result<std::collections::HashMap<String, serde_json::Value>, serde_json::Error> {
+        let mut map = std::collections::HashMap::new();
+        map.insert("id".to_string(), serde_json::to_value(&self.id)?);
+        map.insert("type".to_string(), serde_json::to_value(&self.r#type)?);
+        map.insert("ref".to_string(), serde_json::to_value(&self.r#ref)?);
+        Ok(map)
+    }
+
+    /// Build from a map of field names to JSON values (missing fields are treated as null)
+    pub fn from_map(
+        mut map: std::collections::HashMap<String, serde_json::Value>,
+    ) -> Result<Self, serde_json::Error> {
+        Ok(Self {
+            id: serde_json::from_value(map.remove("id").unwrap_or(serde_json::Value::Null))?,
+            r#type: serde_json::from_value(map.remove("type").unwrap_or(serde_json::Value::Null))?,
+            r#ref: serde_json::from_value(map.remove("ref").unwrap_or(serde_json::Value::Null))?,
+        })
+    }
+}
+
+/// Find users with parameters and columns named after Rust keywords
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// === find_users_by_keyword_names (base) ===
+/// Bitmap Heap Scan on users
+///   Recheck Cond: (id > 0)
+///   Filter: ((name)::text = 'dummy'::text)
+///   ->  Bitmap Index Scan on users_pkey
+///         Index Cond: (id > 0)
+///
+/// === find_users_by_keyword_names (variant 1) ===
+/// Index Scan using users_email_key on users
+///   Index Cond: ((email)::text = 'dummy'::text)
+///   Filter: ((id > 0) AND ((name)::text = 'dummy'::text))
+#[tracing::instrument(
+    level = "trace",
+    skip_all,
+    fields(
+        match = %super::telemetry::param(&r#match, Some(256)),
+        sql = "SELECT id, name AS type, email AS ref\nFROM public.users\nWHERE name = #{type}\n  AND id > #{match}\n#[AND email = #{ref?}]",
+    )
+)]
+pub async fn find_users_by_keyword_names(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    r#type: String,
+    r#match: i32,
+    r#ref: Option<String>,
+) -> Result<Vec<FindUsersByKeywordNamesItem>, super::ErrorReadOnly> {
+    let mut included_params: Vec<&str> = Vec::new();
+    if r#ref.is_some() {
+        included_params.push("ref");
+    }
+    let final_sql = find_users_by_keyword_names_sql(&included_params);
+
+    let mut query = sqlx::query(&final_sql);
+
+    query = query.bind(&r#type);
+    query = query.bind(&r#match);
+    if included_params.contains(&r"ref") {
+        query = query.bind(r#ref.as_ref().unwrap());
+    }
+
+    let rows = query.fetch_all(executor).await?;
+    let result: Result<Vec<_>, sqlx::Error> = rows
+        .iter()
+        .map(|row| {
+            Ok(FindUsersByKeywordNamesItem {
+                id: row.try_get::<i32, _>("id")?,
+                r#type: row.try_get::<String, _>("type")?,
+                r#ref: row.try_get::<String, _>("ref")?,
+            })
+        })
+        .collect();
+    result.map_err(Into::into)
+}
+
+/// Assemble the SQL of `find_users_by_keyword_names` for the given included conditional parameters
+fn find_users_by_keyword_names_sql(included_params: &[&str]) -> String {
+    let mut final_sql = r"SELECT id, name AS type, email AS ref
+FROM public.users
+WHERE name = #{type}
+  AND id > #{match}
+#[AND email = #{ref?}]"
+        .to_string();
+
+    if included_params.contains(&"ref") {
+        final_sql = final_sql.replacen(r"#[AND email = #{ref?}]", r"AND email = #{ref?}", 1);
+    } else {
+        final_sql = final_sql.replacen(r"#[AND email = #{ref?}]", "", 1);
+    }
+
+    #[allow(unused_assignments)]
+    let mut param_counter = 1;
+    final_sql = final_sql.replace(r"#{type}", &format!("${}", param_counter));
+    param_counter += 1;
+    final_sql = final_sql.replace(r"#{match}", &format!("${}", param_counter));
+    param_counter += 1;
+    if included_params.contains(&"ref") {
+        final_sql = final_sql.replace(r"#{ref?}", &format!("${}", param_counter));
+        param_counter += 1;
+    }
+    let _ = param_counter; // Suppress unused assignment warning
+    final_sql
+}
+
+/// Constraint violations specific to this query
+#[derive(Debug)]
+pub enum UpdateUserKeywordNamesDiffConstraints {
+    /// Constraint: users_email_key on table users
+    UsersEmailKey,
+    /// Constraint: users_pkey on table users
+    UsersPkey,
+    /// Constraint: users_referrer_id_fkey on table users
+    UsersReferrerIdFkey,
+    /// Constraint: users_id_not_null on table users
+    UsersIdNotNull,
+    /// Constraint: users_name_not_null on table users
+    UsersNameNotNull,
+    /// Constraint: users_email_not_null on table users
+    UsersEmailNotNull,
+}
+
+impl TryFrom<super::ErrorConstraintInfo> for UpdateUserKeywordNamesDiffConstraints {
+    type Error = ();
+
+    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
+        match info.constraint_name.as_str() {
+            "users_email_key" => Ok(Self::UsersEmailKey),
+            "users_pkey" => Ok(Self::UsersPkey),
+            "users_referrer_id_fkey" => Ok(Self::UsersReferrerIdFkey),
+            "users_id_not_null" => Ok(Self::UsersIdNotNull),
+            "users_name_not_null" => Ok(Self::UsersNameNotNull),
+            "users_email_not_null" => Ok(Self::UsersEmailNotNull),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct UpdateUserKeywordNamesDiffParams {
+    pub r#type: String,
+    pub r#ref: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct UpdateUserKeywordNamesDiffItem {
+    pub id: i32,
+    pub r#type: String,
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Diff-based update with parameters named after Rust keywords
+///
+/// Tables: `users`
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "UPDATE public.users\nSET updated_at = NOW()\n#[, name = #{type?}]\n#[, email = #{ref?}]\nWHERE id = #{match}\nRETURNING id, name AS type, updated_at"
+    )
+)]
+pub async fn update_user_keyword_names_diff(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    old: &UpdateUserKeywordNamesDiffParams,
+    new: &UpdateUserKeywordNamesDiffParams,
+    r#match: i32,
+) -> Result<UpdateUserKeywordNamesDiffItem, super::Error<UpdateUserKeywordNamesDiffConstraints>> {
+    let mut included_params: Vec<&str> = Vec::new();
+    if old.r#type != new.r#type {
+        included_params.push("type");
+    }
+    if old.r#ref != new.r#ref {
+        included_params.push("ref");
+    }
+    let final_sql = update_user_keyword_names_diff_sql(&included_params);
+
+    let mut query = sqlx::query(&final_sql);
+
+    if included_params.contains(&r"type") {
+        query = query.bind(&new.r#type);
+    }
+    if included_params.contains(&r"ref") {
+        query = query.bind(&new.r#ref);
+    }
+    query = query.bind(&r#match);
+
+    let row = query.fetch_one(executor).await?;
+    let result: Result<_, sqlx::Error> = (|| {
+        Ok(UpdateUserKeywordNamesDiffItem {
+            id: row.try_get::<i32, _>("id")?,
+            r#type: row.try_get::<String, _>("type")?,
+            updated_at: row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("updated_at")?,
+        })
+    })();
+    result.map_err(Into::into)
+}
+
+/// Assemble the SQL of `update_user_keyword_names_diff` for the given included conditional parameters
+fn update_user_keyword_names_diff_sql(included_params: &[&str]) -> String {
+    let mut final_sql = r"UPDATE public.users
+SET updated_at = NOW()
+#[, name = #{type?}]
+#[, email = #{ref?}]
+WHERE id = #{match}
+RETURNING id, name AS type, updated_at"
+        .to_string();
+
+    if included_params.contains(&"type") {
+        final_sql = final_sql.replacen(r"#[, name = #{type?}]", r", name = #{type?}", 1);
+    } else {
+        final_sql = final_sql.replacen(r"#[, name = #{type?}]", "", 1);
+    }
+    if included_params.contains(&"ref") {
+        final_sql = final_sql.replacen(r"#[, email = #{ref?}]", r", email = #{ref?}", 1);
+    } else {
+        final_sql = final_sql.replacen(r"#[, email = #{ref?}]", "", 1);
+    }
+
+    #[allow(unused_assignments)]
+    let mut param_counter = 1;
+    if included_params.contains(&"type") {
+        final_sql = final_sql.replace(r"#{type?}", &format!("${}", param_counter));
+        param_counter += 1;
+    }
+    if included_params.contains(&"ref") {
+        final_sql = final_sql.replace(r"#{ref?}", &format!("${}", param_counter));
+        param_counter += 1;
+    }
+    final_sql = final_sql.replace(r"#{match}", &format!("${}", param_counter));
+    param_counter += 1;
+    let _ = param_counter; // Suppress unused assignment warning
+    final_sql
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GetUsersByIdsItem {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+}
+
+/// Get users by a list of ids which may contain thousands of entries, joined through a temp table
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Index Scan using users_pkey on users
+///   Index Cond: (id = ANY ('{}'::integer[]))
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(sql = "SELECT id, name, email\nFROM public.users\nWHERE id = ANY(#{ids})\nORDER BY id")
+)]
+pub async fn get_users_by_ids(
+    session: &mut super::Session,
+    ids: Vec<i32>,
+) -> Result<Vec<GetUsersByIdsItem>, super::ErrorReadOnly> {
+    let executor = session.connection();
+    let mut tx = sqlx::Connection::begin(executor).await?;
+    sqlx::query(r"CREATE TEMP TABLE automodel_list_ids (value int4)")
+        .execute(&mut *tx)
+        .await?;
+    let mut copy = tx
+        .copy_in_raw(r"COPY automodel_list_ids (value) FROM STDIN")
+        .await?;
+    copy.send(super::large_list::copy_data(&ids)).await?;
+    copy.finish().await?;
+    sqlx::query(r"ANALYZE automodel_list_ids")
+        .execute(&mut *tx)
+        .await?;
+
+    let result: Result<Vec<GetUsersByIdsItem>, super::ErrorReadOnly> = async {
+        let executor = &mut *tx;
+        let query = sqlx::query(
+            r"SELECT id, name, email
+            FROM public.users
+            WHERE id IN (SELECT value FROM automodel_list_ids)
+            ORDER BY id",
+        );
+        let rows = query.fetch_all(executor).await?;
+        let result: Result<Vec<_>, sqlx::Error> = rows
+            .iter()
+            .map(|row| {
+                Ok(GetUsersByIdsItem {
+                    id: row.try_get::<i32, _>("id")?,
+                    name: row.try_get::<String, _>("name")?,
+                    email: row.try_get::<String, _>("email")?,
+                })
+            })
+            .collect();
+        result.map_err(Into::into)
+    }
+    .await;
+    let result = result?;
+
+    sqlx::query(r"DROP TABLE automodel_list_ids")
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(result)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GetPublicUserProfileItem {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+}
+
+/// Get a user for public listings, with the email address redacted by a row mapper
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Index Scan using users_pkey on users
+///   Index Cond: (id = 0)
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(sql = "SELECT id, name, email\nFROM public.users\nWHERE id = #{id}")
+)]
+pub async fn get_public_user_profile(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    id: i32,
+) -> Result<Option<GetPublicUserProfileItem>, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT id, name, email
+        FROM public.users
+        WHERE id = $1",
+    );
+    let query = query.bind(id);
+    let row = query.fetch_optional(executor).await?;
+    match row {
+        Some(row) => {
+            let result: Result<_, sqlx::Error> = (|| {
+                Ok(crate::mappers::redact_email(GetPublicUserProfileItem {
+                    id: row.try_get::<i32, _>("id")?,
+                    name: row.try_get::<String, _>("name")?,
+                    email: row.try_get::<String, _>("email")?,
+                }))
+            })();
+            result.map(Some).map_err(Into::into)
+        }
+        None => Ok(None),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FindUsersMatchingTextItem {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+    pub age: Option<i32>,
+}
+
+/// Find users whose name or email contains the text, optionally within five years of an age
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// === find_users_matching_text (base) ===
+/// Index Scan using users_pkey on users
+///   Filter: (((name)::text ~~* '%dummy%'::text) OR ((email)::text ~~* '%dummy%'::text))
+///
+/// === find_users_matching_text (variant 1) ===
+/// Sort
+///   Sort Key: id
+///   ->  Index Scan using idx_users_age_updated_at on users
+///         Index Cond: ((age >= '-5'::integer) AND (age <= 5))
+///         Filter: (((name)::text ~~* '%dummy%'::text) OR ((email)::text ~~* '%dummy%'::text))
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "SELECT id, name, email, age\nFROM public.users\nWHERE (name ILIKE '%' || #{text} || '%' OR email ILIKE '%' || #{text} || '%')\n#[AND age BETWEEN #{around_age?} - 5 AND #{around_age?} + 5]\nORDER BY id"
+    )
+)]
+pub async fn find_users_matching_text(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    text: String,
+    around_age: Option<i32>,
+) -> Result<Vec<FindUsersMatchingTextItem>, super::ErrorReadOnly> {
+    let mut included_params: Vec<&str> = Vec::new();
+    if around_age.is_some() {
+        included_params.push("around_age");
+    }
+    let final_sql = find_users_matching_text_sql(&included_params);
+
+    let mut query = sqlx::query(&final_sql);
+
+    query = query.bind(&text);
+    if included_params.contains(&r"around_age") {
+        query = query.bind(around_age.as_ref().unwrap());
+    }
+
+    let rows = query.fetch_all(executor).await?;
+    let result: Result<Vec<_>, sqlx::Error> = rows
+        .iter()
+        .map(|row| {
+            Ok(FindUsersMatchingTextItem {
+                id: row.try_get::<i32, _>("id")?,
+                name: row.try_get::<String, _>("name")?,
+                email: row.try_get::<String, _>("email")?,
+                age: row.try_get::<Option<i32>, _>("age")?,
+            })
+        })
+        .collect();
+    result.map_err(Into::into)
+}
+
+/// Assemble the SQL of `find_users_matching_text` for the given included conditional parameters
+fn find_users_matching_text_sql(included_params: &[&str]) -> String {
+    let mut final_sql = r"SELECT id, name, email, age
+FROM public.users
+WHERE (name ILIKE '%' || #{text} || '%' OR email ILIKE '%' || #{text} || '%')
+#[AND age BETWEEN #{around_age?} - 5 AND #{around_age?} + 5]
+ORDER BY id"
+        .to_string();
+
+    if included_params.contains(&"around_age") {
+        final_sql = final_sql.replacen(
+            r"#[AND age BETWEEN #{around_age?} - 5 AND #{around_age?} + 5]",
+            r"AND age BETWEEN #{around_age?} - 5 AND #{around_age?} + 5",
+            1,
+        );
+    } else {
+        final_sql = final_sql.replacen(
+            r"#[AND age BETWEEN #{around_age?} - 5 AND #{around_age?} + 5]",
+            "",
+            1,
+        );
+    }
+
+    #[allow(unused_assignments)]
+    let mut param_counter = 1;
+    final_sql = final_sql.replace(r"#{text}", &format!("${}", param_counter));
+    param_counter += 1;
+    if included_params.contains(&"around_age") {
+        final_sql = final_sql.replace(r"#{around_age?}", &format!("${}", param_counter));
+        param_counter += 1;
+    }
+    let _ = param_counter; // Suppress unused assignment warning
+    final_sql
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GetOrCreateUserByEmailItem {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+    pub age: Option<i32>,
+}
+
+/// Look up the existing row of `get_or_create_user_by_email`
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Index Scan using users_email_key on users
+///   Index Cond: ((email)::text = 'dummy'::text)
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(sql = "SELECT id, name, email, age\nFROM public.users\nWHERE email = #{email}")
+)]
+async fn get_or_create_user_by_email_lookup(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    email: String,
+) -> Result<Option<GetOrCreateUserByEmailItem>, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT id, name, email, age
+        FROM public.users
+        WHERE email = $1",
+    );
+    let query = query.bind(&email);
+    let row = query.fetch_optional(executor).await?;
+    match row {
+        Some(row) => {
+            let result: Result<_, sqlx::Error> = (|| {
+                Ok(GetOrCreateUserByEmailItem {
+                    id: row.try_get::<i32, _>("id")?,
+                    name: row.try_get::<String, _>("name")?,
+                    email: row.try_get::<String, _>("email")?,
+                    age: row.try_get::<Option<i32>, _>("age")?,
+                })
+            })();
+            result.map(Some).map_err(Into::into)
+        }
+        None => Ok(None),
+    }
+}
+
+/// Get the user with the email, creating it when no such user exists
+///
+/// Returns the existing row, or inserts it with `insert_user` and returns the inserted row
+/// Runs in a transaction, a row inserted concurrently by another transaction is returned
+pub async fn get_or_create_user_by_email(
+    executor: &mut sqlx::PgConnection,
+    email: String,
+    name: String,
+    age: i32,
+    profile: UserProfile,
+) -> Result<GetOrCreateUserByEmailItem, super::Error<InsertUserConstraints>> {
+    let mut tx = sqlx::Connection::begin(executor).await?;
+    if let Some(row) = get_or_create_user_by_email_lookup(&mut *tx, email.clone()).await? {
+        tx.commit().await?;
+        return Ok(row);
+    }
+
+    // A unique violation means another transaction inserted the row in the meantime,
+    // rolling back to the savepoint keeps the transaction usable for looking it up
+    let mut savepoint = sqlx::Connection::begin(&mut *tx).await?;
+    match insert_user(&mut *savepoint, name, email.clone(), age, profile).await {
+        Ok(_) => savepoint.commit().await?,
+        Err(super::Error::ConstraintViolation(_, info))
+            if info.kind == super::ErrorConstraintKind::UniqueViolation =>
+        {
+            savepoint.rollback().await?
+        }
+        Err(e) => return Err(e),
+    }
+
+    let row = get_or_create_user_by_email_lookup(&mut *tx, email)
+        .await?
+        .ok_or(super::Error::RowNotFound)?;
+    tx.commit().await?;
+    Ok(row)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FindUsersWithSimilarNameItem {
+    pub id: i32,
+    pub name: String,
+    pub distance: Option<i32>,
+}
+
+/// Find users whose name is within an edit distance of the given name
+///
+/// Requires the search_path of the connection to be `public, extensions`
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Sort
+///   Sort Key: (levenshtein((name)::text, 'dummy'::text)), id
+///   ->  Seq Scan on users
+///         Filter: (levenshtein((name)::text, 'dummy'::text) <= 0)
+/// JIT:
+///   Functions: 4
+///   Options: Inlining true, Optimization true, Expressions true, Deforming true
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "SELECT id, name, levenshtein(name, #{name}) AS distance\nFROM users\nWHERE levenshtein(name, #{name}) <= #{max_distance}\nORDER BY distance, id"
+    )
+)]
+pub async fn find_users_with_similar_name(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    name: String,
+    max_distance: i32,
+) -> Result<Vec<FindUsersWithSimilarNameItem>, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT id, name, levenshtein(name, $1) AS distance
+        FROM users
+        WHERE levenshtein(name, $1) <= $2
+        ORDER BY distance, id",
+    );
+    let query = query.bind(&name);
+    let query = query.bind(max_distance);
+    let rows = query.fetch_all(executor).await?;
+    let result: Result<Vec<_>, sqlx::Error> = rows
+        .iter()
+        .map(|row| {
+            Ok(FindUsersWithSimilarNameItem {
+                id: row.try_get::<i32, _>("id")?,
+                name: row.try_get::<String, _>("name")?,
+                distance: row.try_get::<Option<i32>, _>("distance")?,
+            })
+        })
+        .collect();
+    result.map_err(Into::into)
+}
+
+/// Constraint violations specific to this query
+#[derive(Debug)]
+pub enum DeleteUserConstraints {
+    /// Constraint: users_email_key on table users
+    UsersEmailKey,
+    /// Constraint: users_pkey on table users
+    UsersPkey,
+    /// Constraint: users_referrer_id_fkey on table users
+    UsersReferrerIdFkey,
+    /// Constraint: users_id_not_null on table users
+    UsersIdNotNull,
+    /// Constraint: users_name_not_null on table users
+    UsersNameNotNull,
+    /// Constraint: users_email_not_null on table users
+    UsersEmailNotNull,
+}
+
+impl TryFrom<super::ErrorConstraintInfo> for DeleteUserConstraints {
+    type Error = ();
+
+    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
+        match info.constraint_name.as_str() {
+            "users_email_key" => Ok(Self::UsersEmailKey),
+            "users_pkey" => Ok(Self::UsersPkey),
+            "users_referrer_id_fkey" => Ok(Self::UsersReferrerIdFkey),
+            "users_id_not_null" => Ok(Self::UsersIdNotNull),
+            "users_name_not_null" => Ok(Self::UsersNameNotNull),
+            "users_email_not_null" => Ok(Self::UsersEmailNotNull),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Delete a user, with a preview of the posts, comments and users referencing it
+///
+/// Tables: `users`
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(sql = "DELETE FROM public.users\nWHERE id = #{id}")
+)]
+pub async fn delete_user(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    id: i32,
+) -> Result<(), super::Error<DeleteUserConstraints>> {
+    let query = sqlx::query(
+        r"DELETE FROM public.users
+        WHERE id = $1",
+    );
+    let query = query.bind(id);
+    query.execute(executor).await?;
+    Ok(())
+}
+
+/// Count the rows `delete_user` would delete and the rows referencing them, without deleting anything
+/// Referencing rows are counted per foreign key, following ON DELETE CASCADE foreign keys
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "WITH automodel_deleted AS (\n    SELECT * FROM public.users WHERE id = #{id}\n),\nautomodel_referencing_1 AS (\n    SELECT * FROM public.comments WHERE (author_id) IN (SELECT id FROM automodel_deleted)\n),\nautomodel_referencing_2 AS (\n    SELECT * FROM public.posts WHERE (author_id) IN (SELECT id FROM automodel_deleted)\n),\nautomodel_referencing_3 AS (\n    SELECT * FROM public.users WHERE (referrer_id) IN (SELECT id FROM automodel_deleted)\n)\nSELECT\n    (SELECT count(*) FROM automodel_deleted),\n    (SELECT count(*) FROM automodel_referencing_1),\n    (SELECT count(*) FROM automodel_referencing_2),\n    (SELECT count(*) FROM automodel_referencing_3)"
+    )
+)]
+pub async fn delete_user_preview(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    id: i32,
+) -> Result<super::CascadeImpact, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"WITH automodel_deleted AS (
+            SELECT * FROM public.users WHERE id = $1
+        ),
+        automodel_referencing_1 AS (
+            SELECT * FROM public.comments WHERE (author_id) IN (SELECT id FROM automodel_deleted)
+        ),
+        automodel_referencing_2 AS (
+            SELECT * FROM public.posts WHERE (author_id) IN (SELECT id FROM automodel_deleted)
+        ),
+        automodel_referencing_3 AS (
+            SELECT * FROM public.users WHERE (referrer_id) IN (SELECT id FROM automodel_deleted)
+        )
+        SELECT
+            (SELECT count(*) FROM automodel_deleted),
+            (SELECT count(*) FROM automodel_referencing_1),
+            (SELECT count(*) FROM automodel_referencing_2),
+            (SELECT count(*) FROM automodel_referencing_3)",
+    );
+    let query = query.bind(id);
+    let row = query.fetch_one(executor).await?;
+    Ok(super::CascadeImpact {
+        deleted: sqlx::Row::try_get(&row, 0)?,
+        referencing: vec![
+            super::ReferencingRows {
+                table: "public.comments",
+                constraint: "comments_author_id_fkey",
+                on_delete: super::OnDelete::NoAction,
+                rows: sqlx::Row::try_get(&row, 1)?,
+            },
+            super::ReferencingRows {
+                table: "public.posts",
+                constraint: "posts_author_id_fkey",
+                on_delete: super::OnDelete::NoAction,
+                rows: sqlx::Row::try_get(&row, 2)?,
+            },
+            super::ReferencingRows {
+                table: "public.users",
+                constraint: "users_referrer_id_fkey",
+                on_delete: super::OnDelete::NoAction,
+                rows: sqlx::Row::try_get(&row, 3)?,
+            },
+        ],
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FindUsersByThemeItem {
+    pub id: i32,
+    pub name: String,
+}
+
+/// Find users whose profile preferences use the given theme
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Index Scan using users_pkey on users
+///   Filter: (profile @> jsonb_build_object('preferences', jsonb_build_object('theme', 'dummy'::text)))
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "SELECT id, name\nFROM public.users\nWHERE profile @> jsonb_build_object('preferences', jsonb_build_object('theme', #{theme}::text))\nORDER BY id"
+    )
+)]
+pub async fn find_users_by_theme(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    theme: String,
+) -> Result<Vec<FindUsersByThemeItem>, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT id, name
+        FROM public.users
+        WHERE profile @> jsonb_build_object('preferences', jsonb_build_object('theme', $1::text))
+        ORDER BY id",
+    );
+    let query = query.bind(&theme);
+    let rows = query.fetch_all(executor).await?;
+    let result: Result<Vec<_>, sqlx::Error> = rows
+        .iter()
+        .map(|row| {
+            Ok(FindUsersByThemeItem {
+                id: row.try_get::<i32, _>("id")?,
+                name: row.try_get::<String, _>("name")?,
+            })
+        })
+        .collect();
+    result.map_err(Into::into)
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct GetUserProfileCardsItem {
+    pub id: i32,
+    pub name: String,
+    #[serde(flatten)]
+    pub profile: Option<UserProfile>,
+}
+
+/// Get users with their profile fields inlined, for serializing as flat JSON objects
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Limit
+///   ->  Index Scan using users_pkey on users
+///         Filter: (profile IS NOT NULL)
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "SELECT id, name, profile\nFROM public.users\nWHERE profile IS NOT NULL\nORDER BY id\nLIMIT #{limit}"
+    )
+)]
+pub async fn get_user_profile_cards(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    limit: i64,
+) -> Result<Vec<GetUserProfileCardsItem>, super::ErrorReadOnly> {
+    let query = sqlx::query(
+        r"SELECT id, name, profile
+        FROM public.users
+        WHERE profile IS NOT NULL
+        ORDER BY id
+        LIMIT $1",
+    );
+    let query = query.bind(limit);
+    let rows = query.fetch_all(executor).await?;
+    let result: Result<Vec<_>, sqlx::Error> = rows
+        .iter()
+        .map(|row| {
+            Ok(GetUserProfileCardsItem {
+                id: row.try_get::<i32, _>("id")?,
+                name: row.try_get::<String, _>("name")?,
+                profile: row
+                    .try_get::<Option<serde_json::Value>, _>("profile")?
+                    .map(|v| {
+                        serde_json::from_value::<UserProfile>(v)
+                            .map_err(|e| sqlx::Error::Decode(Box::new(e)))
+                    })
+                    .transpose()?,
+            })
+        })
+        .collect();
+    result.map_err(Into::into)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FilterUsersItem {
+    pub id: i32,
+    pub name: String,
+    pub email: String,
+    pub age: Option<i32>,
+}
+
+/// Filter of `filter_users`, the given filters are combined with AND
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum UserFilter {
+    /// `email = #{email}`
+    EmailEq(String),
+    /// `name ILIKE #{name_pattern}`
+    NameILike(String),
+    /// `age >= #{min_age}`
+    AgeGte(i32),
+    /// `age <= #{max_age}`
+    AgeLte(i32),
+    /// `status = ANY(#{statuses})`
+    StatusIn(Vec<UserStatus>),
+}
+
+impl UserFilter {
+    /// SQL of the condition, comparing the column with the positional parameter `index`
+    fn condition(&self, index: usize) -> String {
+        match self {
+            Self::EmailEq(_) => format!("email = ${}", index),
+            Self::NameILike(_) => format!("name ILIKE ${}", index),
+            Self::AgeGte(_) => format!("age >= ${}", index),
+            Self::AgeLte(_) => format!("age <= ${}", index),
+            Self::StatusIn(_) => format!("status = ANY(${})", index),
+        }
+    }
+}
+
+/// List public.users matching the given filters, e.g. by email or age
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Limit
+///   ->  Sort
+///         Sort Key: id
+///         ->  Index Scan using idx_users_age on users
+///               Index Cond: ((age >= 0) AND (age <= 0))
+///               Filter: ((status = ANY ('{}'::public.user_status[])) AND ((name)::text ~~* 'dummy'::text) AND ((email)::text = 'dummy'::text))
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "SELECT id, name, email, age\nFROM public.users\nWHERE email = #{email}\n  AND name ILIKE #{name_pattern}\n  AND age >= #{min_age}\n  AND age <= #{max_age}\n  AND status = ANY(#{statuses})\nORDER BY id\nLIMIT #{limit}"
+    )
+)]
+pub async fn filter_users(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    filters: Vec<UserFilter>,
+    limit: i64,
+) -> Result<Vec<FilterUsersItem>, super::ErrorReadOnly> {
+    let mut final_sql = String::from(
+        r"SELECT id, name, email, age
+        FROM public.users",
+    );
+    for (index, filter) in filters.iter().enumerate() {
+        final_sql.push_str(if index == 0 { "\nWHERE " } else { "\n  AND " });
+        final_sql.push_str(&filter.condition(index + 1));
+    }
+    final_sql.push_str(&format!("\nORDER BY id\nLIMIT ${0}", filters.len() + 1));
+
+    let mut query = sqlx::query(&final_sql);
+    for filter in filters {
+        query = match filter {
+            UserFilter::EmailEq(value) => query.bind(value),
+            UserFilter::NameILike(value) => query.bind(value),
+            UserFilter::AgeGte(value) => query.bind(value),
+            UserFilter::AgeLte(value) => query.bind(value),
+            UserFilter::StatusIn(value) => query.bind(value),
+        };
+    }
+    query = query.bind(limit);
+
+    let rows = query.fetch_all(executor).await?;
+    let result: Result<Vec<_>, sqlx::Error> = rows
+        .iter()
+        .map(|row| {
+            Ok(FilterUsersItem {
+                id: row.try_get::<i32, _>("id")?,
+                name: row.try_get::<String, _>("name")?,
+                email: row.try_get::<String, _>("email")?,
+                age: row.try_get::<Option<i32>, _>("age")?,
+            })
+        })
+        .collect();
+    result.map_err(Into::into)
+}
+
+/// Constraint violations specific to this query
+#[derive(Debug)]
+pub enum UpdateUserAgesConstraints {
+    /// Constraint: users_email_key on table users
+    UsersEmailKey,
+    /// Constraint: users_pkey on table users
+    UsersPkey,
+    /// Constraint: users_referrer_id_fkey on table users
+    UsersReferrerIdFkey,
+    /// Constraint: users_id_not_null on table users
+    UsersIdNotNull,
+    /// Constraint: users_name_not_null on table users
+    UsersNameNotNull,
+    /// Constraint: users_email_not_null on table users
+    UsersEmailNotNull,
+}
+
+impl TryFrom<super::ErrorConstraintInfo> for UpdateUserAgesConstraints {
+    type Error = ();
+
+    fn try_from(info: super::ErrorConstraintInfo) -> Result<Self, Self::Error> {
+        match info.constraint_name.as_str() {
+            "users_email_key" => Ok(Self::UsersEmailKey),
+            "users_pkey" => Ok(Self::UsersPkey),
+            "users_referrer_id_fkey" => Ok(Self::UsersReferrerIdFkey),
+            "users_id_not_null" => Ok(Self::UsersIdNotNull),
+            "users_name_not_null" => Ok(Self::UsersNameNotNull),
+            "users_email_not_null" => Ok(Self::UsersEmailNotNull),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct UpdateUserAgesItem {
+    pub id: i32,
+    pub age: Option<i32>,
+    pub status: Option<UserStatus>,
+}
+
+/// Update the age and status of a user, update_user_ages_bulk updates many users at once
+///
+/// Tables: `users`
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "UPDATE public.users\nSET age = #{age}, status = #{status}, updated_at = NOW()\nWHERE id = #{id}\nRETURNING id, age, status"
+    )
+)]
+pub async fn update_user_ages(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    age: i32,
+    status: UserStatus,
+    id: i32,
+) -> Result<Option<UpdateUserAgesItem>, super::Error<UpdateUserAgesConstraints>> {
+    let query = sqlx::query(
+        r"UPDATE public.users
+        SET age = $1, status = $2, updated_at = NOW()
+        WHERE id = $3
+        RETURNING id, age, status",
+    );
+    let query = query.bind(age);
+    let query = query.bind(status);
+    let query = query.bind(id);
+    let row = query.fetch_optional(executor).await?;
+    match row {
+        Some(row) => {
+            let result: Result<_, sqlx::Error> = (|| {
+                Ok(UpdateUserAgesItem {
+                    id: row.try_get::<i32, _>("id")?,
+                    age: row.try_get::<Option<i32>, _>("age")?,
+                    status: row.try_get::<Option<UserStatus>, _>("status")?,
+                })
+            })();
+            result.map(Some).map_err(Into::into)
+        }
+        None => Ok(None),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct UpdateUserAgesBulkRecord {
+    pub age: i32,
+    pub status: UserStatus,
+    pub id: i32,
+}
+
+/// Same as [`update_user_ages`] for a list of records, updating the row of every record with one statement
+///
+/// Tables: `users`
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "UPDATE public.users\nSET age = bulk.bulk_age, status = bulk.bulk_status, updated_at = NOW()\nFROM UNNEST(#{age}::integer[], #{status}::public.user_status[], #{id}::integer[]) AS bulk(bulk_age, bulk_status, bulk_id)\nWHERE id = bulk.bulk_id\nRETURNING id, age, status"
+    )
+)]
+pub async fn update_user_ages_bulk(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    items: Vec<UpdateUserAgesBulkRecord>,
+) -> Result<Vec<UpdateUserAgesItem>, super::Error<UpdateUserAgesConstraints>> {
+    use itertools::Itertools;
+    let query = sqlx::query(
+        r"UPDATE public.users
+        SET age = bulk.bulk_age, status = bulk.bulk_status, updated_at = NOW()
+        FROM UNNEST($1::integer[], $2::public.user_status[], $3::integer[]) AS bulk(bulk_age, bulk_status, bulk_id)
+        WHERE id = bulk.bulk_id
+        RETURNING id, age, status",
+    );
+    let (age, status, id): (Vec<_>, Vec<_>, Vec<_>) = items
+        .into_iter()
+        .map(|item| (item.age, item.status, item.id))
+        .multiunzip();
+    let query = query.bind(age);
+    let query = query.bind(status);
+    let query = query.bind(id);
+    let rows = query.fetch_all(executor).await?;
+    let result: Result<Vec<_>, sqlx::Error> = rows
+        .iter()
+        .map(|row| {
+            Ok(UpdateUserAgesItem {
+                id: row.try_get::<i32, _>("id")?,
+                age: row.try_get::<Option<i32>, _>("age")?,
+                status: row.try_get::<Option<UserStatus>, _>("status")?,
+            })
+        })
+        .collect();
+    result.map_err(Into::into)
+}
+
+/// Count users whose name is within an edit distance of the given name
+///
+/// Runs in a transaction with `SET LOCAL search_path TO public, extensions`
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Aggregate
+///   ->  Seq Scan on users
+///         Filter: (levenshtein((name)::text, 'dummy'::text) <= 0)
+/// JIT:
+///   Functions: 4
+///   Options: Inlining true, Optimization true, Expressions true, Deforming true
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        sql = "SELECT count(*) AS total\nFROM users\nWHERE levenshtein(name, #{name}) <= #{max_distance}"
+    )
+)]
+pub async fn count_users_with_similar_name(
+    executor: impl sqlx::Acquire<'_, Database = sqlx::Postgres>,
+    name: String,
+    max_distance: i32,
+) -> Result<Option<i64>, super::ErrorReadOnly> {
+    let mut tx = executor.begin().await?;
+    let search_path: String = sqlx::query_scalar("SELECT current_setting('search_path')")
+        .fetch_one(&mut *tx)
+        .await?;
+    sqlx::query("SET LOCAL search_path TO \"public\", \"extensions\"")
+        .execute(&mut *tx)
+        .await?;
+    let executor = &mut *tx;
+    let result: Result<Option<i64>, super::ErrorReadOnly> = async move {
+        let query = sqlx::query(
+            r"SELECT count(*) AS total
+        FROM users
+        WHERE levenshtein(name, $1) <= $2",
+        );
+        let query = query.bind(&name);
+        let query = query.bind(max_distance);
+        let row = query.fetch_one(executor).await?;
+        Ok(row.try_get::<Option<i64>, _>("total")?)
+    }
+    .await;
+    if result.is_ok() {
+        sqlx::query("SELECT set_config('search_path', $1, true)")
+            .bind(search_path)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+    }
+    result
+}
+
+/// Get the name of a user, cached until a notification on user_events names the user
+///
+/// Tables: `users`
+///
+/// Query Plan:
+/// Index Scan using users_pkey on users
+///   Index Cond: (id = 0)
+#[tracing::instrument(
+    level = "debug",
+    skip_all,
+    fields(sql = "SELECT name FROM public.users WHERE id = #{id}")
+)]
+pub async fn get_user_name(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    id: i32,
+) -> Result<Option<String>, super::ErrorReadOnly> {
+    let query = sqlx::query(r"SELECT name FROM public.users WHERE id = $1");
+    let query = query.bind(id);
+    let row = query.fetch_optional(executor).await?;
+    match row {
+        Some(row) => Ok(Some(row.try_get::<String, _>("name")?)),
+        None => Ok(None),
+    }
+}
+
+/// Results of [`get_user_name`] cached by [`get_user_name_cached`]
+static GET_USER_NAME_CACHE: super::cache_invalidation::QueryCache<(i32,), Option<String>> =
+    super::cache_invalidation::QueryCache::new("user_events", &["users"]);
+
+/// Cached version of [`get_user_name`], returning the result of an earlier call with the same
+/// arguments until a notification on `user_events` with the `id` of the call as payload clears it
+/// Cached results are only cleared while the task of `cache_invalidation::spawn` runs
+/// Changes of the tables it reads notified by the cache invalidation triggers clear every result
+/// The cache is unbounded, it keeps the result of every distinct arguments
+pub async fn get_user_name_cached(
+    executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+    id: i32,
+) -> Result<Option<String>, super::ErrorReadOnly> {
+    let key = (id,);
+    if let Some(value) = GET_USER_NAME_CACHE.get(&key) {
+        return Ok(value);
+    }
+    let generation = GET_USER_NAME_CACHE.generation();
+    let value = get_user_name(executor, id).await?;
+    let invalidation_key = Some(key.0.to_string());
+    GET_USER_NAME_CACHE.insert(key, invalidation_key, generation, value.clone());
+    Ok(value)
+}
+
+#[cfg(test)]
+mod conditional_variant_tests {
+    use super::*;
+
+    fn normalize_sql(sql: &str) -> String {
+        sql.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    #[test]
+    fn find_users_by_keyword_names_sql_variants() {
+        assert_eq!(
+            normalize_sql(&find_users_by_keyword_names_sql(&[])),
+            normalize_sql(
+                r"SELECT id, name AS type, email AS ref
+FROM public.users
+WHERE name = $1
+ AND id > $2"
+            ),
+            "find_users_by_keyword_names (base)"
+        );
+        assert_eq!(
+            normalize_sql(&find_users_by_keyword_names_sql(&["ref"])),
+            normalize_sql(
+                r"SELECT id, name AS type, email AS ref
+FROM public.users
+WHERE name = $1
+ AND id > $2
+AND email = $3"
+            ),
+            "find_users_by_keyword_names (variant 1)"
+        );
+    }
+
+    #[test]
+    fn update_user_keyword_names_diff_sql_variants() {
+        assert_eq!(
+            normalize_sql(&update_user_keyword_names_diff_sql(&[])),
+            normalize_sql(
+                r"UPDATE public.users
+SET updated_at = NOW()
+
+
+WHERE id = $1
+RETURNING id, name AS type, updated_at"
+            ),
+            "update_user_keyword_names_diff (base)"
+        );
+        assert_eq!(
+            normalize_sql(&update_user_keyword_names_diff_sql(&["type"])),
+            normalize_sql(
+                r"UPDATE public.users
+SET updated_at = NOW()
+, name = $1
+
+WHERE id = $2
+RETURNING id, name AS type, updated_at"
+            ),
+            "update_user_keyword_names_diff (variant 1)"
+        );
+        assert_eq!(
+            normalize_sql(&update_user_keyword_names_diff_sql(&["ref"])),
+            normalize_sql(
+                r"UPDATE public.users
+SET updated_at = NOW()
+
+, email = $1
+WHERE id = $2
+RETURNING id, name AS type, updated_at"
+            ),
+            "update_user_keyword_names_diff (variant 2)"
+        );
+    }
+
+    #[test]
+    fn find_users_matching_text_sql_variants() {
+        assert_eq!(
+            normalize_sql(&find_users_matching_text_sql(&[])),
+            normalize_sql(
+                r"SELECT id, name, email, age
+FROM public.users
+WHERE (name ILIKE '%' || $1 || '%' OR email ILIKE '%' || $1 || '%')
+
+ORDER BY id"
+            ),
+            "find_users_matching_text (base)"
+        );
+        assert_eq!(
+            normalize_sql(&find_users_matching_text_sql(&["around_age"])),
+            normalize_sql(
+                r"SELECT id, name, email, age
+FROM public.users
+WHERE (name ILIKE '%' || $1 || '%' OR email ILIKE '%' || $1 || '%')
+AND age BETWEEN $2 - 5 AND $2 + 5
+ORDER BY id"
+            ),
+            "find_users_matching_text (variant 1)"
+        );
+    }
+}
+// AUTOMODEL_CONTENT_HASH: eaf2ad62c11193ce
@@ -1,7 +1,10 @@
 #[allow(dead_code)]
 mod generated;
+mod mappers;
 mod models;
+mod services;
 
+use services::UserService;
 use sqlx::PgPool;
 use std::env;
 
@@ -31,7 +34,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 }
 
 async fn connect_to_database(database_url: &str) -> Result<PgPool, Box<dyn std::error::Error>> {
-    let pool = PgPool::connect(database_url).await?;
+    // Queries analyzed with `search_path: [public, extensions]` need it at runtime too
+    let options = database_url
+        .parse::<sqlx::postgres::PgConnectOptions>()?
+        .options([("search_path", "public,extensions")]);
+    let pool = PgPool::connect_with(options).await?;
     Ok(pool)
 }
 
@@ -54,6 +61,10 @@ async fn run_examples(pool: &PgPool) -> Result<(), Box<dyn std::error::Error>> {
     println!("\n=== Testing Structured Parameters ===");
     test_structured_parameters(pool).await?;
 
+    // Test group commit batching of concurrent inserts
+    println!("\n=== Testing Batched Inserts ===");
+    test_batched_inserts(pool).await?;
+
     // Test struct reuse
     println!("\n=== Testing Struct Reuse ===");
     test_struct_reuse(pool).await?;
@@ -62,6 +73,64 @@ async fn run_examples(pool: &PgPool) -> Result<(), Box<dyn std::error::Error>> {
     println!("\n=== Testing All PostgreSQL Types ===");
     test_all_types(pool).await?;
 
+    // Test service trait methods bound to generated queries
+    println!("\n=== Testing Query-Bound Service Trait ===");
+    test_service_trait(pool).await?;
+
+    // Test queries against per-tenant shard tables
+    println!("\n=== Testing Sharded Queries ===");
+    test_sharded_queries(pool).await?;
+
+    // Test enums and allowed values generated from CHECK constraints
+    println!("\n=== Testing CHECK Constraint Values ===");
+    test_check_values(pool).await?;
+
+    // Test amounts of currency_columns
+    println!("\n=== Testing Currency Columns ===");
+    test_currency_columns(pool).await?;
+
+    // Test legacy Y/N and "char" code columns converted to bool and enums
+    println!("\n=== Testing Char Flags ===");
+    test_char_flags(pool).await?;
+
+    // Test row post-processing with map_row
+    println!("\n=== Testing Row Mappers ===");
+    test_row_mappers(pool).await?;
+
+    // Test tsvector and tsquery columns and parameters
+    println!("\n=== Testing Full-Text Search ===");
+    test_full_text_search(pool).await?;
+
+    // Test filtering rows with the enum generated from the WHERE clause
+    println!("\n=== Testing Filter Types ===");
+    test_filter_types(pool).await?;
+
+    // Test updating many rows with one UNNEST statement
+    println!("\n=== Testing Bulk Updates ===");
+    test_bulk_update(pool).await?;
+
+    // Test preparing the queries of the modules as a startup health check
+    println!("\n=== Testing Warmup ===");
+    test_warmup(pool).await?;
+
+    // Test running several queries in one transaction through the module's Dao
+    println!("\n=== Testing Dao ===");
+    test_dao(pool).await?;
+
+    // Test blocking wrappers from a thread without a tokio runtime
+    #[cfg(feature = "blocking")]
+    {
+        println!("\n=== Testing Blocking Wrappers ===");
+        test_blocking_wrappers()?;
+    }
+
+    // Test request-scoped cancellation of a running query
+    #[cfg(feature = "cancellation")]
+    {
+        println!("\n=== Testing Cancellable Variants ===");
+        test_cancellable_variants(pool).await?;
+    }
+
     println!("\nTo see the actual generated code, check src/generated/ directory");
     println!("Functions are organized into modules: admin.rs, setup.rs, users.rs, and mod.rs");
     println!(
@@ -71,6 +140,45 @@ async fn run_examples(pool: &PgPool) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Cancels a query sleeping on the server, as a request timeout would
+#[cfg(feature = "cancellation")]
+async fn test_cancellable_variants(pool: &PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    let token = tokio_util::sync::CancellationToken::new();
+    let timeout = token.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        timeout.cancel();
+    });
+    let started = std::time::Instant::now();
+    match generated::admin::sleep_cancellable(pool, &token, 5.0).await {
+        Err(generated::ErrorReadOnly::Cancelled) => {
+            println!("Sleep cancelled after {:?}", started.elapsed())
+        }
+        result => println!("Unexpected sleep result: {:?}", result),
+    }
+
+    // The pool keeps working after the cancelled connection is closed
+    let finished_at = generated::admin::sleep(pool, 0.0).await?;
+    println!("Sleep without cancellation finished at {:?}", finished_at);
+    Ok(())
+}
+
+/// Runs on a plain thread, as the wrappers cannot be called from async code
+#[cfg(feature = "blocking")]
+fn test_blocking_wrappers() -> Result<(), Box<dyn std::error::Error>> {
+    let database_url = env::var("AUTOMODEL_DATABASE_URL")?;
+    let version = std::thread::spawn(move || {
+        // The pool is opened on the runtime of the blocking wrappers
+        let pool = generated::blocking::block_on(PgPool::connect(&database_url))
+            .map_err(|e| e.to_string())?;
+        generated::admin::get_version_blocking(&pool).map_err(|e| e.to_string())
+    })
+    .join()
+    .map_err(|_| "blocking thread panicked")??;
+    println!("PostgreSQL version (blocking): {:?}", version);
+    Ok(())
+}
+
 async fn test_basic_queries(pool: &PgPool) -> Result<(), Box<dyn std::error::Error>> {
     // Admin functions
     match generated::admin::get_current_time(pool).await {
@@ -78,18 +186,158 @@ async fn test_basic_queries(pool: &PgPool) -> Result<(), Box<dyn std::error::Err
         Err(e) => println!("Error getting time: {}", e),
     }
 
+    // Utility statements run with the simple query protocol, without a prepared statement
+    match generated::admin::refresh_statistics(pool).await {
+        Ok(()) => println!("Planner statistics refreshed"),
+        Err(e) => println!("Error refreshing statistics: {}", e),
+    }
+    match generated::admin::get_search_path(pool).await {
+        Ok(search_path) => println!("Search path: {:?}", search_path),
+        Err(e) => println!("Error getting search path: {}", e),
+    }
+
     // Setup functions
     match generated::setup::create_users_table(pool).await {
         Ok(_) => println!("Users table created successfully"),
         Err(e) => println!("Error creating table: {}", e),
     }
 
+    // Calls returning far more or fewer rows than the planner estimated are reported
+    generated::cardinality_drift::enable(|drift| {
+        println!(
+            "Cardinality drift in {}: {} row(s) returned, {} estimated",
+            drift.query, drift.actual_rows, drift.estimated_rows
+        );
+    });
+
     // Users functions
     match generated::users::get_all_users(pool).await {
         Ok(users) => println!("All users: {:?}", users),
         Err(e) => println!("Error listing users: {}", e),
     }
 
+    // Repeated parameters are bound once and share a placeholder
+    match generated::users::find_users_matching_text(pool, "example".to_string(), Some(30)).await {
+        Ok(users) => println!("Users matching 'example' around age 30: {:?}", users),
+        Err(e) => println!("Error searching users: {}", e),
+    }
+
+    match generated::users::find_users_with_similar_name(pool, "Jon Doe".to_string(), 2).await {
+        Ok(users) => println!("Users with a name similar to 'Jon Doe': {:?}", users),
+        Err(e) => println!("Error finding similar names: {}", e),
+    }
+
+    // The theme is declared as text, as PostgreSQL cannot infer the type of jsonb_build_object values
+    match generated::users::find_users_by_theme(pool, "dark".to_string()).await {
+        Ok(users) => println!("Users with the dark theme: {}", users.len()),
+        Err(e) => println!("Error finding users by theme: {}", e),
+    }
+
+    // The profile fields are serialized inline, next to id and name
+    match generated::users::get_user_profile_cards(pool, 2).await {
+        Ok(cards) => {
+            for card in cards {
+                println!("Profile card: {}", serde_json::to_string(&card)?);
+            }
+        }
+        Err(e) => println!("Error getting profile cards: {}", e),
+    }
+
+    // Get-or-create: the second call returns the user created by the first one
+    let mut conn = pool.acquire().await?;
+    let email = format!(
+        "get.or.create.{}@example.com",
+        chrono::Utc::now().timestamp_millis()
+    );
+    for attempt in 1..=2 {
+        let user = generated::users::get_or_create_user_by_email(
+            &mut conn,
+            email.clone(),
+            "Get Or Create".to_string(),
+            41,
+            models::UserProfile {
+                bio: None,
+                avatar_url: None,
+                preferences: models::UserPreferences {
+                    theme: "light".to_string(),
+                    language: "en".to_string(),
+                    notifications_enabled: false,
+                },
+                social_links: vec![],
+            },
+        )
+        .await?;
+        println!("Get-or-create attempt {}: user ID={}", attempt, user.id);
+    }
+
+    // Unit of work: the user and the welcome post are inserted in one transaction, and the post
+    // is written by the user returned by the first step
+    let registration = generated::user_model::perform_registration(
+        pool,
+        generated::user_model::RegistrationParams {
+            name: "Registered User".to_string(),
+            email: format!(
+                "registered.{}@example.com",
+                chrono::Utc::now().timestamp_millis()
+            ),
+            title: "Hello".to_string(),
+            content: Some("My first post".to_string()),
+        },
+    )
+    .await?;
+    println!(
+        "Registration: user ID={}, welcome post ID={}",
+        registration.register_user, registration.create_welcome_post
+    );
+
+    // A missing row fails with the not_found_message of the query instead of a bare RowNotFound
+    match generated::users::get_user_details(pool, -1).await {
+        Ok(user) => println!("Unexpected user: {:?}", user),
+        Err(e) if e.is_not_found() => println!("Expected error: {}", e),
+        Err(e) => return Err(e.into()),
+    }
+
+    // Tables of each module, e.g. to find the functions affected by an incident on a table
+    println!("Analytics tables: {:?}", generated::analytics::TABLES);
+
+    // Cached queries are invalidated by changes of their tables, notified by the triggers of
+    // migrations/013_cache_invalidation.sql to generated::cache_invalidation::Listener
+    let invalidation = generated::cache_invalidation::Invalidation::Table("users".to_string());
+    for (query, tables) in generated::users::CACHED_QUERIES {
+        println!(
+            "Change of users invalidates {}: {}",
+            query,
+            invalidation.affects(tables)
+        );
+    }
+
+    // Fields classified with sensitivity are listed per module and redacted from Debug output
+    println!("Sensitive fields: {:?}", generated::users::SENSITIVE_FIELDS);
+    if let Some(user) =
+        generated::users::find_user_by_email(pool, "john@example.com".to_string()).await?
+    {
+        println!("User with redacted email: {:?}", user);
+    }
+
+    // Temp table queries take a Session, which keeps them on a single pooled connection
+    let mut session = generated::Session::acquire(pool).await?;
+    let users = generated::users::get_users_by_ids(&mut session, vec![1, 2, 3]).await?;
+    println!("Users by ids in a session: {}", users.len());
+
+    // The preview counts the rows a DELETE would remove and those referencing them
+    let impact = generated::users::delete_user_preview(pool, 1).await?;
+    println!(
+        "Deleting user 1 would remove {} row(s), blocked by references: {}",
+        impact.total_deleted(),
+        impact.is_blocked()
+    );
+    for referencing in &impact.referencing {
+        println!(
+            "  {} ({:?}): {} row(s) through {}",
+            referencing.table, referencing.on_delete, referencing.rows, referencing.constraint
+        );
+    }
+
     Ok(())
 }
 
@@ -121,6 +369,51 @@ async fn test_conditional_update(pool: &PgPool) -> Result<(), Box<dyn std::error
         user.id, user.name, user.email, user.age
     );
 
+    // The id-only variant skips decoding the other returned columns
+    let other_id = generated::users::insert_user_id_only(
+        pool,
+        "Jim Doe".to_string(),
+        format!("jim.doe.{}@example.com", timestamp),
+        31,
+        models::UserProfile {
+            bio: None,
+            avatar_url: None,
+            preferences: models::UserPreferences {
+                theme: "light".to_string(),
+                language: "en".to_string(),
+                notifications_enabled: false,
+            },
+            social_links: vec![],
+        },
+    )
+    .await?;
+    println!("✓ Created user with id-only insert: ID={}", other_id);
+
+    // Inserting an existing email with the retrying variant returns the existing row
+    let mut conn = pool.acquire().await?;
+    let existing = generated::users::insert_user_retrying(
+        &mut conn,
+        "John Again".to_string(),
+        user.email.clone(),
+        32,
+        models::UserProfile {
+            bio: None,
+            avatar_url: None,
+            preferences: models::UserPreferences {
+                theme: "light".to_string(),
+                language: "en".to_string(),
+                notifications_enabled: false,
+            },
+            social_links: vec![],
+        },
+    )
+    .await?;
+    println!(
+        "✓ Retrying insert of an existing email returned user: ID={}, name={}",
+        existing.id, existing.name
+    );
+    drop(conn);
+
     // Example 1: Update only the name
     println!("\n2. Updating only the name (email and age remain unchanged)...");
     let updated = generated::users::update_user_fields(
@@ -336,6 +629,45 @@ async fn test_structured_parameters(pool: &PgPool) -> Result<(), Box<dyn std::er
     Ok(())
 }
 
+async fn test_batched_inserts(pool: &PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    let timestamp = chrono::Utc::now().timestamp();
+    let batcher = generated::users::insert_user_structured_batcher(pool.clone());
+
+    // Concurrent calls are written with one multi-row INSERT
+    let params = |i: i32| generated::users::InsertUserStructuredParams {
+        name: format!("Batched User {}", i),
+        email: format!("batched.{}.{}@example.com", i, timestamp),
+        age: 20 + i,
+    };
+    let (first, second, third) = tokio::join!(
+        generated::users::insert_user_structured_batched(&batcher, params(1)),
+        generated::users::insert_user_structured_batched(&batcher, params(2)),
+        generated::users::insert_user_structured_batched(&batcher, params(3)),
+    );
+    for user in [first?, second?, third?] {
+        println!(
+            "✓ Created batched user: ID={}, email={}",
+            user.id, user.email
+        );
+    }
+
+    // A failing row only fails its own call, the rest of the batch is still written
+    let (duplicate, fresh) = tokio::join!(
+        generated::users::insert_user_structured_batched(&batcher, params(1)),
+        generated::users::insert_user_structured_batched(&batcher, params(4)),
+    );
+    match duplicate {
+        Ok(_) => println!("Unexpected: duplicate email was inserted"),
+        Err(e) => println!("✓ Duplicate email rejected: {}", e),
+    }
+    println!(
+        "✓ Created batched user alongside the failure: ID={}",
+        fresh?.id
+    );
+
+    Ok(())
+}
+
 async fn test_struct_reuse(pool: &PgPool) -> Result<(), Box<dyn std::error::Error>> {
     println!("Demonstrating struct reuse across queries...");
     println!("This feature allows queries to reuse structs defined by previous queries,");
@@ -459,6 +791,341 @@ async fn test_struct_reuse(pool: &PgPool) -> Result<(), Box<dyn std::error::Erro
     Ok(())
 }
 
+async fn test_service_trait(pool: &PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    let service = services::UserRepository;
+
+    match service
+        .find_user_by_email(pool, "john@example.com".to_string())
+        .await
+    {
+        Ok(user) => println!("User found via service trait: {:?}", user),
+        Err(e) => println!("Error finding user via service trait: {}", e),
+    }
+
+    match service
+        .search_users_by_name_pattern(pool, "J%".to_string())
+        .await
+    {
+        Ok(users) => println!("Users matching 'J%' via service trait: {}", users.len()),
+        Err(e) => println!("Error searching users via service trait: {}", e),
+    }
+
+    Ok(())
+}
+
+async fn test_sharded_queries(pool: &PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    let since = chrono::Utc::now() - chrono::Duration::days(30);
+
+    let orders = generated::orders::get_tenant_orders_since(pool, "acme", since).await?;
+    println!("Recent orders in the 'acme' shard: {}", orders.len());
+
+    // Shard keys which could alter the SQL are rejected before the query runs
+    match generated::orders::get_tenant_orders_since(pool, "acme; DROP TABLE users", since).await {
+        Ok(_) => println!("Unexpected: invalid shard key was accepted"),
+        Err(e) => println!("Invalid shard key rejected: {}", e),
+    }
+
+    // SUM() of bigint is NUMERIC, converted to i64 failing with Overflow when out of range
+    match generated::orders::get_tenant_order_totals(pool, "acme").await {
+        Ok(totals) => println!(
+            "Orders in the 'acme' shard: {:?}, revenue in cents: {:?}",
+            totals.order_count, totals.revenue_cents
+        ),
+        Err(generated::ErrorReadOnly::Overflow(overflow)) => {
+            println!("Revenue does not fit in i64: {}", overflow)
+        }
+        Err(e) => println!("Error summing orders: {}", e),
+    }
+
+    Ok(())
+}
+
+async fn test_check_values(pool: &PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    use generated::tickets::{TicketsPriority, TICKETS_STATE_VALUES};
+
+    let ticket = generated::tickets::insert_ticket(
+        pool,
+        "Printer on fire".to_string(),
+        TicketsPriority::High,
+        "open".to_string(),
+    )
+    .await?;
+    println!(
+        "Inserted ticket {} with priority {} and state {}",
+        ticket.id, ticket.priority, ticket.state
+    );
+
+    // Values outside the CHECK constraint are rejected before the query runs
+    match generated::tickets::insert_ticket(
+        pool,
+        "Printer still on fire".to_string(),
+        TicketsPriority::High,
+        "reopened".to_string(),
+    )
+    .await
+    {
+        Ok(_) => println!(
+            "Unexpected: state outside {:?} was accepted",
+            TICKETS_STATE_VALUES
+        ),
+        Err(e) => println!("Invalid state rejected: {}", e),
+    }
+
+    // The audit trigger records every change in tickets_history, read with the generated
+    // history function over a time range
+    let since = chrono::Utc::now() - chrono::Duration::hours(1);
+    let history = generated::history::get_tickets_history(
+        pool,
+        ticket.id,
+        sqlx::postgres::types::PgRange::from(since..),
+    )
+    .await?;
+    for change in &history {
+        println!(
+            "Ticket {} {} at {}: state {}",
+            change.id, change.operation, change.changed_at, change.state
+        );
+    }
+
+    let urgent = generated::tickets::get_tickets_by_priorities(
+        pool,
+        vec![TicketsPriority::High, TicketsPriority::Normal],
+    )
+    .await?;
+    println!("Tickets with high or normal priority: {}", urgent.len());
+
+    // With auto_derives, rows whose fields are all hashable can go straight into sets
+    let distinct: std::collections::HashSet<_> = urgent.into_iter().collect();
+    println!(
+        "Distinct tickets with high or normal priority: {}",
+        distinct.len()
+    );
+
+    // Flagged queries fail until the provider switches the flag on, and without the cargo
+    // feature of a feature_flag_cfg query a stub always fails
+    match generated::tickets::count_tickets_by_state(pool).await {
+        Err(generated::ErrorReadOnly::FeatureDisabled(flag)) => {
+            println!("Ticket stats disabled by flag {}", flag)
+        }
+        result => println!(
+            "Unexpected ticket stats: {:?}",
+            result.map(|rows| rows.len())
+        ),
+    }
+    generated::feature_flags::set_provider(|flag: &str| flag == "ticket_stats");
+    match generated::tickets::count_tickets_by_state(pool).await {
+        Ok(counts) => println!("Ticket states: {}", counts.len()),
+        Err(e) => println!("Ticket stats unavailable: {}", e),
+    }
+
+    // Deleting a closed ticket moves it to tickets_archive in the same statement
+    let closed = generated::tickets::insert_ticket(
+        pool,
+        "Duplicate printer report".to_string(),
+        TicketsPriority::Low,
+        "closed".to_string(),
+    )
+    .await?;
+    if let Some(archived) = generated::tickets::archive_ticket(pool, closed.id).await? {
+        println!(
+            "Archived ticket {} '{}' at {}",
+            archived.id, archived.title, archived.archived_at
+        );
+    }
+
+    Ok(())
+}
+
+async fn test_currency_columns(pool: &PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    use generated::money::Money;
+    use rust_decimal::Decimal;
+
+    sqlx::query("INSERT INTO public.invoices (customer, amount) VALUES ('ACME', 1250.50)")
+        .execute(pool)
+        .await?;
+
+    let min_amount = Money::new(Decimal::new(100000, 2), "EUR");
+    for invoice in generated::orders::get_invoices_from(pool, min_amount).await? {
+        println!(
+            "Invoice {} for {}: {} (fee {:?})",
+            invoice.id, invoice.customer, invoice.amount, invoice.fee
+        );
+    }
+
+    // Amounts in another currency are rejected before the query runs
+    let dollars = Money::new(Decimal::new(100000, 2), "USD");
+    if let Err(e) = generated::orders::get_invoices_from(pool, dollars).await {
+        println!("Rejected amount in dollars: {}", e);
+    }
+
+    Ok(())
+}
+
+async fn test_warmup(pool: &PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    let mut conn = pool.acquire().await?;
+    let mut report = generated::users::warmup(&mut conn).await;
+    report.extend(generated::orders::warmup(&mut conn).await);
+    report.extend(generated::tickets::warmup(&mut conn).await);
+
+    let ready = report.iter().filter(|status| status.is_ok()).count();
+    println!("Warmed up {} of {} queries", ready, report.len());
+    for status in report.iter().filter(|status| !status.is_ok()) {
+        println!(
+            "Query {} does not match the schema: {:?}",
+            status.query, status.result
+        );
+    }
+
+    Ok(())
+}
+
+async fn test_dao(pool: &PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    let mut tx = pool.begin().await?;
+    let mut dao = generated::users::Dao::new(&mut tx);
+
+    let user = dao
+        .insert_user(
+            "Dao User".to_string(),
+            format!("dao.user.{}@example.com", chrono::Utc::now().timestamp()),
+            40,
+            models::UserProfile {
+                bio: None,
+                avatar_url: None,
+                preferences: models::UserPreferences {
+                    theme: "light".to_string(),
+                    language: "en".to_string(),
+                    notifications_enabled: false,
+                },
+                social_links: vec![],
+            },
+        )
+        .await?;
+    let updated = dao
+        .update_user_status(generated::users::UserStatus::Suspended, user.id)
+        .await?;
+    println!(
+        "Inserted user {} and set status {:?} in one transaction",
+        updated.id, updated.status
+    );
+
+    // Nothing is kept, the transaction only demonstrates the Dao
+    tx.rollback().await?;
+    Ok(())
+}
+
+async fn test_char_flags(pool: &PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    use generated::accounts;
+    use models::AccountKind;
+
+    let id = accounts::insert_account(pool, "Legacy Ltd".to_string(), true, AccountKind::Business)
+        .await?;
+    println!("Inserted account {} with is_active stored as 'Y'", id);
+
+    for account in accounts::get_accounts_by_activity(pool, true).await? {
+        println!(
+            "Account {} '{}': active {}, locked {:?}, kind {:?}",
+            account.id, account.name, account.is_active, account.is_locked, account.kind
+        );
+    }
+
+    // Tags are bound and decoded as text, element by element
+    let tagged = accounts::set_account_tags(
+        pool,
+        vec![models::AccountTag::Vip, models::AccountTag::Overdue],
+        id,
+    )
+    .await?;
+    println!("Account {} tagged {:?}", tagged.id, tagged.tags);
+
+    Ok(())
+}
+
+async fn test_full_text_search(pool: &PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    use generated::full_text_search::{websearch_to_tsquery, TsQuery};
+
+    let found = generated::tickets::search_tickets(pool, "printer -water".to_string()).await?;
+    for ticket in &found {
+        println!(
+            "Found ticket {} '{}' (rank {:?})",
+            ticket.id, ticket.title, ticket.rank
+        );
+    }
+
+    // Parse user input once and reuse the tsquery
+    let terms = websearch_to_tsquery(pool, "english", "\"printer on\" or fire").await?;
+    println!("Parsed search input into tsquery {}", terms);
+    let matching = generated::tickets::find_tickets_matching(pool, terms).await?;
+    println!("Tickets matching the parsed input: {}", matching.len());
+
+    // Queries can also be written by hand in tsquery syntax
+    let terms = TsQuery("'printer' & !( 'water' | 'jam':* )".to_string());
+    for ticket in generated::tickets::find_tickets_matching(pool, terms).await? {
+        println!("Ticket {} search vector: {:?}", ticket.id, ticket.search);
+    }
+
+    Ok(())
+}
+
+async fn test_filter_types(pool: &PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    use generated::users::{UserFilter, UserStatus};
+
+    let all = generated::users::filter_users(pool, vec![], 100).await?;
+    println!("Users without filters: {}", all.len());
+
+    let filters = vec![
+        UserFilter::AgeGte(18),
+        UserFilter::AgeLte(65),
+        UserFilter::StatusIn(vec![UserStatus::Active, UserStatus::Pending]),
+    ];
+    for user in generated::users::filter_users(pool, filters, 10).await? {
+        println!("Adult user {} '{}' aged {:?}", user.id, user.name, user.age);
+    }
+
+    let by_name =
+        generated::users::filter_users(pool, vec![UserFilter::NameILike("j%".to_string())], 10)
+            .await?;
+    println!("Users named j%: {}", by_name.len());
+
+    Ok(())
+}
+
+async fn test_bulk_update(pool: &PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    use generated::users::{UpdateUserAgesBulkRecord, UserStatus};
+
+    let records = vec![
+        UpdateUserAgesBulkRecord {
+            age: 31,
+            status: UserStatus::Active,
+            id: 1,
+        },
+        UpdateUserAgesBulkRecord {
+            age: 42,
+            status: UserStatus::Pending,
+            id: 2,
+        },
+    ];
+    for user in generated::users::update_user_ages_bulk(pool, records).await? {
+        println!(
+            "Updated user {} to age {:?} and status {:?}",
+            user.id, user.age, user.status
+        );
+    }
+
+    Ok(())
+}
+
+async fn test_row_mappers(pool: &PgPool) -> Result<(), Box<dyn std::error::Error>> {
+    match generated::users::get_public_user_profile(pool, 1).await? {
+        Some(user) => println!(
+            "Public profile of user {}: {} <{}>",
+            user.id, user.name, user.email
+        ),
+        None => println!("User 1 not found"),
+    }
+
+    Ok(())
+}
+
 async fn test_all_types(pool: &PgPool) -> Result<(), Box<dyn std::error::Error>> {
     use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
     use rust_decimal::Decimal;
@@ -658,3 +1325,254 @@ async fn test_all_types(pool: &PgPool) -> Result<(), Box<dyn std::error::Error>>
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Functions of queries with `search_path_at_runtime` set the search_path they were analyzed
+    /// with themselves, only for their own transaction
+    #[tokio::test]
+    #[ignore = "runs queries against AUTOMODEL_DATABASE_URL"]
+    async fn search_path_at_runtime_is_set_for_the_statement_only() {
+        let database_url = env::var("AUTOMODEL_DATABASE_URL")
+            .expect("AUTOMODEL_DATABASE_URL must be set to run database tests");
+        // A single connection with the default search_path, which lacks the extensions schema
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .max_connections(1)
+            .connect(&database_url)
+            .await
+            .expect("failed to connect to AUTOMODEL_DATABASE_URL");
+
+        let result =
+            generated::users::find_users_with_similar_name(&pool, "Jon Doe".to_string(), 2).await;
+        assert!(
+            result.is_err(),
+            "levenshtein resolved without the extensions schema"
+        );
+
+        generated::users::count_users_with_similar_name(&pool, "Jon Doe".to_string(), 2)
+            .await
+            .expect("search_path_at_runtime did not set the search_path");
+
+        let search_path: String = sqlx::query_scalar("SHOW search_path")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(!search_path.contains("extensions"), "{}", search_path);
+    }
+
+    /// Called with a transaction, a function of a query with `search_path_at_runtime` leaves the
+    /// search_path of the rest of the transaction unchanged
+    #[tokio::test]
+    #[ignore = "runs queries against AUTOMODEL_DATABASE_URL"]
+    async fn search_path_at_runtime_is_restored_in_an_outer_transaction() {
+        let database_url = env::var("AUTOMODEL_DATABASE_URL")
+            .expect("AUTOMODEL_DATABASE_URL must be set to run database tests");
+        let pool = PgPool::connect(&database_url)
+            .await
+            .expect("failed to connect to AUTOMODEL_DATABASE_URL");
+
+        let mut tx = pool.begin().await.unwrap();
+        generated::users::count_users_with_similar_name(&mut tx, "Jon Doe".to_string(), 2)
+            .await
+            .expect("search_path_at_runtime did not set the search_path");
+        let search_path: String = sqlx::query_scalar("SHOW search_path")
+            .fetch_one(&mut *tx)
+            .await
+            .unwrap();
+        assert!(!search_path.contains("extensions"), "{}", search_path);
+        tx.rollback().await.unwrap();
+    }
+
+    /// The cache of a `cache_invalidate_on` query keeps results until the task of
+    /// `cache_invalidation::spawn` receives a notification naming their key
+    #[tokio::test]
+    #[ignore = "runs queries against AUTOMODEL_DATABASE_URL"]
+    async fn cache_invalidate_on_clears_the_notified_key() {
+        let database_url = env::var("AUTOMODEL_DATABASE_URL")
+            .expect("AUTOMODEL_DATABASE_URL must be set to run database tests");
+        let pool = PgPool::connect(&database_url)
+            .await
+            .expect("failed to connect to AUTOMODEL_DATABASE_URL");
+        let task = generated::cache_invalidation::spawn(&pool).await.unwrap();
+
+        let email = format!(
+            "cached.{}@example.com",
+            chrono::Utc::now().timestamp_millis()
+        );
+        let id: i32 = sqlx::query_scalar(
+            "INSERT INTO users (name, email) VALUES ('Before', $1) RETURNING id",
+        )
+        .bind(&email)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let name = generated::users::get_user_name_cached(&pool, id)
+            .await
+            .unwrap();
+        assert_eq!(name.as_deref(), Some("Before"));
+
+        // Changes are only seen once notified, the change of the table is not notified as the
+        // triggers of the cache invalidation migration are skipped
+        let mut tx = pool.begin().await.unwrap();
+        sqlx::query("SET LOCAL session_replication_role = replica")
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE users SET name = 'After' WHERE id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+        let name = generated::users::get_user_name_cached(&pool, id)
+            .await
+            .unwrap();
+        assert_eq!(name.as_deref(), Some("Before"));
+
+        sqlx::query("SELECT pg_notify('user_events', $1)")
+            .bind(id.to_string())
+            .execute(&pool)
+            .await
+            .unwrap();
+        let mut name = None;
+        for _ in 0..50 {
+            name = generated::users::get_user_name_cached(&pool, id)
+                .await
+                .unwrap();
+            if name.as_deref() == Some("After") {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!(name.as_deref(), Some("After"));
+
+        task.abort();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    /// A result read before an invalidation is not cached, even when the cache has not cached
+    /// any result yet
+    #[test]
+    fn cache_invalidation_while_the_query_runs_is_not_missed() {
+        use generated::cache_invalidation::{invalidate, Invalidation, QueryCache};
+        static CACHE: QueryCache<(i32,), String> = QueryCache::new("user_events", &["users"]);
+
+        let generation = CACHE.generation();
+        invalidate(&Invalidation::Channel {
+            channel: "user_events".to_string(),
+            payload: "1".to_string(),
+        });
+        CACHE.insert(
+            (1,),
+            Some("1".to_string()),
+            generation,
+            "Before".to_string(),
+        );
+        assert_eq!(CACHE.get(&(1,)), None);
+
+        let generation = CACHE.generation();
+        CACHE.insert((1,), Some("1".to_string()), generation, "After".to_string());
+        assert_eq!(CACHE.get(&(1,)).as_deref(), Some("After"));
+        invalidate(&Invalidation::Table("users".to_string()));
+        assert_eq!(CACHE.get(&(1,)), None);
+    }
+
+    /// Every call queued on a batcher receives the row inserted from its own parameters
+    #[tokio::test]
+    #[ignore = "runs queries against AUTOMODEL_DATABASE_URL"]
+    async fn batched_inserts_return_the_row_of_each_call() {
+        let database_url = env::var("AUTOMODEL_DATABASE_URL")
+            .expect("AUTOMODEL_DATABASE_URL must be set to run database tests");
+        let pool = PgPool::connect(&database_url)
+            .await
+            .expect("failed to connect to AUTOMODEL_DATABASE_URL");
+        let batcher = generated::users::insert_user_structured_batcher(pool.clone());
+
+        let prefix = format!("batched.{}", chrono::Utc::now().timestamp_millis());
+        let calls: Vec<_> = (0..40)
+            .map(|i| {
+                let batcher = batcher.clone();
+                let params = generated::users::InsertUserStructuredParams {
+                    name: format!("Batched {}", i),
+                    email: format!("{}.{}@example.com", prefix, i),
+                    age: i,
+                };
+                tokio::spawn(async move {
+                    generated::users::insert_user_structured_batched(&batcher, params).await
+                })
+            })
+            .collect();
+        for (i, call) in calls.into_iter().enumerate() {
+            let user = call.await.unwrap().unwrap();
+            assert_eq!(user.email, format!("{}.{}@example.com", prefix, i));
+            assert_eq!(user.age, Some(i as i32));
+        }
+
+        sqlx::query("DELETE FROM users WHERE email LIKE $1")
+            .bind(format!("{}.%", prefix))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    /// Deeply nested tsqueries are encoded without overflowing the stack
+    #[test]
+    fn deeply_nested_tsqueries_are_encoded() {
+        use generated::full_text_search::TsQuery;
+        use sqlx::Encode;
+
+        let depth = 100_000;
+        let terms = TsQuery(format!(
+            "{}'rat'{}",
+            "!( ".repeat(depth),
+            " )".repeat(depth)
+        ));
+        let mut buf = sqlx::postgres::PgArgumentBuffer::default();
+        assert!(matches!(
+            terms.encode_by_ref(&mut buf),
+            Ok(sqlx::encode::IsNull::No)
+        ));
+        assert_eq!(buf[..4], ((depth + 1) as u32).to_be_bytes());
+
+        let unbalanced = TsQuery(format!("{}'rat'", "( ".repeat(depth)));
+        let mut buf = sqlx::postgres::PgArgumentBuffer::default();
+        assert!(unbalanced.encode_by_ref(&mut buf).is_err());
+        assert!(buf.is_empty());
+    }
+
+    /// tsqueries read in the binary format have the text PostgreSQL prints for them
+    #[tokio::test]
+    #[ignore = "runs queries against AUTOMODEL_DATABASE_URL"]
+    async fn tsqueries_round_trip_through_the_binary_format() {
+        use generated::full_text_search::TsQuery;
+
+        let database_url = env::var("AUTOMODEL_DATABASE_URL")
+            .expect("AUTOMODEL_DATABASE_URL must be set to run database tests");
+        let pool = PgPool::connect(&database_url)
+            .await
+            .expect("failed to connect to AUTOMODEL_DATABASE_URL");
+
+        let nested = format!("{}'rat'{}", "!( 'fat' & ".repeat(500), " )".repeat(500));
+        for text in [
+            "'fat' | 'rat' & 'cat'",
+            "( 'fat' | 'rat' ) & !'cat':*AB",
+            "'a' <-> ( 'b' <2> 'c' ) | !!'d'",
+            "'a' & 'b' & ( 'c' & 'd' )",
+            nested.as_str(),
+        ] {
+            let (decoded, printed): (TsQuery, String) =
+                sqlx::query_as("SELECT $1::tsquery, $1::tsquery::text")
+                    .bind(TsQuery(text.to_string()))
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap();
+            assert_eq!(decoded.0, printed, "{}", text);
+        }
+    }
+}
@@ -0,0 +1,11 @@
+use crate::generated::users::GetPublicUserProfileItem;
+
+/// Hide the local part of the email address except its first character, e.g. `j***@example.com`
+/// Applied by `get_public_user_profile` to every row via `map_row`
+pub fn redact_email(mut user: GetPublicUserProfileItem) -> GetPublicUserProfileItem {
+    if let Some((local, domain)) = user.email.split_once('@') {
+        let first = local.chars().next().map(String::from).unwrap_or_default();
+        user.email = format!("{}***@{}", first, domain);
+    }
+    user
+}
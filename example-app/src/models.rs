@@ -23,3 +23,38 @@ pub struct SocialLink {
     pub platform: String,
     pub url: String,
 }
+
+/// Kind of a legacy account, stored as a "char" code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AccountKind {
+    Personal,
+    Business,
+}
+
+/// Tag of a legacy account, stored as text in a TEXT[] column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AccountTag {
+    Vip,
+    Overdue,
+}
+
+impl std::fmt::Display for AccountTag {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            AccountTag::Vip => "vip",
+            AccountTag::Overdue => "overdue",
+        })
+    }
+}
+
+impl std::str::FromStr for AccountTag {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "vip" => Ok(AccountTag::Vip),
+            "overdue" => Ok(AccountTag::Overdue),
+            _ => Err(format!("unknown account tag '{}'", s)),
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use crate::generated::users::{
+    FindUserByEmailItem, GetOrCreateUserByEmailItem, InsertUserConstraints,
+    SearchUsersByNamePatternItem,
+};
+use crate::generated::{Error, ErrorReadOnly};
+use crate::models::UserProfile;
+
+/// Handwritten service trait whose methods are bound to generated queries
+/// The macro fails the build if a signature drifts from the analyzed SQL
+pub trait UserService {
+    #[automodel::query(file = "queries/users/07_find_user_by_email.sql")]
+    async fn find_user_by_email(
+        &self,
+        executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        email: String,
+    ) -> Result<Option<FindUserByEmailItem>, ErrorReadOnly>;
+
+    #[automodel::query(file = "queries/users/12_search_users_by_name_pattern.sql")]
+    async fn search_users_by_name_pattern(
+        &self,
+        executor: impl sqlx::Executor<'_, Database = sqlx::Postgres>,
+        pattern: String,
+    ) -> Result<Vec<SearchUsersByNamePatternItem>, ErrorReadOnly>;
+
+    #[automodel::query(file = "queries/users/62_get_or_create_user_by_email.sql")]
+    async fn get_or_create_user_by_email(
+        &self,
+        executor: &mut sqlx::PgConnection,
+        email: String,
+        name: String,
+        age: i32,
+        profile: UserProfile,
+    ) -> Result<GetOrCreateUserByEmailItem, Error<InsertUserConstraints>>;
+}
+
+/// Service using the generated implementations as they are
+pub struct UserRepository;
+
+impl UserService for UserRepository {}
@@ -0,0 +1,34 @@
+//! The committed generated code must stay up to date for every build of the example, whatever
+//! cargo features it enables, as CI checks it without a database
+
+include!("../automodel_defaults.rs");
+
+/// Whether the generated code is up to date for a build with the features
+/// Generation only asks for the database when the code has to be regenerated
+fn is_up_to_date(features: &[&str]) -> bool {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    runtime
+        .block_on(automodel::AutoModel::generate_with_context(
+            automodel::GenerationContext::quiet(),
+            || Err("the generated code is not up to date".to_string()),
+            "queries",
+            "src/generated",
+            automodel_defaults(features.iter().map(|feature| feature.to_string()).collect()),
+        ))
+        .is_ok()
+}
+
+#[test]
+fn features_without_sql_blocks_keep_the_generated_code() {
+    assert!(is_up_to_date(&[]));
+    assert!(is_up_to_date(&["blocking"]));
+    assert!(is_up_to_date(&["blocking", "dynamic", "cancellation"]));
+}
+
+#[test]
+fn features_of_sql_blocks_select_other_generated_code() {
+    assert!(!is_up_to_date(&["ticket_stats"]));
+}